@@ -0,0 +1,241 @@
+//! Micro-benchmarks del camino crítico de los comandos.
+//!
+//! Mide GET, SET, LPUSH y LRANGE directamente sobre el `DataStore` y a
+//! través del camino completo: parseo RESP, conversión a `Instruction` y
+//! ejecución en el `CommandExecutor`. La base se precarga con
+//! `DEBUG POPULATE` usando claves y valores de tamaño realista.
+//!
+//! Con `cargo bench` corre la cantidad completa de iteraciones y reporta
+//! operaciones por segundo; con `cargo test` corre unas pocas, para que
+//! el propio harness quede cubierto por los tests.
+
+use rustidocs::{
+    cluster::{state::node_data::NodeData, types::KnownNode},
+    command::{
+        CommandExecutor, Instruction, TryFrom,
+        commands::{get, get_slice, left_push, set},
+        types::SetOptions,
+    },
+    config::node_configs::NodeConfigs,
+    logs::aof_logger::AofLogger,
+    network::{resp_message::RespMessage, resp_parser::parse_resp_line},
+    storage::DataStore,
+};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::io::Cursor;
+use std::sync::{Arc, RwLock, mpsc};
+use std::time::Instant;
+
+/// Claves precargadas con `DEBUG POPULATE` en una corrida completa.
+const BENCH_KEYS: usize = 100_000;
+/// Iteraciones por benchmark en una corrida completa.
+const BENCH_ITERATIONS: usize = 100_000;
+/// Claves e iteraciones en modo test.
+const SMOKE_KEYS: usize = 100;
+const SMOKE_ITERATIONS: usize = 50;
+/// Tamaño en bytes de cada valor, similar al de una sesión o un documento chico.
+const VALUE_SIZE: usize = 256;
+/// Elementos de la lista usada por LRANGE.
+const LIST_LEN: usize = 100;
+/// Listas entre las que se reparten los LPUSH, para que ninguna crezca
+/// tanto que la medición pase a depender de su largo.
+const PUSH_LISTS: usize = 1_000;
+
+/// Parámetros de una corrida.
+struct BenchConfig {
+    keys: usize,
+    iterations: usize,
+}
+
+impl BenchConfig {
+    /// `cargo bench` invoca los harness propios con `--bench`; sin esa
+    /// bandera se asume `cargo test` y se corre el modo reducido.
+    fn from_args() -> Self {
+        if std::env::args().any(|arg| arg == "--bench") {
+            Self {
+                keys: BENCH_KEYS,
+                iterations: BENCH_ITERATIONS,
+            }
+        } else {
+            Self {
+                keys: SMOKE_KEYS,
+                iterations: SMOKE_ITERATIONS,
+            }
+        }
+    }
+}
+
+/// Nodo de prueba: un master único con todos los slots, sin snapshots
+/// automáticos ni logs de debug que distorsionen las mediciones.
+struct BenchNode {
+    executor: CommandExecutor,
+    store: Arc<RwLock<DataStore>>,
+    _dir: tempfile::TempDir,
+}
+
+impl BenchNode {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().expect("No se pudo crear el directorio temporal");
+        let config_path = dir.path().join("bench.conf");
+        let config = format!(
+            "bind 127.0.0.1\nport 6379\nrole M\nsave 900 1000000000\ndbfilename bench.rdb\ndir {}\nlogfile {}\nloglevel warning\nnode-id 6f1c2e9a4b7d3058e1a9c4f2b6d8e0a3c5f7b9d1\nhash-slots 0-16383\n",
+            dir.path().display(),
+            dir.path().join("bench.log").display()
+        );
+        std::fs::write(&config_path, config).expect("No se pudo escribir la configuración");
+        let settings = NodeConfigs::new(config_path.to_str().unwrap())
+            .expect("Configuración de benchmark inválida");
+
+        let mut node_data = NodeData::new(settings.clone());
+        node_data.set_as_master();
+        let store = Arc::new(RwLock::new(DataStore::new()));
+        let nodes: HashMap<_, KnownNode> = HashMap::new();
+        let executor = CommandExecutor::new(
+            store.clone(),
+            mpsc::channel().1,
+            settings.clone(),
+            AofLogger::new(settings),
+            mpsc::channel().0,
+            Arc::new(RwLock::new(nodes)),
+            Arc::new(RwLock::new(node_data)),
+            Arc::new(RwLock::new(HashMap::new())),
+        );
+        Self {
+            executor,
+            store,
+            _dir: dir,
+        }
+    }
+
+    /// Ejecuta una instrucción recibida como bytes RESP, igual que una conexión.
+    fn execute_resp(&mut self, request: &[u8]) -> RespMessage {
+        let message = parse_resp_line(&mut Cursor::new(request)).expect("RESP inválido");
+        let instruction = Instruction::try_from(message).expect("Instrucción inválida");
+        let (pubsub_tx, _) = mpsc::channel();
+        let (response_tx, _) = mpsc::channel();
+        self.executor.execute_instruction(
+            "bench".to_string(),
+            instruction,
+            &pubsub_tx,
+            &response_tx,
+        )
+    }
+
+    /// Ejecuta un comando de preparación, que no puede fallar.
+    fn execute(&mut self, args: &[&str]) -> RespMessage {
+        let response = self.execute_resp(&encode(args));
+        assert!(!response.is_error(), "Respuesta inesperada: {:?}", response);
+        response
+    }
+}
+
+/// Codifica un comando como array RESP de bulk strings.
+fn encode(args: &[&str]) -> Vec<u8> {
+    RespMessage::Array(
+        args.iter()
+            .map(|arg| RespMessage::BulkString(Some(arg.as_bytes().to_vec())))
+            .collect(),
+    )
+    .as_bytes()
+}
+
+/// Corre `op` la cantidad de veces indicada e imprime las operaciones por segundo.
+fn bench<F: FnMut(usize)>(name: &str, iterations: usize, mut op: F) {
+    let started = Instant::now();
+    for i in 0..iterations {
+        op(i);
+    }
+    let elapsed = started.elapsed();
+    let ops_per_sec = iterations as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "{:<24} {:>10} ops {:>12.0} ops/sec ({:?})",
+        name, iterations, ops_per_sec, elapsed
+    );
+}
+
+/// Benchmarks que operan directamente sobre el `DataStore`.
+fn bench_data_store(node: &BenchNode, config: &BenchConfig) {
+    let value = "v".repeat(VALUE_SIZE);
+    let keys: Vec<String> = (0..config.keys).map(|i| format!("key:{}", i)).collect();
+    let list = "list:direct".to_string();
+    let push_lists: Vec<String> = (0..PUSH_LISTS)
+        .map(|i| format!("push:direct:{}", i))
+        .collect();
+    let elements = vec![value.clone()];
+
+    bench("datastore GET", config.iterations, |i| {
+        let store = node.store.read().unwrap();
+        black_box(get(&store, &keys[i % keys.len()]).unwrap());
+    });
+    let options = SetOptions::default();
+    bench("datastore SET", config.iterations, |i| {
+        let mut store = node.store.write().unwrap();
+        let key = keys[i % keys.len()].clone();
+        black_box(set(&mut store, key, value.clone(), &options).unwrap());
+    });
+    bench("datastore LPUSH", config.iterations, |i| {
+        let mut store = node.store.write().unwrap();
+        black_box(left_push(&mut store, &push_lists[i % PUSH_LISTS], &elements).unwrap());
+    });
+    bench("datastore LRANGE", config.iterations, |_| {
+        let store = node.store.read().unwrap();
+        black_box(get_slice(&store, &list, 0, LIST_LEN as i64 - 1).unwrap());
+    });
+}
+
+/// Benchmarks que recorren el parser RESP y el ejecutor completos.
+fn bench_executor(node: &mut BenchNode, config: &BenchConfig) {
+    let value = "v".repeat(VALUE_SIZE);
+    let last = (LIST_LEN - 1).to_string();
+    let get_requests: Vec<Vec<u8>> = (0..config.keys)
+        .map(|i| encode(&["GET", &format!("key:{}", i)]))
+        .collect();
+    let set_requests: Vec<Vec<u8>> = (0..config.keys)
+        .map(|i| encode(&["SET", &format!("key:{}", i), &value]))
+        .collect();
+    let lpush_requests: Vec<Vec<u8>> = (0..PUSH_LISTS)
+        .map(|i| encode(&["LPUSH", &format!("push:executor:{}", i), &value]))
+        .collect();
+    let lrange_request = encode(&["LRANGE", "list:executor", "0", &last]);
+
+    bench("executor GET", config.iterations, |i| {
+        black_box(node.execute_resp(&get_requests[i % get_requests.len()]));
+    });
+    bench("executor SET", config.iterations, |i| {
+        black_box(node.execute_resp(&set_requests[i % set_requests.len()]));
+    });
+    bench("executor LPUSH", config.iterations, |i| {
+        black_box(node.execute_resp(&lpush_requests[i % PUSH_LISTS]));
+    });
+    bench("executor LRANGE", config.iterations, |_| {
+        black_box(node.execute_resp(&lrange_request));
+    });
+}
+
+fn main() {
+    let config = BenchConfig::from_args();
+    let mut node = BenchNode::new();
+
+    let populated = node.execute(&[
+        "DEBUG",
+        "POPULATE",
+        &config.keys.to_string(),
+        "key",
+        &VALUE_SIZE.to_string(),
+    ]);
+    assert_eq!(populated, RespMessage::SimpleString("OK".to_string()));
+    let value = "v".repeat(VALUE_SIZE);
+    for list in ["list:direct", "list:executor"] {
+        let mut push = vec!["RPUSH", list];
+        push.extend(std::iter::repeat_n(value.as_str(), LIST_LEN));
+        assert_eq!(node.execute(&push), RespMessage::Integer(LIST_LEN as i64));
+    }
+
+    bench_data_store(&node, &config);
+    bench_executor(&mut node, &config);
+
+    // El camino completo debe seguir respondiendo lo mismo que el DataStore
+    let response = node.execute(&["GET", "key:0"]);
+    assert_eq!(response, RespMessage::BulkString(Some(value.into_bytes())));
+}
@@ -1,8 +1,10 @@
 use crate::app::operation::generic::Applicable;
 use crate::app::operation::generic::Instruction;
 use crate::app::operation::generic::InstructionId;
+use crate::app::operation::generic::Invertible;
 use crate::app::operation::generic::ParsableBytes;
 use crate::app::operation::generic::Transformable;
+use crate::app::operation::version_vector::VersionVector;
 use std::sync::mpsc::Sender;
 
 #[derive(Clone)]
@@ -15,7 +17,13 @@ where
     pub local_data: D,
     pub local_version: u64, // Representa la version local del cliente pero confirmada por el sv
     pub pending_operations: Vec<Instruction<O>>,
+    pub version_vector: VersionVector, // Operaciones confirmadas por el sv de cada cliente
     output: Option<Sender<Instruction<O>>>,
+    read_only: Option<String>, // Motivo por el que no se puede editar
+    // Cada paso para deshacer o rehacer son las operaciones de una misma
+    // acción del usuario, en el orden en que hay que aplicarlas
+    undo_stack: Vec<Vec<O>>,
+    redo_stack: Vec<Vec<O>>,
 }
 
 impl<D, O> Client<D, O>
@@ -35,6 +43,10 @@ where
             output: Some(output),
             local_operation_id: 0, // Comienza en 0
             pending_operations: Vec::new(),
+            version_vector: VersionVector::new(),
+            read_only: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
         }
     }
 
@@ -46,6 +58,34 @@ where
             output: None,
             local_operation_id: 0, // Comienza en 0
             pending_operations: Vec::new(),
+            version_vector: VersionVector::new(),
+            read_only: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// Marca el documento como de solo lectura. Las operaciones locales
+    /// se rechazan con `reason`; las remotas se siguen aplicando.
+    pub fn set_read_only(&mut self, reason: String) {
+        self.read_only = Some(reason);
+    }
+
+    /// Motivo por el que el documento es de solo lectura, si lo es.
+    pub fn read_only_reason(&self) -> Option<&str> {
+        self.read_only.as_deref()
+    }
+
+    /// Aplica una operación local si el documento se puede editar.
+    ///
+    /// # Returns
+    ///
+    /// La instrucción enviada al servidor, o el motivo por el que el
+    /// documento es de solo lectura
+    pub fn try_apply_local_operation(&mut self, operation: O) -> Result<Instruction<O>, String> {
+        match &self.read_only {
+            Some(reason) => Err(reason.clone()),
+            None => Ok(self.apply_local_operation(operation)),
         }
     }
 
@@ -77,6 +117,9 @@ where
     pub fn receive_remote_instruction(&mut self, mut remote_instruction: Instruction<O>) {
         println!("Cliente id {} recibe operacion remota", self.client_id);
 
+        self.version_vector
+            .record(remote_instruction.operation_id.client_id);
+
         // Si la instrucción es del mismo cliente quitamos del pendig operation.
         if remote_instruction.operation_id.client_id == self.client_id {
             self.remove_pending_operation(remote_instruction.operation_id);
@@ -97,6 +140,30 @@ where
             for pending in &mut self.pending_operations {
                 pending.operation = pending.operation.transform(&remote_instruction.operation);
             }
+
+            // Lo que se deshaga o rehaga se aplica después de la remota
+            for operation in self
+                .undo_stack
+                .iter_mut()
+                .chain(self.redo_stack.iter_mut())
+                .flatten()
+            {
+                *operation = operation.transform(&remote_instruction.operation);
+            }
+        }
+    }
+
+    /// Base y operaciones pendientes para pedir un guardado al servidor.
+    pub fn save_request(&self) -> (VersionVector, Vec<Instruction<O>>) {
+        (self.version_vector.clone(), self.pending_operations.clone())
+    }
+
+    /// Aplica las operaciones que el servidor informó al rechazar un
+    /// guardado viejo, transformando las pendientes contra ellas. Después
+    /// de esto el guardado se puede volver a pedir.
+    pub fn rebase(&mut self, missing: Vec<Instruction<O>>) {
+        for instruction in missing {
+            self.receive_remote_instruction(instruction);
         }
     }
 
@@ -109,3 +176,65 @@ where
         }
     }
 }
+
+impl<D, O> Client<D, O>
+where
+    O: Applicable<D> + Invertible<D> + Transformable + Clone + ParsableBytes,
+{
+    /// Aplica las operaciones locales de una misma acción del usuario, que
+    /// se deshacen juntas. Descarta lo que había para rehacer.
+    pub fn apply_undoable_operations(&mut self, operations: Vec<O>) -> Vec<Instruction<O>> {
+        let (inverse, instructions) = self.apply_recording_inverse(operations);
+        if !inverse.is_empty() {
+            self.undo_stack.push(inverse);
+            self.redo_stack.clear();
+        }
+        instructions
+    }
+
+    /// Deshace la última acción aplicando sus inversas, que se envían al
+    /// servidor como cualquier operación local.
+    ///
+    /// # Returns
+    ///
+    /// Las instrucciones enviadas, o `None` si no hay nada para deshacer o
+    /// el documento es de solo lectura
+    pub fn undo(&mut self) -> Option<Vec<Instruction<O>>> {
+        if self.read_only.is_some() {
+            return None;
+        }
+        let operations = self.undo_stack.pop()?;
+        let (inverse, instructions) = self.apply_recording_inverse(operations);
+        self.redo_stack.push(inverse);
+        Some(instructions)
+    }
+
+    /// Vuelve a aplicar la última acción deshecha.
+    ///
+    /// # Returns
+    ///
+    /// Las instrucciones enviadas, o `None` si no hay nada para rehacer o
+    /// el documento es de solo lectura
+    pub fn redo(&mut self) -> Option<Vec<Instruction<O>>> {
+        if self.read_only.is_some() {
+            return None;
+        }
+        let operations = self.redo_stack.pop()?;
+        let (inverse, instructions) = self.apply_recording_inverse(operations);
+        self.undo_stack.push(inverse);
+        Some(instructions)
+    }
+
+    /// Aplica las operaciones y devuelve sus inversas, en el orden en que
+    /// hay que aplicarlas para volver al estado anterior.
+    fn apply_recording_inverse(&mut self, operations: Vec<O>) -> (Vec<O>, Vec<Instruction<O>>) {
+        let mut inverse = Vec::with_capacity(operations.len());
+        let mut instructions = Vec::with_capacity(operations.len());
+        for operation in operations {
+            inverse.push(operation.inverse(&self.local_data));
+            instructions.push(self.apply_local_operation(operation));
+        }
+        inverse.reverse();
+        (inverse, instructions)
+    }
+}
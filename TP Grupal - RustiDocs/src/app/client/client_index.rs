@@ -1,12 +1,16 @@
 use std::{
-    io::BufReader,
+    io::{BufRead, BufReader},
     sync::mpsc::{Receiver, Sender, channel},
     thread,
 };
 
 use crate::{
     app::{
-        index::{document::DocType, documents::Documents, index_instructions::IndexInstructions},
+        index::{
+            document::{DocAccess, DocType},
+            documents::Documents,
+            index_instructions::IndexInstructions,
+        },
         operation::generic::ParsableBytes,
     },
     client_lib::cluster_manager::ClusterManager,
@@ -17,27 +21,41 @@ const INDEX_CHANNEL: &str = "INDEX";
 
 pub struct ClientIndex {
     cluster: ClusterManager,
+    // Usuario con el que se crean, borran y comparten documentos
+    user: String,
 }
 
 impl ClientIndex {
+    /// Se suscribe al canal del índice y devuelve el receptor de la lista de
+    /// documentos, que se actualiza cada vez que el índice avisa un cambio.
     pub fn new(address: &str, user: &str, password: &str) -> (Self, Receiver<Documents>) {
-        let cluster =
+        let mut cluster =
             ClusterManager::new(address.to_string(), user.to_string(), password.to_string())
                 .unwrap();
 
         let (sender, receiver) = channel();
 
-        let mut listener = IndexListener::new(sender, address, user, password).unwrap();
+        // La suscripción queda hecha antes de devolver el cliente, para no
+        // perder la respuesta del primer refresh
+        let stream = cluster.subscribe(INDEX_CHANNEL).unwrap();
+        let mut listener = IndexListener::new(sender);
 
         // MANEJAR ESTE THREATH
-        thread::spawn(move || listener.run());
+        thread::spawn(move || listener.run(BufReader::new(stream)));
 
-        (Self { cluster }, receiver)
+        (
+            Self {
+                cluster,
+                user: user.to_string(),
+            },
+            receiver,
+        )
     }
 
     pub fn add_doc(&mut self, doc_name: String, doc_type: DocType) {
         println!("[ClientIndex::add_doc] Creando documento: {} tipo: {:?}", doc_name, doc_type);
-        let instruction = IndexInstructions::CreateDoc(doc_name.clone(), doc_type);
+        let instruction =
+            IndexInstructions::CreateDoc(doc_name.clone(), doc_type, self.user.clone());
         let bytes = instruction.to_bytes();
         println!("[ClientIndex::add_doc] Bytes a enviar: {:?}", bytes);
         match self.cluster.publish(INDEX_CHANNEL, &bytes) {
@@ -47,12 +65,23 @@ impl ClientIndex {
     }
 
     pub fn remove_doc(&mut self, doc_name: String) {
-        let instruction = IndexInstructions::RemoveDoc(doc_name);
+        let instruction = IndexInstructions::RemoveDoc(doc_name, self.user.clone());
         self.cluster
             .publish(INDEX_CHANNEL, &instruction.to_bytes())
             .unwrap();
     }
 
+    /// Otorga o quita (con `None`) el permiso de `user` sobre un documento.
+    /// El índice lo ignora si este cliente no es el dueño.
+    pub fn set_access(&mut self, doc_name: String, user: String, access: Option<DocAccess>) {
+        let instruction = IndexInstructions::SetAccess(doc_name, self.user.clone(), user, access);
+        self.cluster
+            .publish(INDEX_CHANNEL, &instruction.to_bytes())
+            .unwrap();
+    }
+
+    /// Pide la lista completa de documentos. Los cambios llegan solos por el
+    /// canal del índice; esto sirve para la carga inicial o para resincronizar.
     pub fn refresh(&mut self) {
         let instruction = IndexInstructions::Refresh;
         self.cluster
@@ -61,52 +90,144 @@ impl ClientIndex {
     }
 }
 
+/// Escucha el canal del índice y mantiene la lista de documentos.
 struct IndexListener {
     sender: Sender<Documents>,
-    cluster: ClusterManager,
+    docs: Documents,
 }
 
 impl IndexListener {
-    pub fn new(
-        sender: Sender<Documents>,
-        address: &str,
-        user: &str,
-        password: &str,
-    ) -> Result<Self, ()> {
-        let cluster =
-            ClusterManager::new(address.to_string(), user.to_string(), password.to_string())
-                .unwrap();
-
-        Ok(Self { sender, cluster })
+    pub fn new(sender: Sender<Documents>) -> Self {
+        Self {
+            sender,
+            docs: Documents::new(),
+        }
     }
 
-    pub fn run(&mut self) {
-        let active_stream = self.cluster.subscribe(INDEX_CHANNEL).unwrap();
-        let mut reader = BufReader::new(active_stream);
+    pub fn run<R: BufRead>(&mut self, mut reader: R) {
         loop {
             match parse_resp_line(&mut reader) {
                 Err(e) => {
                     eprintln!("Error leyendo del socket: {}", e);
                     break;
                 }
-                Ok(contenido) => match contenido {
-                    RespMessage::BulkString(Some(bytes)) => {
-                        if let Some((IndexInstructions::Docs(docs), _)) =
-                            IndexInstructions::from_bytes(&bytes)
-                        {
-                            let _ = self.sender.send(docs);
-                        }
-                    }
-                    RespMessage::SimpleString(bytes) => {
-                        if let Some((IndexInstructions::Docs(docs), _)) =
-                            IndexInstructions::from_bytes(bytes.as_bytes())
-                        {
-                            let _ = self.sender.send(docs);
-                        }
+                Ok(contenido) => {
+                    let bytes = match contenido {
+                        RespMessage::BulkString(Some(bytes)) => bytes,
+                        RespMessage::SimpleString(string) => string.into_bytes(),
+                        _ => continue,
+                    };
+                    let changed = IndexInstructions::from_bytes(&bytes).is_some_and(
+                        |(instruction, _)| apply_index_event(&mut self.docs, instruction),
+                    );
+                    if changed {
+                        let _ = self.sender.send(self.docs.clone());
                     }
-                    _ => continue,
-                },
+                }
             }
         }
     }
 }
+
+/// Aplica sobre la lista local un evento publicado por el índice.
+///
+/// # Returns
+///
+/// `true` si la instrucción cambió (o reemplazó) la lista
+fn apply_index_event(docs: &mut Documents, instruction: IndexInstructions) -> bool {
+    match instruction {
+        IndexInstructions::Docs(all) => *docs = all,
+        IndexInstructions::DocAdded(doc) => {
+            match docs.iter_mut().find(|d| d.get_name() == doc.get_name()) {
+                Some(existing) => *existing = doc,
+                None => docs.push(doc),
+            }
+        }
+        IndexInstructions::DocRemoved(name) => docs.retain(|d| d.get_name() != name),
+        // Pedidos de otros clientes al índice, no cambian la lista
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::index::document::Document;
+    use std::io::Cursor;
+
+    /// Lo que recibe un cliente suscripto cuando el índice publica
+    /// `instruction`: el payload del PUBLISH como bulk string.
+    fn published(instruction: IndexInstructions) -> Vec<u8> {
+        let payload = instruction.to_bytes();
+        let mut bytes = format!("${}\r\n", payload.len()).into_bytes();
+        bytes.extend(payload);
+        bytes.extend(b"\r\n");
+        bytes
+    }
+
+    /// Corre el listener de otro cliente sobre los mensajes del canal.
+    fn listen(messages: Vec<u8>) -> Vec<Documents> {
+        let (sender, receiver) = channel();
+        IndexListener::new(sender).run(Cursor::new(messages));
+        receiver.try_iter().collect()
+    }
+
+    fn names(docs: &Documents) -> Vec<String> {
+        docs.iter().map(|doc| doc.get_name()).collect()
+    }
+
+    #[test]
+    fn test_created_doc_is_pushed_to_subscribed_client() {
+        let doc = Document::with_owner("Roster".to_string(), DocType::Text, "Illari".to_string());
+        let mut messages = published(IndexInstructions::Docs(vec![Document::new(
+            "Patch".to_string(),
+            DocType::SpreadSheet,
+        )]));
+        // El pedido de otro cliente no cambia la lista; el evento del índice sí
+        messages.extend(published(IndexInstructions::CreateDoc(
+            "Roster".to_string(),
+            DocType::Text,
+            "Illari".to_string(),
+        )));
+        messages.extend(published(IndexInstructions::DocAdded(doc.clone())));
+
+        let updates = listen(messages);
+        assert_eq!(updates.len(), 2);
+        assert_eq!(names(&updates[1]), vec!["Patch", "Roster"]);
+        assert_eq!(updates[1][1], doc);
+    }
+
+    #[test]
+    fn test_removed_doc_is_pushed_to_subscribed_client() {
+        let mut messages = published(IndexInstructions::DocAdded(Document::new(
+            "Roster".to_string(),
+            DocType::Text,
+        )));
+        messages.extend(published(IndexInstructions::DocAdded(Document::new(
+            "Patch".to_string(),
+            DocType::Text,
+        ))));
+        messages.extend(published(IndexInstructions::DocRemoved(
+            "Roster".to_string(),
+        )));
+
+        let updates = listen(messages);
+        assert_eq!(updates.len(), 3);
+        assert_eq!(names(&updates[2]), vec!["Patch"]);
+    }
+
+    #[test]
+    fn test_doc_added_twice_replaces_previous_entry() {
+        let mut shared =
+            Document::with_owner("Roster".to_string(), DocType::Text, "Illari".to_string());
+        let mut docs = vec![shared.clone()];
+        shared.set_access("Illari", "Kiriko", Some(DocAccess::Read));
+
+        assert!(apply_index_event(
+            &mut docs,
+            IndexInstructions::DocAdded(shared.clone())
+        ));
+        assert_eq!(docs, vec![shared]);
+    }
+}
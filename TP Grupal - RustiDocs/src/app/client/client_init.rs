@@ -1,6 +1,7 @@
 use crate::app::client::client_data::Client;
 use crate::app::client::client_input::ClientInput;
 use crate::app::client::client_output::ClientOutput;
+use crate::app::index::document::ERR_READ_ONLY_ACCESS;
 use crate::app::network::header::Message;
 use crate::app::network::redis_parser::content_to_message;
 use crate::app::network::redis_parser::sub_to_channel;
@@ -8,6 +9,7 @@ use crate::app::operation::generic::Applicable;
 use crate::app::operation::generic::Instruction;
 use crate::app::operation::generic::ParsableBytes;
 use crate::app::operation::generic::Transformable;
+use crate::app::operation::text::ERR_NON_UTF8_DOCUMENT;
 use crate::cluster::types::DEFAULT_BUFFER_SIZE;
 use crate::network::resp_parser::parse_resp_line;
 use std::io::Write;
@@ -26,6 +28,7 @@ pub struct ClientThread {
 impl ClientThread {
     pub fn init<D, O>(
         client_id: u64,
+        user: &str,
         redis_stream: &mut TcpStream,
         channel_name: String,
     ) -> Result<(Client<D, O>, Receiver<Instruction<O>>), String>
@@ -58,17 +61,21 @@ impl ClientThread {
         }
 
         println!("[INIT] Enviando init message");
-        let init_message = Message::<D, O>::Init(client_id).message_to_pub(&channel_name);
+        let init_message =
+            Message::<D, O>::Init(client_id, user.to_string()).message_to_pub(&channel_name);
         println!("El init message es {:?}", &init_message);
         let _ = redis_stream.write_all(&init_message);
         redis_stream.flush().unwrap();
         println!("[INIT] Subscribiendo a canal");
-        let (data, version) = get_state::<D, O>(client_id, redis_stream)?;
+        let (data, version, read_only) = get_state::<D, O>(client_id, redis_stream)?;
         println!("[INIT] Data");
         let (input, receiver) = init_input::<D, O>(&redis_stream, client_id);
         let (output, sender) = init_output::<D, O>(&redis_stream, channel_name, client_id);
         println!("[INIT] Output: {:?}", output);
-        let client = Client::new(data, sender.clone(), version, client_id);
+        let mut client = Client::new(data, sender.clone(), version, client_id);
+        if let Some(reason) = read_only {
+            client.set_read_only(reason.to_string());
+        }
         println!("[INIT] Client");
         // ACA HAY QUE MANEJAR THREADS PERO BUENO
         Self {
@@ -118,7 +125,16 @@ where
     (join, sender)
 }
 
-fn get_state<D, O>(client_id: u64, stream: &mut TcpStream) -> Result<(D, u64), String>
+/// Espera el estado del documento dirigido a este cliente.
+///
+/// # Returns
+///
+/// Los datos, la versión y el motivo por el que se abre en solo lectura,
+/// o error si el usuario no tiene acceso al documento
+fn get_state<D, O>(
+    client_id: u64,
+    stream: &mut TcpStream,
+) -> Result<(D, u64, Option<&'static str>), String>
 where
     O: Clone + ParsableBytes,
     D: Clone + ParsableBytes + 'static,
@@ -133,9 +149,20 @@ where
             }
             Ok(contenido) => {
                 println!("Client: Entró en rama Ok de parse_resp_line");
-                if let Some(Message::State(mut data, version, id)) =
-                    content_to_message::<D, O>(contenido)
-                {
+                let state = match content_to_message::<D, O>(contenido) {
+                    Some(Message::State(data, version, id)) => Some((data, version, id, None)),
+                    Some(Message::LossyState(data, version, id)) => {
+                        Some((data, version, id, Some(ERR_NON_UTF8_DOCUMENT)))
+                    }
+                    Some(Message::ReadOnlyState(data, version, id)) => {
+                        Some((data, version, id, Some(ERR_READ_ONLY_ACCESS)))
+                    }
+                    Some(Message::Denied(id)) if id == client_id => {
+                        return Err("[INIT] Sin acceso al documento".to_string());
+                    }
+                    _ => None,
+                };
+                if let Some((mut data, version, id, read_only)) = state {
                     if id == client_id {
                         // Inicialización robusta para SpreadSheet
                         if let Some(sheet) = any_as_mut_spreadsheet(&mut data) {
@@ -147,7 +174,7 @@ where
                             "Client: id {} coincide con client_id {}, retornando Ok",
                             id, client_id
                         );
-                        return Ok((data, version));
+                        return Ok((data, version, read_only));
                     } else {
                         println!(
                             "Client: id {} NO coincide con client_id {}, continuando",
@@ -174,3 +201,106 @@ fn any_as_mut_spreadsheet<D: 'static>(
     use std::any::Any;
     (data as &mut dyn Any).downcast_mut::<crate::app::operation::csv::SpreadSheet>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::operation::text::TextOperation;
+    use std::net::TcpListener;
+
+    /// Microservicio falso que responde al init con `reply`, ya en bytes.
+    fn serve_init_reply(listener: TcpListener, reply: Vec<u8>) {
+        let (mut socket, _) = listener.accept().unwrap();
+        let mut buffer = [0; DEFAULT_BUFFER_SIZE];
+        let _ = socket.read(&mut buffer).unwrap(); // SUBSCRIBE
+        socket.write_all(b":1\r\n").unwrap();
+        let _ = socket.read(&mut buffer).unwrap(); // PUBLISH del init
+
+        let hex: String = reply.iter().map(|byte| format!("{:02x}", byte)).collect();
+        socket
+            .write_all(format!("${}\r\n{}\r\n", hex.len(), hex).as_bytes())
+            .unwrap();
+    }
+
+    /// Estado cuyo texto no es UTF-8 válido, como uno guardado por otro
+    /// cliente.
+    fn serve_non_utf8_state(listener: TcpListener, client_id: u64) {
+        let mut state = vec![1u8]; // STATE
+        state.extend(client_id.to_le_bytes());
+        state.extend(5u64.to_le_bytes());
+        state.extend(8u64.to_le_bytes());
+        state.extend(b"Doomfis\xFF");
+        serve_init_reply(listener, state);
+    }
+
+    #[test]
+    fn test_non_utf8_document_opens_read_only() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || serve_non_utf8_state(listener, 42));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let (mut client, _) =
+            ClientThread::init::<String, TextOperation>(42, "Mei", &mut stream, "Doc".to_string())
+                .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(client.local_data, "Doomfis\u{FFFD}");
+        assert_eq!(client.local_version, 5);
+        assert_eq!(client.read_only_reason(), Some(ERR_NON_UTF8_DOCUMENT));
+
+        let edit = client.try_apply_local_operation(TextOperation::Insert {
+            position: 0,
+            character: 'X',
+        });
+        assert_eq!(edit, Err(ERR_NON_UTF8_DOCUMENT.to_string()));
+        assert_eq!(client.local_data, "Doomfis\u{FFFD}");
+        assert!(client.pending_operations.is_empty());
+    }
+
+    #[test]
+    fn test_read_only_grant_joins_but_cannot_edit() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut state = vec![5u8]; // READ_ONLY_STATE
+        state.extend(7u64.to_le_bytes());
+        state.extend(2u64.to_le_bytes());
+        state.extend("Mauga".to_string().to_bytes());
+        let server = thread::spawn(move || serve_init_reply(listener, state));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let (mut client, _) = ClientThread::init::<String, TextOperation>(
+            7,
+            "Kiriko",
+            &mut stream,
+            "Doc".to_string(),
+        )
+        .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(client.local_data, "Mauga");
+        assert_eq!(client.read_only_reason(), Some(ERR_READ_ONLY_ACCESS));
+        let edit = client.try_apply_local_operation(TextOperation::Delete { position: 0 });
+        assert_eq!(edit, Err(ERR_READ_ONLY_ACCESS.to_string()));
+        assert!(client.pending_operations.is_empty());
+    }
+
+    #[test]
+    fn test_denied_join_fails() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut denied = vec![6u8]; // DENIED
+        denied.extend(9u64.to_le_bytes());
+        let server = thread::spawn(move || serve_init_reply(listener, denied));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let result = ClientThread::init::<String, TextOperation>(
+            9,
+            "Junker Queen",
+            &mut stream,
+            "Doc".to_string(),
+        );
+        server.join().unwrap();
+        assert!(result.is_err());
+    }
+}
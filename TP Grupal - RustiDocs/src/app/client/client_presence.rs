@@ -0,0 +1,140 @@
+use crate::app::network::presence::{PresenceMessage, presence_channel};
+use crate::app::network::redis_parser::sub_to_channel;
+use crate::network::resp_parser::parse_resp_line;
+use std::collections::HashMap;
+use std::io::{self, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread::{self, JoinHandle};
+
+/// Publica la posición del cursor de este cliente y recibe la de los demás
+/// colaboradores del documento. Usa una conexión propia, suscripta al
+/// canal de presencia del documento.
+pub struct ClientPresence {
+    client_id: u64,
+    document: String,
+    socket: TcpStream,
+    receiver: Receiver<PresenceMessage>,
+    positions: HashMap<u64, usize>,
+    _input_join: JoinHandle<()>,
+}
+
+impl ClientPresence {
+    /// Se suscribe al canal de presencia de `document` por `socket`, una
+    /// conexión ya autenticada que no se usa para otra cosa.
+    pub fn init(client_id: u64, document: &str, mut socket: TcpStream) -> io::Result<Self> {
+        socket.write_all(&sub_to_channel(&presence_channel(document)))?;
+        socket.flush()?;
+
+        let (sender, receiver) = channel();
+        let input = socket.try_clone()?;
+        let join = thread::spawn(move || read_presence(input, client_id, sender));
+
+        Ok(Self {
+            client_id,
+            document: document.to_string(),
+            socket,
+            receiver,
+            positions: HashMap::new(),
+            _input_join: join,
+        })
+    }
+
+    /// Publica la posición del cursor de este cliente, en caracteres.
+    pub fn publish_position(&mut self, position: usize) -> io::Result<()> {
+        let message = PresenceMessage {
+            client_id: self.client_id,
+            document: self.document.clone(),
+            position,
+        };
+        self.socket.write_all(&message.message_to_pub())?;
+        self.socket.flush()
+    }
+
+    /// Última posición conocida del cursor de cada uno de los otros
+    /// clientes, por id de cliente.
+    pub fn positions(&mut self) -> &HashMap<u64, usize> {
+        for message in self.receiver.try_iter() {
+            self.positions.insert(message.client_id, message.position);
+        }
+        &self.positions
+    }
+}
+
+/// Lee el canal de presencia hasta que se cierre la conexión o se descarte
+/// el `ClientPresence`. Las publicaciones propias se ignoran.
+fn read_presence(socket: TcpStream, client_id: u64, sender: Sender<PresenceMessage>) {
+    let mut reader = BufReader::new(socket);
+    while let Ok(content) = parse_resp_line(&mut reader) {
+        let Some(message) = PresenceMessage::from_resp(&content) else {
+            continue;
+        };
+        if message.client_id != client_id && sender.send(message).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::RespMessage;
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_client_presence_publishes_and_receives_positions() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(socket.try_clone().unwrap());
+            let mut socket = socket;
+            let subscribe = parse_resp_line(&mut reader).unwrap();
+            socket.write_all(b":1\r\n").unwrap();
+
+            // Lo que publicaría el pub/sub: una posición propia y una ajena
+            for (client_id, position) in [(1, 4), (2, 7)] {
+                let message = PresenceMessage {
+                    client_id,
+                    document: "doc1".to_string(),
+                    position,
+                };
+                let RespMessage::SimpleString(hex) = message.payload() else {
+                    unreachable!();
+                };
+                socket
+                    .write_all(format!("+{}\r\n", hex).as_bytes())
+                    .unwrap();
+            }
+            (subscribe, parse_resp_line(&mut reader).unwrap())
+        });
+
+        let mut presence =
+            ClientPresence::init(1, "doc1", TcpStream::connect(addr).unwrap()).unwrap();
+        presence.publish_position(3).unwrap();
+        let (subscribe, publish) = server.join().unwrap();
+
+        assert_eq!(
+            subscribe,
+            RespMessage::Array(vec![
+                RespMessage::BulkString(Some(b"SUBSCRIBE".to_vec())),
+                RespMessage::BulkString(Some(b"doc1:presence".to_vec())),
+            ])
+        );
+        let RespMessage::Array(publish) = publish else {
+            panic!("Se esperaba un PUBLISH");
+        };
+        let RespMessage::BulkString(Some(hex)) = &publish[2] else {
+            panic!("Se esperaba el mensaje publicado");
+        };
+        let published = PresenceMessage::from_resp(&RespMessage::BulkString(Some(hex.clone())));
+        assert_eq!(published.map(|message| message.position), Some(3));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while presence.positions().is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(presence.positions(), &HashMap::from([(2, 7)]));
+    }
+}
@@ -1,42 +1,131 @@
 use crate::{
-    app::microservice::llm::utils::LLMRequest,
-    app::microservice::llm::utils::LLMResponse,
-    client_lib::cluster_manager::ClusterManager,
-    network::resp_message::RespMessage,
+    app::microservice::llm::utils::LLMRequest, app::microservice::llm::utils::LLMResponse,
+    client_lib::cluster_manager::ClusterManager, network::resp_message::RespMessage,
     network::resp_parser::parse_resp_line,
 };
 use serde_json;
-use std::io::{BufReader, Read};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, ErrorKind};
+use std::net::TcpStream;
+use std::sync::mpsc::{Receiver, channel};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 const LLM_CHANNEL: &str = "LLM_REQUESTS";
 const LLM_RESPONSES_CHANNEL: &str = "LLM_RESPONSES";
 
+/// Errores de las solicitudes de AI, separados para que la interfaz pueda
+/// mostrar qué pasó.
+#[derive(Debug)]
+pub enum LLMError {
+    /// El servicio LLM no respondió dentro del tiempo configurado.
+    Timeout(Duration),
+    /// El nodo del cluster rechazó la conexión (no está levantado).
+    ConnectionRefused,
+    /// Cualquier otro error de conexión con el cluster.
+    Connection(String),
+    /// El servicio LLM respondió con un error.
+    Service(String),
+    /// La respuesta no se pudo interpretar.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for LLMError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LLMError::Timeout(timeout) => write!(
+                f,
+                "El servicio LLM no respondió en {} segundos",
+                timeout.as_secs_f32()
+            ),
+            LLMError::ConnectionRefused => {
+                write!(f, "Conexión rechazada: el cluster no está disponible")
+            }
+            LLMError::Connection(e) => write!(f, "Error de conexión: {}", e),
+            LLMError::Service(e) => write!(f, "{}", e),
+            LLMError::InvalidResponse(e) => write!(f, "Respuesta inválida del servicio LLM: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LLMError {}
+
+impl From<io::Error> for LLMError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            ErrorKind::ConnectionRefused => LLMError::ConnectionRefused,
+            _ => LLMError::Connection(e.to_string()),
+        }
+    }
+}
+
+/// Tiempos del cliente LLM.
+#[derive(Debug, Clone)]
+pub struct LLMClientConfig {
+    /// Cuánto se espera la respuesta de cada solicitud.
+    pub request_timeout: Duration,
+    /// Reintentos de la conexión al cluster, además del primer intento.
+    pub connect_retries: u32,
+    /// Espera antes del primer reintento; se duplica en cada uno.
+    pub retry_backoff: Duration,
+}
+
+impl Default for LLMClientConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(30),
+            connect_retries: 3,
+            retry_backoff: Duration::from_millis(200),
+        }
+    }
+}
+
 /// Cliente LLM que maneja las solicitudes de AI
 pub struct LLMClient {
     cluster: ClusterManager,
-    //response_receiver: Option<Receiver<LLMResponse>>,
+    request_timeout: Duration,
 }
 
 impl LLMClient {
-    pub fn new(
+    pub fn new(redis_address: &str, user: &str, password: &str) -> Result<Self, LLMError> {
+        Self::with_config(redis_address, user, password, LLMClientConfig::default())
+    }
+
+    /// Se conecta al cluster, reintentando con espera exponencial hasta
+    /// `config.connect_retries` veces.
+    pub fn with_config(
         redis_address: &str,
         user: &str,
         password: &str,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let cluster = ClusterManager::new(
-            redis_address.to_string(),
-            user.to_string(),
-            password.to_string(),
-        )
-        .map_err(|e| format!("Error conectando al cluster: {:?}", e))?;
-
-        Ok(LLMClient {
-            cluster,
-            //response_receiver: None,
-        })
+        config: LLMClientConfig,
+    ) -> Result<Self, LLMError> {
+        let mut backoff = config.retry_backoff;
+        let mut attempt = 0;
+        loop {
+            match ClusterManager::new(
+                redis_address.to_string(),
+                user.to_string(),
+                password.to_string(),
+            ) {
+                Ok(cluster) => {
+                    return Ok(LLMClient {
+                        cluster,
+                        request_timeout: config.request_timeout,
+                    });
+                }
+                Err(e) if attempt < config.connect_retries => {
+                    println!(
+                        "[LLMClient] Error conectando al cluster ({}), reintento en {:?}",
+                        e, backoff
+                    );
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
     }
 
     /// Envía una solicitud de AI para insertar texto en una posición específica
@@ -46,7 +135,7 @@ impl LLMClient {
         prompt: String,
         position: usize,
         client_id: u64,
-    ) -> Result<String, Box<dyn std::error::Error>> {
+    ) -> Result<String, LLMError> {
         let request = LLMRequest {
             document_id,
             prompt,
@@ -65,7 +154,7 @@ impl LLMClient {
         document_id: String,
         prompt: String,
         client_id: u64,
-    ) -> Result<String, Box<dyn std::error::Error>> {
+    ) -> Result<String, LLMError> {
         let request = LLMRequest {
             document_id,
             prompt,
@@ -85,7 +174,7 @@ impl LLMClient {
         prompt: String,
         selected_text: String,
         client_id: u64,
-    ) -> Result<String, Box<dyn std::error::Error>> {
+    ) -> Result<String, LLMError> {
         let request = LLMRequest {
             document_id,
             prompt,
@@ -98,118 +187,303 @@ impl LLMClient {
         self.send_request(request)
     }
 
-    fn send_request(&mut self, request: LLMRequest) -> Result<String, Box<dyn std::error::Error>> {
-    let request_json = serde_json::to_string(&request)?;
-    let request_id = request.request_id.clone();
+    /// Igual que las solicitudes anteriores, pero devuelve los fragmentos
+    /// de la respuesta a medida que llegan, para mostrarlos mientras se
+    /// generan. El canal se cierra después del último fragmento; si algo
+    /// falla, el último mensaje es el error.
+    pub fn request_ai_stream(
+        &mut self,
+        document_id: String,
+        prompt: String,
+        position: Option<usize>,
+        selected_text: Option<String>,
+        client_id: u64,
+    ) -> Result<Receiver<Result<String, LLMError>>, LLMError> {
+        let request = LLMRequest {
+            document_id,
+            prompt,
+            position,
+            selected_text,
+            request_id: Uuid::new_v4().to_string(),
+            client_id,
+        };
+        let response_stream = self.publish_request(&request)?;
 
-    println!("[send_request] Publicando solicitud en canal {}", LLM_CHANNEL);
+        let (sender, receiver) = channel();
+        let timeout = self.request_timeout;
+        thread::spawn(move || {
+            let result = read_responses(response_stream, &request.request_id, timeout, |chunk| {
+                sender.send(Ok(chunk)).is_ok()
+            });
+            if let Err(e) = result {
+                let _ = sender.send(Err(e));
+            }
+        });
+        Ok(receiver)
+    }
 
-    
-    // Suscribirse al canal de respuestas para recibir la respuesta
-    let mut response_stream = self
-        .cluster
-        .subscribe(LLM_RESPONSES_CHANNEL)
-        .map_err(|e| {
-            println!("[send_request] Error suscribiéndose al canal de respuestas: {:?}", e);
-            format!("Error suscribiéndose al canal de respuestas: {:?}", e)
-        })?;
+    /// Publica la solicitud y espera la respuesta completa.
+    fn send_request(&mut self, request: LLMRequest) -> Result<String, LLMError> {
+        let response_stream = self.publish_request(&request)?;
+        let mut text = String::new();
+        read_responses(
+            response_stream,
+            &request.request_id,
+            self.request_timeout,
+            |chunk| {
+                text.push_str(&chunk);
+                true
+            },
+        )?;
+        Ok(text)
+    }
 
-        // Enviar la solicitud
-    self.cluster
-        .publish(LLM_CHANNEL, request_json.as_bytes())
-        .map_err(|e| {
-            println!("[send_request] Error publicando solicitud: {:?}", e);
-            format!("Error publicando solicitud: {:?}", e)
-        })?;
+    /// Se suscribe al canal de respuestas y publica la solicitud. Cada
+    /// solicitud usa una conexión de respuestas propia que se descarta al
+    /// terminar, así una respuesta que llega tarde o a medias no queda para
+    /// la siguiente.
+    fn publish_request(&mut self, request: &LLMRequest) -> Result<TcpStream, LLMError> {
+        let request_json =
+            serde_json::to_string(request).map_err(|e| LLMError::InvalidResponse(e.to_string()))?;
 
-    println!("[send_request] Suscribiéndose al canal de respuestas {}", LLM_RESPONSES_CHANNEL);
+        // Suscribirse antes de publicar para no perder la respuesta
+        let response_stream = self.cluster.subscribe(LLM_RESPONSES_CHANNEL).map_err(|e| {
+            LLMError::Connection(format!(
+                "Error suscribiéndose al canal de respuestas: {:?}",
+                e
+            ))
+        })?;
 
+        println!(
+            "[send_request] Publicando solicitud en canal {}",
+            LLM_CHANNEL
+        );
+        self.cluster
+            .publish(LLM_CHANNEL, request_json.as_bytes())
+            .map_err(|e| LLMError::Connection(format!("Error publicando solicitud: {:?}", e)))?;
 
-        // HAGO QUE EL READ SEA NO BLOQUEANTE
-    response_stream
-    .set_nonblocking(true)
-    .map_err(|e| format!("No se pudo poner el stream en modo no bloqueante: {:?}", e))?;
+        Ok(response_stream)
+    }
+}
 
-    // Esperar la respuesta del microservicio
-    let mut buffer = vec![0; 4096];
-    let mut attempts = 0;
-    let max_attempts = 300; // 30 segundos de timeout
+/// Lee el canal de respuestas y pasa a `on_chunk` el texto de cada mensaje
+/// de `request_id`, hasta el último (`partial: false`) o hasta que
+/// `on_chunk` devuelva `false`. Falla si pasa `timeout` sin recibir nada de
+/// la solicitud: el plazo se renueva con cada fragmento.
+fn read_responses(
+    stream: TcpStream,
+    request_id: &str,
+    timeout: Duration,
+    mut on_chunk: impl FnMut(String) -> bool,
+) -> Result<(), LLMError> {
+    let mut deadline = Instant::now() + timeout;
+    let mut reader = BufReader::new(stream);
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(LLMError::Timeout(timeout));
+        }
+        reader.get_ref().set_read_timeout(Some(remaining))?;
 
-    while attempts < max_attempts {
-        println!("[send_request] Intento {} esperando respuesta...", attempts + 1);
-        match response_stream.read(&mut buffer) {
-            Ok(0) => {
-                println!("[send_request] Conexión cerrada por el servidor");
-                return Err("Conexión cerrada por el servidor".into());
+        match reader.fill_buf() {
+            Ok([]) => {
+                return Err(LLMError::Connection(
+                    "Conexión cerrada por el servidor".to_string(),
+                ));
             }
-            Ok(n) => {
-                println!("[send_request] Leídos {} bytes del canal de respuesta", n);
-                let mut reader = BufReader::new(&buffer[..n]);
-                match parse_resp_line(&mut reader) {
-                    Ok(RespMessage::SimpleString(content)) => {
-                        println!("[send_request] Recibido SimpleString: {}", content);
-                        // Intentar parsear la respuesta como JSON
-                        if let Ok(response) = serde_json::from_str::<LLMResponse>(&content) {
-                            println!("[send_request] Parseado LLMResponse correctamente (SimpleString)");
-                            if response.request_id == request_id {
-                                println!("[send_request] request_id coincide (SimpleString)");
-                                if let Some(error) = response.error {
-                                    println!("[send_request] Error de AI: {}", error);
-                                    return Err(format!("Error de AI: {}", error).into());
-                                }
-                                println!("[send_request] Respuesta exitosa (SimpleString)");
-                                return Ok(response.generated_text);
-                            } else {
-                                println!("[send_request] request_id NO coincide (SimpleString)");
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                return Err(LLMError::Timeout(timeout));
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let content = match parse_resp_line(&mut reader) {
+            Ok(RespMessage::SimpleString(content)) => content.into_bytes(),
+            Ok(RespMessage::BulkString(Some(content))) => content,
+            Ok(_) => continue,
+            Err(_) if Instant::now() >= deadline => return Err(LLMError::Timeout(timeout)),
+            Err(e) => return Err(LLMError::InvalidResponse(format!("{:?}", e))),
+        };
+
+        // Las respuestas de otras solicitudes se ignoran
+        let Ok(response) = serde_json::from_slice::<LLMResponse>(&content) else {
+            continue;
+        };
+        if response.request_id != request_id {
+            continue;
+        }
+        if let Some(error) = response.error {
+            return Err(LLMError::Service(error));
+        }
+        if !response.generated_text.is_empty() && !on_chunk(response.generated_text) {
+            return Ok(());
+        }
+        if !response.partial {
+            return Ok(());
+        }
+        deadline = Instant::now() + timeout;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Nodo falso: responde lo mínimo a cada comando (AUTH, CLUSTER SLOTS,
+    /// SUBSCRIBE, PUBLISH). Por cada solicitud publicada, envía a los
+    /// suscriptos los mensajes que arme `respond`, de a uno.
+    fn start_node(respond: fn(&LLMRequest) -> Vec<LLMResponse>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let subscribers = Arc::new(Mutex::new(Vec::<TcpStream>::new()));
+        thread::spawn(move || {
+            for socket in listener.incoming().flatten() {
+                let subscribers = subscribers.clone();
+                thread::spawn(move || {
+                    let mut writer = socket.try_clone().unwrap();
+                    let mut reader = BufReader::new(socket);
+                    while let Ok(RespMessage::Array(command)) = parse_resp_line(&mut reader) {
+                        let Some(RespMessage::BulkString(Some(name))) = command.first() else {
+                            break;
+                        };
+                        let reply: &[u8] = match name.as_slice() {
+                            b"CLUSTER" => b"*0\r\n",
+                            b"AUTH" => b"+Usuario logeado correctamente - WRITE\r\n",
+                            b"SUBSCRIBE" => {
+                                subscribers
+                                    .lock()
+                                    .unwrap()
+                                    .push(writer.try_clone().unwrap());
+                                b":1\r\n"
                             }
-                        } else {
-                            println!("[send_request] No se pudo parsear LLMResponse (SimpleString)");
-                        }
-                    }
-                    Ok(RespMessage::BulkString(Some(content))) => {
-                        let content_str = String::from_utf8_lossy(&content);
-                        println!("[send_request] Recibido BulkString: {}", content_str);
-                        if let Ok(response) = serde_json::from_str::<LLMResponse>(&content_str) {
-                            println!("[send_request] Parseado LLMResponse correctamente (BulkString)");
-                            if response.request_id == request_id {
-                                println!("[send_request] request_id coincide (BulkString)");
-                                if let Some(error) = response.error {
-                                    println!("[send_request] Error de AI: {}", error);
-                                    return Err(format!("Error de AI: {}", error).into());
+                            b"PUBLISH" => {
+                                if let Some(RespMessage::BulkString(Some(json))) = command.get(2) {
+                                    let request = serde_json::from_slice(json).unwrap();
+                                    let targets = subscribers.lock().unwrap().drain(..).collect();
+                                    send_responses(targets, respond(&request));
                                 }
-                                println!("[send_request] Respuesta exitosa (BulkString)");
-                                return Ok(response.generated_text);
-                            } else {
-                                println!("[send_request] request_id NO coincide (BulkString)");
+                                b":1\r\n"
                             }
-                        } else {
-                            println!("[send_request] No se pudo parsear LLMResponse (BulkString)");
+                            _ => b":1\r\n",
+                        };
+                        if writer.write_all(reply).is_err() {
+                            break;
                         }
                     }
-                    Ok(other) => {
-                        println!("[send_request] Recibido otro tipo de mensaje: {:?}", other);
-                        // Continuar esperando
-                    }
-                    Err(e) => {
-                        println!("[send_request] Error parseando respuesta: {:?}", e);
-                        // Continuar esperando
-                    }
-                }
+                });
             }
-            Err(e) => {
-                if e.kind() == std::io::ErrorKind::WouldBlock {
-                    thread::sleep(Duration::from_millis(100));
-                } else {
-                    println!("[send_request] Error leyendo respuesta: {}", e);
-                    return Err(format!("Error leyendo respuesta: {}", e).into());
+        });
+        addr
+    }
+
+    /// Envía cada respuesta como un mensaje del pub/sub, con una pausa
+    /// entre mensajes.
+    fn send_responses(mut targets: Vec<TcpStream>, responses: Vec<LLMResponse>) {
+        thread::spawn(move || {
+            for response in responses {
+                thread::sleep(Duration::from_millis(50));
+                let json = serde_json::to_string(&response).unwrap();
+                for target in &mut targets {
+                    let _ = target.write_all(format!("+{}\r\n", json).as_bytes());
                 }
             }
+        });
+    }
+
+    fn response(request: &LLMRequest, text: &str, partial: bool) -> LLMResponse {
+        LLMResponse {
+            document_id: request.document_id.clone(),
+            request_id: request.request_id.clone(),
+            client_id: request.client_id,
+            generated_text: text.to_string(),
+            position: request.position,
+            selected_text: None,
+            error: None,
+            partial,
         }
-        attempts += 1;
     }
 
-    println!("[send_request] Timeout esperando respuesta del microservicio LLM");
-    Err("Timeout esperando respuesta del microservicio LLM".into())
-}
+    /// La respuesta llega en tres fragmentos, precedida por la de otra
+    /// solicitud que se debe ignorar.
+    fn respond_in_chunks(request: &LLMRequest) -> Vec<LLMResponse> {
+        let mut other = response(request, "Reaper", false);
+        other.request_id = "otra".to_string();
+        vec![
+            other,
+            response(request, "Tracer", true),
+            response(request, " vuelve", true),
+            response(request, " al ataque!", false),
+        ]
+    }
+
+    fn test_config() -> LLMClientConfig {
+        LLMClientConfig {
+            request_timeout: Duration::from_millis(500),
+            ..LLMClientConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_request_times_out_when_llm_never_answers() {
+        let addr = start_node(|_| Vec::new());
+        let mut client = LLMClient::with_config(&addr, "super", "1234", test_config()).unwrap();
+
+        let start = Instant::now();
+        let result = client.request_ai_replace("doc1".to_string(), "Ultimate".to_string(), 7);
+
+        assert!(matches!(result, Err(LLMError::Timeout(_))));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // El cliente sigue usable: la siguiente solicitud también vence sola
+        let result = client.request_ai_insert("doc1".to_string(), "Lúcio".to_string(), 0, 7);
+        assert!(matches!(result, Err(LLMError::Timeout(_))));
+    }
+
+    #[test]
+    fn test_refused_connection_is_reported_after_retries() {
+        // Un puerto libre en el que no escucha nadie
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let config = LLMClientConfig {
+            connect_retries: 2,
+            retry_backoff: Duration::from_millis(10),
+            ..LLMClientConfig::default()
+        };
+
+        let result = LLMClient::with_config(&addr, "super", "1234", config);
+
+        assert!(matches!(result, Err(LLMError::ConnectionRefused)));
+    }
+
+    #[test]
+    fn test_stream_receives_chunks_in_order() {
+        let addr = start_node(respond_in_chunks);
+        let mut client = LLMClient::with_config(&addr, "super", "1234", test_config()).unwrap();
+
+        let receiver = client
+            .request_ai_stream("doc1".to_string(), "Blink".to_string(), Some(3), None, 7)
+            .unwrap();
+        let chunks: Vec<String> = receiver.iter().map(Result::unwrap).collect();
 
+        assert_eq!(chunks, vec!["Tracer", " vuelve", " al ataque!"]);
+    }
+
+    #[test]
+    fn test_blocking_request_joins_chunks() {
+        let addr = start_node(respond_in_chunks);
+        let mut client = LLMClient::with_config(&addr, "super", "1234", test_config()).unwrap();
+
+        let text = client
+            .request_ai_replace("doc1".to_string(), "Recall".to_string(), 7)
+            .unwrap();
+
+        assert_eq!(text, "Tracer vuelve al ataque!");
+    }
 }
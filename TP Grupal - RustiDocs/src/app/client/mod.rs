@@ -3,5 +3,6 @@ pub mod client_index;
 pub mod client_init;
 pub mod client_input;
 pub mod client_output;
+pub mod client_presence;
 pub mod llm_client;
 pub mod tests;
@@ -117,4 +117,150 @@ mod tests {
         assert_eq!(client.local_data, "AB");
         assert_eq!(client.pending_operations.len(), 1);
     }
+
+    #[test]
+    fn test_read_only_client_rejects_local_but_applies_remote() {
+        let mut client = Client::new_no_output(String::from("Ana"), 0, 1);
+        client.set_read_only("solo lectura".to_string());
+
+        let edit = client.try_apply_local_operation(TextOperation::Delete { position: 0 });
+        assert_eq!(edit, Err("solo lectura".to_string()));
+        assert_eq!(client.local_data, "Ana");
+        assert!(client.pending_operations.is_empty());
+
+        client.receive_remote_instruction(Instruction {
+            base_version: 0,
+            operation: TextOperation::Insert {
+                position: 3,
+                character: '!',
+            },
+            operation_id: make_op_id(2, 0),
+        });
+        assert_eq!(client.local_data, "Ana!");
+        assert_eq!(client.local_version, 1);
+    }
+
+    /// Aplica `operation` como acción del usuario y verifica que deshacerla
+    /// deja el texto exactamente como estaba, y que rehacerla lo vuelve a
+    /// dejar igual que después de aplicarla.
+    fn assert_undo_redo(initial: &str, operation: TextOperation, expected: &str) {
+        let mut client = Client::new_no_output(initial.to_string(), 0, 1);
+
+        client.apply_undoable_operations(vec![operation]);
+        assert_eq!(client.local_data, expected);
+
+        assert!(client.undo().is_some());
+        assert_eq!(client.local_data, initial);
+
+        assert!(client.redo().is_some());
+        assert_eq!(client.local_data, expected);
+    }
+
+    #[test]
+    fn test_undo_insert_and_delete() {
+        assert_undo_redo(
+            "Mercy",
+            TextOperation::Insert {
+                position: 1,
+                character: 'é',
+            },
+            "Méercy",
+        );
+        assert_undo_redo("Lúcio", TextOperation::Delete { position: 1 }, "Lcio");
+    }
+
+    #[test]
+    fn test_undo_insert_text_and_delete_range() {
+        assert_undo_redo(
+            "Hola Mei",
+            TextOperation::InsertText {
+                position: 5,
+                text: "世界 ".to_string(),
+            },
+            "Hola 世界 Mei",
+        );
+        assert_undo_redo(
+            "Hola 世界 Mei",
+            TextOperation::DeleteRange { start: 5, end: 8 },
+            "Hola Mei",
+        );
+    }
+
+    #[test]
+    fn test_undo_delete_all_restores_snapshot() {
+        assert_undo_redo(
+            "Zenyatta: «Experiencia tranquilidad»",
+            TextOperation::DeleteAll,
+            "",
+        );
+    }
+
+    #[test]
+    fn test_undo_reverts_whole_action() {
+        // Como el reemplazo de la IA: borrar todo e insertar la respuesta
+        let mut client = Client::new_no_output("borrador".to_string(), 0, 1);
+        client.apply_undoable_operations(vec![
+            TextOperation::DeleteAll,
+            TextOperation::InsertText {
+                position: 0,
+                text: "Texto de Echo".to_string(),
+            },
+        ]);
+        assert_eq!(client.local_data, "Texto de Echo");
+
+        let sent = client.undo().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(client.local_data, "borrador");
+        // Las inversas se envían al servidor como operaciones locales
+        assert_eq!(client.pending_operations.len(), 4);
+        assert!(client.undo().is_none());
+    }
+
+    #[test]
+    fn test_new_action_discards_redo() {
+        let mut client = Client::new_no_output("Ana".to_string(), 0, 1);
+        client.apply_undoable_operations(vec![TextOperation::Delete { position: 0 }]);
+        client.undo();
+
+        client.apply_undoable_operations(vec![TextOperation::Insert {
+            position: 3,
+            character: '!',
+        }]);
+
+        assert!(client.redo().is_none());
+        assert_eq!(client.local_data, "Ana!");
+    }
+
+    #[test]
+    fn test_undo_after_remote_operation() {
+        let mut client = Client::new_no_output("Ana".to_string(), 0, 1);
+        client.apply_undoable_operations(vec![TextOperation::Insert {
+            position: 3,
+            character: '!',
+        }]);
+
+        // Otro cliente escribe al principio antes de que se deshaga
+        client.receive_remote_instruction(Instruction {
+            base_version: 0,
+            operation: TextOperation::InsertText {
+                position: 0,
+                text: "Hola ".to_string(),
+            },
+            operation_id: make_op_id(2, 0),
+        });
+        assert_eq!(client.local_data, "Hola Ana!");
+
+        client.undo();
+        assert_eq!(client.local_data, "Hola Ana");
+    }
+
+    #[test]
+    fn test_read_only_client_cannot_undo() {
+        let mut client = Client::new_no_output("Ana".to_string(), 0, 1);
+        client.apply_undoable_operations(vec![TextOperation::DeleteAll]);
+        client.set_read_only("solo lectura".to_string());
+
+        assert!(client.undo().is_none());
+        assert_eq!(client.local_data, "");
+    }
 }
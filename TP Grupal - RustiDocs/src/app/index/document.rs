@@ -1,4 +1,9 @@
 use crate::app::operation::generic::ParsableBytes;
+use std::collections::BTreeMap;
+
+/// Motivo por el que un participante con permiso de lectura no puede editar.
+pub const ERR_READ_ONLY_ACCESS: &str =
+    "Solo tenés permiso de lectura sobre este documento: se abrió en modo solo lectura";
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DocType {
@@ -38,12 +43,42 @@ impl ParsableBytes for DocType {
     }
 }
 
+/// Permiso de un usuario sobre un documento. Un usuario sin entrada en la
+/// ACL no tiene acceso.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocAccess {
+    Read,
+    Write,
+}
+
+impl ParsableBytes for DocAccess {
+    fn to_bytes(&self) -> Vec<u8> {
+        let byte = match self {
+            DocAccess::Read => 0u8,
+            DocAccess::Write => 1u8,
+        };
+        vec![byte]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<(Self, usize)> {
+        let access = match bytes.first()? {
+            0 => DocAccess::Read,
+            1 => DocAccess::Write,
+            _ => return None,
+        };
+        Some((access, 1))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Document {
     name: String,
     doc_type: DocType,
     connected_clients: u64,
     active: bool,
+    // Vacío en documentos creados antes de existir dueños: sin restricciones
+    owner: String,
+    acl: BTreeMap<String, DocAccess>,
 }
 
 impl Document {
@@ -53,7 +88,58 @@ impl Document {
             doc_type,
             connected_clients: 0,
             active: false,
+            owner: String::new(),
+            acl: BTreeMap::new(),
+        }
+    }
+
+    /// Crea un documento cuyo dueño es `owner`.
+    pub fn with_owner(doc_name: String, doc_type: DocType, owner: String) -> Self {
+        Self {
+            owner,
+            ..Self::new(doc_name, doc_type)
+        }
+    }
+
+    pub fn get_owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Permiso de `user` sobre el documento. El dueño siempre puede
+    /// escribir, y un documento sin dueño queda abierto a todos.
+    ///
+    /// # Returns
+    ///
+    /// `None` si el usuario no tiene acceso
+    pub fn access_for(&self, user: &str) -> Option<DocAccess> {
+        if self.owner.is_empty() || self.owner == user {
+            return Some(DocAccess::Write);
+        }
+        self.acl.get(user).copied()
+    }
+
+    /// Solo el dueño puede borrar el documento.
+    pub fn can_remove(&self, user: &str) -> bool {
+        self.owner.is_empty() || self.owner == user
+    }
+
+    /// Otorga (o quita, con `None`) el permiso de `user`. Solo el dueño puede
+    /// cambiar la ACL, y su propio permiso no se modifica.
+    ///
+    /// # Returns
+    ///
+    /// `false` si `requester` no es el dueño
+    pub fn set_access(&mut self, requester: &str, user: &str, access: Option<DocAccess>) -> bool {
+        if self.owner.is_empty() || self.owner != requester {
+            return false;
+        }
+        if user != self.owner {
+            match access {
+                Some(access) => self.acl.insert(user.to_string(), access),
+                None => self.acl.remove(user),
+            };
         }
+        true
     }
 
     pub fn get_name(&self) -> String {
@@ -87,6 +173,14 @@ impl ParsableBytes for Document {
         // Serialize active
         bytes.push(self.active as u8);
 
+        // Serialize owner y ACL
+        bytes.extend(self.owner.to_bytes());
+        bytes.extend(&(self.acl.len() as u32).to_le_bytes());
+        for (user, access) in &self.acl {
+            bytes.extend(user.to_bytes());
+            bytes.extend(access.to_bytes());
+        }
+
         bytes
     }
 
@@ -132,12 +226,36 @@ impl ParsableBytes for Document {
         let active = bytes[offset] != 0;
         offset += 1;
 
+        // Read owner y ACL, ausentes en documentos guardados antes de existir
+        let mut owner = String::new();
+        let mut acl = BTreeMap::new();
+        if bytes.len() > offset {
+            let used;
+            (owner, used) = String::from_bytes(&bytes[offset..])?;
+            offset += used;
+
+            if bytes.len() < offset + 4 {
+                return None;
+            }
+            let acl_len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().ok()?);
+            offset += 4;
+            for _ in 0..acl_len {
+                let (user, used) = String::from_bytes(&bytes[offset..])?;
+                offset += used;
+                let (access, used) = DocAccess::from_bytes(&bytes[offset..])?;
+                offset += used;
+                acl.insert(user, access);
+            }
+        }
+
         Some((
             Document {
                 name,
                 doc_type,
                 connected_clients,
                 active,
+                owner,
+                acl,
             },
             offset,
         ))
@@ -155,6 +273,8 @@ mod tests {
             doc_type: DocType::Text,
             connected_clients: 42,
             active: true,
+            owner: String::new(),
+            acl: BTreeMap::new(),
         };
         let bytes = doc.to_bytes();
         let (parsed_doc, used) = Document::from_bytes(&bytes).unwrap();
@@ -175,6 +295,8 @@ mod tests {
             doc_type: DocType::SpreadSheet,
             connected_clients: 0,
             active: false,
+            owner: String::new(),
+            acl: BTreeMap::new(),
         };
         let bytes = doc.to_bytes();
         let (parsed_doc, used) = Document::from_bytes(&bytes).unwrap();
@@ -195,6 +317,8 @@ mod tests {
             doc_type: DocType::Text,
             connected_clients: 1,
             active: false,
+            owner: String::new(),
+            acl: BTreeMap::new(),
         }
         .to_bytes();
         // Overwrite doc_type byte with invalid value
@@ -211,10 +335,71 @@ mod tests {
             doc_type: DocType::Text,
             connected_clients: 1,
             active: false,
+            owner: String::new(),
+            acl: BTreeMap::new(),
         }
         .to_bytes();
-        // Remove last byte (active)
-        let truncated = &doc[..doc.len() - 1];
+        // Remove active y lo que sigue
+        let truncated = &doc[..4 + 5 + 1 + 8];
         assert!(Document::from_bytes(truncated).is_none());
     }
+
+    #[test]
+    fn test_document_with_acl_round_trip() {
+        let mut doc = Document::with_owner(
+            "Roster".to_string(),
+            DocType::SpreadSheet,
+            "Illari".to_string(),
+        );
+        assert!(doc.set_access("Illari", "Kiriko", Some(DocAccess::Read)));
+        assert!(doc.set_access("Illari", "Lifeweaver", Some(DocAccess::Write)));
+
+        let bytes = doc.to_bytes();
+        let (parsed, used) = Document::from_bytes(&bytes).unwrap();
+        assert_eq!(used, bytes.len());
+        assert_eq!(parsed, doc);
+    }
+
+    #[test]
+    fn test_legacy_document_without_owner_is_open() {
+        let mut bytes = Document::new("Notes".to_string(), DocType::Text).to_bytes();
+        // Formato anterior: termina en el byte de active
+        bytes.truncate(4 + 5 + 1 + 8 + 1);
+
+        let (parsed, used) = Document::from_bytes(&bytes).unwrap();
+        assert_eq!(used, bytes.len());
+        assert_eq!(parsed.get_owner(), "");
+        assert_eq!(parsed.access_for("Junker Queen"), Some(DocAccess::Write));
+        assert!(parsed.can_remove("Junker Queen"));
+    }
+
+    #[test]
+    fn test_owner_can_always_modify() {
+        let mut doc =
+            Document::with_owner("Patch".to_string(), DocType::Text, "Illari".to_string());
+        assert_eq!(doc.access_for("Illari"), Some(DocAccess::Write));
+        assert!(doc.can_remove("Illari"));
+
+        // Ni el dueño puede bajarse el permiso a sí mismo
+        assert!(doc.set_access("Illari", "Illari", Some(DocAccess::Read)));
+        assert_eq!(doc.access_for("Illari"), Some(DocAccess::Write));
+    }
+
+    #[test]
+    fn test_only_owner_changes_acl() {
+        let mut doc =
+            Document::with_owner("Patch".to_string(), DocType::Text, "Illari".to_string());
+        assert_eq!(doc.access_for("Kiriko"), None);
+        assert!(!doc.can_remove("Kiriko"));
+
+        assert!(!doc.set_access("Kiriko", "Kiriko", Some(DocAccess::Write)));
+        assert_eq!(doc.access_for("Kiriko"), None);
+
+        assert!(doc.set_access("Illari", "Kiriko", Some(DocAccess::Read)));
+        assert_eq!(doc.access_for("Kiriko"), Some(DocAccess::Read));
+        assert!(!doc.can_remove("Kiriko"));
+
+        assert!(doc.set_access("Illari", "Kiriko", None));
+        assert_eq!(doc.access_for("Kiriko"), None);
+    }
 }
@@ -1,13 +1,32 @@
-use crate::app::index::document::DocType;
+use crate::app::index::document::{DocAccess, DocType, Document};
 use crate::app::index::documents::Documents;
 use crate::app::operation::generic::ParsableBytes;
 
+/// Instrucciones que viajan por el canal del índice. Las que modifican un
+/// documento llevan el usuario que las pide, para que el índice valide que
+/// sea el dueño.
+///
+/// Ante cada cambio el índice publica un evento (`DocAdded`, `DocRemoved`)
+/// que los clientes suscriptos aplican sobre su lista; `Docs` lleva la lista
+/// completa y se usa como respuesta a `Refresh`.
 #[derive(Debug)]
 pub enum IndexInstructions {
     Docs(Documents),
-    CreateDoc(String, DocType),
-    RemoveDoc(String),
+    /// Nombre, tipo y dueño del documento. El índice toma como dueño al
+    /// usuario autenticado que publicó la instrucción
+    CreateDoc(String, DocType, String),
+    /// Nombre del documento y usuario que lo borra, que el índice toma
+    /// del publicador
+    RemoveDoc(String, String),
     Refresh,
+    /// Documento, usuario que lo pide, usuario afectado y permiso
+    /// (`None` quita el acceso). El índice toma como solicitante al
+    /// publicador
+    SetAccess(String, String, String, Option<DocAccess>),
+    /// Se creó un documento o cambiaron sus permisos
+    DocAdded(Document),
+    /// Se borró el documento con ese nombre
+    DocRemoved(String),
 }
 impl ParsableBytes for IndexInstructions {
     fn from_bytes(bytes: &[u8]) -> Option<(IndexInstructions, usize)> {
@@ -22,20 +41,52 @@ impl ParsableBytes for IndexInstructions {
                 // CreateDoc
                 let (name, used1) = String::from_bytes(&bytes[1..])?;
                 let (doc_type, used2) = DocType::from_bytes(&bytes[1 + used1..])?;
+                let (owner, used3) = String::from_bytes(&bytes[1 + used1 + used2..])?;
                 Some((
-                    IndexInstructions::CreateDoc(name, doc_type),
-                    1 + used1 + used2,
+                    IndexInstructions::CreateDoc(name, doc_type, owner),
+                    1 + used1 + used2 + used3,
                 ))
             }
             2 => {
                 // RemoveDoc
-                let (name, used) = String::from_bytes(&bytes[1..])?;
-                Some((IndexInstructions::RemoveDoc(name), 1 + used))
+                let (name, used1) = String::from_bytes(&bytes[1..])?;
+                let (user, used2) = String::from_bytes(&bytes[1 + used1..])?;
+                Some((IndexInstructions::RemoveDoc(name, user), 1 + used1 + used2))
             }
             3 => {
                 // Refresh
                 Some((IndexInstructions::Refresh, 1))
             }
+            4 => {
+                // SetAccess
+                let mut offset = 1;
+                let (name, used) = String::from_bytes(&bytes[offset..])?;
+                offset += used;
+                let (requester, used) = String::from_bytes(&bytes[offset..])?;
+                offset += used;
+                let (user, used) = String::from_bytes(&bytes[offset..])?;
+                offset += used;
+                // 0 si se quita el acceso, 1 seguido del permiso si se otorga
+                let access = match bytes.get(offset)? {
+                    0 => None,
+                    _ => Some(DocAccess::from_bytes(&bytes[offset + 1..])?.0),
+                };
+                offset += if access.is_some() { 2 } else { 1 };
+                Some((
+                    IndexInstructions::SetAccess(name, requester, user, access),
+                    offset,
+                ))
+            }
+            5 => {
+                // DocAdded
+                let (doc, used) = Document::from_bytes(&bytes[1..])?;
+                Some((IndexInstructions::DocAdded(doc), 1 + used))
+            }
+            6 => {
+                // DocRemoved
+                let (name, used) = String::from_bytes(&bytes[1..])?;
+                Some((IndexInstructions::DocRemoved(name), 1 + used))
+            }
             _ => None,
         }
     }
@@ -47,18 +98,44 @@ impl ParsableBytes for IndexInstructions {
                 v.extend(docs.to_bytes());
                 v
             }
-            IndexInstructions::CreateDoc(name, doc_type) => {
+            IndexInstructions::CreateDoc(name, doc_type, owner) => {
                 let mut v = vec![1];
                 v.extend(name.to_bytes());
                 v.extend(doc_type.to_bytes());
+                v.extend(owner.to_bytes());
                 v
             }
-            IndexInstructions::RemoveDoc(name) => {
+            IndexInstructions::RemoveDoc(name, user) => {
                 let mut v = vec![2];
                 v.extend(name.to_bytes());
+                v.extend(user.to_bytes());
                 v
             }
             IndexInstructions::Refresh => vec![3],
+            IndexInstructions::SetAccess(name, requester, user, access) => {
+                let mut v = vec![4];
+                v.extend(name.to_bytes());
+                v.extend(requester.to_bytes());
+                v.extend(user.to_bytes());
+                match access {
+                    Some(access) => {
+                        v.push(1);
+                        v.extend(access.to_bytes());
+                    }
+                    None => v.push(0),
+                }
+                v
+            }
+            IndexInstructions::DocAdded(doc) => {
+                let mut v = vec![5];
+                v.extend(doc.to_bytes());
+                v
+            }
+            IndexInstructions::DocRemoved(name) => {
+                let mut v = vec![6];
+                v.extend(name.to_bytes());
+                v
+            }
         }
     }
 }
@@ -105,6 +182,45 @@ mod tests {
         assert_eq!(used, 1);
     }
 
+    #[test]
+    fn test_to_bytes_and_from_bytes_remove_doc() {
+        let instr = IndexInstructions::RemoveDoc("Roster".to_string(), "Kiriko".to_string());
+        let bytes = instr.to_bytes();
+        let (parsed, used) = IndexInstructions::from_bytes(&bytes).unwrap();
+        match parsed {
+            IndexInstructions::RemoveDoc(name, user) => {
+                assert_eq!(name, "Roster");
+                assert_eq!(user, "Kiriko");
+            }
+            _ => panic!("Expected RemoveDoc variant"),
+        }
+        assert_eq!(used, bytes.len());
+    }
+
+    #[test]
+    fn test_to_bytes_and_from_bytes_set_access() {
+        for access in [Some(DocAccess::Read), Some(DocAccess::Write), None] {
+            let instr = IndexInstructions::SetAccess(
+                "Roster".to_string(),
+                "Illari".to_string(),
+                "Kiriko".to_string(),
+                access,
+            );
+            let bytes = instr.to_bytes();
+            let (parsed, used) = IndexInstructions::from_bytes(&bytes).unwrap();
+            match parsed {
+                IndexInstructions::SetAccess(name, requester, user, parsed_access) => {
+                    assert_eq!(name, "Roster");
+                    assert_eq!(requester, "Illari");
+                    assert_eq!(user, "Kiriko");
+                    assert_eq!(parsed_access, access);
+                }
+                _ => panic!("Expected SetAccess variant"),
+            }
+            assert_eq!(used, bytes.len());
+        }
+    }
+
     #[test]
     fn test_from_bytes_invalid_instruction() {
         let bytes = vec![42, 0, 1, 2];
@@ -2,6 +2,7 @@ use crate::app::operation::generic::Applicable;
 use crate::app::operation::generic::Instruction;
 use crate::app::operation::generic::ParsableBytes;
 use crate::app::operation::generic::Transformable;
+use crate::app::operation::version_vector::VersionVector;
 
 /// Modulo de control generico, recibe la estructura de datos
 /// y las operaciones que se van a aplicar sobre ella.
@@ -23,6 +24,11 @@ where
     pub data: D,
     pub operations_log: Vec<Instruction<O>>,
     pub version: u64,
+    /// Versión de la primera operación que sigue en el log: las anteriores
+    /// se descartaron al compactar y ya están incluidas en `data`.
+    pub log_offset: u64,
+    /// Operaciones aplicadas de cada cliente, para validar los guardados.
+    pub version_vector: VersionVector,
 }
 
 impl<D, O> ControlService<D, O>
@@ -34,6 +40,8 @@ where
             data,
             operations_log: Vec::new(),
             version: 0,
+            log_offset: 0,
+            version_vector: VersionVector::new(),
         }
     }
     // Dada una instruccion en bruto, se la transforma a la ultima version
@@ -52,6 +60,12 @@ where
             return Err(ControlServiceError::VersionHigherThanCurrent);
         }
 
+        // Las operaciones anteriores a la versión base ya no están en el log,
+        // por lo que no se puede transformar.
+        if instruction.base_version < self.log_offset {
+            return Err(ControlServiceError::VersionCompacted);
+        }
+
         // Si la version base es menor a la version actual, se transforma la operacion
         // teniendo en cuenta las operaciones que ya se aplicaron.
         if instruction.base_version != self.version {
@@ -59,7 +73,7 @@ where
             for operation_history in self
                 .operations_log
                 .iter()
-                .skip(instruction.base_version as usize)
+                .skip((instruction.base_version - self.log_offset) as usize)
             {
                 instruction.operation = instruction
                     .operation
@@ -72,6 +86,8 @@ where
 
         // Actualizo la version del servicio de control.
         self.version += 1;
+        self.version_vector
+            .record(instruction.operation_id.client_id);
 
         // Agrego la instruccion al log de operaciones.
         self.operations_log.push(instruction.clone());
@@ -82,9 +98,91 @@ where
         // Devuelvo la instruccion transformada.
         Ok(instruction)
     }
+
+    // Guarda las operaciones pendientes de un cliente hechas sobre `base`,
+    // el vector de versiones que conocia. Si el servidor ya aplico operaciones
+    // que la base no cuenta, el guardado es viejo: se rechaza con esas
+    // operaciones para que el cliente rebase sus pendientes con la
+    // transformacion y vuelva a intentar. Si no, se aplican tal cual y se
+    // devuelven las instrucciones aplicadas.
+    pub fn save(
+        &mut self,
+        base: &VersionVector,
+        operations: Vec<Instruction<O>>,
+    ) -> Result<Vec<Instruction<O>>, SaveError<O>> {
+        if !self.version_vector.dominates(base) {
+            return Err(SaveError::BaseAhead);
+        }
+        if *base != self.version_vector {
+            return Err(SaveError::Rebase(self.missing_operations(base)?));
+        }
+
+        let mut applied = Vec::with_capacity(operations.len());
+        for mut instruction in operations {
+            instruction.base_version = self.version;
+            applied.push(
+                self.apply_operation(instruction)
+                    .map_err(|_| SaveError::BaseAhead)?,
+            );
+        }
+        Ok(applied)
+    }
+
+    // Operaciones del log que `base` no cuenta, en el orden en que se
+    // aplicaron. Si alguna ya se descarto al compactar, el cliente tiene que
+    // volver a partir del estado actual.
+    fn missing_operations(
+        &self,
+        base: &VersionVector,
+    ) -> Result<Vec<Instruction<O>>, SaveError<O>> {
+        let mut missing = self.version_vector.missing_from(base);
+        let mut operations = Vec::new();
+        for instruction in self.operations_log.iter().rev() {
+            if let Some(count) = missing
+                .get_mut(&instruction.operation_id.client_id)
+                .filter(|count| **count > 0)
+            {
+                *count -= 1;
+                operations.push(instruction.clone());
+            }
+        }
+        if missing.values().any(|count| *count > 0) {
+            return Err(SaveError::HistoryCompacted);
+        }
+        operations.reverse();
+        Ok(operations)
+    }
+
+    // Descarta del log las operaciones anteriores a `acked_version`, la
+    // version que ya confirmaron todos los clientes que pueden escribir.
+    // Ninguno va a volver a mandar una instruccion con una version base
+    // menor, y los que se unan despues parten de `data`, que ya las incluye.
+    // Devuelve la cantidad de operaciones descartadas.
+    pub fn compact(&mut self, acked_version: u64) -> usize {
+        let acked_version = acked_version.min(self.version);
+        if acked_version <= self.log_offset {
+            return 0;
+        }
+        let discarded = (acked_version - self.log_offset) as usize;
+        self.operations_log.drain(..discarded);
+        self.log_offset = acked_version;
+        discarded
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ControlServiceError {
     VersionHigherThanCurrent,
+    VersionCompacted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveError<O: ParsableBytes> {
+    /// La base es vieja: el cliente tiene que rebasar sus operaciones
+    /// pendientes sobre estas antes de volver a guardar.
+    Rebase(Vec<Instruction<O>>),
+    /// Las operaciones que le faltan a la base ya se compactaron.
+    HistoryCompacted,
+    /// La base cuenta operaciones que el servidor no aplicó.
+    BaseAhead,
 }
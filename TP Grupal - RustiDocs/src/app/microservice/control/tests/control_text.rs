@@ -1,10 +1,16 @@
 #[cfg(test)]
 mod tests {
 
-    use crate::app::microservice::control::control_service::ControlService;
+    use crate::app::client::client_data::Client;
+    use crate::app::microservice::control::control_service::{
+        ControlService, ControlServiceError, SaveError,
+    };
+    use crate::app::microservice::participants::Participants;
     use crate::app::operation::generic::Instruction;
     use crate::app::operation::generic::InstructionId;
+    use crate::app::operation::generic::ParsableBytes;
     use crate::app::operation::text::TextOperation;
+    use crate::app::operation::version_vector::VersionVector;
 
     fn new_instruction<O: crate::app::operation::generic::ParsableBytes>(
         client_id: u64,
@@ -168,4 +174,187 @@ mod tests {
         let result = engine.apply_operation(instr);
         assert!(result.is_err());
     }
+
+    fn insert(position: usize, character: char) -> TextOperation {
+        TextOperation::Insert {
+            position,
+            character,
+        }
+    }
+
+    #[test]
+    fn test_history_trimmed_once_all_clients_ack() {
+        let mut engine = ControlService::<String, TextOperation>::new(String::new());
+        let mut participants = Participants::new();
+        participants.join(1, "Mei", None, 0);
+        participants.join(2, "Cassidy", None, 0);
+
+        for (seq, character) in "Snow".chars().enumerate() {
+            let instr = new_instruction(1, seq as u64, seq as u64, insert(seq, character));
+            participants.ack(1, instr.base_version);
+            engine.apply_operation(instr).unwrap();
+        }
+        participants.ack(2, 2);
+
+        let acked = participants.min_acked_version().unwrap();
+        assert_eq!(acked, 2);
+        assert_eq!(engine.compact(acked), 2);
+
+        assert_eq!(engine.operations_log.len(), 2);
+        assert_eq!(engine.log_offset, 2);
+        assert_eq!(engine.version, 4);
+        assert_eq!(engine.data, "Snow");
+
+        // Compactar de nuevo hasta el mismo punto no descarta nada
+        assert_eq!(engine.compact(acked), 0);
+    }
+
+    #[test]
+    fn test_late_joiner_reconstructs_document_after_compaction() {
+        let mut engine = ControlService::<String, TextOperation>::new(String::new());
+        for (seq, character) in "Mei".chars().enumerate() {
+            let instr = new_instruction(1, seq as u64, seq as u64, insert(seq, character));
+            engine.apply_operation(instr).unwrap();
+        }
+        assert_eq!(engine.compact(engine.version), 3);
+        assert!(engine.operations_log.is_empty());
+
+        // El cliente nuevo parte del estado compactado
+        let mut late = Client::new_no_output(engine.data.clone(), engine.version, 3);
+        let instr = late.apply_local_operation(insert(3, '!'));
+        let response = engine.apply_operation(instr).unwrap();
+        late.receive_remote_instruction(response);
+
+        assert_eq!(engine.data, "Mei!");
+        assert_eq!(late.local_data, engine.data);
+        assert!(late.pending_operations.is_empty());
+    }
+
+    #[test]
+    fn test_stuck_client_prevents_unsafe_trimming() {
+        let mut engine = ControlService::<String, TextOperation>::new("Zarya".to_string());
+        let mut participants = Participants::new();
+        participants.join(1, "Mei", None, 0);
+        participants.join(2, "Cassidy", None, 0);
+
+        for (seq, character) in "abc".chars().enumerate() {
+            let instr = new_instruction(1, seq as u64, seq as u64, insert(5 + seq, character));
+            participants.ack(1, instr.base_version);
+            engine.apply_operation(instr).unwrap();
+        }
+
+        // El cliente 2 no confirmó nada: no se puede descartar historial
+        let acked = participants.min_acked_version().unwrap();
+        assert_eq!(acked, 0);
+        assert_eq!(engine.compact(acked), 0);
+        assert_eq!(engine.operations_log.len(), 3);
+
+        // Su instrucción atrasada todavía se transforma correctamente
+        let stale = new_instruction(2, 0, 0, TextOperation::Delete { position: 0 });
+        engine.apply_operation(stale.clone()).unwrap();
+        assert_eq!(engine.data, "aryaabc");
+
+        // Si se hubiera compactado igual, se rechaza en vez de aplicarse mal
+        engine.compact(engine.version);
+        assert_eq!(
+            engine.apply_operation(stale),
+            Err(ControlServiceError::VersionCompacted)
+        );
+        assert_eq!(engine.data, "aryaabc");
+    }
+
+    /// Dos clientes que editaron por separado a partir de "Mei".
+    fn clients_from_same_base() -> (
+        ControlService<String, TextOperation>,
+        Client<String, TextOperation>,
+        Client<String, TextOperation>,
+    ) {
+        let engine = ControlService::<String, TextOperation>::new("Mei".to_string());
+        let mut first = Client::new_no_output("Mei".to_string(), 0, 1);
+        let mut second = Client::new_no_output("Mei".to_string(), 0, 2);
+        first.apply_local_operation(insert(3, '!'));
+        second.apply_local_operation(insert(0, 'A'));
+        (engine, first, second)
+    }
+
+    #[test]
+    fn test_second_save_from_same_base_must_rebase() {
+        let (mut engine, mut first, second) = clients_from_same_base();
+
+        let (base, operations) = first.save_request();
+        let applied = engine.save(&base, operations).unwrap();
+        first.rebase(applied);
+        assert!(first.pending_operations.is_empty());
+
+        let (base, operations) = second.save_request();
+        match engine.save(&base, operations) {
+            Err(SaveError::Rebase(missing)) => {
+                assert_eq!(missing.len(), 1);
+                assert_eq!(missing[0].operation_id.client_id, 1);
+            }
+            other => panic!("Expected Rebase, got {:?}", other),
+        }
+        // El guardado rechazado no cambia el documento
+        assert_eq!(engine.data, "Mei!");
+    }
+
+    #[test]
+    fn test_rebased_save_applies_cleanly() {
+        let (mut engine, mut first, mut second) = clients_from_same_base();
+        let (base, operations) = first.save_request();
+        let applied_first = engine.save(&base, operations).unwrap();
+        first.rebase(applied_first.clone());
+
+        let (base, operations) = second.save_request();
+        let Err(SaveError::Rebase(missing)) = engine.save(&base, operations) else {
+            panic!("Expected Rebase");
+        };
+        second.rebase(missing);
+        assert_eq!(second.local_data, "AMei!");
+        assert_eq!(second.version_vector, engine.version_vector);
+
+        let (base, operations) = second.save_request();
+        let applied_second = engine.save(&base, operations).unwrap();
+        second.rebase(applied_second.clone());
+        first.rebase(applied_second);
+
+        assert!(second.pending_operations.is_empty());
+        assert_eq!(first.local_data, engine.data);
+        assert_eq!(second.local_data, engine.data);
+    }
+
+    #[test]
+    fn test_persisted_document_reflects_both_edits() {
+        let (mut engine, mut first, mut second) = clients_from_same_base();
+        let (base, operations) = first.save_request();
+        first.rebase(engine.save(&base, operations).unwrap());
+        let (base, operations) = second.save_request();
+        let Err(SaveError::Rebase(missing)) = engine.save(&base, operations) else {
+            panic!("Expected Rebase");
+        };
+        second.rebase(missing);
+        let (base, operations) = second.save_request();
+        engine.save(&base, operations).unwrap();
+
+        // Lo que guarda el servicio: el contenido y su vector de versiones
+        let (data, _) = String::from_bytes(&engine.data.to_bytes()).unwrap();
+        let (vector, _) = VersionVector::from_bytes(&engine.version_vector.to_bytes()).unwrap();
+        assert_eq!(data, "AMei!");
+        assert_eq!(vector.get(1), 1);
+        assert_eq!(vector.get(2), 1);
+    }
+
+    #[test]
+    fn test_save_of_compacted_history_is_rejected() {
+        let (mut engine, mut first, second) = clients_from_same_base();
+        let (base, operations) = first.save_request();
+        first.rebase(engine.save(&base, operations).unwrap());
+        engine.compact(engine.version);
+
+        let (base, operations) = second.save_request();
+        assert_eq!(
+            engine.save(&base, operations),
+            Err(SaveError::HistoryCompacted)
+        );
+    }
 }
@@ -1,12 +1,13 @@
-use crate::app::index::document::Document;
+use crate::app::index::document::{DocAccess, Document};
 use crate::{
     app::{
         index::{document::DocType, index_instructions::IndexInstructions},
-        microservice::service::Service,
+        microservice::{service::Service, storage::DocumentStorage},
         operation::{
             csv::{SpreadOperation, SpreadSheet},
             generic::ParsableBytes,
             text::TextOperation,
+            version_vector::version_vector_key,
         },
     },
     client_lib::cluster_manager::ClusterManager,
@@ -26,26 +27,33 @@ use crate::app::index::documents::Documents;
 use crate::cluster::types::DEFAULT_BUFFER_SIZE;
 
 /// Key donde se almacenan los documentos creados
-const DOC_KEY: &str = "INDEX";
+pub const DOC_KEY: &str = "INDEX";
 // Nombre del canal donde opera Index
 const INDEX_CHANNEL: &str = "INDEX";
 
+#[derive(Debug, PartialEq)]
 enum IndexError {
     ChannelClosed,
+    DocNotFound,
+    NotOwner,
 }
 
 pub struct Index {
     cluster: ClusterManager,
     docs: Documents,
     service_handles: HashMap<String, JoinHandle<()>>,
+    storage: DocumentStorage,
 }
 
 impl Index {
-    pub fn new(cluster_manager: ClusterManager) -> Self {
+    /// Crea el índice. Los servicios de cada documento guardan una copia
+    /// en `storage`.
+    pub fn new(cluster_manager: ClusterManager, storage: DocumentStorage) -> Self {
         Self {
             cluster: cluster_manager,
             docs: Vec::new(),
             service_handles: HashMap::new(),
+            storage,
         }
     }
 
@@ -62,7 +70,10 @@ impl Index {
             self.ensure_service_running(&document);
         }
 
-        let pub_sub_stream = self.cluster.subscribe(INDEX_CHANNEL).unwrap();
+        let pub_sub_stream = self
+            .cluster
+            .subscribe_with_publisher(INDEX_CHANNEL)
+            .unwrap();
 
         // si esto da error hay que codear cambiar de channel proximament
         let _ = self.run_read_channel(pub_sub_stream);
@@ -107,44 +118,17 @@ impl Index {
 
                     println!("[INDEX] Mensaje recibido: {:?}", contenido);
 
-                    if let RespMessage::SimpleString(bytes) = contenido {
-                        println!("[INDEX] Bytes del mensaje: {:?}", bytes.as_bytes());
+                    if let Some((publisher, bytes)) = publication(contenido) {
                         if let Some((instruction, _)) =
                             IndexInstructions::from_bytes(bytes.as_bytes())
                         {
                             println!("[INDEX] Instrucción parseada: {:?}", instruction);
-                            match instruction {
-                                IndexInstructions::CreateDoc(name, tipo) => {
-                                    println!("[INDEX] Creating document: {}", name);
-                                    self.add_doc(Document::new(name, tipo));
-                                    self.set_docs();
-                                }
-                                IndexInstructions::RemoveDoc(name) => {
-                                    println!("[INDEX] Removing document: {}", name);
-                                    self.remove_doc(name);
-                                    self.set_docs();
-                                }
-                                IndexInstructions::Refresh => {
-                                    println!("[INDEX] Refreshing docs");
-                                    let instruction = IndexInstructions::Docs(self.docs.clone());
-                                    let bytes = instruction.to_bytes();
-
-                                    if let Err(e) = self.cluster.publish(INDEX_CHANNEL, &bytes) {
-                                        eprintln!("[INDEX] Error publishing refresh: {:?}", e);
-                                        // Decide whether to break or continue based on your error handling strategy
-                                    }
-                                }
-                                IndexInstructions::Docs(_) => {
-                                    println!(
-                                        "[INDEX] Instrucción Docs recibida (sin acción en el microservicio)"
-                                    );
-                                }
-                            }
+                            self.handle_instruction(instruction, publisher);
                         } else {
                             println!("[INDEX] Failed to parse instruction from bytes");
                         }
                     } else {
-                        println!("[INDEX] Received non-SimpleString message");
+                        println!("[INDEX] Received non-publication message");
                     }
 
                     let docs_clonados: Vec<Document> = self.docs.clone();
@@ -167,6 +151,56 @@ impl Index {
         Err(IndexError::ChannelClosed)
     }
 
+    /// Atiende una instrucción publicada en el canal del índice.
+    ///
+    /// Crear, borrar o cambiar permisos de un documento se hace en nombre
+    /// de `publisher`, el usuario con el que se autenticó la conexión que
+    /// publicó; el dueño o solicitante que traiga la instrucción se ignora.
+    fn handle_instruction(&mut self, instruction: IndexInstructions, publisher: Option<String>) {
+        match (instruction, publisher) {
+            (IndexInstructions::CreateDoc(name, tipo, _), Some(owner)) => {
+                println!("[INDEX] Creating document: {}", name);
+                self.add_doc(Document::with_owner(name, tipo, owner));
+                self.set_docs();
+            }
+            (IndexInstructions::RemoveDoc(name, _), Some(user)) => {
+                println!("[INDEX] Removing document: {}", name);
+                self.remove_doc(name, &user);
+            }
+            (IndexInstructions::SetAccess(name, _, user, access), Some(requester)) => {
+                println!("[INDEX] Setting access of {} to {}", user, name);
+                self.set_access(&name, &requester, &user, access);
+            }
+            (
+                IndexInstructions::CreateDoc(..)
+                | IndexInstructions::RemoveDoc(..)
+                | IndexInstructions::SetAccess(..),
+                None,
+            ) => {
+                eprintln!("[INDEX] Instrucción sin usuario autenticado, se descarta");
+            }
+            (IndexInstructions::Refresh, _) => {
+                println!("[INDEX] Refreshing docs");
+                let instruction = IndexInstructions::Docs(self.docs.clone());
+                let bytes = instruction.to_bytes();
+
+                if let Err(e) = self.cluster.publish(INDEX_CHANNEL, &bytes) {
+                    eprintln!("[INDEX] Error publishing refresh: {:?}", e);
+                }
+            }
+            (
+                IndexInstructions::Docs(_)
+                | IndexInstructions::DocAdded(_)
+                | IndexInstructions::DocRemoved(_),
+                _,
+            ) => {
+                println!(
+                    "[INDEX] Evento para los clientes recibido (sin acción en el microservicio)"
+                );
+            }
+        }
+    }
+
     fn init_service(&self, doc: &Document) -> JoinHandle<()> {
         let (sx, _) = channel();
         
@@ -190,6 +224,8 @@ impl Index {
                     doc.get_name().to_string(),
                     node_address,
                     sx,
+                    doc.get_type(),
+                    self.storage.clone(),
                 )
                 .unwrap();
                 thread::spawn(move || service.run())
@@ -201,6 +237,8 @@ impl Index {
                     doc.get_name().to_string(),
                     node_address,
                     sx,
+                    doc.get_type(),
+                    self.storage.clone(),
                 )
                 .unwrap();
                 thread::spawn(move || service.run())
@@ -221,26 +259,50 @@ impl Index {
         self.ensure_service_running(&doc_clon);
         self.docs.push(doc);
         self.set_docs();
-        // Publicar la lista actualizada
-        let instruction =
-            crate::app::index::index_instructions::IndexInstructions::Docs(self.docs.clone());
+        // Avisar a los clientes suscriptos
+        let instruction = IndexInstructions::DocAdded(doc_clon);
         let bytes = instruction.to_bytes();
         let _ = self.cluster.publish(INDEX_CHANNEL, &bytes);
     }
 
-    fn remove_doc(&mut self, doc_name: String) {
-        for i in 0..self.docs.len() {
-            if self.docs[i].get_name() == doc_name {
-                self.docs.remove(i);
-                break;
-            }
+    fn remove_doc(&mut self, doc_name: String, user: &str) {
+        if let Err(e) = remove_owned_doc(&mut self.docs, &doc_name, user) {
+            eprintln!(
+                "[INDEX] {} no puede borrar el documento '{}': {:?}",
+                user, doc_name, e
+            );
+            return;
         }
         self.set_docs();
 
-        let instruction = IndexInstructions::Docs(self.docs.clone());
+        let _ = self.cluster.del(&doc_name);
+        let _ = self.cluster.del(&version_vector_key(&doc_name));
+        let instruction = IndexInstructions::DocRemoved(doc_name);
         let bytes = instruction.to_bytes();
         let _ = self.cluster.publish(INDEX_CHANNEL, &bytes);
-        let _ = self.cluster.del(&doc_name);
+    }
+
+    fn set_access(
+        &mut self,
+        doc_name: &str,
+        requester: &str,
+        user: &str,
+        access: Option<DocAccess>,
+    ) {
+        let doc = match set_doc_access(&mut self.docs, doc_name, requester, user, access) {
+            Ok(doc) => doc,
+            Err(e) => {
+                eprintln!(
+                    "[INDEX] {} no puede cambiar los permisos de '{}': {:?}",
+                    requester, doc_name, e
+                );
+                return;
+            }
+        };
+        self.set_docs();
+
+        let instruction = IndexInstructions::DocAdded(doc);
+        let _ = self.cluster.publish(INDEX_CHANNEL, &instruction.to_bytes());
     }
 
     fn set_docs(&mut self) {
@@ -295,3 +357,128 @@ impl Drop for Index {
         self.set_docs();
     }
 }
+
+/// Extrae de un push `message` el mensaje publicado y el usuario que lo
+/// publicó, si el servidor lo marcó.
+///
+/// # Returns
+///
+/// `Option<(Option<String>, String)>` - Publicador y mensaje, o `None` si
+/// no es una publicación
+fn publication(message: RespMessage) -> Option<(Option<String>, String)> {
+    let RespMessage::Push(mut items) = message else {
+        return None;
+    };
+    if items.len() != 3 {
+        return None;
+    }
+    match items.swap_remove(2).split_publisher() {
+        (publisher, RespMessage::SimpleString(payload)) => Some((publisher, payload)),
+        (publisher, RespMessage::BulkString(Some(payload))) => {
+            Some((publisher, String::from_utf8_lossy(&payload).to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Quita `doc_name` de `docs` si `user` es su dueño.
+fn remove_owned_doc(docs: &mut Documents, doc_name: &str, user: &str) -> Result<(), IndexError> {
+    let position = docs
+        .iter()
+        .position(|doc| doc.get_name() == doc_name)
+        .ok_or(IndexError::DocNotFound)?;
+    if !docs[position].can_remove(user) {
+        return Err(IndexError::NotOwner);
+    }
+    docs.remove(position);
+    Ok(())
+}
+
+/// Cambia el permiso de `user` sobre `doc_name`, si `requester` es el dueño.
+///
+/// # Returns
+///
+/// El documento actualizado, para avisar a los clientes
+fn set_doc_access(
+    docs: &mut Documents,
+    doc_name: &str,
+    requester: &str,
+    user: &str,
+    access: Option<DocAccess>,
+) -> Result<Document, IndexError> {
+    let doc = docs
+        .iter_mut()
+        .find(|doc| doc.get_name() == doc_name)
+        .ok_or(IndexError::DocNotFound)?;
+    if !doc.set_access(requester, user, access) {
+        return Err(IndexError::NotOwner);
+    }
+    Ok(doc.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owned_docs() -> Documents {
+        vec![Document::with_owner(
+            "Roster".to_string(),
+            DocType::Text,
+            "Illari".to_string(),
+        )]
+    }
+
+    #[test]
+    fn test_publication_takes_publisher_from_server_attribute() {
+        let payload =
+            RespMessage::with_publisher(RespMessage::SimpleString("Roster".to_string()), "Illari");
+        let message = RespMessage::pubsub_message(INDEX_CHANNEL, payload);
+        assert_eq!(
+            publication(message),
+            Some((Some("Illari".to_string()), "Roster".to_string()))
+        );
+
+        let unmarked =
+            RespMessage::pubsub_message(INDEX_CHANNEL, RespMessage::SimpleString("Roster".into()));
+        assert_eq!(publication(unmarked), Some((None, "Roster".to_string())));
+        assert_eq!(
+            publication(RespMessage::SimpleString("Roster".into())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_non_owner_remove_doc_is_rejected() {
+        let mut docs = owned_docs();
+        set_doc_access(&mut docs, "Roster", "Illari", "Kiriko", Some(DocAccess::Write)).unwrap();
+
+        assert_eq!(
+            remove_owned_doc(&mut docs, "Roster", "Kiriko"),
+            Err(IndexError::NotOwner)
+        );
+        assert_eq!(docs.len(), 1);
+
+        assert_eq!(remove_owned_doc(&mut docs, "Roster", "Illari"), Ok(()));
+        assert!(docs.is_empty());
+    }
+
+    #[test]
+    fn test_remove_missing_doc_is_rejected() {
+        let mut docs = owned_docs();
+        assert_eq!(
+            remove_owned_doc(&mut docs, "Patch", "Illari"),
+            Err(IndexError::DocNotFound)
+        );
+        assert_eq!(docs.len(), 1);
+    }
+
+    #[test]
+    fn test_non_owner_cannot_grant_access() {
+        let mut docs = owned_docs();
+        assert_eq!(
+            set_doc_access(&mut docs, "Roster", "Kiriko", "Kiriko", Some(DocAccess::Write)),
+            Err(IndexError::NotOwner)
+        );
+        assert_eq!(docs[0].access_for("Kiriko"), None);
+    }
+}
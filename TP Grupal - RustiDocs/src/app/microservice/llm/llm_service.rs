@@ -133,10 +133,10 @@ where T: LLMProvider + Clone + Send + 'static {
         request: LLMRequest,
         response_sender: Sender<LLMResponse>,
     ) {
-        let response = provider.proccess_request(&request); 
-         
-        if response_sender.send(response).is_err() {
-            println!("Error al enviar la request al cliente");
-        }
+        provider.stream_request(&request, &mut |response| {
+            if response_sender.send(response).is_err() {
+                println!("Error al enviar la request al cliente");
+            }
+        });
     }
 }
@@ -57,6 +57,7 @@ impl LLMProvider for GeminiProvider {
                     position: request.position,
                     selected_text: request.selected_text.clone(),
                     error: None,
+                    partial: false,
                 };
             }
             Err(e) => {
@@ -68,6 +69,7 @@ impl LLMProvider for GeminiProvider {
                     position: request.position,
                     selected_text: request.selected_text.clone(),
                     error: Some(e.to_string()),
+                    partial: false,
                 };
             }
         };
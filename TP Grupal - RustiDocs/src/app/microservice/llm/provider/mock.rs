@@ -22,6 +22,7 @@ impl LLMProvider for MockProvider{
             position: request.position,
             selected_text: request.selected_text.clone(),
             error: None, 
+            partial: false,
         }
 
         
@@ -6,4 +6,11 @@ pub trait LLMProvider {
         &self,
         request: &LLMRequest,
     ) -> LLMResponse; // El LLMResponse tiene el error integrado y manejado por el propio servicio
+
+    /// Procesa la solicitud entregando la respuesta por partes a `emit`: los
+    /// fragmentos con `partial: true` y el último con `partial: false`. Por
+    /// defecto entrega la respuesta completa en un solo mensaje.
+    fn stream_request(&self, request: &LLMRequest, emit: &mut dyn FnMut(LLMResponse)) {
+        emit(self.proccess_request(request));
+    }
 }   
\ No newline at end of file
@@ -22,4 +22,9 @@ pub struct LLMResponse {
     pub position: Option<usize>,
     pub selected_text: Option<String>, // Texto original seleccionado
     pub error: Option<String>,
+    /// Si es `true`, el mensaje trae un fragmento del texto y siguen más
+    /// mensajes para la misma solicitud. Una respuesta de un solo mensaje
+    /// lo deja en `false`.
+    #[serde(default)]
+    pub partial: bool,
 }
\ No newline at end of file
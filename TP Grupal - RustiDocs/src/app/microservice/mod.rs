@@ -2,4 +2,6 @@ pub mod control;
 pub mod control_instructions;
 pub mod index;
 pub mod llm;
+pub mod participants;
 pub mod service;
+pub mod storage;
@@ -0,0 +1,172 @@
+//! Permisos y confirmaciones de los clientes unidos a un documento.
+//!
+//! El servicio de cada documento registra con qué permiso entró cada
+//! cliente y descarta las operaciones de quienes solo pueden leer, sin
+//! depender de que la interfaz les oculte la edición.
+//!
+//! También lleva la última versión que confirmó cada cliente: la versión
+//! base de una instrucción indica que ya vio todas las operaciones
+//! anteriores. El mínimo entre los que pueden escribir marca hasta dónde se
+//! puede compactar el historial.
+
+use crate::app::index::document::{DocAccess, Document};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+struct Participant {
+    access: DocAccess,
+    acked_version: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Participants {
+    clients: HashMap<u64, Participant>,
+}
+
+impl Participants {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra el ingreso de un cliente con el permiso que `user` tiene
+    /// sobre `doc`. Un documento que no figura en el índice queda abierto.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - Versión del estado que se le envía al cliente
+    ///
+    /// # Returns
+    ///
+    /// El permiso con el que se unió, o `None` si no tiene acceso
+    pub fn join(
+        &mut self,
+        client_id: u64,
+        user: &str,
+        doc: Option<&Document>,
+        version: u64,
+    ) -> Option<DocAccess> {
+        let access = match doc {
+            Some(doc) => doc.access_for(user),
+            None => Some(DocAccess::Write),
+        };
+        match access {
+            Some(access) => self.clients.insert(
+                client_id,
+                Participant {
+                    access,
+                    acked_version: version,
+                },
+            ),
+            None => self.clients.remove(&client_id),
+        };
+        access
+    }
+
+    /// Solo los clientes que se unieron con permiso de escritura pueden
+    /// aplicar operaciones.
+    pub fn can_modify(&self, client_id: u64) -> bool {
+        self.clients
+            .get(&client_id)
+            .is_some_and(|client| client.access == DocAccess::Write)
+    }
+
+    /// Registra que el cliente ya vio todas las operaciones anteriores a
+    /// `version`. Una confirmación atrasada no hace retroceder la anterior.
+    pub fn ack(&mut self, client_id: u64, version: u64) {
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.acked_version = client.acked_version.max(version);
+        }
+    }
+
+    /// Versión que ya confirmaron todos los clientes que pueden escribir.
+    /// Los de solo lectura no mandan instrucciones, así que no necesitan el
+    /// historial para transformarlas.
+    ///
+    /// # Returns
+    ///
+    /// `None` si no hay ningún cliente que pueda escribir
+    pub fn min_acked_version(&self) -> Option<u64> {
+        self.clients
+            .values()
+            .filter(|client| client.access == DocAccess::Write)
+            .map(|client| client.acked_version)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::index::document::DocType;
+
+    fn shared_doc() -> Document {
+        let mut doc =
+            Document::with_owner("Roster".to_string(), DocType::Text, "Illari".to_string());
+        doc.set_access("Illari", "Kiriko", Some(DocAccess::Read));
+        doc
+    }
+
+    #[test]
+    fn test_read_only_grant_joins_but_cannot_modify() {
+        let doc = shared_doc();
+        let mut participants = Participants::new();
+
+        assert_eq!(
+            participants.join(2, "Kiriko", Some(&doc), 0),
+            Some(DocAccess::Read)
+        );
+        assert!(!participants.can_modify(2));
+    }
+
+    #[test]
+    fn test_owner_can_always_modify() {
+        let doc = shared_doc();
+        let mut participants = Participants::new();
+
+        assert_eq!(
+            participants.join(1, "Illari", Some(&doc), 0),
+            Some(DocAccess::Write)
+        );
+        assert!(participants.can_modify(1));
+    }
+
+    #[test]
+    fn test_user_without_access_cannot_join() {
+        let doc = shared_doc();
+        let mut participants = Participants::new();
+
+        assert_eq!(participants.join(3, "Junker Queen", Some(&doc), 0), None);
+        assert!(!participants.can_modify(3));
+        // Un cliente que nunca se unió tampoco modifica
+        assert!(!participants.can_modify(4));
+    }
+
+    #[test]
+    fn test_doc_outside_index_is_open() {
+        let mut participants = Participants::new();
+        assert_eq!(
+            participants.join(5, "Junker Queen", None, 0),
+            Some(DocAccess::Write)
+        );
+        assert!(participants.can_modify(5));
+    }
+
+    #[test]
+    fn test_min_acked_version_waits_for_every_writer() {
+        let doc = shared_doc();
+        let mut participants = Participants::new();
+        participants.join(1, "Illari", Some(&doc), 0);
+        participants.join(2, "Kiriko", Some(&doc), 0);
+        participants.join(5, "Lifeweaver", None, 3);
+        assert_eq!(participants.min_acked_version(), Some(0));
+
+        participants.ack(1, 8);
+        // El otro escritor sigue en la versión con la que se unió
+        assert_eq!(participants.min_acked_version(), Some(3));
+
+        participants.ack(5, 6);
+        participants.ack(5, 4);
+        // El de solo lectura no frena la compactación
+        assert_eq!(participants.min_acked_version(), Some(6));
+    }
+}
@@ -1,13 +1,21 @@
 use crate::{
     app::{
+        index::{
+            document::{DocAccess, DocType, Document},
+            documents::Documents,
+        },
         microservice::{
             control::control_service::ControlService, control_instructions::ControlInstruction,
+            index::DOC_KEY, participants::Participants, storage::DocumentStorage,
         },
         network::{
             header::{InstructionType, Message},
             redis_parser::content_to_message,
         },
-        operation::generic::{Applicable, ParsableBytes, Transformable},
+        operation::{
+            generic::{Applicable, ParsableBytes, Transformable},
+            version_vector::{VersionVector, version_vector_key},
+        },
     },
     client_lib::cluster_manager::ClusterManager,
     network::resp_parser::parse_resp_line,
@@ -19,6 +27,8 @@ use std::{
 };
 
 const VERSION_TO_SAVE: u64 = 1;
+/// Cada cuántas operaciones aplicadas se intenta compactar el historial.
+const OPERATIONS_TO_COMPACT: u64 = 100;
 
 #[derive(Debug)]
 pub struct Service<D, O>
@@ -34,6 +44,13 @@ where
     pub control_service: ControlService<D, O>,
     // TODO: state_sender: Sender<ControlInstruction>,
     pub delta_version: u64,
+    // El contenido guardado no era UTF-8 válido: se sirve en solo lectura
+    // y no se vuelve a guardar, para no pisar los bytes originales.
+    lossy: bool,
+    participants: Participants,
+    operations_since_compaction: u64,
+    doc_type: DocType,
+    storage: DocumentStorage,
 }
 
 impl<D, O> Service<D, O>
@@ -47,14 +64,35 @@ where
         doc_channel: String,
         redis_address: String,
         _: Sender<ControlInstruction>,
+        doc_type: DocType,
+        storage: DocumentStorage,
     ) -> Result<Self, std::io::Error> {
         let mut cluster_data =
             ClusterManager::new(redis_address, "super".to_string(), "1234".to_string()).unwrap(); // TODO: HARDCODEADO
 
         let mut data = D::default();
+        let mut lossy = false;
 
-        if let Ok(data_get) = cluster_data.get(&doc_hash) {
-            (data, _) = D::from_bytes(&data_get).unwrap_or((data, 0));
+        // Si el cluster perdió el documento, se parte de la copia en disco
+        let saved = cluster_data.get(&doc_hash).ok().or_else(|| {
+            storage
+                .load_document(&doc_name, &doc_type)
+                .inspect_err(|e| {
+                    eprintln!("[SERVICE] Error leyendo '{}' de disco: {}", doc_name, e)
+                })
+                .ok()
+                .flatten()
+        });
+        if let Some(data_get) = saved {
+            (data, _, lossy) = D::from_bytes_lossy(&data_get).unwrap_or((data, 0, false));
+        }
+        let mut control_service = ControlService::new(data);
+        if let Some((version_vector, _)) = cluster_data
+            .get(&version_vector_key(&doc_name))
+            .ok()
+            .and_then(|bytes| VersionVector::from_bytes(&bytes))
+        {
+            control_service.version_vector = version_vector;
         }
 
         let sub_channel = cluster_data.subscribe(&doc_channel).unwrap();
@@ -65,8 +103,13 @@ where
             doc_channel,
             redis_stream: sub_channel,
             cluster_data,
-            control_service: ControlService::new(data),
+            control_service,
             delta_version: 0,
+            lossy,
+            participants: Participants::new(),
+            operations_since_compaction: 0,
+            doc_type,
+            storage,
             //state_sender,
         })
         /*
@@ -119,14 +162,33 @@ where
                                                 continue;
                                             }
                                             InstructionType::Request => {
+                                                let client_id = instruction.operation_id.client_id;
+                                                if !self.participants.can_modify(client_id) {
+                                                    println!(
+                                                        "[SERVICE] Cliente {} sin permiso de escritura, descarto {:?}",
+                                                        client_id, instruction
+                                                    );
+                                                    continue;
+                                                }
                                                 println!(
                                                     "Entró en InstructionType::Request, aplicando instrucción recibida {:?}",
                                                     instruction
                                                 );
-                                                let instruction = self
+                                                self.participants
+                                                    .ack(client_id, instruction.base_version);
+                                                let instruction = match self
                                                     .control_service
                                                     .apply_operation(instruction)
-                                                    .unwrap();
+                                                {
+                                                    Ok(instruction) => instruction,
+                                                    Err(e) => {
+                                                        eprintln!(
+                                                            "[SERVICE] Instrucción de cliente {} descartada: {:?}",
+                                                            client_id, e
+                                                        );
+                                                        continue;
+                                                    }
+                                                };
                                                 let response: Message<D, O> =
                                                     Message::create_response(instruction);
                                                 println!("Creo la instruccion y trato de enviarla");
@@ -145,18 +207,42 @@ where
                                                         self.delta_version
                                                     );
                                                 }
+                                                self.operations_since_compaction += 1;
+                                                if self.operations_since_compaction
+                                                    >= OPERATIONS_TO_COMPACT
+                                                {
+                                                    self.operations_since_compaction = 0;
+                                                    self.compact_history();
+                                                }
                                             }
                                         }
                                     }
-                                    Message::Init(client_id) => {
+                                    Message::Init(client_id, user) => {
                                         println!(
-                                            "[SERVICE] Recibido Init de cliente {}",
-                                            client_id
+                                            "[SERVICE] Recibido Init de cliente {} ({})",
+                                            client_id, user
                                         );
+                                        let doc = self.indexed_document();
                                         let data = self.control_service.data.clone();
                                         let version = self.control_service.version;
-                                        let state: Message<D, O> =
-                                            Message::State(data, version, client_id);
+                                        let access = self.participants.join(
+                                            client_id,
+                                            &user,
+                                            doc.as_ref(),
+                                            version,
+                                        );
+                                        let state: Message<D, O> = match access {
+                                            None => Message::Denied(client_id),
+                                            Some(_) if self.lossy => {
+                                                Message::LossyState(data, version, client_id)
+                                            }
+                                            Some(DocAccess::Read) => {
+                                                Message::ReadOnlyState(data, version, client_id)
+                                            }
+                                            Some(DocAccess::Write) => {
+                                                Message::State(data, version, client_id)
+                                            }
+                                        };
                                         let pub_message = state.message_to_pub(&self.doc_channel);
                                         let _ = self.redis_stream.write_all(&pub_message);
                                         println!("[SERVICE] Enviado State a cliente {}", client_id);
@@ -177,9 +263,66 @@ where
         
     
 
+    /// Busca este documento en el índice guardado en el cluster, para
+    /// conocer su dueño y permisos actuales.
+    fn indexed_document(&mut self) -> Option<Document> {
+        let bytes = self.cluster_data.get(DOC_KEY).ok()?;
+        let (docs, _) = Documents::from_bytes(&bytes)?;
+        docs.into_iter().find(|doc| doc.get_name() == self.doc_name)
+    }
+
+    /// Descarta el historial que ya confirmaron todos los clientes que
+    /// pueden escribir y guarda el documento, que pasa a ser el punto de
+    /// partida de los que se unan después.
+    fn compact_history(&mut self) {
+        let Some(acked_version) = self.participants.min_acked_version() else {
+            return;
+        };
+        let discarded = self.control_service.compact(acked_version);
+        if discarded > 0 {
+            println!(
+                "[SERVICE] Historial compactado hasta la versión {} ({} operaciones)",
+                acked_version, discarded
+            );
+            self.save_data();
+        }
+    }
+
+    /// Guarda el documento junto con su vector de versiones, y el documento
+    /// también en disco. Si el vector guardado cuenta operaciones que este
+    /// servicio no aplicó, otro guardó cambios que se perderían: no se pisa.
     fn save_data(&mut self) {
+        if self.lossy {
+            return;
+        }
+        let vector_key = version_vector_key(&self.doc_name);
+        let stored = self
+            .cluster_data
+            .get(&vector_key)
+            .ok()
+            .and_then(|bytes| VersionVector::from_bytes(&bytes));
+        if let Some((stored, _)) = stored
+            .filter(|(stored, _)| !self.control_service.version_vector.dominates(stored))
+        {
+            eprintln!(
+                "[SERVICE] Guardado de '{}' rechazado: el vector guardado {:?} tiene cambios que este servicio no aplicó",
+                self.doc_name, stored
+            );
+            return;
+        }
         let bytes = self.control_service.data.to_bytes();
         let _ = self.cluster_data.set(&self.doc_name, &bytes);
+        let vector = self.control_service.version_vector.to_bytes();
+        let _ = self.cluster_data.set(&vector_key, &vector);
+        if let Err(e) = self
+            .storage
+            .save_document(&self.doc_name, &self.doc_type, &bytes)
+        {
+            eprintln!(
+                "[SERVICE] Error guardando '{}' en disco: {}",
+                self.doc_name, e
+            );
+        }
     }
 }
 
@@ -0,0 +1,160 @@
+//! Copia en disco del contenido de cada documento.
+//!
+//! El servicio de cada documento guarda su contenido en el cluster; además
+//! lo escribe en un archivo bajo el directorio del microservicio, para que
+//! el documento sobreviva aunque el cluster se reinicie sin sus datos. Al
+//! levantar un servicio, si el cluster no tiene el documento se usa el
+//! archivo.
+
+use crate::app::index::document::DocType;
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+/// Directorio por defecto donde se guardan los documentos.
+pub const DEFAULT_DOCUMENTS_DIR: &str = "./documents";
+
+#[derive(Debug, Clone)]
+pub struct DocumentStorage {
+    dir: PathBuf,
+}
+
+impl DocumentStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Guarda el contenido de un documento, reemplazando el anterior. Se
+    /// escribe en un archivo temporal que después se renombra, para no
+    /// dejar un documento a medio escribir si el proceso se corta.
+    pub fn save_document(&self, name: &str, doc_type: &DocType, content: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(name, doc_type);
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(temp_path, path)
+    }
+
+    /// Lee el último contenido guardado de un documento.
+    ///
+    /// # Returns
+    ///
+    /// `None` si el documento nunca se guardó
+    pub fn load_document(&self, name: &str, doc_type: &DocType) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(name, doc_type)) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Archivo de un documento. El nombre lo elige el usuario: todo lo que
+    /// no sea alfanumérico, `-` o `_` se escapa como `%XX` para que no pueda
+    /// salirse del directorio ni chocar con otro nombre.
+    fn path(&self, name: &str, doc_type: &DocType) -> PathBuf {
+        let mut file_name = String::with_capacity(name.len());
+        for byte in name.bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+                file_name.push(byte as char);
+            } else {
+                file_name.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        let extension = match doc_type {
+            DocType::Text => "text",
+            DocType::SpreadSheet => "sheet",
+        };
+        self.dir.join(format!("{}.{}", file_name, extension))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::operation::{csv::SpreadSheet, generic::ParsableBytes};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_text_document_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let storage = DocumentStorage::new(dir.path());
+        let content = "Notas de Kiriko: ¡el zorro 狐 vuelve!"
+            .to_string()
+            .to_bytes();
+
+        storage
+            .save_document("notas", &DocType::Text, &content)
+            .unwrap();
+
+        // Otra instancia sobre el mismo directorio, como tras reiniciar
+        let reloaded = DocumentStorage::new(dir.path())
+            .load_document("notas", &DocType::Text)
+            .unwrap();
+        assert_eq!(reloaded, Some(content.clone()));
+        let (text, _) = String::from_bytes(&reloaded.unwrap()).unwrap();
+        assert_eq!(text, "Notas de Kiriko: ¡el zorro 狐 vuelve!");
+    }
+
+    #[test]
+    fn test_save_replaces_previous_content() {
+        let dir = TempDir::new().unwrap();
+        let storage = DocumentStorage::new(dir.path().join("docs"));
+
+        storage
+            .save_document("roster", &DocType::Text, b"Ana")
+            .unwrap();
+        storage
+            .save_document("roster", &DocType::Text, b"Ana, Kiriko")
+            .unwrap();
+
+        assert_eq!(
+            storage.load_document("roster", &DocType::Text).unwrap(),
+            Some(b"Ana, Kiriko".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_documents_are_keyed_by_name_and_type() {
+        let dir = TempDir::new().unwrap();
+        let storage = DocumentStorage::new(dir.path());
+        let mut sheet = SpreadSheet::default();
+        sheet.data[0][0] = "Sojourn".to_string();
+
+        storage
+            .save_document("equipo", &DocType::Text, b"Sojourn")
+            .unwrap();
+        storage
+            .save_document("equipo", &DocType::SpreadSheet, &sheet.to_bytes())
+            .unwrap();
+
+        assert_eq!(
+            storage.load_document("equipo", &DocType::Text).unwrap(),
+            Some(b"Sojourn".to_vec())
+        );
+        let bytes = storage
+            .load_document("equipo", &DocType::SpreadSheet)
+            .unwrap()
+            .unwrap();
+        assert_eq!(SpreadSheet::from_bytes(&bytes).unwrap().0.data, sheet.data);
+        assert_eq!(storage.load_document("otro", &DocType::Text).unwrap(), None);
+    }
+
+    #[test]
+    fn test_document_name_cannot_escape_the_directory() {
+        let dir = TempDir::new().unwrap();
+        let storage = DocumentStorage::new(dir.path().join("docs"));
+
+        storage
+            .save_document("../fuera", &DocType::Text, b"Sombra")
+            .unwrap();
+
+        assert!(!dir.path().join("fuera.text").exists());
+        assert_eq!(
+            storage.load_document("../fuera", &DocType::Text).unwrap(),
+            Some(b"Sombra".to_vec())
+        );
+    }
+}
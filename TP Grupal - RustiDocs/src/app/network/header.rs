@@ -11,6 +11,9 @@ const INSTRUCTION: u8 = 0;
 const STATE: u8 = 1;
 const INIT: u8 = 2;
 const RESYNC: u8 = 3;
+const LOSSY_STATE: u8 = 4;
+const READ_ONLY_STATE: u8 = 5;
+const DENIED: u8 = 6;
 
 #[derive(Debug, PartialEq)]
 pub enum Message<D, O>
@@ -19,8 +22,17 @@ where
     D: ParsableBytes,
 {
     Instruction(InstructionType, Instruction<O>),
-    Init(u64),
+    /// ID del cliente y usuario con el que se une al documento
+    Init(u64, String),
     State(D, u64, u64),
+    /// Estado de un documento cuyo contenido no era UTF-8 válido y se
+    /// decodificó con reemplazos. El cliente lo abre en modo solo lectura.
+    LossyState(D, u64, u64),
+    /// Estado enviado a un participante con permiso de lectura. El cliente
+    /// lo abre en modo solo lectura.
+    ReadOnlyState(D, u64, u64),
+    /// El usuario del cliente no tiene acceso al documento.
+    Denied(u64),
     Resync,
 }
 
@@ -46,18 +58,31 @@ where
                 argument.extend_from_slice(&instruction_bytes);
                 create_pub_string(channel_name.to_string(), &argument)
             }
-            Message::State(state, version, client_id) => {
+            Message::State(state, version, client_id)
+            | Message::LossyState(state, version, client_id)
+            | Message::ReadOnlyState(state, version, client_id) => {
                 let mut argument: Vec<u8> = Vec::new();
-                argument.push(STATE);
+                argument.push(match self {
+                    Message::LossyState(..) => LOSSY_STATE,
+                    Message::ReadOnlyState(..) => READ_ONLY_STATE,
+                    _ => STATE,
+                });
                 argument.extend_from_slice(&client_id.to_le_bytes()); // u64 como 8 bytes little endian
                 argument.extend_from_slice(&version.to_le_bytes()); // u64 como 8 bytes little endian
                 argument.extend_from_slice(&state.to_bytes()); // bytes del state
                 create_pub_string(channel_name.to_string(), &argument)
             }
-            Message::Init(client_id) => {
+            Message::Init(client_id, user) => {
                 let mut argument: Vec<u8> = Vec::new();
                 argument.push(INIT);
                 argument.extend_from_slice(&client_id.to_le_bytes());
+                argument.extend_from_slice(user.as_bytes());
+                create_pub_string(channel_name.to_string(), &argument)
+            }
+            Message::Denied(client_id) => {
+                let mut argument: Vec<u8> = Vec::new();
+                argument.push(DENIED);
+                argument.extend_from_slice(&client_id.to_le_bytes());
                 create_pub_string(channel_name.to_string(), &argument)
             }
             Message::Resync => {
@@ -87,7 +112,7 @@ where
                 let (instruction, _) = Instruction::<O>::from_bytes(instruction_bytes)?;
                 Some(Message::Instruction(instruction_type, instruction))
             }
-            Some(&STATE) => {
+            Some(&tag @ (STATE | LOSSY_STATE | READ_ONLY_STATE)) => {
                 // STATE | client_id (8 bytes) | version (8 bytes) | state_bytes
                 if resp.len() < 1 + 8 + 8 {
                     return None;
@@ -95,16 +120,32 @@ where
                 let client_id = u64::from_le_bytes(resp[1..9].try_into().ok()?);
                 let version = u64::from_le_bytes(resp[9..17].try_into().ok()?);
                 let state_bytes = &resp[17..];
-                let (state, _) = D::from_bytes(state_bytes)?;
-                Some(Message::State(state, version, client_id))
+                // Un estado con texto inválido se muestra igual, en solo lectura
+                let (state, _, lossy) = D::from_bytes_lossy(state_bytes)?;
+                if lossy || tag == LOSSY_STATE {
+                    Some(Message::LossyState(state, version, client_id))
+                } else if tag == READ_ONLY_STATE {
+                    Some(Message::ReadOnlyState(state, version, client_id))
+                } else {
+                    Some(Message::State(state, version, client_id))
+                }
             }
             Some(&INIT) => {
-                // INIT | client_id (8 bytes)
+                // INIT | client_id (8 bytes) | usuario
                 if resp.len() < 1 + 8 {
                     return None;
                 }
                 let client_id = u64::from_le_bytes(resp[1..9].try_into().ok()?);
-                Some(Message::Init(client_id))
+                let user = String::from_utf8(resp[9..].to_vec()).ok()?;
+                Some(Message::Init(client_id, user))
+            }
+            Some(&DENIED) => {
+                // DENIED | client_id (8 bytes)
+                if resp.len() != 1 + 8 {
+                    return None;
+                }
+                let client_id = u64::from_le_bytes(resp[1..9].try_into().ok()?);
+                Some(Message::Denied(client_id))
             }
             Some(&RESYNC) => {
                 // Solo el byte RESYNC
@@ -119,7 +160,7 @@ where
 }
 
 // Funciones auxiliares para convertir entre bytes y String hexadecimal
-fn bytes_to_hex_string(bytes: &[u8]) -> String {
+pub(crate) fn bytes_to_hex_string(bytes: &[u8]) -> String {
     let mut hex_string = String::with_capacity(bytes.len() * 2);
     for byte in bytes {
         hex_string.push_str(&format!("{:02x}", byte));
@@ -127,7 +168,7 @@ fn bytes_to_hex_string(bytes: &[u8]) -> String {
     hex_string
 }
 
-fn hex_string_to_bytes(hex_string: &str) -> Option<Vec<u8>> {
+pub(crate) fn hex_string_to_bytes(hex_string: &str) -> Option<Vec<u8>> {
     // Verificar que la longitud sea par
     if hex_string.len() % 2 != 0 {
         return None;
@@ -154,7 +195,7 @@ fn hex_string_to_bytes(hex_string: &str) -> Option<Vec<u8>> {
     Some(bytes)
 }
 
-fn create_pub_string(channel_name: String, argument_bytes: &[u8]) -> Vec<u8> {
+pub(crate) fn create_pub_string(channel_name: String, argument_bytes: &[u8]) -> Vec<u8> {
     let mut resp = Vec::new();
     resp.extend_from_slice(b"*3\r\n");
     resp.extend_from_slice(b"$7\r\nPUBLISH\r\n");
@@ -235,4 +276,52 @@ mod tests {
 
         assert_eq!(mes, Message::create_request(instruction));
     }
+
+    /// Decodifica un PUBLISH como lo haría un cliente suscripto al canal.
+    fn decode_published(publish: Vec<u8>) -> Option<Message<String, TextOperation>> {
+        let parsed = parse_resp_line(&mut Cursor::new(publish)).unwrap();
+        let instruction = crate::command::Instruction::try_from(parsed).unwrap();
+        Message::resp_to_message(&instruction.arguments[1])
+    }
+
+    #[test]
+    fn test_non_utf8_state_is_decoded_as_lossy_state() {
+        let mut argument = vec![STATE];
+        argument.extend(7u64.to_le_bytes());
+        argument.extend(3u64.to_le_bytes());
+        argument.extend(4u64.to_le_bytes());
+        argument.extend(b"Mei\xFF");
+
+        let message = decode_published(create_pub_string("Doc".to_string(), &argument));
+        assert_eq!(
+            message,
+            Some(Message::LossyState("Mei\u{FFFD}".to_string(), 3, 7))
+        );
+    }
+
+    #[test]
+    fn test_state_and_lossy_state_round_trip() {
+        let lossy: Message<String, TextOperation> =
+            Message::LossyState("Mei\u{FFFD}".to_string(), 3, 7);
+        assert_eq!(decode_published(lossy.message_to_pub("Doc")), Some(lossy));
+
+        let valid: Message<String, TextOperation> = Message::State("Mei".to_string(), 3, 7);
+        assert_eq!(decode_published(valid.message_to_pub("Doc")), Some(valid));
+    }
+
+    #[test]
+    fn test_access_messages_round_trip() {
+        let init: Message<String, TextOperation> = Message::Init(7, "Kiriko".to_string());
+        assert_eq!(decode_published(init.message_to_pub("Doc")), Some(init));
+
+        let read_only: Message<String, TextOperation> =
+            Message::ReadOnlyState("Mei".to_string(), 3, 7);
+        assert_eq!(
+            decode_published(read_only.message_to_pub("Doc")),
+            Some(read_only)
+        );
+
+        let denied: Message<String, TextOperation> = Message::Denied(7);
+        assert_eq!(decode_published(denied.message_to_pub("Doc")), Some(denied));
+    }
 }
@@ -1,2 +1,3 @@
 pub mod header;
+pub mod presence;
 pub mod redis_parser;
@@ -0,0 +1,114 @@
+//! Presencia de los colaboradores de un documento.
+//!
+//! Cada cliente publica la posición de su cursor en un canal propio del
+//! documento, separado del canal de operaciones: las posiciones no pasan
+//! por el servicio del documento ni por la transformación, solo por el
+//! pub/sub del cluster.
+
+use crate::{
+    app::{
+        network::header::{bytes_to_hex_string, create_pub_string, hex_string_to_bytes},
+        operation::generic::ParsableBytes,
+    },
+    network::RespMessage,
+};
+
+/// Posición del cursor de un cliente en un documento, en caracteres.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresenceMessage {
+    pub client_id: u64,
+    pub document: String,
+    pub position: usize,
+}
+
+/// Canal donde se publica la presencia de los clientes de `document`.
+pub fn presence_channel(document: &str) -> String {
+    format!("{}:presence", document)
+}
+
+impl PresenceMessage {
+    /// Comando `PUBLISH` que envía este mensaje al canal de presencia de su
+    /// documento.
+    pub fn message_to_pub(&self) -> Vec<u8> {
+        create_pub_string(presence_channel(&self.document), &self.to_bytes())
+    }
+
+    /// Mensaje tal como se publica, en hexadecimal como el resto de los
+    /// mensajes de los documentos.
+    pub fn payload(&self) -> RespMessage {
+        RespMessage::SimpleString(bytes_to_hex_string(&self.to_bytes()))
+    }
+
+    /// Interpreta un mensaje recibido por el canal de presencia.
+    ///
+    /// # Returns
+    ///
+    /// `None` si no es un mensaje de presencia, como las confirmaciones de
+    /// la suscripción
+    pub fn from_resp(content: &RespMessage) -> Option<Self> {
+        let hex = match content {
+            RespMessage::SimpleString(string) => string.clone(),
+            RespMessage::BulkString(Some(bytes)) => String::from_utf8(bytes.clone()).ok()?,
+            _ => return None,
+        };
+        let (message, _) = Self::from_bytes(&hex_string_to_bytes(&hex)?)?;
+        Some(message)
+    }
+}
+
+impl ParsableBytes for PresenceMessage {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(self.client_id.to_le_bytes());
+        bytes.extend((self.position as u64).to_le_bytes());
+        bytes.extend(self.document.to_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<(Self, usize)> {
+        let client_id = u64::from_le_bytes(bytes.get(0..8)?.try_into().ok()?);
+        let position = u64::from_le_bytes(bytes.get(8..16)?.try_into().ok()?) as usize;
+        let (document, size) = String::from_bytes(&bytes[16..])?;
+        Some((
+            Self {
+                client_id,
+                document,
+                position,
+            },
+            16 + size,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presence_message_round_trip() {
+        let message = PresenceMessage {
+            client_id: 76,
+            document: "Plan de Cassidy".to_string(),
+            position: 12,
+        };
+
+        assert_eq!(
+            PresenceMessage::from_resp(&message.payload()),
+            Some(message.clone())
+        );
+        let bulk = match message.payload() {
+            RespMessage::SimpleString(hex) => RespMessage::BulkString(Some(hex.into_bytes())),
+            other => other,
+        };
+        assert_eq!(PresenceMessage::from_resp(&bulk), Some(message));
+    }
+
+    #[test]
+    fn test_other_messages_are_not_presence() {
+        assert_eq!(PresenceMessage::from_resp(&RespMessage::Integer(1)), None);
+        assert_eq!(
+            PresenceMessage::from_resp(&RespMessage::SimpleString("OK".to_string())),
+            None
+        );
+    }
+}
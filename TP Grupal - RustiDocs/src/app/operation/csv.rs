@@ -1,4 +1,5 @@
 use crate::app::operation::{
+    formula::Evaluator,
     generic::{Applicable, ParsableBytes, Transformable},
     text::TextOperation,
 };
@@ -61,9 +62,26 @@ impl SpreadSheet {
         self.data[row][column].remove(pos);
         Ok(())
     }
+
+    /// Contenido de la planilla con las fórmulas (celdas que empiezan con
+    /// `=`) reemplazadas por su resultado. Las celdas con error muestran el
+    /// error, como `#CYCLE!` para una referencia circular. La planilla no
+    /// se modifica.
+    pub fn evaluated(&self) -> Vec<Vec<String>> {
+        let mut evaluator = Evaluator::new(&self.data);
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(row, cells)| {
+                (0..cells.len())
+                    .map(|column| evaluator.display(row, column))
+                    .collect()
+            })
+            .collect()
+    }
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct SpreadOperation {
     pub row: usize,
     pub column: usize,
@@ -88,12 +106,16 @@ impl Applicable<SpreadSheet> for SpreadOperation {
 }
 
 impl Transformable for SpreadOperation {
+    /// Operaciones sobre celdas distintas conmutan y no se modifican; sobre
+    /// la misma celda se resuelve con la transformación de texto.
     fn transform(&self, other: &Self) -> Self {
-        let operation = self.operation.transform(&other.operation);
+        if (self.row, self.column) != (other.row, other.column) {
+            return self.clone();
+        }
         Self {
             row: self.row,
             column: self.column,
-            operation: operation,
+            operation: self.operation.transform(&other.operation),
         }
     }
 }
@@ -166,3 +188,99 @@ impl ParsableBytes for SpreadSheet {
         Some((Self { data }, offset))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(row: usize, column: usize, position: usize, character: char) -> SpreadOperation {
+        SpreadOperation {
+            row,
+            column,
+            operation: TextOperation::Insert {
+                position,
+                character,
+            },
+        }
+    }
+
+    /// Aplica `first` y luego `second` transformada contra `first`.
+    fn apply_both(
+        sheet: &SpreadSheet,
+        first: &SpreadOperation,
+        second: &SpreadOperation,
+    ) -> SpreadSheet {
+        let mut sheet = sheet.clone();
+        first.apply(&mut sheet);
+        second.transform(first).apply(&mut sheet);
+        sheet
+    }
+
+    #[test]
+    fn test_transform_same_cell_concurrent_inserts() {
+        let mut sheet = SpreadSheet::default();
+        sheet.data[1][2] = "Mercy".to_string();
+        let left = insert(1, 2, 0, 'A');
+        let right = insert(1, 2, 5, 'Z');
+
+        assert_eq!(right.transform(&left), insert(1, 2, 6, 'Z'));
+        assert_eq!(left.transform(&right), left);
+
+        let left_first = apply_both(&sheet, &left, &right);
+        let right_first = apply_both(&sheet, &right, &left);
+        assert_eq!(left_first.data[1][2], "AMercyZ");
+        assert_eq!(left_first.data, right_first.data);
+    }
+
+    #[test]
+    fn test_transform_same_cell_delete_and_insert() {
+        let mut sheet = SpreadSheet::default();
+        sheet.data[0][0] = "Genji".to_string();
+        let delete = SpreadOperation {
+            row: 0,
+            column: 0,
+            operation: TextOperation::Delete { position: 0 },
+        };
+        let append = insert(0, 0, 5, '!');
+
+        let delete_first = apply_both(&sheet, &delete, &append);
+        let append_first = apply_both(&sheet, &append, &delete);
+        assert_eq!(delete_first.data[0][0], "enji!");
+        assert_eq!(delete_first.data, append_first.data);
+    }
+
+    #[test]
+    fn test_transform_different_cells_are_independent() {
+        let mut sheet = SpreadSheet::default();
+        sheet.data[0][0] = "Ana".to_string();
+        sheet.data[0][1] = "Baptiste".to_string();
+        let same_row = insert(0, 1, 0, 'X');
+        let other = insert(0, 0, 0, 'Y');
+        let same_column = insert(3, 0, 0, 'Z');
+
+        assert_eq!(same_row.transform(&other), same_row);
+        assert_eq!(other.transform(&same_row), other);
+        assert_eq!(same_column.transform(&other), same_column);
+
+        let sheet = apply_both(&sheet, &same_row, &other);
+        assert_eq!(sheet.data[0][0], "YAna");
+        assert_eq!(sheet.data[0][1], "XBaptiste");
+    }
+
+    #[test]
+    fn test_evaluated_keeps_formulas_intact() {
+        let mut sheet = SpreadSheet::default();
+        sheet.data[0][0] = "Reinhardt".to_string();
+        sheet.data[0][1] = "550".to_string();
+        sheet.data[1][1] = "=B1*2".to_string();
+        sheet.data[2][1] = "=B3".to_string();
+
+        let evaluated = sheet.evaluated();
+
+        assert_eq!(evaluated[0][0], "Reinhardt");
+        assert_eq!(evaluated[1][1], "1100");
+        assert_eq!(evaluated[2][1], "#CYCLE!");
+        assert_eq!(evaluated[4][9], "");
+        assert_eq!(sheet.data[1][1], "=B1*2");
+    }
+}
@@ -0,0 +1,438 @@
+//! Evaluación de fórmulas de la planilla.
+//!
+//! Una celda que empieza con `=` es una fórmula: números, referencias a
+//! celdas (`A1`, `b2`), `+ - * /`, paréntesis y `SUM` sobre rangos
+//! (`A1:A3`) o valores sueltos separados por `,` o `;`. Las celdas vacías
+//! y las que quedan fuera de la planilla valen 0. Los errores se muestran
+//! en la celda en lugar del valor.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Máxima cantidad de celdas que se evalúan encadenadas por referencias.
+const MAX_REFERENCE_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormulaError {
+    /// La fórmula no se pudo interpretar.
+    Parse,
+    /// Se referenció una celda con texto que no es un número.
+    Value,
+    DivisionByZero,
+    /// La celda depende de sí misma.
+    Cycle,
+    /// La referencia no entra en una posición válida o encadena más de
+    /// `MAX_REFERENCE_DEPTH` celdas.
+    Reference,
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormulaError::Parse => write!(f, "#ERROR!"),
+            FormulaError::Value => write!(f, "#VALUE!"),
+            FormulaError::DivisionByZero => write!(f, "#DIV/0!"),
+            FormulaError::Cycle => write!(f, "#CYCLE!"),
+            FormulaError::Reference => write!(f, "#REF!"),
+        }
+    }
+}
+
+type CellResult = Result<f64, FormulaError>;
+
+/// Evalúa las celdas de una planilla, recordando los valores ya calculados
+/// y las celdas en evaluación para detectar referencias circulares.
+pub struct Evaluator<'a> {
+    data: &'a [Vec<String>],
+    values: HashMap<(usize, usize), CellResult>,
+    evaluating: HashSet<(usize, usize)>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(data: &'a [Vec<String>]) -> Self {
+        Self {
+            data,
+            values: HashMap::new(),
+            evaluating: HashSet::new(),
+        }
+    }
+
+    /// Texto a mostrar en una celda: el resultado si es una fórmula, o el
+    /// contenido tal cual si no.
+    pub fn display(&mut self, row: usize, column: usize) -> String {
+        let cell = self.cell(row, column);
+        if !cell.starts_with('=') {
+            return cell.to_string();
+        }
+        match self.value(row, column) {
+            Ok(value) => format_number(value),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// Valor numérico de una celda. Las celdas fuera de la planilla están
+    /// vacías.
+    pub fn value(&mut self, row: usize, column: usize) -> CellResult {
+        if let Some(value) = self.values.get(&(row, column)) {
+            return value.clone();
+        }
+        if self.evaluating.len() >= MAX_REFERENCE_DEPTH {
+            return Err(FormulaError::Reference);
+        }
+        if !self.evaluating.insert((row, column)) {
+            return Err(FormulaError::Cycle);
+        }
+
+        let cell = self.cell(row, column);
+        let value = match cell.strip_prefix('=') {
+            Some(formula) => Parser::new(formula, self).parse(),
+            None if cell.trim().is_empty() => Ok(0.0),
+            None => cell.trim().parse().map_err(|_| FormulaError::Value),
+        };
+
+        self.evaluating.remove(&(row, column));
+        self.values.insert((row, column), value.clone());
+        value
+    }
+
+    fn cell(&self, row: usize, column: usize) -> &'a str {
+        self.data
+            .get(row)
+            .and_then(|cells| cells.get(column))
+            .map_or("", String::as_str)
+    }
+
+    /// Cantidad de filas y de columnas de la planilla.
+    fn size(&self) -> (usize, usize) {
+        let columns = self.data.iter().map(Vec::len).max().unwrap_or(0);
+        (self.data.len(), columns)
+    }
+}
+
+/// Muestra los enteros sin decimales.
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Convierte una referencia como `B12` en (fila, columna), empezando de 0.
+///
+/// # Returns
+///
+/// `Ok(None)` si `reference` no es una referencia, o `FormulaError::Reference`
+/// si la fila o la columna no entran en un `usize`
+fn parse_reference(reference: &str) -> Result<Option<(usize, usize)>, FormulaError> {
+    let Some(digits) = reference.find(|c: char| c.is_ascii_digit()) else {
+        return Ok(None);
+    };
+    let (letters, number) = reference.split_at(digits);
+    if letters.is_empty()
+        || !letters.chars().all(|c| c.is_ascii_alphabetic())
+        || !number.chars().all(|c| c.is_ascii_digit())
+    {
+        return Ok(None);
+    }
+    let column = letters
+        .to_ascii_uppercase()
+        .bytes()
+        .try_fold(0usize, |column, letter| {
+            column
+                .checked_mul(26)?
+                .checked_add((letter - b'A') as usize + 1)
+        })
+        .ok_or(FormulaError::Reference)?;
+    let row: usize = number.parse().map_err(|_| FormulaError::Reference)?;
+    Ok(row.checked_sub(1).map(|row| (row, column - 1)))
+}
+
+/// Parser descendente recursivo que evalúa mientras lee.
+struct Parser<'p, 'a> {
+    chars: Vec<char>,
+    position: usize,
+    evaluator: &'p mut Evaluator<'a>,
+}
+
+impl<'p, 'a> Parser<'p, 'a> {
+    fn new(formula: &str, evaluator: &'p mut Evaluator<'a>) -> Self {
+        Self {
+            chars: formula.chars().collect(),
+            position: 0,
+            evaluator,
+        }
+    }
+
+    fn parse(mut self) -> CellResult {
+        let value = self.expression()?;
+        self.skip_spaces();
+        if self.position < self.chars.len() {
+            return Err(FormulaError::Parse);
+        }
+        Ok(value)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.chars.get(self.position) == Some(&' ') {
+            self.position += 1;
+        }
+    }
+
+    /// Consume `expected` si es el próximo carácter.
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_spaces();
+        if self.chars.get(self.position) == Some(&expected) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expression(&mut self) -> CellResult {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> CellResult {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat('*') {
+                value *= self.factor()?;
+            } else if self.eat('/') {
+                let divisor = self.factor()?;
+                if divisor == 0.0 {
+                    return Err(FormulaError::DivisionByZero);
+                }
+                value /= divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn factor(&mut self) -> CellResult {
+        if self.eat('-') {
+            return Ok(-self.factor()?);
+        }
+        if self.eat('(') {
+            let value = self.expression()?;
+            return if self.eat(')') {
+                Ok(value)
+            } else {
+                Err(FormulaError::Parse)
+            };
+        }
+
+        let word = self.word();
+        if word.is_empty() {
+            return Err(FormulaError::Parse);
+        }
+        if word.eq_ignore_ascii_case("SUM") {
+            return self.sum();
+        }
+        if let Ok(number) = word.parse() {
+            return Ok(number);
+        }
+        let (row, column) = parse_reference(&word)?.ok_or(FormulaError::Parse)?;
+        self.evaluator.value(row, column)
+    }
+
+    /// Lee un número, una referencia o un nombre de función.
+    fn word(&mut self) -> String {
+        self.skip_spaces();
+        let start = self.position;
+        while self
+            .chars
+            .get(self.position)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '.')
+        {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    /// Argumentos de `SUM`: rangos o expresiones separados por `,` o `;`.
+    fn sum(&mut self) -> CellResult {
+        if !self.eat('(') {
+            return Err(FormulaError::Parse);
+        }
+        let mut total = 0.0;
+        if self.eat(')') {
+            return Ok(total);
+        }
+        loop {
+            total += self.range_or_expression()?;
+            if self.eat(')') {
+                return Ok(total);
+            }
+            if !self.eat(',') && !self.eat(';') {
+                return Err(FormulaError::Parse);
+            }
+        }
+    }
+
+    /// Suma de un rango como `A1:B3`, o el valor de una expresión.
+    ///
+    /// El rango se recorta a la planilla: las celdas que quedan afuera
+    /// valen 0 y no se recorren.
+    fn range_or_expression(&mut self) -> CellResult {
+        let start = self.position;
+        let word = self.word();
+        let first = parse_reference(&word)?;
+        let Some((first_row, first_column)) = first.filter(|_| self.eat(':')) else {
+            self.position = start;
+            return self.expression();
+        };
+        let (last_row, last_column) = parse_reference(&self.word())?.ok_or(FormulaError::Parse)?;
+
+        let (rows, columns) = self.evaluator.size();
+        let row_end = rows.min(first_row.max(last_row).saturating_add(1));
+        let column_end = columns.min(first_column.max(last_column).saturating_add(1));
+        let mut total = 0.0;
+        for row in first_row.min(last_row)..row_end {
+            for column in first_column.min(last_column)..column_end {
+                total += self.evaluator.value(row, column)?;
+            }
+        }
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_reference() {
+        assert_eq!(parse_reference("A1"), Ok(Some((0, 0))));
+        assert_eq!(parse_reference("b12"), Ok(Some((11, 1))));
+        assert_eq!(parse_reference("AA3"), Ok(Some((2, 26))));
+        assert_eq!(parse_reference("A0"), Ok(None));
+        assert_eq!(parse_reference("12"), Ok(None));
+        assert_eq!(parse_reference("A1B"), Ok(None));
+    }
+
+    #[test]
+    fn test_oversized_reference_is_reported() {
+        let columns = "Z".repeat(20);
+        assert_eq!(
+            parse_reference(&format!("{}1", columns)),
+            Err(FormulaError::Reference)
+        );
+        assert_eq!(
+            parse_reference("A99999999999999999999999"),
+            Err(FormulaError::Reference)
+        );
+
+        let formula = format!("=SUM({}1:A2)", columns);
+        let data = sheet(&[&["=A99999999999999999999999+1", &formula]]);
+        let mut evaluator = Evaluator::new(&data);
+        assert_eq!(evaluator.display(0, 0), "#REF!");
+        assert_eq!(evaluator.display(0, 1), "#REF!");
+    }
+
+    #[test]
+    fn test_range_is_clamped_to_the_sheet() {
+        let data = sheet(&[&["10", "=SUM(A1:A1048576)"], &["20"]]);
+        let mut evaluator = Evaluator::new(&data);
+
+        assert_eq!(evaluator.display(0, 1), "30");
+        // Un rango entero fuera de la planilla suma 0
+        let data = sheet(&[&["=SUM(C5:ZZ9999999)"]]);
+        assert_eq!(Evaluator::new(&data).display(0, 0), "0");
+    }
+
+    #[test]
+    fn test_long_reference_chain_is_reported() {
+        let chain = |length: usize| -> Vec<Vec<String>> {
+            let mut rows = vec![vec!["1".to_string()]];
+            rows.extend((1..length).map(|row| vec![format!("=A{}+1", row)]));
+            rows
+        };
+
+        let data = chain(MAX_REFERENCE_DEPTH);
+        let mut evaluator = Evaluator::new(&data);
+        assert_eq!(
+            evaluator.display(MAX_REFERENCE_DEPTH - 1, 0),
+            MAX_REFERENCE_DEPTH.to_string()
+        );
+
+        let data = chain(MAX_REFERENCE_DEPTH + 1);
+        let mut evaluator = Evaluator::new(&data);
+        assert_eq!(evaluator.display(MAX_REFERENCE_DEPTH, 0), "#REF!");
+    }
+
+    #[test]
+    fn test_arithmetic_with_references() {
+        let data = sheet(&[&["2", "3", "=A1+B1*2"], &["=(A1+B1)/2", "=-A1", "=C1-1.5"]]);
+        let mut evaluator = Evaluator::new(&data);
+
+        assert_eq!(evaluator.display(0, 2), "8");
+        assert_eq!(evaluator.display(1, 0), "2.5");
+        assert_eq!(evaluator.display(1, 1), "-2");
+        assert_eq!(evaluator.display(1, 2), "6.5");
+    }
+
+    #[test]
+    fn test_sum_over_range() {
+        let data = sheet(&[
+            &["Tracer", "10"],
+            &["Genji", "20"],
+            &["Ana", "=B1+B2"],
+            &["Total", "=SUM(B1:B3)"],
+            &["", "=sum(B1:B2; 5, B3)"],
+        ]);
+        let mut evaluator = Evaluator::new(&data);
+
+        assert_eq!(evaluator.display(3, 1), "60");
+        assert_eq!(evaluator.display(4, 1), "65");
+    }
+
+    #[test]
+    fn test_empty_cell_is_zero() {
+        let data = sheet(&[&["", "=A1+4", "=Z99*2"]]);
+        let mut evaluator = Evaluator::new(&data);
+
+        assert_eq!(evaluator.display(0, 1), "4");
+        assert_eq!(evaluator.display(0, 2), "0");
+    }
+
+    #[test]
+    fn test_circular_reference_is_reported() {
+        let data = sheet(&[&["=B1", "=A1+1", "=A1", "=C1*0"], &["=A2"]]);
+        let mut evaluator = Evaluator::new(&data);
+
+        assert_eq!(evaluator.value(0, 0), Err(FormulaError::Cycle));
+        assert_eq!(evaluator.display(0, 1), "#CYCLE!");
+        assert_eq!(evaluator.display(0, 2), "#CYCLE!");
+        assert_eq!(evaluator.display(0, 3), "#CYCLE!");
+        assert_eq!(evaluator.display(1, 0), "#CYCLE!");
+    }
+
+    #[test]
+    fn test_errors_are_shown_in_the_cell() {
+        let data = sheet(&[&["Mercy", "=A1+1", "=1/0", "=1+", "=SUM(1", "texto"]]);
+        let mut evaluator = Evaluator::new(&data);
+
+        assert_eq!(evaluator.display(0, 1), "#VALUE!");
+        assert_eq!(evaluator.display(0, 2), "#DIV/0!");
+        assert_eq!(evaluator.display(0, 3), "#ERROR!");
+        assert_eq!(evaluator.display(0, 4), "#ERROR!");
+        assert_eq!(evaluator.display(0, 5), "texto");
+    }
+}
@@ -88,10 +88,24 @@ pub trait Applicable<D> {
     fn apply(&self, data: &mut D);
 }
 
+/// Trait para las operaciones que se pueden deshacer.
+pub trait Invertible<D> {
+    /// Devuelve la operación que deshace a esta. `data` es el dato antes de
+    /// aplicarla, porque algunas inversas necesitan lo que se borra.
+    fn inverse(&self, data: &D) -> Self;
+}
+
 // ESTE TRAIT HAY QUE MOVERLO A UNA JERARQUIA MAS GENERAL PORQUE ES USADA POR CLIENT_LIB
 pub trait ParsableBytes: Sized {
     fn to_bytes(&self) -> Vec<u8>;
     fn from_bytes(bytes: &[u8]) -> Option<(Self, usize)>;
+
+    /// Igual que `from_bytes`, pero los tipos que guardan texto pueden
+    /// reemplazar los bytes que no son UTF-8 válido en lugar de fallar.
+    /// El booleano indica si hubo reemplazos.
+    fn from_bytes_lossy(bytes: &[u8]) -> Option<(Self, usize, bool)> {
+        Self::from_bytes(bytes).map(|(value, size)| (value, size, false))
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,5 @@
 pub mod csv;
+pub mod formula;
 pub mod generic;
 pub mod text;
+pub mod version_vector;
@@ -1,5 +1,5 @@
 
-use crate::app::operation::generic::{Applicable, ParsableBytes, Transformable};
+use crate::app::operation::generic::{Applicable, Invertible, ParsableBytes, Transformable};
 
 const INSERT: u8 = 0;
 const DELETE: u8 = 1;
@@ -30,8 +30,27 @@ impl ParsableBytes for String {
         let string = String::from_utf8(bytes[8..8 + len].to_vec()).ok()?;
         Some((string, 8 + len))
     }
+
+    fn from_bytes_lossy(bytes: &[u8]) -> Option<(Self, usize, bool)> {
+        if bytes.len() < 8 {
+            return None;
+        }
+        let len = u64::from_le_bytes(bytes[0..8].try_into().ok()?) as usize;
+        if bytes.len() < 8 + len {
+            return None;
+        }
+        let string = String::from_utf8_lossy(&bytes[8..8 + len]);
+        let lossy = matches!(string, std::borrow::Cow::Owned(_));
+        Some((string.into_owned(), 8 + len, lossy))
+    }
 }
 
+/// Motivo por el que no se puede editar un documento cuyo contenido no es
+/// UTF-8 válido: se muestra con reemplazos y solo en modo lectura, para no
+/// pisar los bytes originales.
+pub const ERR_NON_UTF8_DOCUMENT: &str =
+    "El documento contiene bytes que no son UTF-8 válido: se abrió en modo solo lectura";
+
 /// Operaciones de texto que pueden ser aplicadas a un documento de texto.
 /// Estas operaciones incluyen insertar un caracter en una posicion especifica
 /// o eliminar un caracter de una posicion especifica.
@@ -107,6 +126,48 @@ impl Applicable<String> for TextOperation {
     }
 }
 
+impl Invertible<String> for TextOperation {
+    fn inverse(&self, data: &String) -> Self {
+        // Las posiciones fuera del texto se aplican al final
+        let len = data.chars().count();
+        match self {
+            TextOperation::Insert { position, .. } => TextOperation::Delete {
+                position: (*position).min(len),
+            },
+            TextOperation::Delete { position } => match data.chars().nth(*position) {
+                Some(character) => TextOperation::Insert {
+                    position: *position,
+                    character,
+                },
+                None => TextOperation::NoOperation,
+            },
+            TextOperation::DeleteAll => TextOperation::InsertText {
+                position: 0,
+                text: data.clone(),
+            },
+            TextOperation::DeleteRange { start, end } => {
+                let start = (*start).min(len);
+                let end = (*end).min(len);
+                if start >= end {
+                    return TextOperation::NoOperation;
+                }
+                TextOperation::InsertText {
+                    position: start,
+                    text: data.chars().skip(start).take(end - start).collect(),
+                }
+            }
+            TextOperation::InsertText { position, text } => {
+                let start = (*position).min(len);
+                TextOperation::DeleteRange {
+                    start,
+                    end: start + text.chars().count(),
+                }
+            }
+            TextOperation::NoOperation => TextOperation::NoOperation,
+        }
+    }
+}
+
 impl ParsableBytes for TextOperation {
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
@@ -299,6 +360,39 @@ impl TextOperation {
     }
 }
 
+/// Calcula las operaciones que transforman `old` en `new`, sobre índices de
+/// caracteres (no de bytes). Se recorta el prefijo y el sufijo común; lo
+/// que queda en el medio se borra de atrás hacia adelante, para que los
+/// índices no se invaliden, y después se inserta de principio a fin.
+pub fn char_diff(old: &str, new: &str) -> Vec<TextOperation> {
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+
+    let prefix_len = old_chars
+        .iter()
+        .zip(&new_chars)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix_len = old_chars[prefix_len..]
+        .iter()
+        .rev()
+        .zip(new_chars[prefix_len..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_mid_end = old_chars.len() - suffix_len;
+    let new_mid_end = new_chars.len() - suffix_len;
+
+    let deletes = (prefix_len..old_mid_end)
+        .rev()
+        .map(|position| TextOperation::Delete { position });
+    let inserts = (prefix_len..new_mid_end).map(|position| TextOperation::Insert {
+        position,
+        character: new_chars[position],
+    });
+    deletes.chain(inserts).collect()
+}
+
 impl Transformable for TextOperation {
     fn transform(&self, other: &Self) -> Self {
         match (self, other) {
@@ -719,6 +813,23 @@ mod tests {
         bytes.extend(b"abc");
         assert!(String::from_bytes(&bytes).is_none());
     }
+
+    #[test]
+    fn test_string_non_utf8_is_decoded_lossy() {
+        let mut bytes = 6u64.to_le_bytes().to_vec();
+        bytes.extend(b"Ana\xFF\xFEx");
+        assert!(String::from_bytes(&bytes).is_none());
+
+        let (parsed, used, lossy) = String::from_bytes_lossy(&bytes).unwrap();
+        assert_eq!(parsed, "Ana\u{FFFD}\u{FFFD}x");
+        assert_eq!(used, bytes.len());
+        assert!(lossy);
+
+        let bytes = String::from("Baptiste").to_bytes();
+        let (parsed, _, lossy) = String::from_bytes_lossy(&bytes).unwrap();
+        assert_eq!(parsed, "Baptiste");
+        assert!(!lossy);
+    }
     
     #[test]
     fn test_delete_range(){
@@ -727,4 +838,75 @@ mod tests {
         op.apply(&mut doc);
         assert_eq!(doc, "Hello, !");
     }
+
+    /// Aplica las operaciones de `char_diff` sobre `old`.
+    fn apply_diff(old: &str, new: &str) -> String {
+        let mut text = old.to_string();
+        for operation in char_diff(old, new) {
+            operation.apply(&mut text);
+        }
+        text
+    }
+
+    #[test]
+    fn test_char_diff_equal_strings() {
+        assert!(char_diff("Reinhardt", "Reinhardt").is_empty());
+    }
+
+    #[test]
+    fn test_char_diff_insert_multibyte_in_ascii() {
+        let ops = char_diff("Lucio", "Lúcio");
+        assert_eq!(
+            ops,
+            vec![
+                TextOperation::Delete { position: 1 },
+                TextOperation::Insert {
+                    position: 1,
+                    character: 'ú'
+                },
+            ]
+        );
+
+        let ops = char_diff("Torbjrn", "Torbjérn");
+        assert_eq!(
+            ops,
+            vec![TextOperation::Insert {
+                position: 5,
+                character: 'é'
+            }]
+        );
+        assert_eq!(apply_diff("Torbjrn", "Torbjérn"), "Torbjérn");
+    }
+
+    #[test]
+    fn test_char_diff_positions_after_multibyte_prefix() {
+        // El cambio está después de caracteres de 3 bytes: las posiciones
+        // tienen que contar caracteres
+        let ops = char_diff("世界 Zarya", "世界 Zarya!");
+        assert_eq!(
+            ops,
+            vec![TextOperation::Insert {
+                position: 8,
+                character: '!'
+            }]
+        );
+        assert_eq!(apply_diff("世界 Zarya", "世界 Zarya!"), "世界 Zarya!");
+    }
+
+    #[test]
+    fn test_char_diff_delete_across_multibyte_boundary() {
+        let ops = char_diff("Hola 世界 Mei", "Hola Mei");
+        assert_eq!(
+            ops,
+            vec![
+                TextOperation::Delete { position: 7 },
+                TextOperation::Delete { position: 6 },
+                TextOperation::Delete { position: 5 },
+            ]
+        );
+        assert_eq!(apply_diff("Hola 世界 Mei", "Hola Mei"), "Hola Mei");
+        assert_eq!(apply_diff("Hola 世界 Mei", "Hola 界 Mei"), "Hola 界 Mei");
+        assert_eq!(apply_diff("世界", ""), "");
+        assert_eq!(apply_diff("", "世界"), "世界");
+    }
 }
@@ -0,0 +1,120 @@
+use crate::app::operation::generic::ParsableBytes;
+use std::collections::BTreeMap;
+
+/// Sufijo de la key donde se guarda el vector de versiones de un documento,
+/// al lado de su contenido.
+const VERSION_VECTOR_SUFFIX: &str = ":version";
+
+/// Key del vector de versiones del documento `doc_name`.
+pub fn version_vector_key(doc_name: &str) -> String {
+    format!("{}{}", doc_name, VERSION_VECTOR_SUFFIX)
+}
+
+/// Cantidad de operaciones aplicadas de cada cliente.
+///
+/// Un guardado se hace sobre la base del vector que conocía el cliente: si
+/// el servidor ya aplicó operaciones que esa base no cuenta, el guardado es
+/// viejo y el cliente tiene que rebasar sus operaciones pendientes antes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionVector {
+    counts: BTreeMap<u64, u64>,
+}
+
+impl VersionVector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cuenta una operación más aplicada de `client_id`.
+    pub fn record(&mut self, client_id: u64) {
+        *self.counts.entry(client_id).or_insert(0) += 1;
+    }
+
+    /// Operaciones aplicadas de `client_id`.
+    pub fn get(&self, client_id: u64) -> u64 {
+        self.counts.get(&client_id).copied().unwrap_or(0)
+    }
+
+    /// Indica si este vector ya incluye todas las operaciones de `other`.
+    pub fn dominates(&self, other: &VersionVector) -> bool {
+        other
+            .counts
+            .iter()
+            .all(|(client_id, count)| self.get(*client_id) >= *count)
+    }
+
+    /// Operaciones de cada cliente que cuenta este vector y no `base`.
+    pub fn missing_from(&self, base: &VersionVector) -> BTreeMap<u64, u64> {
+        self.counts
+            .iter()
+            .map(|(client_id, count)| (*client_id, count.saturating_sub(base.get(*client_id))))
+            .filter(|(_, missing)| *missing > 0)
+            .collect()
+    }
+}
+
+impl ParsableBytes for VersionVector {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(&(self.counts.len() as u32).to_le_bytes());
+        for (client_id, count) in &self.counts {
+            bytes.extend(&client_id.to_le_bytes());
+            bytes.extend(&count.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<(Self, usize)> {
+        if bytes.len() < 4 {
+            return None;
+        }
+        let len = u32::from_le_bytes(bytes[0..4].try_into().ok()?) as usize;
+        let mut offset = 4;
+        let mut counts = BTreeMap::new();
+        for _ in 0..len {
+            if bytes.len() < offset + 16 {
+                return None;
+            }
+            let client_id = u64::from_le_bytes(bytes[offset..offset + 8].try_into().ok()?);
+            let count = u64::from_le_bytes(bytes[offset + 8..offset + 16].try_into().ok()?);
+            counts.insert(client_id, count);
+            offset += 16;
+        }
+        Some((Self { counts }, offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dominates_and_missing_operations() {
+        let mut base = VersionVector::new();
+        base.record(1);
+
+        let mut server = base.clone();
+        server.record(2);
+        server.record(2);
+
+        assert!(server.dominates(&base));
+        assert!(!base.dominates(&server));
+        assert_eq!(server.missing_from(&base), BTreeMap::from([(2, 2)]));
+        assert!(base.missing_from(&server).is_empty());
+    }
+
+    #[test]
+    fn test_version_vector_round_trip() {
+        let mut vector = VersionVector::new();
+        vector.record(7);
+        vector.record(3);
+        vector.record(7);
+
+        let bytes = vector.to_bytes();
+        let (parsed, used) = VersionVector::from_bytes(&bytes).unwrap();
+        assert_eq!(used, bytes.len());
+        assert_eq!(parsed, vector);
+        assert_eq!(parsed.get(7), 2);
+        assert!(VersionVector::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+    }
+}
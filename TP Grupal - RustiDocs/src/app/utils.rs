@@ -2,6 +2,7 @@ use crate::cluster::types::DEFAULT_BUFFER_SIZE;
 use crate::network::RespMessage;
 use crate::network::resp_parser::parse_resp_line;
 use crate::parser::response_parser::format_resp_message;
+use crate::security::tls_lite::{TlsClientConfig, TlsClientStream};
 use std::io::{BufReader, Error, ErrorKind, Read, Write};
 use std::net::TcpStream;
 use std::sync::mpsc::{Receiver, Sender, channel};
@@ -22,45 +23,66 @@ pub fn connect_to_cluster(
     let stream = TcpStream::connect(address);
     thread::sleep(Duration::from_millis(150)); // Espero que la conexión se inicie
 
-    if let Ok(mut stream) = stream {
-        // Autentico
-        let auth_cmd = format!("AUTH {} {}", username, password);
-        let cmd = format_resp_message(auth_cmd.as_str()).unwrap();
-        stream.write_all(cmd.as_bytes())?;
-        stream.flush()?;
-
-        // Reviso
-        let mut buffer = [0; DEFAULT_BUFFER_SIZE];
-        match stream.read(&mut buffer) {
-            Ok(n) => {
-                let mut reader = BufReader::new(&buffer[..n]);
-                let res = parse_resp_line(&mut reader).unwrap();
-                match res {
-                    RespMessage::SimpleString(msg) => {
-                        println!("\x1b[32m[AUTH] Autenticado\x1b[0m");
-                        if msg == "Usuario logeado correctamente - WRITE" {
-                            Ok((stream, true))
-                        } else {
-                            Ok((stream, false))
-                        }
-                    }
-                    _ => {
-                        println!("\x1b[31m[AUTH] Usuario y/o contraseña incorrectos\x1b[0m");
-                        Err(Error::new(ErrorKind::Other, "Error al autenticar"))
-                    }
+    match stream {
+        Ok(stream) => authenticate(stream, username, password),
+        Err(e) => {
+            println!("Error al conectar: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Igual que `connect_to_cluster`, para nodos con `tls yes`: negocia
+/// TLS-lite antes de autenticarse, y todo lo que sigue viaja cifrado.
+pub fn connect_to_cluster_tls(
+    address: String,
+    username: String,
+    password: String,
+) -> Result<(TlsClientStream, bool), Error> {
+    let stream = TcpStream::connect(&address)?;
+    let host = address.split(':').next().unwrap_or_default().to_string();
+    let stream = TlsClientStream::new(stream, TlsClientConfig::new(host)).map_err(|e| {
+        println!("\x1b[31m[TLS] Error en el handshake: {}\x1b[0m", e);
+        Error::other(e.to_string())
+    })?;
+    authenticate(stream, username, password)
+}
+
+/// Envía `AUTH` por la conexión y devuelve la conexión junto con un
+/// booleano, `true` si el usuario es de escritura.
+fn authenticate<S: Read + Write>(
+    mut stream: S,
+    username: String,
+    password: String,
+) -> Result<(S, bool), Error> {
+    // Autentico
+    let auth_cmd = format!("AUTH {} {}", username, password);
+    let cmd = format_resp_message(auth_cmd.as_str()).unwrap();
+    stream.write_all(cmd.as_bytes())?;
+    stream.flush()?;
+
+    // Reviso
+    let mut buffer = [0; DEFAULT_BUFFER_SIZE];
+    match stream.read(&mut buffer) {
+        Ok(n) => {
+            let mut reader = BufReader::new(&buffer[..n]);
+            let res = parse_resp_line(&mut reader).unwrap();
+            match res {
+                RespMessage::SimpleString(msg) => {
+                    println!("\x1b[32m[AUTH] Autenticado\x1b[0m");
+                    let is_writer = msg == "Usuario logeado correctamente - WRITE";
+                    Ok((stream, is_writer))
+                }
+                _ => {
+                    println!("\x1b[31m[AUTH] Usuario y/o contraseña incorrectos\x1b[0m");
+                    Err(Error::other("Error al autenticar"))
                 }
             }
-            Err(_) => {
-                println!("[AUTH] Error al recibir respuesta");
-                Err(Error::new(
-                    ErrorKind::Other,
-                    "Error al recibir respuesta de logueo",
-                ))
-            }
         }
-    } else {
-        println!("Error al conectar");
-        Err(Error::new(ErrorKind::Other, "Error al conectar"))
+        Err(_) => {
+            println!("[AUTH] Error al recibir respuesta");
+            Err(Error::other("Error al recibir respuesta de logueo"))
+        }
     }
 }
 
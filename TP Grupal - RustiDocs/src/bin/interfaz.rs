@@ -9,7 +9,7 @@ use eframe::egui::{self, Visuals};
 use rustidocs::app::client::client_data::Client;
 use rustidocs::app::client::client_init::ClientThread;
 use rustidocs::app::operation::generic::{Instruction};
-use rustidocs::app::operation::text::TextOperation;
+use rustidocs::app::operation::text::{TextOperation, char_diff};
 use std::fs;
 use std::io::{Error, ErrorKind};
 use std::net::TcpStream;
@@ -19,7 +19,8 @@ use std::time::Duration;
 use std::{env, path::PathBuf};
 // Al inicio del archivo
 use rustidocs::app::client::client_index::ClientIndex;
-use rustidocs::app::client::llm_client::LLMClient;
+use rustidocs::app::client::client_presence::ClientPresence;
+use rustidocs::app::client::llm_client::{LLMClient, LLMError};
 use rustidocs::app::index::document::DocType;
 use rustidocs::app::operation::csv::{SpreadOperation, SpreadSheet};
 
@@ -48,14 +49,15 @@ fn detect_docker_environment() -> (String, String) {
 /// Verifica si el cluster en la dirección dada es un cluster Docker
 fn is_docker_cluster(addr: &str) -> bool {
     use std::net::TcpStream;
-    use std::io::Write;
+    use std::io::{Read, Write};
     
     if let Ok(socket_addr) = addr.parse::<std::net::SocketAddr>() {
         if let Ok(mut stream) = TcpStream::connect(socket_addr) {
-            // Enviar un comando AUTH para verificar que es nuestro cluster
-            let auth_cmd = b"AUTH super 1234\r\n";
-            if stream.write_all(auth_cmd).is_ok() {
-                return true;
+            // PING no necesita sesión: si responde PONG es nuestro cluster
+            let _ = stream.set_read_timeout(Some(Duration::from_millis(500)));
+            if stream.write_all(b"*1\r\n$4\r\nPING\r\n").is_ok() {
+                let mut reply = [0; 7];
+                return stream.read_exact(&mut reply).is_ok() && &reply == b"+PONG\r\n";
             }
         }
     }
@@ -134,6 +136,9 @@ struct RedisApp {
     //show_creatio_button: bool,
     text_data: Option<Client<String, TextOperation>>,
     text_remote: Option<Receiver<Instruction<TextOperation>>>,
+    // Cursores de los otros colaboradores del texto
+    text_presence: Option<ClientPresence>,
+    last_cursor: Option<usize>,
     // Para CSV - cambiar a SpreadSheet y SpreadOperation
     csv_data: Option<Client<SpreadSheet, SpreadOperation>>,
     csv_remote: Option<Receiver<Instruction<SpreadOperation>>>,
@@ -154,6 +159,8 @@ struct RedisApp {
     selected_text: String,
     show_text_selection: bool,
     ai_response: Option<String>,
+    // Fragmentos de la respuesta de AI que todavía se están generando
+    ai_stream: Option<Receiver<Result<String, LLMError>>>,
     show_ai_response_dialog: bool,
     //process_ai_request_for_selected: bool,
 }
@@ -193,6 +200,8 @@ impl RedisApp {
             //show_creatio_button: false,
             text_data: None,
             text_remote: None,
+            text_presence: None,
+            last_cursor: None,
             csv_data: None,
             csv_remote: None,
             available_documents: None,
@@ -211,6 +220,7 @@ impl RedisApp {
             selected_text: String::new(),
             show_text_selection: false,
             ai_response: None,
+            ai_stream: None,
             show_ai_response_dialog: false,
             //process_ai_request_for_selected: false,
         }
@@ -225,72 +235,31 @@ impl RedisApp {
             let stored_content = text_data.local_data.clone();
 
             if current_content != stored_content {
-                // Convertimos las cadenas a vectores de caracteres para trabajar con índices de caracteres
-                let current_chars: Vec<char> = current_content.chars().collect();
-                let stored_chars: Vec<char> = stored_content.chars().collect();
-
-                // Creamos un mapeo de índices de caracteres a índices de bytes en la cadena original
-                let mut byte_indices = Vec::new();
-                let mut char_pos = 0;
-                for (byte_pos, _) in stored_content.char_indices() {
-                    while char_pos < byte_pos {
-                        byte_indices.push(byte_pos);
-                        char_pos += 1;
-                    }
-                    byte_indices.push(byte_pos);
-                    char_pos += 1;
-                }
-                byte_indices.push(stored_content.len()); // Añadir el final
-
-                // 1. Encontrar el prefijo común
-                let mut prefix_len = 0;
-                while prefix_len < current_chars.len().min(stored_chars.len())
-                    && current_chars[prefix_len] == stored_chars[prefix_len]
-                {
-                    prefix_len += 1;
-                }
-
-                // 2. Encontrar el sufijo común (trabajando desde el final)
-                let mut suffix_len = 0;
-                while suffix_len
-                    < (current_chars.len() - prefix_len).min(stored_chars.len() - prefix_len)
-                    && current_chars[current_chars.len() - 1 - suffix_len]
-                        == stored_chars[stored_chars.len() - 1 - suffix_len]
-                {
-                    suffix_len += 1;
-                }
-
-                // 3. Determinar qué ha cambiado
-                let old_mid_start = prefix_len;
-                let old_mid_end = stored_chars.len() - suffix_len;
-                let new_mid_start = prefix_len;
-                let new_mid_end = current_chars.len() - suffix_len;
-
-                // 4. Primero eliminar caracteres viejos (de atrás hacia adelante)
-                // para evitar que los índices se invaliden
-                for i in (old_mid_start..old_mid_end).rev() {
-                    // Usamos el mismo índice i, que es un índice de carácter, no de bytes
-                    let delete_op = TextOperation::Delete { position: i };
-                    text_data.apply_local_operation(delete_op);
+                if let Some(reason) = text_data.read_only_reason() {
+                    // Documento no editable: se descarta el cambio local
                     self.file_notifications
                         .lock()
                         .unwrap()
-                        .push(format!("Eliminación en posición {}", i));
+                        .push(format!("⚠️ {}", reason));
+                    self.text_editor_content = stored_content;
+                    return;
                 }
-
-                // 5. Luego insertar los nuevos caracteres (de principio a fin)
-                for (j, &ch) in current_chars[new_mid_start..new_mid_end].iter().enumerate() {
-                    let pos = old_mid_start + j; // índice de carácter, no de bytes
-                    let insert_op = TextOperation::Insert {
-                        position: pos,
-                        character: ch,
+                // Las posiciones de char_diff son índices de caracteres, no de bytes
+                let operations = char_diff(&stored_content, &current_content);
+                for operation in &operations {
+                    let notification = match operation {
+                        TextOperation::Delete { position } => {
+                            format!("Eliminación en posición {}", position)
+                        }
+                        TextOperation::Insert {
+                            position,
+                            character,
+                        } => format!("Inserción de '{}' en posición {}", character, position),
+                        _ => continue,
                     };
-                    text_data.apply_local_operation(insert_op);
-                    self.file_notifications
-                        .lock()
-                        .unwrap()
-                        .push(format!("Inserción de '{}' en posición {}", ch, pos));
+                    self.file_notifications.lock().unwrap().push(notification);
                 }
+                text_data.apply_undoable_operations(operations);
 
                 // Finalmente, actualizar el contenido del editor
                 self.text_editor_content = text_data.local_data.clone();
@@ -313,23 +282,49 @@ impl RedisApp {
     fn create_text_client_data(&mut self, mut stream: TcpStream) {
         if let Ok((client_data, remote_receiver)) = ClientThread::init::<String, TextOperation>(
             self.client_id,
+            &self.username,
             &mut stream,
             self.remote_filename.to_string(),
         ) {
             println!("ok!");
+            if let Some(reason) = client_data.read_only_reason() {
+                self.file_notifications
+                    .lock()
+                    .unwrap()
+                    .push(format!("⚠️ {}", reason));
+            }
             self.text_editor_content = client_data.local_data.clone();
             self.text_data = Some(client_data);
             self.text_remote = Some(remote_receiver);
+            self.last_cursor = None;
+            // La presencia va por una conexión propia, suscripta a su canal
+            self.text_presence = connect_to_cluster(
+                self.remote_address.clone(),
+                self.username.clone(),
+                self.password.clone(),
+            )
+            .and_then(|(stream, _)| {
+                ClientPresence::init(self.client_id, &self.remote_filename, stream)
+            })
+            .inspect_err(|e| eprintln!("No se pudo iniciar la presencia: {}", e))
+            .ok();
         }
     }
 
     fn create_csv_client_data(&mut self, mut stream: TcpStream) {
         if let Ok((client_data, remote_receiver)) = ClientThread::init::<SpreadSheet, SpreadOperation>(
             self.client_id,
+            &self.username,
             &mut stream,
             self.remote_filename.to_string(),
         ) {
             println!("ok!");
+            if let Some(reason) = client_data.read_only_reason() {
+                self.file_notifications
+                    .lock()
+                    .unwrap()
+                    .push(format!("⚠️ {}", reason));
+            }
             self.spreadsheet_data = client_data.local_data.clone();
             self.csv_data = Some(client_data);
             self.csv_remote = Some(remote_receiver);
@@ -354,31 +349,23 @@ impl RedisApp {
         }
 
         if let Some(client) = &mut self.llm_client {
-            let result = if self.ai_position == 0 {
-                // Reemplazar todo el documento
-                client.request_ai_replace(
-                    self.remote_filename.clone(),
-                    self.ai_prompt.clone(),
-                    self.client_id,
-                )
-            } else {
-                // Insertar en posición específica
-                client.request_ai_insert(
-                    self.remote_filename.clone(),
-                    self.ai_prompt.clone(),
-                    self.ai_position,
-                    self.client_id,
-                )
-            };
-
-            match result {
-                Ok(response) => {
-                    self.ai_response = Some(response);
+            // La posición 0 reemplaza todo el documento
+            let position = (self.ai_position != 0).then_some(self.ai_position);
+            match client.request_ai_stream(
+                self.remote_filename.clone(),
+                self.ai_prompt.clone(),
+                position,
+                None,
+                self.client_id,
+            ) {
+                Ok(receiver) => {
+                    self.ai_stream = Some(receiver);
+                    self.ai_response = Some(String::new());
                     self.show_ai_response_dialog = true;
                     self.ai_error_message.clear();
                 }
                 Err(e) => {
-                    self.ai_error_message = format!("Error de AI: {}", e);
+                    self.ai_error_message = e.to_string();
                 }
             }
         }
@@ -407,80 +394,70 @@ impl RedisApp {
         }
 
         if let Some(client) = &mut self.llm_client {
-            match client.request_ai_replace_selected(
+            match client.request_ai_stream(
                 self.remote_filename.clone(),
                 self.ai_prompt.clone(),
-                self.selected_text.clone(),
+                None,
+                Some(self.selected_text.clone()),
                 self.client_id,
             ) {
-                Ok(response) => {
-                    self.ai_response = Some(response);
+                Ok(receiver) => {
+                    self.ai_stream = Some(receiver);
+                    self.ai_response = Some(String::new());
                     self.show_ai_response_dialog = true;
                     self.ai_error_message.clear();
                 }
                 Err(e) => {
-                    self.ai_error_message = format!("Error de AI: {}", e);
+                    self.ai_error_message = e.to_string();
+                }
+            }
+        }
+    }
+
+    /// Suma a la respuesta de AI los fragmentos que llegaron desde el último
+    /// cuadro. La respuesta está completa cuando se cierra el canal.
+    fn poll_ai_stream(&mut self) {
+        let Some(receiver) = &self.ai_stream else {
+            return;
+        };
+        loop {
+            match receiver.try_recv() {
+                Ok(Ok(chunk)) => self
+                    .ai_response
+                    .get_or_insert_with(String::new)
+                    .push_str(&chunk),
+                Ok(Err(e)) => {
+                    self.ai_error_message = e.to_string();
+                    self.ai_response = None;
+                    self.show_ai_response_dialog = false;
                 }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => break,
             }
         }
+        self.ai_stream = None;
     }
 
     fn apply_ai_response(&mut self) {
         if let Some(response) = &self.ai_response {
             if let Some(text_data) = &mut self.text_data {
+                if let Some(reason) = text_data.read_only_reason() {
+                    self.ai_error_message = reason.to_string();
+                    return;
+                }
                 // Primero, asegurarnos de que todos los cambios pendientes se han aplicado
                 let current_content = self.text_editor_content.clone();
                 let stored_content = text_data.local_data.clone();
                 
                 // Si hay diferencias, aplicar cambios pendientes primero
                 if current_content != stored_content {
-                    let current_chars: Vec<char> = current_content.chars().collect();
-                    let stored_chars: Vec<char> = stored_content.chars().collect();
-
                     // Si el usuario borró todo el texto de golpe
-                    if current_chars.is_empty() && !stored_chars.is_empty() {
-                        text_data.apply_local_operation(TextOperation::DeleteAll);
+                    let operations = if current_content.is_empty() {
+                        vec![TextOperation::DeleteAll]
                     } else {
-                        // Aplicar algoritmo de diferencias (similar a apply_new_changes_on_file)
-                        let mut prefix_len = 0;
-                        while prefix_len < current_chars.len().min(stored_chars.len())
-                            && current_chars[prefix_len] == stored_chars[prefix_len]
-                        {
-                            prefix_len += 1;
-                        }
-
-                        let mut suffix_len = 0;
-                        while suffix_len
-                            < (current_chars.len() - prefix_len).min(stored_chars.len() - prefix_len)
-                            && current_chars[current_chars.len() - 1 - suffix_len]
-                                == stored_chars[stored_chars.len() - 1 - suffix_len]
-                        {
-                            suffix_len += 1;
-                        }
-
-                        let old_mid_start = prefix_len;
-                        let old_mid_end = stored_chars.len() - suffix_len;
-                        let new_mid_start = prefix_len;
-                        let new_mid_end = current_chars.len() - suffix_len;
-
-                        // Eliminar caracteres viejos (de atrás hacia adelante)
-                        for i in (old_mid_start..old_mid_end).rev() {
-                            if i < text_data.local_data.len() {
-                                let delete_op = TextOperation::Delete { position: i };
-                                text_data.apply_local_operation(delete_op);
-                            }
-                        }
-
-                        // Insertar los nuevos caracteres
-                        for (j, &ch) in current_chars[new_mid_start..new_mid_end].iter().enumerate() {
-                            let pos = old_mid_start + j;
-                            let insert_op = TextOperation::Insert {
-                                position: pos,
-                                character: ch,
-                            };
-                            text_data.apply_local_operation(insert_op);
-                        }
-                    }
+                        char_diff(&stored_content, &current_content)
+                    };
+                    text_data.apply_undoable_operations(operations);
                 }
 
                 if !self.selected_text.is_empty() {
@@ -501,11 +478,13 @@ impl RedisApp {
                                     start: start_pos,
                                     end: end_pos,
                                 };
-                                text_data.apply_local_operation(delete);
-                                text_data.apply_local_operation(TextOperation::InsertText {
-                                    position: start_pos,
-                                    text: response.clone(),
-                                });
+                                text_data.apply_undoable_operations(vec![
+                                    delete,
+                                    TextOperation::InsertText {
+                                        position: start_pos,
+                                        text: response.clone(),
+                                    },
+                                ]);
                                 
                                 self.file_notifications.lock().unwrap().push(format!(
                                     "🤖 AI: Reemplazado texto seleccionado '{}' con '{}'",
@@ -523,8 +502,13 @@ impl RedisApp {
                     }
                     self.text_editor_content = text_data.local_data.clone();
                 } else if self.ai_position == 0 {
-                    text_data.apply_local_operation(TextOperation::DeleteAll);
-                    text_data.apply_local_operation(TextOperation::InsertText { position: 0, text: response.clone() });
+                    text_data.apply_undoable_operations(vec![
+                        TextOperation::DeleteAll,
+                        TextOperation::InsertText {
+                            position: 0,
+                            text: response.clone(),
+                        },
+                    ]);
                     
                     self.file_notifications.lock().unwrap().push(format!(
                         "🤖 AI: Reemplazado todo el documento con {} caracteres",
@@ -538,7 +522,7 @@ impl RedisApp {
                         position: self.ai_position,
                         text: response.clone(),
                     };
-                    text_data.apply_local_operation(insert_text_op);
+                    text_data.apply_undoable_operations(vec![insert_text_op]);
                     
                     self.file_notifications.lock().unwrap().push(format!(
                         "🤖 AI: Insertado texto en posición {} ({} caracteres)",
@@ -818,83 +802,34 @@ impl RedisApp {
             return;
         }
 
-        // Determinar cambios a nivel de caracteres
-        let old_chars: Vec<char> = old_value.chars().collect();
-        let new_chars: Vec<char> = new_value.chars().collect();
-
-        // 1. Encontrar el prefijo común
-        let mut prefix_len = 0;
-        while prefix_len < old_chars.len().min(new_chars.len())
-            && old_chars[prefix_len] == new_chars[prefix_len]
-        {
-            prefix_len += 1;
-        }
-
-        // 2. Encontrar el sufijo común
-        let mut suffix_len = 0;
-        while suffix_len < (old_chars.len() - prefix_len).min(new_chars.len() - prefix_len)
-            && old_chars[old_chars.len() - 1 - suffix_len]
-                == new_chars[new_chars.len() - 1 - suffix_len]
-        {
-            suffix_len += 1;
-        }
-
-        // 3. Determinar qué ha cambiado
-        let old_mid_start = prefix_len;
-        let old_mid_end = old_chars.len() - suffix_len;
-        let new_mid_start = prefix_len;
-        let new_mid_end = new_chars.len() - suffix_len;
-
-        // 4. Primero eliminar caracteres viejos (de atrás hacia adelante)
-        for i in (old_mid_start..old_mid_end).rev() {
-            // Crear TextOperation para eliminar caracteres
-            let text_op = TextOperation::Delete { position: i };
-
-            // Envolver en SpreadOperation con la información de la celda
-            let spread_op = SpreadOperation {
-                row,
-                column: col,
-                operation: text_op,
-            };
-
-            // Aplicar la operación localmente
-            csv_data.apply_local_operation(spread_op);
-
-            file_notifications.lock().unwrap().push(format!(
-                "CSV: Eliminación en celda [{},{}] posición {}",
-                row + 1,
-                col + 1,
-                i
-            ));
-        }
-
-        // 5. Luego insertar los nuevos caracteres
-        for (j, &ch) in new_chars[new_mid_start..new_mid_end].iter().enumerate() {
-            let pos = old_mid_start + j;
-
-            // Crear TextOperation para insertar caracteres
-            let text_op = TextOperation::Insert {
-                position: pos,
-                character: ch,
+        for operation in char_diff(old_value, new_value) {
+            let notification = match &operation {
+                TextOperation::Delete { position } => format!(
+                    "CSV: Eliminación en celda [{},{}] posición {}",
+                    row + 1,
+                    col + 1,
+                    position
+                ),
+                TextOperation::Insert {
+                    position,
+                    character,
+                } => format!(
+                    "CSV: Inserción de '{}' en celda [{},{}] posición {}",
+                    character,
+                    row + 1,
+                    col + 1,
+                    position
+                ),
+                _ => continue,
             };
 
             // Envolver en SpreadOperation con la información de la celda
-            let spread_op = SpreadOperation {
+            csv_data.apply_local_operation(SpreadOperation {
                 row,
                 column: col,
-                operation: text_op,
-            };
-
-            // Aplicar la operación localmente
-            csv_data.apply_local_operation(spread_op);
-
-            file_notifications.lock().unwrap().push(format!(
-                "CSV: Inserción de '{}' en celda [{},{}] posición {}",
-                ch,
-                row + 1,
-                col + 1,
-                pos
-            ));
+                operation,
+            });
+            file_notifications.lock().unwrap().push(notification);
         }
     }
 
@@ -921,11 +856,17 @@ impl RedisApp {
                             ui.label("No hay documentos disponibles.");
                         } else {
                             // Collect document info to avoid borrowing self mutably and immutably
-                            let docs_info: Vec<(String, DocType)> = documents
+                            let docs_info: Vec<(String, DocType, bool)> = documents
                                 .iter()
-                                .map(|doc| (doc.get_name(), doc.get_type()))
+                                .map(|doc| {
+                                    (
+                                        doc.get_name(),
+                                        doc.get_type(),
+                                        doc.can_remove(&self.username),
+                                    )
+                                })
                                 .collect();
-                            for (doc_name, doc_type) in docs_info {
+                            for (doc_name, doc_type, can_remove) in docs_info {
                                 let doc_type_icon = match doc_type {
                                     DocType::Text => "📝",
                                     DocType::SpreadSheet => "📊",
@@ -973,10 +914,10 @@ impl RedisApp {
                                         }
                                     }
 
-                                    // Botón para borrar el documento - sigue deshabilitado en modo solo lectura
+                                    // Botón para borrar el documento - solo para el dueño y fuera del modo solo lectura
                                     if ui
                                         .add_enabled(
-                                            !self.modo_lectura,
+                                            !self.modo_lectura && can_remove,
                                             egui::Button::new("🗑️ Borrar"),
                                         )
                                         .clicked()
@@ -1092,8 +1033,18 @@ impl RedisApp {
                     self.current_view = CurrentView::MainApp;
                 }
 
-                // Botones de AI - solo mostrar si no está en modo lectura
+                // Botones de edición y de AI - solo mostrar si no está en modo lectura
                 if !self.modo_lectura {
+                    ui.separator();
+                    if let Some(text_data) = &mut self.text_data {
+                        if ui.button("↩️ Deshacer").clicked() && text_data.undo().is_some() {
+                            self.text_editor_content = text_data.local_data.clone();
+                        }
+                        if ui.button("↪️ Rehacer").clicked() && text_data.redo().is_some() {
+                            self.text_editor_content = text_data.local_data.clone();
+                        }
+                    }
+
                     ui.separator();
                     ui.label("🤖 AI:");
 
@@ -1120,13 +1071,40 @@ impl RedisApp {
 
             egui::ScrollArea::vertical().show(ui, |ui| {
                 // Campo de texto deshabilitado en modo solo lectura
+                let editable = !self.modo_lectura
+                    && self
+                        .text_data
+                        .as_ref()
+                        .is_none_or(|data| data.read_only_reason().is_none());
                 let editor = egui::TextEdit::multiline(&mut self.text_editor_content)
                     .desired_width(f32::INFINITY)
                     .desired_rows(20)
-                    .interactive(!self.modo_lectura);
-                ui.add(editor);
+                    .interactive(editable);
+                // La posición del cursor de egui está en caracteres, igual que las operaciones
+                let moved = editor
+                    .show(ui)
+                    .cursor_range
+                    .map(|range| range.primary.ccursor.index)
+                    .filter(|cursor| Some(*cursor) != self.last_cursor);
+                if let (Some(cursor), Some(presence)) = (moved, &mut self.text_presence) {
+                    self.last_cursor = Some(cursor);
+                    let _ = presence.publish_position(cursor);
+                }
             });
 
+            if let Some(presence) = &mut self.text_presence {
+                let mut positions: Vec<_> = presence.positions().iter().collect();
+                if !positions.is_empty() {
+                    positions.sort();
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("👥 Cursores:");
+                        for (client_id, position) in positions {
+                            ui.label(format!("cliente {} en {}", client_id, position));
+                        }
+                    });
+                }
+            }
+
             if self.modo_lectura {
                 ui.add_space(5.0);
                 ui.colored_label(
@@ -1241,15 +1219,21 @@ impl RedisApp {
         }
 
         // Diálogo de respuesta de AI
+        self.poll_ai_stream();
         if self.show_ai_response_dialog {
             let ai_response = self.ai_response.clone();
+            let generating = self.ai_stream.is_some();
             let mut should_accept = false;
             let mut should_reject = false;
 
             egui::Window::new("🤖 Respuesta de AI")
                 .open(&mut self.show_ai_response_dialog)
                 .show(ctx, |ui| {
-                    ui.label("Texto generado por la AI:");
+                    if generating {
+                        ui.label("⏳ Generando texto...");
+                    } else {
+                        ui.label("Texto generado por la AI:");
+                    }
                     if let Some(response) = &ai_response {
                         let mut response_text = response.clone();
                         ui.text_edit_multiline(&mut response_text);
@@ -1257,7 +1241,11 @@ impl RedisApp {
                     ui.add_space(10.0);
 
                     ui.horizontal(|ui| {
-                        if ui.button("✅ Aceptar").clicked() {
+                        // Solo se puede aceptar la respuesta completa
+                        if ui
+                            .add_enabled(!generating, egui::Button::new("✅ Aceptar"))
+                            .clicked()
+                        {
                             should_accept = true;
                         }
                         if ui.button("❌ Rechazar").clicked() {
@@ -1272,6 +1260,10 @@ impl RedisApp {
             } else if should_reject {
                 self.show_ai_response_dialog = false;
             }
+            if !self.show_ai_response_dialog {
+                // Descartar lo que quede por llegar de una respuesta rechazada
+                self.ai_stream = None;
+            }
         }
     }
 
@@ -1322,6 +1314,9 @@ impl RedisApp {
                             self.previous_spreadsheet_data.data.push(Vec::new());
                         }
 
+                        // Resultado de las fórmulas, para las celdas que no se están editando
+                        let evaluated = self.spreadsheet_data.evaluated();
+
                         for row_idx in 0..max_rows {
                             let max_cols = if row_idx < self.spreadsheet_data.data.len()
                                 && row_idx < self.previous_spreadsheet_data.data.len()
@@ -1365,13 +1360,27 @@ impl RedisApp {
                                     String::new()
                                 };
 
+                                // Una fórmula muestra su resultado salvo mientras se edita
+                                let cell_id = ui.make_persistent_id(("celda", row_idx, col_idx));
+                                let showing_result = cell_value.starts_with('=')
+                                    && !ui.memory(|memory| memory.has_focus(cell_id));
+                                if showing_result {
+                                    cell_value = evaluated
+                                        .get(row_idx)
+                                        .and_then(|cells| cells.get(col_idx))
+                                        .cloned()
+                                        .unwrap_or_default();
+                                }
+
                                 // Hacer el TextEdit deshabilitado en modo solo lectura
                                 let response = egui::TextEdit::singleline(&mut cell_value)
+                                    .id(cell_id)
                                     .desired_width(80.0)
                                     .interactive(!self.modo_lectura)
                                     .show(ui);
 
                                 if response.response.changed()
+                                    && !showing_result
                                     && cell_value != prev_cell_value
                                     && !self.modo_lectura
                                 {
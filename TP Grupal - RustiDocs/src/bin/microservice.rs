@@ -1,9 +1,15 @@
 //! Este binario inicia el microservicio de indexación.
 //!
 //! # Uso
-//! cargo run --bin microservice
+//! cargo run --bin microservice [dirección del nodo] [directorio de documentos]
 
-use rustidocs::{app::microservice::index::Index, client_lib::cluster_manager::ClusterManager};
+use rustidocs::{
+    app::microservice::{
+        index::Index,
+        storage::{DEFAULT_DOCUMENTS_DIR, DocumentStorage},
+    },
+    client_lib::cluster_manager::ClusterManager,
+};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -12,8 +18,12 @@ fn main() {
     } else {
         "0.0.0.0:7001".to_string()
     };
+    let documents_dir = args
+        .get(2)
+        .cloned()
+        .unwrap_or(DEFAULT_DOCUMENTS_DIR.to_string());
 
     let cluster = ClusterManager::new(addr, "super".to_string(), "1234".to_string()).unwrap();
-    let mut x = Index::new(cluster);
+    let mut x = Index::new(cluster, DocumentStorage::new(documents_dir));
     x.run();
 }
@@ -1,4 +1,10 @@
-use rustidocs::{app::microservice::index::Index, client_lib::cluster_manager::ClusterManager};
+use rustidocs::{
+    app::microservice::{
+        index::Index,
+        storage::{DEFAULT_DOCUMENTS_DIR, DocumentStorage},
+    },
+    client_lib::cluster_manager::ClusterManager,
+};
 use std::env;
 
 fn main() {
@@ -8,6 +14,10 @@ fn main() {
     } else {
         "node_1:7001".to_string()
     };
+    let documents_dir = args
+        .get(2)
+        .cloned()
+        .unwrap_or(DEFAULT_DOCUMENTS_DIR.to_string());
 
     println!("🚀 Iniciando microservicio de indexación...");
     println!("📡 Conectando a Redis: {}", addr);
@@ -16,7 +26,7 @@ fn main() {
     match ClusterManager::new(addr.clone(), "super".to_string(), "1234".to_string()) {
         Ok(cluster) => {
             println!("✅ Conectado exitosamente al cluster");
-            let mut index_service = Index::new(cluster);
+            let mut index_service = Index::new(cluster, DocumentStorage::new(documents_dir));
             
             // Forzar que siempre use node_1 para evitar problemas de switching en Docker
             println!("🔧 Configurando para entorno Docker - deshabilitando cluster switching");
@@ -21,6 +21,7 @@ pub enum ClusterError {
     TcpConnectionError,
     NotSubscribedToChannel,
     CannotGetClusterData,
+    TooManyRedirects,
 }
 
 /// Struct encargado de la conexion con un cluster de redis
@@ -502,11 +503,14 @@ impl ClusterManager {
         let address = self.node_address.clone();
         println!("[ClusterManager::subscribe] Conectando para suscripción a: {}", address);
         let (mut stream, _) =
-            connect_to_cluster(address, self.username.clone(), self.password.clone()).unwrap();
+            connect_to_cluster(address, self.username.clone(), self.password.clone())
+                .map_err(|_| ClusterError::TcpConnectionError)?;
 
         println!("[ClusterManager::subscribe] Suscribiéndose al canal: {}", channel);
         let resp_message = create_subscribe(channel);
-        stream.write_all(&resp_message).unwrap();
+        stream
+            .write_all(&resp_message)
+            .map_err(|_| ClusterError::TcpConnectionError)?;
 
         // Descartar la respuesta inicial del SUBSCRIBE
         let mut reader = BufReader::new(&stream);
@@ -516,6 +520,34 @@ impl ClusterManager {
         Ok(stream)
     }
 
+    /// Se suscribe a `channel` en RESP3, para recibir cada publicación con
+    /// el usuario que la publicó como atributo.
+    ///
+    /// # Returns
+    ///
+    /// `Result<TcpStream, ClusterError>` - Conexión suscripta al canal
+    pub fn subscribe_with_publisher(&mut self, channel: &str) -> Result<TcpStream, ClusterError> {
+        let (mut stream, _) = connect_to_cluster(
+            self.node_address.clone(),
+            self.username.clone(),
+            self.password.clone(),
+        )
+        .map_err(|_| ClusterError::TcpConnectionError)?;
+
+        stream
+            .write_all(b"*2\r\n$5\r\nHELLO\r\n$1\r\n3\r\n")
+            .map_err(|_| ClusterError::TcpConnectionError)?;
+        stream
+            .write_all(&create_subscribe(channel))
+            .map_err(|_| ClusterError::TcpConnectionError)?;
+
+        // Descartar las respuestas del HELLO y del SUBSCRIBE
+        let mut reader = BufReader::new(&stream);
+        let _ = parse_resp_line(&mut reader);
+        let _ = parse_resp_line(&mut reader);
+        Ok(stream)
+    }
+
     fn fill_cluster(&mut self) -> Result<(), ClusterError> {
         self.active_node.write_all(&create_cluster_slot()).unwrap();
 
@@ -610,7 +642,7 @@ impl ClusterManager {
     }
 }
 
-fn array_to_vec(array: RespMessage) -> Option<Vec<String>> {
+pub(crate) fn array_to_vec(array: RespMessage) -> Option<Vec<String>> {
     match array {
         RespMessage::Array(vec) => {
             let mut vec_string: Vec<String> = Vec::new();
@@ -1 +1,2 @@
 pub mod cluster_manager;
+pub mod pool;
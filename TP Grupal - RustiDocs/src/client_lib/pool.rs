@@ -0,0 +1,673 @@
+//! Pool de conexiones al cluster con soporte de pipelining.
+//!
+//! Los comandos de un `Pipeline` se agrupan por nodo y se envían en una
+//! única escritura, leyendo luego las respuestas en orden. Las respuestas
+//! `MOVED` refrescan el mapa de slots y reintentan el comando en el nodo
+//! indicado; las `ASK` reintentan una única vez sin refrescar el mapa.
+
+// IMPORTS
+use crate::app::utils::connect_to_cluster;
+use crate::client_lib::cluster_manager::{ClusterError, array_to_vec};
+use crate::cluster::{
+    sharding::hash_slot::{MAX_HASH_SLOTS, hash_slot_with_count, validate_slot_count},
+    types::SlotRange,
+};
+use crate::command::utils::parse_flat_cluster_slots;
+use crate::network::{RespMessage, resp_parser::parse_resp_line};
+use std::{
+    collections::HashMap,
+    io::{BufReader, Write},
+    net::TcpStream,
+    sync::{Mutex, RwLock},
+    time::Duration,
+};
+
+// CONSTANTES
+
+/// Cantidad máxima de redirecciones seguidas para un mismo comando.
+const MAX_REDIRECTS: usize = 5;
+
+/// Tiempo máximo de espera por las respuestas de un nodo.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Lote de comandos a enviar en una única escritura.
+#[derive(Debug, Default, Clone)]
+pub struct Pipeline {
+    commands: Vec<Vec<String>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Agrega un comando al lote, ej: `cmd(&["SET", "Hero", "Tracer"])`.
+    pub fn cmd(&mut self, args: &[&str]) -> &mut Self {
+        self.commands
+            .push(args.iter().map(|arg| arg.to_string()).collect());
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// Codifica un comando como array RESP de bulk strings.
+fn encode_command(command: &[String], buffer: &mut Vec<u8>) {
+    buffer.extend_from_slice(format!("*{}\r\n", command.len()).as_bytes());
+    for arg in command {
+        buffer.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        buffer.extend_from_slice(arg.as_bytes());
+        buffer.extend_from_slice(b"\r\n");
+    }
+}
+
+/// Redirección indicada por el servidor.
+enum Redirect {
+    Moved(String),
+    Ask(String),
+}
+
+/// Interpreta una respuesta `-MOVED <slot> <ip:puerto>` o `-ASK <slot> <ip:puerto>`.
+fn parse_redirect(reply: &RespMessage) -> Option<Redirect> {
+    let message = match reply {
+        RespMessage::Error(msg) | RespMessage::SimpleError(msg) => msg,
+        _ => return None,
+    };
+    let mut parts = message.split_whitespace();
+    let kind = parts.next()?;
+    let address = parts.nth(1)?.to_string();
+    match kind {
+        "MOVED" => Some(Redirect::Moved(address)),
+        "ASK" => Some(Redirect::Ask(address)),
+        _ => None,
+    }
+}
+
+/// Obtiene la cantidad de slots de la sección `cluster` de INFO, en la
+/// línea `cluster_slot_count:<n>`.
+fn parse_slot_count(info: &str) -> Option<u16> {
+    info.lines()
+        .find_map(|line| line.trim().strip_prefix("cluster_slot_count:"))
+        .and_then(|count| count.parse().ok())
+        .and_then(|count| validate_slot_count(count).ok())
+}
+
+/// Asigna `range` a `master` en el mapa de slots, recortando los rangos
+/// conocidos que se superponen con él.
+fn merge_range(map: &mut HashMap<SlotRange, String>, range: SlotRange, master: String) {
+    let overlapping: Vec<SlotRange> = map
+        .keys()
+        .filter(|(start, end)| *start <= range.1 && range.0 <= *end)
+        .copied()
+        .collect();
+    for old in overlapping {
+        let Some(owner) = map.remove(&old) else {
+            continue;
+        };
+        if old.0 < range.0 {
+            map.insert((old.0, range.0 - 1), owner.clone());
+        }
+        if old.1 > range.1 {
+            map.insert((range.1 + 1, old.1), owner);
+        }
+    }
+    map.insert(range, master);
+}
+
+/// Pool de conexiones autenticadas a los nodos del cluster.
+#[derive(Debug)]
+pub struct Pool {
+    address: String,
+    username: String,
+    password: String,
+    size: usize,
+    idle: Mutex<HashMap<String, Vec<TcpStream>>>,
+    slot_map: RwLock<HashMap<SlotRange, String>>,
+    /// Cantidad de slots del cluster, informada por el nodo inicial
+    slot_count: RwLock<u16>,
+}
+
+impl Pool {
+    /// Abre `size` conexiones contra el nodo indicado y carga el mapa de slots.
+    /// Devuelve error si no se pudo conectar.
+    pub fn new(
+        address: String,
+        username: String,
+        password: String,
+        size: usize,
+    ) -> Result<Self, ClusterError> {
+        let pool = Self {
+            address: address.clone(),
+            username,
+            password,
+            size: size.max(1),
+            idle: Mutex::new(HashMap::new()),
+            slot_map: RwLock::new(HashMap::new()),
+            slot_count: RwLock::new(MAX_HASH_SLOTS),
+        };
+        let mut streams = Vec::new();
+        for _ in 0..pool.size {
+            streams.push(pool.connect(&address)?);
+        }
+        for stream in streams {
+            pool.checkin(&address, stream);
+        }
+        // Si el nodo no la informa se asume la cantidad de slots por defecto
+        if let Err(e) = pool.load_slot_count(&address) {
+            println!("[Pool::new] Error obteniendo la cantidad de slots: {:?}", e);
+        }
+        // Sin mapa de slots todos los comandos van al nodo inicial
+        if let Err(e) = pool.refresh_slots(&address) {
+            println!("[Pool::new] Error cargando el mapa de slots: {:?}", e);
+        }
+        Ok(pool)
+    }
+
+    /// Ejecuta un único comando, siguiendo redirecciones.
+    pub fn query(&self, args: &[&str]) -> Result<RespMessage, ClusterError> {
+        let mut pipeline = Pipeline::new();
+        pipeline.cmd(args);
+        self.execute(&pipeline)?
+            .pop()
+            .ok_or(ClusterError::InvalidRedisResponse)
+    }
+
+    /// Obtiene los valores de varias claves en un único pipeline.
+    pub fn mget(&self, keys: &[&str]) -> Result<Vec<RespMessage>, ClusterError> {
+        let mut pipeline = Pipeline::new();
+        for key in keys {
+            pipeline.cmd(&["GET", key]);
+        }
+        self.execute(&pipeline)
+    }
+
+    /// Envía el pipeline con una escritura por nodo y devuelve las
+    /// respuestas en el orden de los comandos.
+    pub fn execute(&self, pipeline: &Pipeline) -> Result<Vec<RespMessage>, ClusterError> {
+        let mut batches: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, command) in pipeline.commands.iter().enumerate() {
+            batches
+                .entry(self.node_for(command))
+                .or_default()
+                .push(index);
+        }
+
+        let mut replies: Vec<Option<RespMessage>> = vec![None; pipeline.len()];
+        for (address, indexes) in batches {
+            let commands: Vec<&Vec<String>> =
+                indexes.iter().map(|i| &pipeline.commands[*i]).collect();
+            let batch_replies = self.send_batch(&address, &commands)?;
+            for (index, reply) in indexes.into_iter().zip(batch_replies) {
+                replies[index] = Some(reply);
+            }
+        }
+
+        let mut results = Vec::with_capacity(pipeline.len());
+        for (command, reply) in pipeline.commands.iter().zip(replies) {
+            let reply = reply.ok_or(ClusterError::InvalidRedisResponse)?;
+            results.push(self.follow_redirects(command, reply)?);
+        }
+        Ok(results)
+    }
+
+    /// Vuelve a pedir CLUSTER SLOTS al nodo indicado y combina su vista con
+    /// el mapa de slots: los rangos que informa el nodo reemplazan lo que
+    /// se sabía de esos slots, y el resto de las rutas se conserva, porque
+    /// la vista de un nodo puede estar incompleta.
+    pub fn refresh_slots(&self, address: &str) -> Result<(), ClusterError> {
+        let view = self.slots_of(address)?;
+        if view.is_empty() {
+            return Err(ClusterError::CannotGetClusterData);
+        }
+        let mut map = self
+            .slot_map
+            .write()
+            .map_err(|_| ClusterError::CannotGetClusterData)?;
+        for (range, master) in view {
+            merge_range(&mut map, range, master);
+        }
+        Ok(())
+    }
+
+    /// Pide `INFO cluster` al nodo indicado y guarda la cantidad de slots
+    /// del cluster, con la que se calcula el slot de cada clave.
+    pub fn load_slot_count(&self, address: &str) -> Result<(), ClusterError> {
+        let command = vec!["INFO".to_string(), "cluster".to_string()];
+        let reply = self
+            .send_batch(address, &[&command])?
+            .pop()
+            .ok_or(ClusterError::CannotGetClusterData)?;
+        let info = match reply {
+            RespMessage::BulkString(Some(info)) => String::from_utf8_lossy(&info).into_owned(),
+            _ => return Err(ClusterError::InvalidRedisResponse),
+        };
+        let count = parse_slot_count(&info).ok_or(ClusterError::CannotGetClusterData)?;
+        let mut slot_count = self
+            .slot_count
+            .write()
+            .map_err(|_| ClusterError::CannotGetClusterData)?;
+        *slot_count = count;
+        Ok(())
+    }
+
+    /// Cantidad de slots con la que el pool calcula el slot de cada clave.
+    pub fn slot_count(&self) -> u16 {
+        self.slot_count
+            .read()
+            .map(|count| *count)
+            .unwrap_or(MAX_HASH_SLOTS)
+    }
+
+    /// Pide CLUSTER SLOTS al nodo indicado y devuelve el master de cada
+    /// rango según ese nodo.
+    pub fn slots_of(&self, address: &str) -> Result<HashMap<SlotRange, String>, ClusterError> {
+        let command = vec!["CLUSTER".to_string(), "SLOTS".to_string()];
+        let reply = self
+            .send_batch(address, &[&command])?
+            .pop()
+            .ok_or(ClusterError::CannotGetClusterData)?;
+        let flat = array_to_vec(reply).ok_or(ClusterError::CannotGetClusterData)?;
+
+        let mut slots = HashMap::new();
+        for (range, nodes) in parse_flat_cluster_slots(&flat) {
+            // El primer nodo de cada rango es el master
+            if let Some(master) = nodes.first() {
+                slots.insert(range, format!("{}:{}", master[0], master[1]));
+            }
+        }
+        Ok(slots)
+    }
+
+    /// Devuelve la dirección del master que atiende el slot, si se conoce.
+    pub fn node_for_slot(&self, slot: u16) -> Option<String> {
+        let map = self.slot_map.read().ok()?;
+        map.iter()
+            .find(|((start, end), _)| slot >= *start && slot <= *end)
+            .map(|(_, address)| address.clone())
+    }
+
+    /// Dirección del nodo al que corresponde el comando según su clave.
+    fn node_for(&self, command: &[String]) -> String {
+        command
+            .get(1)
+            .and_then(|key| hash_slot_with_count(key, self.slot_count()).ok())
+            .and_then(|slot| self.node_for_slot(slot))
+            .unwrap_or_else(|| self.address.clone())
+    }
+
+    /// Reintenta el comando mientras la respuesta sea una redirección.
+    fn follow_redirects(
+        &self,
+        command: &Vec<String>,
+        mut reply: RespMessage,
+    ) -> Result<RespMessage, ClusterError> {
+        for _ in 0..MAX_REDIRECTS {
+            reply = match parse_redirect(&reply) {
+                None => return Ok(reply),
+                Some(Redirect::Moved(address)) => {
+                    self.refresh_slots(&address)?;
+                    self.send_batch(&address, &[command])?
+                        .pop()
+                        .ok_or(ClusterError::InvalidRedisResponse)?
+                }
+                Some(Redirect::Ask(address)) => {
+                    let asking = vec!["ASKING".to_string()];
+                    self.send_batch(&address, &[&asking, command])?
+                        .pop()
+                        .ok_or(ClusterError::InvalidRedisResponse)?
+                }
+            };
+        }
+        match parse_redirect(&reply) {
+            None => Ok(reply),
+            Some(_) => Err(ClusterError::TooManyRedirects),
+        }
+    }
+
+    /// Escribe todos los comandos juntos y lee una respuesta por comando.
+    fn send_batch(
+        &self,
+        address: &str,
+        commands: &[&Vec<String>],
+    ) -> Result<Vec<RespMessage>, ClusterError> {
+        let mut buffer = Vec::new();
+        for command in commands {
+            encode_command(command, &mut buffer);
+        }
+
+        let mut stream = self.checkout(address)?;
+        if stream
+            .write_all(&buffer)
+            .and_then(|_| stream.flush())
+            .is_err()
+        {
+            return Err(ClusterError::TcpConnectionError);
+        }
+
+        let mut replies = Vec::with_capacity(commands.len());
+        {
+            let mut reader = BufReader::new(&stream);
+            for _ in 0..commands.len() {
+                match parse_resp_line(&mut reader) {
+                    Ok(reply) => replies.push(reply),
+                    // La conexión queda desfasada, no se devuelve al pool
+                    Err(_) => return Err(ClusterError::InvalidRedisResponse),
+                }
+            }
+        }
+        self.checkin(address, stream);
+        Ok(replies)
+    }
+
+    /// Toma una conexión libre hacia el nodo o abre una nueva.
+    fn checkout(&self, address: &str) -> Result<TcpStream, ClusterError> {
+        let stream = match self.idle.lock() {
+            Ok(mut idle) => idle.get_mut(address).and_then(|streams| streams.pop()),
+            Err(_) => None,
+        };
+        match stream {
+            Some(stream) => Ok(stream),
+            None => self.connect(address),
+        }
+    }
+
+    /// Devuelve la conexión al pool si todavía hay lugar.
+    fn checkin(&self, address: &str, stream: TcpStream) {
+        if let Ok(mut idle) = self.idle.lock() {
+            let streams = idle.entry(address.to_string()).or_default();
+            if streams.len() < self.size {
+                streams.push(stream);
+            }
+        }
+    }
+
+    fn connect(&self, address: &str) -> Result<TcpStream, ClusterError> {
+        let (stream, _) = connect_to_cluster(
+            address.to_string(),
+            self.username.clone(),
+            self.password.clone(),
+        )
+        .map_err(|_| ClusterError::TcpConnectionError)?;
+        stream
+            .set_read_timeout(Some(READ_TIMEOUT))
+            .map_err(|_| ClusterError::TcpConnectionError)?;
+        Ok(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::sharding::hash_slot::hash_slot;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread;
+
+    /// Estado compartido de un nodo simulado.
+    struct MockNode {
+        store: Mutex<HashMap<String, String>>,
+        /// Respuesta plana de CLUSTER SLOTS
+        slots: Mutex<Vec<String>>,
+        /// Si está presente, responde MOVED hacia esa dirección
+        moved_to: Mutex<Option<String>>,
+        /// Cantidad de slots que informa en INFO
+        slot_count: Mutex<u16>,
+        /// Cantidad de comandos de datos a acumular antes de responder
+        batch: usize,
+    }
+
+    fn flat_slots(address: &str) -> Vec<String> {
+        flat_slots_with_count(address, MAX_HASH_SLOTS)
+    }
+
+    fn flat_slots_with_count(address: &str, slot_count: u16) -> Vec<String> {
+        let (ip, port) = address.split_once(':').unwrap();
+        let last = (slot_count - 1).to_string();
+        vec!["0", last.as_str(), ip, port, "node", "master"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    fn reply_to(node: &MockNode, args: &[String]) -> RespMessage {
+        let name = args[0].to_uppercase();
+        if let Some(target) = node.moved_to.lock().unwrap().clone() {
+            let slot = hash_slot(&args[1]).unwrap();
+            return RespMessage::Error(format!("MOVED {} {}", slot, target));
+        }
+        let mut store = node.store.lock().unwrap();
+        match name.as_str() {
+            "SET" => {
+                store.insert(args[1].clone(), args[2].clone());
+                RespMessage::SimpleString("OK".to_string())
+            }
+            "GET" => RespMessage::BulkString(store.get(&args[1]).map(|v| v.as_bytes().to_vec())),
+            _ => RespMessage::Error("ERR unknown command".to_string()),
+        }
+    }
+
+    fn handle_client(node: Arc<MockNode>, stream: TcpStream) {
+        let mut writer = stream.try_clone().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut pending = Vec::new();
+        let mut pending_count = 0;
+        while let Ok(RespMessage::Array(items)) = parse_resp_line(&mut reader) {
+            let args: Vec<String> = array_to_vec(RespMessage::Array(items)).unwrap();
+            match args[0].to_uppercase().as_str() {
+                "AUTH" => {
+                    let ok = RespMessage::SimpleString(
+                        "Usuario logeado correctamente - WRITE".to_string(),
+                    );
+                    writer.write_all(&ok.as_bytes()).unwrap();
+                }
+                "CLUSTER" => {
+                    let slots = node.slots.lock().unwrap().clone();
+                    let reply = RespMessage::Array(
+                        slots
+                            .into_iter()
+                            .map(|s| RespMessage::BulkString(Some(s.into_bytes())))
+                            .collect(),
+                    );
+                    writer.write_all(&reply.as_bytes()).unwrap();
+                }
+                "INFO" => {
+                    let info = format!(
+                        "# Cluster\r\ncluster_enabled:1\r\ncluster_slot_count:{}\r\n",
+                        node.slot_count.lock().unwrap()
+                    );
+                    let reply = RespMessage::BulkString(Some(info.into_bytes()));
+                    writer.write_all(&reply.as_bytes()).unwrap();
+                }
+                _ => {
+                    pending.extend(reply_to(&node, &args).as_bytes());
+                    pending_count += 1;
+                    if pending_count >= node.batch {
+                        writer.write_all(&pending).unwrap();
+                        pending.clear();
+                        pending_count = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Levanta un nodo simulado y devuelve su dirección.
+    fn spawn_mock_node(batch: usize) -> (String, Arc<MockNode>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let node = Arc::new(MockNode {
+            store: Mutex::new(HashMap::new()),
+            slots: Mutex::new(flat_slots(&address)),
+            moved_to: Mutex::new(None),
+            slot_count: Mutex::new(MAX_HASH_SLOTS),
+            batch,
+        });
+        let shared = Arc::clone(&node);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let node = Arc::clone(&shared);
+                thread::spawn(move || handle_client(node, stream));
+            }
+        });
+        (address, node)
+    }
+
+    fn new_pool(address: &str) -> Pool {
+        Pool::new(
+            address.to_string(),
+            "admin".to_string(),
+            "admin".to_string(),
+            2,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_pipeline_encodes_commands_in_order() {
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .cmd(&["SET", "Hero", "Mercy"])
+            .cmd(&["GET", "Hero"]);
+
+        let mut buffer = Vec::new();
+        for command in &pipeline.commands {
+            encode_command(command, &mut buffer);
+        }
+        assert_eq!(pipeline.len(), 2);
+        assert_eq!(
+            buffer,
+            b"*3\r\n$3\r\nSET\r\n$4\r\nHero\r\n$5\r\nMercy\r\n*2\r\n$3\r\nGET\r\n$4\r\nHero\r\n"
+        );
+    }
+
+    #[test]
+    fn test_pipeline_round_trip() {
+        // El nodo no responde hasta recibir los 100 comandos: si el cliente
+        // esperara respuesta por comando, la lectura vencería por timeout.
+        let (address, _) = spawn_mock_node(100);
+        let pool = new_pool(&address);
+
+        let heroes: Vec<String> = (0..50).map(|i| format!("Hero{}", i)).collect();
+        let mut pipeline = Pipeline::new();
+        for hero in &heroes {
+            pipeline.cmd(&["SET", hero, &format!("Overwatch-{}", hero)]);
+        }
+        for hero in &heroes {
+            pipeline.cmd(&["GET", hero]);
+        }
+
+        let replies = pool.execute(&pipeline).unwrap();
+        assert_eq!(replies.len(), 100);
+        for reply in &replies[..50] {
+            assert_eq!(*reply, RespMessage::SimpleString("OK".to_string()));
+        }
+        for (hero, reply) in heroes.iter().zip(&replies[50..]) {
+            assert_eq!(
+                *reply,
+                RespMessage::BulkString(Some(format!("Overwatch-{}", hero).into_bytes()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_mget() {
+        let (address, node) = spawn_mock_node(1);
+        node.store
+            .lock()
+            .unwrap()
+            .insert("Tank".to_string(), "Reinhardt".to_string());
+        let pool = new_pool(&address);
+
+        let replies = pool.mget(&["Tank"]).unwrap();
+        assert_eq!(
+            replies,
+            vec![RespMessage::BulkString(Some(b"Reinhardt".to_vec()))]
+        );
+    }
+
+    #[test]
+    fn test_moved_refreshes_slot_map_and_retries() {
+        let (address_a, node_a) = spawn_mock_node(1);
+        let (address_b, node_b) = spawn_mock_node(1);
+        let pool = new_pool(&address_a);
+        let slot = hash_slot("Genji").unwrap();
+        assert_eq!(pool.node_for_slot(slot), Some(address_a.clone()));
+
+        // Los slots migran al nodo B
+        *node_a.moved_to.lock().unwrap() = Some(address_b.clone());
+        *node_a.slots.lock().unwrap() = flat_slots(&address_b);
+        *node_b.slots.lock().unwrap() = flat_slots(&address_b);
+
+        let reply = pool.query(&["SET", "Genji", "Shimada"]).unwrap();
+        assert_eq!(reply, RespMessage::SimpleString("OK".to_string()));
+        assert_eq!(pool.node_for_slot(slot), Some(address_b.clone()));
+        assert_eq!(
+            node_b.store.lock().unwrap().get("Genji"),
+            Some(&"Shimada".to_string())
+        );
+        assert!(node_a.store.lock().unwrap().is_empty());
+
+        // Con el mapa actualizado, el siguiente comando va directo a B
+        *node_a.moved_to.lock().unwrap() = None;
+        let reply = pool.query(&["GET", "Genji"]).unwrap();
+        assert_eq!(reply, RespMessage::BulkString(Some(b"Shimada".to_vec())));
+    }
+
+    #[test]
+    fn test_pool_uses_slot_count_reported_by_node() {
+        let (address_a, node_a) = spawn_mock_node(1);
+        let (address_b, _) = spawn_mock_node(1);
+        // Cluster de 1024 slots: A tiene la primera mitad y B la segunda
+        *node_a.slot_count.lock().unwrap() = 1024;
+        let (ip_a, port_a) = address_a.split_once(':').unwrap();
+        let (ip_b, port_b) = address_b.split_once(':').unwrap();
+        *node_a.slots.lock().unwrap() = [
+            "0", "511", ip_a, port_a, "node-a", "master", "512", "1023", ip_b, port_b, "node-b",
+            "master",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let pool = new_pool(&address_a);
+        assert_eq!(pool.slot_count(), 1024);
+
+        // Con 1024 slots Genji cae en el 559, de B; con 16384 caería en el
+        // 2607, fuera del mapa, y se enviaría al nodo inicial
+        assert_eq!(hash_slot_with_count("Genji", 1024).unwrap(), 559);
+        assert_eq!(hash_slot("Genji").unwrap(), 2607);
+        assert_eq!(
+            pool.node_for(&["GET".to_string(), "Genji".to_string()]),
+            address_b
+        );
+    }
+
+    #[test]
+    fn test_parse_slot_count() {
+        let info = "# Cluster\r\ncluster_enabled:1\r\ncluster_slot_count:4096\r\n";
+        assert_eq!(parse_slot_count(info), Some(4096));
+        assert_eq!(parse_slot_count("cluster_slot_count:1000\r\n"), None);
+        assert_eq!(parse_slot_count("# Persistence\r\n"), None);
+    }
+
+    #[test]
+    fn test_merge_range_keeps_routes_outside_new_range() {
+        let mut map = HashMap::new();
+        map.insert((0, 16383), "Numbani:7000".to_string());
+
+        merge_range(&mut map, (8192, 16383), "Hanamura:7001".to_string());
+        merge_range(&mut map, (100, 199), "Ilios:7002".to_string());
+
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get(&(0, 99)), Some(&"Numbani:7000".to_string()));
+        assert_eq!(map.get(&(100, 199)), Some(&"Ilios:7002".to_string()));
+        assert_eq!(map.get(&(200, 8191)), Some(&"Numbani:7000".to_string()));
+        assert_eq!(map.get(&(8192, 16383)), Some(&"Hanamura:7001".to_string()));
+    }
+}
@@ -22,14 +22,21 @@ use crate::cluster::{
     },
     state::node_data::NodeData,
     time_tracker::TimeTracker,
-    types::{JOIN_TYPE, KnownNode, NodeId, NodeMessage, SlotRange},
+    types::{JOIN_TYPE, KnownNode, NodeId, NodeMessage},
 };
 
-use crate::command::{command_executor::CommandExecutor, instruction::Instruction, types::Command};
+use crate::command::{
+    command_executor::{ClusterBus, CommandExecutor},
+    instruction::Instruction,
+    types::Command,
+};
 
 use crate::{config::node_configs::NodeConfigs, logs::aof_logger::AofLogger};
 
-use crate::network::{connection_handler::Handler, resp_message::RespMessage};
+use crate::network::{
+    connection_handler::{ClientRegistry, Handler},
+    resp_message::RespMessage,
+};
 
 use crate::pubsub::{
     cluster_communication::ClusterCommunicationManager,
@@ -48,7 +55,6 @@ use crate::storage::{
 pub static NODE_TIMEOUT: u64 = 10000; // Tiempo en ms hasta timeout para ping/pong.
 pub static PING_INTERVAL: u64 = 750; // Tiempo en ms hasta el próximo ping.
 pub static GOSSIP_SECTION_ENTRIES: u64 = 3;
-pub static SLOTS_RANGE: SlotRange = (0, 16383);
 
 pub struct ClusterNode {
     configs: NodeConfigs,
@@ -102,9 +108,17 @@ impl ClusterNode {
         let (instruction_sender, instruction_receiver) =
             channel::<(String, Instruction, Sender<RespMessage>)>();
         let (pubsub_sender, pubsub_receiver) = channel();
+        let (output_sender, output_receiver) = channel::<(NodeId, SocketAddr, Option<Vec<u8>>)>();
 
-        self.start_command_executor(ds.clone(), instruction_receiver, pubsub_sender);
-        self.start_client_connections_handler(instruction_sender.clone());
+        let clients: ClientRegistry = Arc::new(RwLock::new(HashMap::new()));
+        self.start_command_executor(
+            ds.clone(),
+            instruction_receiver,
+            pubsub_sender,
+            clients.clone(),
+            output_sender.clone(),
+        );
+        self.start_client_connections_handler(instruction_sender.clone(), clients);
 
         ClusterNode::connect_to_cluster(
             self.configs.clone(),
@@ -117,7 +131,6 @@ impl ClusterNode {
             self.configs.get_id()
         );
 
-        let (output_sender, output_receiver) = channel::<(NodeId, SocketAddr, Option<Vec<u8>>)>();
         let tracker = Arc::new(RwLock::new(TimeTracker::new(NODE_TIMEOUT)));
 
         // Determinar tipo de encriptación para node_output
@@ -169,6 +182,9 @@ impl ClusterNode {
 
     fn load_ds(&self) -> Result<Arc<RwLock<DataStore>>, Box<dyn Error>> {
         let loader = DiskLoader::new(self.configs.clone(), self.logger.clone());
+        if self.configs.get_aof_rdb_check() {
+            loader.report_aof_rdb_consistency();
+        }
         loader.load().map_err(|e| e.into())
     }
 
@@ -184,6 +200,8 @@ impl ClusterNode {
         ds: Arc<RwLock<DataStore>>,
         instruction_receiver: Receiver<(String, Instruction, Sender<RespMessage>)>,
         pubsub_sender: Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
+        clients: ClientRegistry,
+        cluster_bus: ClusterBus,
     ) {
         let logger_clone = self.logger.clone();
         let ds_clone = ds.clone();
@@ -199,7 +217,9 @@ impl ClusterNode {
                 pubsub_sender,
                 known_nodes_clone,
                 data_clone,
-            );
+                clients,
+            )
+            .with_cluster_bus(cluster_bus);
             executor.run();
         });
     }
@@ -252,6 +272,7 @@ impl ClusterNode {
     fn start_client_connections_handler(
         &self,
         instruction_sender: Sender<(String, Instruction, Sender<RespMessage>)>,
+        clients: ClientRegistry,
     ) {
         let user_base = load_users_from_acl("user.acl").unwrap_or(UserBase::new());
         // Handler
@@ -260,6 +281,7 @@ impl ClusterNode {
             self.configs.clone(),
             self.logger.clone(),
             user_base,
+            clients,
         );
         thread::spawn(move || {
             let _ = connection_handler.init();
@@ -345,7 +367,10 @@ impl ClusterNode {
             // Por default el nodo se inicia como master con todos los slots disponibles
             println!("[CLUSTER] First node of the cluster, taking every slot");
             if let Some(node_data) = node_data_lock {
-                node_data.write().unwrap().set_slots(SLOTS_RANGE);
+                node_data
+                    .write()
+                    .unwrap()
+                    .set_slots(configs.get_full_slot_range());
                 node_data.write().unwrap().set_as_master();
             }
         }
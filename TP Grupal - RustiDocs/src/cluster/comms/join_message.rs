@@ -1,5 +1,5 @@
 use crate::cluster::state::flags::{CONNECTED, HANDSHAKE, NodeFlags};
-use crate::cluster::types::SlotRange;
+use crate::cluster::types::{EMPTY_SLOT_RANGE, SlotRange};
 use crate::cluster::utils::{read_string_from_buffer, read_u16_from_buffer};
 use crate::cluster::{
     sharding::rehash_message::RehashMessage,
@@ -178,7 +178,7 @@ pub fn handle_join_message(
             );
             if let Some(failed) = nodes.get_mut(&failed_master.get_id()) {
                 failed.set_as_replaced();
-                failed.set_hash_slots((0, 0));
+                failed.set_hash_slots(EMPTY_SLOT_RANGE);
                 failed.set_last_pong_time(None);
                 failed.add_cepoch();
             }
@@ -7,13 +7,14 @@ use crate::cluster::comms::gossip_receiver::process_gossip_msg;
 use crate::cluster::comms::join_message::process_join_msg;
 use crate::cluster::comms::psync_reciever::process_psync_message;
 use crate::cluster::comms::pubsub_message::process_pubsub_msg;
-use crate::cluster::comms::replica_promotion::process_promotion_msg;
+use crate::cluster::comms::replica_promotion::{process_new_master_msg, process_promotion_msg};
 use crate::cluster::sharding::rehash_message::process_rehash_msg;
 use crate::cluster::state::node_data::NodeData;
 use crate::cluster::time_tracker::TimeTracker;
 use crate::cluster::types::{
     CONNECTION_CLOSE_TYPE, DEFAULT_BUFFER_SIZE, FAIL_TYPE, GOSSIP_TYPE, JOIN_TYPE, KnownNode,
-    NodeId, NodeMessage, PROMOTION_TYPE, PUBSUB_TYPE, REHASH_TYPE, REQUEST_PSYNC_TYPE,
+    NEW_MASTER_TYPE, NodeId, NodeMessage, PROMOTION_TYPE, PUBSUB_TYPE, REHASH_TYPE,
+    REQUEST_PSYNC_TYPE,
 };
 use crate::pubsub::distributed_manager::PubSubMessage;
 use crate::security::tls_lite::{TlsServerConfig, TlsServerStream};
@@ -220,6 +221,7 @@ fn read_stream(
                 REHASH_TYPE => process_rehash_msg(message, node_data, known_nodes, output_sender),
                 FAIL_TYPE => process_node_fail_msg(message, node_data, known_nodes),
                 PROMOTION_TYPE => process_promotion_msg(message, node_data, known_nodes),
+                NEW_MASTER_TYPE => process_new_master_msg(message, node_data, known_nodes),
                 PUBSUB_TYPE => process_pubsub_msg(
                     message,
                     node_data,
@@ -227,9 +229,13 @@ fn read_stream(
                     output_sender,
                     pubsub_sender,
                 ),
-                REQUEST_PSYNC_TYPE => {
-                    process_psync_message(message, node_data, data_store, output_sender)
-                }
+                REQUEST_PSYNC_TYPE => process_psync_message(
+                    message,
+                    node_data,
+                    data_store,
+                    output_sender,
+                    known_nodes,
+                ),
                 _ => Err("[NI-CLUSTER] Wrong message type received".to_string()),
             }
         }
@@ -253,6 +259,7 @@ fn map_type_to_variable_name(msg_type: u8) -> &'static str {
         PROMOTION_TYPE => "PROMOTION_TYPE",
         PUBSUB_TYPE => "PUBSUB_TYPE",
         REQUEST_PSYNC_TYPE => "REQUEST_PSYNC_TYPE",
+        NEW_MASTER_TYPE => "NEW_MASTER_TYPE",
         _ => "UNKNOWN_TYPE",
     }
 }
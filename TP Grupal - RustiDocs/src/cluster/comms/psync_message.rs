@@ -1,6 +1,6 @@
 use crate::cluster::types::TimeStamp;
 use crate::cluster::utils::{
-    read_string_from_buffer, read_timestamp_from_buffer, read_u16_from_buffer,
+    read_string_from_buffer, read_timestamp_from_buffer, read_u16_from_buffer, read_u64_from_buffer,
 };
 use crate::storage::DataStore;
 use std::io::Read;
@@ -8,6 +8,9 @@ use std::io::Read;
 pub struct PsyncMessage {
     pub node_id: String,
     pub last_update_time: TimeStamp,
+    /// Offset de replicación: en el pedido de la réplica, el que ya
+    /// aplicó; en la respuesta del master, el de los datos enviados.
+    pub replication_offset: u64,
     pub data_store: DataStore,
 }
 
@@ -16,6 +19,7 @@ impl PsyncMessage {
         node_id: String,
         data_store: DataStore,
         last_update_time: Option<TimeStamp>,
+        replication_offset: u64,
     ) -> Self {
         PsyncMessage {
             node_id,
@@ -24,6 +28,7 @@ impl PsyncMessage {
             } else {
                 -1
             },
+            replication_offset,
             data_store,
         }
     }
@@ -34,6 +39,7 @@ impl PsyncMessage {
         bytes.extend_from_slice(&(id_bytes.len() as u16).to_be_bytes());
         bytes.extend_from_slice(id_bytes);
         bytes.extend_from_slice(&self.last_update_time.to_be_bytes());
+        bytes.extend_from_slice(&self.replication_offset.to_be_bytes());
         bytes.extend_from_slice(&self.data_store.serialize());
         bytes
     }
@@ -42,11 +48,13 @@ impl PsyncMessage {
         let node_id_len = read_u16_from_buffer(buffer).unwrap();
         let node_id = read_string_from_buffer(buffer, node_id_len as usize).unwrap();
         let last_update_time = read_timestamp_from_buffer(buffer).unwrap();
+        let replication_offset = read_u64_from_buffer(buffer).unwrap();
         let data_store = DataStore::from_bytes(buffer).unwrap();
 
         PsyncMessage {
             node_id,
             last_update_time,
+            replication_offset,
             data_store,
         }
     }
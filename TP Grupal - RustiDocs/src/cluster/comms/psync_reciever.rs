@@ -5,10 +5,11 @@ use crate::{
     cluster::{
         comms::psync_message::PsyncMessage,
         state::node_data::NodeData,
-        types::{NodeId, NodeMessage},
+        types::{KnownNode, NodeId, NodeMessage},
     },
     storage::DataStore,
 };
+use std::collections::HashMap;
 use std::io::Cursor;
 use std::sync::RwLockWriteGuard;
 use std::time::SystemTime;
@@ -18,11 +19,15 @@ use std::{
 };
 
 /// Propone iniciar el psync, se lo manda al maestro y el maestro hace los cambios en la data store para devolver.
+///
+/// El master registra, en los nodos conocidos, el offset de replicación
+/// que la réplica confirma con el pedido.
 pub fn process_psync_message(
     message: NodeMessage,
     node_data: &Arc<RwLock<NodeData>>,
     data_store: &Arc<RwLock<DataStore>>,
     output: &Sender<(NodeId, SocketAddr, Option<Vec<u8>>)>,
+    known_nodes: &Arc<RwLock<HashMap<NodeId, KnownNode>>>,
 ) -> Result<(), String> {
     let mut myself = node_data.write().unwrap();
     if NodeFlags::state_contains(myself.get_state(), SLAVE) {
@@ -33,8 +38,12 @@ pub fn process_psync_message(
     let mut cursor = Cursor::new(&mut payload);
     let psync_message = PsyncMessage::from_bytes(&mut cursor);
     let replica_node_id = psync_message.node_id.clone();
+    let acked_offset = psync_message.replication_offset;
     let data_store_replica = psync_message.data_store;
 
+    // El offset se lee antes que los datos: las escrituras lo avanzan
+    // después de aplicarse, así que nunca supera lo que se envía
+    let offset = myself.get_replication_offset();
     let mut updated_data_store = data_store_replica.clone();
     let master_data_store = data_store.read().unwrap();
 
@@ -51,6 +60,7 @@ pub fn process_psync_message(
         replica_node_id.clone(),
         updated_data_store,
         Some(system_time_to_i64(SystemTime::now())),
+        offset,
     );
     let bytes = psync_res.serialize();
 
@@ -62,6 +72,12 @@ pub fn process_psync_message(
         bytes.len() as u16,
         bytes,
     );
+    drop(master_data_store);
+    drop(myself);
+
+    if let Some(replica) = known_nodes.write().unwrap().get_mut(&replica_node_id) {
+        replica.set_replication_offset(acked_offset);
+    }
 
     if let Err(e) = output.send((replica_node_id, node_addr, Some(response.serialize()))) {
         eprintln!("Failed to send PSYNC response: {}", e);
@@ -82,5 +98,6 @@ fn update_data_store(
 
     data_store.update(psync_message.data_store);
     myself.set_last_update_time(system_time_to_i64(SystemTime::now()));
+    myself.set_replication_offset(psync_message.replication_offset);
     Ok(())
 }
@@ -51,8 +51,12 @@ pub fn psync_send(
     //de los conocidos, busco el nodo que es mi master
 
     if let Some(master_node) = nodos_conocidos.get(&id_de_mi_master) {
-        let psync_message =
-            PsyncMessage::new(myself.get_id(), data_store.read().unwrap().clone(), None);
+        let psync_message = PsyncMessage::new(
+            myself.get_id(),
+            data_store.read().unwrap().clone(),
+            None,
+            myself.get_replication_offset(),
+        );
 
         let bytes = psync_message.serialize();
 
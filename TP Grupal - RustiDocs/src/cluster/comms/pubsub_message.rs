@@ -105,6 +105,6 @@ mod tests {
             node_message.get_request_type(),
             crate::cluster::types::PUBSUB_TYPE
         );
-        assert_eq!(node_message.get_src_id(), "test_node");
+        assert_eq!(node_message.get_src_id(), node_data.get_id());
     }
 }
@@ -1,7 +1,7 @@
 use crate::cluster::state::flags::{FAIL, NodeFlags, SLAVE};
 use crate::cluster::state::node_data::NodeData;
 use crate::cluster::types::{
-    Epoch, KnownNode, NodeId, NodeMessage, PROMOTION_TYPE, SlotRange, TimeStamp,
+    EMPTY_SLOT_RANGE, Epoch, KnownNode, NodeId, NodeMessage, PROMOTION_TYPE, SlotRange, TimeStamp,
 };
 use crate::cluster::utils::{read_string_from_buffer, read_u16_from_buffer, read_u64_from_buffer};
 use std::collections::HashMap;
@@ -171,6 +171,64 @@ pub fn process_promotion_msg(
     Ok(())
 }
 
+/// Procesa el anuncio de un failover manual (`FAILOVER`), enviado por el
+/// master saliente una vez que su réplica se puso al día.
+///
+/// A diferencia de la promoción por falla, el master saliente sigue vivo:
+/// pasa a ser réplica del candidato en lugar de quedar marcado como caído.
+pub fn process_new_master_msg(
+    message: NodeMessage,
+    node_data_lock: &Arc<RwLock<NodeData>>,
+    known_nodes_lock: &Arc<RwLock<HashMap<NodeId, KnownNode>>>,
+) -> Result<(), String> {
+    let promotion_msg = PromotionMessage::from_bytes(&message.get_payload())?;
+    apply_manual_failover(&promotion_msg, node_data_lock, known_nodes_lock);
+    Ok(())
+}
+
+/// Intercambia los roles del master saliente y el candidato en el estado
+/// local. Las demás réplicas del master saliente pasan a replicar al candidato.
+pub fn apply_manual_failover(
+    promotion_msg: &PromotionMessage,
+    node_data_lock: &Arc<RwLock<NodeData>>,
+    known_nodes_lock: &Arc<RwLock<HashMap<NodeId, KnownNode>>>,
+) {
+    let candidate_id = promotion_msg.get_candidate_id().clone();
+    let old_master_id = promotion_msg.get_failed_master_id().clone();
+    let slots_to_assume = promotion_msg.get_slots_to_assume();
+    let config_epoch = promotion_msg.get_config_epoch();
+
+    let mut myself = node_data_lock.write().unwrap();
+    let mut known_nodes = known_nodes_lock.write().unwrap();
+    if myself.get_id() == candidate_id {
+        myself.set_as_master();
+        myself.set_slots(slots_to_assume);
+        myself.add_cepoch();
+    } else if myself.get_id() == old_master_id {
+        myself.set_as_slave(candidate_id.clone());
+        myself.set_slots(EMPTY_SLOT_RANGE);
+    } else if myself.get_master_id() == Some(old_master_id.clone()) {
+        myself.set_as_slave(candidate_id.clone());
+    }
+    drop(myself);
+
+    for node in known_nodes.values_mut() {
+        if node.is_slave() && node.get_master_id() == Some(&old_master_id) {
+            node.set_master(Some(candidate_id.clone()));
+        }
+    }
+    if let Some(candidate) = known_nodes.get_mut(&candidate_id) {
+        candidate.promote_to_master(slots_to_assume, config_epoch);
+    }
+    if let Some(old_master) = known_nodes.get_mut(&old_master_id) {
+        old_master.demote_to_replica(candidate_id.clone());
+    }
+    println!(
+        "[FAILOVER] Nodo {} reemplaza al master {}",
+        candidate_id, old_master_id
+    );
+}
+
 /// Mensaje de promoción de réplica
 #[derive(Debug)]
 pub struct PromotionMessage {
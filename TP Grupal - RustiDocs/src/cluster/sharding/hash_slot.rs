@@ -9,6 +9,7 @@
 ///
 /// Este valor es estándar en Redis Cluster y define el espacio total
 /// de slots disponibles para distribuir entre los nodos del cluster.
+/// Es también la cantidad por defecto de `cluster-slot-count`.
 pub const MAX_HASH_SLOTS: u16 = 16384;
 
 /// Errores específicos del cálculo de hash slots.
@@ -16,12 +17,19 @@ pub const MAX_HASH_SLOTS: u16 = 16384;
 pub enum HashSlotError {
     /// Clave vacía o inválida
     InvalidKey(String),
+    /// Cantidad de slots que no es potencia de dos o supera el máximo
+    InvalidSlotCount(u32),
 }
 
 impl std::fmt::Display for HashSlotError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             HashSlotError::InvalidKey(msg) => write!(f, "Clave inválida: {}", msg),
+            HashSlotError::InvalidSlotCount(count) => write!(
+                f,
+                "Cantidad de slots inválida: {}, debe ser potencia de dos y a lo sumo {}",
+                count, MAX_HASH_SLOTS
+            ),
         }
     }
 }
@@ -49,7 +57,7 @@ impl std::error::Error for HashSlotError {}
 /// 3. Para cada bit del byte, verifica el bit más significativo
 /// 4. Si está activado, desplaza y hace XOR con 0x1021
 /// 5. Si no, solo desplaza a la izquierda
-fn crc16_xmodem(data: &[u8]) -> u16 {
+pub(crate) fn crc16_xmodem(data: &[u8]) -> u16 {
     let mut crc: u16 = 0x0000;
 
     for &byte in data {
@@ -94,6 +102,42 @@ fn crc16_xmodem(data: &[u8]) -> u16 {
 /// - La clave está vacía
 /// - Hay un error interno en el cálculo del hash
 pub fn hash_slot(key: &str) -> Result<u16, HashSlotError> {
+    hash_slot_with_count(key, MAX_HASH_SLOTS)
+}
+
+/// Valida la cantidad de slots del cluster.
+///
+/// El slot se obtiene enmascarando el CRC16 con `slot_count - 1`, lo que
+/// equivale al módulo sólo si la cantidad es potencia de dos.
+///
+/// # Argumentos
+///
+/// * `slot_count` - Cantidad de slots configurada
+///
+/// # Retorna
+///
+/// * `Ok(u16)` - La cantidad, si es válida
+/// * `Err(HashSlotError)` - Si no es potencia de dos o supera `MAX_HASH_SLOTS`
+pub fn validate_slot_count(slot_count: u32) -> Result<u16, HashSlotError> {
+    if !slot_count.is_power_of_two() || slot_count > MAX_HASH_SLOTS as u32 {
+        return Err(HashSlotError::InvalidSlotCount(slot_count));
+    }
+    Ok(slot_count as u16)
+}
+
+/// Calcula el hash slot de una clave en un cluster de `slot_count` slots.
+///
+/// # Argumentos
+///
+/// * `key` - Clave para la cual calcular el hash slot
+/// * `slot_count` - Cantidad de slots del cluster, potencia de dos
+///
+/// # Retorna
+///
+/// * `Ok(u16)` - Número de slot (0 a slot_count-1)
+/// * `Err(HashSlotError)` - Error si la clave o la cantidad son inválidas
+pub fn hash_slot_with_count(key: &str, slot_count: u16) -> Result<u16, HashSlotError> {
+    validate_slot_count(slot_count as u32)?;
     if key.is_empty() {
         return Err(HashSlotError::InvalidKey(
             "La clave no puede estar vacía".to_string(),
@@ -109,7 +153,7 @@ pub fn hash_slot(key: &str) -> Result<u16, HashSlotError> {
     }
 
     let crc = crc16_xmodem(hash_key.as_bytes());
-    let slot = crc % MAX_HASH_SLOTS;
+    let slot = crc & (slot_count - 1);
 
     Ok(slot)
 }
@@ -186,14 +230,30 @@ pub fn hash_slots(keys: &[&str]) -> Result<Vec<u16>, HashSlotError> {
 /// * `Err(HashSlotError)` - Error si alguna clave es inválida
 #[allow(dead_code)]
 pub fn keys_same_slot(keys: &[&str]) -> Result<bool, HashSlotError> {
+    keys_same_slot_with_count(keys, MAX_HASH_SLOTS)
+}
+
+/// Verifica si todas las claves caen en el mismo slot de un cluster de
+/// `slot_count` slots.
+///
+/// # Argumentos
+///
+/// * `keys` - Slice de claves a verificar
+/// * `slot_count` - Cantidad de slots del cluster, potencia de dos
+///
+/// # Retorna
+///
+/// * `Ok(bool)` - `true` si todas las claves están en el mismo slot
+/// * `Err(HashSlotError)` - Error si alguna clave o la cantidad son inválidas
+pub fn keys_same_slot_with_count(keys: &[&str], slot_count: u16) -> Result<bool, HashSlotError> {
     if keys.is_empty() {
         return Ok(true);
     }
 
-    let first_slot = hash_slot(keys[0])?;
+    let first_slot = hash_slot_with_count(keys[0], slot_count)?;
 
     for &key in &keys[1..] {
-        if hash_slot(key)? != first_slot {
+        if hash_slot_with_count(key, slot_count)? != first_slot {
             return Ok(false);
         }
     }
@@ -262,9 +322,7 @@ mod tests {
     fn test_hash_slot_empty_key() {
         let result = hash_slot("");
         assert!(result.is_err());
-        match result.unwrap_err() {
-            HashSlotError::InvalidKey(_) => (),
-        }
+        assert!(matches!(result.unwrap_err(), HashSlotError::InvalidKey(_)));
     }
 
     #[test]
@@ -357,6 +415,13 @@ mod tests {
         assert!(keys_same_slot(&[]).unwrap());
     }
 
+    #[test]
+    fn test_keys_same_slot_with_count_uses_tags() {
+        let keys = ["{Talon}Reaper", "{Talon}Widowmaker"];
+        assert!(keys_same_slot_with_count(&keys, 256).unwrap());
+        assert!(keys_same_slot_with_count(&["Talon", ""], 256).is_err());
+    }
+
     #[test]
     fn test_keys_same_slot_single() {
         assert!(keys_same_slot(&["single_key"]).unwrap());
@@ -388,6 +453,40 @@ mod tests {
         assert_eq!(min_slot, max_slot); // Min y max iguales
     }
 
+    #[test]
+    fn test_validate_slot_count() {
+        assert_eq!(validate_slot_count(256), Ok(256));
+        assert_eq!(validate_slot_count(16384), Ok(16384));
+        assert_eq!(
+            validate_slot_count(300),
+            Err(HashSlotError::InvalidSlotCount(300))
+        );
+        assert_eq!(
+            validate_slot_count(32768),
+            Err(HashSlotError::InvalidSlotCount(32768))
+        );
+        assert!(validate_slot_count(0).is_err());
+    }
+
+    #[test]
+    fn test_keys_distribute_across_256_slots() {
+        let mut seen = std::collections::HashSet::new();
+        for i in 0..4096 {
+            let slot = hash_slot_with_count(&format!("hero:{}", i), 256).unwrap();
+            assert!(slot < 256);
+            seen.insert(slot);
+        }
+        // Con 4096 claves se espera cubrir casi todos los slots
+        assert!(seen.len() > 240);
+    }
+
+    #[test]
+    fn test_slot_count_masks_default_slot() {
+        // Con potencias de dos, la máscara coincide con el módulo
+        let slot = hash_slot("Kiriko").unwrap();
+        assert_eq!(hash_slot_with_count("Kiriko", 256).unwrap(), slot % 256);
+    }
+
     #[test]
     fn test_max_hash_slots_constant() {
         assert_eq!(MAX_HASH_SLOTS, 16384);
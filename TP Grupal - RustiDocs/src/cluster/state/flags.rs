@@ -125,6 +125,29 @@ impl NodeFlags {
         format!("({},{},{},{})", myself, role, state, noaddr)
     }
 
+    /// Flags en el formato de `CLUSTER NODES`, separadas por comas, o
+    /// `noflags` si no hay ninguna.
+    pub fn cluster_nodes_format(&self) -> String {
+        let names = [
+            (ME, "myself"),
+            (MASTER, "master"),
+            (SLAVE, "slave"),
+            (PFAIL, "fail?"),
+            (FAIL, "fail"),
+            (HANDSHAKE, "handshake"),
+            (NOADDR, "noaddr"),
+        ];
+        let flags: Vec<&str> = names
+            .iter()
+            .filter(|(flag, _)| self.is_set(*flag))
+            .map(|(_, name)| *name)
+            .collect();
+        if flags.is_empty() {
+            return "noflags".to_string();
+        }
+        flags.join(",")
+    }
+
     pub fn state_contains(state: u8, flag: u8) -> bool {
         (state & flag) == flag
     }
@@ -153,4 +176,18 @@ mod tests {
         flags.unset(FAIL);
         assert!(!flags.is_set(FAIL));
     }
+
+    #[test]
+    fn test_cluster_nodes_format() {
+        let mut flags = NodeFlags::new();
+        assert_eq!(flags.cluster_nodes_format(), "noflags");
+
+        flags.set(ME);
+        flags.set(MASTER);
+        assert_eq!(flags.cluster_nodes_format(), "myself,master");
+
+        flags.set(SLAVE);
+        flags.set(PFAIL);
+        assert_eq!(flags.cluster_nodes_format(), "myself,slave,fail?");
+    }
 }
@@ -1,2 +1,2 @@
 pub(crate) mod flags;
-pub(crate) mod node_data;
+pub mod node_data;
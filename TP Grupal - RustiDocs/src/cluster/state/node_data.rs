@@ -12,9 +12,21 @@ use crate::cluster::types::{Epoch, NodeIp};
 use crate::cluster::types::{NodeId, TimeStamp};
 use crate::cluster::utils::system_time_to_i64;
 use crate::config::node_configs::NodeConfigs;
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::time::SystemTime;
 
+/// Estado de un slot que se está moviendo entre nodos durante un
+/// resharding.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlotMigration {
+    /// El slot es de este nodo y se está moviendo al nodo indicado. Las
+    /// claves que ya no están acá se redirigen con `ASK`.
+    Migrating(NodeId),
+    /// El slot se está trayendo desde el nodo indicado.
+    Importing(NodeId),
+}
+
 #[derive(Debug, Clone)]
 pub struct NodeData {
     node_id: NodeId,
@@ -26,6 +38,10 @@ pub struct NodeData {
     node_flags: NodeFlags,
     master_id: Option<NodeId>,
     last_update_time: TimeStamp,
+    migrations: HashMap<u16, SlotMigration>,
+    /// En un master, la cantidad de escrituras aplicadas; en una réplica,
+    /// el offset del master en su última sincronización.
+    replication_offset: u64,
 }
 
 impl NodeData {
@@ -46,6 +62,8 @@ impl NodeData {
             node_flags,
             master_id: None,
             last_update_time: -1,
+            migrations: HashMap::new(),
+            replication_offset: 0,
         }
     }
 
@@ -81,7 +99,7 @@ impl NodeData {
     }
 
     pub fn get_slots_len(&self) -> u16 {
-        self.slot_range.1 - self.slot_range.0
+        self.slot_range.1.saturating_sub(self.slot_range.0)
     }
 
     pub fn set_slots(&mut self, range: SlotRange) {
@@ -146,4 +164,44 @@ impl NodeData {
     pub fn set_last_update_time(&mut self, time: TimeStamp) {
         self.last_update_time = time;
     }
+
+    pub fn get_replication_offset(&self) -> u64 {
+        self.replication_offset
+    }
+
+    /// Cuenta una escritura aplicada por el master.
+    ///
+    /// # Retorna
+    ///
+    /// El offset que deben alcanzar las réplicas para tener la escritura.
+    pub fn advance_replication_offset(&mut self) -> u64 {
+        self.replication_offset += 1;
+        self.replication_offset
+    }
+
+    pub fn set_replication_offset(&mut self, offset: u64) {
+        self.replication_offset = offset;
+    }
+
+    /// Marca `slot` como migrando hacia el nodo `target`.
+    pub fn set_migrating(&mut self, slot: u16, target: NodeId) {
+        self.migrations
+            .insert(slot, SlotMigration::Migrating(target));
+    }
+
+    /// Marca `slot` como importándose desde el nodo `source`.
+    pub fn set_importing(&mut self, slot: u16, source: NodeId) {
+        self.migrations
+            .insert(slot, SlotMigration::Importing(source));
+    }
+
+    /// Da por terminada la migración de `slot`, sea cual sea su estado.
+    pub fn clear_migration(&mut self, slot: u16) {
+        self.migrations.remove(&slot);
+    }
+
+    /// Estado de migración de `slot`, `None` si no se está moviendo.
+    pub fn get_migration(&self, slot: u16) -> Option<&SlotMigration> {
+        self.migrations.get(&slot)
+    }
 }
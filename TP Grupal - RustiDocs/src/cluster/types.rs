@@ -1,9 +1,11 @@
+use crate::cluster::comms::node_input::NODAL_COMMS_PORT;
 use crate::cluster::state::flags::{CONNECTED, FAIL, HANDSHAKE, MASTER, NOADDR, PFAIL, SLAVE};
 use crate::cluster::utils::{
     read_payload_from_buffer, read_string_from_buffer, read_u8_from_buffer, read_u16_from_buffer,
     system_time_to_i64,
 };
 use crate::cluster::{comms::gossip_message::GossipEntry, state::flags::NodeFlags};
+use rand::RngCore;
 use std::collections::{HashMap, HashSet};
 use std::io::Read;
 use std::net::SocketAddr;
@@ -11,8 +13,14 @@ use std::sync::{Arc, RwLock};
 use std::time::SystemTime;
 
 pub type NodeId = String;
+/// Largo de un ID de nodo: 40 caracteres hexadecimales, como en Redis
+pub const NODE_ID_LEN: usize = 40;
 pub type NodeIp = String;
+/// Rango inclusivo de hash slots `(inicio, fin)`
 pub type SlotRange = (u16, u16);
+/// Rango sin slots, el de los nodos que no atienden ninguno. `(0, 0)` es
+/// el rango con sólo el slot 0
+pub const EMPTY_SLOT_RANGE: SlotRange = (1, 0);
 pub type TimeStamp = i64;
 pub type Epoch = u64;
 pub const GOSSIP_TYPE: u8 = 0; // Tipo de mensaje para gossip
@@ -22,11 +30,24 @@ pub const FAIL_TYPE: u8 = 3;
 pub const PUBSUB_TYPE: u8 = 4;
 pub const PROMOTION_TYPE: u8 = 5; // Tipo de mensaje para promoción de réplicas
 pub const REQUEST_PSYNC_TYPE: u8 = 6; // Tipo de mensaje para solicitud de PSYNC
-pub const NEW_MASTER_TYPE: u8 = 7;
+pub const NEW_MASTER_TYPE: u8 = 7; // Tipo de mensaje para failover manual
 pub const CONNECTION_CLOSE_TYPE: u8 = 0xFF;
 pub const MESSAGE_DELIMITER: &[u8; 5] = b"<END>";
 pub const DEFAULT_BUFFER_SIZE: usize = 8192;
 
+/// Genera un ID de nodo aleatorio de `NODE_ID_LEN` caracteres hexadecimales.
+pub fn generate_node_id() -> NodeId {
+    let mut bytes = [0u8; NODE_ID_LEN / 2];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Verifica que el ID tenga `NODE_ID_LEN` caracteres hexadecimales en
+/// minúscula.
+pub fn is_valid_node_id(id: &str) -> bool {
+    id.len() == NODE_ID_LEN && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
 pub struct NodeMessage {
     src_id: NodeId,
     src_ip: NodeIp,
@@ -146,6 +167,8 @@ pub struct KnownNode {
     replicas_ids: Vec<NodeId>,
     pfail_ids: HashSet<NodeId>,
     replaced: bool,
+    /// Offset de replicación que la réplica confirmó en su último PSYNC
+    replication_offset: u64,
 }
 
 impl KnownNode {
@@ -164,6 +187,7 @@ impl KnownNode {
             replicas_ids: vec![],
             pfail_ids: HashSet::new(),
             replaced: false,
+            replication_offset: 0,
         }
     }
 
@@ -186,6 +210,7 @@ impl KnownNode {
             replicas_ids: vec![], // TODO: entry.get_replicas(),
             pfail_ids: HashSet::new(),
             replaced: entry.was_replaced(),
+            replication_offset: 0,
         }
     }
 
@@ -245,7 +270,7 @@ impl KnownNode {
     }
 
     pub fn get_slots_len(&self) -> u16 {
-        self.slots.1 - self.slots.0
+        self.slots.1.saturating_sub(self.slots.0)
     }
 
     pub fn contains(&self, slot: &u16) -> bool {
@@ -260,6 +285,15 @@ impl KnownNode {
         aux.parse().unwrap()
     }
 
+    /// Dirección en la que el nodo atiende clientes. `node_port` es el
+    /// puerto del bus del cluster, `NODAL_COMMS_PORT` por encima del de
+    /// clientes.
+    pub fn get_client_addr(&self) -> SocketAddr {
+        let mut addr = self.get_addr();
+        addr.set_port(self.node_port.saturating_sub(NODAL_COMMS_PORT));
+        addr
+    }
+
     pub fn set_hash_slots(&mut self, slots: SlotRange) {
         self.slots = slots;
     }
@@ -310,16 +344,27 @@ impl KnownNode {
         self.master_id = None;
     }
 
+    /// Convierte un master en réplica del nodo dado (failover manual)
+    pub fn demote_to_replica(&mut self, master_id: NodeId) {
+        self.flags.unset(MASTER);
+        self.flags.set(SLAVE);
+        self.master_id = Some(master_id);
+        self.clear_slots();
+    }
+
     /// Limpia los slots asignados (para nodos fallidos)
     pub fn clear_slots(&mut self) {
-        self.slots = (0, 0);
+        self.slots = EMPTY_SLOT_RANGE;
     }
 
-    /// Obtiene el offset de replicación (simulado)
-    /// En una implementación real, esto vendría del estado de replicación
+    /// Obtiene el offset de replicación que confirmó la réplica. Sólo lo
+    /// conoce su master, que lo recibe en cada PSYNC.
     pub fn get_replication_offset(&self) -> u64 {
-        // Simulamos que las réplicas más recientes tienen mayor offset
-        self.last_pong_received as u64
+        self.replication_offset
+    }
+
+    pub fn set_replication_offset(&mut self, offset: u64) {
+        self.replication_offset = offset;
     }
 
     pub fn set_pfail(&mut self) {
@@ -366,6 +411,20 @@ impl KnownNode {
         self.replicas_ids.len()
     }
 
+    pub fn get_config_epoch(&self) -> Epoch {
+        self.config_epoch
+    }
+
+    /// Último PING enviado, -1 si nunca se envió.
+    pub fn get_last_ping_time(&self) -> TimeStamp {
+        self.last_ping_sent
+    }
+
+    /// Último PONG recibido, -1 si nunca se recibió.
+    pub fn get_last_pong_time(&self) -> TimeStamp {
+        self.last_pong_received
+    }
+
     pub fn set_last_ping_time(&mut self) {
         self.last_ping_sent = system_time_to_i64(SystemTime::now());
     }
@@ -406,6 +465,10 @@ impl KnownNode {
         self.last_ds_updated
     }
 
+    pub fn set_last_update_time(&mut self, time: TimeStamp) {
+        self.last_ds_updated = time;
+    }
+
     pub fn set_as_replaced(&mut self) {
         self.replaced = true;
     }
@@ -419,6 +482,8 @@ impl KnownNode {
     }
 }
 
+/// Dirección de clientes del nodo conocido que atiende `slot`, para
+/// responder `MOVED`.
 pub fn get_node_ip_for_slot(
     slot: u16,
     known_nodes: &Arc<RwLock<HashMap<NodeId, KnownNode>>>,
@@ -427,12 +492,37 @@ pub fn get_node_ip_for_slot(
     for (_node_id, neighbor) in known_nodes_aux.iter() {
         println!("[NODES] Conocido {:?}, slots {:?}", neighbor, slot);
         if neighbor.contains(&slot) {
-            // Assuming KnownNode has `ip` and `port` fields similar to NodeSettings
-            let addr_str = format!("{}:{}", neighbor.node_ip, neighbor.node_port);
-            if let Ok(addr) = addr_str.parse() {
-                return Some(addr);
-            }
+            return Some(neighbor.get_client_addr());
         }
     }
     None
 }
+
+/// Indica si el rango no tiene slots, como `EMPTY_SLOT_RANGE`.
+pub fn is_empty_slot_range(range: SlotRange) -> bool {
+    range.0 > range.1
+}
+
+/// Indica si entre el rango propio y los de los nodos conocidos se cubren
+/// los `slot_count` hash slots del cluster. Los rangos vacíos se ignoran.
+pub fn slots_fully_covered(
+    own_slots: SlotRange,
+    known_nodes: &Arc<RwLock<HashMap<NodeId, KnownNode>>>,
+    slot_count: u16,
+) -> bool {
+    let mut ranges = vec![own_slots];
+    if let Ok(known_nodes_aux) = known_nodes.read() {
+        ranges.extend(known_nodes_aux.values().map(|node| node.get_slots()));
+    }
+    ranges.retain(|range| !is_empty_slot_range(*range));
+    ranges.sort();
+
+    let mut next_slot: u32 = 0;
+    for (start, end) in ranges {
+        if start as u32 > next_slot {
+            return false;
+        }
+        next_slot = next_slot.max(end as u32 + 1);
+    }
+    next_slot >= slot_count as u32
+}
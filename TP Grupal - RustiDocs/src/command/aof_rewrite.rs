@@ -0,0 +1,263 @@
+//! Reescritura del append only file (BGREWRITEAOF).
+//!
+//! Compacta el contenido del store en la secuencia mínima de comandos que
+//! lo reconstruye: un `SET` por string, un `RPUSH` por lista, un `SADD`
+//! por set, un `HSET` por hash, un `GEOADD` por sorted set y, por stream,
+//! un `XADD` por entrada más un `XGROUP CREATE` por grupo. Los comandos se guardan en formato RESP, de modo que el
+//! archivo se reproduce con el mismo parser que atiende a los clientes.
+//!
+//! Los TTL se guardan como un `EXPIRE` con los segundos restantes al
+//! reescribir, redondeados hacia arriba; las claves ya vencidas no se
+//! escriben. El archivo no guarda las entradas pendientes de los grupos
+//! de consumidores, que se reponen al volver a leerlas.
+
+use crate::command::{Instruction, TryFrom};
+use crate::network::RespMessage;
+use crate::network::resp_parser::parse_resp_line;
+use crate::storage::DataStore;
+use crate::storage::geo;
+use std::fs::{File, rename};
+use std::io::{BufRead, BufWriter, Write};
+
+/// Estado de las reescrituras del AOF, compartido con el hilo que la hace.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AofRewriteStatus {
+    /// Hay una reescritura en curso
+    pub in_progress: bool,
+    /// La última reescritura terminada fue exitosa. Sin reescrituras
+    /// previas se considera exitosa, como en Redis.
+    pub last_ok: bool,
+}
+
+impl Default for AofRewriteStatus {
+    fn default() -> Self {
+        Self {
+            in_progress: false,
+            last_ok: true,
+        }
+    }
+}
+
+/// Compacta el store en los comandos que lo reconstruyen.
+///
+/// Las claves se recorren en orden para que dos reescrituras del mismo
+/// store generen el mismo archivo. Los `EXPIRE` van al final, cuando todas
+/// las claves ya existen.
+///
+/// # Returns
+///
+/// Cada comando como su nombre seguido de sus argumentos
+pub fn compact(store: &DataStore) -> Vec<Vec<String>> {
+    let mut commands = Vec::new();
+    let live = |key: &&String| !store.is_expired(key);
+
+    let mut strings: Vec<_> = store
+        .string_db
+        .iter()
+        .filter(|(key, _)| live(key))
+        .collect();
+    strings.sort();
+    for (key, value) in strings {
+        commands.push(vec!["SET".to_string(), key.clone(), value.clone()]);
+    }
+
+    let mut lists: Vec<_> = store.list_db.iter().filter(|(key, _)| live(key)).collect();
+    lists.sort();
+    for (key, items) in lists.into_iter().filter(|(_, items)| !items.is_empty()) {
+        let mut command = vec!["RPUSH".to_string(), key.clone()];
+        command.extend(items.iter().cloned());
+        commands.push(command);
+    }
+
+    let mut sets: Vec<_> = store.set_db.iter().filter(|(key, _)| live(key)).collect();
+    sets.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, members) in sets.into_iter().filter(|(_, members)| !members.is_empty()) {
+        let mut members: Vec<_> = members.iter().cloned().collect();
+        members.sort();
+        let mut command = vec!["SADD".to_string(), key.clone()];
+        command.extend(members);
+        commands.push(command);
+    }
+
+    let mut hashes: Vec<_> = store.hash_db.iter().filter(|(key, _)| live(key)).collect();
+    hashes.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, hash) in hashes.into_iter().filter(|(_, hash)| !hash.is_empty()) {
+        let mut fields: Vec<_> = hash.iter().collect();
+        fields.sort();
+        let mut command = vec!["HSET".to_string(), key.clone()];
+        for (field, value) in fields {
+            command.push(field.clone());
+            command.push(value.clone());
+        }
+        commands.push(command);
+    }
+
+    // Los sorted sets sólo se crean con GEOADD, así que cada score es un
+    // geohash. Se escribe el centro de su celda, que vuelve a codificarse
+    // en el mismo geohash.
+    let mut zsets: Vec<_> = store.zset_db.iter().filter(|(key, _)| live(key)).collect();
+    zsets.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, zset) in zsets.into_iter().filter(|(_, zset)| !zset.is_empty()) {
+        let mut members: Vec<_> = zset.iter().collect();
+        members.sort_by(|a, b| a.0.cmp(b.0));
+        let mut command = vec!["GEOADD".to_string(), key.clone()];
+        for (member, score) in members {
+            let (lon, lat) = geo::decode(*score as u64);
+            command.push(lon.to_string());
+            command.push(lat.to_string());
+            command.push(member.clone());
+        }
+        commands.push(command);
+    }
+
+    let mut streams: Vec<_> = store
+        .stream_db
+        .iter()
+        .filter(|(key, _)| live(key))
+        .collect();
+    streams.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, stream) in streams {
+        for (id, fields) in stream.entries() {
+            let mut command = vec!["XADD".to_string(), key.clone(), id.to_string()];
+            for (field, value) in fields {
+                command.push(field.clone());
+                command.push(value.clone());
+            }
+            commands.push(command);
+        }
+        let mut groups: Vec<_> = stream.groups().iter().collect();
+        groups.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, group) in groups {
+            commands.push(vec![
+                "XGROUP".to_string(),
+                "CREATE".to_string(),
+                key.clone(),
+                name.clone(),
+                group.last_delivered.to_string(),
+                "MKSTREAM".to_string(),
+            ]);
+        }
+    }
+
+    let mut expires: Vec<_> = store
+        .expires
+        .keys()
+        .filter(|key| live(key) && store.contains_key(key))
+        .collect();
+    expires.sort();
+    for key in expires {
+        if let Some(ttl) = store.remaining_ttl(key) {
+            let seconds = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+            commands.push(vec!["EXPIRE".to_string(), key.clone(), seconds.to_string()]);
+        }
+    }
+    commands
+}
+
+/// Codifica un comando como array RESP de bulk strings.
+fn encode(command: &[String]) -> Vec<u8> {
+    RespMessage::Array(
+        command
+            .iter()
+            .map(|arg| RespMessage::BulkString(Some(arg.as_bytes().to_vec())))
+            .collect(),
+    )
+    .as_bytes()
+}
+
+/// Reescribe el AOF en `dst` a partir del store.
+///
+/// El archivo se escribe primero en `dst.tmp` y recién completo reemplaza
+/// al anterior, para que una reescritura fallida no lo deje a medias.
+pub fn rewrite(store: &DataStore, dst: &str) -> Result<(), std::io::Error> {
+    let tmp = format!("{}.tmp", dst);
+    let mut writer = BufWriter::new(File::create(&tmp)?);
+    for command in compact(store) {
+        writer.write_all(&encode(&command))?;
+    }
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    rename(&tmp, dst)
+}
+
+/// Reproduce un AOF sobre un store vacío.
+///
+/// Una entrada mal formada o que falla al aplicarse aborta la carga. Sólo
+/// se descarta una última entrada cortada, la que deja una caída a mitad
+/// de un append.
+///
+/// # Returns
+///
+/// El store reconstruido, o un error que indica qué comando falló
+pub fn replay<R: BufRead>(reader: &mut R) -> Result<DataStore, String> {
+    let mut store = DataStore::new();
+    loop {
+        let message = match parse_resp_line(reader) {
+            Ok(message) => message,
+            Err(e) if e.is_connection_error() => break,
+            Err(e) => return Err(e.to_string()),
+        };
+        apply(message, &mut store)?;
+    }
+    Ok(store)
+}
+
+/// Aplica sobre el store un comando leído del AOF.
+///
+/// # Returns
+///
+/// Un error que indica qué comando falló, si no se pudo aplicar
+pub(crate) fn apply(message: RespMessage, store: &mut DataStore) -> Result<(), String> {
+    let instruction = Instruction::try_from(message).map_err(|e| e.to_string())?;
+    let command = instruction.to_command().map_err(|e| e.to_string())?;
+    command
+        .execute_write(store)
+        .map_err(|e| format!("{}: {}", command.to_string(), e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::stream::StreamId;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_compacted_store_replays_to_same_data() {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Junker Queen".to_string());
+        store.list_db.insert(
+            "Tanks".to_string(),
+            vec!["Reinhardt".to_string(), "Winston".to_string()],
+        );
+        store.set_db.insert(
+            "Maps".to_string(),
+            ["Junkertown", "Rialto"].map(String::from).into(),
+        );
+        let stream = store.stream_db.entry("Feed".to_string()).or_default();
+        stream
+            .add(
+                Some(StreamId::new(1, 0)),
+                vec![("kill".to_string(), "Roadhog".to_string())],
+                0,
+            )
+            .unwrap();
+        stream.create_group("casters", StreamId::new(1, 0)).unwrap();
+        store.hash_db.insert(
+            "Ultimates".to_string(),
+            [("Ramattra", "Annihilation"), ("Mauga", "Cage Fight")]
+                .map(|(hero, ult)| (hero.to_string(), ult.to_string()))
+                .into(),
+        );
+        let kings_row = geo::encode(-0.1278, 51.5074).unwrap() as f64;
+        store.zset_db.insert(
+            "Maps:geo".to_string(),
+            [("King's Row".to_string(), kings_row)].into(),
+        );
+
+        let bytes: Vec<u8> = compact(&store).iter().flat_map(|c| encode(c)).collect();
+        let replayed = replay(&mut Cursor::new(bytes)).unwrap();
+
+        assert!(replayed.has_same_data(&store));
+    }
+}
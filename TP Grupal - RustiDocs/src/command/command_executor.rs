@@ -11,35 +11,189 @@
 //! - Redirección automática de comandos a nodos correctos
 //! - Manejo de snapshots automáticos
 //! - Integración con sistema PubSub
+//! - Lecturas bloqueantes de streams (XREAD BLOCK)
+//! - Pausa de clientes para ventanas de mantenimiento (CLIENT PAUSE)
+//! - Monitor de eventos de latencia (LATENCY)
+//! - Failover manual coordinado con una réplica (FAILOVER)
+//! - Espera de confirmación de las réplicas (WAIT)
+//! - Cambio de rol en tiempo de ejecución (REPLICAOF)
+//! - Sincronización completa hacia otra conexión (SYNC)
 
 // IMPORTS
+use crate::cluster::comms::node_input::NODAL_COMMS_PORT;
+use crate::cluster::comms::psync_sender::psync_send;
+use crate::cluster::comms::replica_promotion::{PromotionMessage, apply_manual_failover};
 use crate::cluster::state::flags::{MASTER, NodeFlags};
-use crate::cluster::types::get_node_ip_for_slot;
+use crate::cluster::types::{
+    NEW_MASTER_TYPE, NodeMessage, get_node_ip_for_slot, slots_fully_covered,
+};
 use crate::{
     cluster::{
-        sharding::hash_slot::hash_slot,
-        state::node_data::NodeData,
+        sharding::hash_slot::{hash_slot_with_count, keys_same_slot_with_count},
+        state::node_data::{NodeData, SlotMigration},
         types::{KnownNode, NodeId},
     },
     command::ResponseType,
     command::{
         Instruction,
+        aof_rewrite::AofRewriteStatus,
+        command_table::{COMMAND_TABLE, find_command},
         commands::*,
-        types::{Command, PubSubContext},
+        instruction::{ArgumentError, InstructionError},
+        invariants::{check_slot_ownership, check_store},
+        latency::{COMMAND_EVENT, LatencyMonitor, SAVE_EVENT},
+        types::{
+            ClientSubcommand, Command, CommandSubcommand, DebugSubcommand, FailoverOptions,
+            LatencySubcommand, ObjectSubcommand, PauseMode, PubSubContext, PubsubSubcommand,
+            TtlPolicy,
+        },
     },
     config::node_configs::NodeConfigs,
     logs::aof_logger::AofLogger,
-    network::resp_message::RespMessage,
-    storage::{data_store::DataStore, snapshot_manager::create_dump},
+    network::{
+        connection_handler::{ClientFlags, ClientRegistry, Transaction},
+        output_buffer::ClientClass,
+        resp_message::{RespMessage, RespProtocol},
+    },
+    storage::{
+        data_store::DataStore, deserializer::deserialize_db, serializer::serialize_to_bytes,
+        snapshot_manager::create_dump,
+    },
 };
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
     sync::{
-        Arc, RwLock,
-        mpsc::{Receiver, Sender},
+        Arc, Mutex, PoisonError, RwLock,
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
     },
+    thread,
+    time::{Duration, Instant},
 };
 
+// MENSAJES DE ERROR
+const ERR_CLUSTER_DOWN: &str = "CLUSTERDOWN The cluster is down";
+const ERR_SLOT_NOT_SERVED: &str = "CLUSTERDOWN Hash slot not served";
+const ERR_CROSSSLOT: &str = "CROSSSLOT Keys in request don't hash to the same slot";
+const ERR_RELOAD_MISMATCH: &str = "ERR DEBUG RELOAD round trip changed the dataset";
+const ERR_FAILOVER_ON_REPLICA: &str = "ERR FAILOVER is not valid when server is a replica.";
+const ERR_FAILOVER_IN_PROGRESS: &str = "ERR FAILOVER already in progress.";
+const ERR_FAILOVER_NO_REPLICAS: &str = "ERR FAILOVER requires connected replicas.";
+const ERR_FAILOVER_TARGET: &str = "ERR FAILOVER target HOST and PORT is not a replica.";
+const ERR_NO_FAILOVER: &str = "ERR No failover in progress.";
+const ERR_WAIT_ON_REPLICA: &str = "ERR WAIT cannot be used with replica instances.";
+const ERR_UNKNOWN_MASTER: &str = "ERR Unknown node";
+const ERR_REPLICATE_REPLICA: &str = "ERR I can only replicate a master, not a replica.";
+const ERR_REPLICATE_MYSELF: &str = "ERR Can't replicate myself";
+const ERR_NOT_INTEGER: &str = "ERR value is not an integer or out of range";
+const ERR_NOPROTO: &str = "NOPROTO unsupported protocol version";
+const ERR_AOF_REWRITE_IN_PROGRESS: &str =
+    "ERR Background append only file rewriting already in progress";
+const ERR_OOM: &str = "OOM command not allowed when used memory > 'maxmemory'.";
+const ERR_NESTED_MULTI: &str = "ERR MULTI calls can not be nested";
+const ERR_EXEC_WITHOUT_MULTI: &str = "ERR EXEC without MULTI";
+const ERR_DISCARD_WITHOUT_MULTI: &str = "ERR DISCARD without MULTI";
+const ERR_EXECABORT: &str = "EXECABORT Transaction discarded because of previous errors.";
+
+/// Máximo de claves vencidas que borra la expiración activa por instrucción.
+const ACTIVE_EXPIRE_LIMIT: usize = 20;
+
+/// Prefijos de los canales de notificaciones de keyspace. El servidor tiene
+/// una sola base, la 0.
+const KEYSPACE_CHANNEL_PREFIX: &str = "__keyspace@0__:";
+const KEYEVENT_CHANNEL_PREFIX: &str = "__keyevent@0__:";
+
+/// Eventos de keyspace que no corresponden a un comando del cliente.
+const EXPIRED_EVENT: &str = "expired";
+const EVICTED_EVENT: &str = "evicted";
+
+/// Ayuda de `CLUSTER HELP`.
+const CLUSTER_HELP: &str = "CLUSTER <subcommand> [<arg> [value] [opt] ...]. Subcommands are:
+SLOTS
+    Return information about slots range mappings. Each range is made of:
+    start, end, master and replicas IP addresses, ports and ids.
+MYID
+    Return the node id.
+NODES
+    Return cluster configuration seen by node. Output format:
+    <id> <ip:port@cport> <flags> <master> <pings> <pongs> <epoch> <link> <slot> ...
+HELP
+    Print this help.";
+
+/// Indica si `node` es el nodo que atiende clientes en `host:port`. Los
+/// nodos se conocen por su puerto del bus del cluster.
+fn node_listens_on(node: &KnownNode, host: &str, port: u16) -> bool {
+    let addr = node.get_addr();
+    addr.ip().to_string() == host && port.checked_add(NODAL_COMMS_PORT) == Some(addr.port())
+}
+
+/// Arma la respuesta de `CLIENT LIST`: una línea por conexión, ordenadas
+/// por id, con `id`, `addr`, `name` y `age` (segundos desde que se
+/// aceptó la conexión).
+fn format_client_list(clients: &HashMap<String, ClientFlags>) -> String {
+    let mut ids: Vec<&String> = clients.keys().collect();
+    ids.sort();
+    ids.into_iter()
+        .map(|id| {
+            let flags = &clients[id];
+            format!(
+                "id={} addr={} name={} age={}\n",
+                id,
+                flags
+                    .addr
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_else(|| "?".to_string()),
+                flags.name.as_deref().unwrap_or(""),
+                flags
+                    .connected_at
+                    .map(|at| at.elapsed().as_secs())
+                    .unwrap_or(0)
+            )
+        })
+        .collect()
+}
+
+/// Responde `COMMAND COUNT` y `COMMAND DOCS`.
+///
+/// `DOCS` devuelve, como pares nombre-valor en un array plano, cada
+/// comando en minúsculas con su `arity`, sus `flags` y su `group`. Los
+/// nombres que no corresponden a un comando soportado se omiten.
+fn execute_command_info(subcommand: &CommandSubcommand) -> RespMessage {
+    let names = match subcommand {
+        CommandSubcommand::Count => return RespMessage::Integer(COMMAND_TABLE.len() as i64),
+        CommandSubcommand::Docs(names) => names,
+    };
+    let specs: Vec<_> = if names.is_empty() {
+        COMMAND_TABLE.iter().collect()
+    } else {
+        names.iter().filter_map(|name| find_command(name)).collect()
+    };
+
+    let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+    let mut docs = Vec::with_capacity(specs.len() * 2);
+    for spec in specs {
+        docs.push(text(&spec.name.to_lowercase()));
+        docs.push(RespMessage::Array(vec![
+            text("arity"),
+            RespMessage::Integer(spec.arity),
+            text("flags"),
+            RespMessage::Array(spec.flags().into_iter().map(text).collect()),
+            text("group"),
+            text(&spec.group()),
+        ]));
+    }
+    RespMessage::Array(docs)
+}
+
+/// Cada cuánto se revisa si la réplica de un failover en curso se puso al día.
+const FAILOVER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Cada cuánto se revisan las confirmaciones de las réplicas para `WAIT`.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Canal de salida hacia el bus del cluster.
+pub type ClusterBus = Sender<(NodeId, SocketAddr, Option<Vec<u8>>)>;
+
 /// Errores específicos que pueden ocurrir durante la ejecución de comandos.
 #[derive(Debug)]
 pub enum CommandExecutorError {
@@ -49,8 +203,9 @@ pub enum CommandExecutorError {
     /// Error al escribir en el DataStore
     DataStoreWriteError(String),
 
-    /// Error al convertir instrucción a comando
-    CommandConversionError(String),
+    /// Error al convertir instrucción a comando, con el mensaje para el
+    /// cliente y el argumento que lo causó, si se conoce
+    CommandConversionError(String, Option<ArgumentError>),
 
     /// Error al calcular hash slot
     HashSlotError(String),
@@ -86,9 +241,7 @@ impl std::fmt::Display for CommandExecutorError {
             CommandExecutorError::DataStoreWriteError(msg) => {
                 write!(f, "Error al escribir en DataStore: {}", msg)
             }
-            CommandExecutorError::CommandConversionError(msg) => {
-                write!(f, "Error al convertir instrucción a comando: {}", msg)
-            }
+            CommandExecutorError::CommandConversionError(msg, _) => write!(f, "{}", msg),
             CommandExecutorError::HashSlotError(msg) => {
                 write!(f, "Error al calcular hash slot: {}", msg)
             }
@@ -124,12 +277,74 @@ impl std::fmt::Display for CommandExecutorError {
 impl std::error::Error for CommandExecutorError {}
 
 // CÓDIGO
+/// Cliente bloqueado en un `XREAD` con `BLOCK`, esperando entradas nuevas.
+struct BlockedClient {
+    /// ID de la conexión, para descartarla si se desconecta
+    client_id: String,
+    /// Lectura a reintentar
+    command: Command,
+    /// Momento en que vence la espera, `None` si no vence (`BLOCK 0`)
+    deadline: Option<Instant>,
+    /// Canal de respuesta de la conexión
+    response_sender: Sender<RespMessage>,
+}
+
+/// Cliente en un `WAIT`, esperando que suficientes réplicas confirmen
+/// sus escrituras.
+struct WaitingClient {
+    /// Offset de replicación de la última escritura del cliente
+    offset: u64,
+    /// Cantidad de réplicas que deben confirmarlo
+    numreplicas: u32,
+    /// Momento en que vence la espera, `None` si no vence (`WAIT n 0`)
+    deadline: Option<Instant>,
+    /// Canal de respuesta de la conexión
+    response_sender: Sender<RespMessage>,
+}
+
+/// Pausa activa establecida con `CLIENT PAUSE`.
+struct ClientPause {
+    /// Momento en que vence la pausa
+    until: Instant,
+    /// Comandos alcanzados por la pausa
+    mode: PauseMode,
+}
+
+/// Failover manual en curso, iniciado con `FAILOVER`.
+struct Failover {
+    /// Réplica que pasará a ser master
+    replica_id: NodeId,
+    /// Offset de replicación del master al pausar las escrituras. La
+    /// réplica está al día cuando confirma haberlo alcanzado
+    target_offset: u64,
+    /// Momento en que vence la espera (`TIMEOUT`)
+    deadline: Option<Instant>,
+    /// Promover la réplica al vencer la espera aunque no esté al día
+    force: bool,
+}
+
+/// Instrucción postergada por `CLIENT PAUSE`, a ejecutar al levantarse la pausa.
+struct PausedInstruction {
+    client_id: String,
+    instruction: Instruction,
+    response_sender: Sender<RespMessage>,
+}
+
 /// Estructura ejecutora de comandos, responsabilidades:
 /// * Guardar la base de datos del nodo.
 /// * Ejecutar las instrucciones recibidas.
 /// * Manejar redirecciones de comandos basadas en hash slots.
 /// * Crear snapshots automáticos.
 /// * Coordinar con el sistema PubSub.
+/// * Mantener los flags por conexión y la recencia de las claves.
+/// * Mantener en espera a los clientes bloqueados en lecturas de streams.
+/// * Mantener en espera a los clientes en `WAIT` hasta que las réplicas
+///   confirmen sus escrituras.
+/// * Postergar, en orden, los comandos alcanzados por `CLIENT PAUSE`.
+/// * Registrar los eventos que superan el umbral de latencia.
+/// * Coordinar los failovers manuales con una réplica.
+/// * Reenviar las escrituras a las conexiones sincronizadas con `SYNC`.
+/// * Reescribir el append only file en segundo plano.
 pub struct CommandExecutor {
     ds_guard: Arc<RwLock<DataStore>>,
     instruction_receiver: Receiver<(String, Instruction, Sender<RespMessage>)>,
@@ -139,6 +354,17 @@ pub struct CommandExecutor {
     pubsub_sender: Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
     nodes_list: Arc<RwLock<HashMap<NodeId, KnownNode>>>,
     data_lock: Arc<RwLock<NodeData>>,
+    clients: ClientRegistry,
+    blocked: Vec<BlockedClient>,
+    waiting: Vec<WaitingClient>,
+    /// Conexiones que hicieron `SYNC` y reciben cada escritura
+    sync_clients: Vec<Sender<RespMessage>>,
+    pause: Option<ClientPause>,
+    paused: VecDeque<PausedInstruction>,
+    latency: LatencyMonitor,
+    failover: Option<Failover>,
+    cluster_bus: Option<ClusterBus>,
+    aof_rewrite: Arc<Mutex<AofRewriteStatus>>,
 }
 
 impl CommandExecutor {
@@ -153,10 +379,12 @@ impl CommandExecutor {
     /// * `pubsub_sender` - Sender para comunicación PubSub
     /// * `nodes_list` - Lista de nodos conocidos
     /// * `data_lock` - Datos del nodo actual
+    /// * `clients` - Registro de estado por conexión
     ///
     /// # Retorna
     ///
     /// Una nueva instancia de `CommandExecutor`
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         ds_guard: Arc<RwLock<DataStore>>,
         instruction_receiver: Receiver<(String, Instruction, Sender<RespMessage>)>,
@@ -165,7 +393,9 @@ impl CommandExecutor {
         pubsub_sender: Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
         nodes_list: Arc<RwLock<HashMap<NodeId, KnownNode>>>,
         data_lock: Arc<RwLock<NodeData>>,
+        clients: ClientRegistry,
     ) -> Self {
+        let latency = LatencyMonitor::new(settings.get_latency_monitor_threshold());
         Self {
             ds_guard,
             instruction_receiver,
@@ -175,27 +405,423 @@ impl CommandExecutor {
             pubsub_sender,
             nodes_list,
             data_lock,
+            clients,
+            blocked: Vec::new(),
+            waiting: Vec::new(),
+            sync_clients: Vec::new(),
+            pause: None,
+            paused: VecDeque::new(),
+            latency,
+            failover: None,
+            cluster_bus: None,
+            aof_rewrite: Arc::new(Mutex::new(AofRewriteStatus::default())),
         }
     }
 
+    /// Configura el canal hacia el bus del cluster, por el que se anuncia
+    /// el cambio de roles al completarse un `FAILOVER`.
+    pub fn with_cluster_bus(mut self, cluster_bus: ClusterBus) -> Self {
+        self.cluster_bus = Some(cluster_bus);
+        self
+    }
+
     /// Ejecuta el bucle principal del ejecutor de comandos.
     ///
     /// Este método procesa instrucciones de forma continua hasta que
     /// recibe un client_id vacío, momento en el cual termina la ejecución.
+    /// Mientras haya clientes bloqueados con espera limitada, clientes en
+    /// `WAIT`, una pausa activa o un failover en curso, la recepción se
+    /// interrumpe al vencer la más próxima.
     pub fn run(&mut self) {
-        while let Ok((client_id, instruction, response_sender)) = self.instruction_receiver.recv() {
+        loop {
+            let received = match self.next_timeout() {
+                Some(timeout) => self.instruction_receiver.recv_timeout(timeout),
+                None => self
+                    .instruction_receiver
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            let (client_id, instruction, response_sender) = match received {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => {
+                    self.serve_blocked_clients();
+                    self.serve_waiting_clients();
+                    self.advance_failover();
+                    self.release_paused();
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
             if client_id.is_empty() {
                 self.logger.log_debug("Closing executor thread".to_string());
                 break;
             }
-            let pubsub_sender = self.pubsub_sender.clone();
-            let response =
-                self.execute_instruction(client_id, instruction, &pubsub_sender, &response_sender);
-            if let Err(e) = response_sender.send(response) {
+            self.handle_instruction(client_id, instruction, &response_sender);
+        }
+    }
+
+    /// Ejecuta una instrucción y envía su respuesta por el canal de la conexión.
+    ///
+    /// Si la conexión tiene una transacción abierta la instrucción se
+    /// encola hasta el `EXEC`. Si está alcanzada por `CLIENT PAUSE` se
+    /// posterga, junto con su respuesta, hasta que se levante la pausa.
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `instruction` - Instrucción a ejecutar
+    /// * `response_sender` - Canal de respuesta de la conexión
+    pub fn handle_instruction(
+        &mut self,
+        client_id: String,
+        instruction: Instruction,
+        response_sender: &Sender<RespMessage>,
+    ) {
+        if let Some(reply) = self.queue_in_transaction(&client_id, &instruction) {
+            if let Err(e) = response_sender.send(reply) {
+                self.logger
+                    .log_error(format!("Error sending response: {}", e));
+            }
+            return;
+        }
+        self.advance_failover();
+        self.release_paused();
+        if self.must_defer(&client_id, &instruction) {
+            self.paused.push_back(PausedInstruction {
+                client_id,
+                instruction,
+                response_sender: response_sender.clone(),
+            });
+            return;
+        }
+        self.process_instruction(client_id, instruction, response_sender);
+        // La instrucción pudo haber sido un CLIENT UNPAUSE o un FAILOVER
+        self.advance_failover();
+        self.release_paused();
+    }
+
+    /// Encola la instrucción si la conexión tiene una transacción abierta.
+    ///
+    /// `EXEC` y `DISCARD` no se encolan, cierran la transacción. Una
+    /// instrucción que no se puede convertir a comando responde su error
+    /// y hace que el `EXEC` descarte toda la transacción.
+    ///
+    /// # Retorna
+    ///
+    /// `Some(RespMessage)` con la respuesta inmediata (`QUEUED` o un
+    /// error), `None` si la instrucción debe ejecutarse normalmente
+    fn queue_in_transaction(
+        &self,
+        client_id: &str,
+        instruction: &Instruction,
+    ) -> Option<RespMessage> {
+        let mut clients = self.clients.write().ok()?;
+        let transaction = clients.get_mut(client_id)?.transaction.as_mut()?;
+        match instruction.to_command() {
+            Ok(Command::Exec | Command::Discard) => None,
+            Ok(Command::Multi) => Some(RespMessage::Error(ERR_NESTED_MULTI.to_string())),
+            Ok(_) => {
+                transaction.queued.push(instruction.clone());
+                Some(RespMessage::SimpleString("QUEUED".to_string()))
+            }
+            Err(e) => {
+                transaction.aborted = true;
+                let error = self.conversion_error(instruction, e);
+                Some(RespMessage::Error(error.to_string()))
+            }
+        }
+    }
+
+    /// Indica si la instrucción debe postergarse por la pausa activa.
+    ///
+    /// Un failover en curso pausa las escrituras como `CLIENT PAUSE WRITE`.
+    /// Los comandos `CLIENT` y `FAILOVER` nunca se postergan, para poder
+    /// levantar la pausa. Si el cliente ya tiene instrucciones postergadas,
+    /// las siguientes también se postergan para respetar su orden.
+    fn must_defer(&self, client_id: &str, instruction: &Instruction) -> bool {
+        let command = instruction.to_command();
+        if let Ok(Command::Client(_) | Command::Failover(_)) = command {
+            return false;
+        }
+        if self
+            .paused
+            .iter()
+            .any(|paused| paused.client_id == client_id)
+        {
+            return true;
+        }
+        match &self.pause {
+            Some(pause) => match command {
+                Ok(command) => {
+                    pause.mode == PauseMode::All || self.instruction_writes(client_id, &command)
+                }
+                Err(_) => pause.mode == PauseMode::All,
+            },
+            None => {
+                self.failover.is_some()
+                    && command.is_ok_and(|command| self.instruction_writes(client_id, &command))
+            }
+        }
+    }
+
+    /// Indica si el comando escribe en el store. Un `EXEC` escribe si lo
+    /// hace alguno de los comandos encolados en la transacción.
+    fn instruction_writes(&self, client_id: &str, command: &Command) -> bool {
+        if !matches!(command, Command::Exec) {
+            return command.writes_on_db();
+        }
+        self.clients.read().is_ok_and(|clients| {
+            clients
+                .get(client_id)
+                .and_then(|flags| flags.transaction.as_ref())
+                .is_some_and(|transaction| {
+                    transaction.queued.iter().any(|instruction| {
+                        instruction
+                            .to_command()
+                            .is_ok_and(|command| command.writes_on_db())
+                    })
+                })
+        })
+    }
+
+    /// Si la pausa venció o fue levantada y no hay un failover en curso,
+    /// ejecuta en orden las instrucciones postergadas y envía sus respuestas.
+    fn release_paused(&mut self) {
+        if self
+            .pause
+            .as_ref()
+            .is_some_and(|pause| pause.until <= Instant::now())
+        {
+            self.pause = None;
+        }
+        while let Some(paused) = self.paused.pop_front() {
+            if self.pause.is_some() || self.failover.is_some() {
+                // Una instrucción liberada volvió a pausar: el resto sigue esperando
+                self.paused.push_front(paused);
+                break;
+            }
+            self.process_instruction(
+                paused.client_id,
+                paused.instruction,
+                &paused.response_sender,
+            );
+        }
+    }
+
+    /// Ejecuta una instrucción que no está postergada.
+    ///
+    /// Un `XREAD` con `BLOCK` que no encuentra entradas no se responde:
+    /// el cliente queda en espera hasta que una escritura agregue entradas
+    /// o venza el tiempo indicado. Lo mismo un `WAIT` que todavía no tiene
+    /// las confirmaciones pedidas. Mientras tanto el resto de los clientes
+    /// se sigue atendiendo.
+    fn process_instruction(
+        &mut self,
+        client_id: String,
+        instruction: Instruction,
+        response_sender: &Sender<RespMessage>,
+    ) {
+        let command = instruction.to_command();
+        let block = match &command {
+            Ok(command @ Command::Xread(_, Some(block), _)) => {
+                Some((client_id.clone(), command.clone(), *block))
+            }
+            _ => None,
+        };
+        let wait = match command {
+            Ok(Command::Wait(numreplicas, timeout)) => {
+                Some((self.last_write_offset(&client_id), numreplicas, timeout))
+            }
+            _ => None,
+        };
+        let pubsub_sender = self.pubsub_sender.clone();
+        let response =
+            self.execute_instruction(client_id, instruction, &pubsub_sender, response_sender);
+
+        let acked = match response {
+            RespMessage::Integer(acked) => acked,
+            _ => i64::MAX,
+        };
+        match (block, wait) {
+            (Some((client_id, command, block)), _) if response == RespMessage::Null(None) => {
+                let deadline = (block > 0).then(|| Instant::now() + Duration::from_millis(block));
+                self.blocked.push(BlockedClient {
+                    client_id,
+                    command,
+                    deadline,
+                    response_sender: response_sender.clone(),
+                });
+            }
+            (_, Some((offset, numreplicas, timeout))) if acked < i64::from(numreplicas) => {
+                let deadline =
+                    (timeout > 0).then(|| Instant::now() + Duration::from_millis(timeout));
+                self.waiting.push(WaitingClient {
+                    offset,
+                    numreplicas,
+                    deadline,
+                    response_sender: response_sender.clone(),
+                });
+            }
+            _ => {
+                if let Err(e) = response_sender.send(response) {
+                    self.logger
+                        .log_error(format!("Error sending response: {}", e));
+                }
+                self.serve_blocked_clients();
+            }
+        }
+    }
+
+    /// Tiempo hasta que vence la espera más próxima de los clientes
+    /// bloqueados o la pausa activa, o hasta la próxima revisión del
+    /// failover o de las confirmaciones de las réplicas.
+    fn next_timeout(&self) -> Option<Duration> {
+        self.blocked
+            .iter()
+            .filter_map(|client| client.deadline)
+            .chain(self.pause.as_ref().map(|pause| pause.until))
+            .chain((!self.waiting.is_empty()).then(|| Instant::now() + WAIT_POLL_INTERVAL))
+            .chain(
+                self.failover
+                    .as_ref()
+                    .map(|_| Instant::now() + FAILOVER_POLL_INTERVAL),
+            )
+            .min()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Reintenta las lecturas de los clientes bloqueados. Responde a los que
+    /// ya tienen entradas nuevas y con nulo a los que vencieron.
+    ///
+    /// Los clientes que se desconectaron, porque ya no están en el registro
+    /// de conexiones o porque falla el envío de su respuesta, se descartan.
+    fn serve_blocked_clients(&mut self) {
+        if let Ok(clients) = self.clients.read() {
+            self.blocked
+                .retain(|client| clients.contains_key(&client.client_id));
+        }
+        if self.blocked.is_empty() {
+            return;
+        }
+        let guard = match self.ds_guard.read() {
+            Ok(guard) => guard,
+            Err(e) => {
+                self.logger
+                    .log_error(format!("Error serving blocked clients: {}", e));
+                return;
+            }
+        };
+
+        let now = Instant::now();
+        let mut still_blocked = Vec::new();
+        for client in std::mem::take(&mut self.blocked) {
+            let response = match client
+                .command
+                .execute_read(&guard, None, None, None, None, None)
+            {
+                Ok(ResponseType::Null(_)) if client.deadline.is_none_or(|d| d > now) => {
+                    still_blocked.push(client);
+                    continue;
+                }
+                Ok(response) => RespMessage::from_response(response),
+                Err(e) => RespMessage::Error(e.to_string()),
+            };
+            if client.response_sender.send(response).is_err() {
+                self.logger.log_notice(format!(
+                    "Blocked client {} disconnected, discarding its read",
+                    client.client_id
+                ));
+            }
+        }
+        self.blocked = still_blocked;
+    }
+
+    /// Responde a los clientes en `WAIT` que ya tienen las confirmaciones
+    /// pedidas, y a los que vencieron con las que se alcanzaron.
+    fn serve_waiting_clients(&mut self) {
+        if self.waiting.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        let mut still_waiting = Vec::new();
+        for client in std::mem::take(&mut self.waiting) {
+            let acked = self.acked_replicas(client.offset);
+            if acked < client.numreplicas as usize && client.deadline.is_none_or(|d| d > now) {
+                still_waiting.push(client);
+                continue;
+            }
+            let response = RespMessage::Integer(acked as i64);
+            if let Err(e) = client.response_sender.send(response) {
                 self.logger
                     .log_error(format!("Error sending response: {}", e));
             }
         }
+        self.waiting = still_waiting;
+    }
+
+    /// Cantidad de réplicas de este nodo que confirmaron el offset dado.
+    fn acked_replicas(&self, offset: u64) -> usize {
+        let my_id = match self.data_lock.read() {
+            Ok(myself) => myself.get_id(),
+            Err(_) => return 0,
+        };
+        self.nodes_list
+            .read()
+            .map(|nodes| {
+                nodes
+                    .values()
+                    .filter(|node| node.is_slave() && node.get_master_id() == Some(&my_id))
+                    .filter(|replica| replica.get_replication_offset() >= offset)
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    /// Offset de replicación de la última escritura de la conexión.
+    fn last_write_offset(&self, client_id: &str) -> u64 {
+        self.clients
+            .read()
+            .ok()
+            .and_then(|clients| clients.get(client_id).map(|flags| flags.last_write_offset))
+            .unwrap_or(0)
+    }
+
+    /// Avanza el offset de replicación del nodo tras una escritura y lo
+    /// registra como la última escritura de la conexión.
+    fn record_write(&self, client_id: &str) {
+        let offset = match self.data_lock.write() {
+            Ok(mut myself) => myself.advance_replication_offset(),
+            Err(_) => return,
+        };
+        let Ok(mut clients) = self.clients.write() else {
+            return;
+        };
+        if let Some(flags) = clients.get_mut(client_id) {
+            flags.last_write_offset = offset;
+        }
+    }
+
+    /// Ejecuta `WAIT`: responde cuántas réplicas ya confirmaron la última
+    /// escritura del cliente. Si no alcanzan, `process_instruction` deja al
+    /// cliente esperando.
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    ///
+    /// # Retorna
+    ///
+    /// `RespMessage` con la cantidad de réplicas, o error en una réplica
+    fn execute_wait_command(&self, client_id: &str) -> RespMessage {
+        let is_master = self
+            .data_lock
+            .read()
+            .is_ok_and(|myself| NodeFlags::state_contains(myself.get_state(), MASTER));
+        if !is_master {
+            return RespMessage::Error(ERR_WAIT_ON_REPLICA.to_string());
+        }
+        let acked = self.acked_replicas(self.last_write_offset(client_id));
+        RespMessage::Integer(acked as i64)
     }
 
     /// Formatea un error de lectura con contexto.
@@ -231,6 +857,84 @@ impl CommandExecutor {
         format!("ERROR on {} with {:?}, {}", ins_type, args, e)
     }
 
+    /// Respuesta a un comando aplicado sobre una clave de otro tipo.
+    ///
+    /// El cliente recibe el mensaje `WRONGTYPE` canónico, sin agregados;
+    /// el comando y sus argumentos sólo quedan registrados en el log.
+    ///
+    /// # Argumentos
+    ///
+    /// * `instruction` - Instrucción que falló
+    ///
+    /// # Retorna
+    ///
+    /// `RespMessage` con el error
+    fn wrong_type_reply(&self, instruction: &Instruction) -> RespMessage {
+        let error = CommandError::WrongType;
+        self.logger.log_debug(Self::format_op_error(
+            &instruction.instruction_type,
+            &instruction.arguments,
+            &error,
+        ));
+        RespMessage::Error(error.to_string())
+    }
+
+    /// Error de una instrucción que no pudo convertirse a comando.
+    ///
+    /// Los errores de aridad y de enteros usan los mensajes canónicos de
+    /// Redis; el comando, sus argumentos y el argumento culpable quedan en
+    /// el log. El resto conserva el detalle en el propio mensaje.
+    ///
+    /// # Argumentos
+    ///
+    /// * `instruction` - Instrucción que falló
+    /// * `error` - Error de la conversión
+    ///
+    /// # Retorna
+    ///
+    /// `CommandExecutorError::CommandConversionError` con el argumento culpable
+    fn conversion_error(
+        &self,
+        instruction: &Instruction,
+        error: InstructionError,
+    ) -> CommandExecutorError {
+        let argument = error.argument();
+        let detail = Self::format_op_error(
+            &instruction.instruction_type,
+            &instruction.arguments,
+            &error,
+        );
+        let message = match error {
+            _ if error.is_arity_error() => format!(
+                "ERR wrong number of arguments for '{}' command",
+                instruction.instruction_type.to_lowercase()
+            ),
+            InstructionError::ParseIntError(..) | InstructionError::IntegerOutOfRange => {
+                ERR_NOT_INTEGER.to_string()
+            }
+            _ => {
+                return CommandExecutorError::CommandConversionError(
+                    format!("Error al convertir instrucción a comando: {}", detail),
+                    argument,
+                );
+            }
+        };
+        match argument {
+            Some(argument) => self.logger.log_debug(format!(
+                "{} (argument {} {}: {:?})",
+                detail,
+                argument.index,
+                argument.reason,
+                instruction
+                    .arguments
+                    .get(argument.index)
+                    .map_or("<none>", String::as_str)
+            )),
+            None => self.logger.log_debug(detail),
+        }
+        CommandExecutorError::CommandConversionError(message, argument)
+    }
+
     /// Ejecuta un comando de lectura.
     ///
     /// # Argumentos
@@ -260,26 +964,30 @@ impl CommandExecutor {
             ))
         })?;
 
-        let response = command
-            .execute_read(
-                &guard,
-                Some(self.settings.clone()),
-                Some(self.logger.clone()),
-                Some(PubSubContext::new(
-                    client_id,
-                    pubsub_sender,
-                    response_sender,
-                )),
-                Some(&self.data_lock),
-                Some(&self.nodes_list),
-            )
-            .map_err(|e| {
-                CommandExecutorError::ReadCommandError(Self::format_op_error(
-                    &instruction.instruction_type,
-                    &instruction.arguments,
-                    &e,
-                ))
-            })?;
+        let response = match command.execute_read(
+            &guard,
+            Some(self.settings.clone()),
+            Some(self.logger.clone()),
+            Some(PubSubContext::new(
+                client_id,
+                pubsub_sender,
+                response_sender,
+            )),
+            Some(&self.data_lock),
+            Some(&self.nodes_list),
+        ) {
+            Ok(response) => response,
+            Err(CommandError::WrongType) => return Ok(self.wrong_type_reply(instruction)),
+            Err(e) => {
+                return Err(CommandExecutorError::ReadCommandError(
+                    Self::format_op_error(
+                        &instruction.instruction_type,
+                        &instruction.arguments,
+                        &e,
+                    ),
+                ));
+            }
+        };
 
         Ok(RespMessage::from_response(response))
     }
@@ -316,15 +1024,50 @@ impl CommandExecutor {
             ))
         })?;
 
-        let response = command.execute_write(&mut *guard).map_err(|e| {
-            CommandExecutorError::WriteCommandError(Self::format_op_error(
-                &instruction.instruction_type,
-                &instruction.arguments,
-                &e,
-            ))
-        })?;
+        let maxmemory = self.settings.get_maxmemory() as usize;
+        let policy = self.settings.get_maxmemory_policy();
+        // Como en Redis, el límite se controla antes de escribir: se
+        // desaloja lo necesario para hacerle lugar a lo que agrega el
+        // comando, estimado por el largo de sus argumentos, y si aun así
+        // no entra se rechaza todo lo que pueda agregar datos
+        let mut evicted = vec![];
+        if maxmemory > 0 && !command.frees_memory() {
+            let incoming: usize = instruction.arguments.iter().map(String::len).sum();
+            evicted = guard.evict(maxmemory.saturating_sub(incoming), policy, &command.keys());
+            if guard.approx_memory_usage() > maxmemory {
+                drop(guard);
+                self.propagate_evictions(&evicted);
+                return Ok(RespMessage::Error(ERR_OOM.to_string()));
+            }
+        }
+
+        let result = command.execute_write(&mut guard);
+        drop(guard);
+        self.propagate_evictions(&evicted);
+        let response = match result {
+            Ok(response) => response,
+            Err(CommandError::WrongType) => return Ok(self.wrong_type_reply(instruction)),
+            Err(e) => {
+                return Err(CommandExecutorError::WriteCommandError(
+                    Self::format_op_error(
+                        &instruction.instruction_type,
+                        &instruction.arguments,
+                        &e,
+                    ),
+                ));
+            }
+        };
 
+        // Las réplicas reciben el efecto determinístico del comando
+        let replicated = command.to_replication_form(&response);
         self.counter += 1;
+        self.propagate_write(instruction, &replicated);
+        if !replicated.is_empty() {
+            let event = command.to_string().to_lowercase();
+            for key in command.keys() {
+                self.notify_keyspace_event(&event, key);
+            }
+        }
         Ok(RespMessage::from_response(response))
     }
 
@@ -347,24 +1090,31 @@ impl CommandExecutor {
         pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
         response_sender: &Sender<RespMessage>,
     ) -> Result<RespMessage, CommandExecutorError> {
-        let command = instruction.to_command().map_err(|e| {
-            CommandExecutorError::CommandConversionError(Self::format_op_error(
-                &instruction.instruction_type,
-                &instruction.arguments,
-                &e,
-            ))
-        })?;
+        let command = instruction
+            .to_command()
+            .map_err(|e| self.conversion_error(instruction, e))?;
 
         // Verificar si necesitamos redirigir el comando
-        if let Some(key) = get_key_for_command(&command) {
-            let slot =
-                hash_slot(&key).map_err(|e| CommandExecutorError::HashSlotError(e.to_string()))?;
+        let slot_count = self.settings.get_slot_count();
+        let key = match get_key_for_command(&command, slot_count) {
+            Ok(key) => key,
+            Err(message) => return Ok(RespMessage::Error(message)),
+        };
+        if let Some(key) = key {
+            let slot = hash_slot_with_count(&key, slot_count)
+                .map_err(|e| CommandExecutorError::HashSlotError(e.to_string()))?;
 
             let data = self
                 .data_lock
                 .read()
                 .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
 
+            if self.settings.get_require_full_coverage()
+                && !slots_fully_covered(data.get_slots(), &self.nodes_list, slot_count)
+            {
+                return Ok(RespMessage::Error(ERR_CLUSTER_DOWN.to_string()));
+            }
+
             if !data.owns_slot(slot) {
                 // El nodo no maneja este slot, se debe redirigir
                 if let Some(redirect_ip) = get_node_ip_for_slot(slot, &self.nodes_list) {
@@ -373,466 +1123,4671 @@ impl CommandExecutor {
                         slot, redirect_ip
                     )));
                 } else {
-                    return Ok(RespMessage::Error(format!(
-                        "Slot {} not handled and no known owner",
-                        slot
-                    )));
+                    return Ok(RespMessage::Error(ERR_SLOT_NOT_SERVED.to_string()));
                 }
             }
+
+            // Durante una migración las claves que ya se movieron se
+            // atienden en el nodo destino; las que siguen acá, localmente
+            if let Some(redirect) = self.ask_redirect(slot, &key, data.get_migration(slot))? {
+                return Ok(redirect);
+            }
         }
 
-        if command.writes_on_db() {
-            return self.execute_write_command(instruction, &command);
+        self.expire_touched_keys(&command);
+
+        if let Command::Client(subcommand) = &command {
+            return self.execute_client_command(&client_id, subcommand);
         }
 
-        self.execute_read_command(
-            instruction,
-            &command,
-            client_id,
-            pubsub_sender,
-            response_sender,
-        )
+        if let Command::Debug(subcommand) = &command {
+            return self.execute_debug_command(subcommand);
+        }
+
+        if let Command::Pubsub(subcommand) = &command {
+            return self.execute_pubsub_command(&client_id, subcommand, pubsub_sender);
+        }
+
+        if let Command::Latency(subcommand) = &command {
+            return Ok(self.execute_latency_command(subcommand));
+        }
+
+        if let Command::CommandInfo(subcommand) = &command {
+            return Ok(execute_command_info(subcommand));
+        }
+
+        if let Command::Multi = &command {
+            return self.execute_multi_command(&client_id);
+        }
+
+        if let Command::Exec = &command {
+            return self.execute_exec_command(&client_id, pubsub_sender, response_sender);
+        }
+
+        if let Command::Discard = &command {
+            return self.execute_discard_command(&client_id);
+        }
+
+        if let Command::Failover(options) = &command {
+            return Ok(self.execute_failover_command(options));
+        }
+
+        if let Command::Wait(..) = &command {
+            return Ok(self.execute_wait_command(&client_id));
+        }
+
+        if let Command::ReplicaOf(master) = &command {
+            return Ok(self.execute_replicaof_command(master.as_ref()));
+        }
+
+        if let Command::Sync = &command {
+            return self.execute_sync_command(response_sender);
+        }
+
+        if let Command::Hello(protover) = &command {
+            return self.execute_hello_command(&client_id, *protover);
+        }
+
+        if let Some(reply) = self.subscribed_ping_reply(&client_id, &command) {
+            return Ok(reply);
+        }
+
+        if let Command::Bgrewriteaof = &command {
+            return self.start_aof_rewrite();
+        }
+
+        if let Command::Info(section) = &command {
+            return self.execute_info_command(section.as_deref());
+        }
+
+        if let Command::ClusterHelp = &command {
+            return Ok(RespMessage::verbatim_text(CLUSTER_HELP));
+        }
+
+        if let Command::ClusterMyId = &command {
+            let myself = self
+                .data_lock
+                .read()
+                .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+            return Ok(RespMessage::BulkString(Some(myself.get_id().into_bytes())));
+        }
+
+        if let Command::Mget(keys) = &command {
+            return self.execute_mget_command(keys);
+        }
+
+        if let Command::Geopos(..) | Command::Geosearch(..) = &command {
+            return self.execute_geo_command(instruction, &command);
+        }
+
+        if let Command::Sort(..) = &command {
+            return self.execute_sort_command(instruction, &command);
+        }
+
+        if let Command::Touch(keys) = &command {
+            return self.execute_touch_command(keys);
+        }
+
+        let command = self.attach_publisher(&client_id, command);
+        let response = if command.writes_on_db() {
+            let response = self.execute_write_command(instruction, &command)?;
+            self.record_write(&client_id);
+            response
+        } else {
+            self.execute_read_command(
+                instruction,
+                &command,
+                client_id.clone(),
+                pubsub_sender,
+                response_sender,
+            )?
+        };
+
+        if let Ok(Some(key)) = get_key_for_command(&command, slot_count) {
+            self.touch_key(&client_id, &key);
+        }
+        match &command {
+            Command::Subscribe(channel) | Command::Unsubscribe(channel) => {
+                Ok(self.track_subscription(&client_id, &command, channel, response))
+            }
+            Command::PSubscribe(_) | Command::PUnsubscribe(_) => Ok(
+                self.track_pattern_subscription(&client_id, &command, response, response_sender)
+            ),
+            _ => Ok(response),
+        }
     }
 
-    /// Ejecuta una instrucción con manejo de snapshots automáticos.
+    /// Abre una transacción en la conexión (MULTI).
     ///
     /// # Argumentos
     ///
     /// * `client_id` - ID del cliente
-    /// * `instruction` - Instrucción a ejecutar
-    /// * `pubsub_sender` - Sender para PubSub
-    /// * `response_sender` - Sender para respuesta
     ///
     /// # Retorna
     ///
-    /// `RespMessage` con el resultado de la ejecución
-    pub fn execute_instruction(
-        &mut self,
-        client_id: String,
-        instruction: Instruction,
+    /// `OK`; los `MULTI` anidados se rechazan al intentar encolarlos
+    fn execute_multi_command(&self, client_id: &str) -> Result<RespMessage, CommandExecutorError> {
+        let mut clients = self
+            .clients
+            .write()
+            .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?;
+        let flags = clients.entry(client_id.to_string()).or_default();
+        flags.transaction = Some(Transaction::default());
+        Ok(RespMessage::SimpleString("OK".to_string()))
+    }
+
+    /// Ejecuta los comandos encolados en la transacción de la conexión
+    /// (EXEC).
+    ///
+    /// El executor atiende una instrucción por vez, así que los comandos
+    /// corren seguidos sin que se intercale ninguno de otra conexión. Los
+    /// errores de un comando al ejecutarse no frenan a los siguientes.
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `pubsub_sender` - Sender para PubSub
+    /// * `response_sender` - Sender para respuesta
+    ///
+    /// # Retorna
+    ///
+    /// Array con la respuesta de cada comando, `EXECABORT` si alguno no se
+    /// pudo encolar, o error si no hay una transacción abierta
+    fn execute_exec_command(
+        &mut self,
+        client_id: &str,
         pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
         response_sender: &Sender<RespMessage>,
-    ) -> RespMessage {
-        // Verificar si necesitamos crear un snapshot
-        if self.counter > 0 && self.counter % self.settings.get_snapshot_k_changes() == 0 {
-            if let Err(e) = self.create_auto_snapshot() {
-                self.logger
-                    .log_error(format!("Error creating auto-snapshot: {}", e));
-            }
+    ) -> Result<RespMessage, CommandExecutorError> {
+        let transaction = self
+            .clients
+            .write()
+            .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?
+            .get_mut(client_id)
+            .and_then(|flags| flags.transaction.take());
+        let Some(transaction) = transaction else {
+            return Ok(RespMessage::Error(ERR_EXEC_WITHOUT_MULTI.to_string()));
+        };
+        if transaction.aborted {
+            return Ok(RespMessage::Error(ERR_EXECABORT.to_string()));
         }
 
-        self.try_execute(client_id, &instruction, pubsub_sender, response_sender)
-            .unwrap_or_else(|e| {
-                self.logger.log_debug(format!("{}", e));
-                RespMessage::Error(e.to_string())
-            })
+        // La cola entera corre con el lock de escritura del store tomado,
+        // para que los demás hilos (snapshots, réplicas) no vean la
+        // transacción a medias. Como cada comando toma el lock por su
+        // cuenta, mientras tanto el store pasa a un lock propio del executor
+        let shared = Arc::clone(&self.ds_guard);
+        let mut store = shared
+            .write()
+            .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?;
+        let private = Arc::new(RwLock::new(std::mem::replace(
+            &mut *store,
+            DataStore::new(),
+        )));
+        let shared = std::mem::replace(&mut self.ds_guard, private);
+
+        let mut replies = Vec::with_capacity(transaction.queued.len());
+        for instruction in transaction.queued {
+            replies.push(self.execute_instruction(
+                client_id.to_string(),
+                instruction,
+                pubsub_sender,
+                response_sender,
+            ));
+        }
+
+        let private = std::mem::replace(&mut self.ds_guard, shared);
+        let mut executed = private.write().unwrap_or_else(PoisonError::into_inner);
+        *store = std::mem::replace(&mut *executed, DataStore::new());
+        Ok(RespMessage::Array(replies))
     }
 
-    /// Crea un snapshot automático del DataStore.
+    /// Descarta la transacción de la conexión sin ejecutar sus comandos
+    /// (DISCARD).
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
     ///
     /// # Retorna
     ///
-    /// `Result<(), CommandExecutorError>`
-    fn create_auto_snapshot(&self) -> Result<(), CommandExecutorError> {
-        let guard = self
-            .ds_guard
-            .read()
-            .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
-
-        let dst = &self.settings.get_snapshot_dst();
-        create_dump(&guard, dst).map_err(|e| CommandExecutorError::SnapshotError(e.to_string()))
+    /// `OK`, o error si no hay una transacción abierta
+    fn execute_discard_command(
+        &self,
+        client_id: &str,
+    ) -> Result<RespMessage, CommandExecutorError> {
+        let discarded = self
+            .clients
+            .write()
+            .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?
+            .get_mut(client_id)
+            .and_then(|flags| flags.transaction.take());
+        match discarded {
+            Some(_) => Ok(RespMessage::SimpleString("OK".to_string())),
+            None => Ok(RespMessage::Error(ERR_DISCARD_WITHOUT_MULTI.to_string())),
+        }
     }
-}
 
-impl Command {
-    /// Ejecuta la operación de **escritura** asociada sobre la base de datos seleccionada.
+    /// Respuesta a un `PING` de una conexión RESP2 suscripta a canales o
+    /// patrones, que como en Redis recibe `["pong", mensaje]` con el
+    /// mensaje vacío si no se dio uno. En RESP3 el `PING` se responde igual
+    /// que sin suscripciones.
     ///
     /// # Argumentos
     ///
-    /// * `store` - Referencia mutable al DataStore
-    /// * `_` - Configuración del nodo (no utilizada)
-    /// * `_` - Logger AOF (no utilizado)
+    /// * `client_id` - ID del cliente
+    /// * `command` - Comando a ejecutar
     ///
     /// # Retorna
     ///
-    /// `Result<ResponseType, CommandError>` - Respuesta de la operación
+    /// `Some(RespMessage)` con la respuesta, `None` si el comando no es
+    /// `PING` o la conexión no está suscripta
+    fn subscribed_ping_reply(&self, client_id: &str, command: &Command) -> Option<RespMessage> {
+        let Command::Ping(message) = command else {
+            return None;
+        };
+        let clients = self.clients.read().ok()?;
+        let flags = clients.get(client_id)?;
+        let subscribed = !flags.channels.is_empty() || !flags.patterns.is_empty();
+        if !subscribed || flags.protocol != RespProtocol::Resp2 {
+            return None;
+        }
+        let message = message.clone().unwrap_or_default();
+        Some(RespMessage::Array(vec![
+            RespMessage::BulkString(Some(b"pong".to_vec())),
+            RespMessage::BulkString(Some(message.into_bytes())),
+        ]))
+    }
+
+    /// Registra en el estado de la conexión el resultado de un `SUBSCRIBE`
+    /// o `UNSUBSCRIBE`.
     ///
-    /// # Errores
+    /// Los suscriptores quedan sujetos a los límites de buffer de pubsub.
+    /// A las conexiones RESP3 se les confirma con un push que incluye la
+    /// cantidad de canales a los que siguen suscriptas; en RESP2 la
+    /// respuesta no cambia.
     ///
-    /// * `WRONGTYPE` - El valor asociado a la clave no es del tipo soportado por la op
-    /// * `CommandError` - Otros errores de ejecución
-    pub fn execute_write(&self, store: &mut DataStore) -> Result<ResponseType, CommandError> {
-        match self {
-            // STRING COMMANDS
-            Command::Append(key, val) => str_concat(store, key, val),
-            Command::Del(keys) => bulk_delete(store, keys),
-            Command::Getdel(key) => retrieve_delete(store, key),
-            Command::Set(key, value) => set(store, key.clone(), value.clone()),
-
-            // LIST COMMANDS
-            Command::Lpop(key, amount) | Command::Rpop(key, amount) => {
-                list_pop(store, key, amount, &self)
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `command` - `SUBSCRIBE` o `UNSUBSCRIBE` ejecutado
+    /// * `channel` - Canal del comando
+    /// * `response` - Respuesta obtenida al ejecutarlo
+    ///
+    /// # Retorna
+    ///
+    /// `RespMessage` - Respuesta a enviar al cliente
+    fn track_subscription(
+        &self,
+        client_id: &str,
+        command: &Command,
+        channel: &str,
+        response: RespMessage,
+    ) -> RespMessage {
+        let Ok(mut clients) = self.clients.write() else {
+            return response;
+        };
+        let flags = clients.entry(client_id.to_string()).or_default();
+        if let Command::Subscribe(_) = command {
+            flags.class = ClientClass::Pubsub;
+        }
+        if response.is_error() {
+            return response;
+        }
+        let kind = match command {
+            Command::Subscribe(_) => {
+                flags.channels.insert(channel.to_string());
+                "subscribe"
             }
-            Command::Lpush(key, val) => left_push(store, key, val),
-            Command::Rpush(key, values) => append(store, key.clone(), values.clone()),
-
-            // SET COMMANDS
-            Command::Sadd(key, values) => sadd(store, key.clone(), values.clone()),
-            Command::SMove(source, destination, value) => {
-                move_data_to_other_set(store, source, destination, value)
+            _ => {
+                flags.channels.remove(channel);
+                "unsubscribe"
             }
-            Command::Spop(key, amount) => set_pop(store, key, amount),
-
-            _ => Err(CommandError::Custom("Error non write command".to_string())),
+        };
+        match flags.protocol {
+            RespProtocol::Resp2 => response,
+            RespProtocol::Resp3 => RespMessage::Push(vec![
+                RespMessage::BulkString(Some(kind.as_bytes().to_vec())),
+                RespMessage::BulkString(Some(channel.as_bytes().to_vec())),
+                RespMessage::Integer((flags.channels.len() + flags.patterns.len()) as i64),
+            ]),
         }
     }
 
-    /// Ejecuta la operación de **lectura** asociada sobre la base de datos seleccionada.
+    /// Registra en el estado de la conexión el resultado de un `PSUBSCRIBE`
+    /// o `PUNSUBSCRIBE`.
     ///
-    /// Precondición: Ejecutarla con un lock no bloqueante de tipo lectura del DataStore.
+    /// Como en `SUBSCRIBE`, a las conexiones RESP3 se les confirma cada
+    /// patrón con un push: todos menos el último se envían directamente al
+    /// cliente y el último es la respuesta. Un `PUNSUBSCRIBE` sin patrones
+    /// confirma cada patrón que tenía la conexión.
     ///
     /// # Argumentos
     ///
-    /// * `store` - Referencia al DataStore
-    /// * `settings` - Configuración del nodo
-    /// * `logger` - Logger AOF
-    /// * `pub_sub_context` - Contexto para operaciones PubSub
+    /// * `client_id` - ID del cliente
+    /// * `command` - `PSUBSCRIBE` o `PUNSUBSCRIBE` ejecutado
+    /// * `response` - Respuesta obtenida al ejecutarlo
+    /// * `response_sender` - Sender de respuestas del cliente
     ///
     /// # Retorna
     ///
-    /// `Result<ResponseType, CommandError>` - Respuesta de la operación
-    ///
-    /// # Errores
-    ///
-    /// * `WRONGTYPE` - El valor asociado a la clave no es del tipo soportado por la op
-    /// * `CommandError` - Otros errores de ejecución
-    pub fn execute_read(
+    /// `RespMessage` - Respuesta a enviar al cliente
+    fn track_pattern_subscription(
         &self,
-        store: &DataStore,
-        settings: Option<NodeConfigs>,
-        logger: Option<Arc<AofLogger>>,
-        pub_sub_context: Option<PubSubContext>,
-        node_data: Option<&Arc<RwLock<NodeData>>>,
-        known_nodes: Option<&Arc<RwLock<HashMap<NodeId, KnownNode>>>>,
-    ) -> Result<ResponseType, CommandError> {
-        match self {
-            // STRING COMMANDS
-            Command::Echo(val) => Ok(ResponseType::Str(format!("{}", val))),
-            Command::Get(key) => get(store, key),
-            Command::Substr(key, start, end) | Command::Getrange(key, start, end) => {
-                string_slice(store, key, start, end)
+        client_id: &str,
+        command: &Command,
+        response: RespMessage,
+        response_sender: &Sender<RespMessage>,
+    ) -> RespMessage {
+        let Ok(mut clients) = self.clients.write() else {
+            return response;
+        };
+        let flags = clients.entry(client_id.to_string()).or_default();
+        if let Command::PSubscribe(_) = command {
+            flags.class = ClientClass::Pubsub;
+        }
+        if response.is_error() {
+            return response;
+        }
+        let (kind, patterns) = match command {
+            Command::PSubscribe(patterns) => ("psubscribe", patterns.clone()),
+            Command::PUnsubscribe(patterns) if patterns.is_empty() => {
+                ("punsubscribe", flags.patterns.iter().cloned().collect())
             }
-            Command::Strlen(key) => get_len(store, key, &self),
-
-            // LIST COMMANDS
-            Command::Llen(key) => get_len(store, key, &self),
-            Command::Lrange(key, start, end) => get_slice(store, key, *start, *end),
+            Command::PUnsubscribe(patterns) => ("punsubscribe", patterns.clone()),
+            _ => return response,
+        };
+        let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+        let mut confirmations = Vec::new();
+        for pattern in patterns {
+            if kind == "psubscribe" {
+                flags.patterns.insert(pattern.clone());
+            } else {
+                flags.patterns.remove(&pattern);
+            }
+            let count = flags.channels.len() + flags.patterns.len();
+            confirmations.push(RespMessage::Push(vec![
+                text(kind),
+                text(&pattern),
+                RespMessage::Integer(count as i64),
+            ]));
+        }
+        match flags.protocol {
+            RespProtocol::Resp2 => response,
+            RespProtocol::Resp3 => {
+                let count = flags.channels.len() + flags.patterns.len();
+                let last = confirmations.pop().unwrap_or_else(|| {
+                    RespMessage::Push(vec![
+                        text(kind),
+                        RespMessage::Null(None),
+                        RespMessage::Integer(count as i64),
+                    ])
+                });
+                for confirmation in confirmations {
+                    let _ = response_sender.send(confirmation);
+                }
+                last
+            }
+        }
+    }
 
-            // SET COMMANDS
-            Command::Scard(key) => get_len(store, key, &self),
-            Command::Sismember(key, val) => get_set_data(store, key, val),
-            Command::Smembers(key) => get_set_items(store, key),
+    /// Ejecuta un subcomando de `CLIENT` sobre el estado de la conexión.
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `subcommand` - Subcomando a ejecutar
+    ///
+    /// # Retorna
+    ///
+    /// `Result<RespMessage, CommandExecutorError>`
+    fn execute_client_command(
+        &mut self,
+        client_id: &str,
+        subcommand: &ClientSubcommand,
+    ) -> Result<RespMessage, CommandExecutorError> {
+        let mut clients = self
+            .clients
+            .write()
+            .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?;
+        let flags = clients.entry(client_id.to_string()).or_default();
 
-            // PERSISTENCE COMMANDS
-            Command::BgSave => {
-                let settings =
-                    settings.ok_or_else(|| CommandError::Custom("Settings missing".to_string()))?;
-                let logger =
-                    unwrap_or_fail_arc(logger, "logger").map_err(|e| CommandError::Custom(e))?;
-                backup_ds(store, settings, logger, true)
+        match subcommand {
+            ClientSubcommand::NoTouch(enabled) => flags.no_touch = *enabled,
+            ClientSubcommand::NoEvict(enabled) => flags.no_evict = *enabled,
+            ClientSubcommand::Pause(timeout, mode) => {
+                self.pause = Some(ClientPause {
+                    until: Instant::now() + Duration::from_millis(*timeout),
+                    mode: *mode,
+                });
             }
-            Command::Save => {
-                let settings =
-                    settings.ok_or_else(|| CommandError::Custom("Settings missing".to_string()))?;
-                let logger =
-                    unwrap_or_fail_arc(logger, "logger").map_err(|e| CommandError::Custom(e))?;
-                backup_ds(store, settings, logger, false)
+            ClientSubcommand::Unpause => self.pause = None,
+            ClientSubcommand::SetName(name) => {
+                flags.name = Some(name.clone()).filter(|name| !name.is_empty());
+                self.logger
+                    .log_event(format!("Client {} named '{}'", client_id, name));
             }
-
-            // PUBSUB COMMANDS
-            Command::Subscribe(channel_id) => {
-                let context = pub_sub_context
-                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
-                subscribe(
-                    context.get_cid(),
-                    channel_id.clone(),
-                    context.get_sender(),
-                    &context.get_res_sender(),
-                )
+            ClientSubcommand::GetName => {
+                let name = flags.name.clone().unwrap_or_default();
+                return Ok(RespMessage::BulkString(Some(name.into_bytes())));
             }
-            Command::Unsubscribe(channel_id) => {
-                let context = pub_sub_context
-                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
-                unsubscribe(context.get_cid(), channel_id.clone(), context.get_sender())
+            ClientSubcommand::List => {
+                return Ok(RespMessage::from_response(ResponseType::Str(
+                    format_client_list(&clients),
+                )));
             }
-            Command::Publish(channel_id, message) => {
-                let context = pub_sub_context
-                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
-                publish(
-                    context.get_cid(),
-                    channel_id.to_string(),
-                    context.get_sender(),
-                    message,
-                )
+            ClientSubcommand::Kill(target) => {
+                // Una conexión ya terminada no cuenta, aunque todavía no
+                // se haya quitado del registro
+                let Some(victim) = clients.get_mut(target).filter(|victim| !victim.killed) else {
+                    return Ok(RespMessage::Integer(0));
+                };
+                // La conexión queda marcada para que su hilo de salida
+                // cierre el socket en lugar de despedirse como con QUIT
+                victim.killed = true;
+                if let Some(output) = &victim.output {
+                    let _ = output.send(RespMessage::Disconnect);
+                }
+                self.logger
+                    .log_event(format!("Client {} killed by {}", target, client_id));
+                return Ok(RespMessage::Integer(1));
             }
-            Command::Meet(ip) => {
-                let settings =
-                    settings.ok_or_else(|| CommandError::Custom("Settings missing".to_string()))?;
-                send_first_ping(ip, settings)
+        }
+        Ok(RespMessage::from_response(ResponseType::Str(
+            "OK".to_string(),
+        )))
+    }
+
+    /// Ejecuta `HELLO`, cambiando el protocolo de la conexión si se pide
+    /// una versión.
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `protover` - Versión de protocolo pedida, si la hay
+    ///
+    /// # Retorna
+    ///
+    /// `Result<RespMessage, CommandExecutorError>` - Los datos del servidor
+    /// y de la conexión como pares nombre-valor, o `NOPROTO` si la
+    /// versión no está soportada
+    fn execute_hello_command(
+        &mut self,
+        client_id: &str,
+        protover: Option<u8>,
+    ) -> Result<RespMessage, CommandExecutorError> {
+        let requested = match protover.map(RespProtocol::from_version) {
+            Some(None) => return Ok(RespMessage::Error(ERR_NOPROTO.to_string())),
+            Some(protocol) => protocol,
+            None => None,
+        };
+        let protocol = {
+            let mut clients = self
+                .clients
+                .write()
+                .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?;
+            let flags = clients.entry(client_id.to_string()).or_default();
+            if let Some(protocol) = requested {
+                flags.protocol = protocol;
             }
-            Command::Slots => {
-                let data = node_data
-                    .ok_or_else(|| CommandError::Custom("Node data missing".to_string()))?;
-                let cluster_nodes = known_nodes
-                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
-                return_cluster_slots_data(data, cluster_nodes)
+            flags.protocol
+        };
+        let role = {
+            let myself = self
+                .data_lock
+                .read()
+                .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+            if NodeFlags::state_contains(myself.get_state(), MASTER) {
+                "master"
+            } else {
+                "replica"
             }
-            _ => Err(CommandError::Custom(
-                "Error non only-read command".to_string(),
-            )),
+        };
+
+        let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+        Ok(RespMessage::Array(vec![
+            text("server"),
+            text("rustidocs"),
+            text("version"),
+            text(env!("CARGO_PKG_VERSION")),
+            text("proto"),
+            RespMessage::Integer(protocol.version() as i64),
+            text("id"),
+            text(client_id),
+            text("mode"),
+            text("cluster"),
+            text("role"),
+            text(role),
+        ]))
+    }
+
+    /// Redirección `ASK` para una clave de un slot que está migrando, si
+    /// la clave ya no está en este nodo. Si el destino no es un nodo
+    /// conocido la clave se atiende localmente.
+    ///
+    /// # Retorna
+    ///
+    /// `Result<Option<RespMessage>, CommandExecutorError>` - El error `ASK`,
+    /// o `None` si el comando se ejecuta en este nodo
+    fn ask_redirect(
+        &self,
+        slot: u16,
+        key: &str,
+        migration: Option<&SlotMigration>,
+    ) -> Result<Option<RespMessage>, CommandExecutorError> {
+        let Some(SlotMigration::Migrating(target)) = migration else {
+            return Ok(None);
+        };
+        let present = {
+            let store = self
+                .ds_guard
+                .read()
+                .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+            store.contains_key(key) && !store.is_expired(key)
+        };
+        if present {
+            return Ok(None);
+        }
+        let nodes = self
+            .nodes_list
+            .read()
+            .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+        Ok(nodes
+            .get(target)
+            .map(|node| RespMessage::Error(format!("ASK {} {}", slot, node.get_client_addr()))))
+    }
+
+    /// Inicia la reescritura del AOF (`BGREWRITEAOF`) en un hilo aparte.
+    ///
+    /// El hilo trabaja sobre una copia del store tomada al iniciar; las
+    /// escrituras posteriores las agrega el logger al archivo nuevo cuando
+    /// reemplaza al anterior. Sólo puede haber
+    /// una reescritura a la vez: mientras una está en curso las siguientes
+    /// se rechazan, sin encolarse.
+    ///
+    /// # Retorna
+    ///
+    /// `Result<RespMessage, CommandExecutorError>` - La confirmación de
+    /// inicio, o un error si ya hay una reescritura en curso
+    fn start_aof_rewrite(&mut self) -> Result<RespMessage, CommandExecutorError> {
+        {
+            let mut status = self
+                .aof_rewrite
+                .lock()
+                .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?;
+            if status.in_progress {
+                return Ok(RespMessage::Error(ERR_AOF_REWRITE_IN_PROGRESS.to_string()));
+            }
+            status.in_progress = true;
+        }
+        let snapshot = self
+            .ds_guard
+            .read()
+            .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?
+            .clone();
+        // Las escrituras posteriores a la copia se agregan aparte al archivo nuevo
+        self.logger.start_rewrite();
+        let status = self.aof_rewrite.clone();
+        let logger = self.logger.clone();
+
+        self.logger
+            .log_notice("Background append only file rewriting started".to_string());
+        let _ = thread::Builder::new()
+            .name("AOF rewrite".to_string())
+            .spawn(move || {
+                let result = logger.rewrite(&snapshot);
+                match &result {
+                    Ok(()) => logger
+                        .log_notice("Background AOF rewrite finished successfully".to_string()),
+                    Err(e) => logger.log_warning(format!("Background AOF rewrite failed: {}", e)),
+                }
+                if let Ok(mut status) = status.lock() {
+                    status.in_progress = false;
+                    status.last_ok = result.is_ok();
+                }
+            });
+        Ok(RespMessage::SimpleString(
+            "Background append only file rewriting started".to_string(),
+        ))
+    }
+
+    /// Ejecuta `INFO`. Por ahora se informan las secciones `persistence` y
+    /// `cluster`, que se incluyen en las de por defecto; las secciones
+    /// desconocidas responden vacío, como en Redis.
+    ///
+    /// # Argumentos
+    ///
+    /// * `section` - Sección pedida, en minúsculas
+    ///
+    /// # Retorna
+    ///
+    /// `Result<RespMessage, CommandExecutorError>`
+    fn execute_info_command(
+        &self,
+        section: Option<&str>,
+    ) -> Result<RespMessage, CommandExecutorError> {
+        let mut info = String::new();
+        if matches!(
+            section,
+            None | Some("persistence" | "default" | "all" | "everything")
+        ) {
+            let status = *self
+                .aof_rewrite
+                .lock()
+                .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+            info.push_str("# Persistence\r\n");
+            info.push_str(&format!(
+                "aof_rewrite_in_progress:{}\r\n",
+                status.in_progress as u8
+            ));
+            info.push_str(&format!(
+                "aof_last_bgrewrite_status:{}\r\n",
+                if status.last_ok { "ok" } else { "err" }
+            ));
+        }
+        if matches!(
+            section,
+            None | Some("cluster" | "default" | "all" | "everything")
+        ) {
+            info.push_str("# Cluster\r\n");
+            info.push_str("cluster_enabled:1\r\n");
+            info.push_str(&format!(
+                "cluster_slot_count:{}\r\n",
+                self.settings.get_slot_count()
+            ));
+        }
+        Ok(RespMessage::BulkString(Some(info.into_bytes())))
+    }
+
+    /// Ejecuta `MGET`. Se resuelve acá y no en `execute_read` porque la
+    /// respuesta es un array con elementos nulos, que `ResponseType` no
+    /// puede representar.
+    fn execute_mget_command(&self, keys: &[String]) -> Result<RespMessage, CommandExecutorError> {
+        let guard = self
+            .ds_guard
+            .read()
+            .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+        let values = mget(&guard, keys)
+            .into_iter()
+            .map(|value| RespMessage::BulkString(value.map(String::into_bytes)))
+            .collect();
+        Ok(RespMessage::Array(values))
+    }
+
+    /// Ejecuta `SORT`. Se resuelve acá y no en `execute_read` porque con
+    /// `GET` la respuesta es un array con elementos nulos, que
+    /// `ResponseType` no puede representar.
+    fn execute_sort_command(
+        &self,
+        instruction: &Instruction,
+        command: &Command,
+    ) -> Result<RespMessage, CommandExecutorError> {
+        let guard = self
+            .ds_guard
+            .read()
+            .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+        let response = match command {
+            Command::Sort(key, alpha, desc, limit, by_pattern, get_patterns) => {
+                sort(&guard, key, *alpha, *desc, limit, by_pattern, get_patterns)
+            }
+            _ => Err(CommandError::Custom("Error non sort command".to_string())),
+        };
+        match response {
+            Ok(values) => Ok(RespMessage::Array(
+                values
+                    .into_iter()
+                    .map(|value| RespMessage::BulkString(value.map(String::into_bytes)))
+                    .collect(),
+            )),
+            Err(CommandError::WrongType) => Ok(self.wrong_type_reply(instruction)),
+            Err(e) => Err(CommandExecutorError::ReadCommandError(
+                Self::format_op_error(&instruction.instruction_type, &instruction.arguments, &e),
+            )),
+        }
+    }
+
+    /// Ejecuta `GEOPOS` y `GEOSEARCH`. Se resuelven acá y no en
+    /// `execute_read` porque responden arrays anidados, con elementos nulos
+    /// en el caso de `GEOPOS`, que `ResponseType` no puede representar.
+    fn execute_geo_command(
+        &self,
+        instruction: &Instruction,
+        command: &Command,
+    ) -> Result<RespMessage, CommandExecutorError> {
+        let guard = self
+            .ds_guard
+            .read()
+            .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+        let response = match command {
+            Command::Geopos(key, members) => geopos(&guard, key, members).map(|positions| {
+                positions
+                    .into_iter()
+                    .map(|position| match position {
+                        Some(position) => coordinates_reply(position),
+                        None => RespMessage::BulkString(None),
+                    })
+                    .collect()
+            }),
+            Command::Geosearch(key, options) => geosearch(&guard, key, options).map(|found| {
+                found
+                    .into_iter()
+                    .map(|(member, dist, position)| {
+                        let member = RespMessage::BulkString(Some(member.into_bytes()));
+                        if !options.with_dist && !options.with_coord {
+                            return member;
+                        }
+                        let mut item = vec![member];
+                        if options.with_dist {
+                            let dist = format!("{:.4}", dist).into_bytes();
+                            item.push(RespMessage::BulkString(Some(dist)));
+                        }
+                        if options.with_coord {
+                            item.push(coordinates_reply(position));
+                        }
+                        RespMessage::Array(item)
+                    })
+                    .collect()
+            }),
+            _ => Err(CommandError::Custom("Error non geo command".to_string())),
+        };
+        match response {
+            Ok(items) => Ok(RespMessage::Array(items)),
+            Err(CommandError::WrongType) => Ok(self.wrong_type_reply(instruction)),
+            Err(e) => Err(CommandExecutorError::ReadCommandError(
+                Self::format_op_error(&instruction.instruction_type, &instruction.arguments, &e),
+            )),
+        }
+    }
+
+    /// Ejecuta `TOUCH`. Se resuelve acá y no en `execute_read` porque
+    /// actualiza la recencia de las claves, y no pasa por
+    /// `execute_write` porque no modifica los datos: se atiende también en
+    /// las réplicas y no cuenta como cambio para los snapshots. Toca las
+    /// claves aunque la conexión tenga activo NO-TOUCH, como en Redis.
+    fn execute_touch_command(&self, keys: &[String]) -> Result<RespMessage, CommandExecutorError> {
+        let mut guard = self
+            .ds_guard
+            .write()
+            .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?;
+        let response = touch(&mut guard, keys)
+            .map_err(|e| CommandExecutorError::WriteCommandError(e.to_string()))?;
+        Ok(RespMessage::from_response(response))
+    }
+
+    /// Marca un `PUBLISH` con el usuario con el que se autenticó la
+    /// conexión, para que los suscriptores sepan quién lo publicó sin
+    /// confiar en lo que diga el mensaje.
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `command` - Comando a ejecutar
+    ///
+    /// # Retorna
+    ///
+    /// `Command` - El comando, con el publicador si es un `PUBLISH`
+    fn attach_publisher(&self, client_id: &str, command: Command) -> Command {
+        let Command::Publish(channel, payload) = command else {
+            return command;
+        };
+        let user = self
+            .clients
+            .read()
+            .ok()
+            .and_then(|clients| clients.get(client_id)?.user.clone());
+        match user {
+            Some(user) => Command::Publish(channel, RespMessage::with_publisher(payload, &user)),
+            None => Command::Publish(channel, payload),
+        }
+    }
+
+    /// Ejecuta un subcomando de `PUBSUB` en el gestor de canales.
+    ///
+    /// La respuesta no es un string como las de `SUBSCRIBE` o `PUBLISH`,
+    /// por lo que el gestor la envía ya armada por un sender propio de
+    /// la consulta.
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `subcommand` - Subcomando a ejecutar
+    /// * `pubsub_sender` - Sender al gestor de canales
+    ///
+    /// # Retorna
+    ///
+    /// `Result<RespMessage, CommandExecutorError>`
+    fn execute_pubsub_command(
+        &self,
+        client_id: &str,
+        subcommand: &PubsubSubcommand,
+        pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
+    ) -> Result<RespMessage, CommandExecutorError> {
+        let (response_sender, _response_receiver) = mpsc::channel();
+        let (reply_sender, reply_receiver) = mpsc::channel();
+        pubsub_sender
+            .send((
+                client_id.to_string(),
+                Command::Pubsub(subcommand.clone()),
+                response_sender,
+                reply_sender,
+            ))
+            .map_err(|e| CommandExecutorError::ReadCommandError(e.to_string()))?;
+        reply_receiver
+            .recv()
+            .map_err(|e| CommandExecutorError::ReadCommandError(e.to_string()))
+    }
+
+    /// Ejecuta un subcomando de `DEBUG`.
+    ///
+    /// `DEBUG RELOAD` guarda la base en disco de forma sincrónica, la vuelve
+    /// a cargar y la reemplaza en el store vivo, vencimientos incluidos.
+    /// Sólo responde `OK` si el contenido cargado coincide con el guardado
+    /// y vencen las mismas claves.
+    ///
+    /// # Argumentos
+    ///
+    /// * `subcommand` - Subcomando a ejecutar
+    ///
+    /// # Retorna
+    ///
+    /// `Result<RespMessage, CommandExecutorError>`
+    fn execute_debug_command(
+        &self,
+        subcommand: &DebugSubcommand,
+    ) -> Result<RespMessage, CommandExecutorError> {
+        match subcommand {
+            DebugSubcommand::Reload => {
+                let mut guard = self
+                    .ds_guard
+                    .write()
+                    .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?;
+
+                let dst = self.settings.get_snapshot_dst();
+                create_dump(&guard, &dst)
+                    .map_err(|e| CommandExecutorError::SnapshotError(e.to_string()))?;
+                let reloaded = deserialize_db(dst)
+                    .map_err(|e| CommandExecutorError::SnapshotError(e.to_string()))?;
+
+                if !guard.has_same_data(&reloaded) || !guard.has_same_expiring_keys(&reloaded) {
+                    return Ok(RespMessage::Error(ERR_RELOAD_MISMATCH.to_string()));
+                }
+                guard.update(reloaded);
+                self.logger.log_notice("DB reloaded from disk".to_string());
+            }
+            DebugSubcommand::Sleep(duration) => std::thread::sleep(*duration),
+            DebugSubcommand::Populate(count, prefix, size) => {
+                let mut guard = self
+                    .ds_guard
+                    .write()
+                    .map_err(|e| CommandExecutorError::DataStoreWriteError(e.to_string()))?;
+                let created = guard.populate(*count, prefix, *size);
+                self.logger
+                    .log_debug(format!("DEBUG POPULATE created {} keys", created));
+            }
+        }
+        Ok(RespMessage::from_response(ResponseType::Str(
+            "OK".to_string(),
+        )))
+    }
+
+    /// Ejecuta un subcomando de `LATENCY` sobre el historial de eventos.
+    ///
+    /// Las respuestas son listas planas: `LATEST` devuelve, por evento,
+    /// nombre, momento, última latencia y máxima; `HISTORY` devuelve,
+    /// por muestra, momento y latencia.
+    ///
+    /// # Argumentos
+    ///
+    /// * `subcommand` - Subcomando a ejecutar
+    ///
+    /// # Retorna
+    ///
+    /// `RespMessage` con el resultado
+    fn execute_latency_command(&mut self, subcommand: &LatencySubcommand) -> RespMessage {
+        let response = match subcommand {
+            LatencySubcommand::Latest => ResponseType::List(
+                self.latency
+                    .latest()
+                    .into_iter()
+                    .flat_map(|(event, sample, max_ms)| {
+                        [
+                            event,
+                            sample.timestamp.to_string(),
+                            sample.latency_ms.to_string(),
+                            max_ms.to_string(),
+                        ]
+                    })
+                    .collect(),
+            ),
+            LatencySubcommand::History(event) => ResponseType::List(
+                self.latency
+                    .history(event)
+                    .into_iter()
+                    .flat_map(|sample| {
+                        [sample.timestamp.to_string(), sample.latency_ms.to_string()]
+                    })
+                    .collect(),
+            ),
+            LatencySubcommand::Reset(events) => {
+                ResponseType::Int(self.latency.reset(events) as i64)
+            }
+        };
+        RespMessage::from_response(response)
+    }
+
+    /// Inicia o cancela un failover manual.
+    ///
+    /// Al iniciarlo se pausan las escrituras y se elige la réplica indicada
+    /// con `TO` o, si no, la de mayor offset de replicación. El cambio de roles lo completa
+    /// `advance_failover` cuando la réplica se pone al día. Como los roles
+    /// sólo cambian al completarse, `ABORT` se limita a descartar el failover
+    /// y reanudar las escrituras.
+    ///
+    /// # Argumentos
+    ///
+    /// * `options` - Opciones del comando
+    ///
+    /// # Retorna
+    ///
+    /// `RespMessage` con el resultado
+    fn execute_failover_command(&mut self, options: &FailoverOptions) -> RespMessage {
+        if options.abort {
+            if self.failover.take().is_none() {
+                return RespMessage::Error(ERR_NO_FAILOVER.to_string());
+            }
+            self.logger.log_notice("Failover aborted".to_string());
+            return RespMessage::from_response(ResponseType::Str("OK".to_string()));
+        }
+        if self.failover.is_some() {
+            return RespMessage::Error(ERR_FAILOVER_IN_PROGRESS.to_string());
+        }
+
+        let myself = self.data_lock.read().unwrap();
+        if !NodeFlags::state_contains(myself.get_state(), MASTER) {
+            return RespMessage::Error(ERR_FAILOVER_ON_REPLICA.to_string());
+        }
+        let my_id = myself.get_id();
+        let target_offset = myself.get_replication_offset();
+        drop(myself);
+
+        let nodes = self.nodes_list.read().unwrap();
+        let replica = nodes
+            .values()
+            .filter(|node| {
+                node.is_slave() && node.get_master_id() == Some(&my_id) && !node.is_fail()
+            })
+            .filter(|node| match &options.to {
+                Some((host, port)) => node_listens_on(node, host, *port),
+                None => true,
+            })
+            .max_by_key(|node| node.get_replication_offset())
+            .map(|node| node.get_id());
+        drop(nodes);
+
+        let Some(replica_id) = replica else {
+            let error = match options.to {
+                Some(_) => ERR_FAILOVER_TARGET,
+                None => ERR_FAILOVER_NO_REPLICAS,
+            };
+            return RespMessage::Error(error.to_string());
+        };
+
+        self.logger.log_notice(format!(
+            "Manual failover started, waiting for replica {} to catch up",
+            replica_id
+        ));
+        self.failover = Some(Failover {
+            replica_id,
+            target_offset,
+            deadline: options
+                .timeout
+                .map(|timeout| Instant::now() + Duration::from_millis(timeout)),
+            force: options.force,
+        });
+        RespMessage::from_response(ResponseType::Str("OK".to_string()))
+    }
+
+    /// Avanza el failover en curso.
+    ///
+    /// La réplica se considera al día cuando su offset de replicación
+    /// alcanza el que tenía el master al pausar las escrituras. En ese momento, o al vencer la
+    /// espera con `FORCE`, se intercambian los roles. Si la espera vence
+    /// sin `FORCE`, el failover se cancela y se reanudan las escrituras.
+    fn advance_failover(&mut self) {
+        let Some(failover) = &self.failover else {
+            return;
+        };
+        let caught_up = self.nodes_list.read().is_ok_and(|nodes| {
+            nodes
+                .get(&failover.replica_id)
+                .is_some_and(|replica| replica.get_replication_offset() >= failover.target_offset)
+        });
+        let timed_out = failover
+            .deadline
+            .is_some_and(|deadline| deadline <= Instant::now());
+
+        if caught_up || (timed_out && failover.force) {
+            if let Some(failover) = self.failover.take() {
+                self.switch_to_replica(failover.replica_id);
+            }
+        } else if timed_out {
+            self.failover = None;
+            self.logger
+                .log_warning("Failover aborted, the replica didn't catch up in time".to_string());
+        }
+    }
+
+    /// Ejecuta `SYNC`: responde el store serializado y suscribe la conexión
+    /// a las escrituras que se ejecuten desde ahora.
+    ///
+    /// # Argumentos
+    ///
+    /// * `response_sender` - Canal de respuesta de la conexión
+    ///
+    /// # Retorna
+    ///
+    /// `Result<RespMessage, CommandExecutorError>` con el store como bulk string
+    fn execute_sync_command(
+        &mut self,
+        response_sender: &Sender<RespMessage>,
+    ) -> Result<RespMessage, CommandExecutorError> {
+        let guard = self
+            .ds_guard
+            .read()
+            .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+        let payload = serialize_to_bytes(&guard)
+            .map_err(|e| CommandExecutorError::SnapshotError(e.to_string()))?;
+        drop(guard);
+
+        self.sync_clients.push(response_sender.clone());
+        self.logger.log_notice(format!(
+            "Full sync of {} bytes sent, streaming writes to {} connections",
+            payload.len(),
+            self.sync_clients.len()
+        ));
+        Ok(RespMessage::BulkString(Some(payload)))
+    }
+
+    /// Reenvía a las conexiones sincronizadas con `SYNC` la forma de
+    /// replicación de una escritura ejecutada, como arrays RESP. Los
+    /// comandos que no se reescribieron viajan tal como llegaron en
+    /// `instruction`.
+    fn propagate_write(&mut self, instruction: &Instruction, replicated: &[Command]) {
+        for command in replicated {
+            let arguments = command.rewritten_arguments().unwrap_or_else(|| {
+                std::iter::once(&instruction.instruction_type)
+                    .chain(&instruction.arguments)
+                    .cloned()
+                    .collect()
+            });
+            self.propagate(arguments);
+        }
+    }
+
+    /// Registra las claves desalojadas por `maxmemory` y las borra en las
+    /// réplicas con un `DEL`, para que no guarden lo que el master ya no
+    /// tiene.
+    fn propagate_evictions(&mut self, evicted: &[String]) {
+        for key in evicted {
+            self.logger.log_debug(format!("Key {} evicted", key));
+            self.propagate(vec!["DEL".to_string(), key.clone()]);
+            self.notify_keyspace_event(EVICTED_EVENT, key);
+        }
+    }
+
+    /// Publica un evento sobre `key` en los canales de keyspace que
+    /// habilita `notify-keyspace-events`: el nombre del evento en
+    /// `__keyspace@0__:<clave>` y la clave en `__keyevent@0__:<evento>`.
+    /// Si no hay quien escuche, el evento se pierde.
+    fn notify_keyspace_event(&self, event: &str, key: &str) {
+        let events = self.settings.get_notify_keyspace_events();
+        let mut notifications = vec![];
+        if events.keyspace {
+            notifications.push((format!("{}{}", KEYSPACE_CHANNEL_PREFIX, key), event));
+        }
+        if events.keyevent {
+            notifications.push((format!("{}{}", KEYEVENT_CHANNEL_PREFIX, event), key));
+        }
+        for (channel, payload) in notifications {
+            let message = RespMessage::SimpleString(payload.to_string());
+            if let Err(e) = publish(String::new(), channel, &self.pubsub_sender, &message) {
+                self.logger
+                    .log_debug(format!("Keyspace notification failed: {}", e));
+            }
+        }
+    }
+
+    /// Agrega un comando como array RESP al AOF y lo envía a las conexiones
+    /// sincronizadas con `SYNC`. Las que se cerraron dejan de recibirlos.
+    fn propagate(&mut self, arguments: Vec<String>) {
+        let message = RespMessage::Array(
+            arguments
+                .into_iter()
+                .map(|arg| RespMessage::BulkString(Some(arg.into_bytes())))
+                .collect(),
+        );
+        self.logger.append(message.as_bytes());
+        if self.sync_clients.is_empty() {
+            return;
+        }
+        self.sync_clients
+            .retain(|client| client.send(message.clone()).is_ok());
+    }
+
+    /// Cambia el rol del nodo (REPLICAOF).
+    ///
+    /// Como réplica, el nodo pasa a atender los slots de su master, rechaza
+    /// las escrituras y pide de inmediato un PSYNC, que luego se repite
+    /// periódicamente. Con `NO ONE` vuelve a ser master de esos slots.
+    ///
+    /// # Argumentos
+    ///
+    /// * `master` - Host y puerto del master, `None` para `NO ONE`
+    ///
+    /// # Retorna
+    ///
+    /// `RespMessage` con el resultado
+    fn execute_replicaof_command(&mut self, master: Option<&(String, u16)>) -> RespMessage {
+        let Some((host, port)) = master else {
+            let mut myself = self.data_lock.write().unwrap();
+            if !NodeFlags::state_contains(myself.get_state(), MASTER) {
+                myself.set_as_master();
+                myself.add_cepoch();
+                self.logger
+                    .log_notice("Replica promoted to master with REPLICAOF NO ONE".to_string());
+            }
+            return RespMessage::from_response(ResponseType::Str("OK".to_string()));
+        };
+
+        let nodes = self.nodes_list.read().unwrap();
+        let Some(master) = nodes
+            .values()
+            .find(|node| node_listens_on(node, host, *port))
+        else {
+            return RespMessage::Error(ERR_UNKNOWN_MASTER.to_string());
+        };
+        if master.is_slave() {
+            return RespMessage::Error(ERR_REPLICATE_REPLICA.to_string());
+        }
+        let (master_id, master_slots) = (master.get_id(), master.get_slots());
+        drop(nodes);
+
+        let mut myself = self.data_lock.write().unwrap();
+        if myself.get_id() == master_id {
+            return RespMessage::Error(ERR_REPLICATE_MYSELF.to_string());
+        }
+        myself.set_as_slave(master_id.clone());
+        myself.set_slots(master_slots);
+        myself.set_replication_offset(0);
+        drop(myself);
+        self.failover = None;
+
+        self.logger
+            .log_notice(format!("Replicating master {}", master_id));
+        if let Some(cluster_bus) = &self.cluster_bus {
+            psync_send(
+                &self.data_lock,
+                &self.ds_guard,
+                cluster_bus,
+                &self.nodes_list,
+            );
+        }
+        RespMessage::from_response(ResponseType::Str("OK".to_string()))
+    }
+
+    /// Completa el failover: este nodo pasa a ser réplica de `replica_id`,
+    /// que asume sus slots, y se anuncia el cambio al resto del cluster.
+    ///
+    /// # Argumentos
+    ///
+    /// * `replica_id` - Réplica a promover
+    fn switch_to_replica(&mut self, replica_id: NodeId) {
+        let myself = self.data_lock.read().unwrap();
+        let (my_id, my_ip, my_port) = (myself.get_id(), myself.get_ip(), myself.get_port());
+        let promotion = PromotionMessage::new(
+            replica_id.clone(),
+            my_id.clone(),
+            myself.get_slots(),
+            myself.get_cepoch() + 1,
+        );
+        drop(myself);
+
+        apply_manual_failover(&promotion, &self.data_lock, &self.nodes_list);
+
+        if let Some(cluster_bus) = &self.cluster_bus {
+            let bytes = promotion.serialize();
+            let message = NodeMessage::new(
+                my_id,
+                my_ip,
+                my_port,
+                NEW_MASTER_TYPE,
+                bytes.len() as u16,
+                bytes,
+            )
+            .serialize();
+            for node in self.nodes_list.read().unwrap().values() {
+                let _ = cluster_bus.send((node.get_id(), node.get_addr(), Some(message.clone())));
+            }
+        }
+        self.logger.log_notice(format!(
+            "Manual failover completed, {} is the new master",
+            replica_id
+        ));
+    }
+
+    /// Actualiza la recencia de la clave accedida, salvo que la
+    /// conexión tenga activo el flag NO-TOUCH.
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `key` - Clave accedida
+    fn touch_key(&self, client_id: &str, key: &str) {
+        let no_touch = self
+            .clients
+            .read()
+            .map(|clients| clients.get(client_id).is_some_and(|flags| flags.no_touch))
+            .unwrap_or(false);
+        if no_touch {
+            return;
+        }
+
+        match self.ds_guard.write() {
+            Ok(mut guard) => {
+                guard.touch(key);
+            }
+            Err(e) => self
+                .logger
+                .log_error(format!("Error updating access time of {}: {}", key, e)),
+        }
+    }
+
+    /// Ejecuta una instrucción con manejo de snapshots automáticos.
+    ///
+    /// # Argumentos
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `instruction` - Instrucción a ejecutar
+    /// * `pubsub_sender` - Sender para PubSub
+    /// * `response_sender` - Sender para respuesta
+    ///
+    /// # Retorna
+    ///
+    /// `RespMessage` con el resultado de la ejecución
+    pub fn execute_instruction(
+        &mut self,
+        client_id: String,
+        instruction: Instruction,
+        pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
+        response_sender: &Sender<RespMessage>,
+    ) -> RespMessage {
+        // Verificar si necesitamos crear un snapshot
+        if self.counter > 0 && self.counter % self.settings.get_snapshot_k_changes() == 0 {
+            let started = Instant::now();
+            if let Err(e) = self.create_auto_snapshot() {
+                self.logger
+                    .log_error(format!("Error creating auto-snapshot: {}", e));
+            }
+            self.latency.record(SAVE_EVENT, started.elapsed());
+        }
+
+        self.active_expire_cycle();
+
+        let started = Instant::now();
+        let response = self
+            .try_execute(client_id, &instruction, pubsub_sender, response_sender)
+            .unwrap_or_else(|e| {
+                self.logger.log_debug(format!("{}", e));
+                RespMessage::Error(e.to_string())
+            });
+        if self.latency.record(COMMAND_EVENT, started.elapsed()) {
+            self.logger.log_warning(format!(
+                "Slow command {} took {}ms",
+                instruction.instruction_type,
+                started.elapsed().as_millis()
+            ));
+        }
+        if self.settings.get_debug_invariants() {
+            self.check_invariants(&instruction.instruction_type);
+        }
+        response
+    }
+
+    /// Verifica los invariantes del store y de la asignación de slots tras
+    /// ejecutar `command`. Las violaciones se registran en el log; con la
+    /// feature `strict-invariants`, además, hacen panic.
+    fn check_invariants(&self, command: &str) {
+        let mut violations = match self.ds_guard.read() {
+            Ok(guard) => check_store(&guard),
+            Err(_) => return,
+        };
+        let own = self
+            .data_lock
+            .read()
+            .ok()
+            .filter(|myself| NodeFlags::state_contains(myself.get_state(), MASTER))
+            .map(|myself| (myself.get_id(), myself.get_slots()));
+        if let Ok(nodes) = self.nodes_list.read() {
+            violations.extend(check_slot_ownership(own, &nodes));
+        }
+
+        for violation in &violations {
+            self.logger.log_error(format!(
+                "Invariant violated after {}: {}",
+                command, violation
+            ));
+        }
+        #[cfg(feature = "strict-invariants")]
+        if let Some(violation) = violations.first() {
+            panic!("Invariant violated after {}: {}", command, violation);
+        }
+    }
+
+    /// Expiración activa: antes de cada instrucción borra como mucho
+    /// `ACTIVE_EXPIRE_LIMIT` claves vencidas, para que las claves con TTL
+    /// que nadie vuelve a tocar no se acumulen. Sólo toma el lock de
+    /// escritura si hay algún vencimiento ya cumplido.
+    fn active_expire_cycle(&self) {
+        let now = Instant::now();
+        let due = self
+            .ds_guard
+            .read()
+            .is_ok_and(|guard| guard.next_expiry().is_some_and(|deadline| deadline <= now));
+        if !due {
+            return;
+        }
+        let expired = match self.ds_guard.write() {
+            Ok(mut guard) => guard.purge_expired(now, ACTIVE_EXPIRE_LIMIT),
+            Err(_) => return,
+        };
+        for key in expired {
+            self.logger.log_debug(format!("Key {} expired", key));
+            self.notify_keyspace_event(EXPIRED_EVENT, &key);
+        }
+    }
+
+    /// Expiración perezosa: borra las claves que toca `command` cuyo TTL
+    /// ya venció, antes de ejecutarlo.
+    fn expire_touched_keys(&self, command: &Command) {
+        let keys = command.keys();
+        let due = self
+            .ds_guard
+            .read()
+            .is_ok_and(|guard| keys.iter().any(|key| guard.needs_expiry(key)));
+        if !due {
+            return;
+        }
+        let expired: Vec<&String> = match self.ds_guard.write() {
+            Ok(mut guard) => keys
+                .into_iter()
+                .filter(|key| guard.expire_if_due(key))
+                .collect(),
+            Err(_) => return,
+        };
+        for key in expired {
+            self.logger.log_debug(format!("Key {} expired", key));
+            self.notify_keyspace_event(EXPIRED_EVENT, key);
+        }
+    }
+
+    /// Crea un snapshot automático del DataStore.
+    ///
+    /// # Retorna
+    ///
+    /// `Result<(), CommandExecutorError>`
+    fn create_auto_snapshot(&self) -> Result<(), CommandExecutorError> {
+        let guard = self
+            .ds_guard
+            .read()
+            .map_err(|e| CommandExecutorError::DataStoreReadError(e.to_string()))?;
+
+        let dst = &self.settings.get_snapshot_dst();
+        create_dump(&guard, dst).map_err(|e| CommandExecutorError::SnapshotError(e.to_string()))
+    }
+}
+
+impl Command {
+    /// Ejecuta la operación de **escritura** asociada sobre la base de datos seleccionada.
+    ///
+    /// # Argumentos
+    ///
+    /// * `store` - Referencia mutable al DataStore
+    /// * `_` - Configuración del nodo (no utilizada)
+    /// * `_` - Logger AOF (no utilizado)
+    ///
+    /// # Retorna
+    ///
+    /// `Result<ResponseType, CommandError>` - Respuesta de la operación
+    ///
+    /// # Errores
+    ///
+    /// * `WRONGTYPE` - El valor asociado a la clave no es del tipo soportado por la op
+    /// * `CommandError` - Otros errores de ejecución
+    pub fn execute_write(&self, store: &mut DataStore) -> Result<ResponseType, CommandError> {
+        let response = self.apply_write(store)?;
+        for key in self.keys() {
+            if self.ttl_policy() == TtlPolicy::Reset {
+                store.set_ttl(key, None);
+            }
+            store.remove_if_empty(key);
+            store.track_key(key);
+        }
+        Ok(response)
+    }
+
+    /// Aplica la escritura sobre el store, sin tocar el TTL de las claves
+    /// salvo que el comando lo defina por su cuenta ni borrar las
+    /// colecciones que quedan vacías.
+    fn apply_write(&self, store: &mut DataStore) -> Result<ResponseType, CommandError> {
+        match self {
+            // STRING COMMANDS
+            Command::Append(key, val) => str_concat(store, key, val),
+            Command::SetRange(key, offset, value) => set_range(store, key, *offset, value),
+            Command::Incr(key) => incr(store, key),
+            Command::Decr(key) => decr(store, key),
+            Command::IncrBy(key, increment) => incr_by(store, key, *increment),
+            Command::DecrBy(key, decrement) => decr_by(store, key, *decrement),
+            Command::Del(keys) => bulk_delete(store, keys),
+            Command::Getdel(key) => retrieve_delete(store, key),
+            Command::GetSet(key, value) => get_set(store, key, value),
+            Command::Set(key, value, options) => set(store, key.clone(), value.clone(), options),
+            Command::Setnx(key, value) => setnx(store, key, value),
+            Command::Mset(pairs) => mset(store, pairs),
+            Command::Pfadd(key, elements) => pfadd(store, key, elements),
+            Command::Pfmerge(destination, sources) => pfmerge(store, destination, sources),
+            Command::Expire(key, seconds) => expire(store, key, *seconds),
+            Command::PexpireAt(key, unix_ms) => pexpireat(store, key, *unix_ms),
+            Command::Persist(key) => persist(store, key),
+            Command::Rename(source, destination) => rename(store, source, destination),
+            Command::Renamenx(source, destination) => renamenx(store, source, destination),
+
+            // LIST COMMANDS
+            Command::Lpop(key, amount) | Command::Rpop(key, amount) => {
+                list_pop(store, key, amount, &self)
+            }
+            Command::Lpush(key, val) => left_push(store, key, val),
+            Command::Lset(key, index, value) => lset(store, key, *index, value),
+            Command::Lrem(key, count, value) => lrem(store, key, *count, value),
+            Command::Linsert(key, place, pivot, value) => linsert(store, key, *place, pivot, value),
+            Command::LMove(source, destination, from, to) => {
+                lmove(store, source, destination, *from, *to)
+            }
+            Command::Rpush(key, values) => append(store, key.clone(), values.clone()),
+
+            // SET COMMANDS
+            Command::Sadd(key, values) => sadd(store, key.clone(), values.clone()),
+            Command::SMove(source, destination, value) => {
+                move_data_to_other_set(store, source, destination, value)
+            }
+            Command::Spop(key, amount) => set_pop(store, key, amount),
+            Command::Srem(key, members) => set_remove(store, key, members),
+
+            // HASH COMMANDS
+            Command::Hset(key, fields) => hset(store, key, fields),
+            Command::Hdel(key, fields) => hdel(store, key, fields),
+
+            // GEO COMMANDS
+            Command::Geoadd(key, members) => geoadd(store, key, members),
+
+            // STREAM COMMANDS
+            Command::Xadd(key, id, fields) => xadd(store, key, id, fields),
+            Command::XgroupCreate(key, group, id, mkstream) => {
+                xgroup_create(store, key, group, id, *mkstream)
+            }
+            Command::Xreadgroup(group, consumer, count, streams) => {
+                xreadgroup(store, group, consumer, *count, streams)
+            }
+            Command::Xack(key, group, ids) => xack(store, key, group, ids),
+
+            // DB COMMANDS
+            Command::Restore(key, ttl, payload, replace) => {
+                restore(store, key, ttl, payload, replace)
+            }
+            Command::FlushDb => flushdb(store),
+
+            _ => Err(CommandError::Custom("Error non write command".to_string())),
+        }
+    }
+
+    /// Ejecuta la operación de **lectura** asociada sobre la base de datos seleccionada.
+    ///
+    /// Precondición: Ejecutarla con un lock no bloqueante de tipo lectura del DataStore.
+    ///
+    /// # Argumentos
+    ///
+    /// * `store` - Referencia al DataStore
+    /// * `settings` - Configuración del nodo
+    /// * `logger` - Logger AOF
+    /// * `pub_sub_context` - Contexto para operaciones PubSub
+    ///
+    /// # Retorna
+    ///
+    /// `Result<ResponseType, CommandError>` - Respuesta de la operación
+    ///
+    /// # Errores
+    ///
+    /// * `WRONGTYPE` - El valor asociado a la clave no es del tipo soportado por la op
+    /// * `CommandError` - Otros errores de ejecución
+    pub fn execute_read(
+        &self,
+        store: &DataStore,
+        settings: Option<NodeConfigs>,
+        logger: Option<Arc<AofLogger>>,
+        pub_sub_context: Option<PubSubContext>,
+        node_data: Option<&Arc<RwLock<NodeData>>>,
+        known_nodes: Option<&Arc<RwLock<HashMap<NodeId, KnownNode>>>>,
+    ) -> Result<ResponseType, CommandError> {
+        match self {
+            // STRING COMMANDS
+            Command::Echo(val) => Ok(ResponseType::Str(format!("{}", val))),
+            Command::Ping(message) => Ok(ResponseType::Str(
+                message.clone().unwrap_or_else(|| "PONG".to_string()),
+            )),
+            Command::Get(key) => get(store, key),
+            Command::Substr(key, start, end) | Command::Getrange(key, start, end) => {
+                string_slice(store, key, start, end)
+            }
+            Command::Strlen(key) => get_len(store, key, &self),
+            Command::Pfcount(keys) => pfcount(store, keys),
+
+            // LIST COMMANDS
+            Command::Llen(key) => get_len(store, key, &self),
+            Command::Lrange(key, start, end) => get_slice(store, key, *start, *end),
+            Command::Lindex(key, index) => lindex(store, key, *index),
+
+            // SET COMMANDS
+            Command::Scard(key) => get_len(store, key, &self),
+            Command::Sismember(key, val) => get_set_data(store, key, val),
+            Command::Smembers(key) => get_set_items(store, key),
+            Command::Sinter(keys) => sinter(store, keys),
+            Command::Sunion(keys) => sunion(store, keys),
+            Command::Sdiff(keys) => sdiff(store, keys),
+            Command::SRandMember(key, count) => set_random_members(store, key, *count),
+
+            // HASH COMMANDS
+            Command::Hget(key, field) => hget(store, key, field),
+            Command::Hgetall(key) => hgetall(store, key),
+
+            // GEO COMMANDS
+            Command::Geodist(key, from, to, unit) => geodist(store, key, from, to, *unit),
+
+            // STREAM COMMANDS
+            Command::Xlen(key) => xlen(store, key),
+            Command::Xrange(key, start, end, count) => xrange(store, key, *start, *end, *count),
+            Command::Xread(count, _, streams) => xread(store, *count, streams),
+            Command::Xpending(key, group) => xpending(store, key, group),
+
+            // PERSISTENCE COMMANDS
+            Command::Dump(key) => dump(store, key),
+            Command::Exists(keys) => exists(store, keys),
+            Command::Ttl(key) => ttl(store, key),
+            Command::Type(key) => key_type(store, key),
+            Command::Object(ObjectSubcommand::Encoding(key)) => object_encoding(store, key),
+            Command::Scan(cursor, pattern, count) => scan(store, *cursor, pattern, *count),
+            Command::Keys(pattern) => keys(store, pattern),
+            Command::DbSize => dbsize(store),
+            Command::BgSave => {
+                let settings =
+                    settings.ok_or_else(|| CommandError::Custom("Settings missing".to_string()))?;
+                let logger =
+                    unwrap_or_fail_arc(logger, "logger").map_err(|e| CommandError::Custom(e))?;
+                backup_ds(store, settings, logger, true)
+            }
+            Command::Save => {
+                let settings =
+                    settings.ok_or_else(|| CommandError::Custom("Settings missing".to_string()))?;
+                let logger =
+                    unwrap_or_fail_arc(logger, "logger").map_err(|e| CommandError::Custom(e))?;
+                backup_ds(store, settings, logger, false)
+            }
+
+            // PUBSUB COMMANDS
+            Command::Subscribe(channel_id) => {
+                let context = pub_sub_context
+                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
+                subscribe(
+                    context.get_cid(),
+                    channel_id.clone(),
+                    context.get_sender(),
+                    &context.get_res_sender(),
+                )
+            }
+            Command::Unsubscribe(channel_id) => {
+                let context = pub_sub_context
+                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
+                unsubscribe(context.get_cid(), channel_id.clone(), context.get_sender())
+            }
+            Command::PSubscribe(patterns) => {
+                let context = pub_sub_context
+                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
+                psubscribe(
+                    context.get_cid(),
+                    patterns.clone(),
+                    context.get_sender(),
+                    context.get_res_sender(),
+                )
+            }
+            Command::PUnsubscribe(patterns) => {
+                let context = pub_sub_context
+                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
+                punsubscribe(context.get_cid(), patterns.clone(), context.get_sender())
+            }
+            Command::Publish(channel_id, message) => {
+                let context = pub_sub_context
+                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
+                publish(
+                    context.get_cid(),
+                    channel_id.to_string(),
+                    context.get_sender(),
+                    message,
+                )
+            }
+            Command::Meet(ip) => {
+                let settings =
+                    settings.ok_or_else(|| CommandError::Custom("Settings missing".to_string()))?;
+                send_first_ping(ip, settings)
+            }
+            Command::Slots => {
+                let data = node_data
+                    .ok_or_else(|| CommandError::Custom("Node data missing".to_string()))?;
+                let cluster_nodes = known_nodes
+                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
+                return_cluster_slots_data(data, cluster_nodes)
+            }
+            Command::ClusterNodes => {
+                let data = node_data
+                    .ok_or_else(|| CommandError::Custom("Node data missing".to_string()))?;
+                let cluster_nodes = known_nodes
+                    .ok_or_else(|| CommandError::Custom("PubSub context missing".to_string()))?;
+                return_cluster_nodes_data(data, cluster_nodes)
+            }
+            _ => Err(CommandError::Custom(
+                "Error non only-read command".to_string(),
+            )),
+        }
+    }
+
+    /// Determina si el comando realiza operaciones de escritura en la base de datos.
+    ///
+    /// # Retorna
+    ///
+    /// `true` si el comando es de escritura, `false` en caso contrario
+    pub fn writes_on_db(&self) -> bool {
+        matches!(
+            self,
+            Command::Append(_, _)
+                | Command::Incr(_)
+                | Command::Decr(_)
+                | Command::IncrBy(_, _)
+                | Command::DecrBy(_, _)
+                | Command::Expire(_, _)
+                | Command::PexpireAt(_, _)
+                | Command::Persist(_)
+                | Command::Del(_)
+                | Command::Set(_, _, _)
+                | Command::Setnx(_, _)
+                | Command::Mset(_)
+                | Command::Getdel(_)
+                | Command::GetSet(_, _)
+                | Command::SetRange(_, _, _)
+                | Command::Lpop(_, _)
+                | Command::Rpop(_, _)
+                | Command::Lpush(_, _)
+                | Command::Lset(_, _, _)
+                | Command::Lrem(_, _, _)
+                | Command::Linsert(..)
+                | Command::LMove(..)
+                | Command::Rpush(_, _)
+                | Command::Sadd(_, _)
+                | Command::SMove(_, _, _)
+                | Command::Spop(_, _)
+                | Command::Srem(_, _)
+                | Command::Hset(_, _)
+                | Command::Hdel(_, _)
+                | Command::Geoadd(_, _)
+                | Command::Restore(_, _, _, _)
+                | Command::FlushDb
+                | Command::Rename(_, _)
+                | Command::Renamenx(_, _)
+                | Command::Pfadd(_, _)
+                | Command::Pfmerge(_, _)
+                | Command::Xadd(_, _, _)
+                | Command::XgroupCreate(_, _, _, _)
+                | Command::Xreadgroup(_, _, _, _)
+                | Command::Xack(_, _, _)
+        )
+    }
+}
+
+/// Arma la respuesta con la longitud y la latitud de una posición.
+fn coordinates_reply((lon, lat): (f64, f64)) -> RespMessage {
+    RespMessage::Array(vec![
+        RespMessage::BulkString(Some(lon.to_string().into_bytes())),
+        RespMessage::BulkString(Some(lat.to_string().into_bytes())),
+    ])
+}
+
+/// Desenvuelve un Option<Arc<T>> o falla con un mensaje de error descriptivo.
+///
+/// # Argumentos
+///
+/// * `opt` - Option a desenvolver
+/// * `name` - Nombre del recurso para el mensaje de error
+///
+/// # Retorna
+///
+/// `Result<Arc<T>, String>`
+fn unwrap_or_fail_arc<T>(opt: Option<Arc<T>>, name: &str) -> Result<Arc<T>, String> {
+    opt.ok_or_else(|| format!("Missing required dependency: {}", name))
+}
+
+/// Extrae la clave principal del comando si aplica (para hash slot).
+///
+/// # Argumentos
+///
+/// * `cmd` - Comando a analizar
+/// * `slot_count` - Cantidad de slots del cluster, para validar comandos multi-clave
+///
+/// # Retorna
+///
+/// * `Ok(Some(key))` - Clave principal si aplica
+/// * `Ok(None)` - Si el comando no opera sobre claves
+/// * `Err(String)` - Mensaje de error si las claves caen en slots distintos
+fn get_key_for_command(cmd: &Command, slot_count: u16) -> Result<Option<String>, String> {
+    let key = match cmd {
+        Command::Append(key, _)
+        | Command::Get(key)
+        | Command::Incr(key)
+        | Command::Decr(key)
+        | Command::IncrBy(key, _)
+        | Command::DecrBy(key, _)
+        | Command::Getdel(key)
+        | Command::GetSet(key, _)
+        | Command::Set(key, _, _)
+        | Command::Setnx(key, _)
+        | Command::Strlen(key)
+        | Command::Substr(key, _, _)
+        | Command::Getrange(key, _, _)
+        | Command::SetRange(key, _, _)
+        | Command::Llen(key)
+        | Command::Lpop(key, _)
+        | Command::Rpop(key, _)
+        | Command::Lpush(key, _)
+        | Command::Rpush(key, _)
+        | Command::Lrange(key, _, _)
+        | Command::Lindex(key, _)
+        | Command::Lset(key, _, _)
+        | Command::Lrem(key, _, _)
+        | Command::Linsert(key, ..)
+        | Command::Sort(key, ..)
+        | Command::Scard(key)
+        | Command::Sismember(key, _)
+        | Command::Smembers(key)
+        | Command::SRandMember(key, _)
+        | Command::Sadd(key, _)
+        | Command::Spop(key, _)
+        | Command::Srem(key, _)
+        | Command::Hset(key, _)
+        | Command::Hget(key, _)
+        | Command::Hdel(key, _)
+        | Command::Hgetall(key)
+        | Command::Geoadd(key, _)
+        | Command::Geopos(key, _)
+        | Command::Geodist(key, _, _, _)
+        | Command::Geosearch(key, _)
+        | Command::Dump(key)
+        | Command::Expire(key, _)
+        | Command::PexpireAt(key, _)
+        | Command::Persist(key)
+        | Command::Ttl(key)
+        | Command::Type(key)
+        | Command::Object(ObjectSubcommand::Encoding(key))
+        | Command::Restore(key, _, _, _)
+        | Command::Pfadd(key, _)
+        | Command::Xadd(key, _, _)
+        | Command::Xlen(key)
+        | Command::Xrange(key, _, _, _)
+        | Command::XgroupCreate(key, _, _, _)
+        | Command::Xack(key, _, _)
+        | Command::Xpending(key, _) => Some(key.clone()),
+        Command::Mget(keys)
+        | Command::Pfcount(keys)
+        | Command::Exists(keys)
+        | Command::Touch(keys)
+        | Command::Sinter(keys)
+        | Command::Sunion(keys)
+        | Command::Sdiff(keys) => {
+            return same_slot_key(keys.iter().map(String::as_str), slot_count);
+        }
+        Command::Mset(pairs) => {
+            return same_slot_key(pairs.iter().map(|(key, _)| key.as_str()), slot_count);
+        }
+        Command::Pfmerge(destination, sources) => {
+            let keys = std::iter::once(destination).chain(sources);
+            return same_slot_key(keys.map(String::as_str), slot_count);
+        }
+        Command::Xread(_, _, streams) => {
+            return same_slot_key(streams.iter().map(|(key, _)| key.as_str()), slot_count);
+        }
+        Command::Xreadgroup(_, _, _, streams) => {
+            return same_slot_key(streams.iter().map(|(key, _)| key.as_str()), slot_count);
+        }
+
+        //Command::Del(keys) => Some(keys),
+        Command::SMove(source, destination, ..)
+        | Command::LMove(source, destination, ..)
+        | Command::Rename(source, destination)
+        | Command::Renamenx(source, destination) => {
+            // Requiere que ambos estén en el mismo slot
+            let slot_src = hash_slot_with_count(source, slot_count)
+                .map_err(|_| format!("ERR Invalid key: {}", source))?;
+            let slot_dst = hash_slot_with_count(destination, slot_count)
+                .map_err(|_| format!("ERR Invalid key: {}", destination))?;
+            if slot_src != slot_dst {
+                return Err(format!(
+                    "CROSSSLOT Keys {} and {} hash to different slots",
+                    source, destination
+                ));
+            }
+            Some(source.clone()) // Usamos una para comprobar si el nodo lo maneja
+        }
+
+        // Comandos sin clave (como PING, QUIT, SUBSCRIBE, etc.)
+        _ => None,
+    };
+    Ok(key)
+}
+
+/// Verifica que todas las claves de un comando multi-clave caigan en el
+/// mismo slot.
+///
+/// # Argumentos
+///
+/// * `keys` - Claves del comando
+/// * `slot_count` - Cantidad de slots del cluster
+///
+/// # Retorna
+///
+/// La primera clave, con la que se decide si el nodo atiende el comando, o
+/// `CROSSSLOT` si alguna clave cae en otro slot
+fn same_slot_key<'a>(
+    keys: impl Iterator<Item = &'a str>,
+    slot_count: u16,
+) -> Result<Option<String>, String> {
+    let keys: Vec<&str> = keys.collect();
+    match keys_same_slot_with_count(&keys, slot_count) {
+        Ok(true) => Ok(keys.first().map(|key| key.to_string())),
+        Ok(false) => Err(ERR_CROSSSLOT.to_string()),
+        Err(e) => Err(format!("ERR Invalid key: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cluster::sharding::hash_slot::MAX_HASH_SLOTS,
+        cluster::state::{flags::SLAVE, node_data::NodeData},
+        cluster::types::{EMPTY_SLOT_RANGE, NODE_ID_LEN, is_valid_node_id},
+        command::{Instruction, instruction::ArgumentReason, types::SetOptions},
+        config::node_configs::{EvictionPolicy, KeyspaceEvents, NodeConfigs},
+        logs::aof_logger::AofLogger,
+        pubsub::channel_manager::ChannelManager,
+        storage::{data_store::DataStore, deserializer::read_sync_payload},
+    };
+    use std::sync::mpsc;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Crea un DataStore de prueba.
+    fn create_test_datastore() -> Arc<RwLock<DataStore>> {
+        Arc::new(RwLock::new(DataStore::new()))
+    }
+
+    /// Crea un logger de prueba que escribe según la configuración dada.
+    fn create_test_logger(settings: &NodeConfigs) -> Arc<AofLogger> {
+        AofLogger::new(settings.clone())
+    }
+
+    /// Crea configuración de nodo de prueba.
+    fn create_test_settings() -> NodeConfigs {
+        // Crear un archivo de configuración temporal para el test
+        let config_content = r#"
+            bind 0.0.0.0
+            port 6379
+            role M
+            maxclients 1000
+            save 900 15
+            dbfilename dump.rdb
+            dir ./
+            logfile redis.log
+            loglevel notice
+            node-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca
+            hash-slots 0-16383
+            "#;
+
+        std::fs::write("test.conf", config_content).expect("Failed to write test nodes");
+        let result = NodeConfigs::new("test.conf");
+        std::fs::remove_file("test.conf").ok(); // Limpiar archivo temporal
+        result.expect("Failed to create test nodes")
+    }
+
+    /// Crea un CommandExecutor de prueba.
+    fn create_test_executor() -> (
+        CommandExecutor,
+        Sender<(String, Instruction, Sender<RespMessage>)>,
+    ) {
+        create_test_executor_with_settings(create_test_settings())
+    }
+
+    /// Crea un CommandExecutor de prueba con la configuración dada.
+    fn create_test_executor_with_settings(
+        settings: NodeConfigs,
+    ) -> (
+        CommandExecutor,
+        Sender<(String, Instruction, Sender<RespMessage>)>,
+    ) {
+        let (tx, rx) = mpsc::channel();
+        let mut node_data = NodeData::new(settings.clone());
+        node_data.set_as_master();
+
+        let executor = CommandExecutor::new(
+            create_test_datastore(),
+            rx,
+            settings.clone(),
+            create_test_logger(&settings),
+            mpsc::channel().0,
+            Arc::new(RwLock::new(HashMap::new())),
+            Arc::new(RwLock::new(node_data)),
+            Arc::new(RwLock::new(HashMap::new())),
+        );
+        (executor, tx)
+    }
+
+    /// Ejecuta una instrucción en nombre de un cliente.
+    fn run_instruction(
+        executor: &mut CommandExecutor,
+        client_id: &str,
+        cmd_type: &str,
+        args: &[&str],
+    ) -> RespMessage {
+        let (pubsub_tx, _) = mpsc::channel();
+        let (res_tx, _) = mpsc::channel();
+        let instruction = Instruction::new(
+            cmd_type.to_string(),
+            args.iter().map(|arg| arg.to_string()).collect(),
+        );
+        executor.execute_instruction(client_id.to_string(), instruction, &pubsub_tx, &res_tx)
+    }
+
+    /// Crea una instrucción de prueba.
+    #[allow(dead_code)]
+    fn create_test_instruction(cmd_type: &str, args: Vec<String>) -> Instruction {
+        Instruction {
+            instruction_type: cmd_type.to_string(),
+            arguments: args,
+        }
+    }
+
+    #[test]
+    fn test_command_executor_new() {
+        let (executor, _) = create_test_executor();
+        assert_eq!(executor.counter, 0);
+    }
+
+    #[test]
+    fn test_get_from_no_touch_client_keeps_access_time() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Tracer"]);
+        let stamp = *executor
+            .ds_guard
+            .read()
+            .unwrap()
+            .get_access_time("Hero")
+            .unwrap();
+
+        let response = run_instruction(&mut executor, "AAA001", "CLIENT", &["NO-TOUCH", "ON"]);
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let response = run_instruction(&mut executor, "AAA001", "GET", &["Hero"]);
+        assert_eq!(response, RespMessage::BulkString(Some(b"Tracer".to_vec())));
+        let guard = executor.ds_guard.read().unwrap();
+        assert_eq!(*guard.get_access_time("Hero").unwrap(), stamp);
+    }
+
+    #[test]
+    fn test_cluster_help_is_verbatim_text() {
+        let (mut executor, _) = create_test_executor();
+        let response = run_instruction(&mut executor, "AAA000", "CLUSTER", &["HELP"]);
+        match response {
+            RespMessage::Verbatim(format, text) => {
+                assert_eq!(format, "txt");
+                assert!(text.starts_with(b"CLUSTER <subcommand>"));
+            }
+            other => panic!("Respuesta inesperada: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mget_returns_null_for_missing_and_non_string_keys() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "SET",
+            &["{Overwatch}Hero", "Tracer"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "RPUSH",
+            &["{Overwatch}Team", "Winston"],
+        );
+
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "MGET",
+            &["{Overwatch}Hero", "{Overwatch}Team", "{Overwatch}Ghost"],
+        );
+        assert_eq!(
+            response,
+            RespMessage::Array(vec![
+                RespMessage::BulkString(Some(b"Tracer".to_vec())),
+                RespMessage::BulkString(None),
+                RespMessage::BulkString(None),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sort_get_returns_null_for_missing_keys() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "RPUSH", &["Roster", "2", "1"]);
+        run_instruction(&mut executor, "AAA000", "SET", &["hero_1", "Ana"]);
+
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "SORT",
+            &["Roster", "GET", "#", "GET", "hero_*"],
+        );
+        assert_eq!(
+            response,
+            RespMessage::Array(vec![
+                RespMessage::BulkString(Some(b"1".to_vec())),
+                RespMessage::BulkString(Some(b"Ana".to_vec())),
+                RespMessage::BulkString(Some(b"2".to_vec())),
+                RespMessage::BulkString(None),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_geo_commands_reply_nested_arrays() {
+        let (mut executor, _) = create_test_executor();
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "GEOADD",
+            &[
+                "Maps",
+                "-0.1278",
+                "51.5074",
+                "King's Row",
+                "2.3522",
+                "48.8566",
+                "Paris",
+            ],
+        );
+        assert_eq!(response, RespMessage::Integer(2));
+
+        let response =
+            run_instruction(&mut executor, "AAA000", "GEOPOS", &["Maps", "Paris", "Rio"]);
+        let RespMessage::Array(positions) = response else {
+            panic!("GEOPOS should reply an array, got {:?}", response);
+        };
+        assert!(matches!(&positions[0], RespMessage::Array(pair) if pair.len() == 2));
+        assert_eq!(positions[1], RespMessage::BulkString(None));
+
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "GEOSEARCH",
+            &[
+                "Maps",
+                "FROMMEMBER",
+                "Paris",
+                "BYRADIUS",
+                "10",
+                "km",
+                "WITHDIST",
+            ],
+        );
+        assert_eq!(
+            response,
+            RespMessage::Array(vec![RespMessage::Array(vec![
+                RespMessage::BulkString(Some(b"Paris".to_vec())),
+                RespMessage::BulkString(Some(b"0.0000".to_vec())),
+            ])])
+        );
+
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Tracer"]);
+        let response = run_instruction(&mut executor, "AAA000", "GEOPOS", &["Hero", "Tracer"]);
+        assert!(matches!(response, RespMessage::Error(e) if e.starts_with("WRONGTYPE")));
+    }
+
+    #[test]
+    fn test_mset_values_are_read_back_by_mget() {
+        let (mut executor, _) = create_test_executor();
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "MSET",
+            &["{Overwatch}Hero", "Tracer", "{Overwatch}Team", "Overwatch"],
+        );
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "MGET",
+            &["{Overwatch}Hero", "{Overwatch}Team", "{Overwatch}Ghost"],
+        );
+        assert_eq!(
+            response,
+            RespMessage::Array(vec![
+                RespMessage::BulkString(Some(b"Tracer".to_vec())),
+                RespMessage::BulkString(Some(b"Overwatch".to_vec())),
+                RespMessage::BulkString(None),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_get_from_normal_client_updates_access_time() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Tracer"]);
+        let stamp = *executor
+            .ds_guard
+            .read()
+            .unwrap()
+            .get_access_time("Hero")
+            .unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        run_instruction(&mut executor, "AAA001", "GET", &["Hero"]);
+        let guard = executor.ds_guard.read().unwrap();
+        assert!(*guard.get_access_time("Hero").unwrap() > stamp);
+    }
+
+    #[test]
+    fn test_touch_counts_only_existing_keys() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "SET",
+            &["{Overwatch}Hero", "Venture"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "RPUSH",
+            &["{Overwatch}Tanks", "Mauga"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "SADD",
+            &["{Overwatch}Roles", "Support"],
+        );
+
+        let args = [
+            "{Overwatch}Hero",
+            "{Overwatch}Tanks",
+            "{Overwatch}Roles",
+            "{Overwatch}Villain",
+            "{Overwatch}Hero",
+        ];
+        let response = run_instruction(&mut executor, "AAA000", "TOUCH", &args);
+        assert_eq!(response, RespMessage::Integer(4));
+
+        let guard = executor.ds_guard.read().unwrap();
+        assert!(guard.get_access_time("{Overwatch}Villain").is_none());
+    }
+
+    #[test]
+    fn test_touch_updates_access_time_even_with_no_touch() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Venture"]);
+        let stamp = *executor
+            .ds_guard
+            .read()
+            .unwrap()
+            .get_access_time("Hero")
+            .unwrap();
+        run_instruction(&mut executor, "AAA001", "CLIENT", &["NO-TOUCH", "ON"]);
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let response = run_instruction(&mut executor, "AAA001", "TOUCH", &["Hero"]);
+        assert_eq!(response, RespMessage::Integer(1));
+        let guard = executor.ds_guard.read().unwrap();
+        assert!(*guard.get_access_time("Hero").unwrap() > stamp);
+        drop(guard);
+
+        // TOUCH no es una escritura: no cuenta para los snapshots
+        assert_eq!(executor.counter, 1);
+        let response = run_instruction(&mut executor, "AAA001", "TOUCH", &[]);
+        assert!(matches!(response, RespMessage::Error(e) if e.contains("wrong number")));
+    }
+
+    /// Crea un executor con `maxmemory` en `limit` bytes y la política dada.
+    fn create_test_executor_with_maxmemory(limit: u64, policy: EvictionPolicy) -> CommandExecutor {
+        let mut settings = create_test_settings();
+        settings.set_maxmemory(limit);
+        settings.set_maxmemory_policy(policy);
+        create_test_executor_with_settings(settings).0
+    }
+
+    #[test]
+    fn test_allkeys_lru_evicts_least_recently_accessed_key() {
+        // "Tracer" + "Blink" y "Reaper" + "Wraith" ocupan 11 y 12 bytes
+        let mut executor = create_test_executor_with_maxmemory(23, EvictionPolicy::AllKeysLru);
+        run_instruction(&mut executor, "AAA000", "SET", &["Tracer", "Blink"]);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        run_instruction(&mut executor, "AAA000", "SET", &["Reaper", "Wraith"]);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        run_instruction(&mut executor, "AAA000", "GET", &["Tracer"]);
+        assert_eq!(executor.ds_guard.read().unwrap().approx_memory_usage(), 23);
+
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Sombra", "Hack"]);
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+
+        let guard = executor.ds_guard.read().unwrap();
+        assert!(!guard.contains_key("Reaper"));
+        assert!(guard.contains_key("Tracer"));
+        assert!(guard.contains_key("Sombra"));
+        assert!(guard.get_access_time("Reaper").is_none());
+        assert!(guard.approx_memory_usage() <= 23);
+    }
+
+    #[test]
+    fn test_allkeys_lfu_evicts_least_frequently_accessed_key() {
+        let mut executor = create_test_executor_with_maxmemory(23, EvictionPolicy::AllKeysLfu);
+        run_instruction(&mut executor, "AAA000", "SET", &["Tracer", "Blink"]);
+        run_instruction(&mut executor, "AAA000", "GET", &["Tracer"]);
+        run_instruction(&mut executor, "AAA000", "GET", &["Tracer"]);
+        run_instruction(&mut executor, "AAA000", "SET", &["Reaper", "Wraith"]);
+
+        run_instruction(&mut executor, "AAA000", "SET", &["Sombra", "Hack"]);
+
+        // Reaper es la clave más reciente, pero la menos usada
+        let guard = executor.ds_guard.read().unwrap();
+        assert!(!guard.contains_key("Reaper"));
+        assert!(guard.contains_key("Tracer"));
+        assert!(guard.contains_key("Sombra"));
+    }
+
+    #[test]
+    fn test_eviction_makes_room_before_a_large_write() {
+        let mut executor = create_test_executor_with_maxmemory(64, EvictionPolicy::AllKeysLru);
+        for (hero, role) in [
+            ("Tracer", "Damage"),
+            ("Reaper", "Damage"),
+            ("Mercy", "Support"),
+            ("Winston", "Tank"),
+        ] {
+            run_instruction(&mut executor, "AAA000", "SET", &[hero, role]);
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        let sync = handle(&mut executor, "BBB111", "SYNC", &[]);
+        sync.try_recv().unwrap();
+
+        let ultimate = "Nano Boost ".repeat(4);
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Ana", &ultimate]);
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+
+        let guard = executor.ds_guard.read().unwrap();
+        assert!(guard.approx_memory_usage() <= 64);
+        assert!(guard.contains_key("Ana"));
+        assert!(guard.contains_key("Winston"));
+        let mut recomputed = guard.clone();
+        recomputed.recompute_memory_usage();
+        assert_eq!(
+            recomputed.approx_memory_usage(),
+            guard.approx_memory_usage()
+        );
+        drop(guard);
+
+        // Las réplicas borran las claves desalojadas antes de la escritura
+        let propagated: Vec<Vec<String>> = sync.try_iter().map(propagated_arguments).collect();
+        assert_eq!(
+            propagated,
+            vec![
+                vec!["DEL", "Tracer"],
+                vec!["DEL", "Reaper"],
+                vec!["DEL", "Mercy"],
+                vec!["SET", "Ana", ultimate.as_str()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_noeviction_rejects_writes_over_maxmemory() {
+        let mut executor = create_test_executor_with_maxmemory(10, EvictionPolicy::NoEviction);
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Tracer", "Blink"]);
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Reaper", "Wraith"]);
+        assert_eq!(response, RespMessage::Error(ERR_OOM.to_string()));
+        assert!(!executor.ds_guard.read().unwrap().contains_key("Reaper"));
+
+        // Los comandos que liberan memoria se siguen aceptando
+        let response = run_instruction(&mut executor, "AAA000", "DEL", &["Tracer"]);
+        assert_eq!(response, RespMessage::Integer(1));
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Reaper", "Wraith"]);
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+    }
+
+    #[test]
+    fn test_client_no_evict_sets_connection_flag() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "CLIENT", &["NO-EVICT", "ON"]);
+        let clients = executor.clients.read().unwrap();
+        let flags = clients.get("AAA000").unwrap();
+        assert!(flags.no_evict);
+        assert!(!flags.no_touch);
+    }
+
+    #[test]
+    fn test_client_name_is_per_connection() {
+        let (mut executor, _) = create_test_executor();
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "CLIENT",
+            &["SETNAME", "editor-Mei"],
+        );
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+
+        assert_eq!(
+            run_instruction(&mut executor, "AAA000", "CLIENT", &["GETNAME"]),
+            RespMessage::BulkString(Some(b"editor-Mei".to_vec()))
+        );
+        assert_eq!(
+            run_instruction(&mut executor, "AAA001", "CLIENT", &["GETNAME"]),
+            RespMessage::BulkString(Some(vec![]))
+        );
+
+        run_instruction(&mut executor, "AAA000", "CLIENT", &["SETNAME", ""]);
+        assert_eq!(
+            run_instruction(&mut executor, "AAA000", "CLIENT", &["GETNAME"]),
+            RespMessage::BulkString(Some(vec![]))
+        );
+    }
+
+    #[test]
+    fn test_client_list_shows_every_connection() {
+        let (mut executor, _) = create_test_executor();
+        {
+            let mut clients = executor.clients.write().unwrap();
+            for (id, port) in [("AAA000", 50000), ("AAA001", 50001)] {
+                let flags = ClientFlags {
+                    addr: Some(SocketAddr::from(([127, 0, 0, 1], port))),
+                    connected_at: Some(Instant::now()),
+                    ..ClientFlags::default()
+                };
+                clients.insert(id.to_string(), flags);
+            }
+        }
+        run_instruction(
+            &mut executor,
+            "AAA001",
+            "CLIENT",
+            &["SETNAME", "editor-Sigma"],
+        );
+
+        let RespMessage::BulkString(Some(list)) =
+            run_instruction(&mut executor, "AAA000", "CLIENT", &["LIST"])
+        else {
+            panic!("CLIENT LIST debe responder un bulk string");
+        };
+
+        assert_eq!(
+            String::from_utf8(list).unwrap(),
+            "id=AAA000 addr=127.0.0.1:50000 name= age=0\n\
+             id=AAA001 addr=127.0.0.1:50001 name=editor-Sigma age=0\n"
+        );
+    }
+
+    #[test]
+    fn test_client_kill_signals_the_target_connection() {
+        let (mut executor, _) = create_test_executor();
+        let (output, responses) = mpsc::channel();
+        executor.clients.write().unwrap().insert(
+            "AAA001".to_string(),
+            ClientFlags {
+                output: Some(output),
+                ..ClientFlags::default()
+            },
+        );
+
+        let response =
+            run_instruction(&mut executor, "AAA000", "CLIENT", &["KILL", "ID", "AAA001"]);
+        assert_eq!(response, RespMessage::Integer(1));
+        assert!(executor.clients.read().unwrap()["AAA001"].killed);
+        assert_eq!(responses.try_recv().ok(), Some(RespMessage::Disconnect));
+
+        let response =
+            run_instruction(&mut executor, "AAA000", "CLIENT", &["KILL", "ID", "AAA001"]);
+        assert_eq!(response, RespMessage::Integer(0));
+
+        let response =
+            run_instruction(&mut executor, "AAA000", "CLIENT", &["KILL", "ID", "ZZZ999"]);
+        assert_eq!(response, RespMessage::Integer(0));
+    }
+
+    #[test]
+    fn test_hello_negotiates_protocol() {
+        let (mut executor, _) = create_test_executor();
+        let response = run_instruction(&mut executor, "AAA000", "HELLO", &["3"]);
+        let RespMessage::Array(fields) = response else {
+            panic!("HELLO debería responder un array, recibió {:?}", response);
+        };
+        let proto = fields
+            .iter()
+            .position(|field| *field == RespMessage::BulkString(Some(b"proto".to_vec())))
+            .unwrap();
+        assert_eq!(fields[proto + 1], RespMessage::Integer(3));
+        let protocol = |executor: &CommandExecutor| {
+            executor
+                .clients
+                .read()
+                .unwrap()
+                .get("AAA000")
+                .unwrap()
+                .protocol
+        };
+        assert_eq!(protocol(&executor), RespProtocol::Resp3);
+
+        let response = run_instruction(&mut executor, "AAA000", "HELLO", &["4"]);
+        assert_eq!(response, RespMessage::Error(ERR_NOPROTO.to_string()));
+        run_instruction(&mut executor, "AAA000", "HELLO", &[]);
+        assert_eq!(protocol(&executor), RespProtocol::Resp3);
+
+        run_instruction(&mut executor, "AAA000", "HELLO", &["2"]);
+        assert_eq!(protocol(&executor), RespProtocol::Resp2);
+    }
+
+    #[test]
+    fn test_resp3_subscriber_receives_pushes_and_runs_get() {
+        let (mut executor, _) = create_test_executor();
+        let (pubsub_tx, pubsub_rx) = mpsc::channel();
+        std::thread::spawn(move || ChannelManager::new(pubsub_rx).run());
+        let (sub_tx, sub_rx) = mpsc::channel();
+        let (pub_tx, _pub_rx) = mpsc::channel();
+        let mut run = |client_id: &str, res_tx: &Sender<RespMessage>, cmd: &str, args: &[&str]| {
+            let instruction = Instruction::new(
+                cmd.to_string(),
+                args.iter().map(|arg| arg.to_string()).collect(),
+            );
+            executor.execute_instruction(client_id.to_string(), instruction, &pubsub_tx, res_tx)
+        };
+        let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+
+        run("PUB000", &pub_tx, "SET", &["Map", "Kings Row"]);
+        run("SUB001", &sub_tx, "HELLO", &["3"]);
+        assert_eq!(
+            run("SUB001", &sub_tx, "SUBSCRIBE", &["payload"]),
+            RespMessage::Push(vec![
+                text("subscribe"),
+                text("payload"),
+                RespMessage::Integer(1)
+            ])
+        );
+
+        run("PUB000", &pub_tx, "PUBLISH", &["payload", "Overtime"]);
+        assert_eq!(
+            sub_rx.recv().unwrap(),
+            RespMessage::pubsub_message(
+                "payload",
+                RespMessage::SimpleString("Overtime".to_string())
+            )
+        );
+
+        // Suscripto, el cliente puede seguir ejecutando otros comandos
+        assert_eq!(run("SUB001", &sub_tx, "GET", &["Map"]), text("Kings Row"));
+
+        assert_eq!(
+            run("SUB001", &sub_tx, "UNSUBSCRIBE", &["payload"]),
+            RespMessage::Push(vec![
+                text("unsubscribe"),
+                text("payload"),
+                RespMessage::Integer(0)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_publish_carries_the_authenticated_user() {
+        let (mut executor, _) = create_test_executor();
+        let (pubsub_tx, pubsub_rx) = mpsc::channel();
+        std::thread::spawn(move || ChannelManager::new(pubsub_rx).run());
+        let (sub_tx, sub_rx) = mpsc::channel();
+        let (pub_tx, _pub_rx) = mpsc::channel();
+        executor
+            .clients
+            .write()
+            .unwrap()
+            .entry("PUB000".to_string())
+            .or_default()
+            .user = Some("Illari".to_string());
+        let mut run = |client_id: &str, res_tx: &Sender<RespMessage>, cmd: &str, args: &[&str]| {
+            let instruction = Instruction::new(
+                cmd.to_string(),
+                args.iter().map(|arg| arg.to_string()).collect(),
+            );
+            executor.execute_instruction(client_id.to_string(), instruction, &pubsub_tx, res_tx)
+        };
+
+        run("SUB001", &sub_tx, "HELLO", &["3"]);
+        run("SUB001", &sub_tx, "SUBSCRIBE", &["INDEX"]);
+        run("PUB000", &pub_tx, "PUBLISH", &["INDEX", "Roster"]);
+
+        let push = sub_rx.recv().unwrap();
+        assert_eq!(
+            push,
+            RespMessage::pubsub_message(
+                "INDEX",
+                RespMessage::with_publisher(
+                    RespMessage::SimpleString("Roster".to_string()),
+                    "Illari"
+                )
+            )
+        );
+        // En RESP2 el suscriptor recibe el mensaje sin el atributo
+        assert_eq!(
+            push.for_protocol(RespProtocol::Resp2),
+            RespMessage::SimpleString("Roster".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resp3_pattern_subscriber_receives_pmessage() {
+        let (mut executor, _) = create_test_executor();
+        let (pubsub_tx, pubsub_rx) = mpsc::channel();
+        std::thread::spawn(move || ChannelManager::new(pubsub_rx).run());
+        let (sub_tx, sub_rx) = mpsc::channel();
+        let (pub_tx, _pub_rx) = mpsc::channel();
+        let mut run = |client_id: &str, res_tx: &Sender<RespMessage>, cmd: &str, args: &[&str]| {
+            let instruction = Instruction::new(
+                cmd.to_string(),
+                args.iter().map(|arg| arg.to_string()).collect(),
+            );
+            executor.execute_instruction(client_id.to_string(), instruction, &pubsub_tx, res_tx)
+        };
+        let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+        let push = |kind: &str, name: &str, count: i64| {
+            RespMessage::Push(vec![text(kind), text(name), RespMessage::Integer(count)])
+        };
+
+        run("SUB001", &sub_tx, "HELLO", &["3"]);
+        run("SUB001", &sub_tx, "SUBSCRIBE", &["payload"]);
+        assert_eq!(
+            run("SUB001", &sub_tx, "PSUBSCRIBE", &["hero:*", "map:*"]),
+            push("psubscribe", "map:*", 3)
+        );
+        assert_eq!(sub_rx.recv().unwrap(), push("psubscribe", "hero:*", 2));
+
+        assert_eq!(
+            run(
+                "PUB000",
+                &pub_tx,
+                "PUBLISH",
+                &["hero:Kiriko", "Kitsune Rush"]
+            ),
+            RespMessage::Integer(1)
+        );
+        assert_eq!(
+            sub_rx.recv().unwrap(),
+            RespMessage::pubsub_pmessage(
+                "hero:*",
+                "hero:Kiriko",
+                RespMessage::SimpleString("Kitsune Rush".to_string())
+            )
+        );
+
+        assert_eq!(
+            run("SUB001", &sub_tx, "PUNSUBSCRIBE", &["hero:*"]),
+            push("punsubscribe", "hero:*", 2)
+        );
+        assert_eq!(
+            run("SUB001", &sub_tx, "PUNSUBSCRIBE", &[]),
+            push("punsubscribe", "map:*", 1)
+        );
+        let clients = executor.clients.read().unwrap();
+        assert!(clients.get("SUB001").unwrap().patterns.is_empty());
+    }
+
+    #[test]
+    fn test_keyspace_notifications_publish_writes_and_expirations() {
+        let mut settings = create_test_settings();
+        settings.set_notify_keyspace_events(KeyspaceEvents {
+            keyspace: true,
+            keyevent: true,
+        });
+        let (pubsub_tx, pubsub_rx) = mpsc::channel();
+        std::thread::spawn(move || ChannelManager::new(pubsub_rx).run());
+        let mut node_data = NodeData::new(settings.clone());
+        node_data.set_as_master();
+        let mut executor = CommandExecutor::new(
+            create_test_datastore(),
+            mpsc::channel().1,
+            settings.clone(),
+            create_test_logger(&settings),
+            pubsub_tx.clone(),
+            Arc::new(RwLock::new(HashMap::new())),
+            Arc::new(RwLock::new(node_data)),
+            Arc::new(RwLock::new(HashMap::new())),
+        );
+        let (sub_tx, sub_rx) = mpsc::channel();
+        let (pub_tx, _pub_rx) = mpsc::channel();
+        let mut run = |client_id: &str, res_tx: &Sender<RespMessage>, cmd: &str, args: &[&str]| {
+            let instruction = Instruction::new(
+                cmd.to_string(),
+                args.iter().map(|arg| arg.to_string()).collect(),
+            );
+            executor.execute_instruction(client_id.to_string(), instruction, &pubsub_tx, res_tx)
+        };
+        let event = |value: &str| RespMessage::SimpleString(value.to_string());
+
+        run("SUB001", &sub_tx, "HELLO", &["3"]);
+        run("SUB001", &sub_tx, "PSUBSCRIBE", &["__keyspace@0__:hero:*"]);
+        run("SUB001", &sub_tx, "SUBSCRIBE", &["__keyevent@0__:expired"]);
+
+        run("PUB000", &pub_tx, "SET", &["hero:Kiriko", "Kitsune Rush"]);
+        assert_eq!(
+            sub_rx.recv().unwrap(),
+            RespMessage::pubsub_pmessage(
+                "__keyspace@0__:hero:*",
+                "__keyspace@0__:hero:Kiriko",
+                event("set")
+            )
+        );
+
+        run("PUB000", &pub_tx, "SET", &["map:Hanamura", "Assault"]);
+        let deadline = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            + 1;
+        run(
+            "PUB000",
+            &pub_tx,
+            "PEXPIREAT",
+            &["hero:Kiriko", &deadline.to_string()],
+        );
+        assert_eq!(
+            sub_rx.recv().unwrap(),
+            RespMessage::pubsub_pmessage(
+                "__keyspace@0__:hero:*",
+                "__keyspace@0__:hero:Kiriko",
+                event("pexpireat")
+            )
+        );
+
+        std::thread::sleep(Duration::from_millis(5));
+        run("PUB000", &pub_tx, "GET", &["hero:Kiriko"]);
+        assert_eq!(
+            sub_rx.recv().unwrap(),
+            RespMessage::pubsub_pmessage(
+                "__keyspace@0__:hero:*",
+                "__keyspace@0__:hero:Kiriko",
+                event("expired")
+            )
+        );
+        assert_eq!(
+            sub_rx.recv().unwrap(),
+            RespMessage::pubsub_message("__keyevent@0__:expired", event("hero:Kiriko"))
+        );
+        assert!(sub_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_pubsub_introspection() {
+        let (mut executor, _) = create_test_executor();
+        let (pubsub_tx, pubsub_rx) = mpsc::channel();
+        std::thread::spawn(move || ChannelManager::new(pubsub_rx).run());
+        let (res_tx, _res_rx) = mpsc::channel();
+        let mut run = |client_id: &str, cmd: &str, args: &[&str]| {
+            let instruction = Instruction::new(
+                cmd.to_string(),
+                args.iter().map(|arg| arg.to_string()).collect(),
+            );
+            executor.execute_instruction(client_id.to_string(), instruction, &pubsub_tx, &res_tx)
+        };
+        let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+
+        run("SUB001", "SUBSCRIBE", &["doc:Numbani"]);
+        run("SUB002", "SUBSCRIBE", &["doc:Numbani"]);
+        run("SUB002", "SUBSCRIBE", &["chat"]);
+
+        assert_eq!(
+            run("SUB003", "PUBSUB", &["CHANNELS", "doc:*"]),
+            RespMessage::Array(vec![text("doc:Numbani")])
+        );
+        assert_eq!(
+            run("SUB003", "PUBSUB", &["NUMSUB", "doc:Numbani", "chat"]),
+            RespMessage::Array(vec![
+                text("doc:Numbani"),
+                RespMessage::Integer(2),
+                text("chat"),
+                RespMessage::Integer(1),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_command_count_matches_parseable_commands() {
+        let (mut executor, _) = create_test_executor();
+        let parseable = COMMAND_TABLE
+            .iter()
+            .filter(|spec| spec.example_command().is_ok())
+            .count();
+
+        let response = run_instruction(&mut executor, "AAA000", "COMMAND", &["COUNT"]);
+        assert_eq!(response, RespMessage::Integer(parseable as i64));
+        assert_eq!(parseable, COMMAND_TABLE.len());
+    }
+
+    #[test]
+    fn test_command_docs_reports_arity_and_flags() {
+        let (mut executor, _) = create_test_executor();
+        let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+
+        let args = ["DOCS", "set", "Overwatch", "GET"];
+        let response = run_instruction(&mut executor, "AAA000", "COMMAND", &args);
+        assert_eq!(
+            response,
+            RespMessage::Array(vec![
+                text("set"),
+                RespMessage::Array(vec![
+                    text("arity"),
+                    RespMessage::Integer(-3),
+                    text("flags"),
+                    RespMessage::Array(vec![text("write")]),
+                    text("group"),
+                    text("string"),
+                ]),
+                text("get"),
+                RespMessage::Array(vec![
+                    text("arity"),
+                    RespMessage::Integer(2),
+                    text("flags"),
+                    RespMessage::Array(vec![text("readonly")]),
+                    text("group"),
+                    text("string"),
+                ]),
+            ])
+        );
+
+        let RespMessage::Array(all) =
+            run_instruction(&mut executor, "AAA000", "COMMAND", &["DOCS"])
+        else {
+            panic!("COMMAND DOCS debe responder un array");
+        };
+        assert_eq!(all.len(), COMMAND_TABLE.len() * 2);
+    }
+
+    #[test]
+    fn test_ping_replies_pong_or_message() {
+        let (mut executor, _) = create_test_executor();
+
+        let response = run_instruction(&mut executor, "AAA000", "PING", &[]);
+        assert_eq!(response, RespMessage::SimpleString("PONG".to_string()));
+
+        let response = run_instruction(&mut executor, "AAA000", "PING", &["Heroes never die"]);
+        assert_eq!(
+            response,
+            RespMessage::BulkString(Some(b"Heroes never die".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_ping_in_subscribed_mode_replies_multibulk() {
+        let (mut executor, _) = create_test_executor();
+        let (pubsub_tx, pubsub_rx) = mpsc::channel();
+        std::thread::spawn(move || ChannelManager::new(pubsub_rx).run());
+        let (res_tx, _res_rx) = mpsc::channel();
+        let mut run = |cmd: &str, args: &[&str]| {
+            let instruction = Instruction::new(
+                cmd.to_string(),
+                args.iter().map(|arg| arg.to_string()).collect(),
+            );
+            executor.execute_instruction("SUB001".to_string(), instruction, &pubsub_tx, &res_tx)
+        };
+        let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+
+        run("SUBSCRIBE", &["payload"]);
+        assert_eq!(
+            run("PING", &[]),
+            RespMessage::Array(vec![text("pong"), text("")])
+        );
+        assert_eq!(
+            run("PING", &["Overtime"]),
+            RespMessage::Array(vec![text("pong"), text("Overtime")])
+        );
+
+        run("UNSUBSCRIBE", &["payload"]);
+        assert_eq!(
+            run("PING", &[]),
+            RespMessage::SimpleString("PONG".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resp2_subscribe_reply_is_unchanged() {
+        let (mut executor, _) = create_test_executor();
+        let (pubsub_tx, pubsub_rx) = mpsc::channel();
+        std::thread::spawn(move || ChannelManager::new(pubsub_rx).run());
+        let (res_tx, _res_rx) = mpsc::channel();
+        let instruction = Instruction::new("SUBSCRIBE".to_string(), vec!["payload".to_string()]);
+
+        let response =
+            executor.execute_instruction("SUB001".to_string(), instruction, &pubsub_tx, &res_tx);
+        assert_eq!(
+            response,
+            RespMessage::BulkString(Some(b"Successfully subscribed.".to_vec()))
+        );
+        let clients = executor.clients.read().unwrap();
+        let flags = clients.get("SUB001").unwrap();
+        assert_eq!(flags.class, ClientClass::Pubsub);
+        assert!(flags.channels.contains("payload"));
+    }
+
+    #[test]
+    fn test_format_reading_error() {
+        let error = CommandExecutor::format_reading_error(
+            "GET",
+            &["key1".to_string()],
+            &"lock error".to_string(),
+        );
+        assert!(error.contains("ERROR when trying to read on GET"));
+        assert!(error.contains("key1"));
+        assert!(error.contains("lock error"));
+    }
+
+    #[test]
+    fn test_format_op_error() {
+        let error = CommandExecutor::format_op_error(
+            "SET",
+            &["key1".to_string(), "value1".to_string()],
+            &"invalid type".to_string(),
+        );
+        assert!(error.contains("ERROR on SET"));
+        assert!(error.contains("key1"));
+        assert!(error.contains("value1"));
+        assert!(error.contains("invalid type"));
+    }
+
+    #[test]
+    fn test_get_key_for_command_string_commands() {
+        let cmd = Command::Get("test_key".to_string());
+        assert_eq!(
+            get_key_for_command(&cmd, MAX_HASH_SLOTS),
+            Ok(Some("test_key".to_string()))
+        );
+
+        let cmd = Command::Set(
+            "test_key".to_string(),
+            "test_value".to_string(),
+            SetOptions::default(),
+        );
+        assert_eq!(
+            get_key_for_command(&cmd, MAX_HASH_SLOTS),
+            Ok(Some("test_key".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_get_key_for_command_list_commands() {
+        let cmd = Command::Lpush("test_key".to_string(), vec!["value1".to_string()]);
+        assert_eq!(
+            get_key_for_command(&cmd, MAX_HASH_SLOTS),
+            Ok(Some("test_key".to_string()))
+        );
+
+        let cmd = Command::Lrange("test_key".to_string(), 0, 10);
+        assert_eq!(
+            get_key_for_command(&cmd, MAX_HASH_SLOTS),
+            Ok(Some("test_key".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_get_key_for_command_set_commands() {
+        let cmd = Command::Sadd("test_key".to_string(), vec!["value1".to_string()]);
+        assert_eq!(
+            get_key_for_command(&cmd, MAX_HASH_SLOTS),
+            Ok(Some("test_key".to_string()))
+        );
+
+        let cmd = Command::Smembers("test_key".to_string());
+        assert_eq!(
+            get_key_for_command(&cmd, MAX_HASH_SLOTS),
+            Ok(Some("test_key".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_get_key_for_command_no_key_commands() {
+        let cmd = Command::Echo("test".to_string());
+        assert_eq!(get_key_for_command(&cmd, MAX_HASH_SLOTS), Ok(None));
+
+        let cmd = Command::BgSave;
+        assert_eq!(get_key_for_command(&cmd, MAX_HASH_SLOTS), Ok(None));
+    }
+
+    #[test]
+    fn test_command_writes_on_db() {
+        assert!(
+            Command::Set(
+                "key".to_string(),
+                "value".to_string(),
+                SetOptions::default()
+            )
+            .writes_on_db()
+        );
+        assert!(Command::Del(vec!["key".to_string()]).writes_on_db());
+        assert!(Command::Lpush("key".to_string(), vec!["value".to_string()]).writes_on_db());
+        assert!(!Command::Get("key".to_string()).writes_on_db());
+        assert!(!Command::Echo("test".to_string()).writes_on_db());
+    }
+
+    /// Deja al nodo con los slots 0-8000 y el resto sin dueño conocido.
+    fn create_partial_coverage_executor(require_full_coverage: bool) -> CommandExecutor {
+        let (mut executor, _) = create_test_executor();
+        executor
+            .settings
+            .set_require_full_coverage(require_full_coverage);
+        executor.data_lock.write().unwrap().set_slots((0, 8000));
+        executor
+    }
+
+    #[test]
+    fn test_unassigned_slot_with_full_coverage_required() {
+        let mut executor = create_partial_coverage_executor(true);
+
+        // "Tracer" cae en el slot 10343, "Genji" en el 2607
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Tracer", "Oxton"]);
+        assert_eq!(response, RespMessage::Error(ERR_CLUSTER_DOWN.to_string()));
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Genji", "Shimada"]);
+        assert_eq!(response, RespMessage::Error(ERR_CLUSTER_DOWN.to_string()));
+    }
+
+    #[test]
+    fn test_unassigned_slot_without_full_coverage_required() {
+        let mut executor = create_partial_coverage_executor(false);
+
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Tracer", "Oxton"]);
+        assert_eq!(
+            response,
+            RespMessage::Error(ERR_SLOT_NOT_SERVED.to_string())
+        );
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Genji", "Shimada"]);
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+    }
+
+    #[test]
+    fn test_full_coverage_with_known_nodes() {
+        let mut executor = create_partial_coverage_executor(true);
+        let mut neighbor = KnownNode::new("node_b".to_string(), "127.0.0.1".to_string(), 17002);
+        neighbor.set_hash_slots((8001, 16383));
+        executor
+            .nodes_list
+            .write()
+            .unwrap()
+            .insert("node_b".to_string(), neighbor);
+
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Genji", "Shimada"]);
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Tracer", "Oxton"]);
+        assert_eq!(
+            response,
+            RespMessage::Error("MOVED 10343 127.0.0.1:7002".to_string())
+        );
+    }
+
+    #[test]
+    fn test_slot_zero_range_counts_for_coverage() {
+        let (executor, _) = create_test_executor();
+        let mut replica = KnownNode::new("node_b".to_string(), "127.0.0.1".to_string(), 17002);
+        replica.clear_slots();
+        executor
+            .nodes_list
+            .write()
+            .unwrap()
+            .insert("node_b".to_string(), replica);
+        assert!(!slots_fully_covered(
+            (1, 16383),
+            &executor.nodes_list,
+            16384
+        ));
+
+        // `(0, 0)` es el slot 0, no un rango vacío
+        let mut neighbor = KnownNode::new("node_c".to_string(), "127.0.0.1".to_string(), 17003);
+        neighbor.set_hash_slots((0, 0));
+        executor
+            .nodes_list
+            .write()
+            .unwrap()
+            .insert("node_c".to_string(), neighbor);
+        assert!(slots_fully_covered((1, 16383), &executor.nodes_list, 16384));
+        assert!(!slots_fully_covered(
+            EMPTY_SLOT_RANGE,
+            &executor.nodes_list,
+            16384
+        ));
+    }
+
+    #[test]
+    fn test_multi_key_commands_across_slots() {
+        let (mut executor, _) = create_test_executor();
+        let crossslot = RespMessage::Error(ERR_CROSSSLOT.to_string());
+
+        // "Tracer" y "Genji" caen en slots distintos
+        for (command, args) in [
+            ("MGET", vec!["Tracer", "Genji"]),
+            ("MSET", vec!["Tracer", "Oxton", "Genji", "Shimada"]),
+            ("EXISTS", vec!["Tracer", "Genji"]),
+            ("TOUCH", vec!["Tracer", "Genji"]),
+            ("SINTER", vec!["Tracer", "Genji"]),
+            ("SUNION", vec!["Tracer", "Genji"]),
+            ("SDIFF", vec!["Tracer", "Genji"]),
+            ("PFCOUNT", vec!["Tracer", "Genji"]),
+            ("PFMERGE", vec!["Tracer", "Genji"]),
+            ("XREAD", vec!["STREAMS", "Tracer", "Genji", "0", "0"]),
+            (
+                "XREADGROUP",
+                vec![
+                    "GROUP", "Talon", "Reaper", "STREAMS", "Tracer", "Genji", ">", ">",
+                ],
+            ),
+        ] {
+            let response = run_instruction(&mut executor, "AAA000", command, &args);
+            assert_eq!(response, crossslot, "{}", command);
+        }
+        let response = run_instruction(&mut executor, "AAA000", "RENAME", &["Tracer", "Genji"]);
+        assert_eq!(
+            response,
+            RespMessage::Error(
+                "CROSSSLOT Keys Tracer and Genji hash to different slots".to_string()
+            )
+        );
+
+        // Con el mismo hash tag las claves comparten slot
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "MSET",
+            &["{Talon}Reaper", "Reyes", "{Talon}Widowmaker", "Lacroix"],
+        );
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "EXISTS",
+            &["{Talon}Reaper", "{Talon}Widowmaker"],
+        );
+        assert_eq!(response, RespMessage::Integer(2));
+    }
+
+    /// Crea un ejecutor de un cluster de 256 slots que atiende los slots
+    /// 0-127, con un vecino que atiende los 128-255.
+    fn create_256_slot_executor() -> CommandExecutor {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        let config_content = "bind 0.0.0.0\nport 6379\nrole M\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\ncluster-slot-count 256\nhash-slots 0-127\n";
+        std::fs::write(config.path(), config_content).unwrap();
+        let settings = NodeConfigs::new(config.path().to_str().unwrap()).unwrap();
+        let (executor, _) = create_test_executor_with_settings(settings);
+
+        let mut neighbor = KnownNode::new("node_b".to_string(), "127.0.0.1".to_string(), 17002);
+        neighbor.set_hash_slots((128, 255));
+        executor
+            .nodes_list
+            .write()
+            .unwrap()
+            .insert("node_b".to_string(), neighbor);
+        executor
+    }
+
+    #[test]
+    fn test_moved_uses_configured_slot_count() {
+        let mut executor = create_256_slot_executor();
+        let heroes = [
+            "Tracer",
+            "Genji",
+            "Kiriko",
+            "Mercy",
+            "Reinhardt",
+            "Ana",
+            "Sojourn",
+        ];
+        let (hero, slot) = heroes
+            .iter()
+            .map(|hero| (*hero, hash_slot_with_count(hero, 256).unwrap()))
+            .find(|(_, slot)| *slot > 128)
+            .unwrap();
+
+        let response = run_instruction(&mut executor, "AAA000", "GET", &[hero]);
+        assert_eq!(
+            response,
+            RespMessage::Error(format!("MOVED {} 127.0.0.1:7002", slot))
+        );
+    }
+
+    #[test]
+    fn test_cluster_slots_within_configured_slot_count() {
+        let mut executor = create_256_slot_executor();
+        let RespMessage::Array(slots) =
+            run_instruction(&mut executor, "AAA000", "CLUSTER", &["SLOTS"])
+        else {
+            panic!("CLUSTER SLOTS debe responder una lista");
+        };
+        // Cada rango tiene un único nodo: inicio, fin, ip, puerto, id y rol
+        assert_eq!(slots.len(), 12);
+        for range in slots.chunks(6) {
+            for bound in &range[..2] {
+                let RespMessage::BulkString(Some(bound)) = bound else {
+                    panic!("Los límites del rango deben ser strings");
+                };
+                let bound: u16 = String::from_utf8_lossy(bound).parse().unwrap();
+                assert!(bound < 256);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cluster_slots_ranges_are_contiguous_and_sorted() {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            config.path(),
+            "bind 0.0.0.0\nport 6379\nrole M\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\nhash-slots 8192-16383\n",
+        )
+        .unwrap();
+        let settings = NodeConfigs::new(config.path().to_str().unwrap()).unwrap();
+        let (mut executor, _) = create_test_executor_with_settings(settings);
+        let mut master = KnownNode::new("node_b".to_string(), "127.0.0.1".to_string(), 17002);
+        master.set_hash_slots((0, 8191));
+        master.get_flags_mut().set(MASTER);
+        let mut replica = KnownNode::new("node_c".to_string(), "127.0.0.1".to_string(), 17005);
+        replica.demote_to_replica("node_b".to_string());
+        {
+            let mut nodes = executor.nodes_list.write().unwrap();
+            nodes.insert("node_b".to_string(), master);
+            nodes.insert("node_c".to_string(), replica);
+        }
+
+        let RespMessage::Array(slots) =
+            run_instruction(&mut executor, "AAA000", "CLUSTER", &["SLOTS"])
+        else {
+            panic!("CLUSTER SLOTS debe responder una lista");
+        };
+        let flat: Vec<String> = slots
+            .into_iter()
+            .map(|item| match item {
+                RespMessage::BulkString(Some(bytes)) => String::from_utf8(bytes).unwrap(),
+                other => panic!("Se esperaba un bulk string, se recibió {:?}", other),
+            })
+            .collect();
+
+        // 0-8191 con su master y su réplica, luego 8192-16383 con el nodo local
+        assert_eq!(
+            flat,
+            [
+                "0",
+                "8191",
+                "127.0.0.1",
+                "7002",
+                "node_b",
+                "MASTER",
+                "127.0.0.1",
+                "7005",
+                "node_c",
+                "SLAVE",
+                "8192",
+                "16383",
+                "0.0.0.0",
+                "6379",
+                "e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca",
+                "MASTER",
+            ]
+        );
+        let ranges = crate::command::utils::parse_flat_cluster_slots(&flat);
+        let mut bounds: Vec<_> = ranges.keys().copied().collect();
+        bounds.sort();
+        assert_eq!(bounds.first().unwrap().0, 0);
+        assert_eq!(bounds.last().unwrap().1, 16383);
+        for pair in bounds.windows(2) {
+            assert_eq!(pair[0].1 + 1, pair[1].0);
+        }
+    }
+
+    #[test]
+    fn test_info_cluster_reports_slot_count() {
+        let mut executor = create_256_slot_executor();
+        match run_instruction(&mut executor, "AAA000", "INFO", &["cluster"]) {
+            RespMessage::BulkString(Some(info)) => {
+                let info = String::from_utf8(info).unwrap();
+                assert!(info.starts_with("# Cluster\r\n"));
+                assert!(info.contains("cluster_slot_count:256\r\n"));
+                assert!(!info.contains("# Persistence"));
+            }
+            other => panic!("INFO debería responder un bulk string, recibió {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_slot_count_is_validated_at_startup() {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            config.path(),
+            "bind 0.0.0.0\nport 6379\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\ncluster-slot-count 300\n",
+        )
+        .unwrap();
+        assert!(NodeConfigs::new(config.path().to_str().unwrap()).is_err());
+
+        std::fs::write(
+            config.path(),
+            "bind 0.0.0.0\nport 6379\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\ncluster-slot-count 256\nhash-slots 0-16383\n",
+        )
+        .unwrap();
+        assert!(NodeConfigs::new(config.path().to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_malformed_node_id_is_rejected_at_startup() {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        for node_id in [
+            "node_1",
+            "E7D1EECCE10FD6BB5EB35B9F99A514335D9BA9CA",
+            "e7d1eecce10fd6bb5eb35b9f99a514335d9ba9c",
+            "g7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca",
+        ] {
+            std::fs::write(
+                config.path(),
+                format!("bind 0.0.0.0\nport 6379\nnode-id {}\n", node_id),
+            )
+            .unwrap();
+            let error = NodeConfigs::new(config.path().to_str().unwrap()).unwrap_err();
+            assert!(error.to_string().contains("node-id"));
+        }
+    }
+
+    #[test]
+    fn test_absent_node_id_is_generated_and_persisted() {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(config.path(), "bind 0.0.0.0\nport 6379\n").unwrap();
+        let path = config.path().to_str().unwrap();
+
+        let generated = NodeConfigs::new(path).unwrap().get_id();
+        assert_eq!(generated.len(), NODE_ID_LEN);
+        assert!(is_valid_node_id(&generated));
+        assert_eq!(NodeConfigs::new(path).unwrap().get_id(), generated);
+    }
+
+    /// Executor cuyo slot de `key` está migrando hacia `node_b`.
+    fn create_migrating_executor(key: &str) -> (CommandExecutor, u16) {
+        let (executor, _) = create_test_executor();
+        let slot = hash_slot_with_count(key, 16384).unwrap();
+        executor
+            .data_lock
+            .write()
+            .unwrap()
+            .set_migrating(slot, "node_b".to_string());
+        executor.nodes_list.write().unwrap().insert(
+            "node_b".to_string(),
+            KnownNode::new("node_b".to_string(), "127.0.0.1".to_string(), 17002),
+        );
+        (executor, slot)
+    }
+
+    #[test]
+    fn test_owned_slot_without_migration_is_served_locally() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Kiriko"]);
+
+        let response = run_instruction(&mut executor, "AAA000", "GET", &["Hero"]);
+
+        assert_eq!(response, RespMessage::BulkString(Some(b"Kiriko".to_vec())));
+    }
+
+    #[test]
+    fn test_migrating_slot_serves_keys_still_present() {
+        let (mut executor, _) = create_migrating_executor("Hero");
+        executor
+            .ds_guard
+            .write()
+            .unwrap()
+            .set("Hero".to_string(), "Kiriko".to_string());
+
+        let response = run_instruction(&mut executor, "AAA000", "GET", &["Hero"]);
+
+        assert_eq!(response, RespMessage::BulkString(Some(b"Kiriko".to_vec())));
+    }
+
+    #[test]
+    fn test_migrating_slot_asks_for_absent_keys() {
+        let (mut executor, slot) = create_migrating_executor("Hero");
+
+        let response = run_instruction(&mut executor, "AAA000", "GET", &["Hero"]);
+
+        assert_eq!(
+            response,
+            RespMessage::Error(format!("ASK {} 127.0.0.1:7002", slot))
+        );
+    }
+
+    #[test]
+    fn test_cluster_myid_returns_configured_id() {
+        let (mut executor, _) = create_test_executor();
+        let response = run_instruction(&mut executor, "AAA000", "CLUSTER", &["MYID"]);
+        assert_eq!(
+            response,
+            RespMessage::BulkString(Some(b"e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_cluster_nodes_lists_myself_and_known_nodes() {
+        let (mut executor, _) = create_test_executor();
+        let mut master = KnownNode::new(
+            "3c3a0c74aae0b56170ccb03a76b60cfe7dc1912e".to_string(),
+            "127.0.0.1".to_string(),
+            17002,
+        );
+        master.set_hash_slots((5461, 10922));
+        master.get_flags_mut().set(MASTER);
+        master.set_connected();
+        let mut replica = KnownNode::new(
+            "6ec23923021cf3ffec47632106199cb7f496ce01".to_string(),
+            "127.0.0.1".to_string(),
+            17005,
+        );
+        replica.demote_to_replica("3c3a0c74aae0b56170ccb03a76b60cfe7dc1912e".to_string());
+        {
+            let mut nodes = executor.nodes_list.write().unwrap();
+            nodes.insert(master.get_id(), master);
+            nodes.insert(replica.get_id(), replica);
+        }
+
+        let RespMessage::BulkString(Some(output)) =
+            run_instruction(&mut executor, "AAA000", "CLUSTER", &["NODES"])
+        else {
+            panic!("CLUSTER NODES debe responder un bulk string");
+        };
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with(
+            "e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 0.0.0.0:6379@16379 myself,master - "
+        ));
+        assert!(lines[0].ends_with(" connected 0-16383"));
+        assert!(lines[1].starts_with(
+            "3c3a0c74aae0b56170ccb03a76b60cfe7dc1912e 127.0.0.1:7002@17002 master - "
+        ));
+        assert!(lines[1].ends_with(" connected 5461-10922"));
+        assert!(lines[2].starts_with(
+            "6ec23923021cf3ffec47632106199cb7f496ce01 127.0.0.1:7005@17005 slave 3c3a0c74aae0b56170ccb03a76b60cfe7dc1912e "
+        ));
+        assert!(lines[2].ends_with(" disconnected"));
+    }
+
+    #[test]
+    fn test_debug_populate_keeps_existing_keys() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["hero:1", "Kiriko"]);
+
+        let response = run_instruction(
+            &mut executor,
+            "AAA000",
+            "DEBUG",
+            &["POPULATE", "3", "hero", "16"],
+        );
+
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+        let store = executor.ds_guard.read().unwrap();
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.get("hero:0").unwrap(), "value:0000000000");
+        assert_eq!(store.get("hero:1").unwrap(), "Kiriko");
+        assert_eq!(store.get("hero:2").unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_debug_reload_keeps_dataset() {
+        let config_content = "bind 0.0.0.0\nport 6379\nrole M\ndbfilename debug_reload_test.rdb\ndir ./\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\nhash-slots 0-16383";
+        std::fs::write("debug_reload_test.conf", config_content).unwrap();
+        let settings = NodeConfigs::new("debug_reload_test.conf").unwrap();
+        std::fs::remove_file("debug_reload_test.conf").ok();
+        let (mut executor, _) = create_test_executor_with_settings(settings);
+
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Soldier: 76"]);
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "RPUSH",
+            &["Support", "Ana", "Lúcio"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "SADD",
+            &["Maps", "Dorado", "Ilios"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "XADD",
+            &["Feed", "*", "kill", "Genji"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "XGROUP",
+            &["CREATE", "Feed", "casters", "0"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "XREADGROUP",
+            &["GROUP", "casters", "Mr.X", "STREAMS", "Feed", ">"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "HSET",
+            &["Ultimates", "Ana", "Nano Boost", "Lúcio", "Sound Barrier"],
+        );
+        run_instruction(&mut executor, "AAA000", "EXPIRE", &["Support", "100"]);
+        let before = executor.ds_guard.read().unwrap().clone();
+
+        let response = run_instruction(&mut executor, "AAA000", "DEBUG", &["RELOAD"]);
+        std::fs::remove_file("./debug_reload_test.rdb").ok();
+
+        assert_eq!(response, RespMessage::SimpleString("OK".to_string()));
+        let after = executor.ds_guard.read().unwrap();
+        assert_eq!(after.string_db, before.string_db);
+        assert_eq!(after.list_db, before.list_db);
+        assert_eq!(after.set_db, before.set_db);
+        assert_eq!(after.stream_db, before.stream_db);
+        assert_eq!(after.hash_db, before.hash_db);
+        assert_eq!(after.hash_db["Ultimates"].len(), 2);
+        assert_eq!(after.stream_db["Feed"].groups()["casters"].pending.len(), 1);
+        // El TTL viaja por el disco: el store vivo queda con el recargado
+        let ttl = after.remaining_ttl("Support").unwrap();
+        assert!(ttl > Duration::from_secs(98) && ttl <= Duration::from_secs(100));
+        assert!(after.remaining_ttl("Hero").is_none());
+        drop(after);
+        assert_eq!(
+            run_instruction(&mut executor, "AAA000", "TTL", &["Support"]),
+            RespMessage::Integer(100)
+        );
+    }
+
+    /// Crea un ejecutor que escribe el AOF `rewrite.aof` en `dir`.
+    fn create_aof_executor(dir: &std::path::Path) -> CommandExecutor {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        let config_content = format!(
+            "bind 0.0.0.0\nport 6379\nrole M\ndir {}/\nappendfilename rewrite.aof\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\nhash-slots 0-16383\n",
+            dir.display()
+        );
+        std::fs::write(config.path(), config_content).unwrap();
+        let settings = NodeConfigs::new(config.path().to_str().unwrap()).unwrap();
+        create_test_executor_with_settings(settings).0
+    }
+
+    /// Obtiene la sección `persistence` de INFO.
+    fn info_persistence(executor: &mut CommandExecutor) -> String {
+        match run_instruction(executor, "AAA000", "INFO", &["persistence"]) {
+            RespMessage::BulkString(Some(info)) => String::from_utf8(info).unwrap(),
+            other => panic!("INFO debería responder un bulk string, recibió {:?}", other),
+        }
+    }
+
+    /// Espera a que termine la reescritura del AOF en curso.
+    ///
+    /// # Returns
+    ///
+    /// La sección `persistence` de INFO una vez terminada
+    fn wait_for_aof_rewrite(executor: &mut CommandExecutor) -> String {
+        for _ in 0..200 {
+            let info = info_persistence(executor);
+            if info.contains("aof_rewrite_in_progress:0") {
+                return info;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("La reescritura del AOF no terminó");
+    }
+
+    #[test]
+    fn test_bgrewriteaof_replays_to_same_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = create_aof_executor(dir.path());
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Sojourn"]);
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "RPUSH",
+            &["Support", "Mercy", "Baptiste"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "SADD",
+            &["Maps", "Circuit Royal", "Oasis"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "XADD",
+            &["Feed", "*", "kill", "Sigma"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "XGROUP",
+            &["CREATE", "Feed", "casters", "0"],
+        );
+
+        let response = run_instruction(&mut executor, "AAA000", "BGREWRITEAOF", &[]);
+        assert_eq!(
+            response,
+            RespMessage::SimpleString("Background append only file rewriting started".to_string())
+        );
+        let info = wait_for_aof_rewrite(&mut executor);
+        assert!(info.contains("aof_last_bgrewrite_status:ok"));
+
+        let file = std::fs::File::open(dir.path().join("rewrite.aof")).unwrap();
+        let replayed =
+            crate::command::aof_rewrite::replay(&mut std::io::BufReader::new(file)).unwrap();
+        assert!(replayed.has_same_data(&executor.ds_guard.read().unwrap()));
+    }
+
+    /// Crea un ejecutor con `appendonly yes` que escribe `appendonly.aof`
+    /// en `dir`.
+    fn create_append_only_executor(dir: &std::path::Path) -> CommandExecutor {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        let config_content = format!(
+            "bind 0.0.0.0\nport 6379\nrole M\ndir {}/\nlogfile {}\nappendonly yes\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\nhash-slots 0-16383\n",
+            dir.display(),
+            dir.join("node.log").display()
+        );
+        std::fs::write(config.path(), config_content).unwrap();
+        let settings = NodeConfigs::new(config.path().to_str().unwrap()).unwrap();
+        create_test_executor_with_settings(settings).0
+    }
+
+    /// Espera a que el AOF de `dir` reproduzca el store del ejecutor. El
+    /// logger agrega las entradas desde su propio hilo.
+    fn wait_for_aof_to_match(executor: &CommandExecutor, dir: &std::path::Path) {
+        for _ in 0..200 {
+            if let Ok(file) = std::fs::File::open(dir.join("appendonly.aof")) {
+                let replayed =
+                    crate::command::aof_rewrite::replay(&mut std::io::BufReader::new(file));
+                if replayed
+                    .is_ok_and(|store| store.has_same_data(&executor.ds_guard.read().unwrap()))
+                {
+                    return;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("El AOF no reproduce el store");
+    }
+
+    #[test]
+    fn test_append_only_logs_every_applied_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = create_append_only_executor(dir.path());
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Lifeweaver"]);
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "RPUSH",
+            &["Tanks", "Ramattra", "Mauga"],
+        );
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "SADD",
+            &["Maps", "Samoa", "Runasapi"],
+        );
+        run_instruction(&mut executor, "AAA000", "SPOP", &["Maps"]);
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "XADD",
+            &["Feed", "*", "kill", "Ana"],
+        );
+        run_instruction(&mut executor, "AAA000", "EXPIRE", &["Hero", "600"]);
+        // Los comandos fallidos no se agregan
+        run_instruction(&mut executor, "AAA000", "INCR", &["Tanks"]);
+
+        wait_for_aof_to_match(&executor, dir.path());
+    }
+
+    #[test]
+    fn test_append_only_keeps_writes_made_during_rewrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = create_append_only_executor(dir.path());
+        for hero in ["Tracer", "Sombra", "Echo"] {
+            run_instruction(&mut executor, "AAA000", "SET", &["Hero", hero]);
+        }
+
+        run_instruction(&mut executor, "AAA000", "BGREWRITEAOF", &[]);
+        run_instruction(&mut executor, "AAA000", "RPUSH", &["Support", "Juno"]);
+        wait_for_aof_rewrite(&mut executor);
+        run_instruction(&mut executor, "AAA000", "SADD", &["Maps", "Hanaoka"]);
+
+        wait_for_aof_to_match(&executor, dir.path());
+    }
+
+    #[test]
+    fn test_bgrewriteaof_failure_updates_last_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = create_aof_executor(&dir.path().join("missing"));
+        assert!(info_persistence(&mut executor).contains("aof_last_bgrewrite_status:ok"));
+
+        run_instruction(&mut executor, "AAA000", "BGREWRITEAOF", &[]);
+        let info = wait_for_aof_rewrite(&mut executor);
+
+        assert!(info.contains("aof_last_bgrewrite_status:err"));
+    }
+
+    #[test]
+    fn test_bgrewriteaof_rejected_while_in_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = create_aof_executor(dir.path());
+        executor.aof_rewrite.lock().unwrap().in_progress = true;
+
+        let response = run_instruction(&mut executor, "AAA000", "BGREWRITEAOF", &[]);
+        assert_eq!(
+            response,
+            RespMessage::Error(ERR_AOF_REWRITE_IN_PROGRESS.to_string())
+        );
+        assert!(info_persistence(&mut executor).contains("aof_rewrite_in_progress:1"));
+        assert!(!dir.path().join("rewrite.aof").exists());
+
+        executor.aof_rewrite.lock().unwrap().in_progress = false;
+        let response = run_instruction(&mut executor, "AAA000", "BGREWRITEAOF", &[]);
+        assert!(!response.is_error());
+        wait_for_aof_rewrite(&mut executor);
+        assert!(dir.path().join("rewrite.aof").exists());
+    }
+
+    /// Envía una instrucción por `handle_instruction`, como lo hace `run`.
+    fn handle(
+        executor: &mut CommandExecutor,
+        client_id: &str,
+        cmd_type: &str,
+        args: &[&str],
+    ) -> mpsc::Receiver<RespMessage> {
+        let (res_tx, res_rx) = mpsc::channel();
+        let instruction = Instruction::new(
+            cmd_type.to_string(),
+            args.iter().map(|arg| arg.to_string()).collect(),
+        );
+        executor.handle_instruction(client_id.to_string(), instruction, &res_tx);
+        res_rx
+    }
+
+    /// Agrega `client_id` al registro de conexiones, como `ConnectionHandler`.
+    fn register_client(executor: &CommandExecutor, client_id: &str) {
+        executor
+            .clients
+            .write()
+            .unwrap()
+            .insert(client_id.to_string(), ClientFlags::default());
+    }
+
+    #[test]
+    fn test_blocked_xread_wakes_on_xadd() {
+        let (mut executor, _) = create_test_executor();
+        register_client(&executor, "BBB111");
+        handle(
+            &mut executor,
+            "AAA000",
+            "XADD",
+            &["Feed", "1-0", "kill", "Genji"],
+        );
+
+        let blocked = handle(
+            &mut executor,
+            "BBB111",
+            "XREAD",
+            &["BLOCK", "0", "STREAMS", "Feed", "1-0"],
+        );
+        assert!(blocked.try_recv().is_err());
+
+        // Otros clientes se siguen atendiendo mientras tanto
+        let other = handle(&mut executor, "CCC222", "XLEN", &["Feed"]);
+        assert_eq!(other.try_recv().unwrap(), RespMessage::Integer(1));
+        assert!(blocked.try_recv().is_err());
+
+        let added = handle(
+            &mut executor,
+            "AAA000",
+            "XADD",
+            &["Feed", "2-0", "kill", "Hanzo"],
+        );
+        assert_eq!(
+            added.try_recv().unwrap(),
+            RespMessage::from_response(ResponseType::Str("2-0".to_string()))
+        );
+
+        let expected: Vec<String> = vec!["Feed", "1", "2-0", "1", "kill", "Hanzo"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            blocked.try_recv().unwrap(),
+            RespMessage::from_response(ResponseType::List(expected))
+        );
+        assert!(executor.blocked.is_empty());
+    }
+
+    #[test]
+    fn test_blocked_xread_times_out_with_null() {
+        let (mut executor, _) = create_test_executor();
+        register_client(&executor, "BBB111");
+        let blocked = handle(
+            &mut executor,
+            "BBB111",
+            "XREAD",
+            &["BLOCK", "20", "STREAMS", "Feed", "0"],
+        );
+        assert!(blocked.try_recv().is_err());
+        assert!(executor.next_timeout().is_some());
+
+        std::thread::sleep(Duration::from_millis(30));
+        executor.serve_blocked_clients();
+
+        assert_eq!(blocked.try_recv().unwrap(), RespMessage::Null(None));
+        assert!(executor.next_timeout().is_none());
+    }
+
+    #[test]
+    fn test_blocked_xread_is_discarded_when_client_disconnects() {
+        let (mut executor, _) = create_test_executor();
+        register_client(&executor, "BBB111");
+        register_client(&executor, "CCC222");
+        let disconnected = handle(
+            &mut executor,
+            "BBB111",
+            "XREAD",
+            &["BLOCK", "0", "STREAMS", "Feed", "0"],
+        );
+        let dropped = handle(
+            &mut executor,
+            "CCC222",
+            "XREAD",
+            &["BLOCK", "0", "STREAMS", "Feed", "0"],
+        );
+        assert_eq!(executor.blocked.len(), 2);
+
+        // BBB111 cierra la conexión y CCC222 ya no escucha su respuesta
+        executor.clients.write().unwrap().remove("BBB111");
+        drop(dropped);
+        executor.serve_blocked_clients();
+        assert_eq!(executor.blocked.len(), 1);
+
+        handle(
+            &mut executor,
+            "AAA000",
+            "XADD",
+            &["Feed", "1-0", "kill", "Ana"],
+        );
+        assert!(executor.blocked.is_empty());
+        assert!(disconnected.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_xread_without_block_is_answered_immediately() {
+        let (mut executor, _) = create_test_executor();
+        let response = handle(&mut executor, "BBB111", "XREAD", &["STREAMS", "Feed", "0"]);
+        assert_eq!(response.try_recv().unwrap(), RespMessage::Null(None));
+        assert!(executor.blocked.is_empty());
+    }
+
+    #[test]
+    fn test_client_pause_write_delays_set_but_not_get() {
+        let (mut executor, _) = create_test_executor();
+        handle(&mut executor, "AAA000", "SET", &["Hero", "Tracer"]);
+        let paused = handle(&mut executor, "AAA000", "CLIENT", &["PAUSE", "50", "WRITE"]);
+        assert_eq!(
+            paused.try_recv().unwrap(),
+            RespMessage::SimpleString("OK".to_string())
+        );
+
+        let set = handle(&mut executor, "BBB111", "SET", &["Hero", "Genji"]);
+        assert!(set.try_recv().is_err());
+
+        let get = handle(&mut executor, "CCC222", "GET", &["Hero"]);
+        assert_eq!(
+            get.try_recv().unwrap(),
+            RespMessage::BulkString(Some(b"Tracer".to_vec()))
+        );
+
+        std::thread::sleep(Duration::from_millis(60));
+        executor.release_paused();
+
+        assert_eq!(
+            set.try_recv().unwrap(),
+            RespMessage::SimpleString("OK".to_string())
+        );
+        let get = handle(&mut executor, "CCC222", "GET", &["Hero"]);
+        assert_eq!(
+            get.try_recv().unwrap(),
+            RespMessage::BulkString(Some(b"Genji".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_client_unpause_releases_queued_writes_in_order() {
+        let (mut executor, _) = create_test_executor();
+        handle(
+            &mut executor,
+            "AAA000",
+            "CLIENT",
+            &["PAUSE", "60000", "WRITE"],
+        );
+
+        let first = handle(&mut executor, "BBB111", "RPUSH", &["Queue", "Ana"]);
+        let second = handle(&mut executor, "CCC222", "RPUSH", &["Queue", "Kiriko"]);
+        // La lectura de un cliente con escrituras postergadas respeta su orden
+        let read = handle(&mut executor, "BBB111", "LLEN", &["Queue"]);
+        assert!(first.try_recv().is_err());
+        assert!(second.try_recv().is_err());
+        assert!(read.try_recv().is_err());
+
+        let unpause = handle(&mut executor, "AAA000", "CLIENT", &["UNPAUSE"]);
+
+        assert_eq!(
+            unpause.try_recv().unwrap(),
+            RespMessage::SimpleString("OK".to_string())
+        );
+        assert_eq!(first.try_recv().unwrap(), RespMessage::Integer(1));
+        assert_eq!(second.try_recv().unwrap(), RespMessage::Integer(2));
+        assert_eq!(read.try_recv().unwrap(), RespMessage::Integer(2));
+        assert!(executor.paused.is_empty());
+    }
+
+    #[test]
+    fn test_client_pause_write_delays_exec_with_queued_writes() {
+        let (mut executor, _) = create_test_executor();
+        let queued = RespMessage::SimpleString("QUEUED".to_string());
+        handle(&mut executor, "BBB111", "MULTI", &[]);
+        let set = handle(&mut executor, "BBB111", "SET", &["Hero", "Lucio"]);
+        assert_eq!(set.try_recv().unwrap(), queued);
+        handle(&mut executor, "CCC222", "MULTI", &[]);
+        handle(&mut executor, "CCC222", "GET", &["Hero"]);
+        handle(
+            &mut executor,
+            "AAA000",
+            "CLIENT",
+            &["PAUSE", "60000", "WRITE"],
+        );
+
+        // Una transacción de sólo lecturas no espera
+        let read_only = handle(&mut executor, "CCC222", "EXEC", &[]);
+        assert_eq!(
+            read_only.try_recv().unwrap(),
+            RespMessage::Array(vec![RespMessage::Null(None)])
+        );
+        let exec = handle(&mut executor, "BBB111", "EXEC", &[]);
+        assert!(exec.try_recv().is_err());
+        assert_eq!(
+            run_instruction(&mut executor, "CCC222", "GET", &["Hero"]),
+            RespMessage::Null(None)
+        );
+
+        handle(&mut executor, "AAA000", "CLIENT", &["UNPAUSE"]);
+        assert_eq!(
+            exec.try_recv().unwrap(),
+            RespMessage::Array(vec![RespMessage::SimpleString("OK".to_string())])
+        );
+        assert_eq!(
+            run_instruction(&mut executor, "CCC222", "GET", &["Hero"]),
+            RespMessage::BulkString(Some(b"Lucio".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_client_pause_all_delays_reads() {
+        let (mut executor, _) = create_test_executor();
+        handle(&mut executor, "AAA000", "CLIENT", &["PAUSE", "60000"]);
+
+        let get = handle(&mut executor, "BBB111", "GET", &["Hero"]);
+        assert!(get.try_recv().is_err());
+
+        handle(&mut executor, "AAA000", "CLIENT", &["UNPAUSE"]);
+        assert_eq!(get.try_recv().unwrap(), RespMessage::Null(None));
+    }
+
+    #[test]
+    fn test_slow_command_creates_latency_event() {
+        let mut settings = create_test_settings();
+        settings.set_latency_monitor_threshold(20);
+        let (mut executor, _) = create_test_executor_with_settings(settings);
+
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Mauga"]);
+        let latest = run_instruction(&mut executor, "AAA000", "LATENCY", &["LATEST"]);
+        assert_eq!(latest, RespMessage::Array(vec![]));
+
+        run_instruction(&mut executor, "AAA000", "DEBUG", &["SLEEP", "0.03"]);
+        let RespMessage::Array(latest) =
+            run_instruction(&mut executor, "AAA000", "LATENCY", &["LATEST"])
+        else {
+            panic!("LATENCY LATEST debe responder una lista");
+        };
+        assert_eq!(latest.len(), 4);
+        assert_eq!(
+            latest[0],
+            RespMessage::BulkString(Some(b"command".to_vec()))
+        );
+        assert_eq!(executor.latency.history(COMMAND_EVENT).len(), 1);
+        assert!(executor.latency.history(COMMAND_EVENT)[0].latency_ms >= 20);
+    }
+
+    #[test]
+    fn test_latency_reset_clears_history() {
+        let mut settings = create_test_settings();
+        settings.set_latency_monitor_threshold(1);
+        let (mut executor, _) = create_test_executor_with_settings(settings);
+        run_instruction(&mut executor, "AAA000", "DEBUG", &["SLEEP", "0.005"]);
+
+        let reset = run_instruction(&mut executor, "AAA000", "LATENCY", &["RESET"]);
+        assert_eq!(reset, RespMessage::Integer(1));
+        let history = run_instruction(&mut executor, "AAA000", "LATENCY", &["HISTORY", "command"]);
+        assert_eq!(history, RespMessage::Array(vec![]));
+    }
+
+    #[test]
+    fn test_wrong_type_reply_is_canonical() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Tracer"]);
+
+        let response = run_instruction(&mut executor, "AAA000", "LLEN", &["Hero"]);
+        assert_eq!(
+            response,
+            RespMessage::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+        let response = run_instruction(&mut executor, "AAA000", "LPUSH", &["Hero", "Genji"]);
+        assert_eq!(
+            response,
+            RespMessage::Error(CommandError::WrongType.to_string())
+        );
+    }
+
+    #[test]
+    fn test_wrong_type_is_logged_with_command_and_key() {
+        let log_dir = tempfile::tempdir().unwrap();
+        let log_file = log_dir.path().join("wrongtype.log");
+        let config = tempfile::NamedTempFile::new().unwrap();
+        let config_content = format!(
+            "bind 0.0.0.0\nport 6379\nrole M\nlogfile {}\nloglevel debug\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\nhash-slots 0-16383\n",
+            log_file.display()
+        );
+        std::fs::write(config.path(), config_content).unwrap();
+        let settings = NodeConfigs::new(config.path().to_str().unwrap()).unwrap();
+        let (mut executor, _) = create_test_executor_with_settings(settings);
+
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Tracer"]);
+        run_instruction(&mut executor, "AAA000", "LLEN", &["Hero"]);
+        drop(executor);
+
+        // El logger escribe desde su propio hilo
+        let mut log = String::new();
+        for _ in 0..100 {
+            log = std::fs::read_to_string(&log_file).unwrap_or_default();
+            if log.contains("WRONGTYPE") {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let line = log
+            .lines()
+            .find(|line| line.contains("WRONGTYPE"))
+            .expect("El WRONGTYPE debe quedar en el log");
+        assert!(line.contains("LLEN"));
+        assert!(line.contains("Hero"));
+    }
+
+    /// Ejecutor con `debug-invariants yes` que registra en `log_file`.
+    fn create_invariants_executor(log_file: &std::path::Path) -> CommandExecutor {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        let config_content = format!(
+            "bind 0.0.0.0\nport 6379\nrole M\nlogfile {}\nloglevel debug\ndebug-invariants yes\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\nhash-slots 0-16383\n",
+            log_file.display()
+        );
+        std::fs::write(config.path(), config_content).unwrap();
+        let settings = NodeConfigs::new(config.path().to_str().unwrap()).unwrap();
+        assert!(settings.get_debug_invariants());
+        create_test_executor_with_settings(settings).0
+    }
+
+    #[cfg(not(feature = "strict-invariants"))]
+    #[test]
+    fn test_invariant_violation_is_logged_with_command() {
+        let log_dir = tempfile::tempdir().unwrap();
+        let log_file = log_dir.path().join("invariants.log");
+        let mut executor = create_invariants_executor(&log_file);
+
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Kiriko"]);
+        executor
+            .ds_guard
+            .write()
+            .unwrap()
+            .list_db
+            .insert("Hero".to_string(), vec!["Lifeweaver".to_string()]);
+        run_instruction(&mut executor, "AAA000", "STRLEN", &["Hero"]);
+        drop(executor);
+
+        // El logger escribe desde su propio hilo
+        let mut log = String::new();
+        for _ in 0..100 {
+            log = std::fs::read_to_string(&log_file).unwrap_or_default();
+            if log.contains("Invariant violated") {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
         }
+        let line = log
+            .lines()
+            .find(|line| line.contains("Invariant violated"))
+            .expect("La violación debe quedar en el log");
+        assert!(line.contains("STRLEN"));
+        assert!(line.contains("key-in-single-container"));
+        assert!(line.contains("'Hero'"));
     }
 
-    /// Determina si el comando realiza operaciones de escritura en la base de datos.
-    ///
-    /// # Retorna
-    ///
-    /// `true` si el comando es de escritura, `false` en caso contrario
-    pub fn writes_on_db(&self) -> bool {
-        matches!(
-            self,
-            Command::Append(_, _)
-                | Command::Del(_)
-                | Command::Set(_, _)
-                | Command::Getdel(_)
-                | Command::Lpop(_, _)
-                | Command::Rpop(_, _)
-                | Command::Lpush(_, _)
-                | Command::Rpush(_, _)
-                | Command::Sadd(_, _)
-                | Command::SMove(_, _, _)
-                | Command::Spop(_, _)
-        )
+    #[test]
+    fn test_delete_clears_ttl_of_deleted_keys() {
+        let log_dir = tempfile::tempdir().unwrap();
+        let mut executor = create_invariants_executor(&log_dir.path().join("invariants.log"));
+
+        for hero in ["Hero", "Support"] {
+            run_instruction(&mut executor, "AAA000", "SET", &[hero, "Illari"]);
+            executor
+                .ds_guard
+                .write()
+                .unwrap()
+                .set_ttl(hero, Some(Duration::from_secs(60)));
+        }
+        run_instruction(&mut executor, "AAA000", "DEL", &["Hero"]);
+        run_instruction(&mut executor, "AAA000", "GETDEL", &["Support"]);
+
+        let store = executor.ds_guard.read().unwrap();
+        assert!(store.expires.is_empty());
+        assert!(crate::command::invariants::check_store(&store).is_empty());
     }
-}
 
-/// Desenvuelve un Option<Arc<T>> o falla con un mensaje de error descriptivo.
-///
-/// # Argumentos
-///
-/// * `opt` - Option a desenvolver
-/// * `name` - Nombre del recurso para el mensaje de error
-///
-/// # Retorna
-///
-/// `Result<Arc<T>, String>`
-fn unwrap_or_fail_arc<T>(opt: Option<Arc<T>>, name: &str) -> Result<Arc<T>, String> {
-    opt.ok_or_else(|| format!("Missing required dependency: {}", name))
-}
+    #[cfg(feature = "strict-invariants")]
+    #[test]
+    #[should_panic(expected = "Invariant violated after STRLEN")]
+    fn test_strict_invariants_panic_on_violation() {
+        let log_dir = tempfile::tempdir().unwrap();
+        let mut executor = create_invariants_executor(&log_dir.path().join("invariants.log"));
 
-/// Extrae la clave principal del comando si aplica (para hash slot).
-///
-/// # Argumentos
-///
-/// * `cmd` - Comando a analizar
-///
-/// # Retorna
-///
-/// `Option<String>` - Clave principal si aplica, None en caso contrario
-fn get_key_for_command(cmd: &Command) -> Option<String> {
-    match cmd {
-        Command::Append(key, _)
-        | Command::Get(key)
-        | Command::Getdel(key)
-        | Command::Set(key, _)
-        | Command::Strlen(key)
-        | Command::Substr(key, _, _)
-        | Command::Getrange(key, _, _)
-        | Command::Llen(key)
-        | Command::Lpop(key, _)
-        | Command::Rpop(key, _)
-        | Command::Lpush(key, _)
-        | Command::Rpush(key, _)
-        | Command::Lrange(key, _, _)
-        | Command::Scard(key)
-        | Command::Sismember(key, _)
-        | Command::Smembers(key)
-        | Command::Sadd(key, _)
-        | Command::Spop(key, _) => Some(key.clone()),
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Kiriko"]);
+        executor
+            .ds_guard
+            .write()
+            .unwrap()
+            .set_db
+            .insert("Hero".to_string(), Default::default());
+        run_instruction(&mut executor, "AAA000", "STRLEN", &["Hero"]);
+    }
 
-        //Command::Del(keys) => Some(keys),
-        Command::SMove(source, destination, ..) => {
-            // Requiere que ambos estén en el mismo slot
-            let slot_src = match hash_slot(source) {
-                Ok(slot) => slot,
-                Err(_) => return Some(format!("ERR Invalid key: {}", source)),
-            };
-            let slot_dst = match hash_slot(destination) {
-                Ok(slot) => slot,
-                Err(_) => return Some(format!("ERR Invalid key: {}", destination)),
-            };
-            if slot_src != slot_dst {
-                return Some(format!(
-                    "CROSSSLOT Keys {} and {} hash to different slots",
-                    source, destination
-                ));
+    #[test]
+    fn test_conversion_errors_use_canonical_replies() {
+        let (mut executor, _) = create_test_executor();
+
+        let response = run_instruction(&mut executor, "AAA000", "SET", &["Hero"]);
+        assert_eq!(
+            response,
+            RespMessage::Error("ERR wrong number of arguments for 'set' command".to_string())
+        );
+        let response = run_instruction(&mut executor, "AAA000", "LRANGE", &["Roster", "0", "ten"]);
+        assert_eq!(response, RespMessage::Error(ERR_NOT_INTEGER.to_string()));
+    }
+
+    #[test]
+    fn test_conversion_error_carries_argument() {
+        let (executor, _) = create_test_executor();
+        let instruction = Instruction::new(
+            "LRANGE".to_string(),
+            vec!["Roster".to_string(), "zero".to_string(), "10".to_string()],
+        );
+        let error = instruction.to_command().unwrap_err();
+        match executor.conversion_error(&instruction, error) {
+            CommandExecutorError::CommandConversionError(message, argument) => {
+                assert_eq!(message, ERR_NOT_INTEGER);
+                assert_eq!(
+                    argument,
+                    Some(ArgumentError {
+                        index: 1,
+                        reason: ArgumentReason::NotAnInteger
+                    })
+                );
             }
-            Some(source.clone()) // Usamos una para comprobar si el nodo lo maneja
+            other => panic!("Expected CommandConversionError, got {:?}", other),
         }
+    }
 
-        // Comandos sin clave (como PING, QUIT, SUBSCRIBE, etc.)
-        _ => None,
+    #[test]
+    fn test_restored_key_expires_after_ttl() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Lifeweaver"]);
+        let RespMessage::BulkString(Some(payload)) =
+            run_instruction(&mut executor, "AAA000", "DUMP", &["Hero"])
+        else {
+            panic!("DUMP debe responder el payload");
+        };
+        let payload = String::from_utf8(payload).unwrap();
+
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "RESTORE",
+            &["Support", "20", &payload],
+        );
+        let get = run_instruction(&mut executor, "AAA000", "GET", &["Support"]);
+        assert_eq!(get, RespMessage::BulkString(Some(b"Lifeweaver".to_vec())));
+
+        std::thread::sleep(Duration::from_millis(30));
+        let get = run_instruction(&mut executor, "AAA000", "GET", &["Support"]);
+        assert_eq!(get, RespMessage::Null(None));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        cluster::state::node_data::NodeData, command::Instruction,
-        config::node_configs::NodeConfigs, logs::aof_logger::AofLogger,
-        storage::data_store::DataStore,
-    };
-    use std::sync::mpsc;
+    /// Agrega a los nodos conocidos una réplica del nodo de prueba.
+    fn add_test_replica(executor: &CommandExecutor) {
+        add_replica(executor, "replica_kiriko", 16380, 0);
+    }
 
-    /// Crea un DataStore de prueba.
-    fn create_test_datastore() -> Arc<RwLock<DataStore>> {
-        Arc::new(RwLock::new(DataStore::new()))
+    /// Agrega a los nodos conocidos una réplica del nodo de prueba que
+    /// confirmó el offset de replicación dado.
+    fn add_replica(executor: &CommandExecutor, id: &str, port: u16, offset: u64) {
+        let mut replica = KnownNode::new(id.to_string(), "127.0.0.1".to_string(), port);
+        replica.get_flags_mut().set(SLAVE);
+        replica.set_master(Some("e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca".to_string()));
+        replica.set_replication_offset(offset);
+        executor
+            .nodes_list
+            .write()
+            .unwrap()
+            .insert(replica.get_id(), replica);
     }
 
-    /// Crea un logger de prueba.
-    fn create_test_logger() -> Arc<AofLogger> {
-        let settings = create_test_settings();
-        AofLogger::new(settings)
+    /// Indica si el nodo de prueba sigue siendo master.
+    fn is_test_node_master(executor: &CommandExecutor) -> bool {
+        NodeFlags::state_contains(executor.data_lock.read().unwrap().get_state(), MASTER)
     }
 
-    /// Crea configuración de nodo de prueba.
-    fn create_test_settings() -> NodeConfigs {
-        // Crear un archivo de configuración temporal para el test
-        let config_content = r#"
-            bind 0.0.0.0
-            port 6379
-            role M
-            maxclients 1000
-            save 900 15
-            dbfilename dump.rdb
-            dir ./
-            logfile redis.log
-            loglevel notice
-            node-id test_node_123
-            hash-slots 0-16383
-            "#;
+    #[test]
+    fn test_failover_waits_for_replica_catch_up() {
+        let (mut executor, _) = create_test_executor();
+        add_test_replica(&executor);
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Juno"]);
+        let offset = executor.data_lock.read().unwrap().get_replication_offset();
 
-        std::fs::write("test.conf", config_content).expect("Failed to write test nodes");
-        let result = NodeConfigs::new("test.conf");
-        std::fs::remove_file("test.conf").ok(); // Limpiar archivo temporal
-        result.expect("Failed to create test nodes")
+        let failover = handle(&mut executor, "AAA000", "FAILOVER", &[]);
+        assert_eq!(
+            failover.try_recv().unwrap(),
+            RespMessage::SimpleString("OK".to_string())
+        );
+        let set = handle(&mut executor, "BBB111", "SET", &["Hero", "Kiriko"]);
+        assert!(set.try_recv().is_err());
+
+        // La réplica todavía no confirmó el offset del master
+        executor.advance_failover();
+        assert!(is_test_node_master(&executor));
+        ack_replica(&executor, "replica_kiriko", offset - 1);
+        executor.advance_failover();
+        assert!(is_test_node_master(&executor));
+
+        ack_replica(&executor, "replica_kiriko", offset);
+        executor.advance_failover();
+        executor.release_paused();
+
+        assert!(!is_test_node_master(&executor));
+        assert_eq!(
+            executor.data_lock.read().unwrap().get_master_id(),
+            Some("replica_kiriko".to_string())
+        );
+        let nodes = executor.nodes_list.read().unwrap();
+        assert!(nodes.get("replica_kiriko").unwrap().is_master());
+        drop(nodes);
+        // La escritura postergada llega a un nodo que ya no es master
+        assert!(matches!(set.try_recv().unwrap(), RespMessage::Error(_)));
     }
 
-    /// Crea un CommandExecutor de prueba.
-    fn create_test_executor() -> (
-        CommandExecutor,
-        Sender<(String, Instruction, Sender<RespMessage>)>,
-    ) {
-        let (tx, rx) = mpsc::channel();
-        let settings = create_test_settings();
-        let node_data = NodeData::new(settings.clone());
+    #[test]
+    fn test_failover_picks_replica_with_highest_offset() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Juno"]);
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Ana"]);
+        add_replica(&executor, "replica_kiriko", 16380, 1);
+        add_replica(&executor, "replica_ana", 16381, 2);
 
-        let executor = CommandExecutor::new(
-            create_test_datastore(),
-            rx,
-            settings,
-            create_test_logger(),
-            mpsc::channel().0,
-            Arc::new(RwLock::new(HashMap::new())),
-            Arc::new(RwLock::new(node_data)),
+        // Ya confirmó todas las escrituras: el cambio de roles no espera
+        handle(&mut executor, "AAA000", "FAILOVER", &[]);
+        assert!(executor.failover.is_none());
+        assert_eq!(
+            executor.data_lock.read().unwrap().get_master_id(),
+            Some("replica_ana".to_string())
         );
-        (executor, tx)
     }
 
-    /// Crea una instrucción de prueba.
-    #[allow(dead_code)]
-    fn create_test_instruction(cmd_type: &str, args: Vec<String>) -> Instruction {
-        Instruction {
-            instruction_type: cmd_type.to_string(),
-            arguments: args,
-        }
+    #[test]
+    fn test_failover_abort_resumes_writes() {
+        let (mut executor, _) = create_test_executor();
+        add_test_replica(&executor);
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Juno"]);
+
+        handle(&mut executor, "AAA000", "FAILOVER", &["TIMEOUT", "60000"]);
+        let set = handle(&mut executor, "BBB111", "SET", &["Hero", "Kiriko"]);
+        assert!(set.try_recv().is_err());
+
+        let abort = handle(&mut executor, "AAA000", "FAILOVER", &["ABORT"]);
+        assert_eq!(
+            abort.try_recv().unwrap(),
+            RespMessage::SimpleString("OK".to_string())
+        );
+        assert_eq!(
+            set.try_recv().unwrap(),
+            RespMessage::SimpleString("OK".to_string())
+        );
+        assert!(is_test_node_master(&executor));
+        let nodes = executor.nodes_list.read().unwrap();
+        assert!(nodes.get("replica_kiriko").unwrap().is_slave());
+        drop(nodes);
+
+        let abort = handle(&mut executor, "AAA000", "FAILOVER", &["ABORT"]);
+        assert_eq!(
+            abort.try_recv().unwrap(),
+            RespMessage::Error(ERR_NO_FAILOVER.to_string())
+        );
     }
 
     #[test]
-    fn test_command_executor_new() {
-        let (executor, _) = create_test_executor();
-        assert_eq!(executor.counter, 0);
+    fn test_failover_timeout_aborts_unless_forced() {
+        let (mut executor, _) = create_test_executor();
+        let failover = handle(&mut executor, "AAA000", "FAILOVER", &[]);
+        assert_eq!(
+            failover.try_recv().unwrap(),
+            RespMessage::Error(ERR_FAILOVER_NO_REPLICAS.to_string())
+        );
+        add_test_replica(&executor);
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Juno"]);
+
+        handle(&mut executor, "AAA000", "FAILOVER", &["TIMEOUT", "10"]);
+        std::thread::sleep(Duration::from_millis(20));
+        executor.advance_failover();
+        assert!(executor.failover.is_none());
+        assert!(is_test_node_master(&executor));
+
+        let args = ["TO", "127.0.0.1", "6380", "FORCE", "TIMEOUT", "10"];
+        handle(&mut executor, "AAA000", "FAILOVER", &args);
+        std::thread::sleep(Duration::from_millis(20));
+        executor.advance_failover();
+        assert!(!is_test_node_master(&executor));
+    }
+
+    /// Registra la conexión y le hace escribir `Hero`, para que tenga
+    /// una escritura que esperar con `WAIT`.
+    fn write_as_registered_client(executor: &mut CommandExecutor, client_id: &str) {
+        register_client(executor, client_id);
+        let set = handle(executor, client_id, "SET", &["Hero", "Juno"]);
+        assert_eq!(
+            set.try_recv().unwrap(),
+            RespMessage::SimpleString("OK".to_string())
+        );
+    }
+
+    /// Simula el PSYNC de una réplica que confirma el offset dado.
+    fn ack_replica(executor: &CommandExecutor, replica_id: &str, offset: u64) {
+        executor
+            .nodes_list
+            .write()
+            .unwrap()
+            .get_mut(replica_id)
+            .unwrap()
+            .set_replication_offset(offset);
     }
 
     #[test]
-    fn test_format_reading_error() {
-        let error = CommandExecutor::format_reading_error(
-            "GET",
-            &["key1".to_string()],
-            &"lock error".to_string(),
+    fn test_wait_returns_once_replica_acknowledges() {
+        let (mut executor, _) = create_test_executor();
+        add_test_replica(&executor);
+        write_as_registered_client(&mut executor, "AAA000");
+
+        let wait = handle(&mut executor, "AAA000", "WAIT", &["1", "0"]);
+        assert!(wait.try_recv().is_err());
+
+        let offset = executor.data_lock.read().unwrap().get_replication_offset();
+        ack_replica(&executor, "replica_kiriko", offset);
+        executor.serve_waiting_clients();
+
+        assert_eq!(wait.try_recv().unwrap(), RespMessage::Integer(1));
+        assert!(executor.waiting.is_empty());
+
+        // Sin escrituras nuevas, la confirmación ya está: responde al instante
+        let wait = handle(&mut executor, "AAA000", "WAIT", &["1", "0"]);
+        assert_eq!(wait.try_recv().unwrap(), RespMessage::Integer(1));
+    }
+
+    #[test]
+    fn test_wait_times_out_with_fewer_replicas() {
+        let (mut executor, _) = create_test_executor();
+        add_test_replica(&executor);
+        let mut lagging =
+            KnownNode::new("replica_moira".to_string(), "127.0.0.1".to_string(), 16381);
+        lagging.get_flags_mut().set(SLAVE);
+        lagging.set_master(Some("e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca".to_string()));
+        executor
+            .nodes_list
+            .write()
+            .unwrap()
+            .insert(lagging.get_id(), lagging);
+        write_as_registered_client(&mut executor, "AAA000");
+
+        let offset = executor.data_lock.read().unwrap().get_replication_offset();
+        ack_replica(&executor, "replica_kiriko", offset);
+        ack_replica(&executor, "replica_moira", offset - 1);
+
+        let wait = handle(&mut executor, "AAA000", "WAIT", &["2", "10"]);
+        assert!(wait.try_recv().is_err());
+        std::thread::sleep(Duration::from_millis(20));
+        executor.serve_waiting_clients();
+
+        assert_eq!(wait.try_recv().unwrap(), RespMessage::Integer(1));
+    }
+
+    #[test]
+    fn test_wait_on_replica_is_rejected() {
+        let (mut executor, _) = create_test_executor();
+        executor
+            .data_lock
+            .write()
+            .unwrap()
+            .set_as_slave("replica_kiriko".to_string());
+
+        let wait = handle(&mut executor, "AAA000", "WAIT", &["1", "10"]);
+        assert_eq!(
+            wait.try_recv().unwrap(),
+            RespMessage::Error(ERR_WAIT_ON_REPLICA.to_string())
         );
-        assert!(error.contains("ERROR when trying to read on GET"));
-        assert!(error.contains("key1"));
-        assert!(error.contains("lock error"));
+        assert!(executor.waiting.is_empty());
+    }
+
+    /// Agrega a los nodos conocidos un master que atiende clientes en
+    /// `127.0.0.1:7001`, con los mismos slots que el nodo de prueba.
+    fn add_test_master(executor: &CommandExecutor) {
+        let mut master = KnownNode::new(
+            "master_reinhardt".to_string(),
+            "127.0.0.1".to_string(),
+            7001 + NODAL_COMMS_PORT,
+        );
+        master.get_flags_mut().set(MASTER);
+        master.set_hash_slots((0, 16383));
+        executor
+            .nodes_list
+            .write()
+            .unwrap()
+            .insert(master.get_id(), master);
     }
 
     #[test]
-    fn test_format_op_error() {
-        let error = CommandExecutor::format_op_error(
-            "SET",
-            &["key1".to_string(), "value1".to_string()],
-            &"invalid type".to_string(),
+    fn test_replicaof_rejects_writes_until_no_one() {
+        let (executor, _) = create_test_executor();
+        let (bus_tx, bus_rx) = mpsc::channel();
+        let mut executor = executor.with_cluster_bus(bus_tx);
+        add_test_master(&executor);
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Sigma"]);
+
+        let replicaof =
+            run_instruction(&mut executor, "AAA000", "REPLICAOF", &["127.0.0.1", "7001"]);
+        assert_eq!(replicaof, RespMessage::SimpleString("OK".to_string()));
+        assert_eq!(
+            executor.data_lock.read().unwrap().get_master_id(),
+            Some("master_reinhardt".to_string())
         );
-        assert!(error.contains("ERROR on SET"));
-        assert!(error.contains("key1"));
-        assert!(error.contains("value1"));
-        assert!(error.contains("invalid type"));
+        // Se pide la sincronización al master sin esperar al próximo PSYNC
+        let (target, _, _) = bus_rx.try_recv().unwrap();
+        assert_eq!(target, "master_reinhardt");
+
+        let set = run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Ramattra"]);
+        assert!(matches!(set, RespMessage::Error(e) if e.contains("permisos")));
+        let get = run_instruction(&mut executor, "AAA000", "GET", &["Hero"]);
+        assert_eq!(get, RespMessage::BulkString(Some(b"Sigma".to_vec())));
+
+        let no_one = run_instruction(&mut executor, "AAA000", "REPLICAOF", &["NO", "ONE"]);
+        assert_eq!(no_one, RespMessage::SimpleString("OK".to_string()));
+        assert!(is_test_node_master(&executor));
+        let set = run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Ramattra"]);
+        assert_eq!(set, RespMessage::SimpleString("OK".to_string()));
     }
 
     #[test]
-    fn test_get_key_for_command_string_commands() {
-        let cmd = Command::Get("test_key".to_string());
-        assert_eq!(get_key_for_command(&cmd), Some("test_key".to_string()));
+    fn test_replicaof_unknown_master_keeps_role() {
+        let (mut executor, _) = create_test_executor();
+        add_test_replica(&executor);
 
-        let cmd = Command::Set("test_key".to_string(), "test_value".to_string());
-        assert_eq!(get_key_for_command(&cmd), Some("test_key".to_string()));
+        let unknown = run_instruction(&mut executor, "AAA000", "REPLICAOF", &["127.0.0.1", "7009"]);
+        assert_eq!(unknown, RespMessage::Error(ERR_UNKNOWN_MASTER.to_string()));
+        // La réplica de prueba escucha en el puerto 6380 para los clientes
+        let replica = run_instruction(&mut executor, "AAA000", "REPLICAOF", &["127.0.0.1", "6380"]);
+        assert_eq!(
+            replica,
+            RespMessage::Error(ERR_REPLICATE_REPLICA.to_string())
+        );
+        assert!(is_test_node_master(&executor));
     }
 
     #[test]
-    fn test_get_key_for_command_list_commands() {
-        let cmd = Command::Lpush("test_key".to_string(), vec!["value1".to_string()]);
-        assert_eq!(get_key_for_command(&cmd), Some("test_key".to_string()));
+    fn test_sync_sends_store_and_then_writes() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Mauga"]);
+        run_instruction(
+            &mut executor,
+            "AAA000",
+            "RPUSH",
+            &["Tanks", "Orisa", "Zarya"],
+        );
 
-        let cmd = Command::Lrange("test_key".to_string(), 0, 10);
-        assert_eq!(get_key_for_command(&cmd), Some("test_key".to_string()));
+        let sync = handle(&mut executor, "BBB111", "SYNC", &[]);
+        let payload = sync.try_recv().unwrap().as_bytes();
+        let replica = read_sync_payload(&mut payload.as_slice()).unwrap();
+        assert_eq!(replica.get("Hero"), Some(&"Mauga".to_string()));
+        assert_eq!(
+            replica.list_db.get("Tanks").unwrap(),
+            &vec!["Orisa".to_string(), "Zarya".to_string()]
+        );
+
+        // Las escrituras posteriores llegan como comandos; las lecturas no
+        handle(&mut executor, "AAA000", "GET", &["Hero"]);
+        handle(&mut executor, "AAA000", "SET", &["Hero", "Junker Queen"]);
+        assert_eq!(
+            sync.try_recv().unwrap(),
+            RespMessage::Array(vec![
+                RespMessage::BulkString(Some(b"SET".to_vec())),
+                RespMessage::BulkString(Some(b"Hero".to_vec())),
+                RespMessage::BulkString(Some(b"Junker Queen".to_vec())),
+            ])
+        );
+        assert!(sync.try_recv().is_err());
+
+        drop(sync);
+        handle(&mut executor, "AAA000", "DEL", &["Hero"]);
+        assert!(executor.sync_clients.is_empty());
+    }
+
+    /// Argumentos de un comando propagado por SYNC.
+    fn propagated_arguments(message: RespMessage) -> Vec<String> {
+        let RespMessage::Array(items) = message else {
+            panic!("expected an array, got {:?}", message);
+        };
+        items
+            .into_iter()
+            .map(|item| match item {
+                RespMessage::BulkString(Some(bytes)) => String::from_utf8(bytes).unwrap(),
+                other => panic!("expected a bulk string, got {:?}", other),
+            })
+            .collect()
     }
 
     #[test]
-    fn test_get_key_for_command_set_commands() {
-        let cmd = Command::Sadd("test_key".to_string(), vec!["value1".to_string()]);
-        assert_eq!(get_key_for_command(&cmd), Some("test_key".to_string()));
+    fn test_sync_streams_replication_form_and_ttls() {
+        let (mut executor, _) = create_test_executor();
+        run_instruction(&mut executor, "AAA000", "SET", &["Hero", "Mauga"]);
+        run_instruction(&mut executor, "AAA000", "EXPIRE", &["Hero", "100"]);
+        run_instruction(&mut executor, "AAA000", "SADD", &["Maps", "Petra"]);
 
-        let cmd = Command::Smembers("test_key".to_string());
-        assert_eq!(get_key_for_command(&cmd), Some("test_key".to_string()));
+        let sync = handle(&mut executor, "BBB111", "SYNC", &[]);
+        let payload = sync.try_recv().unwrap().as_bytes();
+        let replica = read_sync_payload(&mut payload.as_slice()).unwrap();
+        let ttl = replica.remaining_ttl("Hero").unwrap();
+        assert!(ttl > Duration::from_secs(98) && ttl <= Duration::from_secs(100));
+
+        handle(&mut executor, "AAA000", "SPOP", &["Maps", "1"]);
+        assert_eq!(
+            propagated_arguments(sync.try_recv().unwrap()),
+            vec!["SREM", "Maps", "Petra"]
+        );
+
+        let before = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        handle(&mut executor, "AAA000", "EXPIRE", &["Hero", "60"]);
+        let arguments = propagated_arguments(sync.try_recv().unwrap());
+        assert_eq!(arguments[..2], ["PEXPIREAT", "Hero"]);
+        let unix_ms: u128 = arguments[2].parse().unwrap();
+        assert!(unix_ms >= (before + Duration::from_secs(60)).as_millis());
+
+        handle(
+            &mut executor,
+            "AAA000",
+            "XADD",
+            &["Picks", "*", "hero", "Kiriko"],
+        );
+        let arguments = propagated_arguments(sync.try_recv().unwrap());
+        assert_eq!(arguments[..2], ["XADD", "Picks"]);
+        assert_ne!(arguments[2], "*");
+        assert_eq!(arguments[3..], ["hero", "Kiriko"]);
+
+        // Un EXPIRE sobre una clave inexistente no cambia nada
+        handle(&mut executor, "AAA000", "EXPIRE", &["Ghost", "60"]);
+        assert!(sync.try_recv().is_err());
+    }
+
+    /// Envía una instrucción por el camino de la conexión y devuelve la
+    /// respuesta que le llega al cliente.
+    fn handle_and_reply(
+        executor: &mut CommandExecutor,
+        client_id: &str,
+        cmd_type: &str,
+        args: &[&str],
+    ) -> RespMessage {
+        let (res_tx, res_rx) = mpsc::channel();
+        let instruction = Instruction::new(
+            cmd_type.to_string(),
+            args.iter().map(|arg| arg.to_string()).collect(),
+        );
+        executor.handle_instruction(client_id.to_string(), instruction, &res_tx);
+        res_rx.try_recv().unwrap()
     }
 
     #[test]
-    fn test_get_key_for_command_no_key_commands() {
-        let cmd = Command::Echo("test".to_string());
-        assert_eq!(get_key_for_command(&cmd), None);
+    fn test_exec_runs_queued_commands_in_order() {
+        let (mut executor, _) = create_test_executor();
+        let queued = RespMessage::SimpleString("QUEUED".to_string());
+        let ok = RespMessage::SimpleString("OK".to_string());
 
-        let cmd = Command::BgSave;
-        assert_eq!(get_key_for_command(&cmd), None);
+        assert_eq!(handle_and_reply(&mut executor, "AAA000", "MULTI", &[]), ok);
+        assert_eq!(
+            handle_and_reply(&mut executor, "AAA000", "SET", &["Kills", "1"]),
+            queued
+        );
+        assert_eq!(
+            handle_and_reply(&mut executor, "AAA000", "INCR", &["Kills"]),
+            queued
+        );
+        // Otra conexión no ve las escrituras encoladas
+        assert_eq!(
+            run_instruction(&mut executor, "AAA001", "GET", &["Kills"]),
+            RespMessage::Null(None)
+        );
+
+        assert_eq!(
+            handle_and_reply(&mut executor, "AAA000", "EXEC", &[]),
+            RespMessage::Array(vec![ok.clone(), RespMessage::Integer(2)])
+        );
+        assert_eq!(
+            run_instruction(&mut executor, "AAA001", "GET", &["Kills"]),
+            RespMessage::BulkString(Some(b"2".to_vec()))
+        );
+        // La transacción quedó cerrada
+        assert_eq!(
+            handle_and_reply(&mut executor, "AAA000", "EXEC", &[]),
+            RespMessage::Error(ERR_EXEC_WITHOUT_MULTI.to_string())
+        );
     }
 
     #[test]
-    fn test_command_writes_on_db() {
-        assert!(Command::Set("key".to_string(), "value".to_string()).writes_on_db());
-        assert!(Command::Del(vec!["key".to_string()]).writes_on_db());
-        assert!(Command::Lpush("key".to_string(), vec!["value".to_string()]).writes_on_db());
-        assert!(!Command::Get("key".to_string()).writes_on_db());
-        assert!(!Command::Echo("test".to_string()).writes_on_db());
+    fn test_discard_drops_queued_commands() {
+        let (mut executor, _) = create_test_executor();
+
+        handle_and_reply(&mut executor, "AAA000", "MULTI", &[]);
+        handle_and_reply(&mut executor, "AAA000", "SET", &["Hero", "Sombra"]);
+        assert_eq!(
+            handle_and_reply(&mut executor, "AAA000", "DISCARD", &[]),
+            RespMessage::SimpleString("OK".to_string())
+        );
+        assert_eq!(
+            run_instruction(&mut executor, "AAA000", "GET", &["Hero"]),
+            RespMessage::Null(None)
+        );
+        assert_eq!(
+            handle_and_reply(&mut executor, "AAA000", "DISCARD", &[]),
+            RespMessage::Error(ERR_DISCARD_WITHOUT_MULTI.to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_error_in_transaction_aborts_exec() {
+        let (mut executor, _) = create_test_executor();
+
+        handle_and_reply(&mut executor, "AAA000", "MULTI", &[]);
+        handle_and_reply(&mut executor, "AAA000", "SET", &["Hero", "Kiriko"]);
+        let reply = handle_and_reply(&mut executor, "AAA000", "INCRBY", &["Hero"]);
+        assert!(matches!(reply, RespMessage::Error(_)));
+        assert_eq!(
+            handle_and_reply(&mut executor, "AAA000", "MULTI", &[]),
+            RespMessage::Error(ERR_NESTED_MULTI.to_string())
+        );
+
+        assert_eq!(
+            handle_and_reply(&mut executor, "AAA000", "EXEC", &[]),
+            RespMessage::Error(ERR_EXECABORT.to_string())
+        );
+        assert_eq!(
+            run_instruction(&mut executor, "AAA000", "GET", &["Hero"]),
+            RespMessage::Null(None)
+        );
     }
 
     #[test]
@@ -863,7 +5818,7 @@ mod tests {
 
     #[test]
     fn test_command_executor_error_debug() {
-        let error = CommandExecutorError::CommandConversionError("test error".to_string());
+        let error = CommandExecutorError::CommandConversionError("test error".to_string(), None);
         let debug_str = format!("{:?}", error);
         assert!(debug_str.contains("CommandConversionError"));
         assert!(debug_str.contains("test error"));
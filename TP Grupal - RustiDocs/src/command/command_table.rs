@@ -0,0 +1,225 @@
+//! Tabla de los comandos soportados, usada por `COMMAND COUNT` y
+//! `COMMAND DOCS`.
+//!
+//! Cada comando declara sólo su aridad y una invocación de ejemplo. Los
+//! flags y el grupo no se repiten acá: salen de parsear el ejemplo y
+//! clasificar el `Command` resultante, así no se desincronizan del resto.
+
+use super::{Instruction, instruction::InstructionError, types::Command};
+
+/// Un comando soportado por el servidor.
+#[derive(Debug)]
+pub struct CommandSpec {
+    /// Nombre del comando, en mayúsculas
+    pub name: &'static str,
+    /// Aridad al estilo de Redis: cantidad de argumentos contando el
+    /// nombre, o su mínimo en negativo si el comando acepta más
+    pub arity: i64,
+    /// Argumentos de una invocación válida
+    example: &'static [&'static str],
+}
+
+impl CommandSpec {
+    const fn new(name: &'static str, arity: i64, example: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            arity,
+            example,
+        }
+    }
+
+    /// Comando que resulta de parsear la invocación de ejemplo.
+    pub fn example_command(&self) -> Result<Command, InstructionError> {
+        Instruction::new(
+            self.name.to_string(),
+            self.example.iter().map(|arg| arg.to_string()).collect(),
+        )
+        .to_command()
+    }
+
+    /// Flags del comando: `write` si escribe en la base y `readonly` si
+    /// sólo la lee. Los comandos de servidor y de conexión no tienen.
+    pub fn flags(&self) -> Vec<&'static str> {
+        match self.example_command() {
+            Ok(command) if command.writes_on_db() => vec!["write"],
+            Ok(command) if command.is_read_only() => vec!["readonly"],
+            _ => vec![],
+        }
+    }
+
+    /// Grupo del comando, su categoría en minúsculas.
+    pub fn group(&self) -> String {
+        self.example_command()
+            .map(|command| command.category().to_lowercase())
+            .unwrap_or_default()
+    }
+}
+
+/// Comandos soportados, en el orden en que los parsea `Instruction`.
+pub const COMMAND_TABLE: &[CommandSpec] = &[
+    // String commands
+    CommandSpec::new("APPEND", 3, &["key", "value"]),
+    CommandSpec::new("DEL", -2, &["key"]),
+    CommandSpec::new("ECHO", 2, &["message"]),
+    CommandSpec::new("SET", -3, &["key", "value"]),
+    CommandSpec::new("SETNX", 3, &["key", "value"]),
+    CommandSpec::new("GET", 2, &["key"]),
+    CommandSpec::new("INCR", 2, &["key"]),
+    CommandSpec::new("DECR", 2, &["key"]),
+    CommandSpec::new("INCRBY", 3, &["key", "1"]),
+    CommandSpec::new("DECRBY", 3, &["key", "1"]),
+    CommandSpec::new("MGET", -2, &["key"]),
+    CommandSpec::new("MSET", -3, &["key", "value"]),
+    CommandSpec::new("GETDEL", 2, &["key"]),
+    CommandSpec::new("GETSET", 3, &["key", "value"]),
+    CommandSpec::new("STRLEN", 2, &["key"]),
+    CommandSpec::new("GETRANGE", 4, &["key", "0", "-1"]),
+    CommandSpec::new("SUBSTR", 4, &["key", "0", "-1"]),
+    CommandSpec::new("SETRANGE", 4, &["key", "0", "value"]),
+    CommandSpec::new("PFADD", -2, &["key"]),
+    CommandSpec::new("PFCOUNT", -2, &["key"]),
+    CommandSpec::new("PFMERGE", -2, &["key"]),
+    // List commands
+    CommandSpec::new("LLEN", 2, &["key"]),
+    CommandSpec::new("LPOP", 3, &["key", "1"]),
+    CommandSpec::new("RPOP", 3, &["key", "1"]),
+    CommandSpec::new("LPUSH", -3, &["key", "element"]),
+    CommandSpec::new("RPUSH", -3, &["key", "element"]),
+    CommandSpec::new("LRANGE", 4, &["key", "0", "-1"]),
+    CommandSpec::new("LINDEX", 3, &["key", "0"]),
+    CommandSpec::new("LSET", 4, &["key", "0", "element"]),
+    CommandSpec::new("LREM", 4, &["key", "0", "element"]),
+    CommandSpec::new("LINSERT", 5, &["key", "BEFORE", "pivot", "element"]),
+    CommandSpec::new("LMOVE", 5, &["source", "destination", "LEFT", "RIGHT"]),
+    CommandSpec::new("RPOPLPUSH", 3, &["source", "destination"]),
+    CommandSpec::new("SORT", -2, &["key"]),
+    // Set commands
+    CommandSpec::new("SADD", -3, &["key", "member"]),
+    CommandSpec::new("SMEMBERS", 2, &["key"]),
+    CommandSpec::new("SINTER", -2, &["key"]),
+    CommandSpec::new("SUNION", -2, &["key"]),
+    CommandSpec::new("SDIFF", -2, &["key"]),
+    CommandSpec::new("SCARD", 2, &["key"]),
+    CommandSpec::new("SISMEMBER", 3, &["key", "member"]),
+    CommandSpec::new("SRANDMEMBER", -2, &["key"]),
+    CommandSpec::new("SMOVE", 4, &["source", "destination", "member"]),
+    CommandSpec::new("SPOP", 3, &["key", "1"]),
+    CommandSpec::new("SREM", -3, &["key", "member"]),
+    // Hash commands
+    CommandSpec::new("HSET", -4, &["key", "field", "value"]),
+    CommandSpec::new("HGET", 3, &["key", "field"]),
+    CommandSpec::new("HDEL", -3, &["key", "field"]),
+    CommandSpec::new("HGETALL", 2, &["key"]),
+    // Geo commands
+    CommandSpec::new("GEOADD", -5, &["key", "0", "0", "member"]),
+    CommandSpec::new("GEOPOS", -2, &["key", "member"]),
+    CommandSpec::new("GEODIST", -4, &["key", "member1", "member2"]),
+    CommandSpec::new(
+        "GEOSEARCH",
+        -7,
+        &["key", "FROMMEMBER", "member", "BYRADIUS", "1", "km"],
+    ),
+    // Stream commands
+    CommandSpec::new("XADD", -5, &["key", "*", "field", "value"]),
+    CommandSpec::new("XLEN", 2, &["key"]),
+    CommandSpec::new("XRANGE", -4, &["key", "-", "+"]),
+    CommandSpec::new("XREAD", -4, &["STREAMS", "key", "0"]),
+    CommandSpec::new("XGROUP", -5, &["CREATE", "key", "group", "$"]),
+    CommandSpec::new(
+        "XREADGROUP",
+        -7,
+        &["GROUP", "group", "consumer", "STREAMS", "key", ">"],
+    ),
+    CommandSpec::new("XACK", -4, &["key", "group", "0-1"]),
+    CommandSpec::new("XPENDING", 3, &["key", "group"]),
+    // Database commands
+    CommandSpec::new("BGSAVE", 1, &[]),
+    CommandSpec::new("SAVE", 1, &[]),
+    CommandSpec::new("BGREWRITEAOF", 1, &[]),
+    CommandSpec::new("INFO", -1, &[]),
+    CommandSpec::new("EXISTS", -2, &["key"]),
+    CommandSpec::new("TOUCH", -2, &["key"]),
+    CommandSpec::new("EXPIRE", 3, &["key", "10"]),
+    CommandSpec::new("PEXPIREAT", 3, &["key", "1700000000000"]),
+    CommandSpec::new("PERSIST", 2, &["key"]),
+    CommandSpec::new("TTL", 2, &["key"]),
+    CommandSpec::new("TYPE", 2, &["key"]),
+    CommandSpec::new("RENAME", 3, &["key", "newkey"]),
+    CommandSpec::new("RENAMENX", 3, &["key", "newkey"]),
+    CommandSpec::new("DUMP", 2, &["key"]),
+    CommandSpec::new("RESTORE", -4, &["key", "0", "payload"]),
+    CommandSpec::new("KEYS", 2, &["*"]),
+    CommandSpec::new("DBSIZE", 1, &[]),
+    CommandSpec::new("FLUSHDB", 1, &[]),
+    CommandSpec::new("SCAN", -2, &["0"]),
+    CommandSpec::new("DEBUG", -2, &["RELOAD"]),
+    CommandSpec::new("LATENCY", -2, &["LATEST"]),
+    CommandSpec::new("OBJECT", -2, &["ENCODING", "key"]),
+    CommandSpec::new("COMMAND", -2, &["COUNT"]),
+    // Pub/Sub commands
+    CommandSpec::new("SUBSCRIBE", 2, &["channel"]),
+    CommandSpec::new("UNSUBSCRIBE", 2, &["channel"]),
+    CommandSpec::new("PSUBSCRIBE", -2, &["pattern"]),
+    CommandSpec::new("PUNSUBSCRIBE", -1, &[]),
+    CommandSpec::new("PUBLISH", 3, &["channel", "message"]),
+    CommandSpec::new("PUBSUB", -2, &["CHANNELS"]),
+    // Cluster commands
+    CommandSpec::new("MEET", 2, &["127.0.0.1:7001"]),
+    CommandSpec::new("CLUSTER", 2, &["MYID"]),
+    CommandSpec::new("FAILOVER", -1, &[]),
+    CommandSpec::new("WAIT", 3, &["0", "0"]),
+    CommandSpec::new("SYNC", 1, &[]),
+    CommandSpec::new("REPLICAOF", 3, &["NO", "ONE"]),
+    // Log commands
+    CommandSpec::new("AUTH", 3, &["username", "password"]),
+    // Connection commands
+    CommandSpec::new("CLIENT", -2, &["GETNAME"]),
+    CommandSpec::new("HELLO", -1, &[]),
+    CommandSpec::new("PING", -1, &[]),
+    // Transaction commands
+    CommandSpec::new("MULTI", 1, &[]),
+    CommandSpec::new("EXEC", 1, &[]),
+    CommandSpec::new("DISCARD", 1, &[]),
+];
+
+/// Busca un comando por nombre, sin distinguir mayúsculas.
+pub fn find_command(name: &str) -> Option<&'static CommandSpec> {
+    COMMAND_TABLE
+        .iter()
+        .find(|spec| spec.name.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::users::permissions::Permissions;
+
+    #[test]
+    fn test_every_command_example_parses() {
+        for spec in COMMAND_TABLE {
+            let command = spec.example_command();
+            assert!(command.is_ok(), "{}: {:?}", spec.name, command);
+        }
+    }
+
+    #[test]
+    fn test_table_lists_every_command_of_a_super_user() {
+        let mut permissions = Permissions::new();
+        permissions.set_super();
+
+        // AUTH se atiende antes de iniciar la sesión, fuera de los permisos
+        for spec in COMMAND_TABLE.iter().filter(|spec| spec.name != "AUTH") {
+            assert!(permissions.is_permited(spec.name), "{}", spec.name);
+        }
+        assert!(find_command("set").is_some());
+        assert!(find_command("WATCH").is_none());
+    }
+
+    #[test]
+    fn test_flags_follow_command_classification() {
+        assert_eq!(find_command("SET").unwrap().flags(), vec!["write"]);
+        assert_eq!(find_command("GET").unwrap().flags(), vec!["readonly"]);
+        assert!(find_command("HELLO").unwrap().flags().is_empty());
+        assert_eq!(find_command("LPUSH").unwrap().group(), "list");
+    }
+}
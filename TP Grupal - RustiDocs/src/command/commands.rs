@@ -10,19 +10,30 @@
 // IMPORTS
 use super::types::ResponseType;
 use crate::cluster::cluster_node::ClusterNode;
+use crate::cluster::comms::node_input::NODAL_COMMS_PORT;
+use crate::cluster::state::flags::{CONNECTED, ME, NodeFlags, SLAVE};
 use crate::cluster::state::node_data::NodeData;
-use crate::cluster::types::{KnownNode, NodeId, SlotRange};
-use crate::command::types::Command;
+use crate::cluster::types::{Epoch, KnownNode, NodeId, SlotRange, TimeStamp, is_empty_slot_range};
+use crate::command::types::{
+    Command, GeoOrigin, GeoSearchOptions, GeoShape, InsertWhere, ListEnd, SetOptions,
+};
+use crate::command::utils::glob_match;
 use crate::config::node_configs::NodeConfigs;
 use crate::logs::aof_logger::AofLogger;
 use crate::network::RespMessage;
 use crate::storage::DataStore;
+use crate::storage::dump::{DumpValue, dump_value, from_hex, restore_value, to_hex};
+use crate::storage::geo::{self, GeoUnit};
+use crate::storage::hyperloglog::HyperLogLog;
 use crate::storage::snapshot_manager::create_dump;
-use std::collections::{HashMap, HashSet};
+use crate::storage::stream::{Stream, StreamError, StreamFields, StreamId};
+use rand::seq::SliceRandom;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::mpsc;
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, RwLock};
 use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Errores específicos de comandos
 #[derive(Debug)]
@@ -56,11 +67,26 @@ impl std::error::Error for CommandError {}
 // MENSAJES DE ERROR
 // const ERR_WRONG_TYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
 const ERR_WRONG_NUM_ARGS: &str = "ERR wrong number of arguments for '_' command";
+const ERR_SORT_NOT_DOUBLE: &str = "ERR One or more scores can't be converted into double";
+const ERR_BUSYKEY: &str = "BUSYKEY Target key name already exists.";
+const ERR_NOT_INTEGER: &str = "ERR value is not an integer or out of range";
+const ERR_OVERFLOW: &str = "ERR increment or decrement would overflow";
+const ERR_NO_SUCH_KEY: &str = "ERR no such key";
+const ERR_INDEX_OUT_OF_RANGE: &str = "ERR index out of range";
+const ERR_STRING_TOO_LONG: &str = "ERR string exceeds maximum allowed size (proto-max-bulk-len)";
+const ERR_INVALID_TTL: &str = "ERR Invalid TTL value, must be >= 0";
+const ERR_XGROUP_NO_KEY: &str = "ERR The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically.";
+
+/// Largo máximo en bytes de un string, el mismo límite que usa Redis.
+const MAX_STRING_SIZE: usize = 512 * 1024 * 1024;
 
 // CÓDIGOS DE ERROR
 const STR_CODE: i64 = 0;
 const LIST_CODE: i64 = 1;
 const SET_CODE: i64 = 2;
+const STREAM_CODE: i64 = 3;
+const HASH_CODE: i64 = 4;
+const ZSET_CODE: i64 = 5;
 
 // CÓDIGO
 
@@ -76,35 +102,127 @@ const SET_CODE: i64 = 2;
 ///
 /// Verdadero si el valor no es del tipo buscado. Caso contrario, Falso.
 fn wrong_type_error(store: &DataStore, key: &String, code: i64) -> bool {
+    let is_stream = store.stream_db.contains_key(key);
+    let is_hash = store.hash_db.contains_key(key);
+    let is_zset = store.zset_db.contains_key(key);
     match code {
-        STR_CODE => store.list_db.contains_key(key) || store.set_db.contains_key(key),
-        LIST_CODE => store.string_db.contains_key(key) || store.set_db.contains_key(key),
-        SET_CODE => store.string_db.contains_key(key) || store.list_db.contains_key(key),
+        STR_CODE => {
+            store.list_db.contains_key(key)
+                || store.set_db.contains_key(key)
+                || is_stream
+                || is_hash
+                || is_zset
+        }
+        LIST_CODE => {
+            store.string_db.contains_key(key)
+                || store.set_db.contains_key(key)
+                || is_stream
+                || is_hash
+                || is_zset
+        }
+        SET_CODE => {
+            store.string_db.contains_key(key)
+                || store.list_db.contains_key(key)
+                || is_stream
+                || is_hash
+                || is_zset
+        }
+        STREAM_CODE => {
+            store.string_db.contains_key(key)
+                || store.list_db.contains_key(key)
+                || store.set_db.contains_key(key)
+                || is_hash
+                || is_zset
+        }
+        HASH_CODE => {
+            store.string_db.contains_key(key)
+                || store.list_db.contains_key(key)
+                || store.set_db.contains_key(key)
+                || is_stream
+                || is_zset
+        }
+        ZSET_CODE => {
+            store.string_db.contains_key(key)
+                || store.list_db.contains_key(key)
+                || store.set_db.contains_key(key)
+                || is_stream
+                || is_hash
+        }
         _ => false,
     }
 }
 
+/// Asigna el valor a la clave (SET), pisándola aunque sea de otro tipo.
+/// La clave queda con el vencimiento de `EX` o, sin él, persistente.
+///
+/// # Returns
+///
+/// `OK`, o nulo si no se cumple la condición de `NX` o `XX`.
 pub fn set(
     store: &mut DataStore,
     key: String,
     value: String,
+    options: &SetOptions,
 ) -> Result<ResponseType, CommandError> {
+    let exists = store.contains_key(&key);
+    if (options.nx && exists) || (options.xx && !exists) {
+        return Ok(ResponseType::Null(None));
+    }
     store.list_db.remove(&key);
     store.set_db.remove(&key);
+    store.stream_db.remove(&key);
+    store.hash_db.remove(&key);
+    store.zset_db.remove(&key);
+    store.set_ttl(&key, options.ex.map(Duration::from_secs));
     store.string_db.insert(key, value);
     Ok(ResponseType::Str("OK".to_string()))
 }
 
+/// Asigna el valor sólo si la clave no existe (SETNX).
+///
+/// # Returns
+///
+/// 1 si se asignó, 0 si la clave ya existía.
+pub fn setnx(store: &mut DataStore, key: &str, value: &str) -> Result<ResponseType, CommandError> {
+    let options = SetOptions {
+        nx: true,
+        ..SetOptions::default()
+    };
+    match set(store, key.to_string(), value.to_string(), &options)? {
+        ResponseType::Null(_) => Ok(ResponseType::Int(0)),
+        _ => Ok(ResponseType::Int(1)),
+    }
+}
+
 pub fn get(store: &DataStore, key: &String) -> Result<ResponseType, CommandError> {
     if wrong_type_error(store, key, STR_CODE) {
         return Err(CommandError::WrongType);
     }
-    if let Some(value) = store.string_db.get(key) {
+    if let Some(value) = store.get(key) {
         return Ok(ResponseType::Str(value.clone()));
     }
     Ok(ResponseType::Null(None))
 }
 
+/// Obtiene los valores de varias claves. A diferencia de `GET`, una clave
+/// que no guarda un string no es un error: su valor es nulo.
+pub fn mget(store: &DataStore, keys: &[String]) -> Vec<Option<String>> {
+    keys.iter().map(|key| store.get(key).cloned()).collect()
+}
+
+/// Asigna todos los pares clave-valor (MSET), pisando las claves de
+/// cualquier tipo igual que `SET`. Se aplica entero bajo el mismo lock de
+/// escritura, así que ningún cliente ve una asignación a medias.
+pub fn mset(
+    store: &mut DataStore,
+    pairs: &[(String, String)],
+) -> Result<ResponseType, CommandError> {
+    for (key, value) in pairs {
+        set(store, key.clone(), value.clone(), &SetOptions::default())?;
+    }
+    Ok(ResponseType::Str("OK".to_string()))
+}
+
 pub fn append(
     store: &mut DataStore,
     key: String,
@@ -151,13 +269,9 @@ pub fn get_slice(
     if wrong_type_error(store, key, LIST_CODE) {
         return Err(CommandError::WrongType);
     }
-    if let Some(list) = store.list_db.get(key) {
+    if let Some(list) = store.get_list(key) {
         let len = list.len() as i64;
-        let s = if start < 0 {
-            (len + start).max(0)
-        } else {
-            start
-        };
+        let s = normalize_index(len, start).max(0);
         let e = if end < 0 || end >= len { len - 1 } else { end };
 
         if s > e {
@@ -170,6 +284,173 @@ pub fn get_slice(
     Ok(ResponseType::List(vec![]))
 }
 
+/// Convierte un índice de lista, negativo si se cuenta desde el final,
+/// en su posición desde el inicio. El resultado puede quedar fuera de la
+/// lista.
+fn normalize_index(len: i64, index: i64) -> i64 {
+    if index < 0 { len + index } else { index }
+}
+
+/// Posición del índice dentro de una lista de largo `len`, o `None` si
+/// queda fuera de rango.
+fn list_position(len: usize, index: i64) -> Option<usize> {
+    let position = normalize_index(len as i64, index);
+    (0..len as i64)
+        .contains(&position)
+        .then_some(position as usize)
+}
+
+/// Obtiene el elemento de la lista en el índice (LINDEX).
+///
+/// # Returns
+///
+/// El elemento, o nulo si la clave no existe o el índice está fuera de rango.
+pub fn lindex(store: &DataStore, key: &String, index: i64) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, LIST_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let element = store
+        .list_db
+        .get(key)
+        .and_then(|list| list_position(list.len(), index).map(|position| &list[position]));
+    match element {
+        Some(element) => Ok(ResponseType::Str(element.clone())),
+        None => Ok(ResponseType::Null(None)),
+    }
+}
+
+/// Reemplaza el elemento de la lista en el índice (LSET).
+///
+/// # Errors
+///
+/// Si la clave no existe o el índice está fuera de rango.
+pub fn lset(
+    store: &mut DataStore,
+    key: &String,
+    index: i64,
+    value: &str,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, LIST_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let Some(list) = store.list_db.get_mut(key) else {
+        return Err(CommandError::Custom(ERR_NO_SUCH_KEY.to_string()));
+    };
+    let Some(position) = list_position(list.len(), index) else {
+        return Err(CommandError::Custom(ERR_INDEX_OUT_OF_RANGE.to_string()));
+    };
+    list[position] = value.to_string();
+    Ok(ResponseType::Str("OK".to_string()))
+}
+
+/// Elimina de la lista los elementos iguales a `value` (LREM): con `count`
+/// positivo los primeros `count` desde el inicio, con `count` negativo los
+/// primeros `|count|` desde el final, y con 0 todos.
+///
+/// La lista que queda vacía se borra al terminar la escritura.
+pub fn lrem(
+    store: &mut DataStore,
+    key: &String,
+    count: i64,
+    value: &str,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, LIST_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let Some(list) = store.list_db.get_mut(key) else {
+        return Ok(ResponseType::Int(0));
+    };
+    let limit = match count {
+        0 => usize::MAX,
+        count => usize::try_from(count.unsigned_abs()).unwrap_or(usize::MAX),
+    };
+
+    // Desde el final es lo mismo sobre la lista invertida
+    if count < 0 {
+        list.reverse();
+    }
+    let mut removed = 0;
+    list.retain(|element| {
+        let remove = removed < limit && element == value;
+        if remove {
+            removed += 1;
+        }
+        !remove
+    });
+    if count < 0 {
+        list.reverse();
+    }
+    Ok(ResponseType::Int(removed as i64))
+}
+
+/// Inserta `value` antes o después de la primera aparición de `pivot` en
+/// la lista (LINSERT).
+///
+/// # Returns
+///
+/// El nuevo largo de la lista, -1 si el pivote no está o 0 si la clave no
+/// existe.
+pub fn linsert(
+    store: &mut DataStore,
+    key: &String,
+    place: InsertWhere,
+    pivot: &str,
+    value: &str,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, LIST_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let Some(list) = store.list_db.get_mut(key) else {
+        return Ok(ResponseType::Int(0));
+    };
+    let Some(position) = list.iter().position(|element| element == pivot) else {
+        return Ok(ResponseType::Int(-1));
+    };
+    let position = match place {
+        InsertWhere::Before => position,
+        InsertWhere::After => position + 1,
+    };
+    list.insert(position, value.to_string());
+    Ok(ResponseType::Int(list.len() as i64))
+}
+
+/// Saca un elemento de un extremo de `source` y lo agrega en un extremo de
+/// `destination` (LMOVE, RPOPLPUSH). Si son la misma lista, la rota.
+///
+/// # Returns
+///
+/// El elemento movido, o nulo si el origen no existe.
+pub fn lmove(
+    store: &mut DataStore,
+    source: &String,
+    destination: &String,
+    from: ListEnd,
+    to: ListEnd,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, source, LIST_CODE) || wrong_type_error(store, destination, LIST_CODE)
+    {
+        return Err(CommandError::WrongType);
+    }
+    let Some(list) = store.list_db.get_mut(source) else {
+        return Ok(ResponseType::Null(None));
+    };
+    let element = match from {
+        ListEnd::Left if !list.is_empty() => Some(list.remove(0)),
+        ListEnd::Left => None,
+        ListEnd::Right => list.pop(),
+    };
+    let Some(element) = element else {
+        return Ok(ResponseType::Null(None));
+    };
+
+    let list = store.list_db.entry(destination.clone()).or_default();
+    match to {
+        ListEnd::Left => list.insert(0, element.clone()),
+        ListEnd::Right => list.push(element.clone()),
+    }
+    Ok(ResponseType::Str(element))
+}
+
 pub fn move_vec_to_set(set: &mut HashSet<String>, vec: &Vec<String>) {
     for val in vec {
         set.insert(val.clone());
@@ -180,12 +461,75 @@ pub fn get_set_items(store: &DataStore, key: &String) -> Result<ResponseType, Co
     if wrong_type_error(store, key, SET_CODE) {
         return Err(CommandError::WrongType);
     }
-    if let Some(set) = store.set_db.get(key) {
+    if let Some(set) = store.get_set(key) {
         return Ok(ResponseType::Set(set.clone()));
     }
     Ok(ResponseType::Set(HashSet::new()))
 }
 
+/// Obtiene los sets de las claves, en el mismo orden. Una clave que no
+/// existe se toma como un set vacío.
+///
+/// # Errors
+///
+/// `WrongType` si alguna clave guarda un valor que no es un set.
+fn sets_for<'a>(
+    store: &'a DataStore,
+    keys: &[String],
+) -> Result<Vec<Option<&'a HashSet<String>>>, CommandError> {
+    keys.iter()
+        .map(|key| {
+            if wrong_type_error(store, key, SET_CODE) {
+                return Err(CommandError::WrongType);
+            }
+            Ok(store.get_set(key))
+        })
+        .collect()
+}
+
+/// Elementos presentes en todos los sets (SINTER).
+pub fn sinter(store: &DataStore, keys: &[String]) -> Result<ResponseType, CommandError> {
+    let sets = sets_for(store, keys)?;
+    let Some(sets) = sets.into_iter().collect::<Option<Vec<_>>>() else {
+        return Ok(ResponseType::Set(HashSet::new()));
+    };
+    let Some((first, rest)) = sets.split_first() else {
+        return Ok(ResponseType::Set(HashSet::new()));
+    };
+    let intersection = first
+        .iter()
+        .filter(|member| rest.iter().all(|set| set.contains(*member)))
+        .cloned()
+        .collect();
+    Ok(ResponseType::Set(intersection))
+}
+
+/// Elementos presentes en alguno de los sets (SUNION).
+pub fn sunion(store: &DataStore, keys: &[String]) -> Result<ResponseType, CommandError> {
+    let union = sets_for(store, keys)?
+        .into_iter()
+        .flatten()
+        .flatten()
+        .cloned()
+        .collect();
+    Ok(ResponseType::Set(union))
+}
+
+/// Elementos del primer set que no están en ninguno de los siguientes
+/// (SDIFF).
+pub fn sdiff(store: &DataStore, keys: &[String]) -> Result<ResponseType, CommandError> {
+    let sets = sets_for(store, keys)?;
+    let Some((Some(base), rest)) = sets.split_first() else {
+        return Ok(ResponseType::Set(HashSet::new()));
+    };
+    let difference = base
+        .iter()
+        .filter(|member| !rest.iter().flatten().any(|set| set.contains(*member)))
+        .cloned()
+        .collect();
+    Ok(ResponseType::Set(difference))
+}
+
 pub fn get_set_data(
     store: &DataStore,
     key: &String,
@@ -194,10 +538,8 @@ pub fn get_set_data(
     if wrong_type_error(store, key, SET_CODE) {
         return Err(CommandError::WrongType);
     }
-    if let Some(set) = store.set_db.get(key) {
-        if set.contains(val) {
-            return Ok(ResponseType::Int(1));
-        }
+    if store.get_set(key).is_some_and(|set| set.contains(val)) {
+        return Ok(ResponseType::Int(1));
     }
     Ok(ResponseType::Int(0))
 }
@@ -259,8 +601,11 @@ pub fn string_slice(
     if wrong_type_error(store, key, STR_CODE) {
         return Err(CommandError::WrongType);
     }
-    if let Some(value) = store.string_db.get(key) {
-        let len = value.len() as i64;
+    if let Some(value) = store.get(key) {
+        // Los índices son de bytes, como en Redis: se corta sobre los bytes
+        // para no entrar en pánico en medio de un carácter multibyte.
+        let bytes = value.as_bytes();
+        let len = bytes.len() as i64;
         let floor = if *start < 0 { len + *start } else { *start };
         let roof = if *end < 0 { len + *end } else { *end };
 
@@ -271,9 +616,11 @@ pub fn string_slice(
         let floor = floor.max(0) as usize;
 
         let aux = roof.min(len) as usize;
-        let roof = if aux == value.len() { aux } else { aux + 1 };
+        let roof = if aux == bytes.len() { aux } else { aux + 1 };
 
-        return Ok(ResponseType::Str(value[floor..roof].to_string()));
+        return Ok(ResponseType::Str(bytes_to_string(
+            bytes[floor..roof].to_vec(),
+        )));
     }
     Ok(ResponseType::Str("".to_string()))
 }
@@ -284,30 +631,77 @@ pub fn get_len(
     op: &Command,
 ) -> Result<ResponseType, CommandError> {
     if let Command::Llen(_) = op {
-        if let Some(list) = store.list_db.get(key) {
+        if let Some(list) = store.get_list(key) {
             return Ok(ResponseType::Int(list.len() as i64));
         }
     }
     if let Command::Scard(_) = op {
-        if let Some(set) = store.set_db.get(key) {
+        if let Some(set) = store.get_set(key) {
             return Ok(ResponseType::Int(set.len() as i64));
         }
     }
     if let Command::Strlen(_) = op {
-        if let Some(s) = store.string_db.get(key) {
+        if let Some(s) = store.get(key) {
             return Ok(ResponseType::Int(s.len() as i64));
         }
     }
 
-    if store.list_db.contains_key(key)
-        || store.set_db.contains_key(key)
-        || store.string_db.contains_key(key)
-    {
+    if store.contains_key(key) && !store.is_expired(key) {
         return Err(CommandError::WrongType);
     }
     Ok(ResponseType::Int(0))
 }
 
+/// Sobrescribe el string a partir del byte `offset` (SETRANGE),
+/// completando con `\x00` si el offset supera su largo. Modifica el valor
+/// en el lugar, por lo que conserva el TTL. Con un valor vacío no crea la
+/// clave.
+///
+/// # Returns
+///
+/// La longitud en bytes del string resultante.
+///
+/// # Errors
+///
+/// Error si el string resultante superaría los 512MB.
+pub fn set_range(
+    store: &mut DataStore,
+    key: &String,
+    offset: usize,
+    value: &str,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, STR_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let current = store.get(key).map(String::as_bytes).unwrap_or_default();
+    if value.is_empty() {
+        return Ok(ResponseType::Int(current.len() as i64));
+    }
+    let end = offset
+        .checked_add(value.len())
+        .filter(|end| *end <= MAX_STRING_SIZE)
+        .ok_or_else(|| CommandError::Custom(ERR_STRING_TOO_LONG.to_string()))?;
+
+    let mut bytes = current.to_vec();
+    if bytes.len() < end {
+        bytes.resize(end, 0);
+    }
+    bytes[offset..end].copy_from_slice(value.as_bytes());
+
+    let new_str = bytes_to_string(bytes);
+    let len = new_str.len();
+    store.string_db.insert(key.clone(), new_str);
+    Ok(ResponseType::Int(len as i64))
+}
+
+/// Convierte bytes a `String`. Si el corte partió un carácter multibyte,
+/// los bytes inválidos se reemplazan por `U+FFFD`, ya que los strings del
+/// store tienen que ser UTF-8 válido.
+fn bytes_to_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+}
+
 pub fn str_concat(
     store: &mut DataStore,
     key: &String,
@@ -328,12 +722,88 @@ pub fn str_concat(
     Ok(ResponseType::Int(res as i64))
 }
 
+/// Incrementa en uno el entero guardado en la clave. Modifica el valor en
+/// el lugar, por lo que conserva el TTL.
+pub fn incr(store: &mut DataStore, key: &String) -> Result<ResponseType, CommandError> {
+    update_integer(store, key, |value| value.checked_add(1))
+}
+
+/// Decrementa en uno el entero guardado en la clave. Modifica el valor en
+/// el lugar, por lo que conserva el TTL.
+pub fn decr(store: &mut DataStore, key: &String) -> Result<ResponseType, CommandError> {
+    update_integer(store, key, |value| value.checked_sub(1))
+}
+
+/// Suma `increment` al entero guardado en la clave.
+pub fn incr_by(
+    store: &mut DataStore,
+    key: &String,
+    increment: i64,
+) -> Result<ResponseType, CommandError> {
+    update_integer(store, key, |value| value.checked_add(increment))
+}
+
+/// Resta `decrement` al entero guardado en la clave.
+pub fn decr_by(
+    store: &mut DataStore,
+    key: &String,
+    decrement: i64,
+) -> Result<ResponseType, CommandError> {
+    update_integer(store, key, |value| value.checked_sub(decrement))
+}
+
+/// Reemplaza el entero guardado en la clave, que vale 0 si no existe, por
+/// el resultado de `operation`. Si la operación desborda un i64 la clave no
+/// cambia.
+fn update_integer(
+    store: &mut DataStore,
+    key: &String,
+    operation: impl Fn(i64) -> Option<i64>,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, STR_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let current = match store.string_db.get(key) {
+        Some(value) => value
+            .parse::<i64>()
+            .map_err(|_| CommandError::Custom(ERR_NOT_INTEGER.to_string()))?,
+        None => 0,
+    };
+    let result =
+        operation(current).ok_or_else(|| CommandError::Custom(ERR_OVERFLOW.to_string()))?;
+    store.string_db.insert(key.clone(), result.to_string());
+    Ok(ResponseType::Int(result))
+}
+
+/// Guarda el valor nuevo y devuelve el anterior en una sola operación
+/// (GETSET). El TTL de la clave se descarta como en SET.
+///
+/// # Returns
+///
+/// El valor anterior, o nil si la clave no existía.
+pub fn get_set(
+    store: &mut DataStore,
+    key: &String,
+    value: &str,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, STR_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let previous = store.get(key).cloned();
+    store.string_db.insert(key.clone(), value.to_string());
+    Ok(match previous {
+        Some(previous) => ResponseType::Str(previous),
+        None => ResponseType::Null(None),
+    })
+}
+
 pub fn retrieve_delete(store: &mut DataStore, key: &String) -> Result<ResponseType, CommandError> {
     if wrong_type_error(store, key, STR_CODE) {
         return Err(CommandError::WrongType);
     }
 
     if let Some(value) = store.string_db.remove(key) {
+        store.set_ttl(key, None);
         return Ok(ResponseType::Str(value));
     }
     Ok(ResponseType::Null(None))
@@ -358,6 +828,16 @@ pub fn bulk_delete(
         if let Some(_) = store.set_db.remove(key) {
             deleted_keys += 1;
         }
+        if store.stream_db.remove(key).is_some() {
+            deleted_keys += 1;
+        }
+        if store.hash_db.remove(key).is_some() {
+            deleted_keys += 1;
+        }
+        if store.zset_db.remove(key).is_some() {
+            deleted_keys += 1;
+        }
+        store.set_ttl(key, None);
     }
     Ok(ResponseType::Int(deleted_keys))
 }
@@ -416,74 +896,1102 @@ pub fn set_pop(
     Ok(ResponseType::Null(None))
 }
 
-pub fn backup_ds(
+/// Elementos aleatorios de un set, sin modificarlo (SRANDMEMBER).
+///
+/// Sin `count` devuelve un elemento, o nulo si el set no existe. Con `count`
+/// positivo devuelve hasta `count` elementos distintos; con `count` negativo
+/// devuelve exactamente `|count|` elementos, que se pueden repetir.
+pub fn set_random_members(
     store: &DataStore,
-    settings: NodeConfigs,
-    logger: Arc<AofLogger>,
-    bg_task: bool,
+    key: &String,
+    count: Option<i64>,
 ) -> Result<ResponseType, CommandError> {
-    if !bg_task {
-        return match create_dump(store, &settings.get_snapshot_dst()) {
-            Ok(_) => {
-                logger.log_notice("DB saved on disk".to_string());
-                Ok(ResponseType::Str("OK".to_string()))
-            }
-            Err(_) => Err(CommandError::IoError(
-                "ERROR when saving the database".to_string(),
-            )),
-        };
+    if wrong_type_error(store, key, SET_CODE) {
+        return Err(CommandError::WrongType);
     }
-    let store_aux = store.clone();
-    let logger_aux = logger.clone();
-    logger.log_notice("DB background thread started".to_string());
-    let _ = thread::Builder::new()
-        .name("Background save".to_string())
-        .spawn(
-            move || match create_dump(&store_aux, &settings.get_snapshot_dst()) {
-                Ok(_) => {
-                    logger_aux.log_notice("DB saved on disk".to_string());
-                }
-                Err(_) => {
-                    logger_aux.log_event("ERROR when saving the database".to_string());
-                }
-            },
-        );
-    Ok(ResponseType::Str("Background saving started".to_string()))
+    let members: Vec<&String> = store
+        .set_db
+        .get(key)
+        .map(|set| set.iter().collect())
+        .unwrap_or_default();
+    let mut rng = rand::thread_rng();
+
+    let Some(count) = count else {
+        return Ok(match members.choose(&mut rng) {
+            Some(member) => ResponseType::Str((*member).clone()),
+            None => ResponseType::Null(None),
+        });
+    };
+    let chosen = if count >= 0 {
+        members
+            .choose_multiple(&mut rng, count as usize)
+            .map(|member| (*member).clone())
+            .collect()
+    } else if members.is_empty() {
+        vec![]
+    } else {
+        (0..count.unsigned_abs())
+            .filter_map(|_| members.choose(&mut rng))
+            .map(|member| (*member).clone())
+            .collect()
+    };
+    Ok(ResponseType::List(chosen))
 }
 
-pub fn subscribe(
-    client_id: String,
-    channel_id: String,
-    pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
-    client_sender: &Sender<RespMessage>,
+/// Elimina elementos de un set.
+///
+/// Como en `SPOP`, el set vacío se conserva.
+///
+/// # Arguments
+///
+/// * `store` Store de hashmaps
+/// * `key` Clave del set
+/// * `members` Elementos a eliminar
+pub fn set_remove(
+    store: &mut DataStore,
+    key: &String,
+    members: &[String],
 ) -> Result<ResponseType, CommandError> {
-    let (response_sender, response_receiver) = mpsc::channel::<String>();
-    let command = Command::Subscribe(channel_id);
-
-    pubsub_sender
-        .send((client_id, command, response_sender, client_sender.clone()))
-        .map_err(|e| {
-            CommandError::Custom(format!("Failed to send subscribe instruction: {}", e))
-        })?;
-
-    let response = response_receiver.recv().map_err(|e| {
-        CommandError::Custom(format!("Failed to receive subscribe response: {}", e))
-    })?;
-
-    if response.is_empty() {
-        Ok(ResponseType::Str("Successfully subscribed.".to_string()))
-    } else {
-        Err(CommandError::Custom(response))
+    if wrong_type_error(store, key, SET_CODE) {
+        return Err(CommandError::WrongType);
     }
+    let removed = match store.set_db.get_mut(key) {
+        Some(set) => members.iter().filter(|member| set.remove(*member)).count(),
+        None => 0,
+    };
+    Ok(ResponseType::Int(removed as i64))
 }
 
-pub fn unsubscribe(
-    client_id: String,
-    channel_id: String,
-    pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
+/// Asigna los campos del hash, creándolo si no existe (HSET).
+///
+/// # Returns
+///
+/// Cantidad de campos que no existían.
+pub fn hset(
+    store: &mut DataStore,
+    key: &String,
+    fields: &[(String, String)],
 ) -> Result<ResponseType, CommandError> {
-    let (response_sender, response_receiver) = mpsc::channel::<String>();
-    let command = Command::Unsubscribe(channel_id);
+    if wrong_type_error(store, key, HASH_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let hash = store.hash_db.entry(key.clone()).or_default();
+    let created = fields
+        .iter()
+        .filter(|(field, value)| hash.insert(field.clone(), value.clone()).is_none())
+        .count();
+    Ok(ResponseType::Int(created as i64))
+}
+
+/// Obtiene el valor de un campo del hash (HGET).
+///
+/// # Returns
+///
+/// El valor, o nulo si el hash o el campo no existen.
+pub fn hget(store: &DataStore, key: &String, field: &str) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, HASH_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    match store.get_hash(key).and_then(|hash| hash.get(field)) {
+        Some(value) => Ok(ResponseType::Str(value.clone())),
+        None => Ok(ResponseType::Null(None)),
+    }
+}
+
+/// Elimina campos del hash (HDEL).
+///
+/// # Returns
+///
+/// Cantidad de campos eliminados.
+pub fn hdel(
+    store: &mut DataStore,
+    key: &String,
+    fields: &[String],
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, HASH_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let removed = match store.hash_db.get_mut(key) {
+        Some(hash) => fields
+            .iter()
+            .filter(|field| hash.remove(*field).is_some())
+            .count(),
+        None => 0,
+    };
+    Ok(ResponseType::Int(removed as i64))
+}
+
+/// Obtiene todos los campos del hash seguidos de su valor (HGETALL),
+/// ordenados por campo.
+pub fn hgetall(store: &DataStore, key: &String) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, HASH_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let mut fields: Vec<_> = store.get_hash(key).into_iter().flatten().collect();
+    fields.sort();
+    let flattened = fields
+        .into_iter()
+        .flat_map(|(field, value)| [field.clone(), value.clone()])
+        .collect();
+    Ok(ResponseType::List(flattened))
+}
+
+/// Agrega miembros al sorted set con el geohash de su posición como
+/// score (GEOADD). Las coordenadas se validan todas antes de escribir,
+/// para no dejar el comando aplicado a medias.
+///
+/// # Returns
+///
+/// Cantidad de miembros nuevos.
+pub fn geoadd(
+    store: &mut DataStore,
+    key: &String,
+    members: &[(f64, f64, String)],
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, ZSET_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let scores = members
+        .iter()
+        .map(|(lon, lat, member)| geo::encode(*lon, *lat).map(|hash| (member, hash as f64)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| CommandError::Custom(e.to_string()))?;
+    let zset = store.zset_db.entry(key.clone()).or_default();
+    let added = scores
+        .into_iter()
+        .filter(|(member, score)| zset.insert((*member).clone(), *score).is_none())
+        .count();
+    Ok(ResponseType::Int(added as i64))
+}
+
+/// Obtiene la posición de los miembros del sorted set (GEOPOS): el centro
+/// de la celda de su geohash.
+///
+/// # Returns
+///
+/// Por cada miembro su longitud y latitud, `None` si no existe.
+pub fn geopos(
+    store: &DataStore,
+    key: &String,
+    members: &[String],
+) -> Result<Vec<Option<(f64, f64)>>, CommandError> {
+    if wrong_type_error(store, key, ZSET_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let zset = store.get_zset(key);
+    Ok(members
+        .iter()
+        .map(|member| {
+            zset.and_then(|zset| zset.get(member))
+                .map(|score| geo::decode(*score as u64))
+        })
+        .collect())
+}
+
+/// Obtiene la distancia entre dos miembros del sorted set (GEODIST).
+///
+/// # Returns
+///
+/// La distancia en la unidad pedida con 4 decimales, o nulo si falta
+/// alguno de los miembros.
+pub fn geodist(
+    store: &DataStore,
+    key: &String,
+    from: &str,
+    to: &str,
+    unit: GeoUnit,
+) -> Result<ResponseType, CommandError> {
+    match geopos(store, key, &[from.to_string(), to.to_string()])?.as_slice() {
+        [Some((from_lon, from_lat)), Some((to_lon, to_lat))] => {
+            let meters = geo::distance(*from_lon, *from_lat, *to_lon, *to_lat);
+            Ok(ResponseType::Str(format!(
+                "{:.4}",
+                unit.from_meters(meters)
+            )))
+        }
+        _ => Ok(ResponseType::Null(None)),
+    }
+}
+
+/// Miembro encontrado por GEOSEARCH: su nombre, su distancia al origen y
+/// su longitud y latitud.
+pub type GeoMatch = (String, f64, (f64, f64));
+
+/// Busca los miembros del sorted set dentro del área (GEOSEARCH).
+///
+/// # Returns
+///
+/// Los miembros encontrados, con la distancia en la unidad de la
+/// consulta, en el orden pedido.
+pub fn geosearch(
+    store: &DataStore,
+    key: &String,
+    options: &GeoSearchOptions,
+) -> Result<Vec<GeoMatch>, CommandError> {
+    if wrong_type_error(store, key, ZSET_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let Some(zset) = store.get_zset(key) else {
+        return Ok(vec![]);
+    };
+    let (lon, lat) = match &options.origin {
+        GeoOrigin::Member(member) => zset
+            .get(member)
+            .map(|score| geo::decode(*score as u64))
+            .ok_or_else(|| {
+                CommandError::Custom("ERR could not decode requested zset member".to_string())
+            })?,
+        GeoOrigin::LonLat(lon, lat) => {
+            geo::encode(*lon, *lat).map_err(|e| CommandError::Custom(e.to_string()))?;
+            (*lon, *lat)
+        }
+    };
+    let unit = options.unit;
+    let mut found = match options.shape {
+        GeoShape::Radius(radius) => geo::search_by_radius(zset, lon, lat, unit.to_meters(radius)),
+        GeoShape::Box(width, height) => geo::search_by_box(
+            zset,
+            lon,
+            lat,
+            unit.to_meters(width),
+            unit.to_meters(height),
+        ),
+    };
+    if options.desc {
+        found.reverse();
+    }
+    if let Some(count) = options.count {
+        found.truncate(count);
+    }
+    Ok(found
+        .into_iter()
+        .filter_map(|(member, meters)| {
+            let position = geo::decode(*zset.get(&member)? as u64);
+            Some((member, unit.from_meters(meters), position))
+        })
+        .collect())
+}
+
+/// Busca el valor de la clave externa que resulta de reemplazar
+/// el primer `*` del patrón por el elemento.
+fn lookup_pattern(store: &DataStore, pattern: &str, element: &str) -> Option<String> {
+    store
+        .string_db
+        .get(&pattern.replacen('*', element, 1))
+        .cloned()
+}
+
+/// Ordena los elementos de una lista o set.
+///
+/// # Arguments
+///
+/// * `store` Store de hashmaps
+/// * `key` Clave de la lista o set a ordenar
+/// * `alpha` Ordena lexicográficamente en vez de numéricamente
+/// * `desc` Ordena de forma descendente
+/// * `limit` Ventana `(offset, count)` de elementos a devolver
+/// * `by_pattern` Patrón de claves externas usadas como peso; sin `*` no se ordena
+/// * `get_patterns` Patrones de claves externas a devolver por elemento (`#` es el elemento)
+///
+/// # Returns
+///
+/// Elementos ordenados. Las claves externas inexistentes de `GET` se devuelven
+/// como `None`, que se responde como bulk nulo.
+pub fn sort(
+    store: &DataStore,
+    key: &String,
+    alpha: bool,
+    desc: bool,
+    limit: &Option<(i64, i64)>,
+    by_pattern: &Option<String>,
+    get_patterns: &Vec<String>,
+) -> Result<Vec<Option<String>>, CommandError> {
+    if store.string_db.contains_key(key)
+        || store.stream_db.contains_key(key)
+        || store.hash_db.contains_key(key)
+        || store.zset_db.contains_key(key)
+    {
+        return Err(CommandError::WrongType);
+    }
+    let mut elements: Vec<String> = if let Some(list) = store.get_list(key) {
+        list.clone()
+    } else if let Some(set) = store.get_set(key) {
+        set.iter().cloned().collect()
+    } else {
+        vec![]
+    };
+
+    let weight = |element: &String| match by_pattern {
+        Some(pattern) => lookup_pattern(store, pattern, element),
+        None => Some(element.clone()),
+    };
+    let dont_sort = by_pattern.as_ref().is_some_and(|p| !p.contains('*'));
+
+    if !dont_sort {
+        if alpha {
+            let mut weighted: Vec<(Option<String>, String)> =
+                elements.into_iter().map(|e| (weight(&e), e)).collect();
+            weighted.sort();
+            elements = weighted.into_iter().map(|(_, e)| e).collect();
+        } else {
+            let mut scored = Vec::with_capacity(elements.len());
+            for element in elements {
+                let score = match weight(&element) {
+                    Some(w) => w
+                        .trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|score| !score.is_nan())
+                        .ok_or_else(|| CommandError::Custom(ERR_SORT_NOT_DOUBLE.to_string()))?,
+                    None => 0.0,
+                };
+                scored.push((score, element));
+            }
+            scored.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+            elements = scored.into_iter().map(|(_, e)| e).collect();
+        }
+        if desc {
+            elements.reverse();
+        }
+    }
+
+    let (offset, count) = limit.unwrap_or((0, -1));
+    let window = elements.into_iter().skip(offset.max(0) as usize);
+    let window: Vec<String> = if count < 0 {
+        window.collect()
+    } else {
+        window.take(count as usize).collect()
+    };
+
+    if get_patterns.is_empty() {
+        return Ok(window.into_iter().map(Some).collect());
+    }
+    let mut res = vec![];
+    for element in &window {
+        for pattern in get_patterns {
+            if pattern == "#" {
+                res.push(Some(element.clone()));
+            } else {
+                res.push(lookup_pattern(store, pattern, element));
+            }
+        }
+    }
+    Ok(res)
+}
+
+/// Lee el HyperLogLog guardado en la clave, si existe.
+fn load_hll(store: &DataStore, key: &String) -> Result<Option<HyperLogLog>, CommandError> {
+    if wrong_type_error(store, key, STR_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    match store.get(key) {
+        Some(value) => HyperLogLog::from_string_value(value)
+            .map(Some)
+            .map_err(|e| CommandError::Custom(e.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Agrega elementos al HyperLogLog de la clave, creándolo si no existe (PFADD).
+///
+/// # Returns
+///
+/// 1 si la estimación pudo haber cambiado (o se creó la clave), 0 si no.
+pub fn pfadd(
+    store: &mut DataStore,
+    key: &String,
+    elements: &Vec<String>,
+) -> Result<ResponseType, CommandError> {
+    let (mut hll, mut changed) = match load_hll(store, key)? {
+        Some(hll) => (hll, false),
+        None => (HyperLogLog::new(), true),
+    };
+    for element in elements {
+        changed |= hll.add(element);
+    }
+    if changed {
+        store.string_db.insert(key.clone(), hll.to_string_value());
+    }
+    Ok(ResponseType::Int(changed as i64))
+}
+
+/// Estima la cantidad de elementos distintos en la unión de los
+/// HyperLogLog de las claves. Las claves inexistentes cuentan como vacías (PFCOUNT).
+pub fn pfcount(store: &DataStore, keys: &Vec<String>) -> Result<ResponseType, CommandError> {
+    let mut union = HyperLogLog::new();
+    for key in keys {
+        if let Some(hll) = load_hll(store, key)? {
+            union.merge(&hll);
+        }
+    }
+    Ok(ResponseType::Int(union.count() as i64))
+}
+
+/// Une los HyperLogLog de las claves origen en la clave destino,
+/// incluyendo el contenido previo del destino (PFMERGE).
+pub fn pfmerge(
+    store: &mut DataStore,
+    destination: &String,
+    sources: &Vec<String>,
+) -> Result<ResponseType, CommandError> {
+    let mut union = load_hll(store, destination)?.unwrap_or_default();
+    for key in sources {
+        if let Some(hll) = load_hll(store, key)? {
+            union.merge(&hll);
+        }
+    }
+    store
+        .string_db
+        .insert(destination.clone(), union.to_string_value());
+    Ok(ResponseType::Str("OK".to_string()))
+}
+
+/// Agrega las entradas del stream a la respuesta con el formato
+/// `[id, cantidad de pares, campo, valor, ...]` por entrada.
+fn push_stream_entries<'a, I>(res: &mut Vec<String>, entries: I)
+where
+    I: IntoIterator<Item = (&'a StreamId, &'a StreamFields)>,
+{
+    for (id, fields) in entries {
+        res.push(id.to_string());
+        res.push(fields.len().to_string());
+        for (field, value) in fields {
+            res.push(field.clone());
+            res.push(value.clone());
+        }
+    }
+}
+
+/// Agrega una entrada al stream de la clave, creándolo si no existe (XADD).
+///
+/// # Arguments
+///
+/// * `store` Store de hashmaps
+/// * `key` Clave del stream
+/// * `id` ID explícito, o `None` para generarlo a partir del reloj
+/// * `fields` Pares campo/valor de la entrada
+///
+/// # Returns
+///
+/// El ID de la entrada, o error si el ID explícito no es mayor al último del stream.
+pub fn xadd(
+    store: &mut DataStore,
+    key: &String,
+    id: &Option<StreamId>,
+    fields: &StreamFields,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, STREAM_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0);
+    let result = match store.stream_db.get_mut(key) {
+        Some(stream) => stream.add(*id, fields.clone(), now_ms),
+        None => {
+            let mut stream = Stream::new();
+            let result = stream.add(*id, fields.clone(), now_ms);
+            // Un XADD rechazado no crea la clave
+            if result.is_ok() {
+                store.stream_db.insert(key.clone(), stream);
+            }
+            result
+        }
+    };
+    match result {
+        Ok(id) => Ok(ResponseType::Str(id.to_string())),
+        Err(e) => Err(CommandError::Custom(e.to_string())),
+    }
+}
+
+/// Cantidad de entradas del stream (XLEN).
+pub fn xlen(store: &DataStore, key: &String) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, STREAM_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let len = store.get_stream(key).map_or(0, |stream| stream.len());
+    Ok(ResponseType::Int(len as i64))
+}
+
+/// Entradas del stream con ID entre `start` y `end`, en orden de ID (XRANGE).
+///
+/// # Returns
+///
+/// Lista plana con el formato `[id, cantidad de pares, campo, valor, ...]`
+/// por entrada.
+pub fn xrange(
+    store: &DataStore,
+    key: &String,
+    start: StreamId,
+    end: StreamId,
+    count: Option<usize>,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, STREAM_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let mut res = vec![];
+    if let Some(stream) = store.get_stream(key) {
+        push_stream_entries(&mut res, stream.range(start, end, count));
+    }
+    Ok(ResponseType::List(res))
+}
+
+/// Lee las entradas posteriores al ID indicado de cada stream (XREAD).
+///
+/// No bloquea: si ningún stream tiene entradas nuevas responde nulo.
+///
+/// # Returns
+///
+/// Por cada stream con entradas nuevas, `[clave, cantidad de entradas]`
+/// seguido de las entradas con el formato de XRANGE.
+pub fn xread(
+    store: &DataStore,
+    count: Option<usize>,
+    streams: &Vec<(String, StreamId)>,
+) -> Result<ResponseType, CommandError> {
+    let mut res = vec![];
+    for (key, id) in streams {
+        if wrong_type_error(store, key, STREAM_CODE) {
+            return Err(CommandError::WrongType);
+        }
+        let Some(stream) = store.get_stream(key) else {
+            continue;
+        };
+        let entries = stream.read_after(*id, count);
+        if !entries.is_empty() {
+            res.push(key.clone());
+            res.push(entries.len().to_string());
+            push_stream_entries(&mut res, entries);
+        }
+    }
+    if res.is_empty() {
+        return Ok(ResponseType::Null(None));
+    }
+    Ok(ResponseType::List(res))
+}
+
+/// Convierte un error de grupo en la respuesta correspondiente,
+/// indicando la clave y el grupo si el grupo no existe.
+fn group_error(e: StreamError, key: &str, group: &str) -> CommandError {
+    match e {
+        StreamError::NoGroup => CommandError::Custom(format!(
+            "NOGROUP No such key '{}' or consumer group '{}'",
+            key, group
+        )),
+        e => CommandError::Custom(e.to_string()),
+    }
+}
+
+/// Crea un grupo de consumidores sobre el stream (XGROUP CREATE).
+///
+/// # Arguments
+///
+/// * `store` Store de hashmaps
+/// * `key` Clave del stream
+/// * `group` Nombre del grupo
+/// * `id` Último ID considerado entregado, `None` para el último del stream
+/// * `mkstream` Crea el stream vacío si no existe
+///
+/// # Returns
+///
+/// `OK`, o error si el grupo ya existe o el stream no existe sin MKSTREAM.
+pub fn xgroup_create(
+    store: &mut DataStore,
+    key: &String,
+    group: &str,
+    id: &Option<StreamId>,
+    mkstream: bool,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, STREAM_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    if !store.stream_db.contains_key(key) {
+        if !mkstream {
+            return Err(CommandError::Custom(ERR_XGROUP_NO_KEY.to_string()));
+        }
+        store.stream_db.insert(key.clone(), Stream::new());
+    }
+    if let Some(stream) = store.stream_db.get_mut(key) {
+        let last_delivered = id.unwrap_or(stream.last_id());
+        stream
+            .create_group(group, last_delivered)
+            .map_err(|e| group_error(e, key, group))?;
+    }
+    Ok(ResponseType::Str("OK".to_string()))
+}
+
+/// Entrega entradas de los streams a un consumidor del grupo (XREADGROUP).
+///
+/// Las entradas entregadas quedan pendientes en el grupo hasta su XACK.
+///
+/// # Returns
+///
+/// Mismo formato que XREAD, o nulo si no se entregó ninguna entrada.
+pub fn xreadgroup(
+    store: &mut DataStore,
+    group: &str,
+    consumer: &str,
+    count: Option<usize>,
+    streams: &Vec<(String, Option<StreamId>)>,
+) -> Result<ResponseType, CommandError> {
+    let mut res = vec![];
+    for (key, after) in streams {
+        if wrong_type_error(store, key, STREAM_CODE) {
+            return Err(CommandError::WrongType);
+        }
+        let stream = store
+            .stream_db
+            .get_mut(key)
+            .ok_or_else(|| group_error(StreamError::NoGroup, key, group))?;
+        let delivered = stream
+            .read_group(group, consumer, *after, count)
+            .map_err(|e| group_error(e, key, group))?;
+        if !delivered.is_empty() {
+            res.push(key.clone());
+            res.push(delivered.len().to_string());
+            push_stream_entries(&mut res, delivered.iter().map(|(id, fields)| (id, fields)));
+        }
+    }
+    if res.is_empty() {
+        return Ok(ResponseType::Null(None));
+    }
+    Ok(ResponseType::List(res))
+}
+
+/// Confirma entradas entregadas al grupo, quitándolas de sus pendientes (XACK).
+///
+/// # Returns
+///
+/// Cantidad de entradas que estaban pendientes.
+pub fn xack(
+    store: &mut DataStore,
+    key: &String,
+    group: &str,
+    ids: &[StreamId],
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, STREAM_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let acked = match store.stream_db.get_mut(key) {
+        Some(stream) => stream
+            .ack(group, ids)
+            .map_err(|e| group_error(e, key, group))?,
+        None => 0,
+    };
+    Ok(ResponseType::Int(acked as i64))
+}
+
+/// Resume las entradas pendientes de confirmación del grupo (XPENDING).
+///
+/// # Returns
+///
+/// Lista plana con la cantidad de pendientes y, si hay alguna, el menor
+/// y el mayor ID pendiente seguidos de cada consumidor con su cantidad.
+pub fn xpending(
+    store: &DataStore,
+    key: &String,
+    group: &str,
+) -> Result<ResponseType, CommandError> {
+    if wrong_type_error(store, key, STREAM_CODE) {
+        return Err(CommandError::WrongType);
+    }
+    let summary = store
+        .stream_db
+        .get(key)
+        .ok_or(StreamError::NoGroup)
+        .and_then(|stream| stream.pending_summary(group))
+        .map_err(|e| group_error(e, key, group))?;
+
+    let mut res = vec![summary.count.to_string()];
+    if let Some((first, last)) = summary.bounds {
+        res.push(first.to_string());
+        res.push(last.to_string());
+        for (consumer, pending) in summary.consumers {
+            res.push(consumer);
+            res.push(pending.to_string());
+        }
+    }
+    Ok(ResponseType::List(res))
+}
+
+/// Recorre las claves de forma incremental (SCAN).
+///
+/// Las claves se visitan en orden creciente de su posición en el índice de
+/// SCAN del store, que no depende de la capacidad de los HashMap, y el
+/// cursor es la posición de la primera clave todavía no visitada. Así,
+/// toda clave presente durante el recorrido completo se devuelve al menos
+/// una vez, aunque entre llamadas se agreguen o borren claves, y cada
+/// llamada cuesta lo que visita y no el tamaño del store.
+///
+/// # Arguments
+///
+/// * `store` Store de hashmaps
+/// * `cursor` Cursor devuelto por la llamada anterior, 0 para empezar
+/// * `pattern` Patrón glob que deben cumplir las claves devueltas
+/// * `count` Cantidad aproximada de claves a visitar
+///
+/// # Returns
+///
+/// Lista cuyo primer elemento es el próximo cursor (0 al terminar)
+/// seguido de las claves encontradas.
+pub fn scan(
+    store: &DataStore,
+    cursor: u64,
+    pattern: &Option<String>,
+    count: usize,
+) -> Result<ResponseType, CommandError> {
+    let (next_cursor, visited) = store.scan(cursor, count);
+    let mut res = vec![next_cursor.to_string()];
+    res.extend(
+        visited
+            .into_iter()
+            .filter(|key| pattern.as_ref().is_none_or(|p| glob_match(p, key)))
+            .cloned(),
+    );
+    Ok(ResponseType::List(res))
+}
+
+/// Lista todas las claves que cumplen el patrón glob (KEYS). A
+/// diferencia de SCAN recorre el store completo en una sola llamada.
+///
+/// # Returns
+///
+/// Las claves encontradas ordenadas, sin las que ya vencieron.
+pub fn keys(store: &DataStore, pattern: &str) -> Result<ResponseType, CommandError> {
+    let mut found: Vec<String> = store
+        .keys()
+        .filter(|key| !store.is_expired(key) && glob_match(pattern, key))
+        .cloned()
+        .collect();
+    found.sort_unstable();
+    Ok(ResponseType::List(found))
+}
+
+/// Renombra la clave (RENAME), pisando el destino aunque guarde otro
+/// tipo. El TTL de la clave de origen pasa al destino.
+///
+/// # Errors
+///
+/// `ERR no such key` si la clave de origen no existe.
+pub fn rename(
+    store: &mut DataStore,
+    source: &str,
+    destination: &str,
+) -> Result<ResponseType, CommandError> {
+    if !store.rename(source, destination) {
+        return Err(CommandError::Custom(ERR_NO_SUCH_KEY.to_string()));
+    }
+    Ok(ResponseType::Str("OK".to_string()))
+}
+
+/// Renombra la clave sólo si el destino no existe (RENAMENX).
+///
+/// # Returns
+///
+/// 1 si se renombró y 0 si el destino ya existía.
+///
+/// # Errors
+///
+/// `ERR no such key` si la clave de origen no existe.
+pub fn renamenx(
+    store: &mut DataStore,
+    source: &str,
+    destination: &str,
+) -> Result<ResponseType, CommandError> {
+    if !store.contains_key(source) || store.is_expired(source) {
+        return Err(CommandError::Custom(ERR_NO_SUCH_KEY.to_string()));
+    }
+    if store.contains_key(destination) && !store.is_expired(destination) {
+        return Ok(ResponseType::Int(0));
+    }
+    store.rename(source, destination);
+    Ok(ResponseType::Int(1))
+}
+
+/// Cuenta las claves de la base sin importar su tipo (DBSIZE). Las
+/// claves vencidas que todavía no se purgaron no se cuentan.
+pub fn dbsize(store: &DataStore) -> Result<ResponseType, CommandError> {
+    let count = store.keys().filter(|key| !store.is_expired(key)).count();
+    Ok(ResponseType::Int(count as i64))
+}
+
+/// Borra todas las claves de la base (FLUSHDB).
+pub fn flushdb(store: &mut DataStore) -> Result<ResponseType, CommandError> {
+    store.clear();
+    Ok(ResponseType::Str("OK".to_string()))
+}
+
+/// Cuenta cuántas de las claves existen (EXISTS). Una clave repetida se
+/// cuenta cada vez que aparece y una vencida cuenta como ausente.
+pub fn exists(store: &DataStore, keys: &[String]) -> Result<ResponseType, CommandError> {
+    let count = keys
+        .iter()
+        .filter(|key| store.contains_key(key) && !store.is_expired(key))
+        .count();
+    Ok(ResponseType::Int(count as i64))
+}
+
+/// Actualiza el último acceso de las claves que existen (TOUCH). Como en
+/// EXISTS, una clave repetida se cuenta cada vez y una vencida no existe.
+pub fn touch(store: &mut DataStore, keys: &[String]) -> Result<ResponseType, CommandError> {
+    if keys.is_empty() {
+        return Err(CommandError::WrongNumArgs);
+    }
+    let count = keys
+        .iter()
+        .filter(|key| !store.is_expired(key) && store.touch(key))
+        .count();
+    Ok(ResponseType::Int(count as i64))
+}
+
+/// Fija el vencimiento de la clave a `seconds` segundos de ahora (EXPIRE).
+/// Con `0` la clave se borra en el momento, como en Redis.
+///
+/// # Returns
+///
+/// 1 si la clave existe, 0 si no.
+pub fn expire(
+    store: &mut DataStore,
+    key: &str,
+    seconds: u64,
+) -> Result<ResponseType, CommandError> {
+    if !store.contains_key(key) {
+        return Ok(ResponseType::Int(0));
+    }
+    if seconds == 0 {
+        store.string_db.remove(key);
+        store.list_db.remove(key);
+        store.set_db.remove(key);
+        store.stream_db.remove(key);
+        store.hash_db.remove(key);
+        store.zset_db.remove(key);
+        store.access_times.remove(key);
+        store.access_counts.remove(key);
+        store.set_ttl(key, None);
+    } else {
+        store.set_ttl(key, Some(Duration::from_secs(seconds)));
+    }
+    Ok(ResponseType::Int(1))
+}
+
+/// Fija el vencimiento de la clave en `unix_ms`, milisegundos desde epoch
+/// (PEXPIREAT). Si el instante ya pasó, la clave se borra.
+///
+/// # Returns
+///
+/// 1 si la clave existe, 0 si no.
+pub fn pexpireat(
+    store: &mut DataStore,
+    key: &str,
+    unix_ms: u64,
+) -> Result<ResponseType, CommandError> {
+    if !store.contains_key(key) {
+        return Ok(ResponseType::Int(0));
+    }
+    store.set_expiry_at(key, unix_ms);
+    store.expire_if_due(key);
+    Ok(ResponseType::Int(1))
+}
+
+/// Quita el vencimiento de la clave (PERSIST).
+///
+/// # Returns
+///
+/// 1 si se quitó un vencimiento, 0 si la clave no vencía o no existe.
+pub fn persist(store: &mut DataStore, key: &str) -> Result<ResponseType, CommandError> {
+    let removed = store.contains_key(key) && store.clear_expiry(key);
+    Ok(ResponseType::Int(removed as i64))
+}
+
+/// Segundos de vida que le quedan a la clave (TTL), redondeados.
+///
+/// # Returns
+///
+/// -2 si la clave no existe, -1 si no vence.
+pub fn ttl(store: &DataStore, key: &str) -> Result<ResponseType, CommandError> {
+    if !store.contains_key(key) {
+        return Ok(ResponseType::Int(-2));
+    }
+    match store.remaining_ttl(key) {
+        Some(remaining) => Ok(ResponseType::Int(
+            ((remaining.as_millis() + 500) / 1000) as i64,
+        )),
+        None => Ok(ResponseType::Int(-1)),
+    }
+}
+
+/// Tipo del valor guardado en la clave (TYPE).
+///
+/// # Returns
+///
+/// `string`, `list`, `set`, `stream` o `none` si la clave no existe.
+pub fn key_type(store: &DataStore, key: &str) -> Result<ResponseType, CommandError> {
+    Ok(ResponseType::Str(store.type_of(key).to_string()))
+}
+
+/// Serializa el valor de la clave (DUMP).
+///
+/// # Returns
+///
+/// El payload en hexadecimal, o nulo si la clave no existe.
+pub fn dump(store: &DataStore, key: &str) -> Result<ResponseType, CommandError> {
+    match dump_value(store, key) {
+        Some(payload) => Ok(ResponseType::Str(to_hex(&payload))),
+        None => Ok(ResponseType::Null(None)),
+    }
+}
+
+/// Obtiene el encoding con el que se guarda el valor de la clave
+/// (OBJECT ENCODING).
+///
+/// # Returns
+///
+/// El nombre del encoding, o error si la clave no existe.
+pub fn object_encoding(store: &DataStore, key: &str) -> Result<ResponseType, CommandError> {
+    match store.encoding(key) {
+        Some(encoding) => Ok(ResponseType::Str(encoding.to_string())),
+        None => Err(CommandError::Custom(ERR_NO_SUCH_KEY.to_string())),
+    }
+}
+
+/// Crea la clave a partir de un payload generado por DUMP (RESTORE).
+///
+/// # Arguments
+///
+/// * `store` Store de hashmaps
+/// * `key` Clave a crear
+/// * `ttl` Tiempo de vida en milisegundos, 0 si no expira
+/// * `payload` Payload en hexadecimal
+/// * `replace` Reemplaza la clave si ya existe, aunque sea de otro tipo
+///
+/// El TTL se convierte en un vencimiento absoluto a partir de la
+/// restauración. Para mover una clave sin volverla persistente se
+/// restaura con su TTL restante en el origen (`DataStore::remaining_ttl`);
+/// si la clave vence en el origen antes de leerse su DUMP, ya fue borrada
+/// y no hay nada que mover.
+///
+/// # Returns
+///
+/// `OK`, o `BUSYKEY` si la clave existe y no se pidió reemplazarla.
+pub fn restore(
+    store: &mut DataStore,
+    key: &str,
+    ttl: &i64,
+    payload: &str,
+    replace: &bool,
+) -> Result<ResponseType, CommandError> {
+    if *ttl < 0 {
+        return Err(CommandError::Custom(ERR_INVALID_TTL.to_string()));
+    }
+    let value = from_hex(payload)
+        .and_then(|bytes| restore_value(&bytes))
+        .map_err(|e| CommandError::Custom(e.to_string()))?;
+
+    if store.contains_key(key) {
+        if !*replace {
+            return Err(CommandError::Custom(ERR_BUSYKEY.to_string()));
+        }
+        store.string_db.remove(key);
+        store.list_db.remove(key);
+        store.set_db.remove(key);
+        store.stream_db.remove(key);
+        store.hash_db.remove(key);
+        store.zset_db.remove(key);
+    }
+    match value {
+        DumpValue::Str(value) => {
+            store.string_db.insert(key.to_string(), value);
+        }
+        DumpValue::List(list) => {
+            store.list_db.insert(key.to_string(), list);
+        }
+        DumpValue::Set(set) => {
+            store.set_db.insert(key.to_string(), set);
+        }
+        DumpValue::Hash(hash) => {
+            store.hash_db.insert(key.to_string(), hash);
+        }
+        DumpValue::Stream(stream) => {
+            store.stream_db.insert(key.to_string(), stream);
+        }
+        DumpValue::Zset(zset) => {
+            store.zset_db.insert(key.to_string(), zset);
+        }
+    }
+    let ttl = (*ttl > 0).then(|| Duration::from_millis(*ttl as u64));
+    store.set_ttl(key, ttl);
+    Ok(ResponseType::Str("OK".to_string()))
+}
+
+pub fn backup_ds(
+    store: &DataStore,
+    settings: NodeConfigs,
+    logger: Arc<AofLogger>,
+    bg_task: bool,
+) -> Result<ResponseType, CommandError> {
+    if !bg_task {
+        return match create_dump(store, &settings.get_snapshot_dst()) {
+            Ok(_) => {
+                logger.log_notice("DB saved on disk".to_string());
+                Ok(ResponseType::Str("OK".to_string()))
+            }
+            Err(_) => Err(CommandError::IoError(
+                "ERROR when saving the database".to_string(),
+            )),
+        };
+    }
+    let store_aux = store.clone();
+    let logger_aux = logger.clone();
+    logger.log_notice("DB background thread started".to_string());
+    let _ = thread::Builder::new()
+        .name("Background save".to_string())
+        .spawn(
+            move || match create_dump(&store_aux, &settings.get_snapshot_dst()) {
+                Ok(_) => {
+                    logger_aux.log_notice("DB saved on disk".to_string());
+                }
+                Err(_) => {
+                    logger_aux.log_event("ERROR when saving the database".to_string());
+                }
+            },
+        );
+    Ok(ResponseType::Str("Background saving started".to_string()))
+}
+
+pub fn subscribe(
+    client_id: String,
+    channel_id: String,
+    pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
+    client_sender: &Sender<RespMessage>,
+) -> Result<ResponseType, CommandError> {
+    let (response_sender, response_receiver) = mpsc::channel::<String>();
+    let command = Command::Subscribe(channel_id);
+
+    pubsub_sender
+        .send((client_id, command, response_sender, client_sender.clone()))
+        .map_err(|e| {
+            CommandError::Custom(format!("Failed to send subscribe instruction: {}", e))
+        })?;
+
+    let response = response_receiver.recv().map_err(|e| {
+        CommandError::Custom(format!("Failed to receive subscribe response: {}", e))
+    })?;
+
+    if response.is_empty() {
+        Ok(ResponseType::Str("Successfully subscribed.".to_string()))
+    } else {
+        Err(CommandError::Custom(response))
+    }
+}
+
+pub fn unsubscribe(
+    client_id: String,
+    channel_id: String,
+    pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
+) -> Result<ResponseType, CommandError> {
+    let (response_sender, response_receiver) = mpsc::channel::<String>();
+    let command = Command::Unsubscribe(channel_id);
 
     let (_dummy_sender, _dummy_receiver) = std::sync::mpsc::channel();
     pubsub_sender
@@ -503,6 +2011,81 @@ pub fn unsubscribe(
     }
 }
 
+/// Suscribe a un cliente a los canales que cumplen alguno de los patrones.
+///
+/// # Arguments
+///
+/// * `client_id` - ID del cliente
+/// * `patterns` - Patrones glob de canales
+/// * `pubsub_sender` - Sender al gestor de canales
+/// * `client_sender` - Sender por el que el cliente recibe las publicaciones
+///
+/// # Returns
+///
+/// `Result<ResponseType, CommandError>` - Confirmación de la suscripción
+pub fn psubscribe(
+    client_id: String,
+    patterns: Vec<String>,
+    pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
+    client_sender: &Sender<RespMessage>,
+) -> Result<ResponseType, CommandError> {
+    let (response_sender, response_receiver) = mpsc::channel::<String>();
+    let command = Command::PSubscribe(patterns);
+
+    pubsub_sender
+        .send((client_id, command, response_sender, client_sender.clone()))
+        .map_err(|e| {
+            CommandError::Custom(format!("Failed to send psubscribe instruction: {}", e))
+        })?;
+
+    let response = response_receiver.recv().map_err(|e| {
+        CommandError::Custom(format!("Failed to receive psubscribe response: {}", e))
+    })?;
+
+    if response.is_empty() {
+        Ok(ResponseType::Str("Successfully subscribed.".to_string()))
+    } else {
+        Err(CommandError::Custom(response))
+    }
+}
+
+/// Desuscribe a un cliente de los patrones dados, o de todos si no hay.
+///
+/// # Arguments
+///
+/// * `client_id` - ID del cliente
+/// * `patterns` - Patrones a dejar
+/// * `pubsub_sender` - Sender al gestor de canales
+///
+/// # Returns
+///
+/// `Result<ResponseType, CommandError>` - Confirmación de la desuscripción
+pub fn punsubscribe(
+    client_id: String,
+    patterns: Vec<String>,
+    pubsub_sender: &Sender<(String, Command, Sender<String>, Sender<RespMessage>)>,
+) -> Result<ResponseType, CommandError> {
+    let (response_sender, response_receiver) = mpsc::channel::<String>();
+    let command = Command::PUnsubscribe(patterns);
+
+    let (_dummy_sender, _dummy_receiver) = std::sync::mpsc::channel();
+    pubsub_sender
+        .send((client_id, command, response_sender, _dummy_sender))
+        .map_err(|e| {
+            CommandError::Custom(format!("Failed to send punsubscribe instruction: {}", e))
+        })?;
+
+    let response = response_receiver.recv().map_err(|e| {
+        CommandError::Custom(format!("Failed to receive punsubscribe response: {}", e))
+    })?;
+
+    if response.is_empty() {
+        Ok(ResponseType::Str("Successfully unsubscribed".to_string()))
+    } else {
+        Err(CommandError::Custom(response))
+    }
+}
+
 pub fn publish(
     client_id: String,
     channel_id: String,
@@ -537,59 +2120,168 @@ pub fn send_first_ping(ip: &String, settings: NodeConfigs) -> Result<ResponseTyp
 }
 
 /// Devuelve los slots y los nodos que los contienen.
+///
+/// Los rangos se devuelven ordenados por su primer slot, cada uno con su
+/// master seguido de sus réplicas. Los rangos vacíos no se listan.
 pub fn return_cluster_slots_data(
     node_data_lock: &Arc<RwLock<NodeData>>,
     known_nodes_lock: &Arc<RwLock<HashMap<NodeId, KnownNode>>>,
 ) -> Result<ResponseType, CommandError> {
-    let mut map: HashMap<SlotRange, Vec<Vec<String>>> = HashMap::new();
-    let node_data = node_data_lock.read().unwrap();
-    let slots = node_data.get_slots();
-    let myself = vec![
-        node_data.get_addr().ip().to_string(),
-        node_data.get_addr().port().to_string(),
-        node_data.get_id().to_string(),
-        "MASTER".to_string(),
-    ];
-    map.insert(slots, vec![myself]);
-
-    let known_nodes = known_nodes_lock.read().unwrap();
-    for (id, node) in known_nodes.iter() {
-        let slots = node.get_slots();
-        let role = if node.is_master() {
-            "MASTER".to_string()
-        } else {
-            "SLAVE".to_string()
-        };
-        if !map.contains_key(&slots) {
-            let addr = node.get_addr();
-            let data = vec![
-                addr.ip().to_string(),
-                (addr.port() - 10000).to_string(),
-                id.to_string(),
-                role,
-            ];
-            map.insert(slots, vec![data]);
-        } else {
-            let data = map.get_mut(&slots).unwrap();
-            let addr = node.get_addr();
-            data.push(vec![
+    let node_data = node_data_lock
+        .read()
+        .map_err(|e| CommandError::Internal(e.to_string()))?;
+    let known_nodes = known_nodes_lock
+        .read()
+        .map_err(|e| CommandError::Internal(e.to_string()))?;
+
+    // (id, master, slots, [ip, puerto, id, rol]) de cada nodo
+    let mut nodes = vec![];
+    let addr = node_data.get_addr();
+    let is_slave = node_data.get_flags().is_set(SLAVE);
+    nodes.push((
+        node_data.get_id(),
+        node_data.get_master_id().filter(|_| is_slave),
+        node_data.get_slots(),
+        vec![
+            addr.ip().to_string(),
+            addr.port().to_string(),
+            node_data.get_id(),
+            if is_slave { "SLAVE" } else { "MASTER" }.to_string(),
+        ],
+    ));
+    let mut known: Vec<_> = known_nodes.iter().collect();
+    known.sort_by(|a, b| a.0.cmp(b.0));
+    for (id, node) in known {
+        let addr = node.get_addr();
+        nodes.push((
+            id.clone(),
+            node.get_master_id().filter(|_| node.is_slave()).cloned(),
+            node.get_slots(),
+            vec![
                 addr.ip().to_string(),
-                (addr.port() - 10000).to_string(),
-                id.to_string(),
-                role,
-            ]);
+                addr.port().saturating_sub(NODAL_COMMS_PORT).to_string(),
+                id.clone(),
+                if node.is_master() { "MASTER" } else { "SLAVE" }.to_string(),
+            ],
+        ));
+    }
+
+    let mut ranges: BTreeMap<SlotRange, Vec<Vec<String>>> = BTreeMap::new();
+    let mut master_ranges: HashMap<NodeId, SlotRange> = HashMap::new();
+    for (id, master, slots, data) in nodes.iter() {
+        if master.is_none() && !is_empty_slot_range(*slots) {
+            ranges.entry(*slots).or_default().push(data.clone());
+            master_ranges.insert(id.clone(), *slots);
         }
     }
-    // Lo aplana a la forma [0, 5460, 0.0.0.0, 30001, id, SLAVE, localhost, 30004, id, MASTER, 215.2.1.1, 30002, id, SLAVE, 5461, 16834, ...]
-    let mut res = vec![];
-    for (key, nodes) in map.iter() {
-        res.push(key.0.to_string());
-        res.push(key.1.to_string());
-        for node in nodes.iter() {
-            for value in node.iter() {
-                res.push(value.to_string());
-            }
+    for (_, master, _, data) in nodes {
+        let range = master.and_then(|master| master_ranges.get(&master));
+        if let Some(range_nodes) = range.and_then(|range| ranges.get_mut(range)) {
+            range_nodes.push(data);
         }
     }
+
+    // Lo aplana a la forma [0, 5460, 0.0.0.0, 30001, id, MASTER, 0.0.0.0, 30004, id, SLAVE, 5461, 10922, ...]
+    let mut res = vec![];
+    for (range, nodes) in ranges {
+        res.push(range.0.to_string());
+        res.push(range.1.to_string());
+        res.extend(nodes.into_iter().flatten());
+    }
     Ok(ResponseType::List(res))
 }
+
+/// Rango de slots en el formato de `CLUSTER NODES`: `inicio-fin`, o un solo
+/// número si el rango tiene un slot. Los rangos vacíos no se muestran.
+fn format_slot_range(slots: SlotRange) -> Option<String> {
+    match slots {
+        slots if is_empty_slot_range(slots) => None,
+        (start, end) if start == end => Some(start.to_string()),
+        (start, end) => Some(format!("{}-{}", start, end)),
+    }
+}
+
+/// Línea de `CLUSTER NODES` de un nodo, con `address` ya como
+/// `ip:puerto@puerto-bus`.
+fn format_cluster_node(
+    id: &str,
+    address: String,
+    flags: &NodeFlags,
+    master: Option<&NodeId>,
+    ping_pong: (TimeStamp, TimeStamp),
+    config_epoch: Epoch,
+    slots: SlotRange,
+) -> String {
+    let link_state = if flags.is_set(CONNECTED) || flags.is_set(ME) {
+        "connected"
+    } else {
+        "disconnected"
+    };
+    let mut line = format!(
+        "{} {} {} {} {} {} {} {}",
+        id,
+        address,
+        flags.cluster_nodes_format(),
+        master.map(String::as_str).unwrap_or("-"),
+        ping_pong.0.max(0),
+        ping_pong.1.max(0),
+        config_epoch,
+        link_state,
+    );
+    if let Some(range) = format_slot_range(slots).filter(|_| !flags.is_set(SLAVE)) {
+        line.push(' ');
+        line.push_str(&range);
+    }
+    line.push('\n');
+    line
+}
+
+/// Arma la respuesta de `CLUSTER NODES`: una línea por nodo con
+/// `id ip:puerto@puerto-bus flags master ping-enviado pong-recibido
+/// config-epoch estado-del-link slots`, como en Redis.
+///
+/// El nodo local va primero, marcado `myself`; los conocidos siguen
+/// ordenados por id. Las réplicas no listan slots.
+pub fn return_cluster_nodes_data(
+    node_data_lock: &Arc<RwLock<NodeData>>,
+    known_nodes_lock: &Arc<RwLock<HashMap<NodeId, KnownNode>>>,
+) -> Result<ResponseType, CommandError> {
+    let node_data = node_data_lock
+        .read()
+        .map_err(|e| CommandError::Internal(e.to_string()))?;
+    let known_nodes = known_nodes_lock
+        .read()
+        .map_err(|e| CommandError::Internal(e.to_string()))?;
+
+    let addr = node_data.get_addr();
+    let mut res = format_cluster_node(
+        &node_data.get_id(),
+        format!("{}@{}", addr, node_data.get_port()),
+        &node_data.get_flags(),
+        node_data.get_master_id().as_ref(),
+        (0, 0),
+        node_data.get_cepoch(),
+        node_data.get_slots(),
+    );
+
+    let mut nodes: Vec<_> = known_nodes.iter().collect();
+    nodes.sort_by(|a, b| a.0.cmp(b.0));
+    for (id, node) in nodes {
+        let addr = node.get_addr();
+        res.push_str(&format_cluster_node(
+            id,
+            format!(
+                "{}:{}@{}",
+                addr.ip(),
+                addr.port().saturating_sub(NODAL_COMMS_PORT),
+                addr.port()
+            ),
+            node.get_flags(),
+            node.get_master_id(),
+            (node.get_last_ping_time(), node.get_last_pong_time()),
+            node.get_config_epoch(),
+            node.get_slots(),
+        ));
+    }
+    Ok(ResponseType::Str(res))
+}
@@ -11,30 +11,50 @@
 //! - Parsing de enteros con manejo de errores
 //! - Soporte para todos los comandos Redis implementados
 
-use crate::command::types::Command;
+use crate::command::types::{
+    ClientSubcommand, Command, CommandSubcommand, DebugSubcommand, FailoverOptions, GeoOrigin,
+    GeoSearchOptions, GeoShape, InsertWhere, LatencySubcommand, ListEnd, ObjectSubcommand,
+    PauseMode, PubsubSubcommand, SetOptions,
+};
 use crate::network;
+use crate::storage::geo::GeoUnit;
+use crate::storage::stream::StreamId;
+use std::time::Duration;
+
+/// Cantidad de claves visitadas por SCAN si no se indica COUNT.
+const DEFAULT_SCAN_COUNT: usize = 10;
+/// Prefijo de las claves creadas por DEBUG POPULATE si no se indica otro.
+const DEFAULT_POPULATE_PREFIX: &str = "key";
 
 /// Errores específicos que pueden ocurrir durante el parsing de instrucciones.
 #[derive(Debug)]
 pub enum InstructionError {
     /// Número incorrecto de argumentos para el comando
     WrongArgumentCount(String),
-    /// Error al parsear un entero
-    ParseIntError(String),
+    /// Falta el argumento en la posición indicada
+    MissingArgument(String, usize),
+    /// Sobra el argumento en la posición indicada
+    TooManyArguments(String, usize),
+    /// Error al parsear el entero en la posición indicada
+    ParseIntError(String, usize),
     /// Comando desconocido
     UnknownCommand(String),
     /// Entero fuera del rango válido
     IntegerOutOfRange,
+    /// Argumento con un valor no admitido por el comando
+    InvalidArgument(String),
 }
 
 impl std::fmt::Display for InstructionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            InstructionError::WrongArgumentCount(cmd) => {
+            InstructionError::WrongArgumentCount(cmd)
+            | InstructionError::MissingArgument(cmd, _)
+            | InstructionError::TooManyArguments(cmd, _) => {
                 write!(f, "Wrong number of arguments for {} command", cmd)
             }
-            InstructionError::ParseIntError(context) => {
-                write!(f, "Invalid integer in {}", context)
+            InstructionError::ParseIntError(context, index) => {
+                write!(f, "Invalid integer in {} (argument {})", context, index)
             }
             InstructionError::UnknownCommand(cmd) => {
                 write!(f, "Unknown command: {}", cmd)
@@ -42,17 +62,74 @@ impl std::fmt::Display for InstructionError {
             InstructionError::IntegerOutOfRange => {
                 write!(f, "Integer out of range")
             }
+            InstructionError::InvalidArgument(context) => {
+                write!(f, "Invalid argument in {}", context)
+            }
         }
     }
 }
 
 impl std::error::Error for InstructionError {}
 
+impl InstructionError {
+    /// Posición y motivo del argumento que causó el error, si se conoce.
+    pub fn argument(&self) -> Option<ArgumentError> {
+        let (index, reason) = match self {
+            InstructionError::MissingArgument(_, index) => (*index, ArgumentReason::Missing),
+            InstructionError::TooManyArguments(_, index) => (*index, ArgumentReason::TooMany),
+            InstructionError::ParseIntError(_, index) => (*index, ArgumentReason::NotAnInteger),
+            _ => return None,
+        };
+        Some(ArgumentError { index, reason })
+    }
+
+    /// Indica si el error se debe a la cantidad de argumentos.
+    pub fn is_arity_error(&self) -> bool {
+        matches!(
+            self,
+            InstructionError::WrongArgumentCount(_)
+                | InstructionError::MissingArgument(..)
+                | InstructionError::TooManyArguments(..)
+        )
+    }
+}
+
+/// Motivo por el cual un argumento impide convertir la instrucción.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgumentReason {
+    /// Falta el argumento
+    Missing,
+    /// El argumento sobra
+    TooMany,
+    /// El argumento no es un entero válido
+    NotAnInteger,
+}
+
+impl std::fmt::Display for ArgumentReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgumentReason::Missing => write!(f, "missing"),
+            ArgumentReason::TooMany => write!(f, "too many"),
+            ArgumentReason::NotAnInteger => write!(f, "not an integer"),
+        }
+    }
+}
+
+/// Argumento que impidió convertir una instrucción a comando.
+///
+/// La posición `index` se cuenta desde 0 sobre los argumentos, sin incluir
+/// el nombre del comando.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArgumentError {
+    pub index: usize,
+    pub reason: ArgumentReason,
+}
+
 /// Estructura que representa una instrucción de comando.
 ///
 /// Una instrucción contiene el tipo de comando y sus argumentos como strings,
 /// que luego se convierten a comandos tipados.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Instruction {
     /// Tipo de instrucción (ej: "GET", "SET", etc.)
     pub instruction_type: String,
@@ -78,18 +155,310 @@ fn wrong_arg_count(cmd: &str) -> InstructionError {
 /// # Argumentos
 ///
 /// * `s` - String a parsear
+/// * `index` - Posición del argumento, para el mensaje de error
 /// * `context` - Contexto para el mensaje de error
 ///
 /// # Retorna
 ///
 /// `Result<i64, InstructionError>`
-fn parse_int(s: &str, context: &str) -> Result<i64, InstructionError> {
+fn parse_int(s: &str, index: usize, context: &str) -> Result<i64, InstructionError> {
     s.parse::<i64>()
-        .map_err(|_| InstructionError::ParseIntError(context.to_string()))?
+        .map_err(|_| InstructionError::ParseIntError(context.to_string(), index))?
         .try_into()
         .map_err(|_| InstructionError::IntegerOutOfRange)
 }
 
+/// Parsea un switch `ON`/`OFF` (sin distinguir mayúsculas).
+///
+/// # Argumentos
+///
+/// * `s` - String a parsear
+/// * `context` - Contexto para el mensaje de error
+///
+/// # Retorna
+///
+/// `Result<bool, InstructionError>`
+fn parse_switch(s: &str, context: &str) -> Result<bool, InstructionError> {
+    match s.to_uppercase().as_str() {
+        "ON" => Ok(true),
+        "OFF" => Ok(false),
+        _ => Err(InstructionError::InvalidArgument(context.to_string())),
+    }
+}
+
+/// Parsea un extremo de lista `LEFT`/`RIGHT` (sin distinguir mayúsculas).
+fn parse_list_end(s: &str, context: &str) -> Result<ListEnd, InstructionError> {
+    match s.to_uppercase().as_str() {
+        "LEFT" => Ok(ListEnd::Left),
+        "RIGHT" => Ok(ListEnd::Right),
+        _ => Err(InstructionError::InvalidArgument(context.to_string())),
+    }
+}
+
+/// Parsea el COUNT de los comandos de streams, que debe ser positivo.
+fn parse_stream_count(s: &str, index: usize, cmd: &str) -> Result<usize, InstructionError> {
+    match parse_int(s, index, &format!("COUNT for {}", cmd))? {
+        n if n >= 1 => Ok(n as usize),
+        _ => Err(InstructionError::InvalidArgument(cmd.to_string())),
+    }
+}
+
+/// Opciones y streams de XREAD y XREADGROUP.
+struct StreamRead<'a> {
+    count: Option<usize>,
+    block: Option<u64>,
+    keys: &'a [String],
+    ids: &'a [String],
+}
+
+/// Parsea `[COUNT n] [BLOCK ms] STREAMS k1 k2 ... id1 id2 ...`.
+///
+/// `offset` es la posición de `args` dentro de los argumentos del comando.
+fn parse_stream_read<'a>(
+    args: &'a [String],
+    offset: usize,
+    cmd: &str,
+) -> Result<StreamRead<'a>, InstructionError> {
+    let missing = |index: usize| InstructionError::MissingArgument(cmd.to_string(), offset + index);
+    let mut count = None;
+    let mut block = None;
+    let mut i = 0;
+    loop {
+        let option = args.get(i).ok_or_else(|| missing(i))?.to_uppercase();
+        if option == "STREAMS" {
+            break;
+        }
+        let value = args.get(i + 1).ok_or_else(|| missing(i + 1))?;
+        match option.as_str() {
+            "COUNT" => count = Some(parse_stream_count(value, offset + i + 1, cmd)?),
+            "BLOCK" => {
+                block = match parse_int(value, offset + i + 1, &format!("BLOCK for {}", cmd))? {
+                    ms if ms >= 0 => Some(ms as u64),
+                    _ => return Err(InstructionError::InvalidArgument(cmd.to_string())),
+                }
+            }
+            _ => return Err(InstructionError::InvalidArgument(cmd.to_string())),
+        }
+        i += 2;
+    }
+    // Las claves y los IDs vienen en dos mitades: k1 k2 ... id1 id2 ...
+    let rest = &args[i + 1..];
+    if rest.is_empty() || !rest.len().is_multiple_of(2) {
+        return Err(wrong_arg_count(cmd));
+    }
+    let (keys, ids) = rest.split_at(rest.len() / 2);
+    Ok(StreamRead {
+        count,
+        block,
+        keys,
+        ids,
+    })
+}
+
+/// Indica si el token es una opción de SET. Si el tercer argumento no lo
+/// es, el valor se toma como todos los argumentos que siguen a la clave.
+fn is_set_option(token: &str) -> bool {
+    matches!(token.to_uppercase().as_str(), "NX" | "XX" | "EX")
+}
+
+/// Parsea `[NX | XX] [EX seconds]`, en cualquier orden, a partir del
+/// tercer argumento de SET.
+fn parse_set_options(args: &[String]) -> Result<SetOptions, InstructionError> {
+    let mut options = SetOptions::default();
+    let mut i = 0;
+    while let Some(option) = args.get(i) {
+        match option.to_uppercase().as_str() {
+            "NX" => options.nx = true,
+            "XX" => options.xx = true,
+            "EX" => {
+                let value = args
+                    .get(i + 1)
+                    .ok_or_else(|| InstructionError::MissingArgument("SET".to_string(), i + 3))?;
+                options.ex = match parse_int(value, i + 3, "seconds for SET EX")? {
+                    seconds if seconds > 0 => Some(seconds as u64),
+                    _ => return Err(InstructionError::InvalidArgument("SET EX".to_string())),
+                };
+                i += 1;
+            }
+            _ => return Err(InstructionError::InvalidArgument("SET".to_string())),
+        }
+        i += 1;
+    }
+    if options.nx && options.xx {
+        return Err(InstructionError::InvalidArgument("SET NX XX".to_string()));
+    }
+    Ok(options)
+}
+
+/// Parsea un número de punto flotante finito.
+fn parse_float(s: &str, context: &str) -> Result<f64, InstructionError> {
+    s.parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| InstructionError::InvalidArgument(context.to_string()))
+}
+
+/// Parsea una unidad de distancia de los comandos GEO.
+fn parse_geo_unit(s: &str, context: &str) -> Result<GeoUnit, InstructionError> {
+    GeoUnit::parse(s).ok_or_else(|| InstructionError::InvalidArgument(context.to_string()))
+}
+
+/// Parsea las opciones de GEOSEARCH a partir del argumento que sigue a la
+/// clave. Se exige exactamente un origen (`FROMMEMBER` o `FROMLONLAT`) y
+/// un área (`BYRADIUS` o `BYBOX`).
+fn parse_geosearch(args: &[String]) -> Result<GeoSearchOptions, InstructionError> {
+    let invalid = || InstructionError::InvalidArgument("GEOSEARCH".to_string());
+    let missing = |index: usize| InstructionError::MissingArgument("GEOSEARCH".to_string(), index);
+    let mut origin = None;
+    let mut area = None;
+    let (mut desc, mut count, mut with_coord, mut with_dist) = (false, None, false, false);
+    let mut i = 0;
+    while let Some(option) = args.get(i) {
+        match option.to_uppercase().as_str() {
+            "FROMMEMBER" if origin.is_none() => {
+                let member = args.get(i + 1).ok_or_else(|| missing(i + 2))?;
+                origin = Some(GeoOrigin::Member(member.clone()));
+                i += 2;
+            }
+            "FROMLONLAT" if origin.is_none() => {
+                let (lon, lat) = match (args.get(i + 1), args.get(i + 2)) {
+                    (Some(lon), Some(lat)) => (lon, lat),
+                    _ => return Err(missing(args.len() + 1)),
+                };
+                origin = Some(GeoOrigin::LonLat(
+                    parse_float(lon, "GEOSEARCH longitude")?,
+                    parse_float(lat, "GEOSEARCH latitude")?,
+                ));
+                i += 3;
+            }
+            "BYRADIUS" if area.is_none() => {
+                let (radius, unit) = match (args.get(i + 1), args.get(i + 2)) {
+                    (Some(radius), Some(unit)) => (radius, unit),
+                    _ => return Err(missing(args.len() + 1)),
+                };
+                let radius = parse_float(radius, "GEOSEARCH radius")?;
+                if radius < 0.0 {
+                    return Err(invalid());
+                }
+                area = Some((GeoShape::Radius(radius), parse_geo_unit(unit, "GEOSEARCH")?));
+                i += 3;
+            }
+            "BYBOX" if area.is_none() => {
+                let (width, height, unit) =
+                    match (args.get(i + 1), args.get(i + 2), args.get(i + 3)) {
+                        (Some(width), Some(height), Some(unit)) => (width, height, unit),
+                        _ => return Err(missing(args.len() + 1)),
+                    };
+                let width = parse_float(width, "GEOSEARCH width")?;
+                let height = parse_float(height, "GEOSEARCH height")?;
+                if width < 0.0 || height < 0.0 {
+                    return Err(invalid());
+                }
+                area = Some((
+                    GeoShape::Box(width, height),
+                    parse_geo_unit(unit, "GEOSEARCH")?,
+                ));
+                i += 4;
+            }
+            "ASC" => {
+                desc = false;
+                i += 1;
+            }
+            "DESC" => {
+                desc = true;
+                i += 1;
+            }
+            "COUNT" => {
+                let value = args.get(i + 1).ok_or_else(|| missing(i + 2))?;
+                count = match parse_int(value, i + 2, "COUNT for GEOSEARCH")? {
+                    n if n >= 1 => Some(n as usize),
+                    _ => return Err(invalid()),
+                };
+                i += 2;
+            }
+            "WITHCOORD" => {
+                with_coord = true;
+                i += 1;
+            }
+            "WITHDIST" => {
+                with_dist = true;
+                i += 1;
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    let (Some(origin), Some((shape, unit))) = (origin, area) else {
+        return Err(invalid());
+    };
+    Ok(GeoSearchOptions {
+        origin,
+        shape,
+        unit,
+        desc,
+        count,
+        with_coord,
+        with_dist,
+    })
+}
+
+/// Error de FAILOVER por faltar el argumento en la posición `index`.
+fn missing_failover_argument(index: usize) -> InstructionError {
+    InstructionError::MissingArgument("FAILOVER".to_string(), index)
+}
+
+/// Parsea `[TO host port] [FORCE] [TIMEOUT ms] [ABORT]`, en cualquier orden.
+///
+/// `ABORT` no admite otras opciones y `FORCE` requiere `TO` y `TIMEOUT`.
+fn parse_failover(args: &[String]) -> Result<FailoverOptions, InstructionError> {
+    let mut options = FailoverOptions::default();
+    let mut i = 0;
+    while let Some(option) = args.get(i) {
+        match option.to_uppercase().as_str() {
+            "TO" => {
+                let (host, port) = match (args.get(i + 1), args.get(i + 2)) {
+                    (Some(host), Some(port)) => (host, port),
+                    _ => return Err(missing_failover_argument(args.len())),
+                };
+                let port = u16::try_from(parse_int(port, i + 2, "port for FAILOVER")?)
+                    .map_err(|_| InstructionError::IntegerOutOfRange)?;
+                options.to = Some((host.clone(), port));
+                i += 3;
+            }
+            "TIMEOUT" => {
+                let value = args
+                    .get(i + 1)
+                    .ok_or_else(|| missing_failover_argument(i + 1))?;
+                options.timeout = match parse_int(value, i + 1, "timeout for FAILOVER")? {
+                    ms if ms > 0 => Some(ms as u64),
+                    _ => return Err(InstructionError::InvalidArgument("FAILOVER".to_string())),
+                };
+                i += 2;
+            }
+            "FORCE" => {
+                options.force = true;
+                i += 1;
+            }
+            "ABORT" => {
+                options.abort = true;
+                i += 1;
+            }
+            _ => return Err(InstructionError::InvalidArgument("FAILOVER".to_string())),
+        }
+    }
+    let with_other_options = options.to.is_some() || options.timeout.is_some() || options.force;
+    if options.abort && with_other_options {
+        return Err(InstructionError::InvalidArgument(
+            "FAILOVER ABORT".to_string(),
+        ));
+    }
+    if options.force && (options.to.is_none() || options.timeout.is_none()) {
+        return Err(InstructionError::InvalidArgument(
+            "FAILOVER FORCE".to_string(),
+        ));
+    }
+    Ok(options)
+}
+
 impl Instruction {
     /// Crea una nueva instancia de Instruction.
     ///
@@ -108,6 +477,28 @@ impl Instruction {
         }
     }
 
+    /// Verifica que la cantidad de argumentos esté entre `min` y `max`
+    /// (sin tope si es `None`).
+    ///
+    /// # Retorna
+    ///
+    /// El error indica la posición del primer argumento que falta o sobra
+    fn check_arity(
+        &self,
+        cmd: &str,
+        min: usize,
+        max: Option<usize>,
+    ) -> Result<(), InstructionError> {
+        let len = self.arguments.len();
+        if len < min {
+            return Err(InstructionError::MissingArgument(cmd.to_string(), len));
+        }
+        match max {
+            Some(max) if len > max => Err(InstructionError::TooManyArguments(cmd.to_string(), max)),
+            _ => Ok(()),
+        }
+    }
+
     /// Convierte la instrucción a un comando tipado.
     ///
     /// Este método valida el número de argumentos y parsea los tipos
@@ -120,156 +511,319 @@ impl Instruction {
     /// # Errores
     ///
     /// * `WrongArgumentCount` - Número incorrecto de argumentos
+    /// * `MissingArgument` / `TooManyArguments` - Falta o sobra un argumento
     /// * `ParseIntError` - Error al parsear enteros
     /// * `UnknownCommand` - Comando no reconocido
     /// * `IntegerOutOfRange` - Entero fuera del rango válido
     pub fn to_command(&self) -> Result<Command, InstructionError> {
         match self.instruction_type.to_uppercase().as_str() {
             "APPEND" => {
-                if self.arguments.len() != 2 {
-                    return Err(wrong_arg_count("APPEND"));
-                }
+                self.check_arity("APPEND", 2, Some(2))?;
                 Ok(Command::Append(
                     self.arguments[0].clone(),
                     self.arguments[1].clone(),
                 ))
             }
             "DEL" => {
-                if self.arguments.is_empty() {
-                    return Err(wrong_arg_count("DEL"));
-                }
+                self.check_arity("DEL", 1, None)?;
                 Ok(Command::Del(self.arguments.clone()))
             }
             "ECHO" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("ECHO"));
-                }
+                self.check_arity("ECHO", 1, Some(1))?;
                 Ok(Command::Echo(self.arguments[0].clone()))
             }
             "SET" => {
-                if self.arguments.len() < 2 {
-                    return Err(wrong_arg_count("SET"));
-                }
+                self.check_arity("SET", 2, None)?;
 
                 let key = self.arguments[0].clone();
-                let value = self.arguments[1..].join(" ");
-
-                Ok(Command::Set(key, value))
+                let with_options = self
+                    .arguments
+                    .get(2)
+                    .is_some_and(|token| is_set_option(token));
+                if !with_options {
+                    let value = self.arguments[1..].join(" ");
+                    return Ok(Command::Set(key, value, SetOptions::default()));
+                }
+                let options = parse_set_options(&self.arguments[2..])?;
+                Ok(Command::Set(key, self.arguments[1].clone(), options))
+            }
+            "SETNX" => {
+                self.check_arity("SETNX", 2, Some(2))?;
+                Ok(Command::Setnx(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                ))
             }
             "GET" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("GET"));
-                }
+                self.check_arity("GET", 1, Some(1))?;
                 Ok(Command::Get(self.arguments[0].clone()))
             }
-            "GETDEL" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("GETDEL"));
+            "INCR" => {
+                self.check_arity("INCR", 1, Some(1))?;
+                Ok(Command::Incr(self.arguments[0].clone()))
+            }
+            "DECR" => {
+                self.check_arity("DECR", 1, Some(1))?;
+                Ok(Command::Decr(self.arguments[0].clone()))
+            }
+            "INCRBY" => {
+                self.check_arity("INCRBY", 2, Some(2))?;
+                let increment = parse_int(&self.arguments[1], 1, "increment for INCRBY")?;
+                Ok(Command::IncrBy(self.arguments[0].clone(), increment))
+            }
+            "DECRBY" => {
+                self.check_arity("DECRBY", 2, Some(2))?;
+                let decrement = parse_int(&self.arguments[1], 1, "decrement for DECRBY")?;
+                Ok(Command::DecrBy(self.arguments[0].clone(), decrement))
+            }
+            "MGET" => {
+                self.check_arity("MGET", 1, None)?;
+                Ok(Command::Mget(self.arguments.clone()))
+            }
+            "MSET" => {
+                if self.arguments.is_empty() || !self.arguments.len().is_multiple_of(2) {
+                    return Err(wrong_arg_count("MSET"));
                 }
+                let pairs = self
+                    .arguments
+                    .chunks(2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect();
+                Ok(Command::Mset(pairs))
+            }
+            "GETDEL" => {
+                self.check_arity("GETDEL", 1, Some(1))?;
                 Ok(Command::Getdel(self.arguments[0].clone()))
             }
+            "GETSET" => {
+                self.check_arity("GETSET", 2, Some(2))?;
+                Ok(Command::GetSet(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                ))
+            }
             "STRLEN" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("STRLEN"));
-                }
+                self.check_arity("STRLEN", 1, Some(1))?;
                 Ok(Command::Strlen(self.arguments[0].clone()))
             }
             "GETRANGE" => {
-                if self.arguments.len() != 3 {
-                    return Err(wrong_arg_count("GETRANGE"));
-                }
-                let start = parse_int(&self.arguments[1], "start index for GETRANGE")?;
-                let end = parse_int(&self.arguments[2], "end index for GETRANGE")?;
+                self.check_arity("GETRANGE", 3, Some(3))?;
+                let start = parse_int(&self.arguments[1], 1, "start index for GETRANGE")?;
+                let end = parse_int(&self.arguments[2], 2, "end index for GETRANGE")?;
                 Ok(Command::Getrange(self.arguments[0].clone(), start, end))
             }
             "SUBSTR" => {
-                if self.arguments.len() != 3 {
-                    return Err(wrong_arg_count("SUBSTR"));
-                }
-                let start = parse_int(&self.arguments[1], "start index for SUBSTR")?;
-                let end = parse_int(&self.arguments[2], "end index for SUBSTR")?;
+                self.check_arity("SUBSTR", 3, Some(3))?;
+                let start = parse_int(&self.arguments[1], 1, "start index for SUBSTR")?;
+                let end = parse_int(&self.arguments[2], 2, "end index for SUBSTR")?;
                 Ok(Command::Substr(self.arguments[0].clone(), start, end))
             }
+            "SETRANGE" => {
+                self.check_arity("SETRANGE", 3, Some(3))?;
+                let offset = parse_int(&self.arguments[1], 1, "offset for SETRANGE")?;
+                let offset = usize::try_from(offset)
+                    .map_err(|_| InstructionError::InvalidArgument("SETRANGE".to_string()))?;
+                Ok(Command::SetRange(
+                    self.arguments[0].clone(),
+                    offset,
+                    self.arguments[2].clone(),
+                ))
+            }
+            "PFADD" => {
+                self.check_arity("PFADD", 1, None)?;
+                Ok(Command::Pfadd(
+                    self.arguments[0].clone(),
+                    self.arguments[1..].to_vec(),
+                ))
+            }
+            "PFCOUNT" => {
+                self.check_arity("PFCOUNT", 1, None)?;
+                Ok(Command::Pfcount(self.arguments.clone()))
+            }
+            "PFMERGE" => {
+                self.check_arity("PFMERGE", 1, None)?;
+                Ok(Command::Pfmerge(
+                    self.arguments[0].clone(),
+                    self.arguments[1..].to_vec(),
+                ))
+            }
             "LLEN" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("LLEN"));
-                }
+                self.check_arity("LLEN", 1, Some(1))?;
                 Ok(Command::Llen(self.arguments[0].clone()))
             }
             "LPOP" => {
-                if self.arguments.len() != 2 {
-                    return Err(wrong_arg_count("LPOP"));
-                }
-                let amount = parse_int(&self.arguments[1], "amount for LPOP")?;
+                self.check_arity("LPOP", 2, Some(2))?;
+                let amount = parse_int(&self.arguments[1], 1, "amount for LPOP")?;
                 Ok(Command::Lpop(self.arguments[0].clone(), amount))
             }
             "RPOP" => {
-                if self.arguments.len() != 2 {
-                    return Err(wrong_arg_count("RPOP"));
-                }
-                let amount = parse_int(&self.arguments[1], "amount for RPOP")?;
+                self.check_arity("RPOP", 2, Some(2))?;
+                let amount = parse_int(&self.arguments[1], 1, "amount for RPOP")?;
                 Ok(Command::Rpop(self.arguments[0].clone(), amount))
             }
             "LPUSH" => {
-                if self.arguments.len() < 2 {
-                    return Err(wrong_arg_count("LPUSH"));
-                }
+                self.check_arity("LPUSH", 2, None)?;
                 Ok(Command::Lpush(
                     self.arguments[0].clone(),
                     self.arguments[1..].to_vec(),
                 ))
             }
             "RPUSH" => {
-                if self.arguments.len() < 2 {
-                    return Err(wrong_arg_count("RPUSH"));
-                }
+                self.check_arity("RPUSH", 2, None)?;
                 Ok(Command::Rpush(
                     self.arguments[0].clone(),
                     self.arguments[1..].to_vec(),
                 ))
             }
             "LRANGE" => {
-                if self.arguments.len() != 3 {
-                    return Err(wrong_arg_count("LRANGE"));
-                }
-                let start = parse_int(&self.arguments[1], "start index for LRANGE")?;
-                let end = parse_int(&self.arguments[2], "end index for LRANGE")?;
+                self.check_arity("LRANGE", 3, Some(3))?;
+                let start = parse_int(&self.arguments[1], 1, "start index for LRANGE")?;
+                let end = parse_int(&self.arguments[2], 2, "end index for LRANGE")?;
                 Ok(Command::Lrange(self.arguments[0].clone(), start, end))
             }
-            "SADD" => {
-                if self.arguments.len() < 2 {
-                    return Err(wrong_arg_count("SADD"));
+            "LINDEX" => {
+                self.check_arity("LINDEX", 2, Some(2))?;
+                let index = parse_int(&self.arguments[1], 1, "index for LINDEX")?;
+                Ok(Command::Lindex(self.arguments[0].clone(), index))
+            }
+            "LSET" => {
+                self.check_arity("LSET", 3, Some(3))?;
+                let index = parse_int(&self.arguments[1], 1, "index for LSET")?;
+                Ok(Command::Lset(
+                    self.arguments[0].clone(),
+                    index,
+                    self.arguments[2].clone(),
+                ))
+            }
+            "LREM" => {
+                self.check_arity("LREM", 3, Some(3))?;
+                let count = parse_int(&self.arguments[1], 1, "count for LREM")?;
+                Ok(Command::Lrem(
+                    self.arguments[0].clone(),
+                    count,
+                    self.arguments[2].clone(),
+                ))
+            }
+            "LINSERT" => {
+                self.check_arity("LINSERT", 4, Some(4))?;
+                let place = match self.arguments[1].to_uppercase().as_str() {
+                    "BEFORE" => InsertWhere::Before,
+                    "AFTER" => InsertWhere::After,
+                    _ => return Err(InstructionError::InvalidArgument("LINSERT".to_string())),
+                };
+                Ok(Command::Linsert(
+                    self.arguments[0].clone(),
+                    place,
+                    self.arguments[2].clone(),
+                    self.arguments[3].clone(),
+                ))
+            }
+            "LMOVE" => {
+                self.check_arity("LMOVE", 4, Some(4))?;
+                Ok(Command::LMove(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                    parse_list_end(&self.arguments[2], "LMOVE")?,
+                    parse_list_end(&self.arguments[3], "LMOVE")?,
+                ))
+            }
+            "RPOPLPUSH" => {
+                self.check_arity("RPOPLPUSH", 2, Some(2))?;
+                Ok(Command::LMove(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                    ListEnd::Right,
+                    ListEnd::Left,
+                ))
+            }
+            "SORT" => {
+                self.check_arity("SORT", 1, None)?;
+                let mut alpha = false;
+                let mut desc = false;
+                let mut limit = None;
+                let mut by_pattern = None;
+                let mut get_patterns = vec![];
+
+                let mut i = 1;
+                while i < self.arguments.len() {
+                    let remaining = self.arguments.len() - i - 1;
+                    match self.arguments[i].to_uppercase().as_str() {
+                        "ASC" => desc = false,
+                        "DESC" => desc = true,
+                        "ALPHA" => alpha = true,
+                        "LIMIT" if remaining >= 2 => {
+                            let offset =
+                                parse_int(&self.arguments[i + 1], i + 1, "LIMIT offset for SORT")?;
+                            let count =
+                                parse_int(&self.arguments[i + 2], i + 2, "LIMIT count for SORT")?;
+                            limit = Some((offset, count));
+                            i += 2;
+                        }
+                        "BY" if remaining >= 1 => {
+                            by_pattern = Some(self.arguments[i + 1].clone());
+                            i += 1;
+                        }
+                        "GET" if remaining >= 1 => {
+                            get_patterns.push(self.arguments[i + 1].clone());
+                            i += 1;
+                        }
+                        _ => return Err(InstructionError::InvalidArgument("SORT".to_string())),
+                    }
+                    i += 1;
                 }
+                Ok(Command::Sort(
+                    self.arguments[0].clone(),
+                    alpha,
+                    desc,
+                    limit,
+                    by_pattern,
+                    get_patterns,
+                ))
+            }
+            "SADD" => {
+                self.check_arity("SADD", 2, None)?;
                 Ok(Command::Sadd(
                     self.arguments[0].clone(),
                     self.arguments[1..].to_vec(),
                 ))
             }
             "SMEMBERS" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("SMEMBERS"));
-                }
+                self.check_arity("SMEMBERS", 1, Some(1))?;
                 Ok(Command::Smembers(self.arguments[0].clone()))
             }
+            "SINTER" => {
+                self.check_arity("SINTER", 1, None)?;
+                Ok(Command::Sinter(self.arguments.clone()))
+            }
+            "SUNION" => {
+                self.check_arity("SUNION", 1, None)?;
+                Ok(Command::Sunion(self.arguments.clone()))
+            }
+            "SDIFF" => {
+                self.check_arity("SDIFF", 1, None)?;
+                Ok(Command::Sdiff(self.arguments.clone()))
+            }
             "SCARD" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("SCARD"));
-                }
+                self.check_arity("SCARD", 1, Some(1))?;
                 Ok(Command::Scard(self.arguments[0].clone()))
             }
             "SISMEMBER" => {
-                if self.arguments.len() != 2 {
-                    return Err(wrong_arg_count("SISMEMBER"));
-                }
+                self.check_arity("SISMEMBER", 2, Some(2))?;
                 Ok(Command::Sismember(
                     self.arguments[0].clone(),
                     self.arguments[1].clone(),
                 ))
             }
+            "SRANDMEMBER" => {
+                self.check_arity("SRANDMEMBER", 1, Some(2))?;
+                let count = match self.arguments.get(1) {
+                    Some(count) => Some(parse_int(count, 1, "count for SRANDMEMBER")?),
+                    None => None,
+                };
+                Ok(Command::SRandMember(self.arguments[0].clone(), count))
+            }
             "SMOVE" => {
-                if self.arguments.len() != 3 {
-                    return Err(wrong_arg_count("SMOVE"));
-                }
+                self.check_arity("SMOVE", 3, Some(3))?;
                 Ok(Command::SMove(
                     self.arguments[0].clone(),
                     self.arguments[1].clone(),
@@ -277,96 +831,678 @@ impl Instruction {
                 ))
             }
             "SPOP" => {
-                if self.arguments.len() != 2 {
-                    return Err(wrong_arg_count("SPOP"));
-                }
-                let amount = parse_int(&self.arguments[1], "amount for SPOP")?;
+                self.check_arity("SPOP", 2, Some(2))?;
+                let amount = parse_int(&self.arguments[1], 1, "amount for SPOP")?;
                 Ok(Command::Spop(self.arguments[0].clone(), amount))
             }
-            "BGSAVE" => {
-                if !self.arguments.is_empty() {
-                    return Err(wrong_arg_count("BGSAVE"));
-                }
-                Ok(Command::BgSave)
+            "SREM" => {
+                self.check_arity("SREM", 2, None)?;
+                Ok(Command::Srem(
+                    self.arguments[0].clone(),
+                    self.arguments[1..].to_vec(),
+                ))
             }
-            "SAVE" => {
-                if !self.arguments.is_empty() {
-                    return Err(wrong_arg_count("SAVE"));
+            "HSET" => {
+                if self.arguments.len() < 3 || self.arguments.len().is_multiple_of(2) {
+                    return Err(wrong_arg_count("HSET"));
                 }
-                Ok(Command::Save)
+                let fields = self.arguments[1..]
+                    .chunks(2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect();
+                Ok(Command::Hset(self.arguments[0].clone(), fields))
             }
-            "SUBSCRIBE" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("SUBSCRIBE"));
+            "HGET" => {
+                self.check_arity("HGET", 2, Some(2))?;
+                Ok(Command::Hget(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                ))
+            }
+            "HDEL" => {
+                self.check_arity("HDEL", 2, None)?;
+                Ok(Command::Hdel(
+                    self.arguments[0].clone(),
+                    self.arguments[1..].to_vec(),
+                ))
+            }
+            "HGETALL" => {
+                self.check_arity("HGETALL", 1, Some(1))?;
+                Ok(Command::Hgetall(self.arguments[0].clone()))
+            }
+            "GEOADD" => {
+                if self.arguments.len() < 4 || !(self.arguments.len() - 1).is_multiple_of(3) {
+                    return Err(wrong_arg_count("GEOADD"));
                 }
-                Ok(Command::Subscribe(self.arguments[0].clone()))
+                let mut members = Vec::new();
+                for triple in self.arguments[1..].chunks(3) {
+                    members.push((
+                        parse_float(&triple[0], "GEOADD longitude")?,
+                        parse_float(&triple[1], "GEOADD latitude")?,
+                        triple[2].clone(),
+                    ));
+                }
+                Ok(Command::Geoadd(self.arguments[0].clone(), members))
             }
-            "UNSUBSCRIBE" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("UNSUBSCRIBE"));
+            "GEOPOS" => {
+                self.check_arity("GEOPOS", 1, None)?;
+                Ok(Command::Geopos(
+                    self.arguments[0].clone(),
+                    self.arguments[1..].to_vec(),
+                ))
+            }
+            "GEODIST" => {
+                self.check_arity("GEODIST", 3, Some(4))?;
+                let unit = match self.arguments.get(3) {
+                    Some(unit) => parse_geo_unit(unit, "GEODIST")?,
+                    None => GeoUnit::M,
+                };
+                Ok(Command::Geodist(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                    self.arguments[2].clone(),
+                    unit,
+                ))
+            }
+            "GEOSEARCH" => {
+                self.check_arity("GEOSEARCH", 5, None)?;
+                Ok(Command::Geosearch(
+                    self.arguments[0].clone(),
+                    parse_geosearch(&self.arguments[1..])?,
+                ))
+            }
+            "XADD" => {
+                if self.arguments.len() < 4 || !self.arguments.len().is_multiple_of(2) {
+                    return Err(wrong_arg_count("XADD"));
                 }
-                Ok(Command::Unsubscribe(self.arguments[0].clone()))
+                let id =
+                    match self.arguments[1].as_str() {
+                        "*" => None,
+                        id => Some(StreamId::parse(id, 0).map_err(|_| {
+                            InstructionError::InvalidArgument("XADD ID".to_string())
+                        })?),
+                    };
+                let fields = self.arguments[2..]
+                    .chunks(2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect();
+                Ok(Command::Xadd(self.arguments[0].clone(), id, fields))
             }
-            "PUBLISH" => {
-                if self.arguments.len() != 2 {
-                    return Err(wrong_arg_count("PUBLISH"));
+            "XLEN" => {
+                self.check_arity("XLEN", 1, Some(1))?;
+                Ok(Command::Xlen(self.arguments[0].clone()))
+            }
+            "XRANGE" => {
+                if self.arguments.len() != 3 && self.arguments.len() != 5 {
+                    return Err(wrong_arg_count("XRANGE"));
                 }
-                Ok(Command::Publish(
+                let invalid_id = |_| InstructionError::InvalidArgument("XRANGE ID".to_string());
+                let start = StreamId::parse_bound(&self.arguments[1], true).map_err(invalid_id)?;
+                let end = StreamId::parse_bound(&self.arguments[2], false).map_err(invalid_id)?;
+                let mut count = None;
+                if self.arguments.len() == 5 {
+                    if self.arguments[3].to_uppercase() != "COUNT" {
+                        return Err(InstructionError::InvalidArgument("XRANGE".to_string()));
+                    }
+                    count = Some(parse_stream_count(&self.arguments[4], 4, "XRANGE")?);
+                }
+                Ok(Command::Xrange(
                     self.arguments[0].clone(),
-                    network::resp_message::RespMessage::SimpleString(self.arguments[1].clone()),
+                    start,
+                    end,
+                    count,
                 ))
             }
-            "MEET" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("MEET"));
+            "XREAD" => {
+                let read = parse_stream_read(&self.arguments, 0, "XREAD")?;
+                let mut streams = Vec::with_capacity(read.keys.len());
+                for (key, id) in read.keys.iter().zip(read.ids) {
+                    let id = StreamId::parse(id, 0)
+                        .map_err(|_| InstructionError::InvalidArgument("XREAD ID".to_string()))?;
+                    streams.push((key.clone(), id));
                 }
-                Ok(Command::Meet(self.arguments[0].clone()))
+                Ok(Command::Xread(read.count, read.block, streams))
             }
-            "CLUSTER" => {
-                if self.arguments.len() != 1 {
-                    return Err(wrong_arg_count("CLUSTER"));
+            "XGROUP" => {
+                let subcommand = self
+                    .arguments
+                    .first()
+                    .ok_or_else(|| wrong_arg_count("XGROUP"))?;
+                if subcommand.to_uppercase() != "CREATE" {
+                    return Err(InstructionError::UnknownCommand(format!(
+                        "XGROUP {}",
+                        subcommand
+                    )));
                 }
-                if self.arguments[0].to_uppercase() == "SLOTS".to_string() {
-                    return Ok(Command::Slots);
+                let mkstream = match self.arguments.len() {
+                    4 => false,
+                    5 if self.arguments[4].to_uppercase() == "MKSTREAM" => true,
+                    5 => return Err(InstructionError::InvalidArgument("XGROUP".to_string())),
+                    _ => return Err(wrong_arg_count("XGROUP CREATE")),
+                };
+                let id =
+                    match self.arguments[3].as_str() {
+                        "$" => None,
+                        id => Some(StreamId::parse(id, 0).map_err(|_| {
+                            InstructionError::InvalidArgument("XGROUP ID".to_string())
+                        })?),
+                    };
+                Ok(Command::XgroupCreate(
+                    self.arguments[1].clone(),
+                    self.arguments[2].clone(),
+                    id,
+                    mkstream,
+                ))
+            }
+            "XREADGROUP" => {
+                if self.arguments.len() < 3 || self.arguments[0].to_uppercase() != "GROUP" {
+                    return Err(wrong_arg_count("XREADGROUP"));
                 }
-                Err(InstructionError::UnknownCommand(
-                    self.instruction_type.clone(),
+                let read = parse_stream_read(&self.arguments[3..], 3, "XREADGROUP")?;
+                if read.block.is_some() {
+                    return Err(InstructionError::InvalidArgument(
+                        "XREADGROUP BLOCK".to_string(),
+                    ));
+                }
+                let mut streams = Vec::with_capacity(read.keys.len());
+                for (key, id) in read.keys.iter().zip(read.ids) {
+                    let id = match id.as_str() {
+                        ">" => None,
+                        id => Some(StreamId::parse(id, 0).map_err(|_| {
+                            InstructionError::InvalidArgument("XREADGROUP ID".to_string())
+                        })?),
+                    };
+                    streams.push((key.clone(), id));
+                }
+                Ok(Command::Xreadgroup(
+                    self.arguments[1].clone(),
+                    self.arguments[2].clone(),
+                    read.count,
+                    streams,
                 ))
             }
-            "AUTH" => {
-                if self.arguments.len() != 2 {
-                    return Err(wrong_arg_count("AUTH"));
+            "XACK" => {
+                self.check_arity("XACK", 3, None)?;
+                let mut ids = Vec::with_capacity(self.arguments.len() - 2);
+                for id in &self.arguments[2..] {
+                    ids.push(
+                        StreamId::parse(id, 0).map_err(|_| {
+                            InstructionError::InvalidArgument("XACK ID".to_string())
+                        })?,
+                    );
                 }
-                Ok(Command::Auth(
+                Ok(Command::Xack(
                     self.arguments[0].clone(),
                     self.arguments[1].clone(),
+                    ids,
                 ))
             }
-            _ => Err(InstructionError::UnknownCommand(
-                self.instruction_type.clone(),
-            )),
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    /// Crea una instrucción de prueba.
-    fn create_test_instruction(cmd_type: &str, args: Vec<String>) -> Instruction {
-        Instruction::new(cmd_type.to_string(), args)
-    }
-
-    #[test]
-    fn test_instruction_new() {
-        let instruction = Instruction::new("GET".to_string(), vec!["key1".to_string()]);
-        assert_eq!(instruction.instruction_type, "GET");
-        assert_eq!(instruction.arguments, vec!["key1"]);
-    }
-
-    #[test]
-    fn test_to_command_get_success() {
+            "XPENDING" => {
+                self.check_arity("XPENDING", 2, Some(2))?;
+                Ok(Command::Xpending(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                ))
+            }
+            "BGSAVE" => {
+                self.check_arity("BGSAVE", 0, Some(0))?;
+                Ok(Command::BgSave)
+            }
+            "SAVE" => {
+                self.check_arity("SAVE", 0, Some(0))?;
+                Ok(Command::Save)
+            }
+            "BGREWRITEAOF" => {
+                self.check_arity("BGREWRITEAOF", 0, Some(0))?;
+                Ok(Command::Bgrewriteaof)
+            }
+            "INFO" => {
+                self.check_arity("INFO", 0, Some(1))?;
+                Ok(Command::Info(
+                    self.arguments.first().map(|section| section.to_lowercase()),
+                ))
+            }
+            "EXISTS" => {
+                self.check_arity("EXISTS", 1, None)?;
+                Ok(Command::Exists(self.arguments.clone()))
+            }
+            "TOUCH" => {
+                self.check_arity("TOUCH", 1, None)?;
+                Ok(Command::Touch(self.arguments.clone()))
+            }
+            "EXPIRE" => {
+                self.check_arity("EXPIRE", 2, Some(2))?;
+                let seconds = parse_int(&self.arguments[1], 1, "seconds for EXPIRE")?;
+                let seconds = u64::try_from(seconds)
+                    .map_err(|_| InstructionError::InvalidArgument("EXPIRE".to_string()))?;
+                Ok(Command::Expire(self.arguments[0].clone(), seconds))
+            }
+            "PEXPIREAT" => {
+                self.check_arity("PEXPIREAT", 2, Some(2))?;
+                let unix_ms = parse_int(&self.arguments[1], 1, "timestamp for PEXPIREAT")?;
+                let unix_ms = u64::try_from(unix_ms)
+                    .map_err(|_| InstructionError::InvalidArgument("PEXPIREAT".to_string()))?;
+                Ok(Command::PexpireAt(self.arguments[0].clone(), unix_ms))
+            }
+            "PERSIST" => {
+                self.check_arity("PERSIST", 1, Some(1))?;
+                Ok(Command::Persist(self.arguments[0].clone()))
+            }
+            "TTL" => {
+                self.check_arity("TTL", 1, Some(1))?;
+                Ok(Command::Ttl(self.arguments[0].clone()))
+            }
+            "TYPE" => {
+                self.check_arity("TYPE", 1, Some(1))?;
+                Ok(Command::Type(self.arguments[0].clone()))
+            }
+            "RENAME" => {
+                self.check_arity("RENAME", 2, Some(2))?;
+                Ok(Command::Rename(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                ))
+            }
+            "RENAMENX" => {
+                self.check_arity("RENAMENX", 2, Some(2))?;
+                Ok(Command::Renamenx(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                ))
+            }
+            "DUMP" => {
+                self.check_arity("DUMP", 1, Some(1))?;
+                Ok(Command::Dump(self.arguments[0].clone()))
+            }
+            "RESTORE" => {
+                self.check_arity("RESTORE", 3, Some(4))?;
+                let ttl = parse_int(&self.arguments[1], 1, "ttl for RESTORE")?;
+                let replace = match self.arguments.get(3) {
+                    Some(flag) if flag.to_uppercase() == "REPLACE" => true,
+                    Some(_) => {
+                        return Err(InstructionError::InvalidArgument("RESTORE".to_string()));
+                    }
+                    None => false,
+                };
+                Ok(Command::Restore(
+                    self.arguments[0].clone(),
+                    ttl,
+                    self.arguments[2].clone(),
+                    replace,
+                ))
+            }
+            "KEYS" => {
+                self.check_arity("KEYS", 1, Some(1))?;
+                Ok(Command::Keys(self.arguments[0].clone()))
+            }
+            "DBSIZE" => {
+                self.check_arity("DBSIZE", 0, Some(0))?;
+                Ok(Command::DbSize)
+            }
+            "FLUSHDB" => {
+                self.check_arity("FLUSHDB", 0, Some(0))?;
+                Ok(Command::FlushDb)
+            }
+            "SCAN" => {
+                self.check_arity("SCAN", 1, None)?;
+                let cursor = self.arguments[0]
+                    .parse::<u64>()
+                    .map_err(|_| InstructionError::InvalidArgument("SCAN cursor".to_string()))?;
+                let mut pattern = None;
+                let mut count = DEFAULT_SCAN_COUNT;
+
+                let mut i = 1;
+                while i < self.arguments.len() {
+                    let value = self
+                        .arguments
+                        .get(i + 1)
+                        .ok_or_else(|| InstructionError::InvalidArgument("SCAN".to_string()))?;
+                    match self.arguments[i].to_uppercase().as_str() {
+                        "MATCH" => pattern = Some(value.clone()),
+                        "COUNT" => {
+                            count = match parse_int(value, i + 1, "COUNT for SCAN")? {
+                                n if n >= 1 => n as usize,
+                                _ => {
+                                    return Err(InstructionError::InvalidArgument(
+                                        "SCAN".to_string(),
+                                    ));
+                                }
+                            }
+                        }
+                        _ => return Err(InstructionError::InvalidArgument("SCAN".to_string())),
+                    }
+                    i += 2;
+                }
+                Ok(Command::Scan(cursor, pattern, count))
+            }
+            "SUBSCRIBE" => {
+                self.check_arity("SUBSCRIBE", 1, Some(1))?;
+                Ok(Command::Subscribe(self.arguments[0].clone()))
+            }
+            "UNSUBSCRIBE" => {
+                self.check_arity("UNSUBSCRIBE", 1, Some(1))?;
+                Ok(Command::Unsubscribe(self.arguments[0].clone()))
+            }
+            "PSUBSCRIBE" => {
+                self.check_arity("PSUBSCRIBE", 1, None)?;
+                Ok(Command::PSubscribe(self.arguments.clone()))
+            }
+            "PUNSUBSCRIBE" => Ok(Command::PUnsubscribe(self.arguments.clone())),
+            "PUBLISH" => {
+                self.check_arity("PUBLISH", 2, Some(2))?;
+                Ok(Command::Publish(
+                    self.arguments[0].clone(),
+                    network::resp_message::RespMessage::SimpleString(self.arguments[1].clone()),
+                ))
+            }
+            "PUBSUB" => {
+                self.check_arity("PUBSUB", 1, None)?;
+                match self.arguments[0].to_uppercase().as_str() {
+                    "CHANNELS" => {
+                        self.check_arity("PUBSUB CHANNELS", 1, Some(2))?;
+                        Ok(Command::Pubsub(PubsubSubcommand::Channels(
+                            self.arguments.get(1).cloned(),
+                        )))
+                    }
+                    "NUMSUB" => Ok(Command::Pubsub(PubsubSubcommand::NumSub(
+                        self.arguments[1..].to_vec(),
+                    ))),
+                    _ => Err(InstructionError::UnknownCommand(format!(
+                        "{} {}",
+                        self.instruction_type, self.arguments[0]
+                    ))),
+                }
+            }
+            "MEET" => {
+                self.check_arity("MEET", 1, Some(1))?;
+                Ok(Command::Meet(self.arguments[0].clone()))
+            }
+            "CLUSTER" => {
+                self.check_arity("CLUSTER", 1, Some(1))?;
+                if self.arguments[0].to_uppercase() == "SLOTS".to_string() {
+                    return Ok(Command::Slots);
+                }
+                if self.arguments[0].to_uppercase() == "HELP" {
+                    return Ok(Command::ClusterHelp);
+                }
+                if self.arguments[0].to_uppercase() == "MYID" {
+                    return Ok(Command::ClusterMyId);
+                }
+                if self.arguments[0].to_uppercase() == "NODES" {
+                    return Ok(Command::ClusterNodes);
+                }
+                Err(InstructionError::UnknownCommand(
+                    self.instruction_type.clone(),
+                ))
+            }
+            "FAILOVER" => Ok(Command::Failover(parse_failover(&self.arguments)?)),
+            "WAIT" => {
+                self.check_arity("WAIT", 2, Some(2))?;
+                let numreplicas =
+                    u32::try_from(parse_int(&self.arguments[0], 0, "numreplicas for WAIT")?)
+                        .map_err(|_| InstructionError::IntegerOutOfRange)?;
+                let timeout = u64::try_from(parse_int(&self.arguments[1], 1, "timeout for WAIT")?)
+                    .map_err(|_| InstructionError::InvalidArgument("WAIT".to_string()))?;
+                Ok(Command::Wait(numreplicas, timeout))
+            }
+            "SYNC" => {
+                self.check_arity("SYNC", 0, Some(0))?;
+                Ok(Command::Sync)
+            }
+            "REPLICAOF" => {
+                self.check_arity("REPLICAOF", 2, Some(2))?;
+                let (host, port) = (&self.arguments[0], &self.arguments[1]);
+                if host.eq_ignore_ascii_case("NO") && port.eq_ignore_ascii_case("ONE") {
+                    return Ok(Command::ReplicaOf(None));
+                }
+                let port = u16::try_from(parse_int(port, 1, "port for REPLICAOF")?)
+                    .map_err(|_| InstructionError::IntegerOutOfRange)?;
+                Ok(Command::ReplicaOf(Some((host.clone(), port))))
+            }
+            "AUTH" => {
+                self.check_arity("AUTH", 2, Some(2))?;
+                Ok(Command::Auth(
+                    self.arguments[0].clone(),
+                    self.arguments[1].clone(),
+                ))
+            }
+            "CLIENT" => {
+                self.check_arity("CLIENT", 1, None)?;
+                match self.arguments[0].to_uppercase().as_str() {
+                    "NO-TOUCH" => {
+                        self.check_arity("CLIENT NO-TOUCH", 2, Some(2))?;
+                        let enabled = parse_switch(&self.arguments[1], "CLIENT NO-TOUCH")?;
+                        Ok(Command::Client(ClientSubcommand::NoTouch(enabled)))
+                    }
+                    "NO-EVICT" => {
+                        self.check_arity("CLIENT NO-EVICT", 2, Some(2))?;
+                        let enabled = parse_switch(&self.arguments[1], "CLIENT NO-EVICT")?;
+                        Ok(Command::Client(ClientSubcommand::NoEvict(enabled)))
+                    }
+                    "PAUSE" => {
+                        let mode = match self.arguments.len() {
+                            2 => PauseMode::All,
+                            3 => match self.arguments[2].to_uppercase().as_str() {
+                                "WRITE" => PauseMode::Write,
+                                "ALL" => PauseMode::All,
+                                _ => {
+                                    return Err(InstructionError::InvalidArgument(
+                                        "CLIENT PAUSE".to_string(),
+                                    ));
+                                }
+                            },
+                            _ => return Err(wrong_arg_count("CLIENT PAUSE")),
+                        };
+                        let timeout =
+                            match parse_int(&self.arguments[1], 1, "timeout for CLIENT PAUSE")? {
+                                ms if ms >= 0 => ms as u64,
+                                _ => {
+                                    return Err(InstructionError::InvalidArgument(
+                                        "CLIENT PAUSE".to_string(),
+                                    ));
+                                }
+                            };
+                        Ok(Command::Client(ClientSubcommand::Pause(timeout, mode)))
+                    }
+                    "UNPAUSE" => {
+                        self.check_arity("CLIENT UNPAUSE", 1, Some(1))?;
+                        Ok(Command::Client(ClientSubcommand::Unpause))
+                    }
+                    "SETNAME" => {
+                        self.check_arity("CLIENT SETNAME", 2, Some(2))?;
+                        // Como en Redis, el nombre no puede tener espacios
+                        // ni caracteres fuera del ASCII imprimible
+                        let name = &self.arguments[1];
+                        if !name.chars().all(|c| c.is_ascii_graphic()) {
+                            return Err(InstructionError::InvalidArgument(
+                                "CLIENT SETNAME".to_string(),
+                            ));
+                        }
+                        Ok(Command::Client(ClientSubcommand::SetName(name.clone())))
+                    }
+                    "GETNAME" => {
+                        self.check_arity("CLIENT GETNAME", 1, Some(1))?;
+                        Ok(Command::Client(ClientSubcommand::GetName))
+                    }
+                    "LIST" => {
+                        self.check_arity("CLIENT LIST", 1, Some(1))?;
+                        Ok(Command::Client(ClientSubcommand::List))
+                    }
+                    "KILL" => {
+                        self.check_arity("CLIENT KILL", 3, Some(3))?;
+                        if !self.arguments[1].eq_ignore_ascii_case("ID") {
+                            return Err(InstructionError::InvalidArgument(
+                                "CLIENT KILL".to_string(),
+                            ));
+                        }
+                        Ok(Command::Client(ClientSubcommand::Kill(
+                            self.arguments[2].clone(),
+                        )))
+                    }
+                    _ => Err(InstructionError::UnknownCommand(format!(
+                        "{} {}",
+                        self.instruction_type, self.arguments[0]
+                    ))),
+                }
+            }
+            "HELLO" => {
+                self.check_arity("HELLO", 0, Some(1))?;
+                let protover = match self.arguments.first() {
+                    Some(version) => Some(
+                        u8::try_from(parse_int(version, 0, "protover for HELLO")?)
+                            .map_err(|_| InstructionError::IntegerOutOfRange)?,
+                    ),
+                    None => None,
+                };
+                Ok(Command::Hello(protover))
+            }
+            "PING" => {
+                self.check_arity("PING", 0, Some(1))?;
+                Ok(Command::Ping(self.arguments.first().cloned()))
+            }
+            "MULTI" => {
+                self.check_arity("MULTI", 0, Some(0))?;
+                Ok(Command::Multi)
+            }
+            "EXEC" => {
+                self.check_arity("EXEC", 0, Some(0))?;
+                Ok(Command::Exec)
+            }
+            "DISCARD" => {
+                self.check_arity("DISCARD", 0, Some(0))?;
+                Ok(Command::Discard)
+            }
+            "DEBUG" => {
+                self.check_arity("DEBUG", 1, None)?;
+                match self.arguments[0].to_uppercase().as_str() {
+                    "RELOAD" => {
+                        self.check_arity("DEBUG RELOAD", 1, Some(1))?;
+                        Ok(Command::Debug(DebugSubcommand::Reload))
+                    }
+                    "SLEEP" => {
+                        self.check_arity("DEBUG SLEEP", 2, Some(2))?;
+                        let seconds = self.arguments[1]
+                            .parse::<f64>()
+                            .ok()
+                            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                            .ok_or_else(|| {
+                                InstructionError::InvalidArgument("DEBUG SLEEP".to_string())
+                            })?;
+                        Ok(Command::Debug(DebugSubcommand::Sleep(seconds)))
+                    }
+                    "POPULATE" => {
+                        self.check_arity("DEBUG POPULATE", 2, Some(4))?;
+                        let invalid =
+                            || InstructionError::InvalidArgument("DEBUG POPULATE".to_string());
+                        let count = usize::try_from(parse_int(
+                            &self.arguments[1],
+                            1,
+                            "count for DEBUG POPULATE",
+                        )?)
+                        .map_err(|_| invalid())?;
+                        let prefix = self
+                            .arguments
+                            .get(2)
+                            .cloned()
+                            .unwrap_or_else(|| DEFAULT_POPULATE_PREFIX.to_string());
+                        let size = match self.arguments.get(3) {
+                            Some(size) => Some(
+                                usize::try_from(parse_int(size, 3, "size for DEBUG POPULATE")?)
+                                    .map_err(|_| invalid())?,
+                            ),
+                            None => None,
+                        };
+                        Ok(Command::Debug(DebugSubcommand::Populate(
+                            count, prefix, size,
+                        )))
+                    }
+                    _ => Err(InstructionError::UnknownCommand(format!(
+                        "{} {}",
+                        self.instruction_type, self.arguments[0]
+                    ))),
+                }
+            }
+            "LATENCY" => {
+                self.check_arity("LATENCY", 1, None)?;
+                match self.arguments[0].to_uppercase().as_str() {
+                    "LATEST" => {
+                        self.check_arity("LATENCY LATEST", 1, Some(1))?;
+                        Ok(Command::Latency(LatencySubcommand::Latest))
+                    }
+                    "HISTORY" => {
+                        self.check_arity("LATENCY HISTORY", 2, Some(2))?;
+                        Ok(Command::Latency(LatencySubcommand::History(
+                            self.arguments[1].to_lowercase(),
+                        )))
+                    }
+                    "RESET" => Ok(Command::Latency(LatencySubcommand::Reset(
+                        self.arguments[1..]
+                            .iter()
+                            .map(|event| event.to_lowercase())
+                            .collect(),
+                    ))),
+                    _ => Err(InstructionError::UnknownCommand(format!(
+                        "{} {}",
+                        self.instruction_type, self.arguments[0]
+                    ))),
+                }
+            }
+            "OBJECT" => {
+                self.check_arity("OBJECT", 1, None)?;
+                match self.arguments[0].to_uppercase().as_str() {
+                    "ENCODING" => {
+                        self.check_arity("OBJECT ENCODING", 2, Some(2))?;
+                        Ok(Command::Object(ObjectSubcommand::Encoding(
+                            self.arguments[1].clone(),
+                        )))
+                    }
+                    _ => Err(InstructionError::UnknownCommand(format!(
+                        "{} {}",
+                        self.instruction_type, self.arguments[0]
+                    ))),
+                }
+            }
+            "COMMAND" => {
+                self.check_arity("COMMAND", 1, None)?;
+                match self.arguments[0].to_uppercase().as_str() {
+                    "COUNT" => {
+                        self.check_arity("COMMAND COUNT", 1, Some(1))?;
+                        Ok(Command::CommandInfo(CommandSubcommand::Count))
+                    }
+                    "DOCS" => Ok(Command::CommandInfo(CommandSubcommand::Docs(
+                        self.arguments[1..].to_vec(),
+                    ))),
+                    _ => Err(InstructionError::UnknownCommand(format!(
+                        "{} {}",
+                        self.instruction_type, self.arguments[0]
+                    ))),
+                }
+            }
+            _ => Err(InstructionError::UnknownCommand(
+                self.instruction_type.clone(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Crea una instrucción de prueba.
+    fn create_test_instruction(cmd_type: &str, args: Vec<String>) -> Instruction {
+        Instruction::new(cmd_type.to_string(), args)
+    }
+
+    #[test]
+    fn test_instruction_new() {
+        let instruction = Instruction::new("GET".to_string(), vec!["key1".to_string()]);
+        assert_eq!(instruction.instruction_type, "GET");
+        assert_eq!(instruction.arguments, vec!["key1"]);
+    }
+
+    #[test]
+    fn test_to_command_get_success() {
         let instruction = create_test_instruction("GET", vec!["mykey".to_string()]);
         let result = instruction.to_command();
         assert!(result.is_ok());
@@ -382,20 +1518,171 @@ mod tests {
         let instruction = create_test_instruction("GET", vec![]);
         let result = instruction.to_command();
         assert!(result.is_err());
-        if let Err(InstructionError::WrongArgumentCount(cmd)) = result {
+        if let Err(InstructionError::MissingArgument(cmd, index)) = result {
             assert_eq!(cmd, "GET");
+            assert_eq!(index, 0);
         } else {
-            panic!("Expected WrongArgumentCount error");
+            panic!("Expected MissingArgument error");
+        }
+    }
+
+    #[test]
+    fn test_to_command_mget() {
+        let instruction =
+            create_test_instruction("MGET", vec!["Kiriko".to_string(), "Hanzo".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Mget(vec!["Kiriko".to_string(), "Hanzo".to_string()])
+        );
+        assert!(
+            create_test_instruction("MGET", vec![])
+                .to_command()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_to_command_mset_pairs() {
+        let args = vec!["Kiriko", "Support", "Hanzo", "Damage"];
+        let instruction =
+            create_test_instruction("MSET", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Mset(vec![
+                ("Kiriko".to_string(), "Support".to_string()),
+                ("Hanzo".to_string(), "Damage".to_string())
+            ])
+        );
+
+        for args in [vec![], vec!["Kiriko", "Support", "Hanzo"]] {
+            let instruction =
+                create_test_instruction("MSET", args.into_iter().map(String::from).collect());
+            assert!(instruction.to_command().unwrap_err().is_arity_error());
         }
     }
 
+    #[test]
+    fn test_to_command_setrange() {
+        let args = vec!["Ashe".to_string(), "2".to_string(), "X".to_string()];
+        let instruction = create_test_instruction("SETRANGE", args);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::SetRange("Ashe".to_string(), 2, "X".to_string())
+        );
+
+        let args = vec!["Ashe".to_string(), "-1".to_string(), "X".to_string()];
+        let instruction = create_test_instruction("SETRANGE", args);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_getset() {
+        let args = vec!["Ashe".to_string(), "B.O.B".to_string()];
+        let instruction = create_test_instruction("GETSET", args);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::GetSet("Ashe".to_string(), "B.O.B".to_string())
+        );
+
+        let instruction = create_test_instruction("GETSET", vec!["Ashe".to_string()]);
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+    }
+
+    #[test]
+    fn test_to_command_psubscribe() {
+        let args = vec!["news.*".to_string(), "hero:?".to_string()];
+        let instruction = create_test_instruction("PSUBSCRIBE", args.clone());
+        assert_eq!(instruction.to_command().unwrap(), Command::PSubscribe(args));
+
+        let instruction = create_test_instruction("PSUBSCRIBE", vec![]);
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+
+        let instruction = create_test_instruction("PUNSUBSCRIBE", vec![]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::PUnsubscribe(vec![])
+        );
+    }
+
+    #[test]
+    fn test_to_command_pubsub() {
+        let instruction = create_test_instruction("PUBSUB", vec!["channels".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Pubsub(PubsubSubcommand::Channels(None))
+        );
+
+        let args = vec!["CHANNELS".to_string(), "doc:*".to_string()];
+        let instruction = create_test_instruction("PUBSUB", args);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Pubsub(PubsubSubcommand::Channels(Some("doc:*".to_string())))
+        );
+
+        let args = vec![
+            "NUMSUB".to_string(),
+            "doc:1".to_string(),
+            "doc:2".to_string(),
+        ];
+        let instruction = create_test_instruction("PUBSUB", args);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Pubsub(PubsubSubcommand::NumSub(vec![
+                "doc:1".to_string(),
+                "doc:2".to_string()
+            ]))
+        );
+
+        let args = vec!["CHANNELS".to_string(), "a".to_string(), "b".to_string()];
+        let instruction = create_test_instruction("PUBSUB", args);
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+
+        let instruction = create_test_instruction("PUBSUB", vec!["NUMPAT".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_rename() {
+        let args = vec!["Soldier76".to_string(), "Jack".to_string()];
+        let instruction = create_test_instruction("RENAME", args.clone());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Rename("Soldier76".to_string(), "Jack".to_string())
+        );
+        let instruction = create_test_instruction("RENAMENX", args);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Renamenx("Soldier76".to_string(), "Jack".to_string())
+        );
+
+        let instruction = create_test_instruction("RENAME", vec!["Soldier76".to_string()]);
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+    }
+
+    #[test]
+    fn test_to_command_dbsize_and_flushdb() {
+        let instruction = create_test_instruction("DBSIZE", vec![]);
+        assert_eq!(instruction.to_command().unwrap(), Command::DbSize);
+        let instruction = create_test_instruction("FLUSHDB", vec![]);
+        assert_eq!(instruction.to_command().unwrap(), Command::FlushDb);
+
+        let instruction = create_test_instruction("FLUSHDB", vec!["Kiriko".to_string()]);
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+    }
+
     #[test]
     fn test_to_command_set_success() {
         let instruction =
             create_test_instruction("SET", vec!["key".to_string(), "value".to_string()]);
         let result = instruction.to_command();
         assert!(result.is_ok());
-        if let Ok(Command::Set(key, value)) = result {
+        if let Ok(Command::Set(key, value, _)) = result {
             assert_eq!(key, "key");
             assert_eq!(value, "value");
         } else {
@@ -415,7 +1702,7 @@ mod tests {
         );
         let result = instruction.to_command();
         assert!(result.is_ok());
-        if let Ok(Command::Set(key, value)) = result {
+        if let Ok(Command::Set(key, value, _)) = result {
             assert_eq!(key, "key");
             assert_eq!(value, "value1 value2");
         } else {
@@ -423,6 +1710,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_to_command_set_options() {
+        let args = vec!["Hero", "Juno", "ex", "60", "NX"];
+        let instruction =
+            create_test_instruction("SET", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Set(
+                "Hero".to_string(),
+                "Juno".to_string(),
+                SetOptions {
+                    nx: true,
+                    xx: false,
+                    ex: Some(60)
+                }
+            )
+        );
+
+        let args = vec!["Hero", "Juno", "XX"];
+        let instruction =
+            create_test_instruction("SET", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Set(
+                "Hero".to_string(),
+                "Juno".to_string(),
+                SetOptions {
+                    xx: true,
+                    ..SetOptions::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_to_command_set_invalid_options() {
+        for args in [
+            vec!["Hero", "Juno", "NX", "XX"],
+            vec!["Hero", "Juno", "EX", "0"],
+            vec!["Hero", "Juno", "NX", "Mercy"],
+        ] {
+            let instruction =
+                create_test_instruction("SET", args.into_iter().map(String::from).collect());
+            assert!(matches!(
+                instruction.to_command(),
+                Err(InstructionError::InvalidArgument(_))
+            ));
+        }
+
+        let args = vec!["Hero", "Juno", "EX"];
+        let instruction =
+            create_test_instruction("SET", args.into_iter().map(String::from).collect());
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+    }
+
     #[test]
     fn test_to_command_lrange_with_ints() {
         let instruction = create_test_instruction(
@@ -448,8 +1790,9 @@ mod tests {
         );
         let result = instruction.to_command();
         assert!(result.is_err());
-        if let Err(InstructionError::ParseIntError(context)) = result {
+        if let Err(InstructionError::ParseIntError(context, index)) = result {
             assert!(context.contains("start index for LRANGE"));
+            assert_eq!(index, 1);
         } else {
             panic!("Expected ParseIntError");
         }
@@ -496,26 +1839,70 @@ mod tests {
         let instruction = create_test_instruction("BGSAVE", vec!["arg".to_string()]);
         let result = instruction.to_command();
         assert!(result.is_err());
-        if let Err(InstructionError::WrongArgumentCount(cmd)) = result {
+        if let Err(InstructionError::TooManyArguments(cmd, index)) = result {
             assert_eq!(cmd, "BGSAVE");
+            assert_eq!(index, 0);
         } else {
-            panic!("Expected WrongArgumentCount error");
+            panic!("Expected TooManyArguments error");
         }
     }
 
+    #[test]
+    fn test_to_command_reports_offending_argument() {
+        let instruction = create_test_instruction("SET", vec!["hero".to_string()]);
+        let error = instruction.to_command().unwrap_err();
+        assert!(error.is_arity_error());
+        assert_eq!(
+            error.argument(),
+            Some(ArgumentError {
+                index: 1,
+                reason: ArgumentReason::Missing
+            })
+        );
+
+        let instruction = create_test_instruction(
+            "LRANGE",
+            vec!["roster".to_string(), "0".to_string(), "tracer".to_string()],
+        );
+        let error = instruction.to_command().unwrap_err();
+        assert!(!error.is_arity_error());
+        assert_eq!(
+            error.argument(),
+            Some(ArgumentError {
+                index: 2,
+                reason: ArgumentReason::NotAnInteger
+            })
+        );
+
+        let instruction = create_test_instruction(
+            "XREADGROUP",
+            [
+                "GROUP", "casters", "winston", "COUNT", "many", "STREAMS", "matches", ">",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        );
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::ParseIntError(_, 4))
+        ));
+    }
+
     #[test]
     fn test_parse_int_success() {
-        let result = parse_int("123", "test");
+        let result = parse_int("123", 0, "test");
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 123);
     }
 
     #[test]
     fn test_parse_int_invalid() {
-        let result = parse_int("invalid", "test");
+        let result = parse_int("invalid", 2, "test");
         assert!(result.is_err());
-        if let Err(InstructionError::ParseIntError(context)) = result {
+        if let Err(InstructionError::ParseIntError(context, index)) = result {
             assert_eq!(context, "test");
+            assert_eq!(index, 2);
         } else {
             panic!("Expected ParseIntError");
         }
@@ -539,5 +1926,693 @@ mod tests {
         assert!(debug_str.contains("TEST"));
     }
 
+    #[test]
+    fn test_to_command_client_no_touch() {
+        let instruction =
+            create_test_instruction("CLIENT", vec!["no-touch".to_string(), "ON".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Client(ClientSubcommand::NoTouch(true))
+        );
+
+        let instruction =
+            create_test_instruction("CLIENT", vec!["NO-EVICT".to_string(), "off".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Client(ClientSubcommand::NoEvict(false))
+        );
+    }
+
+    #[test]
+    fn test_to_command_hello() {
+        let instruction = create_test_instruction("HELLO", vec![]);
+        assert_eq!(instruction.to_command().unwrap(), Command::Hello(None));
+
+        let instruction = create_test_instruction("HELLO", vec!["3".to_string()]);
+        assert_eq!(instruction.to_command().unwrap(), Command::Hello(Some(3)));
+
+        let instruction = create_test_instruction("HELLO", vec!["three".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::ParseIntError(_, 0))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_command_info() {
+        let instruction = create_test_instruction("COMMAND", vec!["count".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::CommandInfo(CommandSubcommand::Count)
+        );
+
+        let args = vec!["DOCS".to_string(), "get".to_string(), "set".to_string()];
+        let instruction = create_test_instruction("COMMAND", args);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::CommandInfo(CommandSubcommand::Docs(vec![
+                "get".to_string(),
+                "set".to_string()
+            ]))
+        );
+
+        let instruction = create_test_instruction("COMMAND", vec!["GETKEYS".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_transactions() {
+        let instruction = create_test_instruction("MULTI", vec![]);
+        assert_eq!(instruction.to_command().unwrap(), Command::Multi);
+
+        let instruction = create_test_instruction("EXEC", vec![]);
+        assert_eq!(instruction.to_command().unwrap(), Command::Exec);
+
+        let instruction = create_test_instruction("DISCARD", vec![]);
+        assert_eq!(instruction.to_command().unwrap(), Command::Discard);
+
+        let instruction = create_test_instruction("EXEC", vec!["now".to_string()]);
+        assert!(instruction.to_command().is_err());
+    }
+
+    #[test]
+    fn test_to_command_ping() {
+        let instruction = create_test_instruction("PING", vec![]);
+        assert_eq!(instruction.to_command().unwrap(), Command::Ping(None));
+
+        let instruction = create_test_instruction("PING", vec!["Hello".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Ping(Some("Hello".to_string()))
+        );
+
+        let args = vec!["Hello".to_string(), "World".to_string()];
+        let instruction = create_test_instruction("PING", args);
+        assert!(instruction.to_command().is_err());
+    }
+
+    #[test]
+    fn test_to_command_client_invalid_switch() {
+        let instruction =
+            create_test_instruction("CLIENT", vec!["NO-TOUCH".to_string(), "MAYBE".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_linsert() {
+        let args = vec!["Support", "before", "Ana", "Kiriko"];
+        let instruction =
+            create_test_instruction("LINSERT", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Linsert(
+                "Support".to_string(),
+                InsertWhere::Before,
+                "Ana".to_string(),
+                "Kiriko".to_string()
+            )
+        );
+
+        let args = vec!["Support", "AfTeR", "Ana", "Kiriko"];
+        let instruction =
+            create_test_instruction("LINSERT", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command().unwrap(),
+            Command::Linsert(_, InsertWhere::After, _, _)
+        ));
+
+        let args = vec!["Support", "BETWEEN", "Ana", "Kiriko"];
+        let instruction =
+            create_test_instruction("LINSERT", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_lmove_and_rpoplpush() {
+        let args = vec!["Queue", "Picks", "left", "RIGHT"];
+        let instruction =
+            create_test_instruction("LMOVE", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::LMove(
+                "Queue".to_string(),
+                "Picks".to_string(),
+                ListEnd::Left,
+                ListEnd::Right
+            )
+        );
+
+        let args = vec!["Queue", "Picks"];
+        let instruction =
+            create_test_instruction("RPOPLPUSH", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::LMove(
+                "Queue".to_string(),
+                "Picks".to_string(),
+                ListEnd::Right,
+                ListEnd::Left
+            )
+        );
+
+        let args = vec!["Queue", "Picks", "UP", "LEFT"];
+        let instruction =
+            create_test_instruction("LMOVE", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_client_pause() {
+        let args = vec!["pause", "500", "write"];
+        let instruction =
+            create_test_instruction("CLIENT", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Client(ClientSubcommand::Pause(500, PauseMode::Write))
+        );
+
+        let instruction =
+            create_test_instruction("CLIENT", vec!["PAUSE".to_string(), "500".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Client(ClientSubcommand::Pause(500, PauseMode::All))
+        );
+
+        let args = vec!["PAUSE", "500", "READ"];
+        let instruction =
+            create_test_instruction("CLIENT", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+
+        let instruction = create_test_instruction("CLIENT", vec!["UNPAUSE".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Client(ClientSubcommand::Unpause)
+        );
+    }
+
+    #[test]
+    fn test_to_command_client_setname_getname() {
+        let args = vec!["setname", "editor-Mei"];
+        let instruction =
+            create_test_instruction("CLIENT", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Client(ClientSubcommand::SetName("editor-Mei".to_string()))
+        );
+
+        let args = vec!["SETNAME", "Mei Ling Zhou"];
+        let instruction =
+            create_test_instruction("CLIENT", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+
+        let instruction = create_test_instruction("CLIENT", vec!["SETNAME".to_string()]);
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+
+        let instruction = create_test_instruction("CLIENT", vec!["GETNAME".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Client(ClientSubcommand::GetName)
+        );
+    }
+
+    #[test]
+    fn test_to_command_client_list() {
+        let instruction = create_test_instruction("CLIENT", vec!["list".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Client(ClientSubcommand::List)
+        );
+
+        let args = vec!["LIST", "TYPE", "normal"];
+        let instruction =
+            create_test_instruction("CLIENT", args.into_iter().map(String::from).collect());
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+    }
+
+    #[test]
+    fn test_to_command_client_kill() {
+        let args = vec!["kill", "id", "AAA001"];
+        let instruction =
+            create_test_instruction("CLIENT", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Client(ClientSubcommand::Kill("AAA001".to_string()))
+        );
+
+        let args = vec!["KILL", "ADDR", "127.0.0.1:6379"];
+        let instruction =
+            create_test_instruction("CLIENT", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+
+        let args = vec!["KILL", "AAA001"];
+        let instruction =
+            create_test_instruction("CLIENT", args.into_iter().map(String::from).collect());
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+    }
+
+    #[test]
+    fn test_to_command_debug_reload() {
+        let instruction = create_test_instruction("DEBUG", vec!["reload".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Debug(DebugSubcommand::Reload)
+        );
+
+        let instruction = create_test_instruction("DEBUG", vec!["SEGFAULT".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_debug_populate() {
+        let instruction =
+            create_test_instruction("DEBUG", vec!["POPULATE".to_string(), "1000".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Debug(DebugSubcommand::Populate(1000, "key".to_string(), None))
+        );
+        let args = vec!["populate", "50", "hero", "64"];
+        let instruction =
+            create_test_instruction("DEBUG", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Debug(DebugSubcommand::Populate(50, "hero".to_string(), Some(64)))
+        );
+        let args = vec!["POPULATE", "-1"];
+        let instruction =
+            create_test_instruction("DEBUG", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_debug_sleep_and_latency() {
+        let instruction =
+            create_test_instruction("DEBUG", vec!["sleep".to_string(), "0.25".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Debug(DebugSubcommand::Sleep(Duration::from_millis(250)))
+        );
+        let instruction =
+            create_test_instruction("DEBUG", vec!["SLEEP".to_string(), "-1".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+
+        let instruction = create_test_instruction(
+            "LATENCY",
+            vec!["history".to_string(), "Command".to_string()],
+        );
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Latency(LatencySubcommand::History("command".to_string()))
+        );
+        let instruction = create_test_instruction("LATENCY", vec!["RESET".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Latency(LatencySubcommand::Reset(vec![]))
+        );
+        let instruction = create_test_instruction("LATENCY", vec!["DOCTOR".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_hset_pairs() {
+        let args = vec!["Roles", "Ana", "Support", "Orisa", "Tank"];
+        let instruction =
+            create_test_instruction("HSET", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Hset(
+                "Roles".to_string(),
+                vec![
+                    ("Ana".to_string(), "Support".to_string()),
+                    ("Orisa".to_string(), "Tank".to_string())
+                ]
+            )
+        );
+
+        let args = vec!["Roles", "Ana", "Support", "Orisa"];
+        let instruction =
+            create_test_instruction("HSET", args.into_iter().map(String::from).collect());
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+    }
+
+    #[test]
+    fn test_to_command_geosearch_options() {
+        let args = vec![
+            "Maps",
+            "FROMMEMBER",
+            "Paris",
+            "BYBOX",
+            "400",
+            "700",
+            "km",
+            "DESC",
+            "COUNT",
+            "2",
+            "WITHCOORD",
+        ];
+        let instruction =
+            create_test_instruction("GEOSEARCH", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Geosearch(
+                "Maps".to_string(),
+                GeoSearchOptions {
+                    origin: GeoOrigin::Member("Paris".to_string()),
+                    shape: GeoShape::Box(400.0, 700.0),
+                    unit: GeoUnit::Km,
+                    desc: true,
+                    count: Some(2),
+                    with_coord: true,
+                    with_dist: false,
+                }
+            )
+        );
+
+        for args in [
+            vec!["Maps", "FROMMEMBER", "Paris", "BYRADIUS", "10", "yd"],
+            vec!["Maps", "FROMMEMBER", "Paris", "FROMLONLAT", "0", "0"],
+            vec!["Maps", "BYRADIUS", "10", "km", "WITHDIST", "ASC"],
+        ] {
+            let instruction =
+                create_test_instruction("GEOSEARCH", args.into_iter().map(String::from).collect());
+            assert!(instruction.to_command().is_err());
+        }
+    }
+
+    #[test]
+    fn test_to_command_exists_requires_a_key() {
+        let instruction =
+            create_test_instruction("EXISTS", vec!["Ana".to_string(), "Ana".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Exists(vec!["Ana".to_string(), "Ana".to_string()])
+        );
+        let instruction = create_test_instruction("EXISTS", vec![]);
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+    }
+
+    #[test]
+    fn test_to_command_object_encoding() {
+        let instruction =
+            create_test_instruction("OBJECT", vec!["encoding".to_string(), "Hero".to_string()]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Object(ObjectSubcommand::Encoding("Hero".to_string()))
+        );
+        let instruction = create_test_instruction("OBJECT", vec!["ENCODING".to_string()]);
+        assert!(instruction.to_command().unwrap_err().is_arity_error());
+    }
+
+    #[test]
+    fn test_to_command_wait() {
+        let instruction = create_test_instruction("WAIT", vec!["2".to_string(), "500".to_string()]);
+        assert_eq!(instruction.to_command().unwrap(), Command::Wait(2, 500));
+
+        let instruction = create_test_instruction("WAIT", vec!["1".to_string(), "-1".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_replicaof() {
+        let instruction = create_test_instruction(
+            "REPLICAOF",
+            vec!["127.0.0.1".to_string(), "7001".to_string()],
+        );
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::ReplicaOf(Some(("127.0.0.1".to_string(), 7001)))
+        );
+
+        let instruction =
+            create_test_instruction("REPLICAOF", vec!["no".to_string(), "one".to_string()]);
+        assert_eq!(instruction.to_command().unwrap(), Command::ReplicaOf(None));
+
+        let instruction = create_test_instruction(
+            "REPLICAOF",
+            vec!["127.0.0.1".to_string(), "70000".to_string()],
+        );
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::IntegerOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn test_to_command_failover_options() {
+        let args = vec!["TIMEOUT", "5000", "to", "127.0.0.1", "6380", "FORCE"];
+        let instruction =
+            create_test_instruction("FAILOVER", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Failover(FailoverOptions {
+                to: Some(("127.0.0.1".to_string(), 6380)),
+                force: true,
+                timeout: Some(5000),
+                abort: false,
+            })
+        );
+
+        let instruction = create_test_instruction("FAILOVER", vec![]);
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Failover(FailoverOptions::default())
+        );
+
+        let instruction =
+            create_test_instruction("FAILOVER", vec!["ABORT".to_string(), "FORCE".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+        // FORCE sin TO ni TIMEOUT
+        let instruction = create_test_instruction("FAILOVER", vec!["FORCE".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+        let instruction =
+            create_test_instruction("FAILOVER", vec!["TO".to_string(), "127.0.0.1".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::MissingArgument(_, 2))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_scan_options() {
+        let args = vec!["17", "MATCH", "hero:*", "COUNT", "100"];
+        let instruction =
+            create_test_instruction("SCAN", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Scan(17, Some("hero:*".to_string()), 100)
+        );
+
+        let instruction =
+            create_test_instruction("SCAN", vec!["0".to_string(), "COUNT".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_xadd_and_xrange() {
+        let args = vec!["matches", "*", "map", "Ilios", "winner", "Talon"];
+        let instruction =
+            create_test_instruction("XADD", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Xadd(
+                "matches".to_string(),
+                None,
+                vec![
+                    ("map".to_string(), "Ilios".to_string()),
+                    ("winner".to_string(), "Talon".to_string()),
+                ],
+            )
+        );
+
+        let args = vec!["matches", "10-x", "map", "Ilios"];
+        let instruction =
+            create_test_instruction("XADD", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+
+        let args = vec!["matches", "-", "15", "COUNT", "2"];
+        let instruction =
+            create_test_instruction("XRANGE", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Xrange(
+                "matches".to_string(),
+                StreamId::MIN,
+                StreamId::new(15, u64::MAX),
+                Some(2),
+            )
+        );
+    }
+
+    #[test]
+    fn test_to_command_xread_streams() {
+        let args = vec!["COUNT", "5", "STREAMS", "matches", "kills", "0", "12-3"];
+        let instruction =
+            create_test_instruction("XREAD", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Xread(
+                Some(5),
+                None,
+                vec![
+                    ("matches".to_string(), StreamId::MIN),
+                    ("kills".to_string(), StreamId::new(12, 3)),
+                ],
+            )
+        );
+
+        let args = vec!["BLOCK", "0", "COUNT", "1", "STREAMS", "matches", "$"];
+        let instruction =
+            create_test_instruction("XREAD", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+
+        let args = vec!["BLOCK", "1500", "STREAMS", "matches", "3"];
+        let instruction =
+            create_test_instruction("XREAD", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Xread(
+                None,
+                Some(1500),
+                vec![("matches".to_string(), StreamId::new(3, 0))],
+            )
+        );
+
+        let args = vec!["STREAMS", "matches", "kills", "0"];
+        let instruction =
+            create_test_instruction("XREAD", args.into_iter().map(String::from).collect());
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::WrongArgumentCount(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_consumer_groups() {
+        let args = vec!["CREATE", "matches", "casters", "$", "MKSTREAM"];
+        let instruction =
+            create_test_instruction("XGROUP", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::XgroupCreate("matches".to_string(), "casters".to_string(), None, true)
+        );
+
+        let args = vec![
+            "GROUP", "casters", "Mr.X", "COUNT", "2", "STREAMS", "matches", ">",
+        ];
+        let instruction =
+            create_test_instruction("XREADGROUP", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Xreadgroup(
+                "casters".to_string(),
+                "Mr.X".to_string(),
+                Some(2),
+                vec![("matches".to_string(), None)],
+            )
+        );
+
+        let args = vec!["matches", "casters", "1-0", "2-0"];
+        let instruction =
+            create_test_instruction("XACK", args.into_iter().map(String::from).collect());
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Xack(
+                "matches".to_string(),
+                "casters".to_string(),
+                vec![StreamId::new(1, 0), StreamId::new(2, 0)],
+            )
+        );
+
+        let instruction = create_test_instruction("XGROUP", vec!["DESTROY".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_command_sort_with_options() {
+        let instruction = create_test_instruction(
+            "SORT",
+            vec![
+                "Ranks", "BY", "weight_*", "LIMIT", "0", "5", "GET", "#", "DESC", "ALPHA",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        );
+        assert_eq!(
+            instruction.to_command().unwrap(),
+            Command::Sort(
+                "Ranks".to_string(),
+                true,
+                true,
+                Some((0, 5)),
+                Some("weight_*".to_string()),
+                vec!["#".to_string()],
+            )
+        );
+    }
+
+    #[test]
+    fn test_to_command_sort_incomplete_limit() {
+        let instruction =
+            create_test_instruction("SORT", vec!["Ranks".to_string(), "LIMIT".to_string()]);
+        assert!(matches!(
+            instruction.to_command(),
+            Err(InstructionError::InvalidArgument(_))
+        ));
+    }
+
     // TODO: Test para auth
 }
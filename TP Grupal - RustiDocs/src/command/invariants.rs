@@ -0,0 +1,244 @@
+//! Verificación de invariantes internos (`debug-invariants`).
+//!
+//! Con `debug-invariants yes` el ejecutor revisa, después de cada comando,
+//! que el store y la asignación de slots sigan siendo consistentes. Cada
+//! violación se registra en el log con el comando que la dejó a la vista;
+//! compilando con la feature `strict-invariants`, además, se hace panic,
+//! para que los tests fallen en el lugar de la corrupción y no después.
+
+use crate::cluster::types::{KnownNode, NodeId, SlotRange, is_empty_slot_range};
+use crate::storage::DataStore;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Invariante violado y el detalle de la inconsistencia encontrada.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvariantViolation {
+    /// Nombre del invariante
+    pub invariant: &'static str,
+    /// Claves, IDs o nodos involucrados
+    pub detail: String,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.invariant, self.detail)
+    }
+}
+
+/// Una clave vive en una sola de las bases del store.
+pub const SINGLE_CONTAINER: &str = "key-in-single-container";
+/// Toda clave con TTL existe en alguna base.
+pub const EXPIRING_KEY_EXISTS: &str = "expiring-key-exists";
+/// Ninguna entrada de un stream supera su último ID.
+pub const STREAM_LAST_ID: &str = "stream-last-id";
+/// Las entradas pendientes de un grupo ya fueron entregadas por el grupo.
+pub const PENDING_DELIVERED: &str = "pending-entries-delivered";
+/// Dos masters no sirven el mismo slot.
+pub const DISJOINT_SLOTS: &str = "disjoint-slot-ownership";
+
+/// Verifica los invariantes del store.
+///
+/// # Returns
+///
+/// Las violaciones encontradas, vacío si el store es consistente
+pub fn check_store(store: &DataStore) -> Vec<InvariantViolation> {
+    let mut violations = Vec::new();
+
+    let mut containers: HashMap<&String, Vec<&str>> = HashMap::new();
+    let dbs = [
+        ("string", store.string_db.keys().collect::<Vec<_>>()),
+        ("list", store.list_db.keys().collect()),
+        ("set", store.set_db.keys().collect()),
+        ("stream", store.stream_db.keys().collect()),
+        ("hash", store.hash_db.keys().collect()),
+        ("zset", store.zset_db.keys().collect()),
+    ];
+    for (db, keys) in dbs {
+        for key in keys {
+            containers.entry(key).or_default().push(db);
+        }
+    }
+    for (key, dbs) in containers.iter().filter(|(_, dbs)| dbs.len() > 1) {
+        violations.push(InvariantViolation {
+            invariant: SINGLE_CONTAINER,
+            detail: format!("key '{}' is in {}", key, dbs.join(", ")),
+        });
+    }
+
+    for key in store.expires.keys().filter(|key| !store.contains_key(key)) {
+        violations.push(InvariantViolation {
+            invariant: EXPIRING_KEY_EXISTS,
+            detail: format!("TTL of missing key '{}'", key),
+        });
+    }
+
+    for (key, stream) in &store.stream_db {
+        let last_id = stream.last_id();
+        if let Some((id, _)) = stream.entries().last().filter(|(id, _)| **id > last_id) {
+            violations.push(InvariantViolation {
+                invariant: STREAM_LAST_ID,
+                detail: format!(
+                    "stream '{}' has entry {} after last ID {}",
+                    key, id, last_id
+                ),
+            });
+        }
+        for (name, group) in stream.groups() {
+            if let Some((id, _)) = group
+                .pending
+                .iter()
+                .next_back()
+                .filter(|(id, _)| **id > group.last_delivered)
+            {
+                violations.push(InvariantViolation {
+                    invariant: PENDING_DELIVERED,
+                    detail: format!(
+                        "group '{}' of stream '{}' has pending {} after last delivered {}",
+                        name, key, id, group.last_delivered
+                    ),
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Verifica que los rangos de slots de los masters no se superpongan.
+/// Los rangos vacíos se ignoran.
+///
+/// # Arguments
+///
+/// * `own` - ID y slots de este nodo, si es master
+/// * `nodes` - Nodos conocidos del cluster
+///
+/// # Returns
+///
+/// Las violaciones encontradas, vacío si la asignación es consistente
+pub fn check_slot_ownership(
+    own: Option<(NodeId, SlotRange)>,
+    nodes: &HashMap<NodeId, KnownNode>,
+) -> Vec<InvariantViolation> {
+    let mut ranges: Vec<(SlotRange, NodeId)> = own
+        .into_iter()
+        .map(|(id, slots)| (slots, id))
+        .chain(
+            nodes
+                .values()
+                .filter(|node| node.is_master())
+                .map(|node| (node.get_slots(), node.get_id())),
+        )
+        .filter(|(slots, _)| !is_empty_slot_range(*slots))
+        .collect();
+    ranges.sort();
+
+    ranges
+        .windows(2)
+        .filter(|pair| pair[1].0.0 <= pair[0].0.1)
+        .map(|pair| InvariantViolation {
+            invariant: DISJOINT_SLOTS,
+            detail: format!(
+                "{} serves {}-{} and {} serves {}-{}",
+                pair[0].1, pair[0].0.0, pair[0].0.1, pair[1].1, pair[1].0.0, pair[1].0.1
+            ),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::state::flags::MASTER;
+    use crate::storage::stream::{PendingEntry, Stream, StreamId};
+    use std::time::{Duration, Instant};
+
+    /// Store consistente con una clave de cada tipo.
+    fn consistent_store() -> DataStore {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Venture".to_string());
+        store.set_ttl("Hero", Some(Duration::from_secs(60)));
+        store
+            .list_db
+            .insert("Tanks".to_string(), vec!["Mauga".to_string()]);
+        let stream = store.stream_db.entry("Feed".to_string()).or_default();
+        stream
+            .add(
+                Some(StreamId::new(1, 0)),
+                vec![("kill".to_string(), "Ramattra".to_string())],
+                0,
+            )
+            .unwrap();
+        stream.create_group("casters", StreamId::new(1, 0)).unwrap();
+        store
+    }
+
+    /// Nombres de los invariantes violados.
+    fn violated(violations: &[InvariantViolation]) -> Vec<&'static str> {
+        violations.iter().map(|v| v.invariant).collect()
+    }
+
+    #[test]
+    fn test_consistent_store_has_no_violations() {
+        assert!(check_store(&consistent_store()).is_empty());
+    }
+
+    #[test]
+    fn test_key_in_two_containers_is_detected() {
+        let mut store = consistent_store();
+        store.set_db.insert("Hero".to_string(), Default::default());
+
+        let violations = check_store(&store);
+        assert_eq!(violated(&violations), vec![SINGLE_CONTAINER]);
+        assert!(violations[0].detail.contains("'Hero'"));
+    }
+
+    #[test]
+    fn test_ttl_of_missing_key_is_detected() {
+        let mut store = consistent_store();
+        store.expires.insert(
+            "Ghost".to_string(),
+            Instant::now() + Duration::from_secs(60),
+        );
+
+        assert_eq!(violated(&check_store(&store)), vec![EXPIRING_KEY_EXISTS]);
+    }
+
+    #[test]
+    fn test_pending_after_last_delivered_is_detected() {
+        let mut store = consistent_store();
+        let mut groups = store.stream_db["Feed"].groups().clone();
+        groups.get_mut("casters").unwrap().pending.insert(
+            StreamId::new(5, 0),
+            PendingEntry {
+                consumer: "Mr.X".to_string(),
+                deliveries: 1,
+            },
+        );
+        let stream = &store.stream_db["Feed"];
+        let entries = stream
+            .entries()
+            .map(|(id, fields)| (*id, fields.clone()))
+            .collect();
+        let corrupted = Stream::from_parts(entries, stream.last_id(), groups);
+        store.stream_db.insert("Feed".to_string(), corrupted);
+
+        assert_eq!(violated(&check_store(&store)), vec![PENDING_DELIVERED]);
+    }
+
+    #[test]
+    fn test_overlapping_masters_are_detected() {
+        let mut nodes = HashMap::new();
+        let mut node = KnownNode::new("node_b".to_string(), "127.0.0.1".to_string(), 7002);
+        node.get_flags_mut().set(MASTER);
+        node.set_hash_slots((5000, 9999));
+        nodes.insert(node.get_id(), node);
+
+        let own = Some(("node_a".to_string(), (0, 4999)));
+        assert!(check_slot_ownership(own, &nodes).is_empty());
+
+        let own = Some(("node_a".to_string(), (0, 5000)));
+        let violations = check_slot_ownership(own, &nodes);
+        assert_eq!(violated(&violations), vec![DISJOINT_SLOTS]);
+        assert!(violations[0].detail.contains("node_a serves 0-5000"));
+    }
+}
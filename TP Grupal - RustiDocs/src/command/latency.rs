@@ -0,0 +1,171 @@
+//! Monitor de latencia del servidor (LATENCY).
+//!
+//! Registra, por tipo de evento, las operaciones que superan el umbral
+//! `latency-monitor-threshold` de la configuración, guardando un historial
+//! acotado de muestras para diagnosticar picos de latencia.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Evento de un comando lento.
+pub const COMMAND_EVENT: &str = "command";
+/// Evento de un guardado automático de la base en disco.
+pub const SAVE_EVENT: &str = "save";
+
+/// Cantidad máxima de muestras guardadas por evento.
+const LATENCY_HISTORY_LEN: usize = 160;
+
+/// Muestra de latencia de un evento.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LatencySample {
+    /// Momento del evento, en segundos UNIX
+    pub timestamp: u64,
+    /// Duración del evento en milisegundos
+    pub latency_ms: u64,
+}
+
+/// Historial de un tipo de evento.
+#[derive(Debug, Default)]
+struct EventHistory {
+    samples: VecDeque<LatencySample>,
+    max_ms: u64,
+}
+
+/// Monitor de latencia con historial por tipo de evento.
+///
+/// Con umbral 0 el monitor está deshabilitado y no registra eventos.
+#[derive(Debug)]
+pub struct LatencyMonitor {
+    threshold_ms: u64,
+    events: HashMap<String, EventHistory>,
+}
+
+impl LatencyMonitor {
+    /// Crea un monitor con el umbral en milisegundos dado.
+    pub fn new(threshold_ms: u64) -> Self {
+        Self {
+            threshold_ms,
+            events: HashMap::new(),
+        }
+    }
+
+    /// Registra el evento si su duración alcanza el umbral.
+    ///
+    /// # Retorna
+    ///
+    /// `true` si el evento fue registrado
+    pub fn record(&mut self, event: &str, latency: Duration) -> bool {
+        let latency_ms = latency.as_millis() as u64;
+        if self.threshold_ms == 0 || latency_ms < self.threshold_ms {
+            return false;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or(0);
+        let history = self.events.entry(event.to_string()).or_default();
+        if history.samples.len() == LATENCY_HISTORY_LEN {
+            history.samples.pop_front();
+        }
+        history.samples.push_back(LatencySample {
+            timestamp,
+            latency_ms,
+        });
+        history.max_ms = history.max_ms.max(latency_ms);
+        true
+    }
+
+    /// Última muestra y máximo histórico de cada evento, ordenados por nombre.
+    pub fn latest(&self) -> Vec<(String, LatencySample, u64)> {
+        let mut latest: Vec<_> = self
+            .events
+            .iter()
+            .filter_map(|(event, history)| {
+                history
+                    .samples
+                    .back()
+                    .map(|sample| (event.clone(), *sample, history.max_ms))
+            })
+            .collect();
+        latest.sort_by(|a, b| a.0.cmp(&b.0));
+        latest
+    }
+
+    /// Muestras guardadas del evento, de la más vieja a la más nueva.
+    pub fn history(&self, event: &str) -> Vec<LatencySample> {
+        self.events
+            .get(event)
+            .map(|history| history.samples.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Borra el historial de los eventos dados, o de todos si no se indica ninguno.
+    ///
+    /// # Retorna
+    ///
+    /// Cantidad de eventos borrados
+    pub fn reset(&mut self, events: &[String]) -> usize {
+        if events.is_empty() {
+            let reset = self.events.len();
+            self.events.clear();
+            return reset;
+        }
+        events
+            .iter()
+            .filter(|event| self.events.remove(*event).is_some())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_only_above_threshold() {
+        let mut monitor = LatencyMonitor::new(10);
+        assert!(!monitor.record(COMMAND_EVENT, Duration::from_millis(9)));
+        assert!(monitor.record(COMMAND_EVENT, Duration::from_millis(25)));
+        assert!(monitor.record(COMMAND_EVENT, Duration::from_millis(12)));
+
+        let history = monitor.history(COMMAND_EVENT);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].latency_ms, 12);
+
+        let latest = monitor.latest();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].0, COMMAND_EVENT);
+        assert_eq!(latest[0].1.latency_ms, 12);
+        assert_eq!(latest[0].2, 25);
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_monitor() {
+        let mut monitor = LatencyMonitor::new(0);
+        assert!(!monitor.record(SAVE_EVENT, Duration::from_secs(1)));
+        assert!(monitor.latest().is_empty());
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut monitor = LatencyMonitor::new(1);
+        for ms in 1..=(LATENCY_HISTORY_LEN as u64 + 5) {
+            monitor.record(COMMAND_EVENT, Duration::from_millis(ms));
+        }
+        let history = monitor.history(COMMAND_EVENT);
+        assert_eq!(history.len(), LATENCY_HISTORY_LEN);
+        assert_eq!(history[0].latency_ms, 6);
+    }
+
+    #[test]
+    fn test_reset_selected_or_all_events() {
+        let mut monitor = LatencyMonitor::new(1);
+        monitor.record(COMMAND_EVENT, Duration::from_millis(5));
+        monitor.record(SAVE_EVENT, Duration::from_millis(5));
+
+        assert_eq!(monitor.reset(&["save".to_string(), "fork".to_string()]), 1);
+        assert_eq!(monitor.latest().len(), 1);
+        assert_eq!(monitor.reset(&[]), 1);
+        assert!(monitor.history(COMMAND_EVENT).is_empty());
+    }
+}
@@ -1,6 +1,10 @@
+pub mod aof_rewrite;
 pub mod command_executor;
+pub mod command_table;
 pub mod commands;
 pub mod instruction;
+pub mod invariants;
+pub mod latency;
 mod test;
 pub mod try_from;
 pub mod types;
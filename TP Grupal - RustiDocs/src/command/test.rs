@@ -3,11 +3,17 @@
 #[cfg(test)]
 mod command_tests {
     // IMPORTS
-    use crate::command::commands::CommandError;
-    use crate::command::types::Command;
+    use crate::command::commands::{CommandError, geopos, geosearch, sort};
+    use crate::command::types::{
+        Command, GeoOrigin, GeoSearchOptions, GeoShape, InsertWhere, ListEnd, ObjectSubcommand,
+        SetOptions,
+    };
     use crate::command::*;
     use crate::storage::DataStore;
-    use std::collections::HashSet;
+    use crate::storage::data_store::{EMBSTR_SIZE_LIMIT, INTSET_MAX_ENTRIES, LISTPACK_MAX_ENTRIES};
+    use crate::storage::geo::GeoUnit;
+    use crate::storage::stream::StreamId;
+    use std::collections::{HashMap, HashSet};
 
     // CONSTANTES
     static ERR_WRONG_NUM_ARGS: &str = "ERR wrong number of arguments for '_' command";
@@ -236,6 +242,30 @@ mod command_tests {
         );
     }
 
+    /* PING */
+
+    #[test]
+    fn ping_without_argument_returns_pong() {
+        let empty_store = DataStore::new();
+        let cmd = Command::Ping(None);
+
+        let result = cmd.execute_read(&empty_store, None, None, None, None, None);
+        assert_eq!(result.unwrap(), ResponseType::Str("PONG".to_string()));
+    }
+
+    #[test]
+    fn ping_with_argument_returns_it() {
+        let empty_store = DataStore::new();
+        let cmd = Command::Ping(Some("Cheers, love!".to_string()));
+
+        let result = cmd.execute_read(&empty_store, None, None, None, None, None);
+        assert_eq!(
+            result.unwrap(),
+            ResponseType::Str("Cheers, love!".to_string())
+        );
+        assert!(cmd.is_read_only());
+    }
+
     /* GET */
 
     #[test]
@@ -357,6 +387,67 @@ mod command_tests {
         assert!(empty_store.set_db.get("NonExistent").is_none());
     }
 
+    /* GETSET */
+
+    #[test]
+    fn getset_returns_nil_for_missing_key() {
+        let mut store = DataStore::new();
+
+        let cmd = Command::GetSet("Ashe".to_string(), "B.O.B".to_string());
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Null(None));
+        assert_eq!(store.get("Ashe"), Some(&"B.O.B".to_string()));
+    }
+
+    #[test]
+    fn getset_returns_previous_value() {
+        let mut store = DataStore::new();
+        store.set("Ashe".to_string(), "B.O.B".to_string());
+
+        let cmd = Command::GetSet("Ashe".to_string(), "Viper".to_string());
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Str("B.O.B".to_string()));
+        assert_eq!(store.get("Ashe"), Some(&"Viper".to_string()));
+    }
+
+    #[test]
+    fn getset_clears_ttl() {
+        let mut store = DataStore::new();
+        store.set("Ashe".to_string(), "B.O.B".to_string());
+        store.set_ttl("Ashe", Some(std::time::Duration::from_secs(10)));
+
+        let cmd = Command::GetSet("Ashe".to_string(), "Viper".to_string());
+        cmd.execute_write(&mut store).unwrap();
+
+        assert_eq!(store.remaining_ttl("Ashe"), None);
+    }
+
+    #[test]
+    fn getset_doesnt_work_for_existing_list() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+
+        let cmd = Command::GetSet("DPS".to_string(), "Viper".to_string());
+        let result = cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        assert_eq!(store.list_db["DPS"].len(), 5);
+        assert!(store.get("DPS").is_none());
+    }
+
+    #[test]
+    fn getset_doesnt_work_for_existing_set() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+
+        let cmd = Command::GetSet("Maps".to_string(), "Ilios".to_string());
+        let result = cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        assert_eq!(store.set_db["Maps"].len(), 3);
+        assert!(store.get("Maps").is_none());
+    }
+
     /* GETRANGE */
 
     #[test]
@@ -445,12 +536,73 @@ mod command_tests {
         assert!(matches!(result.unwrap_err(), CommandError::WrongType));
     }
 
+    #[test]
+    fn getrange_with_accented_characters() {
+        let mut store = DataStore::new();
+        store.set("Lucio".to_string(), "¡Hola!".to_string());
+
+        let getrange_cmd = Command::Getrange("Lucio".to_string(), 0, -1);
+        let result = getrange_cmd.execute_read(&store, None, None, None, None, None);
+        assert_eq!(result.unwrap(), ResponseType::Str("¡Hola!".to_string()));
+
+        let getrange_cmd = Command::Getrange("Lucio".to_string(), -5, -2);
+        let result = getrange_cmd.execute_read(&store, None, None, None, None, None);
+        assert_eq!(result.unwrap(), ResponseType::Str("Hola".to_string()));
+
+        // Los índices son de bytes: pedir sólo el primero no entra en pánico
+        let getrange_cmd = Command::Getrange("Lucio".to_string(), 0, 0);
+        let result = getrange_cmd.execute_read(&store, None, None, None, None, None);
+        assert_eq!(result.unwrap(), ResponseType::Str("\u{FFFD}".to_string()));
+    }
+
+    /* MSET */
+
+    #[test]
+    fn mset_overwrites_keys_of_every_type() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        store
+            .set_db
+            .insert("Support".to_string(), HashSet::from(["Kiriko".to_string()]));
+
+        let cmd = Command::Mset(vec![
+            ("DPS".to_string(), "Sojourn".to_string()),
+            ("Support".to_string(), "Juno".to_string()),
+            ("Tank".to_string(), "Hazard".to_string()),
+        ]);
+        assert_eq!(
+            cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Str("OK".to_string())
+        );
+
+        assert!(store.list_db.is_empty());
+        assert!(store.set_db.is_empty());
+        assert_eq!(store.get("DPS").unwrap(), "Sojourn");
+        assert_eq!(store.get("Support").unwrap(), "Juno");
+        assert_eq!(store.get("Tank").unwrap(), "Hazard");
+    }
+
+    #[test]
+    fn mset_clears_the_ttl_of_every_key() {
+        let mut store = DataStore::new();
+        store.set("DPS".to_string(), "Junkrat".to_string());
+        store.set_ttl("DPS", Some(std::time::Duration::from_secs(10)));
+
+        let cmd = Command::Mset(vec![("DPS".to_string(), "Cassidy".to_string())]);
+        cmd.execute_write(&mut store).unwrap();
+
+        assert_eq!(store.remaining_ttl("DPS"), None);
+    }
+
     /* SET */
 
     #[test]
     fn set_works() {
         let mut store = DataStore::new();
-        let set_cmd = Command::Set("DPS_1".to_string(), "Junkrat".to_string());
+        let set_cmd = Command::Set(
+            "DPS_1".to_string(),
+            "Junkrat".to_string(),
+            SetOptions::default(),
+        );
         let result = set_cmd.execute_write(&mut store);
 
         assert!(result.is_ok());
@@ -465,7 +617,7 @@ mod command_tests {
             .list_db
             .insert("DPS".to_string(), vec!["Reaper".to_string()]);
 
-        let set_cmd = Command::Set("DPS".to_string(), "Mei".to_string());
+        let set_cmd = Command::Set("DPS".to_string(), "Mei".to_string(), SetOptions::default());
         let result = set_cmd.execute_write(&mut store);
 
         assert!(result.is_ok());
@@ -482,7 +634,11 @@ mod command_tests {
         set_aux.insert("Juno".to_string());
         store.set_db.insert("SUPS".to_string(), set_aux);
 
-        let set_cmd = Command::Set("SUPS".to_string(), "Mercy".to_string());
+        let set_cmd = Command::Set(
+            "SUPS".to_string(),
+            "Mercy".to_string(),
+            SetOptions::default(),
+        );
         let result = set_cmd.execute_write(&mut store);
 
         assert!(result.is_ok());
@@ -491,1643 +647,4572 @@ mod command_tests {
         assert!(store.set_db.get("SUPS").is_none());
     }
 
-    /* STRLEN */
-
     #[test]
-    fn strlen_works_for_an_empty_string() {
+    fn set_clears_the_ttl() {
         let mut store = DataStore::new();
-        store.string_db.insert("Empty".to_string(), "".to_string());
+        store.set("DPS_1".to_string(), "Junkrat".to_string());
+        store.set_ttl("DPS_1", Some(std::time::Duration::from_secs(10)));
 
-        let strlen_cmd = Command::Strlen("Empty".to_string());
-        let result = strlen_cmd.execute_read(&mut store, None, None, None, None, None);
-        let exp_value = 0;
-        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
+        let set_cmd = Command::Set(
+            "DPS_1".to_string(),
+            "Cassidy".to_string(),
+            SetOptions::default(),
+        );
+        set_cmd.execute_write(&mut store).unwrap();
+
+        assert_eq!(store.remaining_ttl("DPS_1"), None);
     }
 
-    #[test]
-    fn strlen_works_for_a_string() {
-        let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("Ashe".to_string(), "B.O.B".to_string());
+    /* SET NX / XX / EX */
 
-        let strlen_cmd = Command::Strlen("Ashe".to_string());
-        let result = strlen_cmd.execute_read(&mut store, None, None, None, None, None);
-        let exp_value = 5;
-        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
+    /// Ejecuta `SET DPS Sojourn` con las opciones dadas.
+    fn set_with(store: &mut DataStore, nx: bool, xx: bool, ex: Option<u64>) -> ResponseType {
+        let options = SetOptions { nx, xx, ex };
+        let cmd = Command::Set("DPS".to_string(), "Sojourn".to_string(), options);
+        cmd.execute_write(store).unwrap()
     }
 
     #[test]
-    fn strlen_0_for_no_value() {
-        let mut empty_store = DataStore::new();
+    fn set_nx_only_sets_a_missing_key() {
+        let mut store = DataStore::new();
+        assert_eq!(
+            set_with(&mut store, true, false, None),
+            ResponseType::Str("OK".to_string())
+        );
 
-        let strlen_cmd = Command::Strlen("No existe".to_string());
-        let result = strlen_cmd.execute_read(&mut empty_store, None, None, None, None, None);
-        let exp_value = 0;
-        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
+        store.set("DPS".to_string(), "Ashe".to_string());
+        assert_eq!(
+            set_with(&mut store, true, false, None),
+            ResponseType::Null(None)
+        );
+        assert_eq!(store.get("DPS").unwrap(), "Ashe");
     }
 
     #[test]
-    fn strlen_doesnt_work_for_a_list() {
+    fn set_xx_only_sets_an_existing_key() {
         let mut store = DataStore::new();
+        assert_eq!(
+            set_with(&mut store, false, true, None),
+            ResponseType::Null(None)
+        );
+        assert!(!store.contains_key("DPS"));
+
         store
             .list_db
-            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
-
-        let strlen_cmd = Command::Strlen("Ashe".to_string());
-        let result = strlen_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+            .insert("DPS".to_string(), vec!["Ashe".to_string()]);
+        assert_eq!(
+            set_with(&mut store, false, true, None),
+            ResponseType::Str("OK".to_string())
+        );
+        assert_eq!(store.get("DPS").unwrap(), "Sojourn");
+        assert!(store.list_db.is_empty());
     }
 
     #[test]
-    fn strlen_doesnt_work_for_a_set() {
+    fn set_ex_registers_the_expiry() {
         let mut store = DataStore::new();
-        let mut set = HashSet::new();
-        set.insert("King's Row".to_string());
-        store.set_db.insert("Maps".to_string(), set);
+        set_with(&mut store, false, false, Some(30));
 
-        let strlen_cmd = Command::Strlen("Maps".to_string());
-        let result = strlen_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        let remaining = store.remaining_ttl("DPS").unwrap();
+        assert!(remaining > std::time::Duration::from_secs(29));
     }
 
-    /* SUBSTR */
-
     #[test]
-    fn substr_works_for_an_string() {
+    fn set_ex_with_nx_on_missing_and_existing_keys() {
         let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("Llave1".to_string(), "Somos todos Montiel".to_string());
-        let substr_cmd = Command::Substr("Llave1".to_string(), 0, 4);
-        let string_expected = "Somos".to_string();
+        assert_eq!(
+            set_with(&mut store, true, false, Some(30)),
+            ResponseType::Str("OK".to_string())
+        );
+        assert!(store.remaining_ttl("DPS").is_some());
 
-        let result = substr_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(result.unwrap(), ResponseType::Str(string_expected));
+        store.set_ttl("DPS", None);
+        assert_eq!(
+            set_with(&mut store, true, false, Some(30)),
+            ResponseType::Null(None)
+        );
+        // La condición falló: no se registra el vencimiento
+        assert_eq!(store.remaining_ttl("DPS"), None);
     }
 
     #[test]
-    fn substr_doesnt_work_for_a_list() {
+    fn set_xx_with_ex_replaces_the_expiry() {
         let mut store = DataStore::new();
-        store.list_db.insert(
-            "Llave1".to_string(),
-            vec!["Somos todos Montiel".to_string()],
-        );
-        let substr_cmd = Command::Substr("Llave1".to_string(), 0, 4);
+        store.set("DPS".to_string(), "Ashe".to_string());
+        store.set_ttl("DPS", Some(std::time::Duration::from_secs(1000)));
 
-        let result = substr_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        set_with(&mut store, false, true, Some(30));
+        let remaining = store.remaining_ttl("DPS").unwrap();
+        assert!(remaining <= std::time::Duration::from_secs(30));
     }
 
+    /* SETNX */
+
     #[test]
-    fn substr_doesnt_work_for_a_set() {
+    fn setnx_sets_a_missing_key() {
         let mut store = DataStore::new();
-        let mut set = HashSet::new();
-        set.insert("King's Row".to_string());
-        store.set_db.insert("Maps".to_string(), set);
-        let substr_cmd = Command::Substr("Maps".to_string(), 0, 4);
 
-        let result = substr_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        let cmd = Command::Setnx("Tank".to_string(), "Hazard".to_string());
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+        assert_eq!(store.get("Tank").unwrap(), "Hazard");
     }
 
-    /* LIST TESTS */
+    #[test]
+    fn setnx_keeps_an_existing_key() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        store.set_ttl("DPS", Some(std::time::Duration::from_secs(10)));
 
-    /* LLEN */
+        let cmd = Command::Setnx("DPS".to_string(), "Sojourn".to_string());
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(0));
+        assert_eq!(store.list_db.get("DPS").unwrap().len(), 5);
+        assert!(store.remaining_ttl("DPS").is_some());
+    }
+
+    /* TTL */
 
     #[test]
-    fn llen_works_for_an_empty_list() {
+    fn append_keeps_the_ttl() {
         let mut store = DataStore::new();
-        store.list_db.insert("Empty".to_string(), vec![]);
+        store.set("DPS_1".to_string(), "Junk".to_string());
+        store.set_ttl("DPS_1", Some(std::time::Duration::from_secs(10)));
 
-        let llen_cmd = Command::Llen("Empty".to_string());
-        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
-        let exp_value = 0;
-        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
+        let cmd = Command::Append("DPS_1".to_string(), "rat".to_string());
+        cmd.execute_write(&mut store).unwrap();
+
+        assert_eq!(store.string_db.get("DPS_1").unwrap(), "Junkrat");
+        assert!(store.remaining_ttl("DPS_1").is_some());
     }
 
     #[test]
-    fn llen_works_for_a_list_with_one_items() {
+    fn incr_keeps_the_ttl() {
         let mut store = DataStore::new();
-        store
-            .list_db
-            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
+        store.set("Kills".to_string(), "41".to_string());
+        store.set_ttl("Kills", Some(std::time::Duration::from_secs(10)));
 
-        let llen_cmd = Command::Llen("Ashe".to_string());
-        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
-        let exp_value = 1;
-        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
+        let cmd = Command::Incr("Kills".to_string());
+        assert_eq!(
+            cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Int(42)
+        );
+        assert!(store.remaining_ttl("Kills").is_some());
     }
 
+    /* EXPIRE */
+
     #[test]
-    fn llen_works_for_a_list_with_multiple_items() {
+    fn expire_sets_the_deadline_of_an_existing_key() {
         let mut store = DataStore::new();
-        store.list_db.insert(
-            "Ashe".to_string(),
-            vec!["B.O.B".to_string(), "F.R.E.D".to_string()],
-        );
+        store.set("Tank".to_string(), "Mauga".to_string());
 
-        let llen_cmd = Command::Llen("Ashe".to_string());
-        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
-        let exp_value = 2;
-        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
+        let cmd = Command::Expire("Tank".to_string(), 30);
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+        let remaining = store.remaining_ttl("Tank").unwrap();
+        assert!(remaining > std::time::Duration::from_secs(29));
     }
 
     #[test]
-    fn llen_0_for_no_value() {
-        let mut empty_store = DataStore::new();
+    fn expire_on_a_missing_key_returns_zero() {
+        let mut store = DataStore::new();
 
-        let llen_cmd = Command::Llen("No existe".to_string());
-        let result = llen_cmd.execute_read(&mut empty_store, None, None, None, None, None);
-        let exp_value = 0;
-        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
+        let cmd = Command::Expire("Tank".to_string(), 30);
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(0));
+        assert_eq!(store.remaining_ttl("Tank"), None);
     }
 
     #[test]
-    fn llen_doesnt_work_for_a_string() {
-        let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("Ashe".to_string(), "B.O.B".to_string());
+    fn expire_with_zero_seconds_deletes_the_key() {
+        let mut store = set_up_data_store_with_multiple_items_list();
 
-        let llen_cmd = Command::Llen("Ashe".to_string());
-        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        let cmd = Command::Expire("DPS".to_string(), 0);
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+        assert!(!store.contains_key("DPS"));
     }
 
+    /* PERSIST */
+
     #[test]
-    fn llen_doesnt_work_for_a_set() {
+    fn persist_removes_the_expiry() {
         let mut store = DataStore::new();
-        let mut set = HashSet::new();
-        set.insert("King's Row".to_string());
-        store.set_db.insert("Maps".to_string(), set);
+        store.set("Tank".to_string(), "Mauga".to_string());
+        store.set_ttl("Tank", Some(std::time::Duration::from_secs(30)));
 
-        let llen_cmd = Command::Llen("Maps".to_string());
-        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        let cmd = Command::Persist("Tank".to_string());
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+        assert_eq!(store.remaining_ttl("Tank"), None);
+        assert_eq!(store.get("Tank").unwrap(), "Mauga");
     }
 
-    /* LPOP */
-
     #[test]
-    fn lpop_empty_list() {
+    fn persist_on_a_key_without_expiry_returns_zero() {
         let mut store = DataStore::new();
-        store.list_db.insert("EmptyList".to_string(), vec![]);
-
-        let lpop_cmd = Command::Lpop("EmptyList".to_string(), 1);
-        let result = lpop_cmd.execute_write(&mut store);
+        store.set("Tank".to_string(), "Mauga".to_string());
 
-        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert!(store.list_db.get("EmptyList").is_some());
+        let cmd = Command::Persist("Tank".to_string());
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(0));
+        assert!(!store.clear_expiry("Tank"));
     }
 
     #[test]
-    fn lpop_empty_list_with_0() {
+    fn persist_on_a_missing_key_returns_zero() {
         let mut store = DataStore::new();
-        store.list_db.insert("EmptyList".to_string(), vec![]);
 
-        let lpop_cmd = Command::Lpop("EmptyList".to_string(), 0);
-        let result = lpop_cmd.execute_write(&mut store);
-
-        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert!(store.list_db.get("EmptyList").is_some());
+        let cmd = Command::Persist("Tank".to_string());
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(0));
     }
 
     #[test]
-    fn lpop_list_with_one_item_0_arg() {
+    fn ttl_of_missing_and_persistent_keys() {
         let mut store = DataStore::new();
-        store
-            .list_db
-            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
+        store.set("Tank".to_string(), "Mauga".to_string());
 
-        let lpop_cmd = Command::Lpop("Ashe".to_string(), 0);
-        let result = lpop_cmd.execute_write(&mut store);
-
-        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert_eq!(store.list_db.get("Ashe").unwrap().len(), 1);
+        let missing = Command::Ttl("Support".to_string());
+        assert_eq!(
+            missing
+                .execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(-2)
+        );
+        let persistent = Command::Ttl("Tank".to_string());
+        assert_eq!(
+            persistent
+                .execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(-1)
+        );
     }
 
     #[test]
-    fn lpop_list_with_one_item_more_than_1_arg() {
+    fn ttl_returns_the_remaining_seconds() {
         let mut store = DataStore::new();
-        store
-            .list_db
-            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
-
-        let lpop_cmd = Command::Lpop("Ashe".to_string(), 1);
-        let result = lpop_cmd.execute_write(&mut store);
+        store.set("Tank".to_string(), "Mauga".to_string());
+        Command::Expire("Tank".to_string(), 100)
+            .execute_write(&mut store)
+            .unwrap();
 
+        let cmd = Command::Ttl("Tank".to_string());
         assert_eq!(
-            result.unwrap(),
-            ResponseType::List(vec!["B.O.B".to_string()])
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(100)
         );
-        assert_eq!(store.list_db.get("Ashe").unwrap().len(), 0);
     }
 
     #[test]
-    fn lpop_list_with_multiple_items_zero_arg() {
-        let mut store = set_up_data_store_with_multiple_items_list();
+    fn get_hides_an_expired_key_before_the_purge() {
+        let mut store = DataStore::new();
+        store.set("Tank".to_string(), "Mauga".to_string());
+        store
+            .expires
+            .insert("Tank".to_string(), std::time::Instant::now());
 
-        let lpop_cmd = Command::Lpop("DPS".to_string(), 0);
-        let result = lpop_cmd.execute_write(&mut store);
+        assert!(store.is_expired("Tank"));
+        assert_eq!(store.get("Tank"), None);
+        // El valor sigue guardado hasta que se purgue
+        assert!(store.string_db.contains_key("Tank"));
+    }
 
-        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert_eq!(store.list_db.get("DPS").unwrap().len(), 5);
+    /* INCR */
+
+    #[test]
+    fn incr_starts_a_missing_key_at_zero() {
+        let mut store = DataStore::new();
+        let cmd = Command::Incr("Kills".to_string());
+
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+        assert_eq!(store.string_db.get("Kills").unwrap(), "1");
     }
 
     #[test]
-    fn lpop_list_with_multiple_items_mid_arg() {
-        let mut store = set_up_data_store_with_multiple_items_list();
+    fn incr_fails_on_non_integer_and_overflow() {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Lucio".to_string());
+        store.set("Kills".to_string(), i64::MAX.to_string());
+
+        for (key, error) in [
+            ("Hero", "ERR value is not an integer or out of range"),
+            ("Kills", "ERR increment or decrement would overflow"),
+        ] {
+            let result = Command::Incr(key.to_string()).execute_write(&mut store);
+            assert_eq!(result.unwrap_err().to_string(), error);
+        }
+        assert_eq!(store.string_db.get("Hero").unwrap(), "Lucio");
+    }
 
-        let lpop_cmd = Command::Lpop("DPS".to_string(), 3);
-        let result = lpop_cmd.execute_write(&mut store);
+    /* DECR */
+
+    #[test]
+    fn decr_starts_a_missing_key_at_zero() {
+        let mut store = DataStore::new();
+        let cmd = Command::Decr("Deaths".to_string());
 
         assert_eq!(
-            result.unwrap(),
-            ResponseType::List(vec![
-                "Ashe".to_string(),
-                "F.R.E.D".to_string(),
-                "B.O.B".to_string()
-            ])
+            cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Int(-1)
         );
-        assert_eq!(store.list_db.get("DPS").unwrap().len(), 2);
-        if let Some(list) = store.list_db.get("DPS") {
-            assert!(list.contains(&"Torbjorn".to_string()));
-            assert!(list.contains(&"Echo".to_string()));
+        assert_eq!(store.string_db.get("Deaths").unwrap(), "-1");
+    }
+
+    #[test]
+    fn decr_fails_on_non_integer_and_underflow() {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Zenyatta".to_string());
+        store.set("Deaths".to_string(), i64::MIN.to_string());
+
+        for (key, error) in [
+            ("Hero", "ERR value is not an integer or out of range"),
+            ("Deaths", "ERR increment or decrement would overflow"),
+        ] {
+            let result = Command::Decr(key.to_string()).execute_write(&mut store);
+            assert_eq!(result.unwrap_err().to_string(), error);
         }
+        assert_eq!(
+            store.string_db.get("Deaths").unwrap(),
+            &i64::MIN.to_string()
+        );
     }
 
     #[test]
-    fn lpop_list_with_multiple_items_large_arg() {
-        let mut store = set_up_data_store_with_multiple_items_list();
+    fn incr_and_decr_reject_lists_and_sets() {
+        let mut store = DataStore::new();
+        store
+            .list_db
+            .insert("Tanks".to_string(), vec!["Orisa".to_string()]);
+        store
+            .set_db
+            .insert("Supports".to_string(), ["Ana".to_string()].into());
+
+        for key in ["Tanks", "Supports"] {
+            for cmd in [
+                Command::Incr(key.to_string()),
+                Command::Decr(key.to_string()),
+            ] {
+                assert!(matches!(
+                    cmd.execute_write(&mut store),
+                    Err(CommandError::WrongType)
+                ));
+            }
+        }
+    }
 
-        let lpop_cmd = Command::Lpop("DPS".to_string(), 50);
-        let result = lpop_cmd.execute_write(&mut store);
+    /* INCRBY / DECRBY */
+
+    #[test]
+    fn incrby_and_decrby_apply_the_delta() {
+        let mut store = DataStore::new();
+        store.set("Kills".to_string(), "10".to_string());
 
+        let cmd = Command::IncrBy("Kills".to_string(), 32);
         assert_eq!(
-            result.unwrap(),
-            ResponseType::List(vec![
-                "Ashe".to_string(),
-                "F.R.E.D".to_string(),
-                "B.O.B".to_string(),
-                "Torbjorn".to_string(),
-                "Echo".to_string(),
-            ])
+            cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Int(42)
+        );
+        let cmd = Command::DecrBy("Kills".to_string(), -8);
+        assert_eq!(
+            cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Int(50)
+        );
+        let cmd = Command::DecrBy("Assists".to_string(), 7);
+        assert_eq!(
+            cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Int(-7)
         );
-        assert_eq!(store.list_db.get("DPS").unwrap().len(), 0);
     }
 
     #[test]
-    fn lpop_wrongtype_str_with_0_arg() {
+    fn incrby_and_decrby_detect_overflow() {
         let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("WrongTypeStr".to_string(), "NotAList".to_string());
+        store.set("Kills".to_string(), i64::MAX.to_string());
+        store.set("Deaths".to_string(), "-2".to_string());
+
+        for cmd in [
+            Command::IncrBy("Kills".to_string(), 1),
+            Command::DecrBy("Kills".to_string(), -1),
+            Command::DecrBy("Deaths".to_string(), i64::MAX),
+            Command::IncrBy("Deaths".to_string(), i64::MIN),
+        ] {
+            assert_eq!(
+                cmd.execute_write(&mut store).unwrap_err().to_string(),
+                "ERR increment or decrement would overflow"
+            );
+        }
+        assert_eq!(store.string_db.get("Kills").unwrap(), &i64::MAX.to_string());
+        assert_eq!(store.string_db.get("Deaths").unwrap(), "-2");
+    }
 
-        let lpop_cmd = Command::Lpop("WrongTypeStr".to_string(), 0);
-        let result = lpop_cmd.execute_write(&mut store);
+    #[test]
+    fn incrby_fails_on_non_integer_value() {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Hazard".to_string());
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        let result = Command::IncrBy("Hero".to_string(), 5).execute_write(&mut store);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "ERR value is not an integer or out of range"
+        );
+        assert_eq!(store.string_db.get("Hero").unwrap(), "Hazard");
     }
 
     #[test]
-    fn lpop_wrongtype_str_with_more_than_1_arg() {
+    fn incrby_and_decrby_reject_list_keys() {
         let mut store = DataStore::new();
         store
-            .string_db
-            .insert("WrongTypeStr".to_string(), "NotAList".to_string());
+            .list_db
+            .insert("Tanks".to_string(), vec!["Mauga".to_string()]);
+
+        for cmd in [
+            Command::IncrBy("Tanks".to_string(), 2),
+            Command::DecrBy("Tanks".to_string(), 2),
+        ] {
+            assert!(matches!(
+                cmd.execute_write(&mut store),
+                Err(CommandError::WrongType)
+            ));
+        }
+    }
 
-        let lpop_cmd = Command::Lpop("WrongTypeStr".to_string(), 10);
-        let result = lpop_cmd.execute_write(&mut store);
+    /* STRLEN */
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    #[test]
+    fn strlen_works_for_an_empty_string() {
+        let mut store = DataStore::new();
+        store.string_db.insert("Empty".to_string(), "".to_string());
+
+        let strlen_cmd = Command::Strlen("Empty".to_string());
+        let result = strlen_cmd.execute_read(&mut store, None, None, None, None, None);
+        let exp_value = 0;
+        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
     }
 
     #[test]
-    fn lpop_wrongtype_set_with_0_arg() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-
-        let lpop_cmd = Command::Lpop("Maps".to_string(), 0);
-        let result = lpop_cmd.execute_write(&mut store);
+    fn strlen_works_for_a_string() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Ashe".to_string(), "B.O.B".to_string());
 
-        assert_eq!(result.unwrap(), ResponseType::Null(None));
+        let strlen_cmd = Command::Strlen("Ashe".to_string());
+        let result = strlen_cmd.execute_read(&mut store, None, None, None, None, None);
+        let exp_value = 5;
+        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
     }
 
     #[test]
-    fn lpop_wrongtype_set_with_more_than_1_arg() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-
-        let lpop_cmd = Command::Lpop("Maps".to_string(), 10);
-        let result = lpop_cmd.execute_write(&mut store);
+    fn strlen_0_for_no_value() {
+        let mut empty_store = DataStore::new();
 
-        assert_eq!(result.unwrap(), ResponseType::Null(None));
+        let strlen_cmd = Command::Strlen("No existe".to_string());
+        let result = strlen_cmd.execute_read(&mut empty_store, None, None, None, None, None);
+        let exp_value = 0;
+        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
     }
 
     #[test]
-    fn lpop_nonexistent_key_with_0_arg() {
+    fn strlen_doesnt_work_for_a_list() {
         let mut store = DataStore::new();
-        let lpop_cmd = Command::Lpop("NonExistentKey".to_string(), 0);
-        let result = lpop_cmd.execute_write(&mut store);
+        store
+            .list_db
+            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
 
-        assert_eq!(result.unwrap(), ResponseType::Null(None));
+        let strlen_cmd = Command::Strlen("Ashe".to_string());
+        let result = strlen_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
     }
 
     #[test]
-    fn lpop_nonexistent_key_with_more_than_1_arg() {
+    fn strlen_doesnt_work_for_a_set() {
         let mut store = DataStore::new();
-        let lpop_cmd = Command::Lpop("NonExistentKey".to_string(), 10);
-        let result = lpop_cmd.execute_write(&mut store);
+        let mut set = HashSet::new();
+        set.insert("King's Row".to_string());
+        store.set_db.insert("Maps".to_string(), set);
 
-        assert_eq!(result.unwrap(), ResponseType::Null(None));
+        let strlen_cmd = Command::Strlen("Maps".to_string());
+        let result = strlen_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
     }
 
-    /* LPUSH */
+    /* SUBSTR */
 
     #[test]
-    fn lpush_works_for_an_list_that_already_exists() {
+    fn substr_works_for_an_string() {
         let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Llave1".to_string(), "Somos todos Montiel".to_string());
+        let substr_cmd = Command::Substr("Llave1".to_string(), 0, 4);
+        let string_expected = "Somos".to_string();
 
-        // Crear una lista inicial con algunos elementos
+        let result = substr_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(result.unwrap(), ResponseType::Str(string_expected));
+    }
+
+    #[test]
+    fn substr_doesnt_work_for_a_list() {
+        let mut store = DataStore::new();
         store.list_db.insert(
-            "DPS".to_string(),
-            vec![
-                "Ashe".to_string(),
-                "F.R.E.D".to_string(),
-                "B.O.B".to_string(),
-            ],
+            "Llave1".to_string(),
+            vec!["Somos todos Montiel".to_string()],
         );
+        let substr_cmd = Command::Substr("Llave1".to_string(), 0, 4);
 
-        // Ejecutar el comando Lpush para agregar un elemento al principio
-        let lpush_cmd = Command::Lpush("DPS".to_string(), vec!["DVA".to_string()]);
-        let result = lpush_cmd.execute_write(&mut store);
+        let result = substr_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
 
-        // Verificar que el resultado sea la longitud de la lista después de la operación
-        assert_eq!(result.unwrap(), ResponseType::Int(4));
+    #[test]
+    fn substr_doesnt_work_for_a_set() {
+        let mut store = DataStore::new();
+        let mut set = HashSet::new();
+        set.insert("King's Row".to_string());
+        store.set_db.insert("Maps".to_string(), set);
+        let substr_cmd = Command::Substr("Maps".to_string(), 0, 4);
 
-        // Verificar que los elementos se hayan insertado correctamente
-        if let Some(list) = store.list_db.get("DPS") {
-            assert_eq!(list.len(), 4);
-            assert_eq!(list[0], "DVA".to_string());
-            assert_eq!(list[1], "Ashe".to_string());
-            assert_eq!(list[2], "F.R.E.D".to_string());
-            assert_eq!(list[3], "B.O.B".to_string());
-        }
+        let result = substr_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
     }
 
-    /* LRANGE */
+    #[test]
+    fn substr_with_accented_characters() {
+        let mut store = DataStore::new();
+        store.set("Lucio".to_string(), "¡Hola!".to_string());
+
+        let substr_cmd = Command::Substr("Lucio".to_string(), 2, 5);
+        let result = substr_cmd.execute_read(&store, None, None, None, None, None);
+        assert_eq!(result.unwrap(), ResponseType::Str("Hola".to_string()));
+
+        // El corte parte la `¡` en dos: los bytes sueltos se reemplazan
+        let substr_cmd = Command::Substr("Lucio".to_string(), 1, 3);
+        let result = substr_cmd.execute_read(&store, None, None, None, None, None);
+        assert_eq!(result.unwrap(), ResponseType::Str("\u{FFFD}Ho".to_string()));
+    }
+
+    /* SETRANGE */
 
     #[test]
-    fn lrange_empty_list() {
+    fn setrange_overwrites_part_of_a_string() {
         let mut store = DataStore::new();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, -1);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        store.set("Ashe".to_string(), "B.O.B".to_string());
 
-        match result.unwrap() {
-            ResponseType::List(list) => assert_eq!(list.len(), 0),
-            _ => assert!(false, "Se esperaba un List vacío"),
-        }
+        let cmd = Command::SetRange("Ashe".to_string(), 2, "X".to_string());
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(5));
+        assert_eq!(store.get("Ashe"), Some(&"B.X.B".to_string()));
     }
 
     #[test]
-    fn lrange_only_one_element_list() {
+    fn setrange_pads_with_zeros_past_the_end() {
         let mut store = DataStore::new();
-        store
-            .list_db
-            .insert("DPS".to_string(), vec!["Ashe".to_string()]);
+        store.set("Mei".to_string(), "Mei".to_string());
 
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, -1);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 1);
-                assert_eq!(list[0], "Ashe".to_string());
-            }
-            _ => assert!(false, "Se esperaba un List con 1 elemento"),
-        }
+        let cmd = Command::SetRange("Mei".to_string(), 5, "¡".to_string());
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(7));
+        assert_eq!(store.get("Mei"), Some(&"Mei\0\0¡".to_string()));
     }
 
     #[test]
-    fn lrange_multiple_elements_list() {
+    fn setrange_creates_missing_key() {
         let mut store = DataStore::new();
-        store.list_db.insert(
-            "DPS".to_string(),
-            vec![
-                "Ashe".to_string(),
-                "F.R.E.D".to_string(),
-                "B.O.B".to_string(),
-            ],
-        );
 
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, -1);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 3);
-                assert_eq!(list[0], "Ashe".to_string());
-                assert_eq!(list[1], "F.R.E.D".to_string());
-                assert_eq!(list[2], "B.O.B".to_string());
-            }
-            _ => assert!(false, "Se esperaba un List con 3 elementos"),
-        }
+        let cmd = Command::SetRange("Kiriko".to_string(), 2, "ok".to_string());
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(4));
+        assert_eq!(store.get("Kiriko"), Some(&"\0\0ok".to_string()));
     }
 
     #[test]
-    fn lrange_multiple_elements_list_only_reduced_slice() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 1, 3);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 3);
-                assert_eq!(list[0], "F.R.E.D".to_string());
-                assert_eq!(list[1], "B.O.B".to_string());
-                assert_eq!(list[2], "Torbjorn".to_string());
-            }
-            _ => assert!(false, "Se esperaba un List de 3 elementos"),
-        }
-    }
+    fn setrange_with_empty_value_does_not_create_key() {
+        let mut store = DataStore::new();
 
-    #[test]
-    fn lrange_reduced_slice_from_start() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, 3);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 4);
-                assert_eq!(list[0], "Ashe".to_string());
-                assert_eq!(list[1], "F.R.E.D".to_string());
-                assert_eq!(list[2], "B.O.B".to_string());
-                assert_eq!(list[3], "Torbjorn".to_string());
-            }
-            _ => assert!(false, "Se esperaba un List de 4 elementos"),
-        }
+        let cmd = Command::SetRange("Kiriko".to_string(), 10, "".to_string());
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(0));
+        assert!(!store.contains_key("Kiriko"));
     }
 
     #[test]
-    fn lrange_reduced_slice_until_end() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 3, -1);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 2);
-                assert_eq!(list[0], "Torbjorn".to_string());
-                assert_eq!(list[1], "Echo".to_string());
-            }
-            _ => assert!(false, "Se esperaba un List de 2 elementos"),
-        }
+    fn setrange_with_accented_characters() {
+        let mut store = DataStore::new();
+        store.set("Lucio".to_string(), "Lúcio".to_string());
+
+        let cmd = Command::SetRange("Lucio".to_string(), 6, "ball".to_string());
+        assert_eq!(
+            cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Int(10)
+        );
+        assert_eq!(store.get("Lucio"), Some(&"Lúcioball".to_string()));
+
+        // Pisar la mitad de la `ú` deja un byte suelto que se reemplaza
+        let cmd = Command::SetRange("Lucio".to_string(), 2, "u".to_string());
+        assert!(cmd.execute_write(&mut store).is_ok());
+        assert_eq!(store.get("Lucio"), Some(&"L\u{FFFD}ucioball".to_string()));
     }
 
     #[test]
-    fn lrange_out_of_bound_lower_limit() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), -1, -1);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 1);
-                assert_eq!(list[0], "Echo".to_string());
-            }
-            _ => assert!(false, "Se esperaba un List con 1 elemento"),
-        }
+    fn setrange_keeps_ttl() {
+        let mut store = DataStore::new();
+        store.set("Ashe".to_string(), "B.O.B".to_string());
+        store.set_ttl("Ashe", Some(std::time::Duration::from_secs(10)));
+
+        let cmd = Command::SetRange("Ashe".to_string(), 0, "b".to_string());
+        cmd.execute_write(&mut store).unwrap();
+
+        assert!(store.remaining_ttl("Ashe").is_some());
     }
 
     #[test]
-    fn lrange_out_of_bound_higher_upper_limit() {
+    fn setrange_doesnt_work_for_a_list() {
         let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 100, -1);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 0);
-            }
-            _ => assert!(false, "Se esperaba un List vacío"),
-        }
+
+        let cmd = Command::SetRange("DPS".to_string(), 0, "Sojourn".to_string());
+        let result = cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        assert_eq!(store.list_db["DPS"].len(), 5);
     }
 
     #[test]
-    fn lrange_out_of_bound_upper_limit() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 1, 100);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 4);
-                assert_eq!(list[0], "F.R.E.D".to_string());
-                assert_eq!(list[1], "B.O.B".to_string());
-                assert_eq!(list[2], "Torbjorn".to_string());
-                assert_eq!(list[3], "Echo".to_string());
-            }
-            _ => assert!(false, "Se esperaba un List de 4 elementos"),
-        }
+    fn setrange_rejects_strings_over_512mb() {
+        let mut store = DataStore::new();
+
+        let cmd = Command::SetRange("Kiriko".to_string(), 512 * 1024 * 1024, "x".to_string());
+        assert!(cmd.execute_write(&mut store).is_err());
+        assert!(!store.contains_key("Kiriko"));
     }
 
+    /* LIST TESTS */
+
+    /* LLEN */
+
     #[test]
-    fn lrange_out_of_bound_both_limits_repectively() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), -10, 100);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 5);
-                assert_eq!(list[0], "Ashe".to_string());
-                assert_eq!(list[1], "F.R.E.D".to_string());
-                assert_eq!(list[2], "B.O.B".to_string());
-                assert_eq!(list[3], "Torbjorn".to_string());
-                assert_eq!(list[4], "Echo".to_string());
-            }
-            _ => assert!(false, "Se esperaba un List de 5 elementos"),
-        }
+    fn llen_works_for_an_empty_list() {
+        let mut store = DataStore::new();
+        store.list_db.insert("Empty".to_string(), vec![]);
+
+        let llen_cmd = Command::Llen("Empty".to_string());
+        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
+        let exp_value = 0;
+        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
     }
 
     #[test]
-    fn lrange_both_are_higher_than_len() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 100, 100);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 0);
-            }
-            _ => assert!(false, "Se esperaba un List vacío"),
-        }
+    fn llen_works_for_a_list_with_one_items() {
+        let mut store = DataStore::new();
+        store
+            .list_db
+            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
+
+        let llen_cmd = Command::Llen("Ashe".to_string());
+        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
+        let exp_value = 1;
+        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
     }
 
     #[test]
-    fn lrange_lower_limit_is_higher_than_higher_limit() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 4, 3);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 0);
-            }
-            _ => assert!(false, "Se esperaba un List vacío"),
-        }
+    fn llen_works_for_a_list_with_multiple_items() {
+        let mut store = DataStore::new();
+        store.list_db.insert(
+            "Ashe".to_string(),
+            vec!["B.O.B".to_string(), "F.R.E.D".to_string()],
+        );
+
+        let llen_cmd = Command::Llen("Ashe".to_string());
+        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
+        let exp_value = 2;
+        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
     }
 
     #[test]
-    fn lrange_both_limits_are_equal() {
-        let mut store = set_up_data_store_with_multiple_items_list();
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 4, 4);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
-        assert_eq!(store.list_db.len(), 1);
-        match result.unwrap() {
-            ResponseType::List(list) => {
-                assert_eq!(list.len(), 1);
-                assert_eq!(list[0], "Echo".to_string());
-            }
-            _ => assert!(false, "Se esperaba un List con 1 elemento"),
-        }
+    fn llen_0_for_no_value() {
+        let mut empty_store = DataStore::new();
+
+        let llen_cmd = Command::Llen("No existe".to_string());
+        let result = llen_cmd.execute_read(&mut empty_store, None, None, None, None, None);
+        let exp_value = 0;
+        assert_eq!(result.unwrap(), ResponseType::Int(exp_value));
     }
 
     #[test]
-    fn lrange_doesnt_work_for_a_set_string() {
+    fn llen_doesnt_work_for_a_string() {
         let mut store = DataStore::new();
         store
             .string_db
-            .insert("DPS".to_string(), "Soldier:76".to_string());
-        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, -1);
-        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+            .insert("Ashe".to_string(), "B.O.B".to_string());
+
+        let llen_cmd = Command::Llen("Ashe".to_string());
+        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
         assert!(matches!(result.unwrap_err(), CommandError::WrongType));
     }
 
-    /* RPOP */
+    #[test]
+    fn llen_doesnt_work_for_a_set() {
+        let mut store = DataStore::new();
+        let mut set = HashSet::new();
+        set.insert("King's Row".to_string());
+        store.set_db.insert("Maps".to_string(), set);
+
+        let llen_cmd = Command::Llen("Maps".to_string());
+        let result = llen_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    /* LPOP */
 
     #[test]
-    fn rpop_empty_list() {
+    fn lpop_empty_list() {
         let mut store = DataStore::new();
         store.list_db.insert("EmptyList".to_string(), vec![]);
 
-        let rpop_cmd = Command::Rpop("EmptyList".to_string(), 1);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("EmptyList".to_string(), 1);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert!(store.list_db.get("EmptyList").is_some());
+        assert!(store.list_db.get("EmptyList").is_none());
     }
 
     #[test]
-    fn rpop_empty_list_with_0() {
+    fn lpop_empty_list_with_0() {
         let mut store = DataStore::new();
         store.list_db.insert("EmptyList".to_string(), vec![]);
 
-        let rpop_cmd = Command::Rpop("EmptyList".to_string(), 0);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("EmptyList".to_string(), 0);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert!(store.list_db.get("EmptyList").is_some());
+        assert!(store.list_db.get("EmptyList").is_none());
     }
 
     #[test]
-    fn rpop_list_with_one_item_0_arg() {
+    fn lpop_list_with_one_item_0_arg() {
         let mut store = DataStore::new();
         store
             .list_db
             .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
 
-        let rpop_cmd = Command::Rpop("Ashe".to_string(), 0);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("Ashe".to_string(), 0);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert_eq!(result.unwrap(), ResponseType::List(vec![]));
         assert_eq!(store.list_db.get("Ashe").unwrap().len(), 1);
     }
 
     #[test]
-    fn rpop_list_with_one_item_more_than_1_arg() {
+    fn lpop_list_with_one_item_more_than_1_arg() {
         let mut store = DataStore::new();
         store
             .list_db
             .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
 
-        let rpop_cmd = Command::Rpop("Ashe".to_string(), 1);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("Ashe".to_string(), 1);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert_eq!(
             result.unwrap(),
             ResponseType::List(vec!["B.O.B".to_string()])
         );
-        assert_eq!(store.list_db.get("Ashe").unwrap().len(), 0);
+        assert!(store.list_db.get("Ashe").is_none());
     }
 
     #[test]
-    fn rpop_list_with_multiple_items_zero_arg() {
+    fn lpop_list_with_multiple_items_zero_arg() {
         let mut store = set_up_data_store_with_multiple_items_list();
 
-        let rpop_cmd = Command::Rpop("DPS".to_string(), 0);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("DPS".to_string(), 0);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert_eq!(result.unwrap(), ResponseType::List(vec![]));
         assert_eq!(store.list_db.get("DPS").unwrap().len(), 5);
     }
 
     #[test]
-    fn rpop_list_with_multiple_items_mid_arg() {
+    fn lpop_list_with_multiple_items_mid_arg() {
         let mut store = set_up_data_store_with_multiple_items_list();
 
-        let rpop_cmd = Command::Rpop("DPS".to_string(), 3);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("DPS".to_string(), 3);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert_eq!(
             result.unwrap(),
             ResponseType::List(vec![
-                "Echo".to_string(),
-                "Torbjorn".to_string(),
+                "Ashe".to_string(),
+                "F.R.E.D".to_string(),
                 "B.O.B".to_string()
             ])
         );
         assert_eq!(store.list_db.get("DPS").unwrap().len(), 2);
         if let Some(list) = store.list_db.get("DPS") {
-            assert!(list.contains(&"Ashe".to_string()));
-            assert!(list.contains(&"F.R.E.D".to_string()));
+            assert!(list.contains(&"Torbjorn".to_string()));
+            assert!(list.contains(&"Echo".to_string()));
         }
     }
 
     #[test]
-    fn rpop_list_with_multiple_items_large_arg() {
+    fn lpop_list_with_multiple_items_large_arg() {
         let mut store = set_up_data_store_with_multiple_items_list();
 
-        let rpop_cmd = Command::Rpop("DPS".to_string(), 50);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("DPS".to_string(), 50);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert_eq!(
             result.unwrap(),
             ResponseType::List(vec![
-                "Echo".to_string(),
-                "Torbjorn".to_string(),
-                "B.O.B".to_string(),
-                "F.R.E.D".to_string(),
                 "Ashe".to_string(),
+                "F.R.E.D".to_string(),
+                "B.O.B".to_string(),
+                "Torbjorn".to_string(),
+                "Echo".to_string(),
             ])
         );
-        assert_eq!(store.list_db.get("DPS").unwrap().len(), 0);
+        assert!(store.list_db.get("DPS").is_none());
     }
 
     #[test]
-    fn rpop_wrongtype_str_with_0_arg() {
+    fn lpop_wrongtype_str_with_0_arg() {
         let mut store = DataStore::new();
         store
             .string_db
             .insert("WrongTypeStr".to_string(), "NotAList".to_string());
 
-        let rpop_cmd = Command::Rpop("WrongTypeStr".to_string(), 0);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("WrongTypeStr".to_string(), 0);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert!(matches!(result.unwrap_err(), CommandError::WrongType));
     }
 
     #[test]
-    fn rpop_wrongtype_str_with_more_than_1_arg() {
+    fn lpop_wrongtype_str_with_more_than_1_arg() {
         let mut store = DataStore::new();
         store
             .string_db
             .insert("WrongTypeStr".to_string(), "NotAList".to_string());
 
-        let rpop_cmd = Command::Rpop("WrongTypeStr".to_string(), 10);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("WrongTypeStr".to_string(), 10);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert!(matches!(result.unwrap_err(), CommandError::WrongType));
     }
 
     #[test]
-    fn rpop_wrongtype_set_with_0_arg() {
-        let mut store = set_up_data_store_with_multiple_items_set();
+    fn lpop_wrongtype_set_with_0_arg() {
+        let mut store = set_up_data_store_with_multiple_items_list();
 
-        let rpop_cmd = Command::Rpop("Maps".to_string(), 0);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("Maps".to_string(), 0);
+        let result = lpop_cmd.execute_write(&mut store);
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        assert_eq!(result.unwrap(), ResponseType::Null(None));
     }
 
     #[test]
-    fn rpop_wrongtype_set_with_more_than_1_arg() {
-        let mut store = set_up_data_store_with_multiple_items_set();
+    fn lpop_wrongtype_set_with_more_than_1_arg() {
+        let mut store = set_up_data_store_with_multiple_items_list();
 
-        let rpop_cmd = Command::Rpop("Maps".to_string(), 10);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("Maps".to_string(), 10);
+        let result = lpop_cmd.execute_write(&mut store);
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        assert_eq!(result.unwrap(), ResponseType::Null(None));
     }
 
     #[test]
-    fn rpop_nonexistent_key_with_0_arg() {
+    fn lpop_nonexistent_key_with_0_arg() {
         let mut store = DataStore::new();
-        let rpop_cmd = Command::Rpop("NonExistentKey".to_string(), 0);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("NonExistentKey".to_string(), 0);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert_eq!(result.unwrap(), ResponseType::Null(None));
     }
 
     #[test]
-    fn rpop_nonexistent_key_with_more_than_1_arg() {
+    fn lpop_nonexistent_key_with_more_than_1_arg() {
         let mut store = DataStore::new();
-        let rpop_cmd = Command::Rpop("NonExistentKey".to_string(), 10);
-        let result = rpop_cmd.execute_write(&mut store);
+        let lpop_cmd = Command::Lpop("NonExistentKey".to_string(), 10);
+        let result = lpop_cmd.execute_write(&mut store);
 
         assert_eq!(result.unwrap(), ResponseType::Null(None));
     }
 
-    /* RPUSH */
+    /* LPUSH */
 
     #[test]
-    fn rpush_with_no_previous_items_works() {
+    fn lpush_works_for_an_list_that_already_exists() {
         let mut store = DataStore::new();
-        let rpush_cmd = Command::Rpush("TANKS".to_string(), vec!["DVA".to_string()]);
-        let result = rpush_cmd.execute_write(&mut store);
-
-        assert_eq!(result.unwrap(), ResponseType::Int(1));
-        assert!(store.string_db.is_empty());
-        assert!(store.set_db.is_empty());
-        let list = store.list_db.get("TANKS").expect("Debe existir la lista");
-        assert_eq!(list.len(), 1);
-        assert_eq!(list[0], "DVA".to_string());
-    }
 
-    #[test]
-    fn rpush_with_previous_items_works() {
-        let mut store = DataStore::new();
-        store
-            .list_db
-            .insert("TANKS".to_string(), vec!["DVA".to_string()]);
-        let rpush_cmd = Command::Rpush(
-            "TANKS".to_string(),
-            vec!["Reinhardt".to_string(), "Orisa".to_string()],
+        // Crear una lista inicial con algunos elementos
+        store.list_db.insert(
+            "DPS".to_string(),
+            vec![
+                "Ashe".to_string(),
+                "F.R.E.D".to_string(),
+                "B.O.B".to_string(),
+            ],
         );
-        let result = rpush_cmd.execute_write(&mut store);
 
-        assert_eq!(result.unwrap(), ResponseType::Int(3));
-        assert_eq!(store.list_db.len(), 1);
-        let list = store.list_db.get("TANKS").expect("Debe existir la lista");
-        assert_eq!(list.len(), 3);
-        assert_eq!(list[0], "DVA".to_string());
-        assert_eq!(list[1], "Reinhardt".to_string());
-        assert_eq!(list[2], "Orisa".to_string());
-    }
+        // Ejecutar el comando Lpush para agregar un elemento al principio
+        let lpush_cmd = Command::Lpush("DPS".to_string(), vec!["DVA".to_string()]);
+        let result = lpush_cmd.execute_write(&mut store);
 
-    #[test]
-    fn rpush_doesnt_work_after_using_a_set_command() {
-        let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("SUPPORT".to_string(), "Kiriko".to_string());
+        // Verificar que el resultado sea la longitud de la lista después de la operación
+        assert_eq!(result.unwrap(), ResponseType::Int(4));
 
-        let rpush_cmd = Command::Rpush(
-            "SUPPORT".to_string(),
-            vec!["Ana".to_string(), "Moira".to_string()],
-        );
-        let result = rpush_cmd.execute_write(&mut store);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
-        assert_eq!(store.string_db.len(), 1);
-        assert_eq!(store.string_db.get("SUPPORT").unwrap(), "Kiriko");
+        // Verificar que los elementos se hayan insertado correctamente
+        if let Some(list) = store.list_db.get("DPS") {
+            assert_eq!(list.len(), 4);
+            assert_eq!(list[0], "DVA".to_string());
+            assert_eq!(list[1], "Ashe".to_string());
+            assert_eq!(list[2], "F.R.E.D".to_string());
+            assert_eq!(list[3], "B.O.B".to_string());
+        }
     }
 
-    /* SET TESTS */
-
-    /* SADD */
-
-    #[test]
-    fn sadd_creates_a_set() {
-        let mut store = DataStore::new();
-        let set_cmd = Command::Sadd(
-            "Maps".to_string(),
-            vec!["King's Row".to_string(), "Gilbraltar".to_string()],
-        );
-        let result = set_cmd.execute_write(&mut store);
-
-        // La función debe retornar la cantidad de elementos insertados.
-        assert_eq!(result.unwrap(), ResponseType::Int(2));
-
-        // Ahora se espera que "Maps" aparezca en el contenedor de sets.
-        assert_eq!(store.set_db.len(), 1);
-        let set = store.set_db.get("Maps").expect("Debe existir el set");
-        let mut aux = HashSet::new();
-        aux.insert("King's Row".to_string());
-        aux.insert("Gilbraltar".to_string());
-        assert_eq!(set, &aux);
-    }
+    /* LRANGE */
 
     #[test]
-    fn sadd_adds_to_current_set() {
+    fn lrange_empty_list() {
         let mut store = DataStore::new();
-        store.set_db.insert(
-            "Maps".to_string(),
-            HashSet::from(["King's Row".to_string(), "Gilbraltar".to_string()]),
-        );
-
-        let set_cmd = Command::Sadd("Maps".to_string(), vec!["Antartica".to_string()]);
-        let result = set_cmd.execute_write(&mut store);
-
-        assert_eq!(result.unwrap(), ResponseType::Int(1));
-        // Se espera que el set tenga 3 elementos.
-        let mut aux = HashSet::new();
-        aux.insert("King's Row".to_string());
-        aux.insert("Gilbraltar".to_string());
-        aux.insert("Antartica".to_string());
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, -1);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
 
-        assert_eq!(store.set_db.len(), 1);
-        let set = store.set_db.get("Maps").expect("Debe existir el set");
-        assert_eq!(set.len(), 3);
-        for expected in aux {
-            assert!(set.contains(&expected));
+        match result.unwrap() {
+            ResponseType::List(list) => assert_eq!(list.len(), 0),
+            _ => assert!(false, "Se esperaba un List vacío"),
         }
     }
 
     #[test]
-    fn sadd_doesnt_work_over_set_strings() {
+    fn lrange_only_one_element_list() {
         let mut store = DataStore::new();
-        // Primero, se inserta un STRING con el comando SET en lugar de un set.
         store
-            .string_db
-            .insert("Ashe".to_string(), "B.O.B".to_string());
-
-        let sadd_cmd = Command::Sadd("Ashe".to_string(), vec!["F.R.E.D".to_string()]);
-        let result_sadd = sadd_cmd.execute_write(&mut store);
+            .list_db
+            .insert("DPS".to_string(), vec!["Ashe".to_string()]);
 
-        assert!(matches!(result_sadd.unwrap_err(), CommandError::WrongType));
-        // La llave "Ashe" debe seguir en string_db.
-        assert_eq!(store.string_db.len(), 1);
-        assert_eq!(store.string_db.get("Ashe").unwrap(), "B.O.B");
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, -1);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 1);
+                assert_eq!(list[0], "Ashe".to_string());
+            }
+            _ => assert!(false, "Se esperaba un List con 1 elemento"),
+        }
     }
 
     #[test]
-    fn sadd_doesnt_work_over_lists() {
+    fn lrange_multiple_elements_list() {
         let mut store = DataStore::new();
-        // Insertamos una lista en "Ashe" mediante RPUSH.
-        store
-            .list_db
-            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
-
-        let sadd_cmd = Command::Sadd("Ashe".to_string(), vec!["F.R.E.D".to_string()]);
-        let result_sadd = sadd_cmd.execute_write(&mut store);
+        store.list_db.insert(
+            "DPS".to_string(),
+            vec![
+                "Ashe".to_string(),
+                "F.R.E.D".to_string(),
+                "B.O.B".to_string(),
+            ],
+        );
 
-        assert!(matches!(result_sadd.unwrap_err(), CommandError::WrongType));
-        // "Ashe" debe permanecer en el contenedor de listas.
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, -1);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
         assert_eq!(store.list_db.len(), 1);
-        let list = store.list_db.get("Ashe").unwrap();
-        assert_eq!(list, &vec!["B.O.B".to_string()]);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 3);
+                assert_eq!(list[0], "Ashe".to_string());
+                assert_eq!(list[1], "F.R.E.D".to_string());
+                assert_eq!(list[2], "B.O.B".to_string());
+            }
+            _ => assert!(false, "Se esperaba un List con 3 elementos"),
+        }
     }
 
-    /* SCARD */
-
     #[test]
-    fn scard_works_over_no_set() {
-        let mut store = DataStore::new();
-        let scard_cmd = Command::Scard("Maps".to_string());
-        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
-
-        // Al no existir el set se retorna 0.
-        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    fn lrange_multiple_elements_list_only_reduced_slice() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 1, 3);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 3);
+                assert_eq!(list[0], "F.R.E.D".to_string());
+                assert_eq!(list[1], "B.O.B".to_string());
+                assert_eq!(list[2], "Torbjorn".to_string());
+            }
+            _ => assert!(false, "Se esperaba un List de 3 elementos"),
+        }
     }
 
     #[test]
-    fn scard_works_over_one_item_set() {
-        let mut store = DataStore::new();
-        store.set_db.insert(
-            "Genji".to_string(),
+    fn lrange_reduced_slice_from_start() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, 3);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 4);
+                assert_eq!(list[0], "Ashe".to_string());
+                assert_eq!(list[1], "F.R.E.D".to_string());
+                assert_eq!(list[2], "B.O.B".to_string());
+                assert_eq!(list[3], "Torbjorn".to_string());
+            }
+            _ => assert!(false, "Se esperaba un List de 4 elementos"),
+        }
+    }
+
+    #[test]
+    fn lrange_reduced_slice_until_end() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 3, -1);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 2);
+                assert_eq!(list[0], "Torbjorn".to_string());
+                assert_eq!(list[1], "Echo".to_string());
+            }
+            _ => assert!(false, "Se esperaba un List de 2 elementos"),
+        }
+    }
+
+    #[test]
+    fn lrange_out_of_bound_lower_limit() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), -1, -1);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 1);
+                assert_eq!(list[0], "Echo".to_string());
+            }
+            _ => assert!(false, "Se esperaba un List con 1 elemento"),
+        }
+    }
+
+    #[test]
+    fn lrange_out_of_bound_higher_upper_limit() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 100, -1);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 0);
+            }
+            _ => assert!(false, "Se esperaba un List vacío"),
+        }
+    }
+
+    #[test]
+    fn lrange_out_of_bound_upper_limit() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 1, 100);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 4);
+                assert_eq!(list[0], "F.R.E.D".to_string());
+                assert_eq!(list[1], "B.O.B".to_string());
+                assert_eq!(list[2], "Torbjorn".to_string());
+                assert_eq!(list[3], "Echo".to_string());
+            }
+            _ => assert!(false, "Se esperaba un List de 4 elementos"),
+        }
+    }
+
+    #[test]
+    fn lrange_out_of_bound_both_limits_repectively() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), -10, 100);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 5);
+                assert_eq!(list[0], "Ashe".to_string());
+                assert_eq!(list[1], "F.R.E.D".to_string());
+                assert_eq!(list[2], "B.O.B".to_string());
+                assert_eq!(list[3], "Torbjorn".to_string());
+                assert_eq!(list[4], "Echo".to_string());
+            }
+            _ => assert!(false, "Se esperaba un List de 5 elementos"),
+        }
+    }
+
+    #[test]
+    fn lrange_both_are_higher_than_len() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 100, 100);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 0);
+            }
+            _ => assert!(false, "Se esperaba un List vacío"),
+        }
+    }
+
+    #[test]
+    fn lrange_lower_limit_is_higher_than_higher_limit() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 4, 3);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 0);
+            }
+            _ => assert!(false, "Se esperaba un List vacío"),
+        }
+    }
+
+    #[test]
+    fn lrange_both_limits_are_equal() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 4, 4);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(store.list_db.len(), 1);
+        match result.unwrap() {
+            ResponseType::List(list) => {
+                assert_eq!(list.len(), 1);
+                assert_eq!(list[0], "Echo".to_string());
+            }
+            _ => assert!(false, "Se esperaba un List con 1 elemento"),
+        }
+    }
+
+    #[test]
+    fn lrange_doesnt_work_for_a_set_string() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("DPS".to_string(), "Soldier:76".to_string());
+        let lrange_cmd = Command::Lrange("DPS".to_string(), 0, -1);
+        let result = lrange_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    /* LINDEX */
+
+    /// Ejecuta LINDEX sobre la clave.
+    fn lindex(store: &DataStore, key: &str, index: i64) -> Result<ResponseType, CommandError> {
+        let cmd = Command::Lindex(key.to_string(), index);
+        cmd.execute_read(store, None, None, None, None, None)
+    }
+
+    #[test]
+    fn lindex_empty_list() {
+        let store = DataStore::new();
+        assert_eq!(lindex(&store, "DPS", 0).unwrap(), ResponseType::Null(None));
+    }
+
+    #[test]
+    fn lindex_from_start_and_end() {
+        let store = set_up_data_store_with_multiple_items_list();
+        assert_eq!(
+            lindex(&store, "DPS", 0).unwrap(),
+            ResponseType::Str("Ashe".to_string())
+        );
+        assert_eq!(
+            lindex(&store, "DPS", 2).unwrap(),
+            ResponseType::Str("B.O.B".to_string())
+        );
+        assert_eq!(
+            lindex(&store, "DPS", -1).unwrap(),
+            ResponseType::Str("Echo".to_string())
+        );
+        assert_eq!(
+            lindex(&store, "DPS", -5).unwrap(),
+            ResponseType::Str("Ashe".to_string())
+        );
+    }
+
+    #[test]
+    fn lindex_out_of_bound_limits() {
+        let store = set_up_data_store_with_multiple_items_list();
+        assert_eq!(lindex(&store, "DPS", 5).unwrap(), ResponseType::Null(None));
+        assert_eq!(lindex(&store, "DPS", -6).unwrap(), ResponseType::Null(None));
+    }
+
+    #[test]
+    fn lindex_doesnt_work_for_a_string() {
+        let mut store = DataStore::new();
+        store.set("DPS".to_string(), "Soldier:76".to_string());
+        assert!(matches!(
+            lindex(&store, "DPS", 0).unwrap_err(),
+            CommandError::WrongType
+        ));
+    }
+
+    /* LSET */
+
+    #[test]
+    fn lset_replaces_from_start_and_end() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+
+        let cmd = Command::Lset("DPS".to_string(), 1, "Sojourn".to_string());
+        assert_eq!(
+            cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Str("OK".to_string())
+        );
+        let cmd = Command::Lset("DPS".to_string(), -1, "Venture".to_string());
+        cmd.execute_write(&mut store).unwrap();
+
+        let list = store.list_db.get("DPS").unwrap();
+        assert_eq!(list[1], "Sojourn");
+        assert_eq!(list[4], "Venture");
+        assert_eq!(list.len(), 5);
+    }
+
+    #[test]
+    fn lset_out_of_bound_limits() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+
+        for index in [5, -6] {
+            let cmd = Command::Lset("DPS".to_string(), index, "Sojourn".to_string());
+            match cmd.execute_write(&mut store).unwrap_err() {
+                CommandError::Custom(msg) => assert_eq!(msg, "ERR index out of range"),
+                other => panic!("Error inesperado: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn lset_on_a_missing_key() {
+        let mut store = DataStore::new();
+
+        let cmd = Command::Lset("DPS".to_string(), 0, "Sojourn".to_string());
+        match cmd.execute_write(&mut store).unwrap_err() {
+            CommandError::Custom(msg) => assert_eq!(msg, "ERR no such key"),
+            other => panic!("Error inesperado: {:?}", other),
+        }
+        assert!(!store.contains_key("DPS"));
+    }
+
+    #[test]
+    fn lset_doesnt_work_for_a_set() {
+        let mut store = DataStore::new();
+        store
+            .set_db
+            .insert("DPS".to_string(), HashSet::from(["Ashe".to_string()]));
+
+        let cmd = Command::Lset("DPS".to_string(), 0, "Sojourn".to_string());
+        assert!(matches!(
+            cmd.execute_write(&mut store).unwrap_err(),
+            CommandError::WrongType
+        ));
+    }
+
+    /* LREM */
+
+    fn set_up_data_store_with_repeated_items_list() -> DataStore {
+        let mut store = DataStore::new();
+        let list = ["Ana", "Kiriko", "Ana", "Lúcio", "Ana", "Mercy"]
+            .iter()
+            .map(|hero| hero.to_string())
+            .collect();
+        store.list_db.insert("Support".to_string(), list);
+        store
+    }
+
+    fn support_list(store: &DataStore) -> Vec<&str> {
+        store
+            .list_db
+            .get("Support")
+            .unwrap()
+            .iter()
+            .map(String::as_str)
+            .collect()
+    }
+
+    #[test]
+    fn lrem_positive_count_removes_from_head() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+
+        let cmd = Command::Lrem("Support".to_string(), 2, "Ana".to_string());
+
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(2));
+        assert_eq!(
+            support_list(&store),
+            vec!["Kiriko", "Lúcio", "Ana", "Mercy"]
+        );
+    }
+
+    #[test]
+    fn lrem_negative_count_removes_from_tail() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+
+        let cmd = Command::Lrem("Support".to_string(), -2, "Ana".to_string());
+
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(2));
+        assert_eq!(
+            support_list(&store),
+            vec!["Ana", "Kiriko", "Lúcio", "Mercy"]
+        );
+    }
+
+    #[test]
+    fn lrem_zero_count_removes_every_occurrence() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+
+        let cmd = Command::Lrem("Support".to_string(), 0, "Ana".to_string());
+
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(3));
+        assert_eq!(support_list(&store), vec!["Kiriko", "Lúcio", "Mercy"]);
+    }
+
+    #[test]
+    fn lrem_missing_value_or_key_removes_nothing() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+
+        let cmd = Command::Lrem("Support".to_string(), 0, "Moira".to_string());
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(0));
+        assert_eq!(support_list(&store).len(), 6);
+
+        let cmd = Command::Lrem("Tank".to_string(), 1, "Moira".to_string());
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(0));
+        assert!(!store.contains_key("Tank"));
+    }
+
+    #[test]
+    fn lrem_deletes_the_key_when_the_list_is_empty() {
+        let mut store = DataStore::new();
+        store.list_db.insert(
+            "Tank".to_string(),
+            vec!["Mauga".to_string(), "Mauga".to_string()],
+        );
+
+        let cmd = Command::Lrem("Tank".to_string(), 0, "Mauga".to_string());
+
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(2));
+        assert!(!store.contains_key("Tank"));
+    }
+
+    #[test]
+    fn lrem_doesnt_work_for_a_string_or_set() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Perú".to_string(), "Illari".to_string());
+        store
+            .set_db
+            .insert("DPS".to_string(), HashSet::from(["Ashe".to_string()]));
+
+        for key in ["Perú", "DPS"] {
+            let cmd = Command::Lrem(key.to_string(), 0, "Ashe".to_string());
+            assert!(matches!(
+                cmd.execute_write(&mut store).unwrap_err(),
+                CommandError::WrongType
+            ));
+        }
+    }
+
+    /* LINSERT */
+
+    fn linsert(store: &mut DataStore, key: &str, place: InsertWhere, pivot: &str) -> ResponseType {
+        Command::Linsert(
+            key.to_string(),
+            place,
+            pivot.to_string(),
+            "Juno".to_string(),
+        )
+        .execute_write(store)
+        .unwrap()
+    }
+
+    #[test]
+    fn linsert_before_the_first_match() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+
+        let result = linsert(&mut store, "Support", InsertWhere::Before, "Ana");
+
+        assert_eq!(result, ResponseType::Int(7));
+        assert_eq!(
+            support_list(&store),
+            vec!["Juno", "Ana", "Kiriko", "Ana", "Lúcio", "Ana", "Mercy"]
+        );
+    }
+
+    #[test]
+    fn linsert_after_the_pivot() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+
+        // El pivote es la primera aparición, aunque haya otras después
+        let result = linsert(&mut store, "Support", InsertWhere::After, "Ana");
+        assert_eq!(result, ResponseType::Int(7));
+        let result = linsert(&mut store, "Support", InsertWhere::After, "Mercy");
+        assert_eq!(result, ResponseType::Int(8));
+
+        assert_eq!(
+            support_list(&store),
+            vec![
+                "Ana", "Juno", "Kiriko", "Ana", "Lúcio", "Ana", "Mercy", "Juno"
+            ]
+        );
+    }
+
+    #[test]
+    fn linsert_pivot_not_found() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+
+        let result = linsert(&mut store, "Support", InsertWhere::Before, "Moira");
+
+        assert_eq!(result, ResponseType::Int(-1));
+        assert_eq!(support_list(&store).len(), 6);
+    }
+
+    #[test]
+    fn linsert_on_a_missing_key() {
+        let mut store = DataStore::new();
+
+        let result = linsert(&mut store, "Support", InsertWhere::After, "Ana");
+
+        assert_eq!(result, ResponseType::Int(0));
+        assert!(!store.contains_key("Support"));
+    }
+
+    #[test]
+    fn linsert_doesnt_work_for_a_string_or_set() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Perú".to_string(), "Illari".to_string());
+        store
+            .set_db
+            .insert("DPS".to_string(), HashSet::from(["Ashe".to_string()]));
+
+        for key in ["Perú", "DPS"] {
+            let cmd = Command::Linsert(
+                key.to_string(),
+                InsertWhere::Before,
+                "Ashe".to_string(),
+                "Juno".to_string(),
+            );
+            assert!(matches!(
+                cmd.execute_write(&mut store).unwrap_err(),
+                CommandError::WrongType
+            ));
+        }
+    }
+
+    /* LMOVE */
+
+    fn lmove(
+        store: &mut DataStore,
+        source: &str,
+        destination: &str,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> ResponseType {
+        Command::LMove(source.to_string(), destination.to_string(), from, to)
+            .execute_write(store)
+            .unwrap()
+    }
+
+    #[test]
+    fn lmove_rpoplpush_moves_the_tail_to_the_head() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+        store
+            .list_db
+            .insert("Picks".to_string(), vec!["Brigitte".to_string()]);
+
+        let result = lmove(
+            &mut store,
+            "Support",
+            "Picks",
+            ListEnd::Right,
+            ListEnd::Left,
+        );
+
+        assert_eq!(result, ResponseType::Str("Mercy".to_string()));
+        assert_eq!(
+            support_list(&store),
+            vec!["Ana", "Kiriko", "Ana", "Lúcio", "Ana"]
+        );
+        assert_eq!(
+            store.list_db.get("Picks").unwrap(),
+            &vec!["Mercy".to_string(), "Brigitte".to_string()]
+        );
+    }
+
+    #[test]
+    fn lmove_creates_the_destination_and_deletes_an_empty_source() {
+        let mut store = DataStore::new();
+        store
+            .list_db
+            .insert("Queue".to_string(), vec!["Illari".to_string()]);
+
+        let result = lmove(&mut store, "Queue", "Picks", ListEnd::Left, ListEnd::Right);
+
+        assert_eq!(result, ResponseType::Str("Illari".to_string()));
+        assert!(!store.contains_key("Queue"));
+        assert_eq!(
+            store.list_db.get("Picks").unwrap(),
+            &vec!["Illari".to_string()]
+        );
+    }
+
+    #[test]
+    fn lmove_same_key_rotates_the_list() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+
+        let result = lmove(
+            &mut store,
+            "Support",
+            "Support",
+            ListEnd::Right,
+            ListEnd::Left,
+        );
+        assert_eq!(result, ResponseType::Str("Mercy".to_string()));
+        assert_eq!(
+            support_list(&store),
+            vec!["Mercy", "Ana", "Kiriko", "Ana", "Lúcio", "Ana"]
+        );
+
+        let result = lmove(
+            &mut store,
+            "Support",
+            "Support",
+            ListEnd::Left,
+            ListEnd::Right,
+        );
+        assert_eq!(result, ResponseType::Str("Mercy".to_string()));
+        assert_eq!(
+            support_list(&store),
+            vec!["Ana", "Kiriko", "Ana", "Lúcio", "Ana", "Mercy"]
+        );
+    }
+
+    #[test]
+    fn lmove_missing_source_returns_null() {
+        let mut store = DataStore::new();
+
+        let result = lmove(&mut store, "Queue", "Picks", ListEnd::Right, ListEnd::Left);
+
+        assert_eq!(result, ResponseType::Null(None));
+        assert!(!store.contains_key("Picks"));
+    }
+
+    #[test]
+    fn lmove_requires_both_keys_to_be_lists() {
+        let mut store = set_up_data_store_with_repeated_items_list();
+        store
+            .string_db
+            .insert("Perú".to_string(), "Illari".to_string());
+
+        for (source, destination) in [("Support", "Perú"), ("Perú", "Support")] {
+            let cmd = Command::LMove(
+                source.to_string(),
+                destination.to_string(),
+                ListEnd::Right,
+                ListEnd::Left,
+            );
+            assert!(matches!(
+                cmd.execute_write(&mut store).unwrap_err(),
+                CommandError::WrongType
+            ));
+        }
+        // El origen no se tocó
+        assert_eq!(support_list(&store).len(), 6);
+    }
+
+    /* RPOP */
+
+    #[test]
+    fn rpop_empty_list() {
+        let mut store = DataStore::new();
+        store.list_db.insert("EmptyList".to_string(), vec![]);
+
+        let rpop_cmd = Command::Rpop("EmptyList".to_string(), 1);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
+        assert!(store.list_db.get("EmptyList").is_none());
+    }
+
+    #[test]
+    fn rpop_empty_list_with_0() {
+        let mut store = DataStore::new();
+        store.list_db.insert("EmptyList".to_string(), vec![]);
+
+        let rpop_cmd = Command::Rpop("EmptyList".to_string(), 0);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
+        assert!(store.list_db.get("EmptyList").is_none());
+    }
+
+    #[test]
+    fn rpop_list_with_one_item_0_arg() {
+        let mut store = DataStore::new();
+        store
+            .list_db
+            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
+
+        let rpop_cmd = Command::Rpop("Ashe".to_string(), 0);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
+        assert_eq!(store.list_db.get("Ashe").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rpop_list_with_one_item_more_than_1_arg() {
+        let mut store = DataStore::new();
+        store
+            .list_db
+            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
+
+        let rpop_cmd = Command::Rpop("Ashe".to_string(), 1);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert_eq!(
+            result.unwrap(),
+            ResponseType::List(vec!["B.O.B".to_string()])
+        );
+        assert!(store.list_db.get("Ashe").is_none());
+    }
+
+    #[test]
+    fn rpop_list_with_multiple_items_zero_arg() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+
+        let rpop_cmd = Command::Rpop("DPS".to_string(), 0);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
+        assert_eq!(store.list_db.get("DPS").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn rpop_list_with_multiple_items_mid_arg() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+
+        let rpop_cmd = Command::Rpop("DPS".to_string(), 3);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert_eq!(
+            result.unwrap(),
+            ResponseType::List(vec![
+                "Echo".to_string(),
+                "Torbjorn".to_string(),
+                "B.O.B".to_string()
+            ])
+        );
+        assert_eq!(store.list_db.get("DPS").unwrap().len(), 2);
+        if let Some(list) = store.list_db.get("DPS") {
+            assert!(list.contains(&"Ashe".to_string()));
+            assert!(list.contains(&"F.R.E.D".to_string()));
+        }
+    }
+
+    #[test]
+    fn rpop_list_with_multiple_items_large_arg() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+
+        let rpop_cmd = Command::Rpop("DPS".to_string(), 50);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert_eq!(
+            result.unwrap(),
+            ResponseType::List(vec![
+                "Echo".to_string(),
+                "Torbjorn".to_string(),
+                "B.O.B".to_string(),
+                "F.R.E.D".to_string(),
+                "Ashe".to_string(),
+            ])
+        );
+        assert!(store.list_db.get("DPS").is_none());
+    }
+
+    #[test]
+    fn rpop_wrongtype_str_with_0_arg() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("WrongTypeStr".to_string(), "NotAList".to_string());
+
+        let rpop_cmd = Command::Rpop("WrongTypeStr".to_string(), 0);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn rpop_wrongtype_str_with_more_than_1_arg() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("WrongTypeStr".to_string(), "NotAList".to_string());
+
+        let rpop_cmd = Command::Rpop("WrongTypeStr".to_string(), 10);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn rpop_wrongtype_set_with_0_arg() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+
+        let rpop_cmd = Command::Rpop("Maps".to_string(), 0);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn rpop_wrongtype_set_with_more_than_1_arg() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+
+        let rpop_cmd = Command::Rpop("Maps".to_string(), 10);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn rpop_nonexistent_key_with_0_arg() {
+        let mut store = DataStore::new();
+        let rpop_cmd = Command::Rpop("NonExistentKey".to_string(), 0);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Null(None));
+    }
+
+    #[test]
+    fn rpop_nonexistent_key_with_more_than_1_arg() {
+        let mut store = DataStore::new();
+        let rpop_cmd = Command::Rpop("NonExistentKey".to_string(), 10);
+        let result = rpop_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Null(None));
+    }
+
+    /* RPUSH */
+
+    #[test]
+    fn rpush_with_no_previous_items_works() {
+        let mut store = DataStore::new();
+        let rpush_cmd = Command::Rpush("TANKS".to_string(), vec!["DVA".to_string()]);
+        let result = rpush_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(1));
+        assert!(store.string_db.is_empty());
+        assert!(store.set_db.is_empty());
+        let list = store.list_db.get("TANKS").expect("Debe existir la lista");
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0], "DVA".to_string());
+    }
+
+    #[test]
+    fn rpush_with_previous_items_works() {
+        let mut store = DataStore::new();
+        store
+            .list_db
+            .insert("TANKS".to_string(), vec!["DVA".to_string()]);
+        let rpush_cmd = Command::Rpush(
+            "TANKS".to_string(),
+            vec!["Reinhardt".to_string(), "Orisa".to_string()],
+        );
+        let result = rpush_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(3));
+        assert_eq!(store.list_db.len(), 1);
+        let list = store.list_db.get("TANKS").expect("Debe existir la lista");
+        assert_eq!(list.len(), 3);
+        assert_eq!(list[0], "DVA".to_string());
+        assert_eq!(list[1], "Reinhardt".to_string());
+        assert_eq!(list[2], "Orisa".to_string());
+    }
+
+    #[test]
+    fn rpush_doesnt_work_after_using_a_set_command() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("SUPPORT".to_string(), "Kiriko".to_string());
+
+        let rpush_cmd = Command::Rpush(
+            "SUPPORT".to_string(),
+            vec!["Ana".to_string(), "Moira".to_string()],
+        );
+        let result = rpush_cmd.execute_write(&mut store);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        assert_eq!(store.string_db.len(), 1);
+        assert_eq!(store.string_db.get("SUPPORT").unwrap(), "Kiriko");
+    }
+
+    /* SET TESTS */
+
+    /* SADD */
+
+    #[test]
+    fn sadd_creates_a_set() {
+        let mut store = DataStore::new();
+        let set_cmd = Command::Sadd(
+            "Maps".to_string(),
+            vec!["King's Row".to_string(), "Gilbraltar".to_string()],
+        );
+        let result = set_cmd.execute_write(&mut store);
+
+        // La función debe retornar la cantidad de elementos insertados.
+        assert_eq!(result.unwrap(), ResponseType::Int(2));
+
+        // Ahora se espera que "Maps" aparezca en el contenedor de sets.
+        assert_eq!(store.set_db.len(), 1);
+        let set = store.set_db.get("Maps").expect("Debe existir el set");
+        let mut aux = HashSet::new();
+        aux.insert("King's Row".to_string());
+        aux.insert("Gilbraltar".to_string());
+        assert_eq!(set, &aux);
+    }
+
+    #[test]
+    fn sadd_adds_to_current_set() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Maps".to_string(),
+            HashSet::from(["King's Row".to_string(), "Gilbraltar".to_string()]),
+        );
+
+        let set_cmd = Command::Sadd("Maps".to_string(), vec!["Antartica".to_string()]);
+        let result = set_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(1));
+        // Se espera que el set tenga 3 elementos.
+        let mut aux = HashSet::new();
+        aux.insert("King's Row".to_string());
+        aux.insert("Gilbraltar".to_string());
+        aux.insert("Antartica".to_string());
+
+        assert_eq!(store.set_db.len(), 1);
+        let set = store.set_db.get("Maps").expect("Debe existir el set");
+        assert_eq!(set.len(), 3);
+        for expected in aux {
+            assert!(set.contains(&expected));
+        }
+    }
+
+    #[test]
+    fn sadd_doesnt_work_over_set_strings() {
+        let mut store = DataStore::new();
+        // Primero, se inserta un STRING con el comando SET en lugar de un set.
+        store
+            .string_db
+            .insert("Ashe".to_string(), "B.O.B".to_string());
+
+        let sadd_cmd = Command::Sadd("Ashe".to_string(), vec!["F.R.E.D".to_string()]);
+        let result_sadd = sadd_cmd.execute_write(&mut store);
+
+        assert!(matches!(result_sadd.unwrap_err(), CommandError::WrongType));
+        // La llave "Ashe" debe seguir en string_db.
+        assert_eq!(store.string_db.len(), 1);
+        assert_eq!(store.string_db.get("Ashe").unwrap(), "B.O.B");
+    }
+
+    #[test]
+    fn sadd_doesnt_work_over_lists() {
+        let mut store = DataStore::new();
+        // Insertamos una lista en "Ashe" mediante RPUSH.
+        store
+            .list_db
+            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
+
+        let sadd_cmd = Command::Sadd("Ashe".to_string(), vec!["F.R.E.D".to_string()]);
+        let result_sadd = sadd_cmd.execute_write(&mut store);
+
+        assert!(matches!(result_sadd.unwrap_err(), CommandError::WrongType));
+        // "Ashe" debe permanecer en el contenedor de listas.
+        assert_eq!(store.list_db.len(), 1);
+        let list = store.list_db.get("Ashe").unwrap();
+        assert_eq!(list, &vec!["B.O.B".to_string()]);
+    }
+
+    /* SCARD */
+
+    #[test]
+    fn scard_works_over_no_set() {
+        let mut store = DataStore::new();
+        let scard_cmd = Command::Scard("Maps".to_string());
+        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        // Al no existir el set se retorna 0.
+        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    }
+
+    #[test]
+    fn scard_works_over_one_item_set() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Genji".to_string(),
             HashSet::from(["I need healing".to_string()]),
         );
 
-        let scard_cmd = Command::Scard("Genji".to_string());
-        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
+        let scard_cmd = Command::Scard("Genji".to_string());
+        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(1));
+
+        let set = store.set_db.get("Genji").unwrap();
+        assert_eq!(set.len(), 1);
+        assert!(set.contains("I need healing"));
+    }
+
+    #[test]
+    fn scard_works_over_multiple_items_set() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Maps".to_string(),
+            HashSet::from([
+                "El Dorado".to_string(),
+                "Petra".to_string(),
+                "Busan".to_string(),
+            ]),
+        );
+
+        let scard_cmd = Command::Scard("Maps".to_string());
+        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(3));
+
+        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let set = store.set_db.get("Maps").unwrap();
+        assert_eq!(set, &expected);
+    }
+
+    #[test]
+    fn scard_doesnt_work_over_set_strings() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Hammond".to_string(), "Ball".to_string());
+
+        let scard_cmd = Command::Scard("Hammond".to_string());
+        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        // "Hammond" debe permanecer en string_db.
+        assert_eq!(store.string_db.get("Hammond").unwrap(), "Ball");
+    }
+
+    #[test]
+    fn scard_doesnt_work_over_lists() {
+        let mut store = DataStore::new();
+        store
+            .list_db
+            .insert("Hammond".to_string(), vec!["Ball".to_string()]);
+
+        let scard_cmd = Command::Scard("Hammond".to_string());
+        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    /* SISMEMBER */
+
+    #[test]
+    fn sismember_works_for_non_existent_set() {
+        let mut store = DataStore::new();
+        let sismemeber_cmd = Command::Sismember("Game modes".to_string(), "Archives".to_string());
+        let result = sismemeber_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        // Al no existir la clave, se retorna 0.
+        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    }
+
+    #[test]
+    fn sismember_works_for_non_existent_value() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Maps".to_string(),
+            HashSet::from([
+                "El Dorado".to_string(),
+                "Petra".to_string(),
+                "Busan".to_string(),
+            ]),
+        );
+
+        let sismemeber_cmd = Command::Sismember("Maps".to_string(), "Gilbraltar".to_string());
+        let result = sismemeber_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        // Se espera 0 ya que "Gilbraltar" no está en el set.
+        assert_eq!(result.unwrap(), ResponseType::Int(0));
+
+        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let set = store.set_db.get("Maps").unwrap();
+        assert_eq!(set, &expected);
+    }
+
+    #[test]
+    fn sismember_works_for_one_item_set() {
+        let mut store = DataStore::new();
+        store
+            .set_db
+            .insert("Maps".to_string(), HashSet::from(["El Dorado".to_string()]));
+
+        let sismember_cmd = Command::Sismember("Maps".to_string(), "El Dorado".to_string());
+        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(1));
+
+        let expected: HashSet<String> = ["El Dorado"].iter().map(|s| s.to_string()).collect();
+        let set = store.set_db.get("Maps").unwrap();
+        assert_eq!(set, &expected);
+    }
+
+    #[test]
+    fn sismember_works_for_multiple_items_set() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Maps".to_string(),
+            HashSet::from([
+                "El Dorado".to_string(),
+                "Petra".to_string(),
+                "Busan".to_string(),
+            ]),
+        );
+
+        let sismember_cmd = Command::Sismember("Maps".to_string(), "Petra".to_string());
+        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(1));
+
+        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let set = store.set_db.get("Maps").unwrap();
+        assert_eq!(set, &expected);
+    }
+
+    #[test]
+    fn sismember_works_for_multiple_items_set_at_beggining() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Maps".to_string(),
+            HashSet::from([
+                "El Dorado".to_string(),
+                "Petra".to_string(),
+                "Busan".to_string(),
+            ]),
+        );
+
+        let sismember_cmd = Command::Sismember("Maps".to_string(), "El Dorado".to_string());
+        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(1));
+
+        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let set = store.set_db.get("Maps").unwrap();
+        assert_eq!(set, &expected);
+    }
+
+    #[test]
+    fn sismember_works_for_multiple_items_set_at_end() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Maps".to_string(),
+            HashSet::from([
+                "El Dorado".to_string(),
+                "Petra".to_string(),
+                "Busan".to_string(),
+            ]),
+        );
+
+        let sismember_cmd = Command::Sismember("Maps".to_string(), "Busan".to_string());
+        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(1));
+
+        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let set = store.set_db.get("Maps").unwrap();
+        assert_eq!(set, &expected);
+    }
+
+    #[test]
+    fn sismember_doesnt_work_for_set_strings() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Mei".to_string(), "Iceberg".to_string());
+
+        let sismember_cmd = Command::Sismember("Mei".to_string(), "Iceberg".to_string());
+        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        // "Mei" debe permanecer en string_db.
+        assert_eq!(store.string_db.get("Mei").unwrap(), "Iceberg");
+    }
+
+    #[test]
+    fn sismember_doesnt_work_for_lists() {
+        let mut store = DataStore::new();
+        // Insertar una lista en "DPS" por ejemplo.
+        store.list_db.insert(
+            "DPS".to_string(),
+            vec![
+                "Ashe".to_string(),
+                "F.R.E.D".to_string(),
+                "B.O.B".to_string(),
+                "Torbjorn".to_string(),
+                "Echo".to_string(),
+            ],
+        );
+
+        let sismember_cmd = Command::Sismember("DPS".to_string(), "F.R.E.D".to_string());
+        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        // La clave "DPS" debe seguir en list_db y sin cambios.
+        let list = store.list_db.get("DPS").unwrap();
+        assert_eq!(list.len(), 5);
+        assert_eq!(list[0], "Ashe".to_string());
+        assert_eq!(list[1], "F.R.E.D".to_string());
+        assert_eq!(list[2], "B.O.B".to_string());
+        assert_eq!(list[3], "Torbjorn".to_string());
+        assert_eq!(list[4], "Echo".to_string());
+    }
+
+    /* SMEMBERS */
+
+    #[test]
+    fn smembers_works_properly_over_an_empty_set() {
+        let mut store = DataStore::new();
+        let smem_cmd = Command::Smembers("Winton".to_string());
+        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        // Al no existir la clave "Winton" se devuelve un set vacío.
+        assert_eq!(result.unwrap(), ResponseType::Set(HashSet::new()));
+    }
+
+    #[test]
+    fn smembers_works_properly_over_one_item_set() {
+        let mut store = DataStore::new();
+        store
+            .set_db
+            .insert("Winton".to_string(), HashSet::from(["Honey".to_string()]));
+
+        let smem_cmd = Command::Smembers("Winton".to_string());
+        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        match result.unwrap() {
+            ResponseType::Set(set) => {
+                assert_eq!(set.len(), 1);
+                assert!(set.contains("Honey"));
+            }
+            _ => assert!(false, "Se esperaba un ResponseType::Set"),
+        }
+        // Se verifica internamente
+        assert_eq!(store.set_db.len(), 1);
+        let set = store.set_db.get("Winton").unwrap();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn smembers_works_properly_over_multiple_items_set() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Winton".to_string(),
+            HashSet::from(["Honey".to_string(), "Glasses".to_string()]),
+        );
+
+        let smem_cmd = Command::Smembers("Winton".to_string());
+        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        match result.unwrap() {
+            ResponseType::Set(set) => {
+                assert_eq!(set.len(), 2);
+                assert!(set.contains("Honey"));
+                assert!(set.contains("Glasses"));
+            }
+            _ => assert!(false, "Se esperaba un ResponseType::Set"),
+        }
+        // Verificamos el estado interno.
+        let set = store.set_db.get("Winton").unwrap();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn smembers_doesnt_work_over_set_strings() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Ashe".to_string(), "B.O.B".to_string());
+
+        let smem_cmd = Command::Smembers("Ashe".to_string());
+        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        // "Ashe" debe seguir en el contenedor de strings.
+        assert_eq!(store.string_db.len(), 1);
+        assert_eq!(store.string_db.get("Ashe").unwrap(), "B.O.B");
+    }
+
+    #[test]
+    fn smembers_doesnt_work_over_lists() {
+        let mut store = DataStore::new();
+        // Inserta una lista en "Maps" por medio de RPUSH.
+        store
+            .list_db
+            .insert("Maps".to_string(), vec!["Oasis".to_string()]);
+
+        let smem_cmd = Command::Smembers("Maps".to_string());
+        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        // La clave "Maps" debe permanecer en list_db.
+        let list = store.list_db.get("Maps").unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0], "Oasis".to_string());
+    }
+
+    /* SINTER / SUNION / SDIFF */
+
+    /// Crea un `DataStore` con los sets `"Tank"`, `"Damage"` y `"Flex"`.
+    fn set_up_data_store_with_role_sets() -> DataStore {
+        let mut store = DataStore::new();
+        let roles = [
+            ("Tank", vec!["D.Va", "Zarya", "Hazard"]),
+            ("Damage", vec!["Sojourn", "Hazard", "Zarya"]),
+            ("Flex", vec!["Zarya", "Echo"]),
+        ];
+        for (role, heroes) in roles {
+            store.set_db.insert(
+                role.to_string(),
+                heroes.into_iter().map(String::from).collect(),
+            );
+        }
+        store
+    }
+
+    /// Ejecuta el comando y devuelve el set resultante.
+    fn set_result(store: &DataStore, cmd: Command) -> HashSet<String> {
+        match cmd
+            .execute_read(store, None, None, None, None, None)
+            .unwrap()
+        {
+            ResponseType::Set(set) => set,
+            other => panic!("Respuesta inesperada: {:?}", other),
+        }
+    }
+
+    fn hero_set(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn role_keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn sinter_keeps_members_of_every_set() {
+        let store = set_up_data_store_with_role_sets();
+        assert_eq!(
+            set_result(&store, Command::Sinter(role_keys(&["Tank", "Damage"]))),
+            hero_set(&["Zarya", "Hazard"])
+        );
+        assert_eq!(
+            set_result(
+                &store,
+                Command::Sinter(role_keys(&["Tank", "Damage", "Flex"]))
+            ),
+            hero_set(&["Zarya"])
+        );
+    }
+
+    #[test]
+    fn sunion_joins_every_set() {
+        let store = set_up_data_store_with_role_sets();
+        assert_eq!(
+            set_result(
+                &store,
+                Command::Sunion(role_keys(&["Tank", "Damage", "Flex"]))
+            ),
+            hero_set(&["D.Va", "Zarya", "Hazard", "Sojourn", "Echo"])
+        );
+    }
+
+    #[test]
+    fn sdiff_subtracts_from_the_first_set() {
+        let store = set_up_data_store_with_role_sets();
+        assert_eq!(
+            set_result(&store, Command::Sdiff(role_keys(&["Tank", "Damage"]))),
+            hero_set(&["D.Va"])
+        );
+        assert_eq!(
+            set_result(
+                &store,
+                Command::Sdiff(role_keys(&["Damage", "Tank", "Flex"]))
+            ),
+            hero_set(&["Sojourn"])
+        );
+    }
+
+    #[test]
+    fn set_operations_treat_a_missing_key_as_empty() {
+        let store = set_up_data_store_with_role_sets();
+        assert!(set_result(&store, Command::Sinter(role_keys(&["Tank", "Support"]))).is_empty());
+        assert_eq!(
+            set_result(&store, Command::Sunion(role_keys(&["Flex", "Support"]))),
+            hero_set(&["Zarya", "Echo"])
+        );
+        assert_eq!(
+            set_result(&store, Command::Sdiff(role_keys(&["Flex", "Support"]))),
+            hero_set(&["Zarya", "Echo"])
+        );
+        assert!(set_result(&store, Command::Sdiff(role_keys(&["Support", "Flex"]))).is_empty());
+    }
+
+    #[test]
+    fn set_operations_fail_with_a_non_set_key() {
+        let mut store = set_up_data_store_with_role_sets();
+        store.set("Support".to_string(), "Juno".to_string());
+
+        for cmd in [
+            Command::Sinter(role_keys(&["Tank", "Support"])),
+            Command::Sunion(role_keys(&["Tank", "Support"])),
+            Command::Sdiff(role_keys(&["Tank", "Support"])),
+        ] {
+            let result = cmd.execute_read(&store, None, None, None, None, None);
+            assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        }
+    }
+
+    /* SMOVE */
+
+    #[test]
+    fn smove_works_for_an_empty_set() {
+        let mut store = DataStore::new();
+        let smove_cmd = Command::SMove(
+            "Maps".to_string(),
+            "Maps2".to_string(),
+            "El Dorado".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+
+        // Al no existir el set "Maps", no se mueve nada.
+        assert_eq!(store.set_db.len(), 0);
+        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    }
+
+    #[test]
+    fn smove_works_for_an_non_empty_set() {
+        let mut store = DataStore::new();
+
+        // Crear el conjunto de origen con 3 elementos.
+        store.set_db.insert(
+            "SourceSet".to_string(),
+            HashSet::from([
+                "El Dorado".to_string(),
+                "Petra".to_string(),
+                "Busan".to_string(),
+            ]),
+        );
+
+        // Crear el conjunto de destino vacío.
+        store
+            .set_db
+            .insert("DestinationSet".to_string(), HashSet::new());
+
+        // Mover "Petra" de SourceSet a DestinationSet.
+        let smove_cmd = Command::SMove(
+            "SourceSet".to_string(),
+            "DestinationSet".to_string(),
+            "Petra".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+
+        // Se espera que se mueva 1 (exitosamente).
+        assert_eq!(result.unwrap(), ResponseType::Int(1));
+
+        // Verificar que "Petra" ya no se encuentre en SourceSet.
+        let source_set = store
+            .set_db
+            .get("SourceSet")
+            .expect("Debe existir SourceSet");
+        assert_eq!(source_set.len(), 2);
+        assert!(source_set.contains("El Dorado"));
+        assert!(source_set.contains("Busan"));
+        assert!(!source_set.contains("Petra"));
+
+        // Verificar que "Petra" se haya insertado en DestinationSet.
+        let dest_set = store
+            .set_db
+            .get("DestinationSet")
+            .expect("Debe existir DestinationSet");
+        assert_eq!(dest_set.len(), 1);
+        assert!(dest_set.contains("Petra"));
+    }
+
+    #[test]
+    fn smove_doesnt_work_for_both_src_and_dst_strings() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Hammond".to_string(), "Ball".to_string());
+        store
+            .string_db
+            .insert("Winton".to_string(), "Honey".to_string());
+        let smove_cmd = Command::SMove(
+            "Hammond".to_string(),
+            "Winton".to_string(),
+            "Ball".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn smove_doesnt_work_for_src_string() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Hammond".to_string(), "Ball".to_string());
+        let mut aux = HashSet::new();
+        aux.insert("Glasses".to_string());
+        aux.insert("Honey".to_string());
+        store.set_db.insert("Winton".to_string(), aux);
+
+        let smove_cmd = Command::SMove(
+            "Hammond".to_string(),
+            "Winton".to_string(),
+            "Hammond".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn smove_doesnt_work_for_dst_string() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Hammond".to_string(), "Ball".to_string());
+        let mut aux = HashSet::new();
+        aux.insert("Glasses".to_string());
+        aux.insert("Honey".to_string());
+        store.set_db.insert("Winton".to_string(), aux);
+
+        let smove_cmd = Command::SMove(
+            "Winton".to_string(),
+            "Hammond".to_string(),
+            "Honey".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn smove_doesnt_work_for_both_src_and_dst_lists() {
+        let mut store = DataStore::new();
+        store.list_db.insert("Hammond".to_string(), vec![]);
+        store.list_db.insert("Winton".to_string(), vec![]);
+        let smove_cmd = Command::SMove(
+            "Hammond".to_string(),
+            "Winton".to_string(),
+            "Ball".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn smove_doesnt_work_for_src_list() {
+        let mut store = DataStore::new();
+        let mut aux = HashSet::new();
+        aux.insert("Ball".to_string());
+        store.set_db.insert("Hammond".to_string(), aux);
+        store
+            .list_db
+            .insert("Winton".to_string(), vec!["Glasses".to_string()]);
+        let smove_cmd = Command::SMove(
+            "Winton".to_string(),
+            "Hammond".to_string(),
+            "Glasses".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn smove_doesnt_work_for_dest_list() {
+        let mut store = DataStore::new();
+        let mut aux = HashSet::new();
+        aux.insert("Ball".to_string());
+        store.set_db.insert("Hammond".to_string(), aux);
+        store.list_db.insert("Winton".to_string(), vec![]);
+        let smove_cmd = Command::SMove(
+            "Hammond".to_string(),
+            "Winton".to_string(),
+            "Ball".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn smove_works_for_both_non_existent_sets() {
+        let mut empty_store = DataStore::new();
+        let smove_cmd = Command::SMove(
+            "Hammond".to_string(),
+            "Winton".to_string(),
+            "Ball".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut empty_store);
+        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    }
+
+    #[test]
+    fn smove_works_for_non_existent_src_set() {
+        let mut store = DataStore::new();
+        let mut aux = HashSet::new();
+        aux.insert("Ball".to_string());
+        store.set_db.insert("Hammond".to_string(), aux);
+        let smove_cmd = Command::SMove(
+            "Winton".to_string(),
+            "Hammond".to_string(),
+            "Glasses".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    }
+
+    #[test]
+    fn smove_works_for_non_existent_dst_set() {
+        let mut store = DataStore::new();
+        let mut aux = HashSet::new();
+        aux.insert("Ball".to_string());
+        store.set_db.insert("Hammond".to_string(), aux);
+        let smove_cmd = Command::SMove(
+            "Hammond".to_string(),
+            "Winton".to_string(),
+            "Glasses".to_string(),
+        );
+        let result = smove_cmd.execute_write(&mut store);
+        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    }
+
+    /* SPOP */
+
+    #[test]
+    fn spop_empty_set_0_arg() {
+        let mut store = DataStore::new();
+        let set = HashSet::new();
+        store.set_db.insert("Maps".to_string(), set);
+        let spop_cmd = Command::Spop("Maps".to_string(), 0);
+        let result = spop_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
+        assert!(store.set_db.get("Maps").is_none());
+    }
+
+    #[test]
+    fn spop_empty_set_bigger_arg() {
+        let mut store = DataStore::new();
+        let set = HashSet::new();
+        store.set_db.insert("Maps".to_string(), set);
+        let spop_cmd = Command::Spop("Maps".to_string(), 5);
+        let result = spop_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
+        assert!(store.set_db.get("Maps").is_none());
+    }
+
+    #[test]
+    fn spop_set_with_one_item() {
+        let mut store = DataStore::new();
+        store
+            .set_db
+            .insert("DPS".to_string(), HashSet::from(["Soldier:76".to_string()]));
+
+        let spop_cmd = Command::Spop("DPS".to_string(), 1);
+        let result = spop_cmd.execute_write(&mut store);
+
+        assert_eq!(
+            result.unwrap(),
+            ResponseType::List(vec!["Soldier:76".to_string()])
+        );
+        assert!(store.set_db.get("DPS").is_none());
+    }
+
+    #[test]
+    fn spop_set_with_one_item_twice() {
+        let mut store = DataStore::new();
+        store
+            .set_db
+            .insert("DPS".to_string(), HashSet::from(["Soldier:76".to_string()]));
+
+        let spop_cmd = Command::Spop("DPS".to_string(), 1);
+        let _ = spop_cmd.execute_write(&mut store);
+
+        let spop_cmd_again = Command::Spop("DPS".to_string(), 1);
+        let result = spop_cmd_again.execute_write(&mut store);
+
+        // El primer SPOP vació el set y borró la clave
+        assert_eq!(result.unwrap(), ResponseType::Null(None));
+        assert!(store.set_db.get("DPS").is_none());
+    }
+
+    #[test]
+    fn spop_set_with_few_items_zero_arg() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "DPS".to_string(),
+            HashSet::from([
+                "Echo".to_string(),
+                "Pharah".to_string(),
+                "Sombra".to_string(),
+            ]),
+        );
+
+        let spop_cmd = Command::Spop("DPS".to_string(), 0);
+        let result = spop_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
+        assert_eq!(store.set_db.get("DPS").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn spop_set_with_few_items_middle_arg() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+
+        let spop_cmd = Command::Spop("Maps".to_string(), 2);
+        let result = spop_cmd.execute_write(&mut store);
+
+        let result_list = match result.unwrap() {
+            ResponseType::List(list) => list,
+            _ => panic!("Expected a list response"),
+        };
+
+        // No sabés que se va a ir
+        assert_eq!(result_list.len(), 2);
+        assert_eq!(store.set_db.get("Maps").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn spop_set_with_few_items_large_arg() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+
+        let spop_cmd = Command::Spop("Maps".to_string(), 50);
+        let result = spop_cmd.execute_write(&mut store);
+
+        let result_list = match result.unwrap() {
+            ResponseType::List(list) => list,
+            _ => panic!("Expected a list response"),
+        };
+
+        assert_eq!(result_list.len(), 3);
+        assert!(result_list.contains(&"El Dorado".to_string()));
+        assert!(result_list.contains(&"Petra".to_string()));
+        assert!(result_list.contains(&"Busan".to_string()));
+        assert!(store.set_db.get("Maps").is_none());
+    }
+
+    #[test]
+    fn spop_wrongtype_str() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Perú".to_string(), "Illari".to_string());
+
+        let spop_cmd = Command::Spop("Perú".to_string(), 1);
+        let result = spop_cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn spop_wrongtype_list() {
+        let mut store = DataStore::new();
+        store
+            .list_db
+            .insert("AUS".to_string(), vec!["Junk*".to_string()]);
+
+        let spop_cmd = Command::Spop("AUS".to_string(), 1);
+        let result = spop_cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    #[test]
+    fn spop_nonexistent_key() {
+        let mut store = DataStore::new();
+        let spop_cmd = Command::Spop("NonExistentKey".to_string(), 1);
+        let result = spop_cmd.execute_write(&mut store);
+        assert_eq!(result.unwrap(), ResponseType::Null(None));
+    }
+
+    /* SRANDMEMBER */
+
+    fn srandmember(store: &mut DataStore, key: &str, count: Option<i64>) -> ResponseType {
+        Command::SRandMember(key.to_string(), count)
+            .execute_read(store, None, None, None, None, None)
+            .unwrap()
+    }
+
+    fn srandmember_list(store: &mut DataStore, key: &str, count: i64) -> Vec<String> {
+        match srandmember(store, key, Some(count)) {
+            ResponseType::List(list) => list,
+            other => panic!("Expected a list response, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn srandmember_without_count_returns_one_member() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+
+        let ResponseType::Str(member) = srandmember(&mut store, "Maps", None) else {
+            panic!("Expected a single member");
+        };
+
+        assert!(store.set_db.get("Maps").unwrap().contains(&member));
+        assert_eq!(store.set_db.get("Maps").unwrap().len(), 3);
+        assert_eq!(
+            srandmember(&mut store, "Rialto", None),
+            ResponseType::Null(None)
+        );
+    }
+
+    #[test]
+    fn srandmember_positive_count_returns_distinct_members() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+
+        let members = srandmember_list(&mut store, "Maps", 2);
+
+        assert_eq!(members.len(), 2);
+        assert_ne!(members[0], members[1]);
+        let set = store.set_db.get("Maps").unwrap();
+        assert!(members.iter().all(|member| set.contains(member)));
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn srandmember_count_larger_than_set_returns_whole_set() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+
+        let members: HashSet<String> = srandmember_list(&mut store, "Maps", 50)
+            .into_iter()
+            .collect();
+
+        assert_eq!(&members, store.set_db.get("Maps").unwrap());
+        assert!(srandmember_list(&mut store, "Maps", 0).is_empty());
+        assert!(srandmember_list(&mut store, "Rialto", 5).is_empty());
+    }
+
+    #[test]
+    fn srandmember_negative_count_allows_repeats() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+
+        let members = srandmember_list(&mut store, "Maps", -10);
+
+        // Diez elementos de un set de tres: alguno se repite
+        assert_eq!(members.len(), 10);
+        let set = store.set_db.get("Maps").unwrap();
+        assert!(members.iter().all(|member| set.contains(member)));
+        let distinct: HashSet<&String> = members.iter().collect();
+        assert!(distinct.len() < members.len());
+        assert_eq!(set.len(), 3);
+        assert!(srandmember_list(&mut store, "Rialto", -3).is_empty());
+    }
+
+    #[test]
+    fn srandmember_wrongtype_str_and_list() {
+        let mut store = DataStore::new();
+        store
+            .string_db
+            .insert("Perú".to_string(), "Illari".to_string());
+        store
+            .list_db
+            .insert("AUS".to_string(), vec!["Junk*".to_string()]);
+
+        for key in ["Perú", "AUS"] {
+            let result = Command::SRandMember(key.to_string(), Some(1))
+                .execute_read(&mut store, None, None, None, None, None);
+            assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        }
+    }
+
+    /* SREM */
+
+    #[test]
+    fn srem_removes_only_existing_members() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+        let srem_cmd = Command::Srem(
+            "Maps".to_string(),
+            vec!["Petra".to_string(), "Ilios".to_string()],
+        );
+        let result = srem_cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Int(1));
+        assert!(!store.set_db.get("Maps").unwrap().contains("Petra"));
+        assert_eq!(store.set_db.get("Maps").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn srem_of_the_last_member_removes_the_set() {
+        let mut store = DataStore::new();
+        store
+            .set_db
+            .insert("Maps".to_string(), HashSet::from(["Runasapi".to_string()]));
+        store.set_ttl("Maps", Some(std::time::Duration::from_secs(10)));
+
+        let srem_cmd = Command::Srem("Maps".to_string(), vec!["Runasapi".to_string()]);
+        assert_eq!(
+            srem_cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Int(1)
+        );
+
+        assert!(!store.contains_key("Maps"));
+        assert_eq!(store.remaining_ttl("Maps"), None);
+    }
+
+    /* EXISTS */
+
+    #[test]
+    fn exists_counts_keys_of_every_type() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        store.set("Tank".to_string(), "Ramattra".to_string());
+        store.set_db.insert(
+            "Support".to_string(),
+            HashSet::from(["Kiriko".to_string(), "Lucio".to_string()]),
+        );
+
+        let keys = vec!["DPS", "Tank", "Flex", "Support", "Healer"];
+        let cmd = Command::Exists(keys.into_iter().map(String::from).collect());
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(3)
+        );
+    }
+
+    #[test]
+    fn exists_counts_a_repeated_key_each_time() {
+        let mut store = DataStore::new();
+        store.set("Tank".to_string(), "Ramattra".to_string());
+
+        let keys = vec!["Tank", "Flex", "Tank"];
+        let cmd = Command::Exists(keys.into_iter().map(String::from).collect());
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(2)
+        );
+    }
+
+    #[test]
+    fn exists_ignores_an_expired_key() {
+        let mut store = DataStore::new();
+        store.set("Tank".to_string(), "Ramattra".to_string());
+        store
+            .expires
+            .insert("Tank".to_string(), std::time::Instant::now());
+
+        let cmd = Command::Exists(vec!["Tank".to_string()]);
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(0)
+        );
+    }
+
+    /* EMPTY COLLECTIONS */
+
+    #[test]
+    fn popping_the_last_element_removes_the_list() {
+        let mut store = DataStore::new();
+        store
+            .list_db
+            .insert("Ashe".to_string(), vec!["B.O.B".to_string()]);
+        let exists_cmd = Command::Exists(vec!["Ashe".to_string(), "Ashe".to_string()]);
+        assert_eq!(
+            exists_cmd
+                .execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(2)
+        );
+
+        let rpop_cmd = Command::Rpop("Ashe".to_string(), 1);
+        rpop_cmd.execute_write(&mut store).unwrap();
+
+        assert!(store.list_db.get("Ashe").is_none());
+        assert_eq!(
+            exists_cmd
+                .execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(0)
+        );
+    }
+
+    #[test]
+    fn smove_of_the_last_member_removes_the_source() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Attack".to_string(),
+            HashSet::from(["Widowmaker".to_string()]),
+        );
+        let smove_cmd = Command::SMove(
+            "Attack".to_string(),
+            "Defense".to_string(),
+            "Widowmaker".to_string(),
+        );
+        smove_cmd.execute_write(&mut store).unwrap();
+
+        assert!(!store.contains_key("Attack"));
+        assert!(store.set_db.get("Defense").unwrap().contains("Widowmaker"));
+    }
+
+    #[test]
+    fn srem_wrong_type() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let srem_cmd = Command::Srem("DPS".to_string(), vec!["Ashe".to_string()]);
+        let result = srem_cmd.execute_write(&mut store);
+
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    /* REPLICATION FORM */
+
+    /// Ejecuta los comandos en `master` y reproduce su forma de replicación
+    /// en `replica`.
+    fn execute_and_replicate(master: &mut DataStore, replica: &mut DataStore, cmds: Vec<Command>) {
+        for cmd in cmds {
+            let result = cmd.execute_write(master).unwrap();
+            for replicated in cmd.to_replication_form(&result) {
+                replicated.execute_write(replica).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn spop_replicates_as_srem_of_popped_members() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+        let spop_cmd = Command::Spop("Maps".to_string(), 2);
+        let result = spop_cmd.execute_write(&mut store).unwrap();
+        let popped = result.as_list().unwrap().clone();
+
+        assert_eq!(
+            spop_cmd.to_replication_form(&result),
+            vec![Command::Srem("Maps".to_string(), popped)]
+        );
+    }
+
+    #[test]
+    fn replaying_replication_form_reproduces_master_state() {
+        let mut master = DataStore::new();
+        let mut replica = DataStore::new();
+        let maps = ["El Dorado", "Petra", "Busan", "Numbani", "Oasis"];
+        execute_and_replicate(
+            &mut master,
+            &mut replica,
+            vec![
+                Command::Sadd(
+                    "Maps".to_string(),
+                    maps.iter().map(|map| map.to_string()).collect(),
+                ),
+                Command::Spop("Maps".to_string(), 3),
+                Command::Spop("Empty".to_string(), 1),
+                Command::Xadd(
+                    "Picks".to_string(),
+                    None,
+                    vec![("hero".to_string(), "Juno".to_string())],
+                ),
+                Command::Set(
+                    "Hero".to_string(),
+                    "Venture".to_string(),
+                    SetOptions::default(),
+                ),
+            ],
+        );
+
+        assert_eq!(master.set_db.get("Maps").unwrap().len(), 2);
+        assert!(master.has_same_data(&replica));
+    }
+
+    /* HASH TESTS */
+
+    /// Crea un `DataStore` con el hash `"Roles"` de dos campos.
+    fn set_up_data_store_with_hash() -> DataStore {
+        let mut store = DataStore::new();
+        let cmd = Command::Hset(
+            "Roles".to_string(),
+            vec![
+                ("Ana".to_string(), "Support".to_string()),
+                ("Orisa".to_string(), "Tank".to_string()),
+            ],
+        );
+        cmd.execute_write(&mut store).unwrap();
+        store
+    }
+
+    #[test]
+    fn hset_counts_only_new_fields() {
+        let mut store = set_up_data_store_with_hash();
+
+        let cmd = Command::Hset(
+            "Roles".to_string(),
+            vec![
+                ("Orisa".to_string(), "Damage".to_string()),
+                ("Sombra".to_string(), "Damage".to_string()),
+            ],
+        );
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+        assert_eq!(store.hash_db["Roles"]["Orisa"], "Damage");
+        assert_eq!(store.hash_db["Roles"].len(), 3);
+    }
+
+    #[test]
+    fn hget_existing_and_missing_fields() {
+        let store = set_up_data_store_with_hash();
+
+        let cmd = Command::Hget("Roles".to_string(), "Ana".to_string());
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Str("Support".to_string())
+        );
+        for (key, field) in [("Roles", "Sombra"), ("Maps", "Ana")] {
+            let cmd = Command::Hget(key.to_string(), field.to_string());
+            assert_eq!(
+                cmd.execute_read(&store, None, None, None, None, None)
+                    .unwrap(),
+                ResponseType::Null(None)
+            );
+        }
+    }
+
+    #[test]
+    fn hdel_counts_removed_fields_and_drops_empty_hash() {
+        let mut store = set_up_data_store_with_hash();
+
+        let cmd = Command::Hdel(
+            "Roles".to_string(),
+            vec!["Ana".to_string(), "Sombra".to_string()],
+        );
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+
+        let cmd = Command::Hdel("Roles".to_string(), vec!["Orisa".to_string()]);
+        cmd.execute_write(&mut store).unwrap();
+        assert!(!store.contains_key("Roles"));
+    }
+
+    #[test]
+    fn hgetall_flattens_fields_and_values() {
+        let store = set_up_data_store_with_hash();
+
+        let cmd = Command::Hgetall("Roles".to_string());
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::List(vec![
+                "Ana".to_string(),
+                "Support".to_string(),
+                "Orisa".to_string(),
+                "Tank".to_string(),
+            ])
+        );
+        let cmd = Command::Hgetall("Maps".to_string());
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::List(vec![])
+        );
+    }
+
+    #[test]
+    fn hash_commands_fail_on_other_types() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        store.set("Hero".to_string(), "Ana".to_string());
+        store
+            .set_db
+            .insert("Maps".to_string(), HashSet::from(["Ilios".to_string()]));
+
+        for key in ["DPS", "Hero", "Maps"] {
+            let hset = Command::Hset(key.to_string(), vec![("a".to_string(), "b".to_string())]);
+            assert!(matches!(
+                hset.execute_write(&mut store).unwrap_err(),
+                CommandError::WrongType
+            ));
+            let hdel = Command::Hdel(key.to_string(), vec!["a".to_string()]);
+            assert!(matches!(
+                hdel.execute_write(&mut store).unwrap_err(),
+                CommandError::WrongType
+            ));
+            for read in [
+                Command::Hget(key.to_string(), "a".to_string()),
+                Command::Hgetall(key.to_string()),
+            ] {
+                let result = read.execute_read(&store, None, None, None, None, None);
+                assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+            }
+        }
+    }
+
+    #[test]
+    fn string_commands_fail_on_a_hash() {
+        let mut store = set_up_data_store_with_hash();
+
+        let cmd = Command::Get("Roles".to_string());
+        let result = cmd.execute_read(&store, None, None, None, None, None);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+
+        let cmd = Command::Lpush("Roles".to_string(), vec!["Ana".to_string()]);
+        assert!(matches!(
+            cmd.execute_write(&mut store).unwrap_err(),
+            CommandError::WrongType
+        ));
+    }
+
+    /* GEO TESTS */
+
+    /// Crea un `DataStore` con el sorted set `"Maps"` con las posiciones
+    /// de King's Row, Paris y Rio.
+    fn set_up_data_store_with_geo() -> DataStore {
+        let mut store = DataStore::new();
+        let cmd = Command::Geoadd(
+            "Maps".to_string(),
+            vec![
+                (-0.1278, 51.5074, "King's Row".to_string()),
+                (2.3522, 48.8566, "Paris".to_string()),
+                (-43.1729, -22.9068, "Rio".to_string()),
+            ],
+        );
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(3));
+        store
+    }
+
+    /// Opciones de GEOSEARCH por radio desde un miembro, en kilómetros.
+    fn radius_search(member: &str, radius: f64) -> GeoSearchOptions {
+        GeoSearchOptions {
+            origin: GeoOrigin::Member(member.to_string()),
+            shape: GeoShape::Radius(radius),
+            unit: GeoUnit::Km,
+            desc: false,
+            count: None,
+            with_coord: false,
+            with_dist: false,
+        }
+    }
+
+    #[test]
+    fn geoadd_counts_only_new_members_and_keeps_positions() {
+        let mut store = set_up_data_store_with_geo();
+
+        let cmd = Command::Geoadd(
+            "Maps".to_string(),
+            vec![
+                (2.3522, 48.8566, "Paris".to_string()),
+                (139.6917, 35.6895, "Hanamura".to_string()),
+            ],
+        );
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+
+        let positions = geopos(
+            &store,
+            &"Maps".to_string(),
+            &[
+                "King's Row".to_string(),
+                "Hanamura".to_string(),
+                "Oasis".to_string(),
+            ],
+        )
+        .unwrap();
+        let (lon, lat) = positions[0].unwrap();
+        assert!((lon + 0.1278).abs() < 1e-5 && (lat - 51.5074).abs() < 1e-5);
+        let (lon, lat) = positions[1].unwrap();
+        assert!((lon - 139.6917).abs() < 1e-5 && (lat - 35.6895).abs() < 1e-5);
+        assert_eq!(positions[2], None);
+    }
+
+    #[test]
+    fn geoadd_invalid_coordinates_writes_nothing() {
+        let mut store = DataStore::new();
+        let cmd = Command::Geoadd(
+            "Maps".to_string(),
+            vec![
+                (2.3522, 48.8566, "Paris".to_string()),
+                (0.0, 86.0, "Ecopoint: Antarctica".to_string()),
+            ],
+        );
+        let err = cmd.execute_write(&mut store).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("ERR invalid longitude,latitude pair")
+        );
+        assert!(!store.contains_key("Maps"));
+    }
+
+    #[test]
+    fn geodist_between_members() {
+        let store = set_up_data_store_with_geo();
+
+        let cmd = Command::Geodist(
+            "Maps".to_string(),
+            "King's Row".to_string(),
+            "Paris".to_string(),
+            GeoUnit::Km,
+        );
+        let dist = cmd
+            .execute_read(&store, None, None, None, None, None)
+            .unwrap();
+        let dist: f64 = dist.as_str().unwrap().parse().unwrap();
+        assert!((dist - 343.5).abs() < 2.0);
+
+        let cmd = Command::Geodist(
+            "Maps".to_string(),
+            "King's Row".to_string(),
+            "Oasis".to_string(),
+            GeoUnit::M,
+        );
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Null(None)
+        );
+    }
+
+    #[test]
+    fn geosearch_by_radius_returns_only_members_in_range() {
+        let store = set_up_data_store_with_geo();
+        let key = "Maps".to_string();
+
+        let found = geosearch(&store, &key, &radius_search("King's Row", 500.0)).unwrap();
+        let names: Vec<&str> = found.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["King's Row", "Paris"]);
+        assert!((found[1].1 - 343.5).abs() < 2.0);
+
+        let mut options = radius_search("Rio", 20_000.0);
+        options.desc = true;
+        options.count = Some(2);
+        let found = geosearch(&store, &key, &options).unwrap();
+        let names: Vec<&str> = found.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["King's Row", "Paris"]);
+
+        let result = geosearch(&store, &key, &radius_search("Oasis", 500.0));
+        assert!(result.is_err());
+        let found = geosearch(&store, &"Ghost".to_string(), &radius_search("Rio", 1.0));
+        assert!(found.unwrap().is_empty());
+    }
+
+    #[test]
+    fn geo_commands_use_a_zset_and_fail_on_other_types() {
+        let mut store = set_up_data_store_with_geo();
+        let cmd = Command::Type("Maps".to_string());
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Str("zset".to_string())
+        );
+
+        let cmd = Command::Get("Maps".to_string());
+        let result = cmd.execute_read(&store, None, None, None, None, None);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+
+        store.set("Hero".to_string(), "Ana".to_string());
+        let cmd = Command::Geoadd("Hero".to_string(), vec![(0.0, 0.0, "Ana".to_string())]);
+        assert!(matches!(
+            cmd.execute_write(&mut store).unwrap_err(),
+            CommandError::WrongType
+        ));
+        let result = geopos(&store, &"Hero".to_string(), &["Ana".to_string()]);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    /* SORT TESTS */
+
+    /// Ejecuta un `Command::Sort` sobre el store.
+    fn run_sort(store: &DataStore, cmd: &Command) -> Result<Vec<Option<String>>, CommandError> {
+        match cmd {
+            Command::Sort(key, alpha, desc, limit, by_pattern, get_patterns) => {
+                sort(store, key, *alpha, *desc, limit, by_pattern, get_patterns)
+            }
+            _ => panic!("Se esperaba un SORT"),
+        }
+    }
+
+    /// Elementos esperados de un `SORT`, todos presentes.
+    fn sorted(items: &[&str]) -> Vec<Option<String>> {
+        items.iter().map(|item| Some(item.to_string())).collect()
+    }
+
+    /// Crea un `DataStore`, agregando en `list_db`,
+    /// `"Ranks" = ["3", "10", "1", "2.5"]`
+    fn set_up_data_store_with_numeric_list() -> DataStore {
+        let mut store = DataStore::new();
+        store.list_db.insert(
+            "Ranks".to_string(),
+            vec![
+                "3".to_string(),
+                "10".to_string(),
+                "1".to_string(),
+                "2.5".to_string(),
+            ],
+        );
+        store
+    }
+
+    #[test]
+    fn sort_list_numeric_ascending() {
+        let store = set_up_data_store_with_numeric_list();
+        let cmd = Command::Sort("Ranks".to_string(), false, false, None, None, vec![]);
+        let result = run_sort(&store, &cmd);
+
+        assert_eq!(result.unwrap(), sorted(&["1", "2.5", "3", "10",]));
+    }
+
+    #[test]
+    fn sort_list_numeric_descending() {
+        let store = set_up_data_store_with_numeric_list();
+        let cmd = Command::Sort("Ranks".to_string(), false, true, None, None, vec![]);
+        let result = run_sort(&store, &cmd);
+
+        assert_eq!(result.unwrap(), sorted(&["10", "3", "2.5", "1",]));
+    }
+
+    #[test]
+    fn sort_set_alpha() {
+        let store = set_up_data_store_with_multiple_items_set();
+        let cmd = Command::Sort("Maps".to_string(), true, false, None, None, vec![]);
+        let result = run_sort(&store, &cmd);
+
+        assert_eq!(result.unwrap(), sorted(&["Busan", "El Dorado", "Petra",]));
+    }
+
+    #[test]
+    fn sort_limit_window() {
+        let store = set_up_data_store_with_numeric_list();
+        let cmd = Command::Sort(
+            "Ranks".to_string(),
+            false,
+            false,
+            Some((1, 2)),
+            None,
+            vec![],
+        );
+        let result = run_sort(&store, &cmd);
+
+        assert_eq!(result.unwrap(), sorted(&["2.5", "3"]));
+    }
 
-        assert_eq!(result.unwrap(), ResponseType::Int(1));
+    #[test]
+    fn sort_non_numeric_without_alpha() {
+        let store = set_up_data_store_with_multiple_items_list();
+        let cmd = Command::Sort("DPS".to_string(), false, false, None, None, vec![]);
+        let result = run_sort(&store, &cmd);
 
-        let set = store.set_db.get("Genji").unwrap();
-        assert_eq!(set.len(), 1);
-        assert!(set.contains("I need healing"));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "ERR One or more scores can't be converted into double"
+        );
     }
 
     #[test]
-    fn scard_works_over_multiple_items_set() {
-        let mut store = DataStore::new();
-        store.set_db.insert(
+    fn sort_by_and_get_patterns() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+        store.set("weight_Busan".to_string(), "3".to_string());
+        store.set("weight_Petra".to_string(), "1".to_string());
+        store.set("weight_El Dorado".to_string(), "2".to_string());
+        store.set("mode_Busan".to_string(), "Control".to_string());
+        store.set("mode_Petra".to_string(), "Deathmatch".to_string());
+
+        let cmd = Command::Sort(
             "Maps".to_string(),
-            HashSet::from([
-                "El Dorado".to_string(),
-                "Petra".to_string(),
-                "Busan".to_string(),
-            ]),
+            false,
+            false,
+            None,
+            Some("weight_*".to_string()),
+            vec!["#".to_string(), "mode_*".to_string()],
         );
+        let result = run_sort(&store, &cmd);
 
-        let scard_cmd = Command::Scard("Maps".to_string());
-        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                Some("Petra".to_string()),
+                Some("Deathmatch".to_string()),
+                Some("El Dorado".to_string()),
+                None,
+                Some("Busan".to_string()),
+                Some("Control".to_string()),
+            ]
+        );
+    }
 
-        assert_eq!(result.unwrap(), ResponseType::Int(3));
+    #[test]
+    fn sort_wrongtype_str() {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Kiriko".to_string());
+        let cmd = Command::Sort("Hero".to_string(), false, false, None, None, vec![]);
+        let result = run_sort(&store, &cmd);
 
-        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
-        let set = store.set_db.get("Maps").unwrap();
-        assert_eq!(set, &expected);
+        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    }
+
+    /* TYPE TESTS */
+
+    /// Obtiene el tipo de la clave según TYPE.
+    fn key_type(store: &DataStore, key: &str) -> ResponseType {
+        let cmd = Command::Type(key.to_string());
+        cmd.execute_read(store, None, None, None, None, None)
+            .unwrap()
     }
 
     #[test]
-    fn scard_doesnt_work_over_set_strings() {
+    fn type_of_a_string_key() {
         let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("Hammond".to_string(), "Ball".to_string());
+        store.set("Hero".to_string(), "Venture".to_string());
+        assert_eq!(
+            key_type(&store, "Hero"),
+            ResponseType::Str("string".to_string())
+        );
+    }
 
-        let scard_cmd = Command::Scard("Hammond".to_string());
-        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
+    #[test]
+    fn type_of_a_list_key() {
+        let store = set_up_data_store_with_multiple_items_list();
+        assert_eq!(
+            key_type(&store, "DPS"),
+            ResponseType::Str("list".to_string())
+        );
+    }
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
-        // "Hammond" debe permanecer en string_db.
-        assert_eq!(store.string_db.get("Hammond").unwrap(), "Ball");
+    #[test]
+    fn type_of_a_set_key() {
+        let mut store = DataStore::new();
+        store
+            .set_db
+            .insert("Support".to_string(), HashSet::from(["Juno".to_string()]));
+        assert_eq!(
+            key_type(&store, "Support"),
+            ResponseType::Str("set".to_string())
+        );
     }
 
     #[test]
-    fn scard_doesnt_work_over_lists() {
+    fn type_of_a_missing_key_is_none() {
         let mut store = DataStore::new();
+        assert_eq!(
+            key_type(&store, "Hero"),
+            ResponseType::Str("none".to_string())
+        );
+
+        store.set("Hero".to_string(), "Venture".to_string());
         store
-            .list_db
-            .insert("Hammond".to_string(), vec!["Ball".to_string()]);
+            .expires
+            .insert("Hero".to_string(), std::time::Instant::now());
+        assert_eq!(
+            key_type(&store, "Hero"),
+            ResponseType::Str("none".to_string())
+        );
+    }
 
-        let scard_cmd = Command::Scard("Hammond".to_string());
-        let result = scard_cmd.execute_read(&mut store, None, None, None, None, None);
+    /* OBJECT ENCODING TESTS */
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    /// Obtiene el encoding de la clave según OBJECT ENCODING.
+    fn object_encoding(store: &DataStore, key: &str) -> ResponseType {
+        let cmd = Command::Object(ObjectSubcommand::Encoding(key.to_string()));
+        cmd.execute_read(store, None, None, None, None, None)
+            .unwrap()
     }
 
-    /* SISMEMBER */
+    #[test]
+    fn object_encoding_short_string_is_embstr() {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Lifeweaver".to_string());
+        assert_eq!(
+            object_encoding(&store, "Hero"),
+            ResponseType::Str("embstr".to_string())
+        );
+    }
 
     #[test]
-    fn sismember_works_for_non_existent_set() {
+    fn object_encoding_long_string_is_raw() {
         let mut store = DataStore::new();
-        let sismemeber_cmd = Command::Sismember("Game modes".to_string(), "Archives".to_string());
-        let result = sismemeber_cmd.execute_read(&mut store, None, None, None, None, None);
+        store.set("Lore".to_string(), "Numbani".repeat(15)[..100].to_string());
+        assert_eq!(
+            object_encoding(&store, "Lore"),
+            ResponseType::Str("raw".to_string())
+        );
+    }
 
-        // Al no existir la clave, se retorna 0.
-        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    #[test]
+    fn object_encoding_integer_string_is_int() {
+        let mut store = DataStore::new();
+        store.set("Elo".to_string(), "12345".to_string());
+        store.set("Code".to_string(), "012345".to_string());
+        assert_eq!(
+            object_encoding(&store, "Elo"),
+            ResponseType::Str("int".to_string())
+        );
+        assert_eq!(
+            object_encoding(&store, "Code"),
+            ResponseType::Str("embstr".to_string())
+        );
     }
 
     #[test]
-    fn sismember_works_for_non_existent_value() {
+    fn object_encoding_append_over_limit_flips_to_raw() {
         let mut store = DataStore::new();
-        store.set_db.insert(
-            "Maps".to_string(),
-            HashSet::from([
-                "El Dorado".to_string(),
-                "Petra".to_string(),
-                "Busan".to_string(),
-            ]),
+        let quote = "a".repeat(EMBSTR_SIZE_LIMIT);
+        store.set("Quote".to_string(), quote);
+        assert_eq!(
+            object_encoding(&store, "Quote"),
+            ResponseType::Str("embstr".to_string())
         );
 
-        let sismemeber_cmd = Command::Sismember("Maps".to_string(), "Gilbraltar".to_string());
-        let result = sismemeber_cmd.execute_read(&mut store, None, None, None, None, None);
+        let cmd = Command::Append("Quote".to_string(), "!".to_string());
+        cmd.execute_write(&mut store).unwrap();
+        assert_eq!(
+            object_encoding(&store, "Quote"),
+            ResponseType::Str("raw".to_string())
+        );
+    }
 
-        // Se espera 0 ya que "Gilbraltar" no está en el set.
-        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    #[test]
+    fn object_encoding_small_list_is_listpack_and_large_is_quicklist() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        assert_eq!(
+            object_encoding(&store, "DPS"),
+            ResponseType::Str("listpack".to_string())
+        );
 
-        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
-            .iter()
-            .map(|s| s.to_string())
+        let queue = (0..=LISTPACK_MAX_ENTRIES)
+            .map(|i| format!("Jugador{}", i))
             .collect();
-        let set = store.set_db.get("Maps").unwrap();
-        assert_eq!(set, &expected);
+        store.list_db.insert("Queue".to_string(), queue);
+        assert_eq!(
+            object_encoding(&store, "Queue"),
+            ResponseType::Str("quicklist".to_string())
+        );
     }
 
     #[test]
-    fn sismember_works_for_one_item_set() {
+    fn object_encoding_integer_set_is_intset() {
         let mut store = DataStore::new();
-        store
-            .set_db
-            .insert("Maps".to_string(), HashSet::from(["El Dorado".to_string()]));
+        store.set_db.insert(
+            "Seasons".to_string(),
+            HashSet::from(["1".to_string(), "12".to_string(), "-3".to_string()]),
+        );
+        assert_eq!(
+            object_encoding(&store, "Seasons"),
+            ResponseType::Str("intset".to_string())
+        );
 
-        let sismember_cmd = Command::Sismember("Maps".to_string(), "El Dorado".to_string());
-        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+        let many = (0..=INTSET_MAX_ENTRIES).map(|i| i.to_string()).collect();
+        store.set_db.insert("Matches".to_string(), many);
+        assert_eq!(
+            object_encoding(&store, "Matches"),
+            ResponseType::Str("hashtable".to_string())
+        );
+    }
 
-        assert_eq!(result.unwrap(), ResponseType::Int(1));
+    #[test]
+    fn object_encoding_set_with_text_is_hashtable() {
+        let mut store = DataStore::new();
+        store.set_db.insert(
+            "Tanks".to_string(),
+            HashSet::from(["Reinhardt".to_string(), "7".to_string()]),
+        );
+        assert_eq!(
+            object_encoding(&store, "Tanks"),
+            ResponseType::Str("hashtable".to_string())
+        );
+    }
 
-        let expected: HashSet<String> = ["El Dorado"].iter().map(|s| s.to_string()).collect();
-        let set = store.set_db.get("Maps").unwrap();
-        assert_eq!(set, &expected);
+    #[test]
+    fn object_encoding_nonexistent_key() {
+        let store = DataStore::new();
+        let cmd = Command::Object(ObjectSubcommand::Encoding("Hero".to_string()));
+        let result = cmd.execute_read(&store, None, None, None, None, None);
+        assert_eq!(result.unwrap_err().to_string(), "ERR no such key");
+    }
+
+    /* DUMP / RESTORE TESTS */
+
+    /// Obtiene el payload de DUMP de la clave.
+    fn dump_payload(store: &DataStore, key: &str) -> String {
+        let cmd = Command::Dump(key.to_string());
+        let result = cmd.execute_read(store, None, None, None, None, None);
+        result.unwrap().as_str().unwrap().clone()
     }
 
     #[test]
-    fn sismember_works_for_multiple_items_set() {
+    fn dump_restore_list_round_trip() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let payload = dump_payload(&store, "DPS");
+
+        let cmd = Command::Restore("DPS copy".to_string(), 0, payload, false);
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Str("OK".to_string()));
+        assert_eq!(store.list_db.get("DPS copy"), store.list_db.get("DPS"));
+    }
+
+    #[test]
+    fn dump_restore_hash_round_trip() {
         let mut store = DataStore::new();
-        store.set_db.insert(
-            "Maps".to_string(),
-            HashSet::from([
-                "El Dorado".to_string(),
-                "Petra".to_string(),
-                "Busan".to_string(),
-            ]),
+        let hset = Command::Hset(
+            "Reinhardt".to_string(),
+            vec![("role".to_string(), "Tank".to_string())],
         );
+        hset.execute_write(&mut store).unwrap();
+        let payload = dump_payload(&store, "Reinhardt");
 
-        let sismember_cmd = Command::Sismember("Maps".to_string(), "Petra".to_string());
-        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+        let cmd = Command::Restore("Reinhardt copy".to_string(), 0, payload, false);
+        let result = cmd.execute_write(&mut store);
 
-        assert_eq!(result.unwrap(), ResponseType::Int(1));
+        assert_eq!(result.unwrap(), ResponseType::Str("OK".to_string()));
+        assert_eq!(
+            store.hash_db.get("Reinhardt copy"),
+            store.hash_db.get("Reinhardt")
+        );
+    }
 
-        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
-        let set = store.set_db.get("Maps").unwrap();
-        assert_eq!(set, &expected);
+    #[test]
+    fn dump_nonexistent_key() {
+        let store = DataStore::new();
+        let cmd = Command::Dump("Hero".to_string());
+        let result = cmd.execute_read(&store, None, None, None, None, None);
+        assert_eq!(result.unwrap(), ResponseType::Null(None));
     }
 
     #[test]
-    fn sismember_works_for_multiple_items_set_at_beggining() {
+    fn restore_existing_key_busykey() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+        store.set("Hero".to_string(), "Mauga".to_string());
+        let payload = dump_payload(&store, "Maps");
+
+        let cmd = Command::Restore("Hero".to_string(), 0, payload, false);
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "BUSYKEY Target key name already exists."
+        );
+        assert_eq!(store.get("Hero").unwrap(), "Mauga");
+    }
+
+    #[test]
+    fn restore_cross_type_with_replace() {
+        let mut store = set_up_data_store_with_multiple_items_set();
+        store.set("Hero".to_string(), "Mauga".to_string());
+        let payload = dump_payload(&store, "Maps");
+
+        let cmd = Command::Restore("Hero".to_string(), 0, payload, true);
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Str("OK".to_string()));
+        assert!(store.get("Hero").is_none());
+        assert_eq!(store.set_db.get("Hero"), store.set_db.get("Maps"));
+    }
+
+    #[test]
+    fn restore_unknown_type_tag() {
         let mut store = DataStore::new();
-        store.set_db.insert(
-            "Maps".to_string(),
-            HashSet::from([
-                "El Dorado".to_string(),
-                "Petra".to_string(),
-                "Busan".to_string(),
-            ]),
+        // Tag 9, sin contenedor, + versión 1, seguido del crc16 del cuerpo
+        let body = [9u8, 0, 1];
+        let crc = crate::cluster::sharding::hash_slot::crc16_xmodem(&body);
+        let payload = format!("090001{:04x}", crc);
+
+        let cmd = Command::Restore("Hero".to_string(), 0, payload, false);
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "ERR Bad data format: unknown type tag 9"
         );
+        assert!(!store.contains_key("Hero"));
+    }
 
-        let sismember_cmd = Command::Sismember("Maps".to_string(), "El Dorado".to_string());
-        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+    #[test]
+    fn restore_oversized_length_is_rejected() {
+        let mut store = DataStore::new();
+        // String que declara 2^64 - 1 bytes + versión 1, con un crc16 válido
+        let body = [0u8, 255, 255, 255, 255, 255, 255, 255, 255, 0, 1];
+        let crc = crate::cluster::sharding::hash_slot::crc16_xmodem(&body);
+        let payload = format!("00ffffffffffffffff0001{:04x}", crc);
 
-        assert_eq!(result.unwrap(), ResponseType::Int(1));
+        let cmd = Command::Restore("Hero".to_string(), 0, payload, false);
+        let result = cmd.execute_write(&mut store);
 
-        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
-        let set = store.set_db.get("Maps").unwrap();
-        assert_eq!(set, &expected);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "ERR DUMP payload version or checksum are wrong"
+        );
+        assert!(!store.contains_key("Hero"));
+    }
+
+    #[test]
+    fn migrated_key_keeps_remaining_ttl() {
+        let mut source = set_up_data_store_with_multiple_items_list();
+        source.set_ttl("DPS", Some(std::time::Duration::from_secs(10)));
+        let mut target = DataStore::new();
+
+        // Se mueve la clave con su TTL restante, como lo haría MIGRATE
+        let payload = dump_payload(&source, "DPS");
+        let ttl = source.remaining_ttl("DPS").unwrap().as_millis() as i64;
+        let cmd = Command::Restore("DPS".to_string(), ttl, payload, false);
+        assert_eq!(
+            cmd.execute_write(&mut target).unwrap(),
+            ResponseType::Str("OK".to_string())
+        );
+
+        let remaining = target.remaining_ttl("DPS").unwrap();
+        assert!(remaining > std::time::Duration::from_millis(9_500));
+        assert!(remaining <= std::time::Duration::from_secs(10));
+    }
+
+    #[test]
+    fn restore_with_zero_ttl_is_persistent() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        store.set_ttl("DPS", Some(std::time::Duration::from_secs(10)));
+        let payload = dump_payload(&store, "DPS");
+
+        let cmd = Command::Restore("DPS".to_string(), 0, payload, true);
+        cmd.execute_write(&mut store).unwrap();
+
+        assert_eq!(store.remaining_ttl("DPS"), None);
+        let far_future = std::time::Instant::now() + std::time::Duration::from_secs(3600);
+        assert!(store.purge_expired(far_future, usize::MAX).is_empty());
+        assert!(store.contains_key("DPS"));
+    }
+
+    #[test]
+    fn expired_restored_key_is_purged() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let payload = dump_payload(&store, "DPS");
+        let cmd = Command::Restore("Support".to_string(), 50, payload, false);
+        cmd.execute_write(&mut store).unwrap();
+
+        let later = std::time::Instant::now() + std::time::Duration::from_millis(100);
+        assert_eq!(
+            store.purge_expired(later, usize::MAX),
+            vec!["Support".to_string()]
+        );
+        assert!(!store.contains_key("Support"));
+        assert!(store.contains_key("DPS"));
+    }
+
+    #[test]
+    fn expired_list_is_hidden_before_it_is_purged() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        store.set_ttl("DPS", Some(std::time::Duration::ZERO));
+
+        assert_eq!(store.get_list("DPS"), None);
+        let cmd = Command::Llen("DPS".to_string());
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(0)
+        );
+        assert!(store.needs_expiry("DPS"));
+        assert!(store.expire_if_due("DPS"));
+        assert!(!store.contains_key("DPS"));
+        assert!(store.expires.is_empty());
     }
 
     #[test]
-    fn sismember_works_for_multiple_items_set_at_end() {
-        let mut store = DataStore::new();
-        store.set_db.insert(
-            "Maps".to_string(),
-            HashSet::from([
-                "El Dorado".to_string(),
-                "Petra".to_string(),
-                "Busan".to_string(),
-            ]),
-        );
+    fn pexpireat_sets_absolute_deadline() {
+        let mut store = set_up_data_store_with_multiple_items_list();
+        let in_a_minute = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        let unix_ms = in_a_minute
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
 
-        let sismember_cmd = Command::Sismember("Maps".to_string(), "Busan".to_string());
-        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+        let cmd = Command::PexpireAt("DPS".to_string(), unix_ms);
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+        let ttl = store.remaining_ttl("DPS").unwrap();
+        assert!(ttl > std::time::Duration::from_secs(58));
 
-        assert_eq!(result.unwrap(), ResponseType::Int(1));
+        let cmd = Command::PexpireAt("DPS".to_string(), 1);
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+        assert!(!store.contains_key("DPS"));
 
-        let expected: HashSet<String> = ["El Dorado", "Petra", "Busan"]
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
-        let set = store.set_db.get("Maps").unwrap();
-        assert_eq!(set, &expected);
+        let cmd = Command::PexpireAt("Tank".to_string(), unix_ms);
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(0));
     }
 
     #[test]
-    fn sismember_doesnt_work_for_set_strings() {
+    fn active_expiry_removes_at_most_limit_keys() {
         let mut store = DataStore::new();
+        for hero in ["Ana", "Mercy", "Lucio", "Kiriko"] {
+            store
+                .string_db
+                .insert(hero.to_string(), "Support".to_string());
+            store.set_ttl(hero, Some(std::time::Duration::ZERO));
+        }
         store
             .string_db
-            .insert("Mei".to_string(), "Iceberg".to_string());
-
-        let sismember_cmd = Command::Sismember("Mei".to_string(), "Iceberg".to_string());
-        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+            .insert("Reinhardt".to_string(), "Tank".to_string());
+        store.set_ttl("Reinhardt", Some(std::time::Duration::from_secs(3600)));
+
+        let now = std::time::Instant::now();
+        assert_eq!(store.purge_expired(now, 3).len(), 3);
+        assert_eq!(store.purge_expired(now, 3).len(), 1);
+        assert!(store.purge_expired(now, 3).is_empty());
+        assert!(store.contains_key("Reinhardt"));
+        assert_eq!(store.expires.len(), 1);
+    }
+
+    /* SCAN TESTS */
+
+    /// Ejecuta un SCAN y devuelve el próximo cursor y las claves.
+    fn scan_step(store: &DataStore, cursor: u64, count: usize) -> (u64, Vec<String>) {
+        let cmd = Command::Scan(cursor, None, count);
+        match cmd
+            .execute_read(store, None, None, None, None, None)
+            .unwrap()
+        {
+            ResponseType::List(mut res) => {
+                let keys = res.split_off(1);
+                (res[0].parse().unwrap(), keys)
+            }
+            other => panic!("Respuesta inesperada de SCAN: {:?}", other),
+        }
+    }
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
-        // "Mei" debe permanecer en string_db.
-        assert_eq!(store.string_db.get("Mei").unwrap(), "Iceberg");
+    /// Recorre el store completo ejecutando `between` entre cada llamada.
+    fn scan_all<F: FnMut(&mut DataStore, usize)>(
+        store: &mut DataStore,
+        count: usize,
+        mut between: F,
+    ) -> Vec<String> {
+        let mut seen = Vec::new();
+        let mut cursor = 0;
+        let mut round = 0;
+        loop {
+            let (next, keys) = scan_step(store, cursor, count);
+            seen.extend(keys);
+            if next == 0 {
+                return seen;
+            }
+            between(store, round);
+            cursor = next;
+            round += 1;
+        }
     }
 
     #[test]
-    fn sismember_doesnt_work_for_lists() {
+    fn scan_returns_every_key_once() {
         let mut store = DataStore::new();
-        // Insertar una lista en "DPS" por ejemplo.
-        store.list_db.insert(
+        let cmd = Command::Rpush(
             "DPS".to_string(),
-            vec![
-                "Ashe".to_string(),
-                "F.R.E.D".to_string(),
-                "B.O.B".to_string(),
-                "Torbjorn".to_string(),
-                "Echo".to_string(),
-            ],
+            vec!["Ashe".to_string(), "Echo".to_string()],
         );
+        cmd.execute_write(&mut store).unwrap();
+        for hero in ["Ana", "Genji", "Mercy", "Reinhardt", "Sombra", "Tracer"] {
+            store.set(hero.to_string(), "Overwatch".to_string());
+        }
 
-        let sismember_cmd = Command::Sismember("DPS".to_string(), "F.R.E.D".to_string());
-        let result = sismember_cmd.execute_read(&mut store, None, None, None, None, None);
+        let mut seen = scan_all(&mut store, 2, |_, _| {});
+        seen.sort();
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
-        // La clave "DPS" debe seguir en list_db y sin cambios.
-        let list = store.list_db.get("DPS").unwrap();
-        assert_eq!(list.len(), 5);
-        assert_eq!(list[0], "Ashe".to_string());
-        assert_eq!(list[1], "F.R.E.D".to_string());
-        assert_eq!(list[2], "B.O.B".to_string());
-        assert_eq!(list[3], "Torbjorn".to_string());
-        assert_eq!(list[4], "Echo".to_string());
+        let mut expected: Vec<String> = store.keys().cloned().collect();
+        expected.sort();
+        assert_eq!(seen, expected);
     }
 
-    /* SMEMBERS */
-
     #[test]
-    fn smembers_works_properly_over_an_empty_set() {
+    fn scan_survives_inserts_deletes_and_growth() {
         let mut store = DataStore::new();
-        let smem_cmd = Command::Smembers("Winton".to_string());
-        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+        let stable: Vec<String> = (0..50).map(|i| format!("hero:{}", i)).collect();
+        for key in &stable {
+            store.set(key.clone(), "Overwatch".to_string());
+        }
+        for i in 0..50 {
+            store.set(format!("bot:{}", i), "Training".to_string());
+        }
+        let capacity = store.string_db.capacity();
+
+        let seen: HashSet<String> = scan_all(&mut store, 5, |store, round| {
+            // Borra claves temporales y, en las primeras rondas, fuerza
+            // el crecimiento del mapa
+            let del = Command::Del(vec![format!("bot:{}", round)]);
+            del.execute_write(store).unwrap();
+            if round < 3 {
+                for i in 0..200 {
+                    store.set(format!("spawn:{}:{}", round, i), "Wave".to_string());
+                }
+            }
+        })
+        .into_iter()
+        .collect();
 
-        // Al no existir la clave "Winton" se devuelve un set vacío.
-        assert_eq!(result.unwrap(), ResponseType::Set(HashSet::new()));
+        assert!(store.string_db.capacity() > capacity);
+        for key in &stable {
+            assert!(seen.contains(key), "SCAN omitió la clave {}", key);
+        }
     }
 
     #[test]
-    fn smembers_works_properly_over_one_item_set() {
+    fn scan_skips_deleted_keys_and_sees_keys_loaded_from_a_snapshot() {
         let mut store = DataStore::new();
-        store
-            .set_db
-            .insert("Winton".to_string(), HashSet::from(["Honey".to_string()]));
+        for hero in ["Ana", "Genji", "Mercy"] {
+            store.set(hero.to_string(), "Overwatch".to_string());
+        }
+        let cmd = Command::Del(vec!["Genji".to_string()]);
+        cmd.execute_write(&mut store).unwrap();
 
-        let smem_cmd = Command::Smembers("Winton".to_string());
-        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+        let mut seen = scan_all(&mut store, 1, |_, _| {});
+        seen.sort();
+        assert_eq!(seen, vec!["Ana".to_string(), "Mercy".to_string()]);
 
-        match result.unwrap() {
-            ResponseType::Set(set) => {
-                assert_eq!(set.len(), 1);
-                assert!(set.contains("Honey"));
-            }
-            _ => assert!(false, "Se esperaba un ResponseType::Set"),
-        }
-        // Se verifica internamente
-        assert_eq!(store.set_db.len(), 1);
-        let set = store.set_db.get("Winton").unwrap();
-        assert_eq!(set.len(), 1);
+        let bytes = crate::storage::serializer::serialize_to_bytes(&store).unwrap();
+        let mut loaded = crate::storage::deserializer::deserialize_ds(&mut &bytes[..]).unwrap();
+        let mut seen = scan_all(&mut loaded, 10, |_, _| {});
+        seen.sort();
+        assert_eq!(seen, vec!["Ana".to_string(), "Mercy".to_string()]);
     }
 
     #[test]
-    fn smembers_works_properly_over_multiple_items_set() {
+    fn scan_with_match_filters_keys() {
         let mut store = DataStore::new();
-        store.set_db.insert(
-            "Winton".to_string(),
-            HashSet::from(["Honey".to_string(), "Glasses".to_string()]),
-        );
+        store.set("hero:Kiriko".to_string(), "Support".to_string());
+        store.set("hero:Junkrat".to_string(), "Damage".to_string());
+        store.set("map:Ilios".to_string(), "Control".to_string());
 
-        let smem_cmd = Command::Smembers("Winton".to_string());
-        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+        let cmd = Command::Scan(0, Some("hero:*".to_string()), 10);
+        let result = cmd.execute_read(&store, None, None, None, None, None);
 
         match result.unwrap() {
-            ResponseType::Set(set) => {
-                assert_eq!(set.len(), 2);
-                assert!(set.contains("Honey"));
-                assert!(set.contains("Glasses"));
+            ResponseType::List(res) => {
+                assert_eq!(res[0], "0");
+                let keys: HashSet<String> = res[1..].iter().cloned().collect();
+                assert_eq!(
+                    keys,
+                    HashSet::from(["hero:Kiriko".to_string(), "hero:Junkrat".to_string()])
+                );
             }
-            _ => assert!(false, "Se esperaba un ResponseType::Set"),
+            other => panic!("Respuesta inesperada de SCAN: {:?}", other),
         }
-        // Verificamos el estado interno.
-        let set = store.set_db.get("Winton").unwrap();
-        assert_eq!(set.len(), 2);
     }
 
-    #[test]
-    fn smembers_doesnt_work_over_set_strings() {
+    /* KEYS TESTS */
+
+    /// Ejecuta KEYS con el patrón y devuelve las claves encontradas.
+    fn keys_matching(store: &DataStore, pattern: &str) -> Vec<String> {
+        let cmd = Command::Keys(pattern.to_string());
+        match cmd
+            .execute_read(store, None, None, None, None, None)
+            .unwrap()
+        {
+            ResponseType::List(keys) => keys,
+            other => panic!("Respuesta inesperada de KEYS: {:?}", other),
+        }
+    }
+
+    /// Crea un `DataStore` con claves de los tres tipos.
+    fn set_up_data_store_for_keys() -> DataStore {
         let mut store = DataStore::new();
+        store.set("hello".to_string(), "Mercy".to_string());
+        store.set("hallo".to_string(), "Moira".to_string());
         store
-            .string_db
-            .insert("Ashe".to_string(), "B.O.B".to_string());
+            .list_db
+            .insert("heroes".to_string(), vec!["Hazard".to_string()]);
+        store
+            .set_db
+            .insert("ac".to_string(), HashSet::from(["Ana".to_string()]));
+        store
+            .set_db
+            .insert("bc".to_string(), HashSet::from(["Brigitte".to_string()]));
+        store.set("cc".to_string(), "Cassidy".to_string());
+        store
+    }
 
-        let smem_cmd = Command::Smembers("Ashe".to_string());
-        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+    #[test]
+    fn keys_with_single_character_wildcard() {
+        let store = set_up_data_store_for_keys();
+        assert_eq!(keys_matching(&store, "h?llo"), vec!["hallo", "hello"]);
+    }
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
-        // "Ashe" debe seguir en el contenedor de strings.
-        assert_eq!(store.string_db.len(), 1);
-        assert_eq!(store.string_db.get("Ashe").unwrap(), "B.O.B");
+    #[test]
+    fn keys_with_prefix_wildcard() {
+        let store = set_up_data_store_for_keys();
+        assert_eq!(
+            keys_matching(&store, "h*"),
+            vec!["hallo", "hello", "heroes"]
+        );
     }
 
     #[test]
-    fn smembers_doesnt_work_over_lists() {
-        let mut store = DataStore::new();
-        // Inserta una lista en "Maps" por medio de RPUSH.
+    fn keys_with_character_class() {
+        let store = set_up_data_store_for_keys();
+        assert_eq!(keys_matching(&store, "[ab]c"), vec!["ac", "bc"]);
+    }
+
+    #[test]
+    fn keys_skips_expired_keys() {
+        let mut store = set_up_data_store_for_keys();
         store
-            .list_db
-            .insert("Maps".to_string(), vec!["Oasis".to_string()]);
+            .expires
+            .insert("hallo".to_string(), std::time::Instant::now());
+        assert_eq!(keys_matching(&store, "h?llo"), vec!["hello"]);
+    }
 
-        let smem_cmd = Command::Smembers("Maps".to_string());
-        let result = smem_cmd.execute_read(&mut store, None, None, None, None, None);
+    /* DBSIZE / FLUSHDB TESTS */
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
-        // La clave "Maps" debe permanecer en list_db.
-        let list = store.list_db.get("Maps").unwrap();
-        assert_eq!(list.len(), 1);
-        assert_eq!(list[0], "Oasis".to_string());
+    /// Ejecuta DBSIZE y devuelve la cantidad de claves.
+    fn dbsize_of(store: &DataStore) -> ResponseType {
+        Command::DbSize
+            .execute_read(store, None, None, None, None, None)
+            .unwrap()
     }
 
-    /* SMOVE */
+    #[test]
+    fn dbsize_on_empty_store() {
+        let store = DataStore::new();
+        assert_eq!(dbsize_of(&store), ResponseType::Int(0));
+    }
 
     #[test]
-    fn smove_works_for_an_empty_set() {
-        let mut store = DataStore::new();
-        let smove_cmd = Command::SMove(
-            "Maps".to_string(),
-            "Maps2".to_string(),
-            "El Dorado".to_string(),
+    fn dbsize_counts_keys_across_types() {
+        let mut store = set_up_data_store_for_keys();
+        store.hash_db.insert(
+            "Reinhardt".to_string(),
+            HashMap::from([("role".to_string(), "tank".to_string())]),
         );
-        let result = smove_cmd.execute_write(&mut store);
+        assert_eq!(dbsize_of(&store), ResponseType::Int(7));
+    }
 
-        // Al no existir el set "Maps", no se mueve nada.
-        assert_eq!(store.set_db.len(), 0);
-        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    #[test]
+    fn dbsize_skips_expired_keys() {
+        let mut store = set_up_data_store_for_keys();
+        store
+            .expires
+            .insert("hallo".to_string(), std::time::Instant::now());
+        assert_eq!(dbsize_of(&store), ResponseType::Int(5));
     }
 
     #[test]
-    fn smove_works_for_an_non_empty_set() {
-        let mut store = DataStore::new();
+    fn flushdb_clears_every_type_and_expiry() {
+        let mut store = set_up_data_store_for_keys();
+        store.set_ttl("hello", Some(std::time::Duration::from_secs(10)));
 
-        // Crear el conjunto de origen con 3 elementos.
-        store.set_db.insert(
-            "SourceSet".to_string(),
-            HashSet::from([
-                "El Dorado".to_string(),
-                "Petra".to_string(),
-                "Busan".to_string(),
-            ]),
-        );
+        let result = Command::FlushDb.execute_write(&mut store).unwrap();
 
-        // Crear el conjunto de destino vacío.
-        store
-            .set_db
-            .insert("DestinationSet".to_string(), HashSet::new());
+        assert_eq!(result, ResponseType::Str("OK".to_string()));
+        assert_eq!(dbsize_of(&store), ResponseType::Int(0));
+        assert!(store.expires.is_empty());
+        assert!(store.list_db.is_empty());
+        assert!(store.set_db.is_empty());
+    }
 
-        // Mover "Petra" de SourceSet a DestinationSet.
-        let smove_cmd = Command::SMove(
-            "SourceSet".to_string(),
-            "DestinationSet".to_string(),
-            "Petra".to_string(),
-        );
-        let result = smove_cmd.execute_write(&mut store);
+    #[test]
+    fn set_after_flushdb_has_no_ttl() {
+        let mut store = set_up_data_store_for_keys();
+        store.set_ttl("hello", Some(std::time::Duration::from_secs(10)));
+        Command::FlushDb.execute_write(&mut store).unwrap();
 
-        // Se espera que se mueva 1 (exitosamente).
-        assert_eq!(result.unwrap(), ResponseType::Int(1));
+        Command::Set(
+            "hello".to_string(),
+            "Mercy".to_string(),
+            SetOptions::default(),
+        )
+        .execute_write(&mut store)
+        .unwrap();
 
-        // Verificar que "Petra" ya no se encuentre en SourceSet.
-        let source_set = store
-            .set_db
-            .get("SourceSet")
-            .expect("Debe existir SourceSet");
-        assert_eq!(source_set.len(), 2);
-        assert!(source_set.contains("El Dorado"));
-        assert!(source_set.contains("Busan"));
-        assert!(!source_set.contains("Petra"));
+        assert_eq!(store.remaining_ttl("hello"), None);
+        assert_eq!(dbsize_of(&store), ResponseType::Int(1));
+    }
 
-        // Verificar que "Petra" se haya insertado en DestinationSet.
-        let dest_set = store
-            .set_db
-            .get("DestinationSet")
-            .expect("Debe existir DestinationSet");
-        assert_eq!(dest_set.len(), 1);
-        assert!(dest_set.contains("Petra"));
+    /* RENAME / RENAMENX TESTS */
+
+    fn rename_cmd(source: &str, destination: &str) -> Command {
+        Command::Rename(source.to_string(), destination.to_string())
+    }
+
+    fn renamenx_cmd(source: &str, destination: &str) -> Command {
+        Command::Renamenx(source.to_string(), destination.to_string())
     }
 
     #[test]
-    fn smove_doesnt_work_for_both_src_and_dst_strings() {
-        let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("Hammond".to_string(), "Ball".to_string());
-        store
-            .string_db
-            .insert("Winton".to_string(), "Honey".to_string());
-        let smove_cmd = Command::SMove(
-            "Hammond".to_string(),
-            "Winton".to_string(),
-            "Ball".to_string(),
-        );
-        let result = smove_cmd.execute_write(&mut store);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    fn rename_moves_string_value() {
+        let mut store = set_up_data_store_for_keys();
+        let result = rename_cmd("hello", "Mercy")
+            .execute_write(&mut store)
+            .unwrap();
+
+        assert_eq!(result, ResponseType::Str("OK".to_string()));
+        assert_eq!(store.get("Mercy"), Some(&"Mercy".to_string()));
+        assert!(!store.contains_key("hello"));
     }
 
     #[test]
-    fn smove_doesnt_work_for_src_string() {
-        let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("Hammond".to_string(), "Ball".to_string());
-        let mut aux = HashSet::new();
-        aux.insert("Glasses".to_string());
-        aux.insert("Honey".to_string());
-        store.set_db.insert("Winton".to_string(), aux);
+    fn rename_string_onto_existing_list() {
+        let mut store = set_up_data_store_for_keys();
+        rename_cmd("hello", "heroes")
+            .execute_write(&mut store)
+            .unwrap();
 
-        let smove_cmd = Command::SMove(
-            "Hammond".to_string(),
-            "Winton".to_string(),
-            "Hammond".to_string(),
-        );
-        let result = smove_cmd.execute_write(&mut store);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        assert_eq!(store.type_of("heroes"), "string");
+        assert_eq!(store.get("heroes"), Some(&"Mercy".to_string()));
+        assert!(!store.list_db.contains_key("heroes"));
+        assert_eq!(dbsize_of(&store), ResponseType::Int(5));
+    }
+
+    #[test]
+    fn rename_moves_set_onto_string() {
+        let mut store = set_up_data_store_for_keys();
+        rename_cmd("ac", "cc").execute_write(&mut store).unwrap();
+
+        assert_eq!(store.type_of("cc"), "set");
+        assert_eq!(store.get("cc"), None);
+        assert_eq!(store.type_of("ac"), "none");
+    }
+
+    #[test]
+    fn rename_missing_key_fails() {
+        let mut store = set_up_data_store_for_keys();
+        match rename_cmd("Sombra", "Olivia")
+            .execute_write(&mut store)
+            .unwrap_err()
+        {
+            CommandError::Custom(msg) => assert_eq!(msg, "ERR no such key"),
+            other => panic!("Error inesperado: {:?}", other),
+        }
+        assert!(!store.contains_key("Olivia"));
+    }
+
+    #[test]
+    fn rename_moves_expiry_with_the_key() {
+        let mut store = set_up_data_store_for_keys();
+        store.set_ttl("hello", Some(std::time::Duration::from_secs(10)));
+        store.set_ttl("heroes", Some(std::time::Duration::from_secs(100)));
+
+        rename_cmd("hello", "heroes")
+            .execute_write(&mut store)
+            .unwrap();
+
+        assert_eq!(store.remaining_ttl("hello"), None);
+        let ttl = store.remaining_ttl("heroes").unwrap();
+        assert!(ttl <= std::time::Duration::from_secs(10));
+    }
+
+    #[test]
+    fn rename_onto_key_with_ttl_drops_destination_ttl() {
+        let mut store = set_up_data_store_for_keys();
+        store.set_ttl("heroes", Some(std::time::Duration::from_secs(100)));
+
+        rename_cmd("hello", "heroes")
+            .execute_write(&mut store)
+            .unwrap();
+
+        assert_eq!(store.remaining_ttl("heroes"), None);
     }
 
     #[test]
-    fn smove_doesnt_work_for_dst_string() {
-        let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("Hammond".to_string(), "Ball".to_string());
-        let mut aux = HashSet::new();
-        aux.insert("Glasses".to_string());
-        aux.insert("Honey".to_string());
-        store.set_db.insert("Winton".to_string(), aux);
+    fn rename_to_itself_keeps_value() {
+        let mut store = set_up_data_store_for_keys();
+        let result = rename_cmd("hello", "hello")
+            .execute_write(&mut store)
+            .unwrap();
 
-        let smove_cmd = Command::SMove(
-            "Winton".to_string(),
-            "Hammond".to_string(),
-            "Honey".to_string(),
-        );
-        let result = smove_cmd.execute_write(&mut store);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        assert_eq!(result, ResponseType::Str("OK".to_string()));
+        assert_eq!(store.get("hello"), Some(&"Mercy".to_string()));
     }
 
     #[test]
-    fn smove_doesnt_work_for_both_src_and_dst_lists() {
-        let mut store = DataStore::new();
-        store.list_db.insert("Hammond".to_string(), vec![]);
-        store.list_db.insert("Winton".to_string(), vec![]);
-        let smove_cmd = Command::SMove(
-            "Hammond".to_string(),
-            "Winton".to_string(),
-            "Ball".to_string(),
-        );
-        let result = smove_cmd.execute_write(&mut store);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    fn renamenx_to_free_key() {
+        let mut store = set_up_data_store_for_keys();
+        let result = renamenx_cmd("heroes", "Junker Queen")
+            .execute_write(&mut store)
+            .unwrap();
+
+        assert_eq!(result, ResponseType::Int(1));
+        assert_eq!(store.list_db["Junker Queen"], vec!["Hazard".to_string()]);
+        assert!(!store.contains_key("heroes"));
     }
 
     #[test]
-    fn smove_doesnt_work_for_src_list() {
-        let mut store = DataStore::new();
-        let mut aux = HashSet::new();
-        aux.insert("Ball".to_string());
-        store.set_db.insert("Hammond".to_string(), aux);
-        store
-            .list_db
-            .insert("Winton".to_string(), vec!["Glasses".to_string()]);
-        let smove_cmd = Command::SMove(
-            "Winton".to_string(),
-            "Hammond".to_string(),
-            "Glasses".to_string(),
-        );
-        let result = smove_cmd.execute_write(&mut store);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    fn renamenx_does_not_overwrite_existing_key() {
+        let mut store = set_up_data_store_for_keys();
+        let result = renamenx_cmd("hello", "heroes")
+            .execute_write(&mut store)
+            .unwrap();
+
+        assert_eq!(result, ResponseType::Int(0));
+        assert_eq!(store.get("hello"), Some(&"Mercy".to_string()));
+        assert_eq!(store.type_of("heroes"), "list");
     }
 
     #[test]
-    fn smove_doesnt_work_for_dest_list() {
-        let mut store = DataStore::new();
-        let mut aux = HashSet::new();
-        aux.insert("Ball".to_string());
-        store.set_db.insert("Hammond".to_string(), aux);
-        store.list_db.insert("Winton".to_string(), vec![]);
-        let smove_cmd = Command::SMove(
-            "Hammond".to_string(),
-            "Winton".to_string(),
-            "Ball".to_string(),
-        );
-        let result = smove_cmd.execute_write(&mut store);
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+    fn renamenx_missing_key_fails() {
+        let mut store = set_up_data_store_for_keys();
+        match renamenx_cmd("Sombra", "Olivia")
+            .execute_write(&mut store)
+            .unwrap_err()
+        {
+            CommandError::Custom(msg) => assert_eq!(msg, "ERR no such key"),
+            other => panic!("Error inesperado: {:?}", other),
+        }
+    }
+
+    /* HYPERLOGLOG TESTS */
+
+    /// Ejecuta PFCOUNT sobre las claves y devuelve la estimación.
+    fn pfcount_of(store: &DataStore, keys: &[&str]) -> i64 {
+        let cmd = Command::Pfcount(keys.iter().map(|key| key.to_string()).collect());
+        match cmd
+            .execute_read(store, None, None, None, None, None)
+            .unwrap()
+        {
+            ResponseType::Int(count) => count,
+            other => panic!("Respuesta inesperada de PFCOUNT: {:?}", other),
+        }
     }
 
     #[test]
-    fn smove_works_for_both_non_existent_sets() {
-        let mut empty_store = DataStore::new();
-        let smove_cmd = Command::SMove(
-            "Hammond".to_string(),
-            "Winton".to_string(),
-            "Ball".to_string(),
-        );
-        let result = smove_cmd.execute_write(&mut empty_store);
-        assert_eq!(result.unwrap(), ResponseType::Int(0));
+    fn pfadd_distinct_elements_within_error_bound() {
+        let mut store = DataStore::new();
+        let players: Vec<String> = (0..5_000).map(|i| format!("player:{}", i)).collect();
+
+        let cmd = Command::Pfadd("Players".to_string(), players.clone());
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(1));
+        // Volver a agregar los mismos elementos no cambia la estimación
+        let cmd = Command::Pfadd("Players".to_string(), players);
+        assert_eq!(cmd.execute_write(&mut store).unwrap(), ResponseType::Int(0));
+
+        let count = pfcount_of(&store, &["Players"]);
+        assert!((count - 5_000).abs() < 150, "{}", count);
     }
 
     #[test]
-    fn smove_works_for_non_existent_src_set() {
+    fn pfmerge_unions_registers() {
         let mut store = DataStore::new();
-        let mut aux = HashSet::new();
-        aux.insert("Ball".to_string());
-        store.set_db.insert("Hammond".to_string(), aux);
-        let smove_cmd = Command::SMove(
-            "Winton".to_string(),
-            "Hammond".to_string(),
-            "Glasses".to_string(),
+        let tanks = vec!["Reinhardt", "Zarya", "Winston", "D.Va"];
+        let damage = vec!["Genji", "Tracer", "D.Va", "Sojourn"];
+        for (key, heroes) in [("Tanks", tanks), ("Damage", damage)] {
+            let elements = heroes.into_iter().map(String::from).collect();
+            Command::Pfadd(key.to_string(), elements)
+                .execute_write(&mut store)
+                .unwrap();
+        }
+
+        let cmd = Command::Pfmerge(
+            "Heroes".to_string(),
+            vec!["Tanks".to_string(), "Damage".to_string()],
         );
-        let result = smove_cmd.execute_write(&mut store);
-        assert_eq!(result.unwrap(), ResponseType::Int(0));
+        let result = cmd.execute_write(&mut store);
+
+        assert_eq!(result.unwrap(), ResponseType::Str("OK".to_string()));
+        assert_eq!(pfcount_of(&store, &["Heroes"]), 7);
+        assert_eq!(pfcount_of(&store, &["Tanks", "Damage"]), 7);
     }
 
     #[test]
-    fn smove_works_for_non_existent_dst_set() {
+    fn pfcount_of_plain_string_fails() {
         let mut store = DataStore::new();
-        let mut aux = HashSet::new();
-        aux.insert("Ball".to_string());
-        store.set_db.insert("Hammond".to_string(), aux);
-        let smove_cmd = Command::SMove(
-            "Hammond".to_string(),
-            "Winton".to_string(),
-            "Glasses".to_string(),
+        store.set("Hero".to_string(), "Mercy".to_string());
+
+        let cmd = Command::Pfcount(vec!["Hero".to_string()]);
+        let result = cmd.execute_read(&store, None, None, None, None, None);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "WRONGTYPE Key is not a valid HyperLogLog string value."
         );
-        let result = smove_cmd.execute_write(&mut store);
-        assert_eq!(result.unwrap(), ResponseType::Int(0));
     }
 
-    /* SPOP */
-
     #[test]
-    fn spop_empty_set_0_arg() {
-        let mut store = DataStore::new();
-        let set = HashSet::new();
-        store.set_db.insert("Maps".to_string(), set);
-        let spop_cmd = Command::Spop("Maps".to_string(), 0);
-        let result = spop_cmd.execute_write(&mut store);
+    fn pfadd_on_list_is_wrongtype() {
+        let mut store = set_up_data_store_with_multiple_items_list();
 
-        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert!(store.set_db.get("Maps").is_some());
+        let cmd = Command::Pfadd("DPS".to_string(), vec!["Ashe".to_string()]);
+        let result = cmd.execute_write(&mut store);
+
+        assert!(matches!(result, Err(CommandError::WrongType)));
+    }
+
+    /* STREAM TESTS */
+
+    /// Ejecuta XADD con un único par `hero` y devuelve el resultado.
+    fn xadd_hero(
+        store: &mut DataStore,
+        id: Option<StreamId>,
+        hero: &str,
+    ) -> Result<ResponseType, CommandError> {
+        let fields = vec![("hero".to_string(), hero.to_string())];
+        Command::Xadd("Picks".to_string(), id, fields).execute_write(store)
     }
 
     #[test]
-    fn spop_empty_set_bigger_arg() {
+    fn xadd_auto_ids_are_increasing() {
         let mut store = DataStore::new();
-        let set = HashSet::new();
-        store.set_db.insert("Maps".to_string(), set);
-        let spop_cmd = Command::Spop("Maps".to_string(), 5);
-        let result = spop_cmd.execute_write(&mut store);
-
-        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert!(store.set_db.get("Maps").is_some());
+        let mut ids = vec![];
+        for hero in ["Ana", "Kiriko", "Lucio", "Mercy", "Moira"] {
+            match xadd_hero(&mut store, None, hero).unwrap() {
+                ResponseType::Str(id) => ids.push(StreamId::parse(&id, 0).unwrap()),
+                other => panic!("Respuesta inesperada de XADD: {:?}", other),
+            }
+        }
+        // Varias entradas en el mismo milisegundo se diferencian por la secuencia
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", ids);
     }
 
     #[test]
-    fn spop_set_with_one_item() {
+    fn xadd_explicit_id_not_greater_than_last_fails() {
         let mut store = DataStore::new();
-        store
-            .set_db
-            .insert("DPS".to_string(), HashSet::from(["Soldier:76".to_string()]));
+        xadd_hero(&mut store, Some(StreamId::new(5, 1)), "Genji").unwrap();
+
+        for id in [StreamId::new(5, 1), StreamId::new(4, 9)] {
+            let result = xadd_hero(&mut store, Some(id), "Hanzo");
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "ERR The ID specified in XADD is equal or smaller than the target stream top item"
+            );
+        }
+        assert_eq!(
+            xadd_hero(&mut store, Some(StreamId::new(5, 2)), "Hanzo").unwrap(),
+            ResponseType::Str("5-2".to_string())
+        );
+    }
 
-        let spop_cmd = Command::Spop("DPS".to_string(), 1);
-        let result = spop_cmd.execute_write(&mut store);
+    #[test]
+    fn xrange_returns_entries_in_id_order() {
+        let mut store = DataStore::new();
+        for (ms, hero) in [(10, "Tracer"), (20, "Sombra"), (30, "Genji")] {
+            xadd_hero(&mut store, Some(StreamId::new(ms, 0)), hero).unwrap();
+        }
 
+        let cmd = Command::Xrange("Picks".to_string(), StreamId::MIN, StreamId::MAX, None);
+        let result = cmd.execute_read(&store, None, None, None, None, None);
+        let expected: Vec<String> = vec![
+            "10-0", "1", "hero", "Tracer", "20-0", "1", "hero", "Sombra", "30-0", "1", "hero",
+            "Genji",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        assert_eq!(result.unwrap(), ResponseType::List(expected));
+
+        let cmd = Command::Xrange(
+            "Picks".to_string(),
+            StreamId::new(15, 0),
+            StreamId::MAX,
+            Some(1),
+        );
+        let result = cmd.execute_read(&store, None, None, None, None, None);
         assert_eq!(
             result.unwrap(),
-            ResponseType::List(vec!["Soldier:76".to_string()])
+            ResponseType::List(vec![
+                "20-0".to_string(),
+                "1".to_string(),
+                "hero".to_string(),
+                "Sombra".to_string(),
+            ])
         );
-        assert_eq!(store.set_db.get("DPS").unwrap().len(), 0);
     }
 
     #[test]
-    fn spop_set_with_one_item_twice() {
+    fn xlen_matches_entry_count() {
         let mut store = DataStore::new();
-        store
-            .set_db
-            .insert("DPS".to_string(), HashSet::from(["Soldier:76".to_string()]));
-
-        let spop_cmd = Command::Spop("DPS".to_string(), 1);
-        let _ = spop_cmd.execute_write(&mut store);
+        let cmd = Command::Xlen("Picks".to_string());
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(0)
+        );
 
-        let spop_cmd_again = Command::Spop("DPS".to_string(), 1);
-        let result = spop_cmd_again.execute_write(&mut store);
+        for hero in ["Reinhardt", "Zarya", "Winston"] {
+            xadd_hero(&mut store, None, hero).unwrap();
+        }
+        // Un XADD rechazado no agrega entradas
+        assert!(xadd_hero(&mut store, Some(StreamId::new(1, 0)), "Orisa").is_err());
 
-        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert_eq!(store.set_db.get("DPS").unwrap().len(), 0);
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::Int(3)
+        );
     }
 
     #[test]
-    fn spop_set_with_few_items_zero_arg() {
+    fn xread_returns_only_newer_entries() {
         let mut store = DataStore::new();
-        store.set_db.insert(
-            "DPS".to_string(),
-            HashSet::from([
-                "Echo".to_string(),
-                "Pharah".to_string(),
-                "Sombra".to_string(),
-            ]),
-        );
+        for (ms, hero) in [(1, "Ana"), (2, "Baptiste")] {
+            xadd_hero(&mut store, Some(StreamId::new(ms, 0)), hero).unwrap();
+        }
 
-        let spop_cmd = Command::Spop("DPS".to_string(), 0);
-        let result = spop_cmd.execute_write(&mut store);
+        let streams = vec![
+            ("Picks".to_string(), StreamId::new(1, 0)),
+            ("Bans".to_string(), StreamId::MIN),
+        ];
+        let result =
+            Command::Xread(None, None, streams).execute_read(&store, None, None, None, None, None);
+        let expected: Vec<String> = vec!["Picks", "1", "2-0", "1", "hero", "Baptiste"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(result.unwrap(), ResponseType::List(expected));
 
-        assert_eq!(result.unwrap(), ResponseType::List(vec![]));
-        assert_eq!(store.set_db.get("DPS").unwrap().len(), 3);
+        let streams = vec![("Picks".to_string(), StreamId::new(2, 0))];
+        let result =
+            Command::Xread(None, None, streams).execute_read(&store, None, None, None, None, None);
+        assert_eq!(result.unwrap(), ResponseType::Null(None));
     }
 
     #[test]
-    fn spop_set_with_few_items_middle_arg() {
-        let mut store = set_up_data_store_with_multiple_items_set();
+    fn xadd_on_string_is_wrongtype() {
+        let mut store = DataStore::new();
+        store.set("Picks".to_string(), "Mercy".to_string());
 
-        let spop_cmd = Command::Spop("Maps".to_string(), 2);
-        let result = spop_cmd.execute_write(&mut store);
+        let result = xadd_hero(&mut store, None, "Ana");
 
-        let result_list = match result.unwrap() {
-            ResponseType::List(list) => list,
-            _ => panic!("Expected a list response"),
-        };
+        assert!(matches!(result, Err(CommandError::WrongType)));
+    }
 
-        // No sabés que se va a ir
-        assert_eq!(result_list.len(), 2);
-        assert_eq!(store.set_db.get("Maps").unwrap().len(), 1);
+    /// Crea un stream `Picks` con tres entradas y el grupo indicado desde el inicio.
+    fn set_up_stream_with_group(group: &str) -> DataStore {
+        let mut store = DataStore::new();
+        for (ms, hero) in [(1, "Tracer"), (2, "Winston"), (3, "Ana")] {
+            xadd_hero(&mut store, Some(StreamId::new(ms, 0)), hero).unwrap();
+        }
+        let cmd = Command::XgroupCreate(
+            "Picks".to_string(),
+            group.to_string(),
+            Some(StreamId::MIN),
+            false,
+        );
+        assert_eq!(
+            cmd.execute_write(&mut store).unwrap(),
+            ResponseType::Str("OK".to_string())
+        );
+        store
     }
 
-    #[test]
-    fn spop_set_with_few_items_large_arg() {
-        let mut store = set_up_data_store_with_multiple_items_set();
+    /// Ejecuta XREADGROUP con `>` sobre `Picks` y devuelve los IDs entregados.
+    fn xreadgroup_ids(store: &mut DataStore, group: &str, consumer: &str) -> Vec<String> {
+        let cmd = Command::Xreadgroup(
+            group.to_string(),
+            consumer.to_string(),
+            Some(2),
+            vec![("Picks".to_string(), None)],
+        );
+        match cmd.execute_write(store).unwrap() {
+            ResponseType::List(items) => items
+                .into_iter()
+                .filter(|item| item.ends_with("-0"))
+                .collect(),
+            ResponseType::Null(_) => vec![],
+            other => panic!("Respuesta inesperada de XREADGROUP: {:?}", other),
+        }
+    }
 
-        let spop_cmd = Command::Spop("Maps".to_string(), 50);
-        let result = spop_cmd.execute_write(&mut store);
+    #[test]
+    fn xreadgroup_delivers_each_entry_once_per_group() {
+        let mut store = set_up_stream_with_group("Overwatch");
+        Command::XgroupCreate(
+            "Picks".to_string(),
+            "Talon".to_string(),
+            Some(StreamId::MIN),
+            false,
+        )
+        .execute_write(&mut store)
+        .unwrap();
 
-        let result_list = match result.unwrap() {
-            ResponseType::List(list) => list,
-            _ => panic!("Expected a list response"),
-        };
+        // Los consumidores de un mismo grupo se reparten las entradas
+        assert_eq!(
+            xreadgroup_ids(&mut store, "Overwatch", "Tracer"),
+            vec!["1-0", "2-0"]
+        );
+        assert_eq!(
+            xreadgroup_ids(&mut store, "Overwatch", "Winston"),
+            vec!["3-0"]
+        );
+        assert!(xreadgroup_ids(&mut store, "Overwatch", "Tracer").is_empty());
 
-        assert_eq!(result_list.len(), 3);
-        assert!(result_list.contains(&"El Dorado".to_string()));
-        assert!(result_list.contains(&"Petra".to_string()));
-        assert!(result_list.contains(&"Busan".to_string()));
-        assert_eq!(store.set_db.get("Maps").unwrap().len(), 0);
+        // Otro grupo recibe todas las entradas de nuevo
+        assert_eq!(
+            xreadgroup_ids(&mut store, "Talon", "Reaper"),
+            vec!["1-0", "2-0"]
+        );
+        assert_eq!(xreadgroup_ids(&mut store, "Talon", "Reaper"), vec!["3-0"]);
     }
 
     #[test]
-    fn spop_wrongtype_str() {
-        let mut store = DataStore::new();
-        store
-            .string_db
-            .insert("Perú".to_string(), "Illari".to_string());
+    fn xack_shrinks_xpending() {
+        let mut store = set_up_stream_with_group("Overwatch");
+        xreadgroup_ids(&mut store, "Overwatch", "Tracer");
+        xreadgroup_ids(&mut store, "Overwatch", "Winston");
 
-        let spop_cmd = Command::Spop("Perú".to_string(), 1);
-        let result = spop_cmd.execute_write(&mut store);
+        let pending = Command::Xpending("Picks".to_string(), "Overwatch".to_string());
+        let expected: Vec<String> = vec!["3", "1-0", "3-0", "Tracer", "2", "Winston", "1"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            pending
+                .execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::List(expected)
+        );
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
+        let ack = Command::Xack(
+            "Picks".to_string(),
+            "Overwatch".to_string(),
+            vec![
+                StreamId::new(1, 0),
+                StreamId::new(3, 0),
+                StreamId::new(9, 0),
+            ],
+        );
+        assert_eq!(ack.execute_write(&mut store).unwrap(), ResponseType::Int(2));
+        // Confirmar dos veces la misma entrada no cuenta
+        assert_eq!(ack.execute_write(&mut store).unwrap(), ResponseType::Int(0));
+
+        let expected: Vec<String> = vec!["1", "2-0", "2-0", "Tracer", "1"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            pending
+                .execute_read(&store, None, None, None, None, None)
+                .unwrap(),
+            ResponseType::List(expected)
+        );
     }
 
     #[test]
-    fn spop_wrongtype_list() {
-        let mut store = DataStore::new();
-        store
-            .list_db
-            .insert("AUS".to_string(), vec!["Junk*".to_string()]);
+    fn xgroup_errors() {
+        let mut store = set_up_stream_with_group("Overwatch");
 
-        let spop_cmd = Command::Spop("AUS".to_string(), 1);
-        let result = spop_cmd.execute_write(&mut store);
+        let cmd = Command::XgroupCreate("Picks".to_string(), "Overwatch".to_string(), None, false);
+        assert_eq!(
+            cmd.execute_write(&mut store).unwrap_err().to_string(),
+            "BUSYGROUP Consumer Group name already exists"
+        );
 
-        assert!(matches!(result.unwrap_err(), CommandError::WrongType));
-    }
+        let cmd = Command::XgroupCreate("Bans".to_string(), "Overwatch".to_string(), None, false);
+        assert!(cmd.execute_write(&mut store).is_err());
+        let cmd = Command::XgroupCreate("Bans".to_string(), "Overwatch".to_string(), None, true);
+        assert!(cmd.execute_write(&mut store).is_ok());
+        assert_eq!(store.stream_db["Bans"].len(), 0);
 
-    #[test]
-    fn spop_nonexistent_key() {
-        let mut store = DataStore::new();
-        let spop_cmd = Command::Spop("NonExistentKey".to_string(), 1);
-        let result = spop_cmd.execute_write(&mut store);
-        assert_eq!(result.unwrap(), ResponseType::Null(None));
+        let cmd = Command::Xpending("Picks".to_string(), "Talon".to_string());
+        assert_eq!(
+            cmd.execute_read(&store, None, None, None, None, None)
+                .unwrap_err()
+                .to_string(),
+            "NOGROUP No such key 'Picks' or consumer group 'Talon'"
+        );
     }
 }
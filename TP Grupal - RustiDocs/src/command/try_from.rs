@@ -139,7 +139,19 @@ impl TryFrom<RespMessage> for Instruction {
                             format!("ERR: {str_err}")
                         }
                         RespMessage::BulkError(None) => "ERR: null".to_string(),
-                        RespMessage::Array(_) => {
+                        RespMessage::Verbatim(_, text) => {
+                            String::from_utf8(text).map_err(|_| {
+                                TryFromError::InvalidUtf8InArgument(format!(
+                                    "at position {}",
+                                    index
+                                ))
+                            })?
+                        }
+                        RespMessage::Array(_)
+                        | RespMessage::Push(_)
+                        | RespMessage::Map(_)
+                        | RespMessage::Set(_)
+                        | RespMessage::Attribute(_, _) => {
                             return Err(TryFromError::NestedArraysNotSupported);
                         }
                         RespMessage::Disconnect => "DISCONNECT".to_string(),
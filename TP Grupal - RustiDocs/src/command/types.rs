@@ -14,8 +14,11 @@
 // IMPORTS
 use crate::network::RespMessage;
 use crate::security::types::Password;
+use crate::storage::geo::GeoUnit;
+use crate::storage::stream::{StreamFields, StreamId};
 use std::collections::HashSet;
 use std::sync::mpsc::Sender;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Errores específicos de tipos de comando
 #[derive(Debug, Clone, PartialEq)]
@@ -149,11 +152,22 @@ impl ResponseType {
 /// - `Append` - Concatena un valor a una clave existente
 /// - `Echo` - Devuelve el string que recibe
 /// - `Get` - Obtiene el valor de una clave
+/// - `Incr` - Incrementa en uno el entero guardado en una clave
+/// - `Decr` - Decrementa en uno el entero guardado en una clave
+/// - `IncrBy` - Suma un valor al entero guardado en una clave
+/// - `DecrBy` - Resta un valor al entero guardado en una clave
+/// - `Mget` - Obtiene los valores de varias claves
+/// - `Mset` - Asigna los valores de varias claves
 /// - `Getdel` - Obtiene y elimina el valor de una clave
+/// - `GetSet` - Reemplaza el valor de una clave devolviendo el anterior
 /// - `Getrange` - Obtiene un substring
 /// - `Set` - Establece el valor de una clave
 /// - `Strlen` - Obtiene la longitud de un string
 /// - `Substr` - Obtiene un substring
+/// - `SetRange` - Sobrescribe parte de un string desde un offset
+/// - `Pfadd` - Agrega elementos a un HyperLogLog
+/// - `Pfcount` - Estima la cardinalidad de uno o más HyperLogLog
+/// - `Pfmerge` - Une varios HyperLogLog en una clave
 ///
 /// ## List Commands
 /// - `Del` - Elimina claves
@@ -161,28 +175,102 @@ impl ResponseType {
 /// - `Lpop` - Elimina elementos del inicio de una lista
 /// - `Lpush` - Agrega elementos al inicio de una lista
 /// - `Lrange` - Obtiene un rango de elementos de una lista
+/// - `Lindex` - Obtiene el elemento de una lista en una posición
+/// - `Lset` - Reemplaza el elemento de una lista en una posición
+/// - `Lrem` - Elimina de una lista los elementos iguales a un valor
+/// - `Linsert` - Inserta un elemento antes o después de otro en una lista
+/// - `LMove` - Mueve un elemento de una lista a otra (también `RPOPLPUSH`)
 /// - `Rpop` - Elimina elementos del final de una lista
 /// - `Rpush` - Agrega elementos al final de una lista
+/// - `Sort` - Ordena los elementos de una lista o set
 ///
 /// ## Set Commands
 /// - `Sadd` - Agrega elementos a un conjunto
 /// - `Scard` - Obtiene el cardinal de un conjunto
 /// - `Sismember` - Verifica si un elemento pertenece a un conjunto
 /// - `Smembers` - Obtiene todos los elementos de un conjunto
+/// - `Sinter` - Obtiene la intersección de varios conjuntos
+/// - `Sunion` - Obtiene la unión de varios conjuntos
+/// - `Sdiff` - Obtiene la diferencia entre un conjunto y otros
+/// - `SRandMember` - Obtiene elementos aleatorios de un conjunto
 /// - `SMove` - Mueve un elemento entre conjuntos
 /// - `Spop` - Elimina elementos aleatorios de un conjunto
+/// - `Srem` - Elimina elementos de un conjunto
+///
+/// ## Hash Commands
+/// - `Hset` - Asigna campos de un hash
+/// - `Hget` - Obtiene el valor de un campo de un hash
+/// - `Hdel` - Elimina campos de un hash
+/// - `Hgetall` - Obtiene todos los campos y valores de un hash
+///
+/// ## Geo Commands
+/// - `Geoadd` - Agrega miembros con su posición a un sorted set
+/// - `Geopos` - Obtiene la posición de miembros
+/// - `Geodist` - Obtiene la distancia entre dos miembros
+/// - `Geosearch` - Busca los miembros dentro de un área
+///
+/// ## Stream Commands
+/// - `Xadd` - Agrega una entrada a un stream
+/// - `Xlen` - Obtiene la cantidad de entradas de un stream
+/// - `Xrange` - Obtiene las entradas de un stream en un rango de IDs
+/// - `Xread` - Lee las entradas posteriores a un ID de uno o más streams
+/// - `XgroupCreate` - Crea un grupo de consumidores
+/// - `Xreadgroup` - Entrega entradas a un consumidor de un grupo
+/// - `Xack` - Confirma entradas entregadas a un grupo
+/// - `Xpending` - Resume las entradas pendientes de confirmación de un grupo
 ///
 /// ## Database Commands
 /// - `BgSave` - Guarda la base de datos en segundo plano
 /// - `Save` - Guarda la base de datos
+/// - `Bgrewriteaof` - Reescribe el append only file en segundo plano
+/// - `Info` - Informa el estado del servidor
+/// - `Dump` - Serializa el valor de una clave
+/// - `Exists` - Cuenta cuántas de las claves existen
+/// - `Touch` - Actualiza el último acceso de las claves que existen
+/// - `Expire` - Fija el vencimiento de una clave
+/// - `PexpireAt` - Fija el vencimiento de una clave en un instante absoluto
+/// - `Persist` - Quita el vencimiento de una clave
+/// - `Ttl` - Consulta el tiempo de vida restante de una clave
+/// - `Type` - Informa el tipo del valor de una clave
+/// - `Rename` - Renombra una clave
+/// - `Renamenx` - Renombra una clave si el destino no existe
+/// - `Restore` - Crea una clave a partir de un valor serializado
+/// - `Debug` - Operaciones de diagnóstico sobre la base de datos
+/// - `Scan` - Recorre las claves de forma incremental
+/// - `Keys` - Lista las claves que cumplen un patrón
+/// - `DbSize` - Cuenta las claves de la base
+/// - `FlushDb` - Borra todas las claves de la base
+/// - `Latency` - Consulta el historial de eventos de latencia
+/// - `Object` - Inspecciona la representación interna de una clave
+/// - `CommandInfo` - Describe los comandos que soporta el servidor
 ///
 /// ## Pub/Sub Commands
 /// - `Subscribe` - Suscribe a un canal
 /// - `Unsubscribe` - Desuscribe de un canal
+/// - `PSubscribe` - Suscribe a los canales que cumplen patrones glob
+/// - `PUnsubscribe` - Desuscribe de patrones glob
 /// - `Publish` - Publica un mensaje en un canal
+/// - `Pubsub` - Consulta los canales activos y sus suscriptores
 ///
 /// ## Cluster Commands
 /// - `Meet` - Inicia el proceso de unión a un cluster
+/// - `ClusterHelp` - Describe los subcomandos de `CLUSTER`
+/// - `ClusterMyId` - Devuelve el ID del nodo
+/// - `ClusterNodes` - Describe los nodos del cluster que conoce el nodo
+/// - `Failover` - Intercambia de forma coordinada los roles del master y una réplica
+/// - `Wait` - Espera a que las réplicas confirmen las escrituras del cliente
+/// - `ReplicaOf` - Convierte al nodo en réplica de un master, o de nuevo en master
+/// - `Sync` - Transfiere el store completo y luego las escrituras a la conexión
+///
+/// ## Connection Commands
+/// - `Client` - Consulta o modifica el estado de la conexión del cliente
+/// - `Hello` - Negocia la versión del protocolo RESP de la conexión
+/// - `Ping` - Verifica que el servidor responda
+///
+/// ## Transaction Commands
+/// - `Multi` - Abre una transacción en la conexión
+/// - `Exec` - Ejecuta los comandos encolados en la transacción
+/// - `Discard` - Descarta la transacción
 #[derive(Clone, Debug, PartialEq)]
 pub enum Command {
     // STRING COMMANDS
@@ -208,6 +296,64 @@ pub enum Command {
     /// * `key` - Clave a obtener
     Get(String),
 
+    /// Incrementa en uno el entero guardado en una clave, que vale 0 si no
+    /// existe
+    ///
+    /// # Arguments
+    /// * `key` - Clave a incrementar
+    ///
+    /// # Returns
+    /// Valor luego del incremento
+    Incr(String),
+
+    /// Decrementa en uno el entero guardado en una clave, que vale 0 si no
+    /// existe
+    ///
+    /// # Arguments
+    /// * `key` - Clave a decrementar
+    ///
+    /// # Returns
+    /// Valor luego del decremento
+    Decr(String),
+
+    /// Suma un valor al entero guardado en una clave, que vale 0 si no
+    /// existe
+    ///
+    /// # Arguments
+    /// * `key` - Clave a incrementar
+    /// * `increment` - Valor a sumar
+    ///
+    /// # Returns
+    /// Valor luego del incremento
+    IncrBy(String, i64),
+
+    /// Resta un valor al entero guardado en una clave, que vale 0 si no
+    /// existe
+    ///
+    /// # Arguments
+    /// * `key` - Clave a decrementar
+    /// * `decrement` - Valor a restar
+    ///
+    /// # Returns
+    /// Valor luego del decremento
+    DecrBy(String, i64),
+
+    /// Devuelve los valores de varias claves, nulo para las que no
+    /// existen o no guardan un string
+    ///
+    /// # Arguments
+    /// * `keys` - Claves a obtener
+    Mget(Vec<String>),
+
+    /// Asigna los valores de varias claves de una sola vez
+    ///
+    /// # Arguments
+    /// * `pairs` - Pares clave-valor a asignar
+    ///
+    /// # Returns
+    /// `OK`
+    Mset(Vec<(String, String)>),
+
     /// Obtiene y elimina el valor de una clave
     ///
     /// # Arguments
@@ -217,6 +363,16 @@ pub enum Command {
     /// String o nil si no existe
     Getdel(String),
 
+    /// Guarda un valor nuevo y devuelve el que tenía la clave
+    ///
+    /// # Arguments
+    /// * `key` - Clave a modificar
+    /// * `value` - Valor nuevo
+    ///
+    /// # Returns
+    /// El valor anterior o nil si no existía
+    GetSet(String, String),
+
     /// Devuelve un substring de un string
     ///
     /// # Arguments
@@ -230,10 +386,21 @@ pub enum Command {
     /// # Arguments
     /// * `key` - Clave a establecer
     /// * `value` - Valor a asignar
+    /// * `options` - Condición (`NX`/`XX`) y vencimiento (`EX`)
     ///
     /// # Returns
-    /// "OK" string
-    Set(String, String),
+    /// "OK" string, nulo si no se cumplió la condición
+    Set(String, String, SetOptions),
+
+    /// Establece el valor de una clave sólo si no existe
+    ///
+    /// # Arguments
+    /// * `key` - Clave a establecer
+    /// * `value` - Valor a asignar
+    ///
+    /// # Returns
+    /// 1 si se asignó, 0 si la clave ya existía
+    Setnx(String, String),
 
     /// Obtiene la longitud de un string
     ///
@@ -252,6 +419,47 @@ pub enum Command {
     /// * `end` - Índice de fin
     Substr(String, i64, i64),
 
+    /// Sobrescribe el string a partir de un offset en bytes, completando
+    /// con `\x00` si el offset supera su largo
+    ///
+    /// # Arguments
+    /// * `key` - Clave del string
+    /// * `offset` - Byte desde el que se escribe
+    /// * `value` - Valor a escribir
+    ///
+    /// # Returns
+    /// Longitud del string resultante
+    SetRange(String, usize, String),
+
+    /// Agrega elementos al HyperLogLog guardado en la clave
+    ///
+    /// # Arguments
+    /// * `key` - Clave del HyperLogLog
+    /// * `elements` - Elementos a agregar
+    ///
+    /// # Returns
+    /// 1 si la cardinalidad estimada pudo haber cambiado, 0 si no
+    Pfadd(String, Vec<String>),
+
+    /// Estima la cardinalidad de la unión de los HyperLogLog
+    ///
+    /// # Arguments
+    /// * `keys` - Claves de los HyperLogLog
+    ///
+    /// # Returns
+    /// Cantidad aproximada de elementos distintos
+    Pfcount(Vec<String>),
+
+    /// Une varios HyperLogLog en la clave destino
+    ///
+    /// # Arguments
+    /// * `destination` - Clave destino
+    /// * `sources` - Claves a unir
+    ///
+    /// # Returns
+    /// `OK`
+    Pfmerge(String, Vec<String>),
+
     // LIST COMMANDS
     /// Elimina claves
     ///
@@ -302,6 +510,64 @@ pub enum Command {
     /// Lista de elementos en el rango
     Lrange(String, i64, i64),
 
+    /// Obtiene el elemento de una lista en una posición
+    ///
+    /// # Arguments
+    /// * `key` - Clave de la lista
+    /// * `index` - Posición, negativa si se cuenta desde el final
+    ///
+    /// # Returns
+    /// El elemento, o nulo si la posición está fuera de rango
+    Lindex(String, i64),
+
+    /// Reemplaza el elemento de una lista en una posición
+    ///
+    /// # Arguments
+    /// * `key` - Clave de la lista
+    /// * `index` - Posición, negativa si se cuenta desde el final
+    /// * `value` - Nuevo valor
+    ///
+    /// # Returns
+    /// `OK` si la posición existe
+    Lset(String, i64, String),
+
+    /// Elimina de una lista los elementos iguales a un valor
+    ///
+    /// # Arguments
+    /// * `key` - Clave de la lista
+    /// * `count` - Cuántos eliminar: positivo desde el inicio, negativo
+    ///   desde el final, 0 todos
+    /// * `value` - Valor a eliminar
+    ///
+    /// # Returns
+    /// Cantidad de elementos eliminados
+    Lrem(String, i64, String),
+
+    /// Inserta un elemento antes o después de la primera aparición de otro
+    ///
+    /// # Arguments
+    /// * `key` - Clave de la lista
+    /// * `where` - Antes o después del pivote
+    /// * `pivot` - Elemento de referencia
+    /// * `value` - Elemento a insertar
+    ///
+    /// # Returns
+    /// Largo de la lista, -1 si no está el pivote o 0 si no existe la clave
+    Linsert(String, InsertWhere, String, String),
+
+    /// Saca un elemento de un extremo de una lista y lo agrega en un
+    /// extremo de otra. `RPOPLPUSH` es `LMove(source, destination, Right, Left)`
+    ///
+    /// # Arguments
+    /// * `source` - Clave de la lista origen
+    /// * `destination` - Clave de la lista destino, puede ser la misma
+    /// * `from` - Extremo del que se saca
+    /// * `to` - Extremo en el que se agrega
+    ///
+    /// # Returns
+    /// El elemento movido, o nulo si el origen no existe
+    LMove(String, String, ListEnd, ListEnd),
+
     /// Elimina elementos del final de una lista
     ///
     /// # Arguments
@@ -361,6 +627,45 @@ pub enum Command {
     /// HashSet con todos los elementos
     Smembers(String),
 
+    /// Obtiene la intersección de varios conjuntos
+    ///
+    /// # Arguments
+    /// * `keys` - Claves de los conjuntos, las inexistentes cuentan como vacíos
+    ///
+    /// # Returns
+    /// HashSet con los elementos presentes en todos los conjuntos
+    Sinter(Vec<String>),
+
+    /// Obtiene la unión de varios conjuntos
+    ///
+    /// # Arguments
+    /// * `keys` - Claves de los conjuntos, las inexistentes cuentan como vacíos
+    ///
+    /// # Returns
+    /// HashSet con los elementos presentes en alguno de los conjuntos
+    Sunion(Vec<String>),
+
+    /// Obtiene la diferencia entre el primer conjunto y los siguientes
+    ///
+    /// # Arguments
+    /// * `keys` - Conjunto base seguido de los conjuntos a restar
+    ///
+    /// # Returns
+    /// HashSet con los elementos del primero que no están en los demás
+    Sdiff(Vec<String>),
+
+    /// Obtiene elementos aleatorios de un conjunto, sin modificarlo
+    ///
+    /// # Arguments
+    /// * `key` - Clave del conjunto
+    /// * `count` - Sin valor, un elemento. Positivo, hasta `count` elementos
+    ///   distintos. Negativo, exactamente `|count|` elementos, que se pueden
+    ///   repetir
+    ///
+    /// # Returns
+    /// El elemento (o nulo) sin `count`, o la lista de elementos
+    SRandMember(String, Option<i64>),
+
     /// Mueve un elemento entre conjuntos
     ///
     /// # Arguments
@@ -382,6 +687,217 @@ pub enum Command {
     /// Vector de elementos eliminados
     Spop(String, i64),
 
+    /// Elimina elementos de un conjunto
+    ///
+    /// # Arguments
+    /// * `key` - Clave del conjunto
+    /// * `members` - Elementos a eliminar
+    ///
+    /// # Returns
+    /// Cantidad de elementos eliminados
+    Srem(String, Vec<String>),
+
+    /// Asigna campos de un hash, creándolo si no existe
+    ///
+    /// # Arguments
+    /// * `key` - Clave del hash
+    /// * `fields` - Pares campo-valor a asignar
+    ///
+    /// # Returns
+    /// Cantidad de campos nuevos
+    Hset(String, Vec<(String, String)>),
+
+    /// Obtiene el valor de un campo de un hash
+    ///
+    /// # Arguments
+    /// * `key` - Clave del hash
+    /// * `field` - Campo a buscar
+    ///
+    /// # Returns
+    /// El valor, o nulo si el hash o el campo no existen
+    Hget(String, String),
+
+    /// Elimina campos de un hash
+    ///
+    /// # Arguments
+    /// * `key` - Clave del hash
+    /// * `fields` - Campos a eliminar
+    ///
+    /// # Returns
+    /// Cantidad de campos eliminados
+    Hdel(String, Vec<String>),
+
+    /// Obtiene todos los campos y valores de un hash
+    ///
+    /// # Arguments
+    /// * `key` - Clave del hash
+    ///
+    /// # Returns
+    /// Lista de campos seguidos de su valor, ordenada por campo
+    Hgetall(String),
+
+    // GEO COMMANDS
+    /// Agrega miembros a un sorted set, con su posición codificada en un
+    /// geohash como score, creándolo si no existe
+    ///
+    /// # Arguments
+    /// * `key` - Clave del sorted set
+    /// * `members` - Longitud, latitud y nombre de cada miembro
+    ///
+    /// # Returns
+    /// Cantidad de miembros nuevos
+    Geoadd(String, Vec<(f64, f64, String)>),
+
+    /// Obtiene la posición de miembros de un sorted set
+    ///
+    /// # Arguments
+    /// * `key` - Clave del sorted set
+    /// * `members` - Miembros a buscar
+    ///
+    /// # Returns
+    /// Por cada miembro su longitud y latitud, o nulo si no existe
+    Geopos(String, Vec<String>),
+
+    /// Obtiene la distancia entre dos miembros de un sorted set
+    ///
+    /// # Arguments
+    /// * `key` - Clave del sorted set
+    /// * `from` - Primer miembro
+    /// * `to` - Segundo miembro
+    /// * `unit` - Unidad de la distancia
+    ///
+    /// # Returns
+    /// La distancia con 4 decimales, o nulo si falta alguno de los miembros
+    Geodist(String, String, String, GeoUnit),
+
+    /// Busca los miembros de un sorted set dentro de un área
+    ///
+    /// # Arguments
+    /// * `key` - Clave del sorted set
+    /// * `options` - Origen, área, orden y datos extra a devolver
+    ///
+    /// # Returns
+    /// Los miembros encontrados, ordenados por distancia al origen
+    Geosearch(String, GeoSearchOptions),
+
+    /// Ordena los elementos de una lista o set
+    ///
+    /// # Arguments
+    /// * `key` - Clave de la lista o set
+    /// * `alpha` - Orden lexicográfico en vez de numérico
+    /// * `desc` - Orden descendente
+    /// * `limit` - Ventana `(offset, count)` a devolver
+    /// * `by_pattern` - Patrón de claves externas usadas como peso
+    /// * `get_patterns` - Patrones de claves externas a devolver
+    ///
+    /// # Returns
+    /// Vector de elementos ordenados
+    Sort(
+        String,
+        bool,
+        bool,
+        Option<(i64, i64)>,
+        Option<String>,
+        Vec<String>,
+    ),
+
+    // STREAM COMMANDS
+    /// Agrega una entrada a un stream, creándolo si no existe
+    ///
+    /// # Arguments
+    /// * `key` - Clave del stream
+    /// * `id` - ID explícito de la entrada, `None` para generarlo (`*`)
+    /// * `fields` - Pares campo/valor de la entrada
+    ///
+    /// # Returns
+    /// ID de la entrada agregada
+    Xadd(String, Option<StreamId>, StreamFields),
+
+    /// Obtiene la cantidad de entradas de un stream
+    ///
+    /// # Arguments
+    /// * `key` - Clave del stream
+    ///
+    /// # Returns
+    /// Cantidad de entradas, 0 si no existe
+    Xlen(String),
+
+    /// Obtiene las entradas de un stream con ID dentro del rango
+    ///
+    /// # Arguments
+    /// * `key` - Clave del stream
+    /// * `start` - ID inicial (inclusive)
+    /// * `end` - ID final (inclusive)
+    /// * `count` - Cantidad máxima de entradas (COUNT)
+    ///
+    /// # Returns
+    /// Por cada entrada su ID, la cantidad de pares y los pares campo/valor
+    Xrange(String, StreamId, StreamId, Option<usize>),
+
+    /// Lee las entradas con ID mayor al indicado
+    ///
+    /// # Arguments
+    /// * `count` - Cantidad máxima de entradas por stream (COUNT)
+    /// * `block` - Milisegundos a esperar entradas nuevas, 0 sin límite (BLOCK)
+    /// * `streams` - Claves de los streams con el último ID ya leído
+    ///
+    /// # Returns
+    /// Por cada stream con entradas nuevas su clave, la cantidad de
+    /// entradas y las entradas con el formato de `Xrange`
+    Xread(Option<usize>, Option<u64>, Vec<(String, StreamId)>),
+
+    /// Crea un grupo de consumidores sobre un stream
+    ///
+    /// # Arguments
+    /// * `key` - Clave del stream
+    /// * `group` - Nombre del grupo
+    /// * `id` - Último ID considerado entregado, `None` para el último del stream (`$`)
+    /// * `mkstream` - Crea el stream vacío si no existe (MKSTREAM)
+    ///
+    /// # Returns
+    /// `OK`
+    XgroupCreate(String, String, Option<StreamId>, bool),
+
+    /// Entrega entradas a un consumidor de un grupo
+    ///
+    /// # Arguments
+    /// * `group` - Nombre del grupo
+    /// * `consumer` - Nombre del consumidor
+    /// * `count` - Cantidad máxima de entradas por stream (COUNT)
+    /// * `streams` - Claves de los streams con `None` (`>`) para entradas
+    ///   nuevas o un ID para releer las pendientes del consumidor
+    ///
+    /// # Returns
+    /// Mismo formato que `Xread`
+    Xreadgroup(
+        String,
+        String,
+        Option<usize>,
+        Vec<(String, Option<StreamId>)>,
+    ),
+
+    /// Confirma entradas entregadas a un grupo
+    ///
+    /// # Arguments
+    /// * `key` - Clave del stream
+    /// * `group` - Nombre del grupo
+    /// * `ids` - IDs a confirmar
+    ///
+    /// # Returns
+    /// Cantidad de entradas que estaban pendientes
+    Xack(String, String, Vec<StreamId>),
+
+    /// Resume las entradas pendientes de confirmación de un grupo
+    ///
+    /// # Arguments
+    /// * `key` - Clave del stream
+    /// * `group` - Nombre del grupo
+    ///
+    /// # Returns
+    /// Cantidad de pendientes, menor y mayor ID pendiente y la
+    /// cantidad de pendientes de cada consumidor
+    Xpending(String, String),
+
     // DB COMMANDS
     /// Guarda la base de datos en segundo plano
     BgSave,
@@ -389,6 +905,198 @@ pub enum Command {
     /// Guarda la base de datos
     Save,
 
+    /// Reescribe el append only file en segundo plano, compactando el
+    /// contenido actual de la base
+    ///
+    /// # Returns
+    /// Confirmación de inicio, o error si ya hay una reescritura en curso
+    Bgrewriteaof,
+
+    /// Informa el estado del servidor
+    ///
+    /// # Arguments
+    /// * `section` - Sección pedida, `None` para las de por defecto
+    ///
+    /// # Returns
+    /// Texto con un campo `nombre:valor` por línea
+    Info(Option<String>),
+
+    /// Serializa el valor de una clave
+    ///
+    /// # Arguments
+    /// * `key` - Clave a serializar
+    ///
+    /// # Returns
+    /// Payload en hexadecimal, nulo si la clave no existe
+    Dump(String),
+
+    /// Cuenta cuántas de las claves existen, contando las repetidas cada
+    /// vez que aparecen
+    ///
+    /// # Arguments
+    /// * `keys` - Claves a buscar
+    ///
+    /// # Returns
+    /// Cantidad de claves que existen
+    Exists(Vec<String>),
+
+    /// Actualiza el último acceso de las claves que existen, usado como
+    /// recencia para LRU. Las repetidas cuentan cada vez que aparecen
+    ///
+    /// # Arguments
+    /// * `keys` - Claves a tocar
+    ///
+    /// # Returns
+    /// Cantidad de claves que existen
+    Touch(Vec<String>),
+
+    /// Fija el vencimiento de una clave
+    ///
+    /// # Arguments
+    /// * `key` - Clave a vencer
+    /// * `seconds` - Segundos hasta el vencimiento
+    ///
+    /// # Returns
+    /// 1 si la clave existe, 0 si no
+    Expire(String, u64),
+
+    /// Fija el vencimiento de una clave en un instante absoluto
+    ///
+    /// # Arguments
+    /// * `key` - Clave a vencer
+    /// * `unix_ms` - Milisegundos desde epoch del vencimiento
+    ///
+    /// # Returns
+    /// 1 si la clave existe, 0 si no
+    PexpireAt(String, u64),
+
+    /// Quita el vencimiento de una clave
+    ///
+    /// # Arguments
+    /// * `key` - Clave a dejar persistente
+    ///
+    /// # Returns
+    /// 1 si la clave vencía, 0 si no vencía o no existe
+    Persist(String),
+
+    /// Consulta el tiempo de vida restante de una clave
+    ///
+    /// # Arguments
+    /// * `key` - Clave a consultar
+    ///
+    /// # Returns
+    /// Segundos restantes, -1 si no vence y -2 si no existe
+    Ttl(String),
+
+    /// Informa el tipo del valor guardado en una clave
+    ///
+    /// # Arguments
+    /// * `key` - Clave a consultar
+    ///
+    /// # Returns
+    /// `string`, `list`, `set`, `stream` o `none` si no existe
+    Type(String),
+
+    /// Renombra una clave, pisando el destino si ya existía
+    ///
+    /// # Arguments
+    /// * `source` - Clave a renombrar
+    /// * `destination` - Nuevo nombre de la clave
+    ///
+    /// # Returns
+    /// `OK`, o error si la clave de origen no existe
+    Rename(String, String),
+
+    /// Renombra una clave sólo si el destino no existe
+    ///
+    /// # Arguments
+    /// * `source` - Clave a renombrar
+    /// * `destination` - Nuevo nombre de la clave
+    ///
+    /// # Returns
+    /// 1 si se renombró, 0 si el destino ya existía
+    Renamenx(String, String),
+
+    /// Crea una clave a partir de un valor serializado con DUMP
+    ///
+    /// # Arguments
+    /// * `key` - Clave a crear
+    /// * `ttl` - Tiempo de vida en milisegundos, 0 si no expira
+    /// * `payload` - Payload en hexadecimal
+    /// * `replace` - Reemplaza la clave si ya existe
+    ///
+    /// # Returns
+    /// `OK` si la clave fue creada
+    Restore(String, i64, String, bool),
+
+    /// Operaciones de diagnóstico sobre la base de datos
+    ///
+    /// # Arguments
+    /// * `subcommand` - Subcomando de DEBUG a ejecutar
+    ///
+    /// # Returns
+    /// `OK` si la operación fue exitosa
+    Debug(DebugSubcommand),
+
+    /// Recorre las claves de forma incremental
+    ///
+    /// # Arguments
+    /// * `cursor` - Cursor de la llamada anterior, 0 para empezar
+    /// * `pattern` - Patrón glob que deben cumplir las claves (MATCH)
+    /// * `count` - Cantidad aproximada de claves a visitar (COUNT)
+    ///
+    /// # Returns
+    /// El próximo cursor seguido de las claves encontradas
+    Scan(u64, Option<String>, usize),
+
+    /// Lista todas las claves que cumplen un patrón glob
+    ///
+    /// # Arguments
+    /// * `pattern` - Patrón glob que deben cumplir las claves
+    ///
+    /// # Returns
+    /// Las claves encontradas, ordenadas
+    Keys(String),
+
+    /// Cuenta las claves de la base, de cualquier tipo
+    ///
+    /// # Returns
+    /// La cantidad de claves sin vencer
+    DbSize,
+
+    /// Borra todas las claves de la base junto con sus vencimientos
+    ///
+    /// # Returns
+    /// `OK`
+    FlushDb,
+
+    /// Consulta o borra el historial de eventos de latencia
+    ///
+    /// # Arguments
+    /// * `subcommand` - Subcomando de LATENCY a ejecutar
+    ///
+    /// # Returns
+    /// Depende del subcomando
+    Latency(LatencySubcommand),
+
+    /// Inspecciona la representación interna del valor de una clave
+    ///
+    /// # Arguments
+    /// * `subcommand` - Subcomando de OBJECT a ejecutar
+    ///
+    /// # Returns
+    /// Depende del subcomando
+    Object(ObjectSubcommand),
+
+    /// Describe los comandos que soporta el servidor (COMMAND)
+    ///
+    /// # Arguments
+    /// * `subcommand` - Subcomando de COMMAND a ejecutar
+    ///
+    /// # Returns
+    /// Depende del subcomando
+    CommandInfo(CommandSubcommand),
+
     // PUBSUB COMMANDS
     /// Suscribe a un canal
     ///
@@ -402,6 +1110,18 @@ pub enum Command {
     /// * `channel` - Nombre del canal
     Unsubscribe(String),
 
+    /// Suscribe a todos los canales que cumplen alguno de los patrones
+    ///
+    /// # Arguments
+    /// * `patterns` - Patrones glob de canales
+    PSubscribe(Vec<String>),
+
+    /// Desuscribe de patrones glob
+    ///
+    /// # Arguments
+    /// * `patterns` - Patrones a dejar; vacío deja todos
+    PUnsubscribe(Vec<String>),
+
     /// Publica un mensaje en un canal
     ///
     /// # Arguments
@@ -409,6 +1129,15 @@ pub enum Command {
     /// * `message` - Mensaje a publicar
     Publish(String, RespMessage),
 
+    /// Consulta el estado de Pub/Sub
+    ///
+    /// # Arguments
+    /// * `subcommand` - Subcomando de PUBSUB a ejecutar
+    ///
+    /// # Returns
+    /// Depende del subcomando
+    Pubsub(PubsubSubcommand),
+
     // CLUSTER COMMANDS
     /// Inicia el proceso de unión a un cluster
     ///
@@ -421,6 +1150,51 @@ pub enum Command {
     /// está conectado.
     Slots,
 
+    /// Describe los subcomandos de `CLUSTER`. En RESP3 la ayuda se
+    /// responde como texto verbatim `txt`.
+    ClusterHelp,
+
+    /// Devuelve el ID del nodo al cual el cliente está conectado.
+    ClusterMyId,
+
+    /// Describe, una línea por nodo, el nodo al cual el cliente está
+    /// conectado y los que conoce del cluster, en el formato de texto de
+    /// `CLUSTER NODES` de Redis.
+    ClusterNodes,
+
+    /// Intercambia de forma coordinada los roles de este master y una de
+    /// sus réplicas: pausa las escrituras, espera a que la réplica se
+    /// ponga al día y recién entonces la promueve
+    ///
+    /// # Arguments
+    /// * `options` - Réplica destino, espera máxima, forzado o cancelación
+    Failover(FailoverOptions),
+
+    /// Espera a que las réplicas confirmen las escrituras hechas hasta
+    /// ahora por el cliente
+    ///
+    /// # Arguments
+    /// * `numreplicas` - Cantidad de réplicas a esperar
+    /// * `timeout` - Espera máxima en milisegundos, 0 para no limitarla
+    ///
+    /// # Returns
+    /// La cantidad de réplicas que confirmaron las escrituras
+    Wait(u32, u64),
+
+    /// Convierte al nodo en réplica del master indicado y empieza a
+    /// sincronizarse con él. `REPLICAOF NO ONE` lo vuelve a hacer master
+    ///
+    /// # Arguments
+    /// * `master` - Host y puerto del master, `None` para `NO ONE`
+    ReplicaOf(Option<(String, u16)>),
+
+    /// Sincronización completa: envía el store serializado, con el formato
+    /// del dump.rdb, y a partir de ahí cada escritura que ejecute el nodo
+    ///
+    /// # Returns
+    /// El store serializado como bulk string
+    Sync,
+
     // LOG COMMANDS
     /// Permite al usuario loggearse y evita que no realize
     /// consultas fuera de sus privilegios.
@@ -429,6 +1203,237 @@ pub enum Command {
     /// * `user` - Nombre de usuario
     /// * `password` - Contraseña
     Auth(String, Password),
+
+    // CONNECTION COMMANDS
+    /// Opera sobre el estado de la conexión que envía el comando.
+    ///
+    /// # Arguments
+    /// * `subcommand` - Subcomando de CLIENT a ejecutar
+    ///
+    /// # Returns
+    /// Depende del subcomando, `OK` para los que modifican flags
+    Client(ClientSubcommand),
+
+    /// Negocia la versión del protocolo RESP de la conexión. En RESP3
+    /// los mensajes de pubsub llegan como push, fuera de banda.
+    ///
+    /// # Arguments
+    /// * `protover` - Versión pedida, 2 o 3. Sin versión no cambia la actual
+    ///
+    /// # Returns
+    /// Datos del servidor y de la conexión
+    Hello(Option<u8>),
+
+    /// Verifica que el servidor responda. No necesita que la conexión
+    /// esté autenticada.
+    ///
+    /// # Arguments
+    /// * `message` - Mensaje opcional a devolver en lugar de `PONG`
+    ///
+    /// # Returns
+    /// `PONG`, o el mensaje si se dio uno. Una conexión RESP2 suscripta
+    /// recibe `["pong", mensaje]`
+    Ping(Option<String>),
+
+    // TRANSACTION COMMANDS
+    /// Abre una transacción: los comandos siguientes de la conexión se
+    /// encolan, respondiendo `QUEUED`, hasta un `EXEC` o un `DISCARD`.
+    ///
+    /// # Returns
+    /// `OK`, o error si la conexión ya tenía una transacción abierta
+    Multi,
+
+    /// Ejecuta seguidos los comandos encolados desde `MULTI`, sin que se
+    /// intercalen comandos de otras conexiones.
+    ///
+    /// # Returns
+    /// Array con la respuesta de cada comando, o error si no hay una
+    /// transacción abierta o algún comando no se pudo encolar
+    Exec,
+
+    /// Descarta la transacción abierta y sus comandos encolados.
+    ///
+    /// # Returns
+    /// `OK`, o error si no hay una transacción abierta
+    Discard,
+}
+
+/// Subcomandos soportados por `DEBUG`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DebugSubcommand {
+    /// `DEBUG RELOAD`, guarda la base en disco y la vuelve a cargar,
+    /// verificando que el contenido no cambie en el proceso.
+    Reload,
+    /// `DEBUG SLEEP <segundos>`, bloquea el servidor durante el tiempo dado.
+    Sleep(Duration),
+    /// `DEBUG POPULATE <cantidad> [prefijo] [tamaño]`, crea claves de tipo
+    /// string `prefijo:N` sin pisar las existentes. Con tamaño, cada valor
+    /// se completa o se corta hasta ocupar esa cantidad de bytes.
+    Populate(usize, String, Option<usize>),
+}
+
+/// Subcomandos soportados por `LATENCY`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LatencySubcommand {
+    /// `LATENCY LATEST`, última muestra y máximo de cada evento.
+    Latest,
+    /// `LATENCY HISTORY <evento>`, muestras guardadas del evento.
+    History(String),
+    /// `LATENCY RESET [evento ...]`, borra el historial de los eventos
+    /// dados o de todos.
+    Reset(Vec<String>),
+}
+
+/// Efecto de un comando de escritura sobre el TTL de las claves que escribe,
+/// según la semántica de Redis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TtlPolicy {
+    /// Modifica el valor en el lugar y conserva el TTL (`APPEND`, `INCR`,
+    /// `INCRBY`, `LPUSH`, `SADD`, ...)
+    Keep,
+    /// Reemplaza el valor y descarta el TTL (`MSET`, `GETSET`)
+    Reset,
+    /// Define el TTL de la clave por su cuenta (`SET`, `RENAME`,
+    /// `RESTORE`, `EXPIRE`, `PEXPIREAT`, `PERSIST`)
+    Own,
+}
+
+/// Subcomandos soportados por `OBJECT`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObjectSubcommand {
+    /// `OBJECT ENCODING <clave>`, encoding con el que se guarda el valor.
+    Encoding(String),
+}
+
+/// Subcomandos soportados por `COMMAND`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommandSubcommand {
+    /// `COMMAND COUNT`, cantidad de comandos soportados.
+    Count,
+    /// `COMMAND DOCS [comando ...]`, aridad, flags y grupo de los comandos
+    /// dados, o de todos si no se da ninguno.
+    Docs(Vec<String>),
+}
+
+/// Subcomandos soportados por `PUBSUB`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PubsubSubcommand {
+    /// `PUBSUB CHANNELS [patrón]`, canales con al menos un suscriptor,
+    /// opcionalmente filtrados por un patrón glob.
+    Channels(Option<String>),
+    /// `PUBSUB NUMSUB [canal ...]`, cantidad de suscriptores de cada canal.
+    NumSub(Vec<String>),
+}
+
+/// Subcomandos soportados por `CLIENT`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientSubcommand {
+    /// `CLIENT NO-TOUCH ON|OFF`, las lecturas de la conexión no
+    /// actualizan la recencia (LRU) de las claves accedidas.
+    NoTouch(bool),
+    /// `CLIENT NO-EVICT ON|OFF`, la conexión queda excluida del
+    /// desalojo de clientes por buffers de salida.
+    NoEvict(bool),
+    /// `CLIENT PAUSE <ms> [WRITE|ALL]`, posterga los comandos alcanzados
+    /// hasta que venza la pausa o se ejecute `CLIENT UNPAUSE`.
+    Pause(u64, PauseMode),
+    /// `CLIENT UNPAUSE`, levanta la pausa antes de que venza.
+    Unpause,
+    /// `CLIENT SETNAME <name>`, nombra la conexión. Un nombre vacío la
+    /// deja sin nombre.
+    SetName(String),
+    /// `CLIENT GETNAME`, el nombre de la conexión, vacío si no tiene.
+    GetName,
+    /// `CLIENT LIST`, una línea por conexión abierta con su id,
+    /// dirección, nombre y antigüedad en segundos.
+    List,
+    /// `CLIENT KILL ID <client-id>`, cierra la conexión indicada.
+    Kill(String),
+}
+
+/// Comandos alcanzados por `CLIENT PAUSE`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseMode {
+    /// Sólo los comandos que escriben en la base
+    Write,
+    /// Todos los comandos salvo `CLIENT`
+    All,
+}
+
+/// Posición de `LINSERT` respecto del pivote.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InsertWhere {
+    Before,
+    After,
+}
+
+/// Extremo de una lista, para `LMOVE`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ListEnd {
+    Left,
+    Right,
+}
+
+/// Opciones de `SET key value [NX | XX] [EX seconds]`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SetOptions {
+    /// Sólo asignar si la clave no existe
+    pub nx: bool,
+    /// Sólo asignar si la clave ya existe
+    pub xx: bool,
+    /// Segundos hasta el vencimiento; sin `EX` la clave queda persistente
+    pub ex: Option<u64>,
+}
+
+/// Origen de la búsqueda de `GEOSEARCH`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GeoOrigin {
+    /// Posición de un miembro del mismo sorted set (FROMMEMBER)
+    Member(String),
+    /// Longitud y latitud dadas (FROMLONLAT)
+    LonLat(f64, f64),
+}
+
+/// Área de la búsqueda de `GEOSEARCH`, en la unidad de la consulta.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GeoShape {
+    /// Círculo con el radio dado (BYRADIUS)
+    Radius(f64),
+    /// Rectángulo con el ancho y el alto dados (BYBOX)
+    Box(f64, f64),
+}
+
+/// Opciones de `GEOSEARCH key origen área [ASC | DESC] [COUNT n]
+/// [WITHCOORD] [WITHDIST]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoSearchOptions {
+    /// Punto desde el que se mide la distancia
+    pub origin: GeoOrigin,
+    /// Área que deben cubrir los miembros
+    pub shape: GeoShape,
+    /// Unidad del área y de las distancias devueltas
+    pub unit: GeoUnit,
+    /// Ordenar del más lejano al más cercano
+    pub desc: bool,
+    /// Cantidad máxima de miembros a devolver
+    pub count: Option<usize>,
+    /// Devolver la posición de cada miembro
+    pub with_coord: bool,
+    /// Devolver la distancia de cada miembro al origen
+    pub with_dist: bool,
+}
+
+/// Opciones de `FAILOVER [TO host port] [FORCE] [TIMEOUT ms] [ABORT]`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FailoverOptions {
+    /// Réplica a promover; si no se indica se elige la más actualizada
+    pub to: Option<(String, u16)>,
+    /// Promover la réplica al vencer `timeout` aunque no esté al día
+    pub force: bool,
+    /// Espera máxima, en milisegundos, a que la réplica se ponga al día
+    pub timeout: Option<u64>,
+    /// Cancelar el failover en curso
+    pub abort: bool,
 }
 
 impl Command {
@@ -443,11 +1448,23 @@ impl Command {
             Command::Append(_, _)
             | Command::Echo(_)
             | Command::Get(_)
+            | Command::Mget(_)
+            | Command::Mset(_)
+            | Command::Incr(_)
+            | Command::Decr(_)
+            | Command::IncrBy(_, _)
+            | Command::DecrBy(_, _)
             | Command::Getdel(_)
+            | Command::GetSet(_, _)
             | Command::Getrange(_, _, _)
-            | Command::Set(_, _)
+            | Command::Set(_, _, _)
+            | Command::Setnx(_, _)
             | Command::Strlen(_)
-            | Command::Substr(_, _, _) => "STRING",
+            | Command::Substr(_, _, _)
+            | Command::SetRange(_, _, _)
+            | Command::Pfadd(_, _)
+            | Command::Pfcount(_)
+            | Command::Pfmerge(_, _) => "STRING",
 
             // List commands
             Command::Del(_)
@@ -455,28 +1472,102 @@ impl Command {
             | Command::Lpop(_, _)
             | Command::Lpush(_, _)
             | Command::Lrange(_, _, _)
+            | Command::Lindex(_, _)
+            | Command::Lset(_, _, _)
+            | Command::Lrem(_, _, _)
+            | Command::Linsert(..)
+            | Command::LMove(..)
             | Command::Rpop(_, _)
-            | Command::Rpush(_, _) => "LIST",
+            | Command::Rpush(_, _)
+            | Command::Sort(..) => "LIST",
 
             // Set commands
             Command::Sadd(_, _)
             | Command::Scard(_)
             | Command::Sismember(_, _)
             | Command::Smembers(_)
+            | Command::Sinter(_)
+            | Command::Sunion(_)
+            | Command::Sdiff(_)
+            | Command::SRandMember(_, _)
             | Command::SMove(_, _, _)
-            | Command::Spop(_, _) => "SET",
+            | Command::Spop(_, _)
+            | Command::Srem(_, _) => "SET",
+
+            // Hash commands
+            Command::Hset(_, _)
+            | Command::Hget(_, _)
+            | Command::Hdel(_, _)
+            | Command::Hgetall(_) => "HASH",
+
+            // Geo commands
+            Command::Geoadd(_, _)
+            | Command::Geopos(_, _)
+            | Command::Geodist(_, _, _, _)
+            | Command::Geosearch(_, _) => "GEO",
+
+            // Stream commands
+            Command::Xadd(_, _, _)
+            | Command::Xlen(_)
+            | Command::Xrange(_, _, _, _)
+            | Command::Xread(_, _, _)
+            | Command::XgroupCreate(_, _, _, _)
+            | Command::Xreadgroup(_, _, _, _)
+            | Command::Xack(_, _, _)
+            | Command::Xpending(_, _) => "STREAM",
 
             // Database commands
-            Command::BgSave | Command::Save => "DB",
+            Command::BgSave
+            | Command::Save
+            | Command::Bgrewriteaof
+            | Command::Info(_)
+            | Command::Dump(_)
+            | Command::Exists(_)
+            | Command::Touch(_)
+            | Command::Expire(_, _)
+            | Command::PexpireAt(_, _)
+            | Command::Persist(_)
+            | Command::Ttl(_)
+            | Command::Type(_)
+            | Command::Rename(_, _)
+            | Command::Renamenx(_, _)
+            | Command::Restore(_, _, _, _)
+            | Command::Debug(_)
+            | Command::Latency(_)
+            | Command::Object(_)
+            | Command::CommandInfo(_)
+            | Command::Scan(_, _, _)
+            | Command::Keys(_)
+            | Command::DbSize
+            | Command::FlushDb => "DB",
 
             // Pub/Sub commands
-            Command::Subscribe(_) | Command::Unsubscribe(_) | Command::Publish(_, _) => "PUBSUB",
+            Command::Subscribe(_)
+            | Command::Unsubscribe(_)
+            | Command::PSubscribe(_)
+            | Command::PUnsubscribe(_)
+            | Command::Publish(_, _)
+            | Command::Pubsub(_) => "PUBSUB",
 
             // Cluster commands
-            Command::Meet(_) | Command::Slots => "CLUSTER",
+            Command::Meet(_)
+            | Command::Slots
+            | Command::ClusterHelp
+            | Command::ClusterMyId
+            | Command::ClusterNodes
+            | Command::Failover(_)
+            | Command::Wait(..)
+            | Command::ReplicaOf(_)
+            | Command::Sync => "CLUSTER",
 
             // Log commands
             Command::Auth(_, _) => "LOG",
+
+            // Connection commands
+            Command::Client(_) | Command::Hello(_) | Command::Ping(_) => "CONNECTION",
+
+            // Transaction commands
+            Command::Multi | Command::Exec | Command::Discard => "TRANSACTION",
         }
     }
 
@@ -489,50 +1580,387 @@ impl Command {
         matches!(
             self,
             Command::Echo(_)
+                | Command::Ping(_)
                 | Command::Get(_)
+                | Command::Mget(_)
                 | Command::Getrange(_, _, _)
                 | Command::Strlen(_)
                 | Command::Substr(_, _, _)
+                | Command::Pfcount(_)
                 | Command::Llen(_)
                 | Command::Lrange(_, _, _)
+                | Command::Lindex(_, _)
+                | Command::Sort(..)
                 | Command::Scard(_)
                 | Command::Sismember(_, _)
                 | Command::Smembers(_)
+                | Command::Sinter(_)
+                | Command::Sunion(_)
+                | Command::Sdiff(_)
+                | Command::SRandMember(_, _)
+                | Command::Hget(_, _)
+                | Command::Hgetall(_)
+                | Command::Geopos(_, _)
+                | Command::Geodist(_, _, _, _)
+                | Command::Geosearch(_, _)
+                | Command::Xlen(_)
+                | Command::Xrange(_, _, _, _)
+                | Command::Xread(_, _, _)
+                | Command::Xpending(_, _)
+                | Command::Dump(_)
+                | Command::Exists(_)
+                | Command::Touch(_)
+                | Command::Ttl(_)
+                | Command::Type(_)
+                | Command::Scan(_, _, _)
+                | Command::Keys(_)
+                | Command::DbSize
+                | Command::Object(_)
+                | Command::ClusterHelp
+                | Command::ClusterMyId
+        )
+    }
+
+    /// Verifica si el comando sólo puede liberar memoria, por lo que se
+    /// acepta aunque los datos superen `maxmemory`
+    ///
+    /// # Returns
+    ///
+    /// `bool` - True si el comando borra o achica valores sin agregar datos
+    pub fn frees_memory(&self) -> bool {
+        matches!(
+            self,
+            Command::Del(_)
+                | Command::Getdel(_)
+                | Command::Lpop(_, _)
+                | Command::Rpop(_, _)
+                | Command::Lrem(_, _, _)
+                | Command::Spop(_, _)
+                | Command::Srem(_, _)
+                | Command::Hdel(_, _)
+                | Command::Expire(_, _)
+                | Command::PexpireAt(_, _)
+                | Command::Persist(_)
+                | Command::FlushDb
         )
     }
 
+    /// Indica qué pasa con el TTL de las claves que escribe el comando.
+    ///
+    /// # Returns
+    ///
+    /// `TtlPolicy` - `Reset` si el comando reemplaza el valor, `Own` si
+    /// define el TTL por su cuenta y `Keep` si modifica el valor en el lugar
+    pub fn ttl_policy(&self) -> TtlPolicy {
+        match self {
+            Command::Mset(_) | Command::GetSet(_, _) => TtlPolicy::Reset,
+            Command::Set(_, _, _)
+            | Command::Setnx(_, _)
+            | Command::Rename(_, _)
+            | Command::Renamenx(_, _)
+            | Command::Restore(_, _, _, _)
+            | Command::Expire(_, _)
+            | Command::PexpireAt(_, _)
+            | Command::Persist(_) => TtlPolicy::Own,
+            _ => TtlPolicy::Keep,
+        }
+    }
+
+    /// Obtiene todas las claves a las que accede el comando, para
+    /// verificarlas contra los patrones de claves de la ACL.
+    ///
+    /// # Returns
+    ///
+    /// `Vec<&String>` - Claves leídas o escritas, vacío si el comando no
+    /// accede a claves
+    pub fn keys(&self) -> Vec<&String> {
+        match self {
+            Command::Append(key, _)
+            | Command::Get(key)
+            | Command::Incr(key)
+            | Command::Decr(key)
+            | Command::IncrBy(key, _)
+            | Command::DecrBy(key, _)
+            | Command::Getdel(key)
+            | Command::GetSet(key, _)
+            | Command::Set(key, _, _)
+            | Command::Setnx(key, _)
+            | Command::Strlen(key)
+            | Command::Substr(key, _, _)
+            | Command::Getrange(key, _, _)
+            | Command::SetRange(key, _, _)
+            | Command::Llen(key)
+            | Command::Lpop(key, _)
+            | Command::Rpop(key, _)
+            | Command::Lpush(key, _)
+            | Command::Rpush(key, _)
+            | Command::Lrange(key, _, _)
+            | Command::Lindex(key, _)
+            | Command::Lset(key, _, _)
+            | Command::Lrem(key, _, _)
+            | Command::Linsert(key, ..)
+            | Command::Sort(key, ..)
+            | Command::Scard(key)
+            | Command::Sismember(key, _)
+            | Command::Smembers(key)
+            | Command::SRandMember(key, _)
+            | Command::Sadd(key, _)
+            | Command::Spop(key, _)
+            | Command::Srem(key, _)
+            | Command::Hset(key, _)
+            | Command::Hget(key, _)
+            | Command::Hdel(key, _)
+            | Command::Hgetall(key)
+            | Command::Geoadd(key, _)
+            | Command::Geopos(key, _)
+            | Command::Geodist(key, _, _, _)
+            | Command::Geosearch(key, _)
+            | Command::Dump(key)
+            | Command::Expire(key, _)
+            | Command::PexpireAt(key, _)
+            | Command::Persist(key)
+            | Command::Ttl(key)
+            | Command::Type(key)
+            | Command::Object(ObjectSubcommand::Encoding(key))
+            | Command::Restore(key, _, _, _)
+            | Command::Pfadd(key, _)
+            | Command::Xadd(key, _, _)
+            | Command::Xlen(key)
+            | Command::Xrange(key, _, _, _)
+            | Command::XgroupCreate(key, _, _, _)
+            | Command::Xack(key, _, _)
+            | Command::Xpending(key, _) => vec![key],
+            Command::Pfmerge(destination, sources) => {
+                std::iter::once(destination).chain(sources).collect()
+            }
+            Command::SMove(source, destination, _)
+            | Command::LMove(source, destination, ..)
+            | Command::Rename(source, destination)
+            | Command::Renamenx(source, destination) => vec![source, destination],
+            Command::Del(keys)
+            | Command::Mget(keys)
+            | Command::Exists(keys)
+            | Command::Touch(keys)
+            | Command::Sinter(keys)
+            | Command::Sunion(keys)
+            | Command::Sdiff(keys)
+            | Command::Pfcount(keys) => keys.iter().collect(),
+            Command::Mset(pairs) => pairs.iter().map(|(key, _)| key).collect(),
+            Command::Xread(_, _, streams) => streams.iter().map(|(key, _)| key).collect(),
+            Command::Xreadgroup(_, _, _, streams) => streams.iter().map(|(key, _)| key).collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Obtiene los patrones de claves que recorre el comando: los de
+    /// `KEYS` y `SCAN MATCH`, y los de `SORT BY/GET`, que leen claves
+    /// externas armadas a partir de los elementos.
+    ///
+    /// # Returns
+    ///
+    /// `Vec<&str>` - Patrones glob de las claves alcanzadas; un `SCAN` sin
+    /// `MATCH` recorre todas las claves (`*`)
+    pub fn key_patterns(&self) -> Vec<&str> {
+        match self {
+            Command::Keys(pattern) => vec![pattern],
+            Command::Scan(_, pattern, _) => vec![pattern.as_deref().unwrap_or("*")],
+            Command::Sort(_, _, _, _, by_pattern, get_patterns) => by_pattern
+                .iter()
+                // Un BY sin `*` no ordena ni lee claves externas
+                .filter(|pattern| pattern.contains('*'))
+                .chain(get_patterns.iter().filter(|pattern| *pattern != "#"))
+                .map(String::as_str)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Obtiene la forma determinística del comando, para registrarlo o
+    /// propagarlo a réplicas a partir del resultado que tuvo en el master.
+    ///
+    /// Los comandos cuyo efecto depende del azar o del reloj se reescriben
+    /// al efecto concreto que tuvieron: `SPOP` pasa a ser un `SREM` de los
+    /// elementos extraídos, `XADD *` un `XADD` con el ID asignado y
+    /// `EXPIRE` un `PEXPIREAT` con el instante absoluto del vencimiento,
+    /// para que una réplica que lo aplica más tarde no lo corra. Los
+    /// comandos que no escriben, o que no modificaron nada, no se propagan.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - Resultado de ejecutar el comando
+    ///
+    /// # Returns
+    ///
+    /// `Vec<Command>` - Comandos que reproducen el efecto del original
+    pub fn to_replication_form(&self, result: &ResponseType) -> Vec<Command> {
+        if !self.writes_on_db() {
+            return vec![];
+        }
+        match (self, result) {
+            (Command::Spop(key, _), ResponseType::List(members)) if !members.is_empty() => {
+                vec![Command::Srem(key.clone(), members.clone())]
+            }
+            (Command::Spop(_, _), _) => vec![],
+            (Command::Expire(key, seconds), ResponseType::Int(1)) => {
+                let deadline = SystemTime::now() + Duration::from_secs(*seconds);
+                let unix_ms = deadline
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_millis() as u64);
+                vec![Command::PexpireAt(key.clone(), unix_ms)]
+            }
+            (Command::Expire(_, _), _) => vec![],
+            (Command::Xadd(key, None, fields), ResponseType::Str(id)) => {
+                match StreamId::parse(id, 0) {
+                    Ok(id) => vec![Command::Xadd(key.clone(), Some(id), fields.clone())],
+                    Err(_) => vec![self.clone()],
+                }
+            }
+            _ => vec![self.clone()],
+        }
+    }
+
+    /// Argumentos RESP de las formas que arma `to_replication_form` al
+    /// reescribir un comando.
+    ///
+    /// # Returns
+    ///
+    /// `Option<Vec<String>>` - Nombre y argumentos, `None` si el comando
+    /// nunca surge de una reescritura y se propaga tal como llegó
+    pub fn rewritten_arguments(&self) -> Option<Vec<String>> {
+        match self {
+            Command::Srem(key, members) => Some(
+                ["SREM".to_string(), key.clone()]
+                    .into_iter()
+                    .chain(members.iter().cloned())
+                    .collect(),
+            ),
+            Command::Xadd(key, Some(id), fields) => Some(
+                ["XADD".to_string(), key.clone(), id.to_string()]
+                    .into_iter()
+                    .chain(
+                        fields
+                            .iter()
+                            .flat_map(|(field, value)| [field.clone(), value.clone()]),
+                    )
+                    .collect(),
+            ),
+            Command::PexpireAt(key, unix_ms) => Some(vec![
+                "PEXPIREAT".to_string(),
+                key.clone(),
+                unix_ms.to_string(),
+            ]),
+            _ => None,
+        }
+    }
+
     /// Returns the name of the command
     pub fn to_string(&self) -> String {
         match self {
             Command::Append(_, _) => "APPEND",
             Command::Echo(_) => "ECHO",
             Command::Get(_) => "GET",
+            Command::Mget(_) => "MGET",
+            Command::Mset(_) => "MSET",
+            Command::Incr(_) => "INCR",
+            Command::Decr(_) => "DECR",
+            Command::IncrBy(_, _) => "INCRBY",
+            Command::DecrBy(_, _) => "DECRBY",
             Command::Getdel(_) => "GETDEL",
+            Command::GetSet(_, _) => "GETSET",
             Command::Getrange(_, _, _) => "GETRANGE",
-            Command::Set(_, _) => "SET",
+            Command::Set(_, _, _) => "SET",
+            Command::Setnx(_, _) => "SETNX",
             Command::Strlen(_) => "STRLEN",
             Command::Substr(_, _, _) => "SUBSTR",
+            Command::SetRange(_, _, _) => "SETRANGE",
+            Command::Pfadd(_, _) => "PFADD",
+            Command::Pfcount(_) => "PFCOUNT",
+            Command::Pfmerge(_, _) => "PFMERGE",
             Command::Del(_) => "DEL",
             Command::Llen(_) => "LLEN",
             Command::Lpop(_, _) => "LPOP",
             Command::Lpush(_, _) => "LPUSH",
             Command::Lrange(_, _, _) => "LRANGE",
+            Command::Lindex(_, _) => "LINDEX",
+            Command::Lset(_, _, _) => "LSET",
+            Command::Lrem(_, _, _) => "LREM",
+            Command::Linsert(..) => "LINSERT",
+            Command::LMove(..) => "LMOVE",
             Command::Rpop(_, _) => "RPOP",
             Command::Rpush(_, _) => "RPUSH",
+            Command::Sort(..) => "SORT",
             Command::Sadd(_, _) => "SADD",
             Command::Scard(_) => "SCARD",
             Command::Sismember(_, _) => "SISMEMBER",
             Command::Smembers(_) => "SMEMBERS",
+            Command::Sinter(_) => "SINTER",
+            Command::Sunion(_) => "SUNION",
+            Command::Sdiff(_) => "SDIFF",
+            Command::SRandMember(_, _) => "SRANDMEMBER",
             Command::SMove(_, _, _) => "SMOVE",
             Command::Spop(_, _) => "SPOP",
+            Command::Srem(_, _) => "SREM",
+            Command::Hset(_, _) => "HSET",
+            Command::Hget(_, _) => "HGET",
+            Command::Hdel(_, _) => "HDEL",
+            Command::Hgetall(_) => "HGETALL",
+            Command::Geoadd(_, _) => "GEOADD",
+            Command::Geopos(_, _) => "GEOPOS",
+            Command::Geodist(_, _, _, _) => "GEODIST",
+            Command::Geosearch(_, _) => "GEOSEARCH",
+            Command::Xadd(_, _, _) => "XADD",
+            Command::Xlen(_) => "XLEN",
+            Command::Xrange(_, _, _, _) => "XRANGE",
+            Command::Xread(_, _, _) => "XREAD",
+            Command::XgroupCreate(_, _, _, _) => "XGROUP",
+            Command::Xreadgroup(_, _, _, _) => "XREADGROUP",
+            Command::Xack(_, _, _) => "XACK",
+            Command::Xpending(_, _) => "XPENDING",
             Command::BgSave => "BGSAVE",
+            Command::Bgrewriteaof => "BGREWRITEAOF",
+            Command::Info(_) => "INFO",
             Command::Save => "SAVE",
+            Command::Dump(_) => "DUMP",
+            Command::Exists(_) => "EXISTS",
+            Command::Touch(_) => "TOUCH",
+            Command::Expire(_, _) => "EXPIRE",
+            Command::PexpireAt(_, _) => "PEXPIREAT",
+            Command::Persist(_) => "PERSIST",
+            Command::Ttl(_) => "TTL",
+            Command::Type(_) => "TYPE",
+            Command::Rename(_, _) => "RENAME",
+            Command::Renamenx(_, _) => "RENAMENX",
+            Command::Restore(_, _, _, _) => "RESTORE",
+            Command::Debug(_) => "DEBUG",
+            Command::Latency(_) => "LATENCY",
+            Command::Object(_) => "OBJECT",
+            Command::CommandInfo(_) => "COMMAND",
+            Command::Scan(_, _, _) => "SCAN",
+            Command::Keys(_) => "KEYS",
+            Command::DbSize => "DBSIZE",
+            Command::FlushDb => "FLUSHDB",
             Command::Subscribe(_) => "SUBSCRIBE",
             Command::Unsubscribe(_) => "UNSUBSCRIBE",
+            Command::PSubscribe(_) => "PSUBSCRIBE",
+            Command::PUnsubscribe(_) => "PUNSUBSCRIBE",
             Command::Publish(_, _) => "PUBLISH",
+            Command::Pubsub(_) => "PUBSUB",
             Command::Meet(_) => "MEET",
             Command::Slots => "SLOTS",
+            Command::ClusterHelp => "HELP",
+            Command::ClusterMyId => "MYID",
+            Command::ClusterNodes => "NODES",
+            Command::Failover(_) => "FAILOVER",
+            Command::Wait(..) => "WAIT",
+            Command::ReplicaOf(_) => "REPLICAOF",
+            Command::Sync => "SYNC",
             Command::Auth(_, _) => "AUTH",
+            Command::Client(_) => "CLIENT",
+            Command::Hello(_) => "HELLO",
+            Command::Ping(_) => "PING",
+            Command::Multi => "MULTI",
+            Command::Exec => "EXEC",
+            Command::Discard => "DISCARD",
         }
         .to_string()
     }
@@ -690,12 +2118,22 @@ mod tests {
         assert_eq!(Command::Get("key".to_string()).category(), "STRING");
         assert_eq!(Command::Llen("key".to_string()).category(), "LIST");
         assert_eq!(Command::Sadd("key".to_string(), vec![]).category(), "SET");
+        assert_eq!(Command::Geopos("key".to_string(), vec![]).category(), "GEO");
         assert_eq!(Command::BgSave.category(), "DB");
+        assert_eq!(Command::Debug(DebugSubcommand::Reload).category(), "DB");
         assert_eq!(
             Command::Subscribe("channel".to_string()).category(),
             "PUBSUB"
         );
+        assert_eq!(
+            Command::PSubscribe(vec!["news.*".to_string()]).category(),
+            "PUBSUB"
+        );
         assert_eq!(Command::Meet("address".to_string()).category(), "CLUSTER");
+        assert_eq!(
+            Command::Client(ClientSubcommand::NoTouch(true)).category(),
+            "CONNECTION"
+        );
     }
 
     #[test]
@@ -704,7 +2142,14 @@ mod tests {
         assert!(Command::Llen("key".to_string()).is_read_only());
         assert!(Command::Scard("key".to_string()).is_read_only());
 
-        assert!(!Command::Set("key".to_string(), "value".to_string()).is_read_only());
+        assert!(
+            !Command::Set(
+                "key".to_string(),
+                "value".to_string(),
+                SetOptions::default()
+            )
+            .is_read_only()
+        );
         assert!(!Command::Del(vec!["key".to_string()]).is_read_only());
         assert!(!Command::Sadd("key".to_string(), vec!["value".to_string()]).is_read_only());
     }
@@ -742,6 +2187,59 @@ mod tests {
         assert_eq!(original, cloned);
     }
 
+    #[test]
+    fn test_replication_form_rewrites_nondeterministic_commands() {
+        let spop = Command::Spop("Maps".to_string(), 2);
+        let popped = ResponseType::List(vec!["Petra".to_string(), "Busan".to_string()]);
+        assert_eq!(
+            spop.to_replication_form(&popped),
+            vec![Command::Srem(
+                "Maps".to_string(),
+                vec!["Petra".to_string(), "Busan".to_string()]
+            )]
+        );
+        assert!(
+            spop.to_replication_form(&ResponseType::Null(None))
+                .is_empty()
+        );
+
+        let fields = vec![("hero".to_string(), "Mercy".to_string())];
+        let xadd = Command::Xadd("Picks".to_string(), None, fields.clone());
+        assert_eq!(
+            xadd.to_replication_form(&ResponseType::Str("1700000000000-3".to_string())),
+            vec![Command::Xadd(
+                "Picks".to_string(),
+                Some(StreamId::new(1700000000000, 3)),
+                fields
+            )]
+        );
+
+        let expire = Command::Expire("Hero".to_string(), 60);
+        assert!(matches!(
+            expire.to_replication_form(&ResponseType::Int(1)).as_slice(),
+            [Command::PexpireAt(key, _)] if key == "Hero"
+        ));
+        assert!(expire.to_replication_form(&ResponseType::Int(0)).is_empty());
+    }
+
+    #[test]
+    fn test_replication_form_keeps_deterministic_commands() {
+        let set = Command::Set(
+            "Hero".to_string(),
+            "Sojourn".to_string(),
+            SetOptions::default(),
+        );
+        assert_eq!(
+            set.to_replication_form(&ResponseType::Str("OK".to_string())),
+            vec![set.clone()]
+        );
+        let get = Command::Get("Hero".to_string());
+        assert!(
+            get.to_replication_form(&ResponseType::Null(None))
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_command_clone() {
         let original = Command::Get("key".to_string());
@@ -759,10 +2257,32 @@ mod tests {
 
     #[test]
     fn test_command_debug() {
-        let command = Command::Set("key".to_string(), "value".to_string());
+        let command = Command::Set(
+            "key".to_string(),
+            "value".to_string(),
+            SetOptions::default(),
+        );
         let debug_str = format!("{:?}", command);
         assert!(debug_str.contains("Set"));
         assert!(debug_str.contains("key"));
         assert!(debug_str.contains("value"));
     }
+
+    #[test]
+    fn test_command_keys_lists_every_accessed_key() {
+        let smove = Command::SMove(
+            "Overwatch".to_string(),
+            "Talon".to_string(),
+            "Sombra".to_string(),
+        );
+        assert_eq!(smove.keys(), vec!["Overwatch", "Talon"]);
+
+        let pfmerge = Command::Pfmerge(
+            "Heroes".to_string(),
+            vec!["Tanks".to_string(), "Supports".to_string()],
+        );
+        assert_eq!(pfmerge.keys(), vec!["Heroes", "Tanks", "Supports"]);
+
+        assert!(Command::Save.keys().is_empty());
+    }
 }
@@ -29,3 +29,125 @@ pub fn parse_flat_cluster_slots(flat: &[String]) -> HashMap<SlotRange, Vec<Vec<S
     }
     map
 }
+
+/// Verifica si `text` cumple el patrón glob estilo Redis.
+///
+/// Soporta `*` (cualquier secuencia), `?` (un carácter), `[abc]`,
+/// `[^abc]`, `[a-z]` y `\` para escapar el siguiente carácter.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let mut p = 0;
+    let mut t = 0;
+    // Posición en el patrón después del último `*` y posición en el texto
+    // desde la que ese `*` empezó a consumir, para retroceder ahí si algo
+    // no coincide
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if pattern.get(p) == Some(&'*') {
+            p += 1;
+            star = Some((p, t));
+        } else if let Some(consumed) = match_one(&pattern[p..], text[t]) {
+            p += consumed;
+            t += 1;
+        } else if let Some((after_star, from)) = star {
+            // El último `*` consume un carácter más y se reintenta
+            p = after_star;
+            t = from + 1;
+            star = Some((after_star, from + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Compara el primer elemento de `pattern`, que no es `*`, con `c`.
+///
+/// # Returns
+///
+/// Cantidad de caracteres del patrón consumidos, o `None` si no coincide
+fn match_one(pattern: &[char], c: char) -> Option<usize> {
+    let (&first, rest) = pattern.split_first()?;
+    match first {
+        '?' => Some(1),
+        '[' => match rest.iter().position(|ch| *ch == ']') {
+            Some(end) => class_matches(&rest[..end], c).then_some(end + 2),
+            // Sin cierre el corchete se toma como literal
+            None => (c == '[').then_some(1),
+        },
+        '\\' if !rest.is_empty() => (rest[0] == c).then_some(2),
+        literal => (literal == c).then_some(1),
+    }
+}
+
+/// Verifica si el carácter pertenece a la clase `[...]` (sin corchetes).
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.split_first() {
+        Some(('^', rest)) => (true, rest),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            let (low, high) = if class[i] <= class[i + 2] {
+                (class[i], class[i + 2])
+            } else {
+                (class[i + 2], class[i])
+            };
+            found |= low <= c && c <= high;
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match_wildcards() {
+        assert!(glob_match("*", "Tracer"));
+        assert!(glob_match("hero:*", "hero:Tracer"));
+        assert!(!glob_match("hero:*", "map:Ilios"));
+        assert!(glob_match("h?llo", "hello"));
+        assert!(!glob_match("h?llo", "hllo"));
+        assert!(glob_match("*:Genji:*", "team:Genji:main"));
+        // `*` también cubre la secuencia vacía
+        assert!(glob_match("Genji*", "Genji"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn test_glob_match_classes_and_escape() {
+        assert!(glob_match("h[ae]llo", "hallo"));
+        assert!(!glob_match("h[ae]llo", "hillo"));
+        assert!(glob_match("h[^e]llo", "hallo"));
+        assert!(!glob_match("h[^e]llo", "hello"));
+        assert!(glob_match("h[a-c]llo", "hbllo"));
+        assert!(glob_match("[ab]c", "bc"));
+        assert!(!glob_match("[ab]c", "cc"));
+        assert!(glob_match("D.Va\\*", "D.Va*"));
+        assert!(!glob_match("D.Va\\*", "D.Va"));
+    }
+
+    #[test]
+    fn test_glob_match_backtracks_to_last_star() {
+        assert!(glob_match("*Genji*Shimada", "Genji Hanzo Genji Shimada"));
+        assert!(!glob_match("*Genji*Shimada", "Genji Hanzo Shimada Genji"));
+        assert!(glob_match("a*b?c", "axxbbyc"));
+        assert!(!glob_match("[", ""));
+
+        // Un patrón con muchos `*` contra un texto largo que no coincide
+        // no crece exponencialmente
+        let text = "a".repeat(10_000);
+        let pattern = format!("{}b", "a*".repeat(50));
+        assert!(!glob_match(&pattern, &text));
+    }
+}
@@ -1,8 +1,11 @@
 use crate::cluster::comms::node_input::NODAL_COMMS_PORT;
-use crate::cluster::types::SlotRange;
-use rand::RngCore;
+use crate::cluster::sharding::hash_slot::{MAX_HASH_SLOTS, validate_slot_count};
+use crate::cluster::types::{
+    EMPTY_SLOT_RANGE, NODE_ID_LEN, NodeId, SlotRange, generate_node_id, is_valid_node_id,
+};
+use crate::network::output_buffer::{OutputBufferLimit, OutputBufferLimits};
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
 use std::net::SocketAddr;
 
 #[derive(Clone, Debug)]
@@ -11,14 +14,29 @@ pub struct NodeConfigs {
     port: String,
     initial_role: String,
     clients_limit: i64,
+    accept_rate_limit: u64,
     snapshot_interval: i64,
     snapshot_k_changes: i64,
     snapshot_file: String,
     snapshot_path: String,
+    aof_file: String,
+    append_only: bool,
+    fsync_policy: FsyncPolicy,
     log_file: String,
     log_level: String,
     node_id: String,
     initial_slots_range: SlotRange,
+    slot_count: u16,
+    require_full_coverage: bool,
+    latency_monitor_threshold: u64,
+    maxmemory: u64,
+    maxmemory_policy: EvictionPolicy,
+    output_buffer_limits: OutputBufferLimits,
+    max_protocol_errors: u32,
+    debug_invariants: bool,
+    aof_rdb_check: bool,
+    tls: bool,
+    notify_keyspace_events: KeyspaceEvents,
 }
 
 impl NodeConfigs {
@@ -33,14 +51,29 @@ impl NodeConfigs {
         let mut port = String::new();
         let mut role = "M".to_string();
         let mut clients_limit = 1000;
+        let mut accept_rate_limit = 0;
         let mut snapshot_interval = 900;
         let mut snapshot_k_changes = 15;
         let mut snapshot_file = "dump.rdb".to_string();
         let mut snapshot_path = "./".to_string();
+        let mut aof_file = "appendonly.aof".to_string();
+        let mut append_only = false;
+        let mut fsync_policy = FsyncPolicy::EverySec;
         let mut log_file = "redis.log".to_string();
         let mut log_level = "notice".to_string();
         let mut node_id: Option<String> = None;
-        let mut slots_range: SlotRange = (0, 0);
+        let mut slots_range: SlotRange = EMPTY_SLOT_RANGE;
+        let mut slot_count = MAX_HASH_SLOTS as u32;
+        let mut require_full_coverage = true;
+        let mut latency_monitor_threshold = 0;
+        let mut maxmemory = 0;
+        let mut maxmemory_policy = EvictionPolicy::NoEviction;
+        let mut output_buffer_limits = OutputBufferLimits::default();
+        let mut max_protocol_errors = 10;
+        let mut debug_invariants = false;
+        let mut aof_rdb_check = false;
+        let mut tls = false;
+        let mut notify_keyspace_events = KeyspaceEvents::default();
 
         let mut lines: Vec<String> = vec![];
         for line in reader.lines() {
@@ -62,6 +95,9 @@ impl NodeConfigs {
                 "port" => port = parts[1].to_string(),
                 "role" => role = parts[1].to_string(),
                 "maxclients" => clients_limit = parts[1].parse().unwrap_or(clients_limit),
+                "accept-rate-limit" => {
+                    accept_rate_limit = parts[1].parse().unwrap_or(accept_rate_limit)
+                }
                 "save" => {
                     if parts.len() >= 3 {
                         snapshot_interval = parts[1].parse().unwrap_or(snapshot_interval);
@@ -70,9 +106,71 @@ impl NodeConfigs {
                 }
                 "dbfilename" => snapshot_file = parts[1].to_string(),
                 "dir" => snapshot_path = parts[1].to_string(),
+                "appendfilename" => aof_file = parts[1].to_string(),
+                "appendonly" => append_only = parts[1] == "yes",
+                "appendfsync" => {
+                    fsync_policy = FsyncPolicy::parse(parts[1]).ok_or_else(|| {
+                        std::io::Error::new(
+                            ErrorKind::InvalidInput,
+                            format!(
+                                "appendfsync '{}' inválido: debe ser always, everysec o no",
+                                parts[1]
+                            ),
+                        )
+                    })?
+                }
                 "logfile" => log_file = parts[1].to_string(),
                 "loglevel" => log_level = parts[1].to_string(),
                 "node-id" => node_id = Some(parts[1].to_string()),
+                "cluster-slot-count" => {
+                    slot_count = parts[1].parse().map_err(|_| {
+                        std::io::Error::new(ErrorKind::InvalidInput, "cluster-slot-count inválido")
+                    })?
+                }
+                "cluster-require-full-coverage" => require_full_coverage = parts[1] != "no",
+                "client-output-buffer-limit" => {
+                    if let Some((class, limit)) = OutputBufferLimit::parse(&parts[1..]) {
+                        output_buffer_limits.set(class, limit);
+                    }
+                }
+                "max-protocol-errors" => {
+                    max_protocol_errors = parts[1].parse().unwrap_or(max_protocol_errors)
+                }
+                "debug-invariants" => debug_invariants = parts[1] == "yes",
+                "aof-rdb-check" => aof_rdb_check = parts[1] == "yes",
+                "tls" => tls = parts[1] == "yes",
+                "notify-keyspace-events" => {
+                    notify_keyspace_events = KeyspaceEvents::parse(parts[1].trim_matches('"'))
+                        .ok_or_else(|| {
+                            std::io::Error::new(
+                                ErrorKind::InvalidInput,
+                                format!("notify-keyspace-events '{}' inválido", parts[1]),
+                            )
+                        })?
+                }
+                "latency-monitor-threshold" => {
+                    latency_monitor_threshold =
+                        parts[1].parse().unwrap_or(latency_monitor_threshold)
+                }
+                "maxmemory" => {
+                    maxmemory = parse_memory_size(parts[1]).ok_or_else(|| {
+                        std::io::Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("maxmemory '{}' inválido", parts[1]),
+                        )
+                    })?
+                }
+                "maxmemory-policy" => {
+                    maxmemory_policy = EvictionPolicy::parse(parts[1]).ok_or_else(|| {
+                        std::io::Error::new(
+                            ErrorKind::InvalidInput,
+                            format!(
+                                "maxmemory-policy '{}' inválido: debe ser noeviction, allkeys-lru o allkeys-lfu",
+                                parts[1]
+                            ),
+                        )
+                    })?
+                }
                 "hash-slots" => {
                     let ranges: Vec<&str> = parts[1..].to_vec();
                     for range in ranges {
@@ -91,8 +189,20 @@ impl NodeConfigs {
             lines.push(trimmed);
         }
 
+        if let Some(id) = node_id.as_ref().filter(|id| !is_valid_node_id(id)) {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "node-id '{}' inválido: debe tener {} caracteres hexadecimales en minúscula",
+                    id, NODE_ID_LEN
+                ),
+            ));
+        }
+
+        // Sin node-id se genera uno y se guarda en el archivo, para que el
+        // nodo mantenga su identidad al reiniciarse
         if node_id.is_none() {
-            let new_id = random_32bit_id();
+            let new_id = generate_node_id();
             lines.push(format!("node-id {}", new_id));
             node_id = Some(new_id);
 
@@ -110,19 +220,48 @@ impl NodeConfigs {
             panic!("Faltan 'bind' o 'port' en la configuración.");
         }
 
+        let slot_count = validate_slot_count(slot_count)
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e.to_string()))?;
+        if slots_range != EMPTY_SLOT_RANGE
+            && (slots_range.0 > slots_range.1 || slots_range.1 >= slot_count)
+        {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "hash-slots {}-{} fuera de los {} slots del cluster",
+                    slots_range.0, slots_range.1, slot_count
+                ),
+            ));
+        }
+
         Ok(Self {
             ip,
             port,
             initial_role: role,
             clients_limit,
+            accept_rate_limit,
             snapshot_interval,
             snapshot_k_changes,
             snapshot_file,
             snapshot_path,
+            aof_file,
+            append_only,
+            fsync_policy,
             log_file,
             log_level,
             node_id: node_id.unwrap(),
             initial_slots_range: slots_range,
+            slot_count,
+            require_full_coverage,
+            latency_monitor_threshold,
+            maxmemory,
+            maxmemory_policy,
+            output_buffer_limits,
+            max_protocol_errors,
+            debug_invariants,
+            aof_rdb_check,
+            tls,
+            notify_keyspace_events,
         })
     }
 
@@ -132,7 +271,7 @@ impl NodeConfigs {
             .unwrap()
     }
 
-    pub fn get_id(&self) -> String {
+    pub fn get_id(&self) -> NodeId {
         self.node_id.clone()
     }
 
@@ -144,6 +283,11 @@ impl NodeConfigs {
         self.clients_limit
     }
 
+    /// Conexiones nuevas aceptadas por segundo, 0 si no hay límite.
+    pub fn get_accept_rate_limit(&self) -> u64 {
+        self.accept_rate_limit
+    }
+
     pub fn get_snapshot_data(&self) -> SnapshotData {
         let path = self.snapshot_path.clone() + &self.snapshot_file.clone();
         SnapshotData::new(
@@ -157,6 +301,23 @@ impl NodeConfigs {
         self.snapshot_path.clone() + &self.snapshot_file.clone()
     }
 
+    /// Ruta del append only file que escribe BGREWRITEAOF, dentro del
+    /// mismo directorio que los snapshots.
+    pub fn get_aof_dst(&self) -> String {
+        self.snapshot_path.clone() + &self.aof_file
+    }
+
+    /// Indica si cada escritura aplicada se agrega al AOF y si al arrancar
+    /// se carga el AOF antes que el snapshot (`appendonly`).
+    pub fn get_append_only(&self) -> bool {
+        self.append_only
+    }
+
+    /// Cuándo se fuerza a disco lo agregado al AOF (`appendfsync`).
+    pub fn get_fsync_policy(&self) -> FsyncPolicy {
+        self.fsync_policy
+    }
+
     pub fn get_snapshot_interval(&self) -> u64 {
         self.snapshot_interval as u64
     }
@@ -190,6 +351,95 @@ impl NodeConfigs {
         self.initial_slots_range = slots;
     }
 
+    /// Cantidad de hash slots del cluster (`cluster-slot-count`).
+    pub fn get_slot_count(&self) -> u16 {
+        self.slot_count
+    }
+
+    /// Rango con todos los slots del cluster, el que toma el primer nodo.
+    pub fn get_full_slot_range(&self) -> SlotRange {
+        (0, self.slot_count - 1)
+    }
+
+    /// Indica si el nodo deja de atender claves cuando hay slots sin asignar.
+    pub fn get_require_full_coverage(&self) -> bool {
+        self.require_full_coverage
+    }
+
+    pub fn set_require_full_coverage(&mut self, required: bool) {
+        self.require_full_coverage = required;
+    }
+
+    /// Umbral en milisegundos a partir del cual se registran eventos de
+    /// latencia, 0 deshabilita el monitor.
+    pub fn get_latency_monitor_threshold(&self) -> u64 {
+        self.latency_monitor_threshold
+    }
+
+    pub fn set_latency_monitor_threshold(&mut self, threshold_ms: u64) {
+        self.latency_monitor_threshold = threshold_ms;
+    }
+
+    /// Memoria aproximada, en bytes, que pueden ocupar los datos del nodo
+    /// (`maxmemory`), 0 si no hay límite.
+    pub fn get_maxmemory(&self) -> u64 {
+        self.maxmemory
+    }
+
+    pub fn set_maxmemory(&mut self, bytes: u64) {
+        self.maxmemory = bytes;
+    }
+
+    /// Qué hace el nodo cuando una escritura superaría `maxmemory`.
+    pub fn get_maxmemory_policy(&self) -> EvictionPolicy {
+        self.maxmemory_policy
+    }
+
+    pub fn set_maxmemory_policy(&mut self, policy: EvictionPolicy) {
+        self.maxmemory_policy = policy;
+    }
+
+    /// Límites de buffer de salida de cada clase de cliente.
+    pub fn get_output_buffer_limits(&self) -> OutputBufferLimits {
+        self.output_buffer_limits
+    }
+
+    /// Errores de protocolo consecutivos tolerados por conexión antes de
+    /// cerrarla, 0 si no hay límite.
+    pub fn get_max_protocol_errors(&self) -> u32 {
+        self.max_protocol_errors
+    }
+
+    /// Indica si se verifican los invariantes internos después de cada
+    /// comando (`debug-invariants`).
+    pub fn get_debug_invariants(&self) -> bool {
+        self.debug_invariants
+    }
+
+    pub fn set_debug_invariants(&mut self, enabled: bool) {
+        self.debug_invariants = enabled;
+    }
+
+    /// Indica si al arrancar se compara el RDB con el AOF (`aof-rdb-check`).
+    pub fn get_aof_rdb_check(&self) -> bool {
+        self.aof_rdb_check
+    }
+
+    /// Indica si los clientes se conectan cifrando con TLS-lite (`tls`).
+    pub fn get_tls(&self) -> bool {
+        self.tls
+    }
+
+    /// Canales en los que se publican las notificaciones de keyspace
+    /// (`notify-keyspace-events`).
+    pub fn get_notify_keyspace_events(&self) -> KeyspaceEvents {
+        self.notify_keyspace_events
+    }
+
+    pub fn set_notify_keyspace_events(&mut self, events: KeyspaceEvents) {
+        self.notify_keyspace_events = events;
+    }
+
     pub fn owns_slot(&self, slot: u16) -> bool {
         if slot > self.initial_slots_range.0 && slot < self.initial_slots_range.1 {
             return true;
@@ -198,6 +448,104 @@ impl NodeConfigs {
     }
 }
 
+/// Política de `appendfsync`: cuándo se llama a `sync_data` sobre el
+/// append only file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    /// Después de cada escritura
+    Always,
+    /// A lo sumo una vez por segundo, si hubo escrituras
+    EverySec,
+    /// Nunca, el sistema operativo decide cuándo bajar los datos a disco
+    No,
+}
+
+impl FsyncPolicy {
+    /// Parsea el valor de `appendfsync`, `None` si no es válido.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "always" => Some(FsyncPolicy::Always),
+            "everysec" => Some(FsyncPolicy::EverySec),
+            "no" => Some(FsyncPolicy::No),
+            _ => None,
+        }
+    }
+}
+
+/// Política de `maxmemory-policy`: qué claves se desalojan cuando los
+/// datos superan `maxmemory`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// No se desaloja nada, las escrituras se rechazan con OOM
+    NoEviction,
+    /// Se desalojan primero las claves accedidas hace más tiempo
+    AllKeysLru,
+    /// Se desalojan primero las claves accedidas menos veces
+    AllKeysLfu,
+}
+
+impl EvictionPolicy {
+    /// Parsea el valor de `maxmemory-policy`, `None` si no es válido.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "noeviction" => Some(EvictionPolicy::NoEviction),
+            "allkeys-lru" => Some(EvictionPolicy::AllKeysLru),
+            "allkeys-lfu" => Some(EvictionPolicy::AllKeysLfu),
+            _ => None,
+        }
+    }
+}
+
+/// Canales de `notify-keyspace-events` en los que se publican los eventos
+/// sobre las claves. Sin `K` ni `E` no se publica nada, como en Redis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyspaceEvents {
+    /// Publicar el evento en `__keyspace@0__:<clave>` (`K`)
+    pub keyspace: bool,
+    /// Publicar la clave en `__keyevent@0__:<evento>` (`E`)
+    pub keyevent: bool,
+}
+
+impl KeyspaceEvents {
+    /// Parsea las flags de `notify-keyspace-events`, `None` si alguna no
+    /// es válida. Las clases de eventos de Redis (`g`, `$`, `l`, `A`, ...)
+    /// se aceptan pero no filtran: se notifican todos los eventos.
+    pub fn parse(flags: &str) -> Option<Self> {
+        let mut events = KeyspaceEvents::default();
+        for flag in flags.chars() {
+            match flag {
+                'K' => events.keyspace = true,
+                'E' => events.keyevent = true,
+                'g' | '$' | 'l' | 's' | 'h' | 'z' | 'x' | 'e' | 't' | 'm' | 'd' | 'n' | 'A' => {}
+                _ => return None,
+            }
+        }
+        Some(events)
+    }
+
+    /// Indica si se publica en algún canal.
+    pub fn enabled(&self) -> bool {
+        self.keyspace || self.keyevent
+    }
+}
+
+/// Parsea un tamaño de memoria como `1048576`, `512kb`, `100mb` o `1gb`.
+fn parse_memory_size(value: &str) -> Option<u64> {
+    let value = value.to_ascii_lowercase();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value.as_str(), ""),
+    };
+    let multiplier = match unit {
+        "" | "b" => 1,
+        "kb" => 1024,
+        "mb" => 1024 * 1024,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
 #[derive(Clone)]
 pub struct SnapshotData {
     pub path: String,
@@ -215,7 +563,98 @@ impl SnapshotData {
     }
 }
 
-pub fn random_32bit_id() -> String {
-    let id: u32 = RngCore::next_u32(&mut rand::thread_rng());
-    id.to_string()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    /// Parsea una configuración mínima con las líneas extra dadas.
+    fn parse_config(extra: &str) -> Result<NodeConfigs, std::io::Error> {
+        let file = NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            format!(
+                "bind 0.0.0.0\nport 6379\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\n{}",
+                extra
+            ),
+        )
+        .unwrap();
+        NodeConfigs::new(file.path().to_str().unwrap())
+    }
+
+    #[test]
+    fn test_append_only_defaults_to_no() {
+        assert!(!parse_config("").unwrap().get_append_only());
+        assert!(parse_config("appendonly yes\n").unwrap().get_append_only());
+    }
+
+    #[test]
+    fn test_fsync_policy_defaults_to_everysec() {
+        let config = parse_config("").unwrap();
+
+        assert_eq!(config.get_fsync_policy(), FsyncPolicy::EverySec);
+    }
+
+    #[test]
+    fn test_fsync_policy_parses_known_values() {
+        for (value, policy) in [
+            ("always", FsyncPolicy::Always),
+            ("everysec", FsyncPolicy::EverySec),
+            ("no", FsyncPolicy::No),
+        ] {
+            let config = parse_config(&format!("appendfsync {}\n", value)).unwrap();
+            assert_eq!(config.get_fsync_policy(), policy);
+        }
+    }
+
+    #[test]
+    fn test_fsync_policy_rejects_unknown_value() {
+        let err = parse_config("appendfsync sometimes\n").unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_maxmemory_defaults_to_unlimited_without_eviction() {
+        let config = parse_config("").unwrap();
+
+        assert_eq!(config.get_maxmemory(), 0);
+        assert_eq!(config.get_maxmemory_policy(), EvictionPolicy::NoEviction);
+    }
+
+    #[test]
+    fn test_maxmemory_parses_size_and_policy() {
+        let config = parse_config("maxmemory 100mb\nmaxmemory-policy allkeys-lru\n").unwrap();
+
+        assert_eq!(config.get_maxmemory(), 100 * 1024 * 1024);
+        assert_eq!(config.get_maxmemory_policy(), EvictionPolicy::AllKeysLru);
+        assert_eq!(parse_memory_size("2048"), Some(2048));
+        assert_eq!(parse_memory_size("3GB"), Some(3 * 1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_notify_keyspace_events_parses_channels() {
+        let config = parse_config("").unwrap();
+        assert!(!config.get_notify_keyspace_events().enabled());
+
+        let config = parse_config("notify-keyspace-events KEA\n").unwrap();
+        let events = config.get_notify_keyspace_events();
+        assert!(events.keyspace && events.keyevent);
+
+        let config = parse_config("notify-keyspace-events Eg$\n").unwrap();
+        let events = config.get_notify_keyspace_events();
+        assert!(!events.keyspace && events.keyevent);
+
+        let err = parse_config("notify-keyspace-events KQ\n").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_maxmemory_rejects_invalid_values() {
+        let err = parse_config("maxmemory-policy volatile-ttl\n").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let err = parse_config("maxmemory lots\n").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
 }
@@ -1,13 +1,17 @@
 //! Implementación del logger y sus funciones/macros relacionadas.
 
 // IMPORTS
-use crate::config::node_configs::NodeConfigs;
+use crate::command::aof_rewrite::{replay, rewrite};
+use crate::config::node_configs::{FsyncPolicy, NodeConfigs};
 use crate::logs::log_types::LogType;
+use crate::storage::DataStore;
+use std::fmt;
 use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Write};
+use std::io::{BufReader, BufWriter, Write};
 use std::sync::Arc;
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
 use std::thread;
+use std::time::{Duration, Instant};
 
 // CÓDIGOS DE NIVELES DE LOGS
 const WARNING: i64 = 0;
@@ -15,8 +19,31 @@ pub const NOTICE: i64 = 1;
 const VERBOSE: i64 = 2;
 const DEBUG: i64 = 3;
 
+/// Cada cuánto se fuerza a disco el AOF con `appendfsync everysec`.
+const FSYNC_INTERVAL: Duration = Duration::from_secs(1);
+
 // CÓDIGO
 
+/// Errores al reproducir el AOF.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AofError {
+    /// No se pudo abrir o leer el archivo
+    IoError(String),
+    /// Una entrada del archivo está mal formada o no se pudo aplicar
+    Corrupt(String),
+}
+
+impl fmt::Display for AofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AofError::IoError(e) => write!(f, "Error de IO: {}", e),
+            AofError::Corrupt(e) => write!(f, "AOF corrupto: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AofError {}
+
 /// Logger del servidor que funciona -precondición- sobre archivos `.aof` (append-only file).
 /// Cada instancia abre un hilo que maneja el file handler del archivo a appendear,
 /// si no existe, lo crea. Y dentro de ese hilo se queda esperando mensajes de log.
@@ -25,11 +52,15 @@ const DEBUG: i64 = 3;
 ///
 /// * `level` Tipo de logs permitidos.
 /// * `sender` Extremo sender del canal de comunicación con el nodo que loggea la información.
+/// * `aof_dst` Ruta del append only file que reproduce y reescribe.
+/// * `append_only` Si las escrituras aplicadas se agregan al AOF (`appendonly`).
 #[derive(Clone, Debug)]
 pub struct AofLogger {
     level: i64,
     sender: Sender<LogType>,
     role: String,
+    aof_dst: String,
+    append_only: bool,
 }
 
 impl Drop for AofLogger {
@@ -41,15 +72,78 @@ impl Drop for AofLogger {
 impl AofLogger {
     /// Método para loggear una operación.
     /// Precondición: **Debe ser llamado una única vez por instancia**
-    pub fn start_log_operation(logfile: String, level: i64, receiver: Receiver<LogType>) {
+    ///
+    /// Las entradas del AOF se agregan a `aof_dst`, que se abre recién con
+    /// la primera. Según `fsync`, el AOF se fuerza a disco con `sync_data`
+    /// después de cada entrada (`always`), a lo sumo una vez por segundo si
+    /// hubo entradas (`everysec`), o nunca (`no`). Con `everysec` el hilo se
+    /// despierta aunque no lleguen logs, para no dejar entradas sin
+    /// sincronizar. El archivo de logs sólo se vacía al sistema operativo.
+    pub fn start_log_operation(
+        logfile: String,
+        aof_dst: String,
+        level: i64,
+        fsync: FsyncPolicy,
+        receiver: Receiver<LogType>,
+    ) {
         let file = create_append_log_file(logfile);
         let mut writer = BufWriter::new(file);
+        let mut aof = AofFile::new(aof_dst);
+        let mut unsynced = false;
+        let mut last_sync = Instant::now();
         loop {
-            match receiver.recv() {
-                Ok(LogType::Shutdown) => break,
-                Ok(log) => process_log(log, level, &mut writer),
-                Err(_) => break,
+            let log = match fsync {
+                FsyncPolicy::EverySec => match receiver.recv_timeout(FSYNC_INTERVAL) {
+                    Ok(log) => Some(log),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                },
+                _ => match receiver.recv() {
+                    Ok(log) => Some(log),
+                    Err(_) => break,
+                },
+            };
+            let appended = match log {
+                Some(LogType::Shutdown) => break,
+                Some(LogType::AofAppend(entry)) => match aof.append(&entry) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        let msg = format!("Failed to append to AOF {}: {}", aof.path, e);
+                        process_log(LogType::Warn(msg, String::new()), level, &mut writer);
+                        false
+                    }
+                },
+                Some(LogType::AofRewriteStarted) => {
+                    aof.start_rewrite();
+                    false
+                }
+                Some(LogType::AofRewriteFinished(ok)) => match aof.finish_rewrite(ok) {
+                    Ok(()) => ok,
+                    Err(e) => {
+                        let msg = format!("Failed to reopen AOF {}: {}", aof.path, e);
+                        process_log(LogType::Warn(msg, String::new()), level, &mut writer);
+                        false
+                    }
+                },
+                Some(log) => {
+                    process_log(log, level, &mut writer);
+                    false
+                }
+                None => false,
             };
+            match fsync {
+                FsyncPolicy::Always if appended => aof.sync(),
+                FsyncPolicy::EverySec => unsynced |= appended,
+                _ => {}
+            }
+            if unsynced && last_sync.elapsed() >= FSYNC_INTERVAL {
+                aof.sync();
+                unsynced = false;
+                last_sync = Instant::now();
+            }
+        }
+        if unsynced {
+            aof.sync();
         }
     }
 
@@ -58,10 +152,14 @@ impl AofLogger {
         let logfile = node_settings.get_log_dst();
         let level = set_level(node_settings.get_log_level());
         let role = node_settings.get_role();
+        let aof_dst = node_settings.get_aof_dst();
+        let append_only = node_settings.get_append_only();
+        let fsync = node_settings.get_fsync_policy();
+        let thread_aof_dst = aof_dst.clone();
         let _ = thread::Builder::new()
             .name("Logger".to_string())
             .spawn(move || {
-                AofLogger::start_log_operation(logfile, level, receiver);
+                AofLogger::start_log_operation(logfile, thread_aof_dst, level, fsync, receiver);
             });
         sender
             .send(LogType::Notice(
@@ -73,6 +171,8 @@ impl AofLogger {
             level,
             sender,
             role: role.to_string(),
+            aof_dst,
+            append_only,
         })
     }
 
@@ -128,6 +228,66 @@ impl AofLogger {
             .unwrap();
     }
 
+    /// Reconstruye un store reproduciendo el AOF de `path`, con las mismas
+    /// reglas que `aof_rewrite::replay`: una entrada mal formada o que falla
+    /// al aplicarse aborta la carga, y sólo se tolera una última entrada
+    /// cortada, la que deja una caída a mitad de un append.
+    ///
+    /// # Returns
+    ///
+    /// El store reconstruido, `AofError::IoError` si no se pudo abrir el
+    /// archivo o `AofError::Corrupt` si alguna entrada es inválida
+    pub fn replay(&self, path: &str) -> Result<DataStore, AofError> {
+        let file = File::open(path).map_err(|e| AofError::IoError(e.to_string()))?;
+        let store = replay(&mut BufReader::new(file)).map_err(|e| {
+            self.log_warning(format!("AOF replay from {} aborted: {}", path, e));
+            AofError::Corrupt(e)
+        })?;
+        self.log_event(format!(
+            "AOF replay from {} finished with {} items",
+            path,
+            store.len()
+        ));
+        Ok(store)
+    }
+
+    /// Agrega al AOF una escritura aplicada, codificada como array RESP.
+    /// Sin `appendonly yes` no hace nada.
+    pub fn append(&self, entry: Vec<u8>) {
+        if self.append_only {
+            self.sender.send(LogType::AofAppend(entry)).unwrap();
+        }
+    }
+
+    /// Avisa que empieza una reescritura a partir del store actual: las
+    /// escrituras que lleguen mientras dura se agregan también al archivo
+    /// nuevo, para que no se pierdan al reemplazar el anterior.
+    pub fn start_rewrite(&self) {
+        self.sender.send(LogType::AofRewriteStarted).unwrap();
+    }
+
+    /// Reescribe el AOF con la secuencia mínima de comandos que reconstruye
+    /// `store`, descartando las escrituras redundantes acumuladas.
+    ///
+    /// El archivo nuevo se escribe aparte y recién completo reemplaza al
+    /// anterior, así que un corte a mitad de camino no lo deja a medias.
+    ///
+    /// # Returns
+    ///
+    /// `AofError` si no se pudo escribir el archivo
+    pub fn rewrite(&self, store: &DataStore) -> Result<(), AofError> {
+        let result = rewrite(store, &self.aof_dst).map_err(|e| AofError::IoError(e.to_string()));
+        self.sender
+            .send(LogType::AofRewriteFinished(result.is_ok()))
+            .unwrap();
+        result
+    }
+
+    /// Obtiene la ruta del append only file.
+    pub fn get_aof_dst(&self) -> &str {
+        &self.aof_dst
+    }
+
     /// Detiene la ejecución del hilo listener y cierra el archivo `.aof`.
     pub fn shutdown(&self) {
         self.sender.send(LogType::Shutdown).unwrap();
@@ -184,6 +344,70 @@ pub fn set_level(level: String) -> i64 {
     }
 }
 
+/// Append only file que escribe el hilo del logger.
+struct AofFile {
+    path: String,
+    /// Se abre con la primera entrada, y se vuelve a abrir después de una
+    /// reescritura porque el archivo fue reemplazado
+    writer: Option<BufWriter<File>>,
+    /// Entradas agregadas desde que empezó la reescritura en curso
+    rewrite_buffer: Option<Vec<u8>>,
+}
+
+impl AofFile {
+    fn new(path: String) -> Self {
+        Self {
+            path,
+            writer: None,
+            rewrite_buffer: None,
+        }
+    }
+
+    /// Agrega una entrada al final del archivo.
+    fn append(&mut self, entry: &[u8]) -> std::io::Result<()> {
+        if let Some(buffer) = &mut self.rewrite_buffer {
+            buffer.extend_from_slice(entry);
+        }
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => self.writer.insert(BufWriter::new(
+                OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(&self.path)?,
+            )),
+        };
+        writer.write_all(entry)?;
+        writer.flush()
+    }
+
+    /// Fuerza a disco lo escrito en el archivo. Un error al sincronizar no
+    /// detiene el logger.
+    fn sync(&self) {
+        if let Some(writer) = &self.writer {
+            let _ = writer.get_ref().sync_data();
+        }
+    }
+
+    fn start_rewrite(&mut self) {
+        self.rewrite_buffer = Some(Vec::new());
+    }
+
+    /// Si la reescritura fue exitosa, pasa a escribir el archivo nuevo y le
+    /// agrega las entradas que llegaron mientras se escribía.
+    fn finish_rewrite(&mut self, ok: bool) -> std::io::Result<()> {
+        let buffer = self.rewrite_buffer.take().unwrap_or_default();
+        if !ok {
+            return Ok(());
+        }
+        self.writer = None;
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        self.append(&buffer)
+    }
+}
+
 /// Función auxiliar, para abrir el file en append mode
 /// o crearlo si no existe.
 pub fn create_append_log_file(logfile: String) -> File {
@@ -196,7 +420,11 @@ pub fn create_append_log_file(logfile: String) -> File {
 
 /// Función auxuliar que procesa el dato recibido por el canal de logs,
 /// verifica el nivel y loggea si el nivel es igual o mayor al tipo de log.
-pub fn process_log(rec_log: LogType, level: i64, writer: &mut BufWriter<File>) {
+///
+/// # Returns
+///
+/// `true` si el log se escribió en el archivo
+pub fn process_log(rec_log: LogType, level: i64, writer: &mut BufWriter<File>) -> bool {
     let should_log = match rec_log {
         LogType::Warn(_, _) | LogType::Error(_, _) if level >= WARNING => true,
         LogType::Notice(_, _) if level >= NOTICE => true,
@@ -205,13 +433,15 @@ pub fn process_log(rec_log: LogType, level: i64, writer: &mut BufWriter<File>) {
         _ => false,
     };
     if !should_log {
-        return;
+        return false;
     }
     let msg = rec_log.get_log_msg();
     if let Some(msg) = msg {
         writeln!(writer, "{}", msg).unwrap();
         writer.flush().unwrap();
+        return true;
     }
+    false
 }
 
 #[cfg(test)]
@@ -233,7 +463,7 @@ mod tests {
             dir ./
             logfile test.log
             loglevel notice
-            node-id test123
+            node-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca
             hash-slots 0-16383
             "#;
         std::fs::write(temp_file.path(), config_content).unwrap();
@@ -242,6 +472,30 @@ mod tests {
         NodeConfigs::new(temp_file.path().to_string_lossy().as_ref()).unwrap()
     }
 
+    /// Configuración de test cuyo AOF es `dir/appendonly.aof`.
+    fn create_test_config_in(dir: &std::path::Path) -> NodeConfigs {
+        let config = dir.join("node.conf");
+        std::fs::write(
+            &config,
+            format!(
+                "bind 0.0.0.0\nport 6379\nrole M\ndir {}/\nlogfile {}\n",
+                dir.display(),
+                dir.join("node.log").display()
+            ),
+        )
+        .unwrap();
+        NodeConfigs::new(config.to_str().unwrap()).unwrap()
+    }
+
+    /// Codifica un comando como array RESP, como los escribe el AOF.
+    fn encode_command(args: &[&str]) -> String {
+        let mut command = format!("*{}\r\n", args.len());
+        for arg in args {
+            command.push_str(&format!("${}\r\n{}\r\n", arg.len(), arg));
+        }
+        command
+    }
+
     #[test]
     fn test_set_level() {
         assert_eq!(set_level("warning".to_string()), WARNING);
@@ -383,6 +637,116 @@ mod tests {
         assert!(debug_str.contains("M"));
     }
 
+    #[test]
+    fn test_aof_entries_go_to_aof_under_every_fsync_policy() {
+        for fsync in [FsyncPolicy::Always, FsyncPolicy::EverySec, FsyncPolicy::No] {
+            let dir = tempfile::tempdir().unwrap();
+            let logfile = dir.path().join("node.log").to_string_lossy().to_string();
+            let aof_dst = dir
+                .path()
+                .join("appendonly.aof")
+                .to_string_lossy()
+                .to_string();
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let entry = encode_command(&["SET", "Hero", "Wuyang"]);
+            sender
+                .send(LogType::AofAppend(entry.clone().into_bytes()))
+                .unwrap();
+            sender.send(LogType::Shutdown).unwrap();
+
+            AofLogger::start_log_operation(
+                logfile.clone(),
+                aof_dst.clone(),
+                DEBUG,
+                fsync,
+                receiver,
+            );
+
+            assert_eq!(std::fs::read_to_string(&aof_dst).unwrap(), entry);
+            assert!(std::fs::read_to_string(&logfile).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_replay_aborts_on_malformed_entries() {
+        let aof = NamedTempFile::new().unwrap();
+        std::fs::write(
+            aof.path(),
+            concat!(
+                "*3\r\n$3\r\nSET\r\n$4\r\nHero\r\n$6\r\nKiriko\r\n",
+                "*2\r\n$7\r\nFLYKICK\r\n$5\r\nGenji\r\n",
+                "*3\r\n$4\r\nSADD\r\n$4\r\nMaps\r\n$5\r\nIlios\r\n",
+            ),
+        )
+        .unwrap();
+        let logger = AofLogger::new(create_test_config());
+
+        let result = logger.replay(aof.path().to_str().unwrap());
+
+        assert!(matches!(result, Err(AofError::Corrupt(_))));
+    }
+
+    #[test]
+    fn test_replay_ignores_truncated_last_entry() {
+        let aof = NamedTempFile::new().unwrap();
+        std::fs::write(
+            aof.path(),
+            concat!(
+                "*3\r\n$3\r\nSET\r\n$4\r\nHero\r\n$6\r\nKiriko\r\n",
+                "*4\r\n$5\r\nRPUSH\r\n$5\r\nTanks\r\n$5\r\nSigma\r\n$8\r\nDoom",
+            ),
+        )
+        .unwrap();
+        let logger = AofLogger::new(create_test_config());
+
+        let store = logger.replay(aof.path().to_str().unwrap()).unwrap();
+
+        let mut expected = DataStore::new();
+        expected.set("Hero".to_string(), "Kiriko".to_string());
+        assert!(store.has_same_data(&expected));
+    }
+
+    #[test]
+    fn test_rewrite_compacts_log_and_replays_to_same_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let logger = AofLogger::new(create_test_config_in(dir.path()));
+        let mut log = String::new();
+        for hero in ["Tracer", "Sombra", "Echo", "Venture"] {
+            log.push_str(&encode_command(&["SET", "Hero", hero]));
+        }
+        for tank in ["Orisa", "Mauga", "Hazard"] {
+            log.push_str(&encode_command(&["RPUSH", "Tanks", tank]));
+        }
+        for map in ["Numbani", "Paraíso", "Numbani"] {
+            log.push_str(&encode_command(&["SADD", "Maps", map]));
+        }
+        log.push_str(&encode_command(&["SET", "Event", "Winter Wonderland"]));
+        log.push_str(&encode_command(&["EXPIRE", "Event", "600"]));
+        std::fs::write(logger.get_aof_dst(), log).unwrap();
+        let store = logger.replay(logger.get_aof_dst()).unwrap();
+
+        logger.rewrite(&store).unwrap();
+
+        let rewritten = std::fs::read_to_string(logger.get_aof_dst()).unwrap();
+        let commands = rewritten.lines().filter(|l| l.starts_with('*')).count();
+        assert_eq!(commands, 5);
+        assert!(rewritten.contains("EXPIRE"));
+        let replayed = logger.replay(logger.get_aof_dst()).unwrap();
+        assert!(replayed.has_same_data(&store));
+        assert_eq!(replayed.get("Hero"), Some(&"Venture".to_string()));
+        assert!(replayed.remaining_ttl("Event").is_some());
+    }
+
+    #[test]
+    fn test_replay_missing_file_is_io_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let logger = AofLogger::new(create_test_config());
+
+        let result = logger.replay(dir.path().join("missing.aof").to_str().unwrap());
+
+        assert!(matches!(result, Err(AofError::IoError(_))));
+    }
+
     #[test]
     fn test_log_level_constants() {
         assert_eq!(WARNING, 0);
@@ -28,6 +28,13 @@ pub enum LogType {
     Debug(String, String),
     RegEvent(String, String),
     Warn(String, String),
+    /// Escritura aplicada, codificada como array RESP, a agregar al AOF
+    AofAppend(Vec<u8>),
+    /// Empieza una reescritura del AOF: hasta que termine, las escrituras
+    /// se guardan también aparte para agregarlas al archivo nuevo
+    AofRewriteStarted,
+    /// Terminó la reescritura del AOF, con o sin éxito
+    AofRewriteFinished(bool),
     Shutdown,
 }
 
@@ -45,7 +52,7 @@ impl LogType {
     /// # Returns
     /// * Mensaje tipo `String` respestando formato de Redis.
     /// `PID:ROLE DATE TYPE MESSAGE`
-    /// * `None` si el tipo de log es `Shutdown`.`
+    /// * `None` si no es un mensaje de log (`Shutdown` o entradas del AOF).
     pub fn get_log_msg(self) -> Option<String> {
         let date = get_date();
         let pid = std::process::id();
@@ -58,7 +65,7 @@ impl LogType {
             LogType::Debug(msg, role) => format_log(".", msg, role),
             LogType::RegEvent(msg, role) => format_log("-", msg, role),
             LogType::Warn(msg, role) => format_log("#", msg, role),
-            LogType::Shutdown => None,
+            _ => None,
         }
     }
 
@@ -66,7 +73,7 @@ impl LogType {
     ///
     /// # Returns
     ///
-    /// `Option<String>` - El mensaje del log o None si es Shutdown
+    /// `Option<String>` - El mensaje del log o None si no es un mensaje de log
     pub fn get_message(&self) -> Option<String> {
         match self {
             LogType::Notice(msg, _) => Some(msg.clone()),
@@ -74,7 +81,7 @@ impl LogType {
             LogType::Debug(msg, _) => Some(msg.clone()),
             LogType::RegEvent(msg, _) => Some(msg.clone()),
             LogType::Warn(msg, _) => Some(msg.clone()),
-            LogType::Shutdown => None,
+            _ => None,
         }
     }
 
@@ -82,7 +89,7 @@ impl LogType {
     ///
     /// # Returns
     ///
-    /// `Option<String>` - El rol del log o None si es Shutdown
+    /// `Option<String>` - El rol del log o None si no es un mensaje de log
     pub fn get_role(&self) -> Option<String> {
         match self {
             LogType::Notice(_, role) => Some(role.clone()),
@@ -90,7 +97,7 @@ impl LogType {
             LogType::Debug(_, role) => Some(role.clone()),
             LogType::RegEvent(_, role) => Some(role.clone()),
             LogType::Warn(_, role) => Some(role.clone()),
-            LogType::Shutdown => None,
+            _ => None,
         }
     }
 
@@ -107,7 +114,7 @@ impl LogType {
     ///
     /// # Returns
     ///
-    /// `Option<&'static str>` - El símbolo del tipo de log o None si es Shutdown
+    /// `Option<&'static str>` - El símbolo del tipo de log o None si no es un mensaje de log
     pub fn get_symbol(&self) -> Option<&'static str> {
         match self {
             LogType::Notice(_, _) => Some("*"),
@@ -115,7 +122,7 @@ impl LogType {
             LogType::Debug(_, _) => Some("."),
             LogType::RegEvent(_, _) => Some("-"),
             LogType::Warn(_, _) => Some("#"),
-            LogType::Shutdown => None,
+            _ => None,
         }
     }
 }
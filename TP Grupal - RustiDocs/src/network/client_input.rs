@@ -1,11 +1,15 @@
 use super::resp_message::RespMessage;
 use crate::command::Instruction;
 use crate::logs::aof_logger::AofLogger;
+use crate::network::connection_handler::{
+    ClientRegistry, ERR_TOO_MANY_PROTOCOL_ERRORS, ProtocolErrorCounter,
+};
 use crate::network::resp_parser::parse_resp_line;
 use crate::security::types::ValidationError;
-use crate::security::users::permissions::Permissions;
+use crate::security::users::permissions::{ERR_NOPERM_KEY, Permissions, err_noperm_command};
 use crate::security::users::user_base::UserBase;
 use std::io::{BufReader, Read, Write};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::mpsc::Sender;
 
@@ -22,6 +26,9 @@ pub struct ClientInput {
     user_base: Arc<UserBase>,
     is_logged: bool,
     permission: Permissions,
+    protocol_errors: ProtocolErrorCounter,
+    peer_addr: Option<SocketAddr>,
+    clients: Option<ClientRegistry>,
 }
 
 impl ClientInput {
@@ -42,25 +49,133 @@ impl ClientInput {
             user_base,
             is_logged: false,
             permission: Permissions::new(),
+            protocol_errors: ProtocolErrorCounter::new(0),
+            peer_addr: None,
+            clients: None,
         }
     }
 
+    /// Cierra la conexión tras `limit` errores de protocolo consecutivos
+    /// (0 no la cierra nunca), registrando en el log la dirección del cliente.
+    pub fn with_protocol_error_limit(mut self, limit: u32, peer_addr: SocketAddr) -> Self {
+        self.protocol_errors = ProtocolErrorCounter::new(limit);
+        self.peer_addr = Some(peer_addr);
+        self
+    }
+
+    /// Registra en `clients` el usuario con el que se autentica la
+    /// conexión, para que el executor sepa quién publica cada mensaje.
+    pub fn with_client_registry(mut self, clients: ClientRegistry) -> Self {
+        self.clients = Some(clients);
+        self
+    }
+
+    /// Guarda el usuario autenticado en los flags de la conexión.
+    fn record_user(&self, username: &str) {
+        let Some(clients) = &self.clients else {
+            return;
+        };
+        let Ok(mut clients) = clients.write() else {
+            return;
+        };
+        if let Some(flags) = clients.get_mut(&self.client_id) {
+            flags.user = Some(username.to_string());
+        }
+    }
+
+    /// Responde a un pedido inválido y lo suma a los errores consecutivos.
+    ///
+    /// # Returns
+    ///
+    /// `true` si la conexión debe cerrarse, ya sea por alcanzar el límite
+    /// de errores o porque no se pudo responder
+    fn handle_protocol_error(&mut self, error: String) -> bool {
+        if !self.protocol_errors.record_error() {
+            return self.output_sender.send(RespMessage::Error(error)).is_err();
+        }
+        let peer = self
+            .peer_addr
+            .map_or_else(|| "unknown".to_string(), |addr| addr.to_string());
+        self.logger.log_warning(format!(
+            "Closing client {} ({}) after {} consecutive protocol errors",
+            self.client_id,
+            peer,
+            self.protocol_errors.consecutive()
+        ));
+        let _ = self
+            .output_sender
+            .send(RespMessage::Error(ERR_TOO_MANY_PROTOCOL_ERRORS.to_string()));
+        let _ = self.output_sender.send(RespMessage::Disconnect);
+        true
+    }
+
+    /// Verifica que el usuario tenga acceso a todas las claves de la
+    /// instrucción, incluidas las que alcanzan sus patrones (`KEYS`, `SCAN`
+    /// y `SORT BY/GET`). Las instrucciones que no se pueden interpretar
+    /// pasan, para que el ejecutor responda el error correspondiente.
+    fn can_access_keys(&self, instruction: &Instruction) -> bool {
+        match instruction.to_command() {
+            Ok(command) => {
+                command
+                    .keys()
+                    .iter()
+                    .all(|key| self.permission.can_access_key(key))
+                    && command
+                        .key_patterns()
+                        .iter()
+                        .all(|pattern| self.permission.can_access_pattern(pattern))
+            }
+            Err(_) => true,
+        }
+    }
+
+    /// Envía la instrucción y el canal de respuesta al command executor.
+    ///
+    /// Devuelve `false` si el executor ya no recibe instrucciones.
+    fn send_to_executor(&self, instruction: Instruction) -> bool {
+        if let Err(e) = self.instruction_sender.send((
+            self.client_id.clone(),
+            instruction,
+            self.output_sender.clone(),
+        )) {
+            eprintln!("Error al enviar la instrucción al ejecutor: {}", e);
+            return false;
+        }
+        true
+    }
+
     pub fn run(&mut self) {
-        let mut reader = BufReader::new(self.connection.as_mut());
+        // El reader toma prestada la conexión, por lo que se reemplaza
+        // temporalmente para poder usar `self` dentro del bucle
+        let mut connection: Box<dyn ClientConnection> = std::mem::replace(
+            &mut self.connection,
+            Box::new(std::io::Cursor::new(Vec::new())),
+        );
+        let mut reader = BufReader::new(connection.as_mut());
         // self.output_sender.send(RespMessage::SimpleString("Debes iniciar sesion con AUTH user password".to_string()));  TODO: Ver si era la que daba problemas
 
         loop {
             // Llama a resp_parser para parsear el mensaje -> devuelve RespMessage
             let parsed = match parse_resp_line(&mut reader) {
                 Ok(msg) => msg,
-                Err(_) => {
+                Err(e) if e.is_connection_error() => {
                     self.logger
                         .log_notice(format!("Client {} disconnected", self.client_id));
+                    eprintln!("Conexión cerrada de {}.", self.client_id);
+                    // Sin esto el hilo de salida sigue esperando respuestas
+                    // y la conexión nunca libera su lugar
+                    let _ = self.output_sender.send(RespMessage::Disconnect);
+                    break;
+                }
+                Err(e) => {
                     eprintln!(
-                        "Error al parsear el mensaje RESP o conexión cerrada de {}.",
-                        self.client_id
+                        "Error al parsear el mensaje RESP de {}: {}",
+                        self.client_id, e
                     );
-                    break;
+                    if self.handle_protocol_error(format!("ERR Protocol error: {}", e)) {
+                        break;
+                    }
+                    continue;
                 }
             };
 
@@ -75,14 +190,13 @@ impl ClientInput {
                 }
                 Err(e) => {
                     eprintln!("Error al convertir RespMessage a Instruction: {}", e);
-                    let error_response = RespMessage::Error(format!("Error: {}", e));
-                    if let Err(e) = self.output_sender.send(error_response) {
-                        eprintln!("Error al enviar la respuesta de error al cliente: {}", e);
+                    if self.handle_protocol_error(format!("Error: {}", e)) {
                         break;
                     }
                     continue;
                 }
             };
+            self.protocol_errors.record_valid();
 
             if instruction.instruction_type == "DISCONNECT" {
                 if let Err(e) = self.output_sender.send(RespMessage::Disconnect) {
@@ -92,25 +206,30 @@ impl ClientInput {
                 break; // Terminar ejecución
             }
 
+            // Los permisos se verifican acá y no en `CommandExecutor::try_execute`:
+            // la sesión de cada conexión vive en su ClientInput y el executor no
+            // la conoce. El NOPERM cumple el rol de `NotEnoughPermissions`.
             if self.is_logged {
-                if self.permission.is_permited(&instruction.instruction_type) {
-                    // Enviar la instruccion y el canal de respeusta al command executor
-                    if let Err(e) = self.instruction_sender.send((
-                        self.client_id.clone(),
-                        instruction,
-                        self.output_sender.clone(),
-                    )) {
-                        eprintln!("Error al enviar la instrucción al ejecutor: {}", e);
+                if !self.can_access_keys(&instruction) {
+                    self.output_sender
+                        .send(RespMessage::Error(ERR_NOPERM_KEY.to_string()))
+                        .unwrap();
+                } else if self.permission.is_permited(&instruction.instruction_type) {
+                    if !self.send_to_executor(instruction) {
                         break;
                     }
                 } else {
-                    eprintln!("La instruccion no esta permitida para el usuario");
                     self.output_sender
-                        .send(RespMessage::SimpleString(
-                            "La instruccion no esta permitida para el usuario".to_string(),
-                        ))
+                        .send(RespMessage::Error(err_noperm_command(
+                            &instruction.instruction_type,
+                        )))
                         .unwrap();
                 }
+            } else if instruction.instruction_type.eq_ignore_ascii_case("PING") {
+                // PING sirve de chequeo de salud, no necesita sesión
+                if !self.send_to_executor(instruction) {
+                    break;
+                }
             } else {
                 if instruction.instruction_type == "AUTH" {
                     match self
@@ -120,6 +239,7 @@ impl ClientInput {
                         Ok(permissions) => {
                             self.permission = permissions;
                             self.is_logged = true;
+                            self.record_user(&instruction.arguments[0]);
                             self.logger.log_event(format!(
                                 "Nuevo usuario {} conectado desde {}",
                                 &instruction.arguments[0], self.client_id
@@ -240,6 +360,305 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_client_input_ping_without_auth() {
+        let (mut client, server_socket) = setup_listener_and_client(0);
+        let (instruction_tx, instruction_rx) = mpsc::channel();
+        let (output_tx, output_rx) = mpsc::channel();
+        let settings = NodeConfigs::new("./tests/utils/test_c_i_1.conf").unwrap();
+        let logger = AofLogger::new(settings);
+
+        thread::spawn(move || {
+            ClientInput::new(
+                "AA000".to_string(),
+                instruction_tx,
+                Box::new(server_socket),
+                output_tx,
+                logger,
+                Arc::new(UserBase::new()),
+            )
+            .run();
+        });
+
+        client.write_all(b"*1\r\n$4\r\nPING\r\n").unwrap();
+        let (_, instruction, _) = instruction_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(instruction.instruction_type, "PING");
+
+        client.write_all(b"*1\r\n$4\r\nping\r\n").unwrap();
+        let (_, instruction, _) = instruction_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(instruction.instruction_type, "ping");
+
+        // El resto de los comandos sigue pidiendo AUTH
+        client
+            .write_all(b"*2\r\n$3\r\nGET\r\n$5\r\nGenji\r\n")
+            .unwrap();
+        let response = output_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert!(response.is_error());
+        assert!(instruction_rx.try_recv().is_err());
+    }
+
+    /// Lanza un ClientInput autenticado que cierra la conexión tras `limit`
+    /// errores de protocolo consecutivos.
+    fn spawn_client_input_with_limit(
+        limit: u32,
+    ) -> (
+        TcpStream,
+        mpsc::Receiver<(String, Instruction, Sender<RespMessage>)>,
+        mpsc::Receiver<RespMessage>,
+    ) {
+        let mut permissions = Permissions::new();
+        permissions.set_super();
+        spawn_client_input(limit, permissions)
+    }
+
+    /// Lanza un ClientInput autenticado con los permisos dados.
+    fn spawn_client_input(
+        limit: u32,
+        permissions: Permissions,
+    ) -> (
+        TcpStream,
+        mpsc::Receiver<(String, Instruction, Sender<RespMessage>)>,
+        mpsc::Receiver<RespMessage>,
+    ) {
+        let (mut client, server_socket) = setup_listener_and_client(0);
+        let peer_addr = server_socket.peer_addr().unwrap();
+        let (instruction_tx, instruction_rx) = mpsc::channel();
+        let (output_tx, output_rx) = mpsc::channel();
+
+        let settings = NodeConfigs::new("./tests/utils/test_c_i_1.conf").unwrap();
+        let logger = AofLogger::new(settings);
+
+        let mut user_base = UserBase::new();
+        user_base.add_user(User::new(
+            "user".to_string(),
+            "pass".to_string(),
+            permissions,
+        ));
+
+        thread::spawn(move || {
+            ClientInput::new(
+                "AA000".to_string(),
+                instruction_tx,
+                Box::new(server_socket),
+                output_tx,
+                logger,
+                Arc::new(user_base),
+            )
+            .with_protocol_error_limit(limit, peer_addr)
+            .run();
+        });
+        client
+            .write_all(b"*3\r\n$4\r\nAUTH\r\n$4\r\nuser\r\n$4\r\npass\r\n")
+            .unwrap();
+        output_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        (client, instruction_rx, output_rx)
+    }
+
+    #[test]
+    fn test_client_input_records_authenticated_user() {
+        let (mut client, server_socket) = setup_listener_and_client(0);
+        let (instruction_tx, _instruction_rx) = mpsc::channel();
+        let (output_tx, output_rx) = mpsc::channel();
+        let settings = NodeConfigs::new("./tests/utils/test_c_i_1.conf").unwrap();
+        let logger = AofLogger::new(settings);
+
+        let mut permissions = Permissions::new();
+        permissions.set_super();
+        let mut user_base = UserBase::new();
+        user_base.add_user(User::new(
+            "Illari".to_string(),
+            "pass".to_string(),
+            permissions,
+        ));
+        let clients: ClientRegistry = Arc::default();
+        clients
+            .write()
+            .unwrap()
+            .insert("AA000".to_string(), Default::default());
+
+        let registry = clients.clone();
+        thread::spawn(move || {
+            ClientInput::new(
+                "AA000".to_string(),
+                instruction_tx,
+                Box::new(server_socket),
+                output_tx,
+                logger,
+                Arc::new(user_base),
+            )
+            .with_client_registry(registry)
+            .run();
+        });
+        client
+            .write_all(b"*3\r\n$4\r\nAUTH\r\n$6\r\nIllari\r\n$4\r\npass\r\n")
+            .unwrap();
+        output_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+
+        let user = clients.read().unwrap()["AA000"].user.clone();
+        assert_eq!(user, Some("Illari".to_string()));
+    }
+
+    #[test]
+    fn test_client_input_closes_after_consecutive_protocol_errors() {
+        let (mut client, _instruction_rx, output_rx) = spawn_client_input_with_limit(3);
+
+        // Un simple string y un comando inline con comillas sin cerrar no
+        // son pedidos válidos
+        client
+            .write_all(b"+Tracer\r\n\"Genji\r\n+Mercy\r\n")
+            .unwrap();
+
+        for _ in 0..2 {
+            let response = output_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+            assert!(response.is_error());
+        }
+        assert_eq!(
+            output_rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+            RespMessage::Error(ERR_TOO_MANY_PROTOCOL_ERRORS.to_string())
+        );
+        assert_eq!(
+            output_rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+            RespMessage::Disconnect
+        );
+    }
+
+    #[test]
+    fn test_client_input_valid_request_resets_protocol_errors() {
+        let (mut client, instruction_rx, output_rx) = spawn_client_input_with_limit(3);
+
+        client.write_all(b"+Tracer\r\n+Genji\r\n").unwrap();
+        client.write_all(b"*1\r\n$4\r\nPING\r\n").unwrap();
+        client.write_all(b"+Mercy\r\n+Reinhardt\r\n").unwrap();
+        client.write_all(b"*1\r\n$4\r\nPING\r\n").unwrap();
+
+        for _ in 0..2 {
+            let (_, instruction, _) = instruction_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+            assert_eq!(instruction.instruction_type, "PING");
+        }
+        for _ in 0..4 {
+            let response = output_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+            assert!(response.is_error());
+            assert_ne!(
+                response,
+                RespMessage::Error(ERR_TOO_MANY_PROTOCOL_ERRORS.to_string())
+            );
+        }
+        assert!(output_rx.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn test_client_input_rejects_keys_outside_acl_patterns() {
+        let mut permissions = Permissions::new();
+        permissions.set_super();
+        permissions.add_key_pattern("cache:*".to_string());
+        let (mut client, instruction_rx, output_rx) = spawn_client_input(3, permissions);
+
+        client
+            .write_all(b"*2\r\n$3\r\nGET\r\n$13\r\ncache:Sojourn\r\n")
+            .unwrap();
+        let (_, instruction, _) = instruction_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(instruction.arguments, vec!["cache:Sojourn"]);
+
+        client
+            .write_all(b"*2\r\n$3\r\nGET\r\n$6\r\nJunker\r\n")
+            .unwrap();
+        client
+            .write_all(b"*3\r\n$4\r\nMGET\r\n$13\r\ncache:Sojourn\r\n$6\r\nJunker\r\n")
+            .unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                output_rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+                RespMessage::Error(ERR_NOPERM_KEY.to_string())
+            );
+        }
+        assert!(
+            instruction_rx
+                .recv_timeout(Duration::from_millis(200))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_client_input_checks_key_patterns_against_acl() {
+        let mut permissions = Permissions::new();
+        permissions.set_super();
+        permissions.add_key_pattern("cache:*".to_string());
+        let (mut client, instruction_rx, output_rx) = spawn_client_input(3, permissions);
+
+        // Patrones propios del usuario y claves literales accesibles
+        client
+            .write_all(b"*2\r\n$4\r\nKEYS\r\n$7\r\ncache:*\r\n")
+            .unwrap();
+        client
+            .write_all(b"*4\r\n$4\r\nSCAN\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$7\r\ncache:*\r\n")
+            .unwrap();
+        client
+            .write_all(b"*4\r\n$4\r\nSORT\r\n$10\r\ncache:Team\r\n$3\r\nGET\r\n$1\r\n#\r\n")
+            .unwrap();
+        for command in ["KEYS", "SCAN", "SORT"] {
+            let (_, instruction, _) = instruction_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+            assert_eq!(instruction.instruction_type, command);
+        }
+
+        // Patrones que alcanzan claves fuera de la ACL
+        client
+            .write_all(b"*2\r\n$4\r\nKEYS\r\n$1\r\n*\r\n")
+            .unwrap();
+        client
+            .write_all(b"*2\r\n$4\r\nSCAN\r\n$1\r\n0\r\n")
+            .unwrap();
+        client
+            .write_all(b"*4\r\n$4\r\nSORT\r\n$10\r\ncache:Team\r\n$2\r\nBY\r\n$8\r\nweight_*\r\n")
+            .unwrap();
+        client
+            .write_all(b"*4\r\n$4\r\nSORT\r\n$10\r\ncache:Team\r\n$3\r\nGET\r\n$6\r\nname_*\r\n")
+            .unwrap();
+        for _ in 0..4 {
+            assert_eq!(
+                output_rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+                RespMessage::Error(ERR_NOPERM_KEY.to_string())
+            );
+        }
+        assert!(
+            instruction_rx
+                .recv_timeout(Duration::from_millis(200))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_client_input_rejects_commands_outside_acl() {
+        let mut permissions = Permissions::new();
+        permissions.add_instruction("GET".to_string());
+        let (mut client, instruction_rx, output_rx) = spawn_client_input(3, permissions);
+
+        client
+            .write_all(b"*2\r\n$3\r\nGET\r\n$7\r\nBastion\r\n")
+            .unwrap();
+        let (_, instruction, _) = instruction_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(instruction.instruction_type, "GET");
+
+        // El nombre del comando no distingue mayúsculas
+        client
+            .write_all(b"*2\r\n$3\r\nget\r\n$7\r\nBastion\r\n")
+            .unwrap();
+        let (_, instruction, _) = instruction_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(instruction.instruction_type, "get");
+
+        client
+            .write_all(b"*3\r\n$3\r\nSET\r\n$7\r\nBastion\r\n$8\r\nGanymede\r\n")
+            .unwrap();
+        assert_eq!(
+            output_rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+            RespMessage::Error(err_noperm_command("SET"))
+        );
+        assert!(
+            instruction_rx
+                .recv_timeout(Duration::from_millis(200))
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_client_input_disconnect() {
         use std::time::Duration;
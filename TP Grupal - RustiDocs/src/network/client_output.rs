@@ -8,15 +8,41 @@
 //! - Manejo de desconexiones
 //! - Comunicación asíncrona con canales
 //! - Manejo robusto de errores de I/O
+//! - Límites de buffer de salida por clase de cliente
 
+use super::connection_handler::ClientRegistry;
+use super::output_buffer::{ClientClass, LimitExceeded, OutputBufferLimits, OutputBufferMonitor};
 use super::resp_message::*;
+use crate::logs::aof_logger::AofLogger;
+use std::collections::VecDeque;
 use std::fmt;
 use std::io::{Error as IoError, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::Arc;
 use std::sync::mpsc::{Receiver, SendError, Sender};
+use std::time::Instant;
 
 // Trait para streams que pueden escribir
-pub trait ClientOutputStream: Write {}
-impl<T: Write> ClientOutputStream for T {}
+pub trait ClientOutputStream: Write {
+    /// Cierra la conexión en ambos sentidos, para que también termine
+    /// la lectura del cliente.
+    fn close(&mut self) -> std::io::Result<()>;
+}
+
+impl ClientOutputStream for TcpStream {
+    fn close(&mut self) -> std::io::Result<()> {
+        self.shutdown(Shutdown::Both)
+    }
+}
+
+/// Límites de buffer de salida y el estado necesario para aplicarlos.
+struct BufferLimits {
+    /// Límites de cada clase de cliente
+    limits: OutputBufferLimits,
+    /// Logger para registrar las desconexiones
+    logger: Arc<AofLogger>,
+    monitor: OutputBufferMonitor,
+}
 
 /// Error que puede ocurrir durante el procesamiento de salida del cliente.
 #[derive(Debug, Clone, PartialEq)]
@@ -68,7 +94,14 @@ pub struct ClientOutput {
     responses: Receiver<RespMessage>,
     /// Canal para enviar señales de desconexión
     disconnect_sender: Sender<String>,
-    message_queue: Vec<RespMessage>,
+    /// Respuestas recibidas pendientes de envío, con su tamaño en bytes
+    pending: VecDeque<(RespMessage, usize)>,
+    /// Total de bytes pendientes de envío
+    pending_bytes: usize,
+    /// Registro de conexiones, del que se obtienen la clase y el protocolo
+    /// del cliente
+    clients: Option<ClientRegistry>,
+    buffer_limits: Option<BufferLimits>,
 }
 
 impl ClientOutput {
@@ -95,75 +128,240 @@ impl ClientOutput {
             client_socket,
             responses,
             disconnect_sender,
-            message_queue: Vec::new(),
+            pending: VecDeque::new(),
+            pending_bytes: 0,
+            clients: None,
+            buffer_limits: None,
         }
     }
 
+    /// Aplica los límites de buffer de salida de la clase del cliente,
+    /// que se consulta en el registro de conexiones. Del mismo registro
+    /// se toma el protocolo con el que se envían los push.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - Límites de cada clase de cliente
+    /// * `clients` - Registro de estado por conexión
+    /// * `logger` - Logger para registrar las desconexiones
+    pub fn with_buffer_limits(
+        mut self,
+        limits: OutputBufferLimits,
+        clients: ClientRegistry,
+        logger: Arc<AofLogger>,
+    ) -> Self {
+        self.clients = Some(clients);
+        self.buffer_limits = Some(BufferLimits {
+            limits,
+            logger,
+            monitor: OutputBufferMonitor::new(),
+        });
+        self
+    }
+
     /// Ejecuta el bucle principal de envío de respuestas.
     ///
     /// Este método procesa mensajes desde el canal de respuestas y los envía
     /// al cliente. Cuando recibe un mensaje de desconexión, envía la señal
     /// correspondiente y termina la ejecución.
     ///
+    /// Antes de cada envío se incorporan las respuestas que ya esperan en el
+    /// canal: si lo pendiente supera los límites del buffer de salida, se
+    /// cierra la conexión. Las respuestas pendientes se envían juntas, de
+    /// modo que los comandos de un pipeline se responden en una escritura.
+    ///
     /// # Returns
     ///
     /// `Result<(), ClientOutputError>` - Resultado de la ejecución
     pub fn run(&mut self) -> Result<(), ClientOutputError> {
         while let Ok(response) = self.responses.recv() {
-            match response {
-                RespMessage::Disconnect => {
-                    self.handle_disconnect()?;
+            self.enqueue(response);
+            loop {
+                while let Ok(response) = self.responses.try_recv() {
+                    self.enqueue(response);
+                }
+                if let Some((class, reason)) = self.exceeded_limit() {
+                    return self.close_for_limit(class, reason);
+                }
+                if self.pending.is_empty() {
                     break;
                 }
-                _ => {
-                    self.send_response(&response)?;
+                if self.send_pending()? {
+                    return Ok(());
                 }
             }
         }
         Ok(())
     }
 
+    /// Agrega una respuesta a las pendientes de envío, ya convertida al
+    /// protocolo del cliente.
+    fn enqueue(&mut self, response: RespMessage) {
+        let response = response.for_protocol(self.protocol());
+        let size = match response {
+            RespMessage::Disconnect => 0,
+            _ => response.as_bytes().len(),
+        };
+        self.pending_bytes += size;
+        self.pending.push_back((response, size));
+    }
+
+    /// Protocolo negociado por el cliente, RESP2 si no figura en el registro.
+    fn protocol(&self) -> RespProtocol {
+        self.clients
+            .as_ref()
+            .and_then(|clients| {
+                clients
+                    .read()
+                    .ok()?
+                    .get(&self.client_id)
+                    .map(|flags| flags.protocol)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Verifica los bytes pendientes contra los límites de la clase del
+    /// cliente. Las conexiones con NO-EVICT quedan excluidas.
+    ///
+    /// # Returns
+    ///
+    /// La clase del cliente y el límite superado, si hubo alguno
+    fn exceeded_limit(&mut self) -> Option<(ClientClass, LimitExceeded)> {
+        let (no_evict, class) = self
+            .clients
+            .as_ref()?
+            .read()
+            .ok()?
+            .get(&self.client_id)
+            .map(|flags| (flags.no_evict, flags.class))
+            .unwrap_or_default();
+        if no_evict {
+            return None;
+        }
+        let buffer_limits = self.buffer_limits.as_mut()?;
+        let limit = buffer_limits.limits.get(class);
+        buffer_limits
+            .monitor
+            .check_at(&limit, self.pending_bytes, Instant::now())
+            .map(|reason| (class, reason))
+    }
+
+    /// Cierra la conexión de un cliente que superó los límites del buffer
+    /// de salida, descartando lo pendiente.
+    ///
+    /// # Returns
+    ///
+    /// `Result<(), ClientOutputError>` - Resultado de la operación
+    fn close_for_limit(
+        &mut self,
+        class: ClientClass,
+        reason: LimitExceeded,
+    ) -> Result<(), ClientOutputError> {
+        if let Some(buffer_limits) = &self.buffer_limits {
+            buffer_limits.logger.log_warning(format!(
+                "Client {} ({}) closed for overcoming of output buffer limits: {}",
+                self.client_id, class, reason
+            ));
+        }
+        self.pending.clear();
+        self.pending_bytes = 0;
+        let _ = self.client_socket.close();
+        self.disconnect_sender.send(self.client_id.clone())?;
+        Ok(())
+    }
+
+    /// Indica si otra conexión cerró a este cliente con `CLIENT KILL`.
+    fn was_killed(&self) -> bool {
+        self.clients
+            .as_ref()
+            .and_then(|clients| {
+                clients
+                    .read()
+                    .ok()?
+                    .get(&self.client_id)
+                    .map(|flags| flags.killed)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Cierra la conexión de un cliente terminado con `CLIENT KILL`. No
+    /// se le responde: se cierra el socket, lo que también termina el
+    /// hilo de entrada, y se notifica la desconexión.
+    ///
+    /// # Returns
+    ///
+    /// `Result<(), ClientOutputError>` - Resultado de la operación
+    fn close_killed(&mut self) -> Result<(), ClientOutputError> {
+        if let Some(buffer_limits) = &self.buffer_limits {
+            buffer_limits
+                .logger
+                .log_event(format!("Client {} killed", self.client_id));
+        }
+        self.pending.clear();
+        self.pending_bytes = 0;
+        let _ = self.client_socket.close();
+        self.disconnect_sender.send(self.client_id.clone())?;
+        Ok(())
+    }
+
     /// Maneja la desconexión del cliente.
     ///
     /// Envía un mensaje de confirmación al cliente y notifica
-    /// al sistema sobre la desconexión.
+    /// al sistema sobre la desconexión. Si el cliente fue terminado con
+    /// `CLIENT KILL`, en cambio, se cierra la conexión sin responder.
     ///
     /// # Returns
     ///
     /// `Result<(), ClientOutputError>` - Resultado de la operación
     fn handle_disconnect(&mut self) -> Result<(), ClientOutputError> {
+        if self.was_killed() {
+            return self.close_killed();
+        }
         /*let res = format!("+{}\r\n", self.client_id);
         let _ = self.client_socket.write_all(res.as_bytes());
         let _ = self.disconnect_sender.send(SupervisorInstruction::Terminate(self.client_id.to_string()));*/
         let client_id = self.client_id.clone();
         let sender = self.disconnect_sender.clone();
         let disconnect_msg = b"+Desconectado con exito\r\n";
-        self.client_socket.write_all(disconnect_msg)?;
-        self.client_socket.flush()?;
+        // Si el cliente ya cerró el socket la escritura falla, pero la
+        // desconexión se notifica igual
+        let written = self
+            .client_socket
+            .write_all(disconnect_msg)
+            .and_then(|_| self.client_socket.flush());
         sender.send(client_id)?;
+        written?;
         Ok(())
     }
 
-    /// Envía una respuesta al cliente.
-    ///
-    /// # Arguments
-    ///
-    /// * `response` - Mensaje RESP a enviar
+    /// Envía al cliente las respuestas pendientes, en orden y con una única
+    /// escritura. Si entre ellas hay una desconexión, se envía lo anterior
+    /// a ella y se maneja la desconexión.
     ///
     /// # Returns
     ///
-    /// `Result<(), ClientOutputError>` - Resultado de la operación
-    fn send_response(&mut self, response: &RespMessage) -> Result<(), ClientOutputError> {
-        println!("Sending response: {:?}", response);
-        self.message_queue.push(response.clone());
+    /// `Result<bool, ClientOutputError>` - `true` si se desconectó al cliente
+    fn send_pending(&mut self) -> Result<bool, ClientOutputError> {
+        let mut bytes = Vec::with_capacity(self.pending_bytes);
+        let mut disconnect = false;
+        while let Some((response, size)) = self.pending.pop_front() {
+            self.pending_bytes -= size;
+            if response == RespMessage::Disconnect {
+                disconnect = true;
+                break;
+            }
+            println!("Sending response: {:?}", response);
+            bytes.extend(response.as_bytes());
+        }
 
-        while let Some(msg) = self.message_queue.pop() {
-            let bytes = msg.as_bytes();
+        if !bytes.is_empty() {
             self.client_socket.write_all(&bytes)?;
             self.client_socket.flush()?;
         }
-
-        Ok(())
+        if disconnect {
+            self.handle_disconnect()?;
+        }
+        Ok(disconnect)
     }
 
     /// Obtiene el ID del cliente.
@@ -189,12 +387,227 @@ impl ClientOutput {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::node_configs::NodeConfigs;
+    use crate::network::connection_handler::ClientFlags;
+    use crate::network::output_buffer::OutputBufferLimit;
+    use std::collections::HashMap;
     use std::io::Read;
     use std::net::{TcpListener, TcpStream};
-    use std::sync::mpsc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{RwLock, mpsc};
     use std::thread;
     use std::time::Duration;
 
+    /// Stream de un cliente que consume su salida lentamente.
+    struct SlowStream {
+        written: Arc<RwLock<Vec<u8>>>,
+        closed: Arc<AtomicBool>,
+    }
+
+    impl Write for SlowStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            thread::sleep(Duration::from_millis(5));
+            self.written.write().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl ClientOutputStream for SlowStream {
+        fn close(&mut self) -> std::io::Result<()> {
+            self.closed.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    /// Corre un `ClientOutput` de un suscriptor lento con los límites de
+    /// pubsub dados, luego de encolarle `messages` publicaciones.
+    ///
+    /// # Returns
+    ///
+    /// Tupla con (bytes escritos, si se cerró la conexión, id desconectado)
+    fn run_slow_subscriber(
+        limit: OutputBufferLimit,
+        messages: usize,
+    ) -> (usize, bool, Option<String>) {
+        let written = Arc::new(RwLock::new(Vec::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let stream = SlowStream {
+            written: written.clone(),
+            closed: closed.clone(),
+        };
+        let (tx, rx) = mpsc::channel();
+        let (disconnect_tx, disconnect_rx) = mpsc::channel();
+
+        let mut limits = OutputBufferLimits::default();
+        limits.set(ClientClass::Pubsub, limit);
+        let flags = ClientFlags {
+            class: ClientClass::Pubsub,
+            ..ClientFlags::default()
+        };
+        let clients = Arc::new(RwLock::new(HashMap::from([("SUB001".to_string(), flags)])));
+        let settings = NodeConfigs::new("./tests/utils/redis.conf").unwrap();
+
+        for _ in 0..messages {
+            tx.send(RespMessage::BulkString(Some(
+                b"Lucio dropped the beat".to_vec(),
+            )))
+            .unwrap();
+        }
+        drop(tx);
+
+        let mut client_output =
+            ClientOutput::new("SUB001".to_string(), Box::new(stream), rx, disconnect_tx)
+                .with_buffer_limits(limits, clients, AofLogger::new(settings));
+        client_output.run().unwrap();
+
+        let written = written.read().unwrap().len();
+        (
+            written,
+            closed.load(Ordering::SeqCst),
+            disconnect_rx.try_recv().ok(),
+        )
+    }
+
+    #[test]
+    fn test_slow_subscriber_over_hard_limit_is_disconnected() {
+        let (written, closed, disconnected) =
+            run_slow_subscriber(OutputBufferLimit::new(512, 0, 0), 100);
+
+        assert_eq!(written, 0);
+        assert!(closed);
+        assert_eq!(disconnected, Some("SUB001".to_string()));
+    }
+
+    #[test]
+    fn test_subscriber_briefly_over_soft_limit_is_kept() {
+        let (written, closed, disconnected) =
+            run_slow_subscriber(OutputBufferLimit::new(0, 512, 60), 100);
+
+        // Cada publicación ocupa 29 bytes y se envían todas
+        assert_eq!(written, 100 * 29);
+        assert!(!closed);
+        assert_eq!(disconnected, None);
+    }
+
+    #[test]
+    fn test_killed_client_is_closed_without_reply() {
+        let written = Arc::new(RwLock::new(Vec::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let stream = SlowStream {
+            written: written.clone(),
+            closed: closed.clone(),
+        };
+        let (tx, rx) = mpsc::channel();
+        let (disconnect_tx, disconnect_rx) = mpsc::channel();
+        let flags = ClientFlags {
+            killed: true,
+            ..ClientFlags::default()
+        };
+        let clients = Arc::new(RwLock::new(HashMap::from([("AAA001".to_string(), flags)])));
+        let settings = NodeConfigs::new("./tests/utils/redis.conf").unwrap();
+
+        tx.send(RespMessage::Disconnect).unwrap();
+        drop(tx);
+
+        let mut client_output =
+            ClientOutput::new("AAA001".to_string(), Box::new(stream), rx, disconnect_tx)
+                .with_buffer_limits(
+                    OutputBufferLimits::default(),
+                    clients,
+                    AofLogger::new(settings),
+                );
+        client_output.run().unwrap();
+
+        assert!(written.read().unwrap().is_empty());
+        assert!(closed.load(Ordering::SeqCst));
+        assert_eq!(disconnect_rx.try_recv().ok(), Some("AAA001".to_string()));
+    }
+
+    /// Corre un `ClientOutput` de un suscriptor que negoció `protocol` y
+    /// le entrega una publicación.
+    ///
+    /// # Returns
+    ///
+    /// Los bytes escritos en la conexión
+    fn deliver_to_subscriber(protocol: RespProtocol) -> Vec<u8> {
+        deliver(
+            protocol,
+            ClientClass::Pubsub,
+            RespMessage::pubsub_message(
+                "numbani",
+                RespMessage::SimpleString("Doomfist escaped".to_string()),
+            ),
+        )
+    }
+
+    /// Corre un `ClientOutput` de un cliente de la clase dada que negoció
+    /// `protocol` y le entrega `response`.
+    ///
+    /// # Returns
+    ///
+    /// Los bytes escritos en la conexión
+    fn deliver(protocol: RespProtocol, class: ClientClass, response: RespMessage) -> Vec<u8> {
+        let written = Arc::new(RwLock::new(Vec::new()));
+        let stream = SlowStream {
+            written: written.clone(),
+            closed: Arc::new(AtomicBool::new(false)),
+        };
+        let (tx, rx) = mpsc::channel();
+        let (disconnect_tx, _) = mpsc::channel();
+        let flags = ClientFlags {
+            class,
+            protocol,
+            ..ClientFlags::default()
+        };
+        let clients = Arc::new(RwLock::new(HashMap::from([("SUB001".to_string(), flags)])));
+        let settings = NodeConfigs::new("./tests/utils/redis.conf").unwrap();
+
+        tx.send(response).unwrap();
+        drop(tx);
+
+        let mut client_output =
+            ClientOutput::new("SUB001".to_string(), Box::new(stream), rx, disconnect_tx)
+                .with_buffer_limits(
+                    OutputBufferLimits::default(),
+                    clients,
+                    AofLogger::new(settings),
+                );
+        client_output.run().unwrap();
+        written.read().unwrap().clone()
+    }
+
+    #[test]
+    fn test_resp3_subscriber_receives_push() {
+        let written = deliver_to_subscriber(RespProtocol::Resp3);
+        assert_eq!(
+            written,
+            b">3\r\n$7\r\nmessage\r\n$7\r\nnumbani\r\n+Doomfist escaped\r\n"
+        );
+    }
+
+    #[test]
+    fn test_resp2_subscriber_receives_published_message() {
+        let written = deliver_to_subscriber(RespProtocol::Resp2);
+        assert_eq!(written, b"+Doomfist escaped\r\n");
+    }
+
+    #[test]
+    fn test_help_is_verbatim_on_resp3_and_bulk_string_on_resp2() {
+        let help = RespMessage::verbatim_text("HELP\n    Print this help.");
+        assert_eq!(
+            deliver(RespProtocol::Resp3, ClientClass::Normal, help.clone()),
+            b"=29\r\ntxt:HELP\n    Print this help.\r\n"
+        );
+        assert_eq!(
+            deliver(RespProtocol::Resp2, ClientClass::Normal, help),
+            b"$25\r\nHELP\n    Print this help.\r\n"
+        );
+    }
+
     /// Configura un listener TCP y un cliente conectado para testing.
     ///
     /// # Returns
@@ -341,8 +754,8 @@ mod tests {
     }
 
     #[test]
-    fn test_client_output_send_response() {
-        let (_, server) = setup_listener_and_client().unwrap();
+    fn test_client_output_send_pending() {
+        let (mut client, server) = setup_listener_and_client().unwrap();
         let (_tx, rx) = mpsc::channel();
         let (disconnect_tx, _) = mpsc::channel();
 
@@ -353,9 +766,17 @@ mod tests {
             disconnect_tx,
         );
 
-        let response = RespMessage::SimpleString("test".to_string());
-        let result = client_output.send_response(&response);
-        assert!(result.is_ok());
+        client_output.enqueue(RespMessage::SimpleString("OK".to_string()));
+        client_output.enqueue(RespMessage::BulkString(Some(b"Zarya".to_vec())));
+        client_output.enqueue(RespMessage::Integer(2));
+        assert!(!client_output.send_pending().unwrap());
+        assert!(client_output.pending.is_empty());
+        assert_eq!(client_output.pending_bytes, 0);
+
+        let expected = b"+OK\r\n$5\r\nZarya\r\n:2\r\n";
+        let mut written = vec![0; expected.len()];
+        client.read_exact(&mut written).unwrap();
+        assert_eq!(written, expected);
     }
 
     #[test]
@@ -10,17 +10,25 @@
 //! - Comunicación asíncrona con el ejecutor de comandos
 
 use std::{
+    collections::{HashMap, HashSet},
     fmt,
     io::{Read, Write},
-    net::{TcpListener, TcpStream},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
     sync::{
-        Arc, Mutex,
+        Arc, Mutex, RwLock,
         mpsc::{Receiver, Sender, channel},
     },
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
-use super::{client_input::ClientInput, client_output::ClientOutput};
+use super::{
+    client_input::ClientInput,
+    client_output::{ClientOutput, ClientOutputStream},
+    connection_supervisor::{AcceptDecision, AcceptRateLimiter, ClientLimiter, ERR_MAX_CLIENTS},
+    output_buffer::ClientClass,
+    resp_message::RespProtocol,
+};
 
 use crate::{
     command::Instruction,
@@ -65,20 +73,121 @@ impl Write for ClientStream {
     }
 }
 
+impl ClientOutputStream for ClientStream {
+    fn close(&mut self) -> std::io::Result<()> {
+        match self {
+            ClientStream::Tcp(stream) => stream.close(),
+            ClientStream::Tls(stream) => stream.shutdown(Shutdown::Both),
+        }
+    }
+}
+
 impl ClientStream {
     fn try_clone(&self) -> std::io::Result<ClientStream> {
         match self {
-            // TODO: Posible problema a lvl cifrado cliente-user
             ClientStream::Tcp(stream) => stream.try_clone().map(ClientStream::Tcp),
-            ClientStream::Tls(_) => {
-                // TlsServerStream no implementa try_clone, así que devolvemos un error
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::Unsupported,
-                    "TLS streams cannot be cloned",
-                ))
-            }
+            ClientStream::Tls(stream) => stream.try_clone().map(ClientStream::Tls),
+        }
+    }
+}
+
+/// Flags de una conexión, modificables por el propio cliente
+/// mediante `CLIENT`, junto con los datos de la conexión que
+/// informa `CLIENT LIST`.
+#[derive(Debug, Clone, Default)]
+pub struct ClientFlags {
+    /// Las lecturas de la conexión no actualizan la recencia de las claves
+    pub no_touch: bool,
+    /// La conexión no es candidata al desalojo por buffers de salida
+    pub no_evict: bool,
+    /// Clase del cliente, define sus límites de buffer de salida
+    pub class: ClientClass,
+    /// Protocolo negociado con `HELLO`, define cómo se envían los push
+    pub protocol: RespProtocol,
+    /// Canales a los que está suscripta la conexión
+    pub channels: HashSet<String>,
+    /// Patrones a los que está suscripta la conexión
+    pub patterns: HashSet<String>,
+    /// Nombre asignado con `CLIENT SETNAME`
+    pub name: Option<String>,
+    /// Usuario ACL con el que se autenticó la conexión
+    pub user: Option<String>,
+    /// Dirección del cliente
+    pub addr: Option<SocketAddr>,
+    /// Momento en que se aceptó la conexión
+    pub connected_at: Option<Instant>,
+    /// Canal de respuestas de la conexión, por el que `CLIENT KILL`
+    /// le pide que se cierre
+    pub output: Option<Sender<RespMessage>>,
+    /// La conexión fue cerrada con `CLIENT KILL`
+    pub killed: bool,
+    /// Offset de replicación de la última escritura de la conexión, el
+    /// que espera `WAIT`
+    pub last_write_offset: u64,
+    /// Transacción abierta con `MULTI`, si la hay
+    pub transaction: Option<Transaction>,
+}
+
+/// Transacción abierta con `MULTI` en una conexión.
+#[derive(Debug, Clone, Default)]
+pub struct Transaction {
+    /// Instrucciones encoladas, en orden, a ejecutar con `EXEC`
+    pub queued: Vec<Instruction>,
+    /// Alguna instrucción no se pudo encolar: `EXEC` descarta la transacción
+    pub aborted: bool,
+}
+
+/// Registro compartido del estado de cada conexión activa, indexado por id de cliente.
+pub type ClientRegistry = Arc<RwLock<HashMap<String, ClientFlags>>>;
+
+/// Conexiones activas (id, input_handle, output_handle), compartidas con
+/// el hilo que atiende las desconexiones.
+type Connections = Arc<Mutex<Vec<(String, JoinHandle<()>, JoinHandle<()>)>>>;
+
+/// Tiempo máximo para completar el handshake de TLS-lite.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Respuesta final a una conexión cerrada por errores de protocolo.
+pub const ERR_TOO_MANY_PROTOCOL_ERRORS: &str =
+    "ERR Protocol error: too many consecutive invalid requests, closing connection";
+
+/// Conteo de los errores de protocolo consecutivos de una conexión.
+///
+/// Un pedido válido reinicia el conteo, de modo que sólo se cierran las
+/// conexiones que no envían más que basura. Con límite 0 no se cierran.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolErrorCounter {
+    limit: u32,
+    consecutive: u32,
+}
+
+impl ProtocolErrorCounter {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            consecutive: 0,
         }
     }
+
+    /// Registra un pedido inválido.
+    ///
+    /// # Returns
+    ///
+    /// `true` si se alcanzó el límite y la conexión debe cerrarse
+    pub fn record_error(&mut self) -> bool {
+        self.consecutive += 1;
+        self.limit > 0 && self.consecutive >= self.limit
+    }
+
+    /// Registra un pedido válido, reiniciando el conteo.
+    pub fn record_valid(&mut self) {
+        self.consecutive = 0;
+    }
+
+    /// Errores consecutivos registrados hasta el momento.
+    pub fn consecutive(&self) -> u32 {
+        self.consecutive
+    }
 }
 
 /// Error que puede ocurrir durante el manejo de conexiones.
@@ -136,18 +245,25 @@ pub struct Handler {
     /// Id del próximo cliente a conectar
     next_id: String,
     /// Lista de conexiones activas (id, input_handle, output_handle)
-    connections: Vec<(String, JoinHandle<()>, JoinHandle<()>)>,
+    connections: Connections,
     /// Canal para enviar instrucciones al ejecutor de comandos
     instruction_sender: Sender<(String, Instruction, Sender<RespMessage>)>,
     /// Canal para enviar señales de desconexión
     disconnect_sender: Sender<String>,
-    /// Canal para recibir señales de desconexión
-    disconnect_receiver: Receiver<String>,
+    /// Canal para recibir señales de desconexión, hasta que `init` lo
+    /// entrega al hilo que las atiende
+    disconnect_receiver: Option<Receiver<String>>,
     /// Configuración del nodo
     configs: NodeConfigs,
     /// Logger para eventos del servidor
     logger: Arc<AofLogger>,
     user_base: Arc<UserBase>,
+    /// Estado por conexión compartido con el ejecutor de comandos
+    clients: ClientRegistry,
+    /// Limitador de la tasa de aceptación de conexiones
+    rate_limiter: AcceptRateLimiter,
+    /// Cupo de conexiones abiertas a la vez (`maxclients`)
+    client_limiter: ClientLimiter,
 }
 
 impl Handler {
@@ -158,6 +274,8 @@ impl Handler {
     /// * `instruction_sender` - Canal para enviar instrucciones al ejecutor
     /// * `configs` - Configuración del nodo
     /// * `logger` - Logger para eventos del servidor
+    /// * `user_base` - Usuarios habilitados
+    /// * `clients` - Registro de estado por conexión
     ///
     /// # Returns
     ///
@@ -167,8 +285,11 @@ impl Handler {
         configs: NodeConfigs,
         logger: Arc<AofLogger>,
         user_base: UserBase,
+        clients: ClientRegistry,
     ) -> Self {
         let (disconnect_sender, disconnect_receiver) = channel();
+        let rate_limiter = AcceptRateLimiter::new(configs.get_accept_rate_limit());
+        let client_limiter = ClientLimiter::new(configs.get_clients_limit());
         /*let mut supervisor = Supervisor::new(disconnect_receiver);
         let supervisor_thread = thread::spawn( move ||{
             supervisor.init();
@@ -176,13 +297,16 @@ impl Handler {
 
         Self {
             next_id: "AAA000".to_string(),
-            connections: Vec::new(),
+            connections: Arc::new(Mutex::new(Vec::new())),
             instruction_sender,
             disconnect_sender,
-            disconnect_receiver,
+            disconnect_receiver: Some(disconnect_receiver),
             configs,
             logger,
             user_base: Arc::new(user_base),
+            clients,
+            rate_limiter,
+            client_limiter,
         }
     }
 
@@ -190,37 +314,26 @@ impl Handler {
     ///
     /// Este mét-odo inicia el listener TCP y comienza a aceptar conexiones
     /// de clientes. También inicia un hilo para manejar las desconexiones.
-    pub fn init(self) -> Result<(), ConnectionHandlerError> {
-        let handler_ref = Arc::new(Mutex::new(self));
-        let handler_clone = Arc::clone(&handler_ref);
-
-        // Hilo que escucha desconexiones
-        thread::spawn(move || {
-            loop {
-                let client_id_opt = {
-                    let handler = handler_clone
-                        .lock()
-                        .map_err(|e| ConnectionHandlerError::LockError(e.to_string()))?;
-                    handler.disconnect_receiver.recv().ok()
-                };
-
-                if let Some(client_id) = client_id_opt {
-                    let mut handler = handler_clone
-                        .lock()
-                        .map_err(|e| ConnectionHandlerError::LockError(e.to_string()))?;
-                    handler.close_connection(client_id)?;
-                } else {
-                    break; // Se cerró el canal
+    pub fn init(mut self) -> Result<(), ConnectionHandlerError> {
+        // Hilo que escucha desconexiones. Sólo comparte con el hilo
+        // principal el registro y las conexiones, para no quedar bloqueado
+        // mientras éste espera nuevas conexiones.
+        if let Some(disconnect_receiver) = self.disconnect_receiver.take() {
+            let clients = self.clients.clone();
+            let connections = self.connections.clone();
+            let client_limiter = self.client_limiter.clone();
+            thread::spawn(move || {
+                for client_id in disconnect_receiver {
+                    if close_connection(&clients, &connections, client_id)? {
+                        client_limiter.release();
+                    }
                 }
-            }
-            Ok::<(), ConnectionHandlerError>(())
-        });
+                Ok::<(), ConnectionHandlerError>(())
+            });
+        }
 
         // Continuar la ejecución principal
-        let mut handler = handler_ref
-            .lock()
-            .map_err(|e| ConnectionHandlerError::LockError(e.to_string()))?;
-        handler.receive_connection()
+        self.receive_connection()
     }
 
     /// Inicializa la conexión de un nuevo cliente al servidor.
@@ -240,10 +353,35 @@ impl Handler {
             .log_notice(format!("Server listening on {}", self.configs.get_addr()));
 
         loop {
-            let (client_stream, socket_addr) = listener
+            let (mut tcp_stream, socket_addr) = listener
                 .accept()
                 .map_err(|e| ConnectionHandlerError::AcceptError(e.to_string()))?;
 
+            match self.rate_limiter.acquire() {
+                AcceptDecision::Accept => {}
+                AcceptDecision::Delay(wait) => thread::sleep(wait),
+                AcceptDecision::Reject => {
+                    self.logger.log_warning(format!(
+                        "Rejected {}:{}, accept rate limit exceeded",
+                        socket_addr.ip(),
+                        socket_addr.port(),
+                    ));
+                    let _ = tcp_stream.write_all(ERR_MAX_CLIENTS.as_bytes());
+                    continue;
+                }
+            }
+
+            if !self.client_limiter.try_acquire() {
+                self.logger.log_warning(format!(
+                    "Rejected {}:{}, max number of clients reached",
+                    socket_addr.ip(),
+                    socket_addr.port(),
+                ));
+                let _ = tcp_stream.write_all(ERR_MAX_CLIENTS.as_bytes());
+                let _ = tcp_stream.shutdown(Shutdown::Both);
+                continue;
+            }
+
             self.logger.log_event(format!(
                 "Accepted {}:{} connected, ID {}",
                 socket_addr.ip(),
@@ -251,202 +389,172 @@ impl Handler {
                 self.next_id,
             ));
 
-            self.handle_new_connection(client_stream)?;
+            self.handle_new_connection(tcp_stream, socket_addr)?;
         }
     }
 
     /// Maneja una nueva conexión de cliente.
     ///
-    /// Detecta automáticamente si la conexión es TLS o TCP normal.
-    /// Crea hilos separados para entrada y salida del cliente.
+    /// Crea hilos separados para entrada y salida del cliente. El de
+    /// entrada establece la conexión y le pasa al de salida su extremo; si
+    /// falla, el de salida notifica la desconexión y se libera el lugar.
     ///
     /// # Arguments
     ///
-    /// * `client_stream` - Stream TCP del cliente
+    /// * `tcp_stream` - Conexión recién aceptada
+    /// * `peer_addr` - Dirección del cliente
     ///
     /// # Returns
     ///
     /// `Result<(), ConnectionHandlerError>` - Resultado de la operación
     fn handle_new_connection(
         &mut self,
-        client_stream: TcpStream,
+        tcp_stream: TcpStream,
+        peer_addr: SocketAddr,
     ) -> Result<(), ConnectionHandlerError> {
-        // Detectar si la conexión es TLS o TCP normal
-        let client_stream = self.detect_and_establish_connection(client_stream)?;
-
         let (output_sender, output_receiver) = channel();
+        let (stream_sender, stream_receiver) = channel();
 
-        // Intentar clonar el stream para input y output
-        let client_stream_clone = match client_stream.try_clone() {
-            Ok(clone) => clone,
-            Err(_) => {
-                // Si no se puede clonar (TLS), usar el stream original solo para input
-                self.handle_tls_connection(client_stream, output_sender)?;
-                return Ok(());
-            }
-        };
-
-        let instruction_sender_clone = self.instruction_sender.clone();
         let client_id = self.next_id.clone();
-        let client_logger = self.logger.clone();
-        let clone_user_base = self.user_base.clone();
+        self.update_id();
 
-        let input = create_client_input_thread(
-            client_id,
-            instruction_sender_clone,
-            client_stream_clone,
+        // La lista queda tomada hasta registrar la conexión, para que una
+        // desconexión temprana no se atienda antes de poder encontrarla
+        let connections = self.connections.clone();
+        let mut connections = connections
+            .lock()
+            .map_err(|e| ConnectionHandlerError::LockError(e.to_string()))?;
+        self.register_client(&client_id, peer_addr, output_sender.clone())?;
+
+        let input = self.spawn_client_input(
+            client_id.clone(),
+            tcp_stream,
+            stream_sender,
             output_sender,
-            client_logger,
-            clone_user_base,
+            peer_addr,
         );
 
-        let client_stream_clone = client_stream
-            .try_clone()
-            .map_err(|e| ConnectionHandlerError::StreamCloneError(e.to_string()))?;
         let disconnect_sender_clone = self.disconnect_sender.clone();
-        let client_id = self.next_id.clone();
-        self.update_id();
+        let output_id = client_id.clone();
+        let buffer_limits = self.configs.get_output_buffer_limits();
+        let clients = self.clients.clone();
+        let output_logger = self.logger.clone();
 
         let output = thread::spawn(move || {
+            let Ok(client_stream) = stream_receiver.recv() else {
+                let _ = disconnect_sender_clone.send(output_id);
+                return;
+            };
             let mut client = ClientOutput::new(
-                client_id,
-                Box::new(client_stream_clone),
+                output_id,
+                Box::new(client_stream),
                 output_receiver,
                 disconnect_sender_clone,
-            );
+            )
+            .with_buffer_limits(buffer_limits, clients, output_logger);
             let _ = client.run();
         });
 
-        let client_id = self.next_id.clone();
-        self.connections.push((client_id, input, output));
+        connections.push((client_id, input, output));
         // TODO: Revisar si se queda self.disconnect_sender_si.send(SupervisorInstruction::Add(self.next_id.clone(), (input, output))).unwrap();
         Ok(())
     }
 
-    /// Detecta si la conexión entrante es TLS o TCP normal
-    fn detect_and_establish_connection(
+    /// Lanza el hilo que establece la conexión con un cliente y después
+    /// lee sus pedidos. El handshake corre en este hilo para no demorar la
+    /// aceptación de otras conexiones.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_id` - ID del cliente
+    /// * `tcp_stream` - Conexión recién aceptada
+    /// * `stream_sender` - Canal por el que se entrega el extremo de
+    ///   escritura al hilo de salida
+    /// * `output_sender` - Canal de respuestas de la conexión
+    /// * `peer_addr` - Dirección del cliente, para los logs
+    ///
+    /// # Returns
+    ///
+    /// Handle del hilo de entrada
+    fn spawn_client_input(
         &self,
-        mut tcp_stream: TcpStream,
-    ) -> Result<ClientStream, ConnectionHandlerError> {
-        // Configurar timeout para no bloquear indefinidamente
-        tcp_stream
-            .set_read_timeout(Some(std::time::Duration::from_millis(100)))
-            .map_err(|e| {
-                ConnectionHandlerError::TlsError(format!("Error configurando timeout: {}", e))
-            })?;
-
-        // Intentar leer el primer byte para detectar TLS
-        let mut peek_buffer = [0u8; 1];
-        match tcp_stream.read(&mut peek_buffer) {
-            Ok(1) => {
-                // Si el primer byte es 0x16, es un handshake TLS
-                if peek_buffer[0] == 0x16 {
-                    self.logger.log_notice(
-                        "Detectada conexión TLS, estableciendo handshake...".to_string(),
-                    );
-
-                    // Restaurar timeout normal
-                    tcp_stream.set_read_timeout(None).map_err(|e| {
-                        ConnectionHandlerError::TlsError(format!(
-                            "Error restaurando timeout: {}",
-                            e
-                        ))
-                    })?;
-
-                    // Establecer conexión TLS
-                    let server_config = TlsServerConfig::new();
-                    let tls_stream =
-                        TlsServerStream::new(tcp_stream, server_config).map_err(|e| {
-                            ConnectionHandlerError::TlsError(format!(
-                                "Error en handshake TLS: {}",
-                                e
-                            ))
-                        })?;
-
-                    self.logger.log_notice("Handshake TLS exitoso".to_string());
-                    Ok(ClientStream::Tls(tls_stream))
-                } else {
-                    // Es una conexión TCP normal
-                    self.logger
-                        .log_notice("Detectada conexión TCP normal".to_string());
-
-                    // Restaurar timeout normal
-                    tcp_stream.set_read_timeout(None).map_err(|e| {
-                        ConnectionHandlerError::TlsError(format!(
-                            "Error restaurando timeout: {}",
-                            e
-                        ))
-                    })?;
-
-                    Ok(ClientStream::Tcp(tcp_stream))
-                }
-            }
-            _ => {
-                // Si no se puede leer, asumir que es TCP normal
-                self.logger
-                    .log_notice("Asumiendo conexión TCP normal".to_string());
-                // Restaurar timeout normal
-                tcp_stream.set_read_timeout(None).map_err(|e| {
-                    ConnectionHandlerError::TlsError(format!("Error restaurando timeout: {}", e))
-                })?;
-                Ok(ClientStream::Tcp(tcp_stream))
-            }
-        }
-    }
-
-    /// Maneja conexiones TLS que no se pueden clonar
-    fn handle_tls_connection(
-        &mut self,
-        client_stream: ClientStream,
+        client_id: String,
+        tcp_stream: TcpStream,
+        stream_sender: Sender<ClientStream>,
         output_sender: Sender<RespMessage>,
-    ) -> Result<(), ConnectionHandlerError> {
-        let instruction_sender_clone = self.instruction_sender.clone();
-        let client_id = self.next_id.clone();
-        let client_logger = self.logger.clone();
+        peer_addr: SocketAddr,
+    ) -> JoinHandle<()> {
+        let instruction_sender = self.instruction_sender.clone();
+        let logger = self.logger.clone();
         let user_base = self.user_base.clone();
-
-        let input = create_client_input_thread(
-            client_id,
-            instruction_sender_clone,
-            client_stream,
-            output_sender,
-            client_logger,
-            user_base,
-        );
-
-        let client_id = self.next_id.clone();
-        self.update_id();
-
-        // Para TLS, solo tenemos un thread (input), ya que no se puede clonar
-        self.connections
-            .push((client_id, input, thread::spawn(|| {})));
-        Ok(())
+        let clients = self.clients.clone();
+        let max_protocol_errors = self.configs.get_max_protocol_errors();
+        let tls = self.configs.get_tls();
+        thread::spawn(move || {
+            // Si falla se descarta sólo esta conexión: al soltar el stream
+            // se cierra, y al soltar `stream_sender` el hilo de salida
+            // notifica la desconexión
+            let client_stream = match establish_connection(tcp_stream, tls).and_then(|stream| {
+                let output_stream = stream
+                    .try_clone()
+                    .map_err(|e| ConnectionHandlerError::StreamCloneError(e.to_string()))?;
+                Ok((stream, output_stream))
+            }) {
+                Ok((client_stream, output_stream)) => {
+                    let _ = stream_sender.send(output_stream);
+                    client_stream
+                }
+                Err(e) => {
+                    logger.log_warning(format!(
+                        "Rejected {}:{}, {}",
+                        peer_addr.ip(),
+                        peer_addr.port(),
+                        e
+                    ));
+                    return;
+                }
+            };
+            let mut client = ClientInput::new(
+                client_id,
+                instruction_sender,
+                Box::new(client_stream),
+                output_sender,
+                logger,
+                user_base,
+            )
+            .with_protocol_error_limit(max_protocol_errors, peer_addr)
+            .with_client_registry(clients);
+            client.run();
+        })
     }
 
-    /// Cierra la conexión de un cliente al servidor.
+    /// Registra una nueva conexión con sus flags por defecto.
     ///
     /// # Arguments
     ///
-    /// * `client_id` - ID del cliente a desconectar
+    /// * `client_id` - ID del cliente a registrar
+    /// * `peer_addr` - Dirección del cliente
+    /// * `output` - Canal de respuestas de la conexión
     ///
     /// # Returns
     ///
     /// `Result<(), ConnectionHandlerError>` - Resultado de la operación
-    fn close_connection(&mut self, client_id: String) -> Result<(), ConnectionHandlerError> {
-        for i in 0..self.connections.len() {
-            let (id, _, _) = &self.connections[i];
-            if *id == client_id {
-                let (_id, input_handle, output_handle) = self.connections.remove(i);
-
-                input_handle.join().map_err(|e| {
-                    ConnectionHandlerError::JoinError(format!("Input thread: {:?}", e))
-                })?;
-                output_handle.join().map_err(|e| {
-                    ConnectionHandlerError::JoinError(format!("Output thread: {:?}", e))
-                })?;
-                break;
-            }
-        }
+    fn register_client(
+        &self,
+        client_id: &str,
+        peer_addr: SocketAddr,
+        output: Sender<RespMessage>,
+    ) -> Result<(), ConnectionHandlerError> {
+        let flags = ClientFlags {
+            addr: Some(peer_addr),
+            connected_at: Some(Instant::now()),
+            output: Some(output),
+            ..ClientFlags::default()
+        };
+        self.clients
+            .write()
+            .map_err(|e| ConnectionHandlerError::LockError(e.to_string()))?
+            .insert(client_id.to_string(), flags);
         Ok(())
     }
 
@@ -456,7 +564,10 @@ impl Handler {
     ///
     /// Número de conexiones activas
     pub fn get_connection_count(&self) -> usize {
-        self.connections.len()
+        self.connections
+            .lock()
+            .map(|connections| connections.len())
+            .unwrap_or_default()
     }
 
     /// Obtiene el ID del próximo cliente.
@@ -529,34 +640,98 @@ impl Handler {
     }
 }
 
-fn create_client_input_thread(
+/// Establece la conexión con el cliente: con `tls` habilitado realiza el
+/// handshake de TLS-lite, con un tiempo máximo para que un cliente que no
+/// lo completa no retenga el hilo.
+///
+/// # Returns
+///
+/// `Result<ClientStream, ConnectionHandlerError>` - La conexión lista para
+/// leer pedidos, o el error si falló el handshake
+fn establish_connection(
+    tcp_stream: TcpStream,
+    tls: bool,
+) -> Result<ClientStream, ConnectionHandlerError> {
+    if !tls {
+        return Ok(ClientStream::Tcp(tcp_stream));
+    }
+
+    // El clon comparte el socket, y con él el timeout de lectura
+    let socket = tcp_stream
+        .try_clone()
+        .map_err(|e| ConnectionHandlerError::StreamCloneError(e.to_string()))?;
+    socket
+        .set_read_timeout(Some(TLS_HANDSHAKE_TIMEOUT))
+        .map_err(|e| {
+            ConnectionHandlerError::TlsError(format!("Error configurando timeout: {}", e))
+        })?;
+    let tls_stream = TlsServerStream::new(tcp_stream, TlsServerConfig::new())
+        .map_err(|e| ConnectionHandlerError::TlsError(e.to_string()))?;
+    socket.set_read_timeout(None).map_err(|e| {
+        ConnectionHandlerError::TlsError(format!("Error restaurando timeout: {}", e))
+    })?;
+    Ok(ClientStream::Tls(tls_stream))
+}
+
+/// Cierra la conexión de un cliente al servidor.
+///
+/// # Arguments
+///
+/// * `clients` - Registro de estado por conexión
+/// * `connections` - Conexiones activas
+/// * `client_id` - ID del cliente a desconectar
+///
+/// # Returns
+///
+/// `Result<bool, ConnectionHandlerError>` - `true` si la conexión estaba
+/// activa y se cerró
+fn close_connection(
+    clients: &ClientRegistry,
+    connections: &Connections,
     client_id: String,
-    instruction_sender: Sender<(String, Instruction, Sender<RespMessage>)>,
-    client_stream: ClientStream,
-    output_sender: Sender<RespMessage>,
-    client_logger: Arc<AofLogger>,
-    clone_user: Arc<UserBase>,
-) -> JoinHandle<()> {
-    thread::spawn(move || {
-        let mut client = ClientInput::new(
-            client_id,
-            instruction_sender,
-            Box::new(client_stream),
-            output_sender,
-            client_logger,
-            clone_user,
-        );
-        let _ = client.run();
-    })
+) -> Result<bool, ConnectionHandlerError> {
+    clients
+        .write()
+        .map_err(|e| ConnectionHandlerError::LockError(e.to_string()))?
+        .remove(&client_id);
+    let connection = {
+        let mut connections = connections
+            .lock()
+            .map_err(|e| ConnectionHandlerError::LockError(e.to_string()))?;
+        connections
+            .iter()
+            .position(|(id, _, _)| *id == client_id)
+            .map(|i| connections.remove(i))
+    };
+    // Los hilos se esperan sin retener la lista, que el hilo principal
+    // necesita para registrar nuevas conexiones
+    let Some((_id, input_handle, output_handle)) = connection else {
+        return Ok(false);
+    };
+    input_handle
+        .join()
+        .map_err(|e| ConnectionHandlerError::JoinError(format!("Input thread: {:?}", e)))?;
+    output_handle
+        .join()
+        .map_err(|e| ConnectionHandlerError::JoinError(format!("Output thread: {:?}", e)))?;
+    Ok(true)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cluster::state::node_data::NodeData;
+    use crate::command::command_executor::CommandExecutor;
     use crate::config::node_configs::NodeConfigs;
     use crate::logs::aof_logger::AofLogger;
     use crate::network::connection_handler::Handler;
-    use crate::security::users::user_base::UserBase;
+    use crate::network::resp_parser::parse_resp_line;
+    use crate::security::tls_lite::{TlsClientConfig, TlsClientStream};
+    use crate::security::users::{permissions::Permissions, user::User, user_base::UserBase};
+    use crate::storage::DataStore;
+    use std::io::{BufReader, ErrorKind};
+    use std::time::Duration;
+    use tempfile::TempDir;
 
     /// Crea un handler de prueba con configuración básica.
     fn create_test_handler() -> Handler {
@@ -565,7 +740,13 @@ mod tests {
         let logger = AofLogger::new(settings.clone());
         let user_base = UserBase::new();
 
-        Handler::new(instruction_tx, settings, logger, user_base)
+        Handler::new(
+            instruction_tx,
+            settings,
+            logger,
+            user_base,
+            Arc::new(RwLock::new(HashMap::new())),
+        )
     }
 
     #[test]
@@ -624,6 +805,27 @@ mod tests {
         assert_eq!(result, "BAA");
     }
 
+    #[test]
+    fn test_handler_register_and_close_client() {
+        let handler = create_test_handler();
+        let peer_addr = "127.0.0.1:50000".parse().unwrap();
+        let (output, _responses) = channel();
+        handler
+            .register_client("AAA000", peer_addr, output)
+            .unwrap();
+        {
+            let clients = handler.clients.read().unwrap();
+            let flags = clients.get("AAA000").unwrap();
+            assert_eq!(flags.addr, Some(peer_addr));
+            assert!(flags.connected_at.is_some());
+            assert!(flags.output.is_some());
+            assert!(!flags.no_touch && !flags.no_evict && !flags.killed);
+        }
+
+        close_connection(&handler.clients, &handler.connections, "AAA000".to_string()).unwrap();
+        assert!(handler.clients.read().unwrap().get("AAA000").is_none());
+    }
+
     #[test]
     fn test_handler_error_display() {
         let error = ConnectionHandlerError::BindError("test error".to_string());
@@ -636,6 +838,27 @@ mod tests {
         assert_eq!(format!("{:?}", error), "AcceptError(\"test error\")");
     }
 
+    #[test]
+    fn test_protocol_error_counter_closes_at_limit() {
+        let mut counter = ProtocolErrorCounter::new(3);
+        assert!(!counter.record_error());
+        assert!(!counter.record_error());
+        assert!(counter.record_error());
+    }
+
+    #[test]
+    fn test_protocol_error_counter_resets_on_valid_request() {
+        let mut counter = ProtocolErrorCounter::new(2);
+        assert!(!counter.record_error());
+        counter.record_valid();
+        assert_eq!(counter.consecutive(), 0);
+        assert!(!counter.record_error());
+        assert!(counter.record_error());
+
+        let mut unlimited = ProtocolErrorCounter::new(0);
+        assert!((0..100).all(|_| !unlimited.record_error()));
+    }
+
     #[test] // TODO: Ver nombre y contenido!!!
     fn test_todo() {
         // Este test requiere un listener real, por lo que se mantiene como estaba,
@@ -643,4 +866,247 @@ mod tests {
         let handler = create_test_handler();
         assert_eq!(handler.get_connection_count(), 0);
     }
+
+    /// Levanta un servidor completo, con su handler de conexiones y su
+    /// ejecutor, en un puerto libre, que admite hasta `max_clients`
+    /// conexiones, cifradas con TLS-lite si `tls` es `true`. Acepta al
+    /// usuario `tracer` con clave `blink`.
+    ///
+    /// # Returns
+    ///
+    /// Tupla con (dirección del servidor, directorio de sus archivos)
+    fn start_server(max_clients: usize, tls: bool) -> (SocketAddr, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config_path = temp_dir.path().join("redis.conf");
+        let config = format!(
+            "bind 127.0.0.1\nport {}\nrole M\nmaxclients {}\nsave 900 15\n\
+             dbfilename dump.rdb\ndir {}\nlogfile {}\nloglevel notice\n\
+             node-id 3f0c5e2a9b7d4c1e8f6a0b2d4c6e8f0a1b3c5d7e\nhash-slots 0-16383\ntls {}",
+            port,
+            max_clients,
+            temp_dir.path().display(),
+            temp_dir.path().join("redis.log").display(),
+            if tls { "yes" } else { "no" },
+        );
+        std::fs::write(&config_path, config).unwrap();
+        let settings = NodeConfigs::new(config_path.to_string_lossy().as_ref()).unwrap();
+        let logger = AofLogger::new(settings.clone());
+
+        let clients = Arc::new(RwLock::new(HashMap::new()));
+        let (instruction_tx, instruction_rx) = channel();
+        let mut node_data = NodeData::new(settings.clone());
+        node_data.set_as_master();
+        let mut executor = CommandExecutor::new(
+            Arc::new(RwLock::new(DataStore::new())),
+            instruction_rx,
+            settings.clone(),
+            logger.clone(),
+            channel().0,
+            Arc::new(RwLock::new(HashMap::new())),
+            Arc::new(RwLock::new(node_data)),
+            clients.clone(),
+        );
+        thread::spawn(move || executor.run());
+
+        let mut permissions = Permissions::new();
+        permissions.set_super();
+        let mut user_base = UserBase::new();
+        user_base.add_user(User::new(
+            "tracer".to_string(),
+            "blink".to_string(),
+            permissions,
+        ));
+        let addr = settings.get_addr();
+        let handler = Handler::new(instruction_tx, settings, logger, user_base, clients);
+        thread::spawn(move || handler.init());
+
+        (addr, temp_dir)
+    }
+
+    /// Conecta con el servidor, reintentando mientras termina de levantarse.
+    fn connect(addr: SocketAddr) -> TcpStream {
+        (0..50)
+            .find_map(|_| {
+                TcpStream::connect(addr)
+                    .inspect_err(|_| thread::sleep(Duration::from_millis(20)))
+                    .ok()
+            })
+            .expect("El servidor no aceptó conexiones")
+    }
+
+    /// Abre una conexión autenticada con el servidor.
+    fn connect_authenticated(addr: SocketAddr) -> BufReader<TcpStream> {
+        let mut stream = connect(addr);
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(b"*3\r\n$4\r\nAUTH\r\n$6\r\ntracer\r\n$5\r\nblink\r\n")
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        assert!(matches!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::SimpleString(_)
+        ));
+        reader
+    }
+
+    #[test]
+    fn test_client_kill_closes_the_victim_connection() {
+        let (addr, _temp_dir) = start_server(1000, false);
+        // Los ids se asignan en orden de llegada: la víctima es AAA000
+        let mut victim = connect_authenticated(addr);
+        let mut killer = connect_authenticated(addr);
+        let kill = b"*4\r\n$6\r\nCLIENT\r\n$4\r\nKILL\r\n$2\r\nID\r\n$6\r\nAAA000\r\n";
+
+        killer.get_mut().write_all(kill).unwrap();
+        assert_eq!(
+            parse_resp_line(&mut killer).unwrap(),
+            RespMessage::Integer(1)
+        );
+
+        // La víctima no recibe respuesta: su conexión se cierra
+        let mut buffer = [0u8; 64];
+        let read = victim
+            .read(&mut buffer)
+            .expect("La conexión de la víctima debería cerrarse, no expirar");
+        assert_eq!(read, 0);
+
+        killer.get_mut().write_all(kill).unwrap();
+        assert_eq!(
+            parse_resp_line(&mut killer).unwrap(),
+            RespMessage::Integer(0)
+        );
+    }
+
+    #[test]
+    fn test_connection_over_maxclients_is_refused() {
+        let (addr, _temp_dir) = start_server(1, false);
+        let _first = connect_authenticated(addr);
+
+        let mut second = TcpStream::connect(addr).unwrap();
+        second
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut refusal = String::new();
+        second
+            .read_to_string(&mut refusal)
+            .expect("La segunda conexión debería cerrarse, no expirar");
+        assert_eq!(refusal, ERR_MAX_CLIENTS);
+    }
+
+    #[test]
+    fn test_disconnected_client_frees_its_maxclients_slot() {
+        let (addr, _temp_dir) = start_server(1, false);
+        let first = connect_authenticated(addr);
+        drop(first);
+
+        // El cupo se libera cuando terminan los hilos de la conexión
+        let reconnected = (0..50).any(|_| {
+            let mut stream = connect(addr);
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            stream
+                .write_all(b"*3\r\n$4\r\nAUTH\r\n$6\r\ntracer\r\n$5\r\nblink\r\n")
+                .unwrap();
+            let accepted = matches!(
+                parse_resp_line(&mut BufReader::new(stream)),
+                Ok(RespMessage::SimpleString(_))
+            );
+            if !accepted {
+                thread::sleep(Duration::from_millis(100));
+            }
+            accepted
+        });
+        assert!(reconnected, "La desconexión debería liberar el cupo");
+    }
+
+    #[test]
+    fn test_tls_client_and_server_exchange_commands() {
+        let (addr, _temp_dir) = start_server(1000, true);
+        let stream = connect(addr);
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let stream = TlsClientStream::new(stream, TlsClientConfig::new("127.0.0.1".to_string()))
+            .expect("El handshake debería completarse");
+        let mut reader = BufReader::new(stream);
+
+        reader
+            .get_mut()
+            .write_all(b"*3\r\n$4\r\nAUTH\r\n$6\r\ntracer\r\n$5\r\nblink\r\n")
+            .unwrap();
+        assert!(matches!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::SimpleString(_)
+        ));
+
+        reader
+            .get_mut()
+            .write_all(b"*3\r\n$3\r\nSET\r\n$4\r\nhero\r\n$6\r\nTracer\r\n")
+            .unwrap();
+        assert_eq!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::SimpleString("OK".to_string())
+        );
+
+        reader
+            .get_mut()
+            .write_all(b"*2\r\n$3\r\nGET\r\n$4\r\nhero\r\n")
+            .unwrap();
+        assert_eq!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::BulkString(Some(b"Tracer".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_stalled_tls_handshake_does_not_block_other_clients() {
+        let (addr, _temp_dir) = start_server(1000, true);
+        // Se conecta pero nunca inicia el handshake
+        let _stalled = connect(addr);
+
+        let started = Instant::now();
+        let stream = connect(addr);
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let stream = TlsClientStream::new(stream, TlsClientConfig::new("127.0.0.1".to_string()))
+            .expect("El handshake debería completarse");
+        let mut reader = BufReader::new(stream);
+        reader
+            .get_mut()
+            .write_all(b"*3\r\n$4\r\nAUTH\r\n$6\r\ntracer\r\n$5\r\nblink\r\n")
+            .unwrap();
+        assert!(matches!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::SimpleString(_)
+        ));
+        assert!(started.elapsed() < TLS_HANDSHAKE_TIMEOUT / 2);
+    }
+
+    #[test]
+    fn test_plaintext_client_is_closed_by_tls_server() {
+        let (addr, _temp_dir) = start_server(1000, true);
+        let mut stream = connect(addr);
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(b"*3\r\n$4\r\nAUTH\r\n$6\r\ntracer\r\n$5\r\nblink\r\n")
+            .unwrap();
+
+        // El handshake falla y el servidor corta la conexión sin responder
+        let mut buffer = [0u8; 64];
+        match stream.read(&mut buffer) {
+            Ok(read) => assert_eq!(read, 0),
+            Err(e) => assert_eq!(e.kind(), ErrorKind::ConnectionReset),
+        }
+    }
 }
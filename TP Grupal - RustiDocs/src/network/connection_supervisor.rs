@@ -1,3 +1,11 @@
+//! Control de admisión de nuevas conexiones.
+//!
+//! Limita la tasa de aceptación con un token bucket para suavizar
+//! ráfagas de reconexiones: las conexiones que exceden la tasa se
+//! demoran brevemente, y las que exceden el cupo de espera se rechazan.
+//! Además limita la cantidad de conexiones abiertas a la vez, según
+//! `maxclients`.
+
 /*use std::sync::mpsc::Receiver;
 use std::thread::JoinHandle;
 use std::collections::HashMap;
@@ -53,3 +61,234 @@ impl Supervisor {
         }
     }
 }*/
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::{Duration, Instant};
+
+/// Error enviado a las conexiones rechazadas.
+pub const ERR_MAX_CLIENTS: &str = "-ERR max number of clients reached\r\n";
+
+/// Resultado de pedir admisión para una nueva conexión.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AcceptDecision {
+    /// La conexión se acepta inmediatamente
+    Accept,
+    /// La conexión se acepta luego de esperar la duración indicada
+    Delay(Duration),
+    /// La conexión excede el cupo y debe rechazarse
+    Reject,
+}
+
+/// Token bucket de aceptación de conexiones.
+///
+/// La estructura posee:
+///
+/// * `rate` Conexiones por segundo permitidas, 0 deshabilita el límite.
+/// * `tokens` Tokens disponibles; negativo indica conexiones en espera.
+/// * `last_refill` Último instante en el que se repusieron tokens.
+#[derive(Debug)]
+pub struct AcceptRateLimiter {
+    rate: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl AcceptRateLimiter {
+    /// Crea un limitador con ráfaga máxima igual a un segundo de tasa.
+    pub fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Pide admisión para una conexión en el instante actual.
+    pub fn acquire(&mut self) -> AcceptDecision {
+        self.acquire_at(Instant::now())
+    }
+
+    /// Pide admisión para una conexión en el instante `now`.
+    ///
+    /// Cada conexión consume un token. Sin tokens, la conexión reserva
+    /// el próximo y espera a que se reponga; si ya hay un segundo entero
+    /// de conexiones en espera, se rechaza.
+    pub fn acquire_at(&mut self, now: Instant) -> AcceptDecision {
+        if self.rate == 0 {
+            return AcceptDecision::Accept;
+        }
+        self.refill(now);
+
+        let capacity = self.rate as f64;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return AcceptDecision::Accept;
+        }
+        if self.tokens - 1.0 < -capacity {
+            return AcceptDecision::Reject;
+        }
+        self.tokens -= 1.0;
+        let wait = -self.tokens / capacity;
+        AcceptDecision::Delay(Duration::from_secs_f64(wait))
+    }
+
+    /// Repone los tokens correspondientes al tiempo transcurrido.
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        let capacity = self.rate as f64;
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * capacity).min(capacity);
+        self.last_refill = self.last_refill.max(now);
+    }
+}
+
+/// Cupo de conexiones abiertas a la vez.
+///
+/// La estructura posee:
+///
+/// * `max_clients` Conexiones abiertas permitidas, 0 deshabilita el límite.
+/// * `active` Conexiones abiertas, compartidas entre los clones del cupo.
+#[derive(Debug, Clone)]
+pub struct ClientLimiter {
+    max_clients: usize,
+    active: Arc<AtomicUsize>,
+}
+
+impl ClientLimiter {
+    /// Crea un cupo de `max_clients` conexiones; un valor no positivo
+    /// deshabilita el límite.
+    pub fn new(max_clients: i64) -> Self {
+        Self {
+            max_clients: usize::try_from(max_clients).unwrap_or_default(),
+            active: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Pide lugar para una nueva conexión.
+    ///
+    /// # Returns
+    ///
+    /// `true` si la conexión entra en el cupo y quedó contada
+    pub fn try_acquire(&self) -> bool {
+        self.active
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| {
+                (self.max_clients == 0 || active < self.max_clients).then_some(active + 1)
+            })
+            .is_ok()
+    }
+
+    /// Libera el lugar de una conexión que se cerró.
+    pub fn release(&self) {
+        let _ = self
+            .active
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| {
+                active.checked_sub(1)
+            });
+    }
+
+    /// Cantidad de conexiones abiertas.
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_limiter_always_accepts() {
+        let mut limiter = AcceptRateLimiter::new(0);
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert_eq!(limiter.acquire_at(now), AcceptDecision::Accept);
+        }
+    }
+
+    #[test]
+    fn test_burst_is_spread_at_configured_rate() {
+        let mut limiter = AcceptRateLimiter::new(10);
+        let start = Instant::now();
+
+        // Ráfaga de 30 conexiones en el mismo instante
+        let decisions: Vec<AcceptDecision> = (0..30).map(|_| limiter.acquire_at(start)).collect();
+
+        let immediate = decisions
+            .iter()
+            .filter(|d| **d == AcceptDecision::Accept)
+            .count();
+        let delays: Vec<Duration> = decisions
+            .iter()
+            .filter_map(|d| match d {
+                AcceptDecision::Delay(wait) => Some(*wait),
+                _ => None,
+            })
+            .collect();
+        let rejected = decisions
+            .iter()
+            .filter(|d| **d == AcceptDecision::Reject)
+            .count();
+
+        assert_eq!(immediate, 10);
+        assert_eq!(delays.len(), 10);
+        assert_eq!(rejected, 10);
+        // Las conexiones demoradas se admiten de a una cada 100ms
+        for (i, wait) in delays.iter().enumerate() {
+            let expected = 0.1 * (i + 1) as f64;
+            assert!((wait.as_secs_f64() - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_steady_rate_is_unaffected() {
+        let mut limiter = AcceptRateLimiter::new(10);
+        let start = Instant::now();
+
+        for i in 0..50 {
+            let now = start + Duration::from_millis(100 * i);
+            assert_eq!(limiter.acquire_at(now), AcceptDecision::Accept);
+        }
+    }
+
+    #[test]
+    fn test_limiter_recovers_after_burst() {
+        let mut limiter = AcceptRateLimiter::new(5);
+        let start = Instant::now();
+        for _ in 0..10 {
+            limiter.acquire_at(start);
+        }
+        assert_eq!(limiter.acquire_at(start), AcceptDecision::Reject);
+
+        // Tras drenar la espera y reponer la ráfaga, vuelve a aceptar
+        let later = start + Duration::from_secs(2);
+        assert_eq!(limiter.acquire_at(later), AcceptDecision::Accept);
+    }
+
+    #[test]
+    fn test_client_limiter_rejects_over_max_clients() {
+        let limiter = ClientLimiter::new(2);
+        let shared = limiter.clone();
+
+        assert!(limiter.try_acquire());
+        assert!(shared.try_acquire());
+        assert!(!limiter.try_acquire());
+        assert_eq!(limiter.active(), 2);
+
+        // Al cerrarse una conexión se libera su lugar
+        shared.release();
+        assert!(limiter.try_acquire());
+        assert!(!shared.try_acquire());
+    }
+
+    #[test]
+    fn test_client_limiter_without_limit_always_accepts() {
+        let limiter = ClientLimiter::new(0);
+        for _ in 0..1000 {
+            assert!(limiter.try_acquire());
+        }
+        limiter.release();
+        assert_eq!(limiter.active(), 999);
+    }
+}
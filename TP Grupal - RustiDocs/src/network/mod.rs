@@ -3,6 +3,7 @@ pub mod client_input;
 pub mod client_output;
 pub mod connection_handler;
 pub mod connection_supervisor;
+pub mod output_buffer;
 pub mod resp_message;
 pub mod resp_parser;
 pub use resp_parser::RespParser;
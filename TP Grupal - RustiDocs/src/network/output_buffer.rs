@@ -0,0 +1,290 @@
+//! Límites de buffer de salida por clase de cliente.
+//!
+//! Un cliente que no consume sus respuestas (por ejemplo, un suscriptor
+//! lento) acumula salida pendiente sin cota. Con
+//! `client-output-buffer-limit <clase> <hard> <soft> <soft-seconds>` la
+//! conexión se cierra si lo pendiente supera el límite duro, o el blando
+//! durante más de `soft-seconds` seguidos.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Clase de un cliente, según la cual se eligen sus límites.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ClientClass {
+    /// Cliente común
+    #[default]
+    Normal,
+    /// Cliente suscripto a canales
+    Pubsub,
+    /// Réplica de este nodo
+    Replica,
+}
+
+impl ClientClass {
+    /// Obtiene la clase a partir de su nombre en la configuración.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "normal" => Some(ClientClass::Normal),
+            "pubsub" => Some(ClientClass::Pubsub),
+            "replica" | "slave" => Some(ClientClass::Replica),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClientClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientClass::Normal => write!(f, "normal"),
+            ClientClass::Pubsub => write!(f, "pubsub"),
+            ClientClass::Replica => write!(f, "replica"),
+        }
+    }
+}
+
+/// Límites de buffer de salida de una clase. Un límite en 0 no se aplica.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OutputBufferLimit {
+    /// Bytes pendientes a partir de los cuales se cierra la conexión
+    pub hard_bytes: usize,
+    /// Bytes pendientes que no se pueden sostener más de `soft_seconds`
+    pub soft_bytes: usize,
+    /// Segundos tolerados por encima del límite blando
+    pub soft_seconds: u64,
+}
+
+impl OutputBufferLimit {
+    pub fn new(hard_bytes: usize, soft_bytes: usize, soft_seconds: u64) -> Self {
+        Self {
+            hard_bytes,
+            soft_bytes,
+            soft_seconds,
+        }
+    }
+
+    /// Parsea `<clase> <hard> <soft> <soft-seconds>`. Los tamaños aceptan
+    /// los sufijos `kb`, `mb` y `gb`.
+    pub fn parse(args: &[&str]) -> Option<(ClientClass, Self)> {
+        if args.len() != 4 {
+            return None;
+        }
+        let class = ClientClass::from_name(args[0])?;
+        let limit = Self::new(
+            parse_size(args[1])?,
+            parse_size(args[2])?,
+            args[3].parse().ok()?,
+        );
+        Some((class, limit))
+    }
+}
+
+/// Parsea un tamaño en bytes, con sufijo opcional `kb`, `mb` o `gb`.
+fn parse_size(size: &str) -> Option<usize> {
+    let size = size.to_lowercase();
+    let units = [
+        ("kb", 1024),
+        ("mb", 1024 * 1024),
+        ("gb", 1024 * 1024 * 1024),
+    ];
+    let (number, unit) = units
+        .iter()
+        .find_map(|(suffix, unit)| size.strip_suffix(suffix).map(|number| (number, *unit)))
+        .unwrap_or((size.as_str(), 1));
+    number.parse::<usize>().ok()?.checked_mul(unit)
+}
+
+/// Límites configurados para cada clase de cliente.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputBufferLimits {
+    normal: OutputBufferLimit,
+    pubsub: OutputBufferLimit,
+    replica: OutputBufferLimit,
+}
+
+impl Default for OutputBufferLimits {
+    /// Los mismos valores por defecto que Redis: sin límite para clientes
+    /// comunes, 32mb/8mb/60 para pubsub y 256mb/64mb/60 para réplicas.
+    fn default() -> Self {
+        Self {
+            normal: OutputBufferLimit::default(),
+            pubsub: OutputBufferLimit::new(32 * 1024 * 1024, 8 * 1024 * 1024, 60),
+            replica: OutputBufferLimit::new(256 * 1024 * 1024, 64 * 1024 * 1024, 60),
+        }
+    }
+}
+
+impl OutputBufferLimits {
+    /// Obtiene los límites de la clase.
+    pub fn get(&self, class: ClientClass) -> OutputBufferLimit {
+        match class {
+            ClientClass::Normal => self.normal,
+            ClientClass::Pubsub => self.pubsub,
+            ClientClass::Replica => self.replica,
+        }
+    }
+
+    /// Reemplaza los límites de la clase.
+    pub fn set(&mut self, class: ClientClass, limit: OutputBufferLimit) {
+        match class {
+            ClientClass::Normal => self.normal = limit,
+            ClientClass::Pubsub => self.pubsub = limit,
+            ClientClass::Replica => self.replica = limit,
+        }
+    }
+}
+
+/// Límite superado por el buffer de salida de un cliente.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitExceeded {
+    /// Lo pendiente superó el límite duro
+    Hard { pending: usize, limit: usize },
+    /// Lo pendiente se mantuvo sobre el límite blando más de lo tolerado
+    Soft {
+        pending: usize,
+        limit: usize,
+        over: Duration,
+    },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::Hard { pending, limit } => {
+                write!(f, "{} bytes pending over hard limit {}", pending, limit)
+            }
+            LimitExceeded::Soft {
+                pending,
+                limit,
+                over,
+            } => write!(
+                f,
+                "{} bytes pending over soft limit {} for {}s",
+                pending,
+                limit,
+                over.as_secs()
+            ),
+        }
+    }
+}
+
+/// Seguimiento del buffer de salida de un cliente respecto de sus límites.
+///
+/// La estructura posee:
+///
+/// * `soft_since` Instante desde el cual lo pendiente supera el límite blando.
+#[derive(Debug, Default)]
+pub struct OutputBufferMonitor {
+    soft_since: Option<Instant>,
+}
+
+impl OutputBufferMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evalúa los bytes pendientes en el instante `now`.
+    ///
+    /// Al bajar del límite blando se reinicia su conteo, por lo que un
+    /// cliente que se recupera a tiempo no se desconecta.
+    pub fn check_at(
+        &mut self,
+        limit: &OutputBufferLimit,
+        pending: usize,
+        now: Instant,
+    ) -> Option<LimitExceeded> {
+        if limit.hard_bytes > 0 && pending >= limit.hard_bytes {
+            return Some(LimitExceeded::Hard {
+                pending,
+                limit: limit.hard_bytes,
+            });
+        }
+        if limit.soft_bytes == 0 || pending < limit.soft_bytes {
+            self.soft_since = None;
+            return None;
+        }
+        let since = *self.soft_since.get_or_insert(now);
+        let over = now.saturating_duration_since(since);
+        if over > Duration::from_secs(limit.soft_seconds) {
+            return Some(LimitExceeded::Soft {
+                pending,
+                limit: limit.soft_bytes,
+                over,
+            });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_limit_with_units() {
+        let parsed = OutputBufferLimit::parse(&["pubsub", "32mb", "8MB", "60"]);
+        assert_eq!(
+            parsed,
+            Some((
+                ClientClass::Pubsub,
+                OutputBufferLimit::new(32 * 1024 * 1024, 8 * 1024 * 1024, 60)
+            ))
+        );
+        assert_eq!(
+            OutputBufferLimit::parse(&["normal", "0", "1024", "5"]),
+            Some((ClientClass::Normal, OutputBufferLimit::new(0, 1024, 5)))
+        );
+        assert_eq!(OutputBufferLimit::parse(&["mercy", "1", "1", "1"]), None);
+        assert_eq!(OutputBufferLimit::parse(&["pubsub", "1tb", "1", "1"]), None);
+    }
+
+    #[test]
+    fn test_hard_limit_is_immediate() {
+        let mut monitor = OutputBufferMonitor::new();
+        let limit = OutputBufferLimit::new(100, 50, 60);
+        assert!(matches!(
+            monitor.check_at(&limit, 150, Instant::now()),
+            Some(LimitExceeded::Hard { .. })
+        ));
+    }
+
+    #[test]
+    fn test_soft_limit_needs_to_be_sustained() {
+        let mut monitor = OutputBufferMonitor::new();
+        let limit = OutputBufferLimit::new(0, 50, 2);
+        let start = Instant::now();
+
+        assert_eq!(monitor.check_at(&limit, 60, start), None);
+        assert_eq!(
+            monitor.check_at(&limit, 60, start + Duration::from_secs(2)),
+            None
+        );
+        assert!(matches!(
+            monitor.check_at(&limit, 60, start + Duration::from_secs(3)),
+            Some(LimitExceeded::Soft { .. })
+        ));
+    }
+
+    #[test]
+    fn test_client_recovering_below_soft_limit_is_kept() {
+        let mut monitor = OutputBufferMonitor::new();
+        let limit = OutputBufferLimit::new(0, 50, 2);
+        let start = Instant::now();
+
+        assert_eq!(monitor.check_at(&limit, 60, start), None);
+        // Se recupera antes de que venza la tolerancia
+        assert_eq!(
+            monitor.check_at(&limit, 10, start + Duration::from_secs(1)),
+            None
+        );
+        // Volver a superar el límite reinicia el conteo
+        assert_eq!(
+            monitor.check_at(&limit, 60, start + Duration::from_secs(2)),
+            None
+        );
+        assert_eq!(
+            monitor.check_at(&limit, 60, start + Duration::from_secs(4)),
+            None
+        );
+    }
+}
@@ -16,13 +16,20 @@
 //! - **Integer**: Números enteros que comienzan con `:`
 //! - **BulkString**: Cadenas de longitud variable que comienzan con `$`
 //! - **Array**: Arrays que comienzan con `*`
-//! - **Boolean**: Valores booleanos
-//! - **Doubles**: Números de punto flotante que comienzan con `!`
+//! - **Boolean**: Valores booleanos que comienzan con `#` (RESP3)
+//! - **Doubles**: Números de punto flotante que comienzan con `,` (RESP3)
 //! - **Null**: Valores nulos representados con `_`
+//! - **Map**: Pares clave-valor que comienzan con `%` (RESP3)
+//! - **Set**: Conjuntos que comienzan con `~` (RESP3)
+//! - **Verbatim**: Texto con formato que comienza con `=` (RESP3)
+//! - **Attribute**: Metadatos de una respuesta que comienzan con `|` (RESP3)
 
 use crate::command::types::ResponseType;
 use std::fmt;
 
+/// Atributo con el que el servidor marca el usuario que hizo un `PUBLISH`
+pub const PUBLISHER_ATTRIBUTE: &str = "publisher";
+
 /// Error que puede ocurrir durante el manejo de mensajes RESP.
 #[derive(Debug, Clone, PartialEq)]
 pub enum RespMessageError {
@@ -76,7 +83,7 @@ pub enum RespMessage {
     Array(Vec<RespMessage>),
     /// Error simple que comienza con `-`
     SimpleError(String),
-    /// Valor booleano
+    /// Valor booleano de RESP3, `#t` o `#f`
     Boolean(bool),
     /// Error de longitud variable que comienza con `!`
     /// Puede ser `None` para un error nulo
@@ -84,12 +91,57 @@ pub enum RespMessage {
     /// Valor nulo representado con `_`
     /// Puede ser `None` para un valor nulo
     Null(Option<()>),
-    /// Número de punto flotante que comienza con `!`
+    /// Número de punto flotante de RESP3 que comienza con `,`
     Doubles(f64),
+    /// Pares clave-valor de RESP3 que comienzan con `%`
+    Map(Vec<(RespMessage, RespMessage)>),
+    /// Conjunto de RESP3 que comienza con `~`
+    Set(Vec<RespMessage>),
+    /// Mensaje fuera de banda de RESP3 que comienza con `>`
+    Push(Vec<RespMessage>),
+    /// Texto de RESP3 que comienza con `=`, con un formato de tres
+    /// caracteres (`txt`, `mkd`) y su contenido
+    Verbatim(String, Vec<u8>),
+    /// Metadatos de RESP3 que comienzan con `|`, como pares clave-valor,
+    /// seguidos de la respuesta a la que describen
+    Attribute(Vec<(RespMessage, RespMessage)>, Box<RespMessage>),
     /// Mensaje de desconexión
     Disconnect,
 }
 
+/// Versión del protocolo RESP que habla una conexión.
+///
+/// Toda conexión empieza en RESP2 y puede pasar a RESP3 con `HELLO 3`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RespProtocol {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+impl RespProtocol {
+    /// Obtiene el protocolo correspondiente a una versión de `HELLO`.
+    ///
+    /// # Returns
+    ///
+    /// `None` si la versión no está soportada
+    pub fn from_version(version: u8) -> Option<Self> {
+        match version {
+            2 => Some(RespProtocol::Resp2),
+            3 => Some(RespProtocol::Resp3),
+            _ => None,
+        }
+    }
+
+    /// Número de versión del protocolo.
+    pub fn version(&self) -> u8 {
+        match self {
+            RespProtocol::Resp2 => 2,
+            RespProtocol::Resp3 => 3,
+        }
+    }
+}
+
 /* TIPOS A IMPLEMENTAR:
 Array(Vec<RespValue>), ya estaria??
 
@@ -112,6 +164,9 @@ impl RespMessage {
     */
     /// Convierte un `ResponseType` interno a un `RespMessage`.
     ///
+    /// Los conjuntos se devuelven como `Set` de RESP3; al enviarlos a una
+    /// conexión RESP2, `for_protocol` los pasa a arrays.
+    ///
     /// # Arguments
     ///
     /// * `response` - El tipo de respuesta interno a convertir
@@ -145,12 +200,159 @@ impl RespMessage {
                         RespMessage::BulkString(Some(bytes))
                     })
                     .collect();
-                RespMessage::Array(inner)
+                RespMessage::Set(inner)
             }
             ResponseType::Null(_) => RespMessage::Null(None),
         }
     }
 
+    /// Crea el push con el que se entrega una publicación a un suscriptor.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - Canal en el que se publicó
+    /// * `payload` - Mensaje publicado
+    ///
+    /// # Returns
+    ///
+    /// `RespMessage` - Push `message` con el canal y el mensaje
+    pub fn pubsub_message(channel: &str, payload: RespMessage) -> Self {
+        RespMessage::Push(vec![
+            RespMessage::BulkString(Some(b"message".to_vec())),
+            RespMessage::BulkString(Some(channel.as_bytes().to_vec())),
+            payload,
+        ])
+    }
+
+    /// Crea el push con el que se entrega una publicación a un suscriptor
+    /// de un patrón.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Patrón al que está suscripto el cliente
+    /// * `channel` - Canal en el que se publicó
+    /// * `payload` - Mensaje publicado
+    ///
+    /// # Returns
+    ///
+    /// `RespMessage` - Push `pmessage` con el patrón, el canal y el mensaje
+    pub fn pubsub_pmessage(pattern: &str, channel: &str, payload: RespMessage) -> Self {
+        RespMessage::Push(vec![
+            RespMessage::BulkString(Some(b"pmessage".to_vec())),
+            RespMessage::BulkString(Some(pattern.as_bytes().to_vec())),
+            RespMessage::BulkString(Some(channel.as_bytes().to_vec())),
+            payload,
+        ])
+    }
+
+    /// Marca un mensaje publicado con el usuario ACL de la conexión que lo
+    /// publicó, como atributo `publisher`. Los suscriptores RESP2 reciben
+    /// el mensaje sin el atributo.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - Mensaje publicado
+    /// * `user` - Usuario autenticado del publicador
+    ///
+    /// # Returns
+    ///
+    /// `RespMessage` - Atributo con el publicador y el mensaje
+    pub fn with_publisher(payload: RespMessage, user: &str) -> Self {
+        RespMessage::Attribute(
+            vec![(
+                RespMessage::SimpleString(PUBLISHER_ATTRIBUTE.to_string()),
+                RespMessage::SimpleString(user.to_string()),
+            )],
+            Box::new(payload),
+        )
+    }
+
+    /// Separa el usuario que publicó un mensaje, si el servidor lo marcó con
+    /// [`RespMessage::with_publisher`], del mensaje publicado.
+    ///
+    /// # Returns
+    ///
+    /// `(Option<String>, RespMessage)` - Publicador y mensaje publicado
+    pub fn split_publisher(self) -> (Option<String>, RespMessage) {
+        let RespMessage::Attribute(attributes, payload) = self else {
+            return (None, self);
+        };
+        let publisher = attributes
+            .into_iter()
+            .find_map(|(key, value)| match (key, value) {
+                (RespMessage::SimpleString(key), RespMessage::SimpleString(user))
+                    if key == PUBLISHER_ATTRIBUTE =>
+                {
+                    Some(user)
+                }
+                _ => None,
+            });
+        (publisher, *payload)
+    }
+
+    /// Crea un texto plano de RESP3, como la salida de un `HELP`.
+    pub fn verbatim_text(text: &str) -> Self {
+        RespMessage::Verbatim("txt".to_string(), text.as_bytes().to_vec())
+    }
+
+    /// Convierte el mensaje al protocolo de la conexión que lo recibe.
+    ///
+    /// RESP2 no tiene push: las publicaciones, también las de un patrón, se
+    /// entregan como el mensaje publicado, igual que antes de RESP3, y el
+    /// resto de los push como
+    /// arrays. Los textos verbatim pasan a bulk strings y los atributos se
+    /// descartan, dejando sólo la respuesta. Los mapas se aplanan en arrays
+    /// de clave y valor, los conjuntos pasan a arrays, los booleanos a
+    /// enteros y los doubles a bulk strings. En RESP3 el mensaje no cambia.
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol` - Protocolo de la conexión
+    ///
+    /// # Returns
+    ///
+    /// `RespMessage` - El mensaje a enviar
+    pub fn for_protocol(self, protocol: RespProtocol) -> Self {
+        match (self, protocol) {
+            (RespMessage::Push(mut items), RespProtocol::Resp2) => {
+                let kind = match items.first() {
+                    Some(RespMessage::BulkString(Some(kind))) => kind.as_slice(),
+                    _ => b"",
+                };
+                match (kind, items.len()) {
+                    (b"message", 3) | (b"pmessage", 4) => {
+                        items.swap_remove(items.len() - 1).for_protocol(protocol)
+                    }
+                    _ => RespMessage::Array(items),
+                }
+            }
+            (RespMessage::Verbatim(_, text), RespProtocol::Resp2) => {
+                RespMessage::BulkString(Some(text))
+            }
+            (RespMessage::Attribute(_, reply), RespProtocol::Resp2) => {
+                reply.for_protocol(RespProtocol::Resp2)
+            }
+            (RespMessage::Map(pairs), RespProtocol::Resp2) => RespMessage::Array(
+                pairs
+                    .into_iter()
+                    .flat_map(|(key, value)| [key, value])
+                    .map(|item| item.for_protocol(RespProtocol::Resp2))
+                    .collect(),
+            ),
+            (RespMessage::Set(items), RespProtocol::Resp2) => RespMessage::Array(
+                items
+                    .into_iter()
+                    .map(|item| item.for_protocol(RespProtocol::Resp2))
+                    .collect(),
+            ),
+            (RespMessage::Boolean(b), RespProtocol::Resp2) => RespMessage::Integer(b as i64),
+            (RespMessage::Doubles(d), RespProtocol::Resp2) => {
+                RespMessage::BulkString(Some(d.to_string().into_bytes()))
+            }
+            (message, _) => message,
+        }
+    }
+
     /// Crea un mensaje de error RESP.
     ///
     /// # Arguments
@@ -202,7 +404,7 @@ impl RespMessage {
                 formatted.into_bytes()
             }
             RespMessage::Boolean(b) => {
-                let formatted = format!("{}{}\r\n", if *b { '+' } else { '-' }, "");
+                let formatted = format!("#{}\r\n", if *b { 't' } else { 'f' });
                 formatted.into_bytes()
             }
             RespMessage::BulkError(None) => b"-1\r\n".to_vec(),
@@ -214,9 +416,47 @@ impl RespMessage {
             }
             RespMessage::Null(_) => b"_\r\n".to_vec(),
             RespMessage::Doubles(d) => {
-                let formatted = format!("!{}\r\n", d);
+                let formatted = format!(",{}\r\n", format_double(*d));
                 formatted.into_bytes()
             }
+            RespMessage::Map(pairs) => {
+                let mut out = format!("%{}\r\n", pairs.len()).into_bytes();
+                for (key, value) in pairs {
+                    out.extend(key.as_bytes());
+                    out.extend(value.as_bytes());
+                }
+                out
+            }
+            RespMessage::Set(items) => {
+                let mut out = format!("~{}\r\n", items.len()).into_bytes();
+                for item in items {
+                    out.extend(item.as_bytes());
+                }
+                out
+            }
+            RespMessage::Push(items) => {
+                let mut out = format!(">{}\r\n", items.len()).into_bytes();
+                for item in items {
+                    out.extend(item.as_bytes());
+                }
+                out
+            }
+            RespMessage::Verbatim(format, text) => {
+                let mut out =
+                    format!("={}\r\n{}:", format.len() + 1 + text.len(), format).into_bytes();
+                out.extend(text);
+                out.extend(b"\r\n");
+                out
+            }
+            RespMessage::Attribute(attributes, reply) => {
+                let mut out = format!("|{}\r\n", attributes.len()).into_bytes();
+                for (key, value) in attributes {
+                    out.extend(key.as_bytes());
+                    out.extend(value.as_bytes());
+                }
+                out.extend(reply.as_bytes());
+                out
+            }
             RespMessage::Disconnect => b"DISCONNECT\r\n".to_vec(),
         }
     }
@@ -238,6 +478,11 @@ impl RespMessage {
             RespMessage::BulkError(_) => "BulkError",
             RespMessage::Null(_) => "Null",
             RespMessage::Doubles(_) => "Doubles",
+            RespMessage::Map(_) => "Map",
+            RespMessage::Set(_) => "Set",
+            RespMessage::Push(_) => "Push",
+            RespMessage::Verbatim(_, _) => "Verbatim",
+            RespMessage::Attribute(_, _) => "Attribute",
             RespMessage::Disconnect => "Disconnect",
         }
     }
@@ -267,6 +512,18 @@ impl RespMessage {
     }
 }
 
+/// Formatea un double como lo espera RESP3, con `inf`, `-inf` y `nan`
+/// para los valores no finitos.
+fn format_double(d: f64) -> String {
+    if d.is_nan() {
+        "nan".to_string()
+    } else if d.is_infinite() {
+        if d > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        d.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -337,18 +594,46 @@ mod tests {
     fn test_boolean_serialization() {
         let msg_true = RespMessage::Boolean(true);
         let bytes_true = msg_true.as_bytes();
-        assert_eq!(bytes_true, b"+\r\n");
+        assert_eq!(bytes_true, b"#t\r\n");
 
         let msg_false = RespMessage::Boolean(false);
         let bytes_false = msg_false.as_bytes();
-        assert_eq!(bytes_false, b"-\r\n");
+        assert_eq!(bytes_false, b"#f\r\n");
     }
 
     #[test]
     fn test_doubles_serialization() {
         let msg = RespMessage::Doubles(3.14);
         let bytes = msg.as_bytes();
-        assert_eq!(bytes, b"!3.14\r\n");
+        assert_eq!(bytes, b",3.14\r\n");
+        assert_eq!(RespMessage::Doubles(f64::INFINITY).as_bytes(), b",inf\r\n");
+        assert_eq!(
+            RespMessage::Doubles(f64::NEG_INFINITY).as_bytes(),
+            b",-inf\r\n"
+        );
+    }
+
+    #[test]
+    fn test_map_and_set_for_protocol() {
+        let map = RespMessage::Map(vec![(
+            RespMessage::BulkString(Some(b"Winston".to_vec())),
+            RespMessage::Boolean(true),
+        )]);
+        assert_eq!(map.clone().for_protocol(RespProtocol::Resp3), map);
+        assert_eq!(
+            map.for_protocol(RespProtocol::Resp2),
+            RespMessage::Array(vec![
+                RespMessage::BulkString(Some(b"Winston".to_vec())),
+                RespMessage::Integer(1),
+            ])
+        );
+
+        let set = RespMessage::Set(vec![RespMessage::Doubles(1.5)]);
+        assert_eq!(set.clone().for_protocol(RespProtocol::Resp3), set);
+        assert_eq!(
+            set.for_protocol(RespProtocol::Resp2),
+            RespMessage::Array(vec![RespMessage::BulkString(Some(b"1.5".to_vec()))])
+        );
     }
 
     #[test]
@@ -365,6 +650,66 @@ mod tests {
         assert_eq!(bytes, b"DISCONNECT\r\n");
     }
 
+    #[test]
+    fn test_push_serialization() {
+        let msg = RespMessage::pubsub_message(
+            "overwatch",
+            RespMessage::SimpleString("Tracer".to_string()),
+        );
+        assert_eq!(
+            msg.as_bytes(),
+            b">3\r\n$7\r\nmessage\r\n$9\r\noverwatch\r\n+Tracer\r\n"
+        );
+    }
+
+    #[test]
+    fn test_for_protocol() {
+        let payload = RespMessage::SimpleString("Tracer".to_string());
+        let msg = RespMessage::pubsub_message("overwatch", payload.clone());
+        assert_eq!(msg.clone().for_protocol(RespProtocol::Resp3), msg);
+        assert_eq!(msg.for_protocol(RespProtocol::Resp2), payload);
+
+        let items = vec![
+            RespMessage::BulkString(Some(b"subscribe".to_vec())),
+            RespMessage::BulkString(Some(b"overwatch".to_vec())),
+            RespMessage::Integer(1),
+        ];
+        assert_eq!(
+            RespMessage::Push(items.clone()).for_protocol(RespProtocol::Resp2),
+            RespMessage::Array(items)
+        );
+
+        let msg = RespMessage::pubsub_pmessage("over*", "overwatch", payload.clone());
+        assert_eq!(
+            msg.as_bytes(),
+            b">4\r\n$8\r\npmessage\r\n$5\r\nover*\r\n$9\r\noverwatch\r\n+Tracer\r\n"
+        );
+        assert_eq!(msg.for_protocol(RespProtocol::Resp2), payload);
+    }
+
+    #[test]
+    fn test_verbatim_and_attribute_for_protocol() {
+        let help = RespMessage::verbatim_text("Ping the payload");
+        assert_eq!(help.as_bytes(), b"=20\r\ntxt:Ping the payload\r\n");
+        assert_eq!(help.clone().for_protocol(RespProtocol::Resp3), help);
+        assert_eq!(
+            help.clone().for_protocol(RespProtocol::Resp2),
+            RespMessage::BulkString(Some(b"Ping the payload".to_vec()))
+        );
+
+        let hinted = RespMessage::Attribute(
+            vec![(
+                RespMessage::SimpleString("popularity".to_string()),
+                RespMessage::Doubles(0.9),
+            )],
+            Box::new(help),
+        );
+        assert_eq!(
+            hinted.for_protocol(RespProtocol::Resp2),
+            RespMessage::BulkString(Some(b"Ping the payload".to_vec()))
+        );
+    }
+
     #[test]
     fn test_from_response_str() {
         let response = ResponseType::Str("OK".to_string());
@@ -405,9 +750,9 @@ mod tests {
         let response = ResponseType::Set(set);
         let msg = RespMessage::from_response(response);
 
-        // Verificar que es un Array con 2 elementos
-        match msg {
-            RespMessage::Array(items) => {
+        // En RESP3 es un Set con 2 elementos
+        match msg.clone() {
+            RespMessage::Set(items) => {
                 assert_eq!(items.len(), 2);
 
                 // Convertir los elementos a strings para comparación
@@ -428,8 +773,14 @@ mod tests {
                 // Verificar que contiene los elementos esperados
                 assert_eq!(strings, vec!["Hello".to_string(), "World".to_string()]);
             }
-            _ => panic!("Expected Array, got {:?}", msg),
+            _ => panic!("Expected Set, got {:?}", msg),
         }
+
+        // En RESP2 se envía como Array
+        assert!(matches!(
+            msg.for_protocol(RespProtocol::Resp2),
+            RespMessage::Array(items) if items.len() == 2
+        ));
     }
 
     #[test]
@@ -458,6 +809,7 @@ mod tests {
         assert_eq!(RespMessage::Boolean(true).get_type_name(), "Boolean");
         assert_eq!(RespMessage::Doubles(0.0).get_type_name(), "Doubles");
         assert_eq!(RespMessage::Disconnect.get_type_name(), "Disconnect");
+        assert_eq!(RespMessage::Push(vec![]).get_type_name(), "Push");
     }
 
     #[test]
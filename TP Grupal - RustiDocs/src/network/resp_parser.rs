@@ -2,8 +2,12 @@
 //!
 //! Este módulo permite parsear mensajes RESP desde un stream de entrada,
 //! devolviendo un enum `RespMessage` o un error detallado.
+//!
+//! Además de RESP, acepta comandos inline como los que envía `telnet` o
+//! `nc`: una línea con los argumentos separados por espacios, como
+//! `SET foo "bar baz"`.
 use std::fmt;
-use std::io::BufRead;
+use std::io::{BufRead, Read};
 use std::str::FromStr;
 
 use super::resp_message::RespMessage;
@@ -25,6 +29,8 @@ pub enum RespParserError {
     ParseDoubleError(String),
     /// Error de formato
     FormatError(String),
+    /// El stream se cerró
+    EndOfStream,
 }
 
 impl fmt::Display for RespParserError {
@@ -37,14 +43,30 @@ impl fmt::Display for RespParserError {
             RespParserError::InvalidLength => write!(f, "Longitud inválida"),
             RespParserError::ParseDoubleError(e) => write!(f, "Error parseando double: {}", e),
             RespParserError::FormatError(e) => write!(f, "Error de formato: {}", e),
+            RespParserError::EndOfStream => write!(f, "Fin de stream"),
         }
     }
 }
 
 impl std::error::Error for RespParserError {}
 
+impl RespParserError {
+    /// Indica si el error se debe a la conexión y no al contenido recibido,
+    /// en cuyo caso no tiene sentido seguir leyendo.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(
+            self,
+            RespParserError::IoError(_) | RespParserError::EndOfStream
+        )
+    }
+}
+
 pub struct RespParser;
 
+/// Tamaño máximo de un bulk string o bulk error, igual al
+/// `proto-max-bulk-len` por defecto de Redis.
+pub const MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+
 /// Parsea una línea RESP desde un BufRead.
 ///
 /// # Returns
@@ -52,12 +74,19 @@ pub struct RespParser;
 /// `Result<RespMessage, RespParserError>`
 pub fn parse_resp_line<R: BufRead>(reader: &mut R) -> Result<RespMessage, RespParserError> {
     let mut line = String::new();
-    let n = reader
-        .read_line(&mut line)
-        .map_err(|e| RespParserError::IoError(e.to_string()))?;
+    loop {
+        line.clear();
+        let n = reader
+            .read_line(&mut line)
+            .map_err(|e| RespParserError::IoError(e.to_string()))?;
 
-    if n == 0 {
-        return Err(RespParserError::FormatError("Fin de stream".to_string()));
+        if n == 0 {
+            return Err(RespParserError::EndOfStream);
+        }
+        // Como Redis, las líneas vacías se ignoran
+        if !(line.ends_with("\r\n") && line.trim().is_empty()) {
+            break;
+        }
     }
     if !line.ends_with("\r\n") {
         return Err(RespParserError::FormatError(
@@ -69,7 +98,7 @@ pub fn parse_resp_line<R: BufRead>(reader: &mut R) -> Result<RespMessage, RespPa
         .chars()
         .next()
         .ok_or_else(|| RespParserError::FormatError("Línea vacía".to_string()))?;
-    let content = line[1..].trim_end_matches("\r\n");
+    let content = line[prefix.len_utf8()..].trim_end_matches("\r\n");
 
     match prefix {
         // Arrays
@@ -94,6 +123,65 @@ pub fn parse_resp_line<R: BufRead>(reader: &mut R) -> Result<RespMessage, RespPa
             }
             Ok(RespMessage::Array(items))
         }
+        // Push
+        '>' => {
+            let count = usize::from_str(content).map_err(|e: std::num::ParseIntError| {
+                RespParserError::ParseIntError(e.to_string())
+            })?;
+            let mut items: Vec<RespMessage> = Vec::with_capacity(count);
+            for _ in 0..count {
+                items.push(parse_resp_line(reader)?);
+            }
+            Ok(RespMessage::Push(items))
+        }
+        // Map: pares clave-valor
+        '%' => {
+            let count = usize::from_str(content).map_err(|e: std::num::ParseIntError| {
+                RespParserError::ParseIntError(e.to_string())
+            })?;
+            let mut pairs = Vec::with_capacity(count);
+            for _ in 0..count {
+                pairs.push((parse_resp_line(reader)?, parse_resp_line(reader)?));
+            }
+            Ok(RespMessage::Map(pairs))
+        }
+        // Set
+        '~' => {
+            let count = usize::from_str(content).map_err(|e: std::num::ParseIntError| {
+                RespParserError::ParseIntError(e.to_string())
+            })?;
+            let mut items: Vec<RespMessage> = Vec::with_capacity(count);
+            for _ in 0..count {
+                items.push(parse_resp_line(reader)?);
+            }
+            Ok(RespMessage::Set(items))
+        }
+        // Attribute: pares clave-valor seguidos de la respuesta que describen
+        '|' => {
+            let count = usize::from_str(content).map_err(|e: std::num::ParseIntError| {
+                RespParserError::ParseIntError(e.to_string())
+            })?;
+            let mut attributes = Vec::with_capacity(count);
+            for _ in 0..count {
+                attributes.push((parse_resp_line(reader)?, parse_resp_line(reader)?));
+            }
+            let reply = parse_resp_line(reader)?;
+            Ok(RespMessage::Attribute(attributes, Box::new(reply)))
+        }
+        // Verbatim string: `formato:contenido`
+        '=' => {
+            let len = usize::from_str(content).map_err(|e: std::num::ParseIntError| {
+                RespParserError::ParseIntError(e.to_string())
+            })?;
+            let mut text = read_bulk(reader, len)?;
+            if text.len() < 4 || text[3] != b':' {
+                return Err(RespParserError::FormatError(
+                    "Verbatim string sin formato".to_string(),
+                ));
+            }
+            let format = String::from_utf8_lossy(&text[..3]).into_owned();
+            Ok(RespMessage::Verbatim(format, text.split_off(4)))
+        }
         // Integer
         ':' => {
             let value = content
@@ -124,23 +212,13 @@ pub fn parse_resp_line<R: BufRead>(reader: &mut R) -> Result<RespMessage, RespPa
             let len: isize = content.parse().map_err(|e: std::num::ParseIntError| {
                 RespParserError::ParseIntError(e.to_string())
             })?;
-            if len == -1 {
-                Ok(RespMessage::BulkError(None))
-            } else {
-                let mut buf: String = String::new();
-                reader
-                    .read_line(&mut buf)
-                    .map_err(|e| RespParserError::IoError(e.to_string()))?;
-                if !buf.ends_with("\r\n") {
-                    return Err(RespParserError::FormatError(
-                        "BulkError no termina en CRLF".to_string(),
-                    ));
-                }
-                let value = buf.trim_end_matches("\r\n").to_string();
-                if value.len() != len as usize {
-                    return Err(RespParserError::InvalidLength);
-                }
-                Ok(RespMessage::BulkError(Some(value.into_bytes())))
+            match len {
+                -1 => Ok(RespMessage::BulkError(None)),
+                0.. => Ok(RespMessage::BulkError(Some(read_bulk(
+                    reader,
+                    len as usize,
+                )?))),
+                _ => Err(RespParserError::InvalidLength),
             }
         }
         // Bulk string
@@ -148,29 +226,100 @@ pub fn parse_resp_line<R: BufRead>(reader: &mut R) -> Result<RespMessage, RespPa
             let len: isize = content.parse().map_err(|e: std::num::ParseIntError| {
                 RespParserError::ParseIntError(e.to_string())
             })?;
-            if len == -1 {
-                Ok(RespMessage::BulkString(None))
-            } else {
-                let mut buf: String = String::new();
-                reader
-                    .read_line(&mut buf)
-                    .map_err(|e| RespParserError::IoError(e.to_string()))?;
-                if !buf.ends_with("\r\n") {
-                    return Err(RespParserError::FormatError(
-                        "BulkString no termina en CRLF".to_string(),
-                    ));
-                }
-                let value = buf.trim_end_matches("\r\n").to_string();
-                if value.len() != len as usize {
-                    return Err(RespParserError::InvalidLength);
+            match len {
+                -1 => Ok(RespMessage::BulkString(None)),
+                0.. => Ok(RespMessage::BulkString(Some(read_bulk(
+                    reader,
+                    len as usize,
+                )?))),
+                _ => Err(RespParserError::InvalidLength),
+            }
+        }
+        // Comando inline
+        _ => {
+            let args = split_inline(line.trim_end_matches("\r\n"))?;
+            if args.len() == 1 && args[0] == b"QUIT" {
+                return Ok(RespMessage::Disconnect);
+            }
+            Ok(RespMessage::Array(
+                args.into_iter()
+                    .map(|arg| RespMessage::BulkString(Some(arg)))
+                    .collect(),
+            ))
+        }
+    }
+}
+
+/// Separa un comando inline en sus argumentos.
+///
+/// Los argumentos se separan por espacios, salvo dentro de comillas
+/// dobles, donde `\"` y `\\` escapan una comilla y una barra.
+///
+/// # Returns
+///
+/// `Result<Vec<Vec<u8>>, RespParserError>` - Los argumentos, o un error si
+/// hay comillas sin cerrar o una comilla de cierre seguida de otro carácter
+fn split_inline(line: &str) -> Result<Vec<Vec<u8>>, RespParserError> {
+    let unclosed = || RespParserError::FormatError("Comillas sin cerrar en el comando".to_string());
+    let mut args = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Ok(args);
+        }
+        let mut arg = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => arg.push(chars.next().ok_or_else(unclosed)?),
+                    Some(c) => arg.push(c),
+                    None => return Err(unclosed()),
                 }
-                Ok(RespMessage::BulkString(Some(value.into_bytes())))
+            }
+            if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                return Err(RespParserError::FormatError(
+                    "Se esperaba un espacio después de las comillas".to_string(),
+                ));
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                arg.push(c);
             }
         }
-        _ => Err(RespParserError::UnknownPrefix(prefix)),
+        args.push(arg.into_bytes());
     }
 }
 
+/// Lee el contenido de un bulk de `len` bytes seguido de CRLF.
+///
+/// El contenido se lee por longitud y no por línea, de modo que puede
+/// tener cualquier byte, incluso CRLF o secuencias que no son UTF-8.
+fn read_bulk<R: BufRead>(reader: &mut R, len: usize) -> Result<Vec<u8>, RespParserError> {
+    if len > MAX_BULK_LEN {
+        return Err(RespParserError::InvalidLength);
+    }
+    // Se lee de a partes para no reservar de antemano lo que declara el header
+    let mut buf = Vec::new();
+    reader
+        .take(len as u64 + 2)
+        .read_to_end(&mut buf)
+        .map_err(|e| RespParserError::IoError(e.to_string()))?;
+    if buf.len() < len + 2 {
+        return Err(RespParserError::EndOfStream);
+    }
+    if !buf.ends_with(b"\r\n") {
+        // Se descarta el resto de la línea para que la próxima lectura
+        // empiece en el siguiente mensaje
+        let mut rest = Vec::new();
+        let _ = reader.read_until(b'\n', &mut rest);
+        return Err(RespParserError::InvalidLength);
+    }
+    buf.truncate(len);
+    Ok(buf)
+}
+
 fn parse_boolean(content: &str) -> Result<bool, RespParserError> {
     match content {
         "t" => Ok(true),
@@ -319,4 +468,187 @@ mod resp_parse_tests {
             _ => panic!("Expected a double"),
         }
     }
+
+    #[test]
+    fn test_push() {
+        let msg = RespMessage::pubsub_message(
+            "overwatch",
+            RespMessage::BulkString(Some(b"Genji".to_vec())),
+        );
+        let bytes = msg.as_bytes();
+        let mut reader = BufReader::new(&bytes[..]);
+        assert_eq!(parse_resp_line(&mut reader).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_verbatim_string() {
+        let mut reader = BufReader::new(&b"=16\r\ntxt:Hello Sombra\r\n"[..]);
+        let msg = parse_resp_line(&mut reader).unwrap();
+        assert_eq!(msg, RespMessage::verbatim_text("Hello Sombra"));
+        assert_eq!(msg.as_bytes(), b"=16\r\ntxt:Hello Sombra\r\n");
+    }
+
+    #[test]
+    fn test_attribute_prefixed_reply() {
+        let msg = RespMessage::Attribute(
+            vec![(
+                RespMessage::BulkString(Some(b"key-popularity".to_vec())),
+                RespMessage::Array(vec![
+                    RespMessage::BulkString(Some(b"Hero".to_vec())),
+                    RespMessage::Integer(75),
+                ]),
+            )],
+            Box::new(RespMessage::Integer(2)),
+        );
+        let bytes = msg.as_bytes();
+        let mut reader = BufReader::new(&bytes[..]);
+        assert_eq!(parse_resp_line(&mut reader).unwrap(), msg);
+    }
+
+    /// Serializa el mensaje y verifica que al parsearlo se obtiene el mismo.
+    fn assert_round_trip(msg: RespMessage) {
+        let bytes = msg.as_bytes();
+        let mut reader = BufReader::new(&bytes[..]);
+        assert_eq!(parse_resp_line(&mut reader).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_map_round_trip() {
+        let msg = RespMessage::Map(vec![
+            (
+                RespMessage::BulkString(Some(b"Tracer".to_vec())),
+                RespMessage::Integer(150),
+            ),
+            (
+                RespMessage::SimpleString("Reinhardt".to_string()),
+                RespMessage::Set(vec![RespMessage::BulkString(Some(b"Tank".to_vec()))]),
+            ),
+        ]);
+        assert_eq!(
+            msg.as_bytes(),
+            b"%2\r\n$6\r\nTracer\r\n:150\r\n+Reinhardt\r\n~1\r\n$4\r\nTank\r\n"
+        );
+        assert_round_trip(msg);
+        assert_round_trip(RespMessage::Map(vec![]));
+    }
+
+    #[test]
+    fn test_set_round_trip() {
+        let msg = RespMessage::Set(vec![
+            RespMessage::BulkString(Some(b"Mercy".to_vec())),
+            RespMessage::BulkString(Some(b"Ana".to_vec())),
+        ]);
+        assert_eq!(msg.as_bytes(), b"~2\r\n$5\r\nMercy\r\n$3\r\nAna\r\n");
+        assert_round_trip(msg);
+        assert_round_trip(RespMessage::Set(vec![]));
+    }
+
+    #[test]
+    fn test_null_boolean_and_double_round_trip() {
+        assert_round_trip(RespMessage::Null(None));
+        assert_round_trip(RespMessage::Boolean(true));
+        assert_round_trip(RespMessage::Boolean(false));
+        assert_round_trip(RespMessage::Doubles(3.14));
+        assert_round_trip(RespMessage::Doubles(-10.0));
+        assert_round_trip(RespMessage::Doubles(f64::INFINITY));
+        assert_round_trip(RespMessage::Doubles(f64::NEG_INFINITY));
+
+        let bytes = RespMessage::Doubles(f64::NAN).as_bytes();
+        assert_eq!(bytes, b",nan\r\n");
+        let mut reader = BufReader::new(&bytes[..]);
+        assert!(matches!(
+            parse_resp_line(&mut reader),
+            Ok(RespMessage::Doubles(d)) if d.is_nan()
+        ));
+    }
+
+    #[test]
+    fn test_inline_ping() {
+        let mut reader = BufReader::new(&b"PING\r\n"[..]);
+        assert_eq!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::Array(vec![RespMessage::BulkString(Some(b"PING".to_vec()))])
+        );
+    }
+
+    #[test]
+    fn test_inline_quoted_argument() {
+        let mut reader = BufReader::new(&b"SET  foo \"bar baz\"\r\n"[..]);
+        assert_eq!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::Array(vec![
+                RespMessage::BulkString(Some(b"SET".to_vec())),
+                RespMessage::BulkString(Some(b"foo".to_vec())),
+                RespMessage::BulkString(Some(b"bar baz".to_vec())),
+            ])
+        );
+
+        let mut reader = BufReader::new(&b"ECHO \"D.Va \\\"Hana\\\"\" \"\"\r\n"[..]);
+        assert_eq!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::Array(vec![
+                RespMessage::BulkString(Some(b"ECHO".to_vec())),
+                RespMessage::BulkString(Some(b"D.Va \"Hana\"".to_vec())),
+                RespMessage::BulkString(Some(b"".to_vec())),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_inline_unbalanced_quotes() {
+        for input in [
+            &b"SET foo \"bar\r\n"[..],
+            &b"SET foo \"bar\\\r\n"[..],
+            &b"SET foo \"bar\"baz\r\n"[..],
+        ] {
+            let mut reader = BufReader::new(input);
+            assert!(matches!(
+                parse_resp_line(&mut reader),
+                Err(RespParserError::FormatError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_inline_and_multibulk_on_same_stream() {
+        let input = b"PING\r\n\r\n  \r\n*2\r\n$3\r\nGET\r\n$5\r\nGenji\r\nGET Genji\r\nQUIT\r\n";
+        let mut reader = BufReader::new(&input[..]);
+        let get = RespMessage::Array(vec![
+            RespMessage::BulkString(Some(b"GET".to_vec())),
+            RespMessage::BulkString(Some(b"Genji".to_vec())),
+        ]);
+        assert_eq!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::Array(vec![RespMessage::BulkString(Some(b"PING".to_vec()))])
+        );
+        // Las líneas vacías se saltean
+        assert_eq!(parse_resp_line(&mut reader).unwrap(), get);
+        assert_eq!(parse_resp_line(&mut reader).unwrap(), get);
+        assert_eq!(
+            parse_resp_line(&mut reader).unwrap(),
+            RespMessage::Disconnect
+        );
+        assert_eq!(
+            parse_resp_line(&mut reader),
+            Err(RespParserError::EndOfStream)
+        );
+    }
+
+    #[test]
+    fn test_echo_binary_round_trip() {
+        // Bytes que no son UTF-8 e incluyen un CRLF en el medio
+        let payload = vec![0x00, 0xFF, b'\r', b'\n', 0xC3, b'D', b'.', b'V', b'a'];
+        let request = RespMessage::Array(vec![
+            RespMessage::BulkString(Some(b"ECHO".to_vec())),
+            RespMessage::BulkString(Some(payload.clone())),
+        ]);
+        let bytes = request.as_bytes();
+        let mut reader = BufReader::new(&bytes[..]);
+        assert_eq!(parse_resp_line(&mut reader).unwrap(), request);
+
+        let reply = RespMessage::BulkString(Some(payload));
+        let bytes = reply.as_bytes();
+        let mut reader = BufReader::new(&bytes[..]);
+        assert_eq!(parse_resp_line(&mut reader).unwrap(), reply);
+    }
 }
@@ -1,4 +1,5 @@
-use crate::command::types::Command;
+use crate::command::types::{Command, PubsubSubcommand};
+use crate::command::utils::glob_match;
 use crate::network::resp_message::RespMessage;
 use std::collections::HashMap;
 use std::fmt;
@@ -63,6 +64,114 @@ impl fmt::Display for ChannelManagerError {
 
 impl std::error::Error for ChannelManagerError {}
 
+/// Suscriptores de cada patrón: pattern -> { client_id -> sender al cliente }
+pub(crate) type PatternSubscribers = HashMap<String, HashMap<String, Sender<RespMessage>>>;
+
+/// Suscribe a un cliente a los patrones dados. Volver a suscribirse a un
+/// patrón no tiene efecto.
+pub(crate) fn add_pattern_subscriptions(
+    subscribers: &mut PatternSubscribers,
+    client_id: &str,
+    patterns: Vec<String>,
+    client_sender: &Sender<RespMessage>,
+) {
+    for pattern in patterns {
+        subscribers
+            .entry(pattern)
+            .or_default()
+            .entry(client_id.to_string())
+            .or_insert_with(|| client_sender.clone());
+    }
+}
+
+/// Desuscribe a un cliente de los patrones dados, o de todos si no se
+/// indica ninguno. Los patrones que se quedan sin suscriptores se eliminan.
+pub(crate) fn remove_pattern_subscriptions(
+    subscribers: &mut PatternSubscribers,
+    client_id: &str,
+    patterns: &[String],
+) {
+    subscribers.retain(|pattern, clients| {
+        if patterns.is_empty() || patterns.contains(pattern) {
+            clients.remove(client_id);
+        }
+        !clients.is_empty()
+    });
+}
+
+/// Canales con al menos un suscriptor, ordenados por nombre.
+pub(crate) fn active_channels_of(
+    channels: &HashMap<String, HashMap<String, Sender<RespMessage>>>,
+) -> Vec<String> {
+    let mut active: Vec<String> = channels
+        .iter()
+        .filter(|(_, subs)| !subs.is_empty())
+        .map(|(channel_id, _)| channel_id.clone())
+        .collect();
+    active.sort();
+    active
+}
+
+/// Responde un subcomando de `PUBSUB` a partir de los suscriptores de
+/// cada canal.
+///
+/// # Returns
+///
+/// `RespMessage` - Para `CHANNELS`, los canales activos que cumplen el
+/// patrón; para `NUMSUB`, cada canal seguido de su cantidad de suscriptores
+pub(crate) fn pubsub_reply(
+    channels: &HashMap<String, HashMap<String, Sender<RespMessage>>>,
+    subcommand: &PubsubSubcommand,
+) -> RespMessage {
+    let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+    match subcommand {
+        PubsubSubcommand::Channels(pattern) => RespMessage::Array(
+            active_channels_of(channels)
+                .iter()
+                .filter(|channel_id| pattern.as_ref().is_none_or(|p| glob_match(p, channel_id)))
+                .map(|channel_id| text(channel_id))
+                .collect(),
+        ),
+        PubsubSubcommand::NumSub(channel_ids) => RespMessage::Array(
+            channel_ids
+                .iter()
+                .flat_map(|channel_id| {
+                    let count = channels.get(channel_id).map_or(0, |subs| subs.len());
+                    [text(channel_id), RespMessage::Integer(count as i64)]
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Entrega una publicación como `pmessage` a los suscriptores de los
+/// patrones que cumple el canal.
+///
+/// # Returns
+///
+/// `usize` - Cantidad de entregas realizadas
+pub(crate) fn publish_to_patterns(
+    subscribers: &PatternSubscribers,
+    channel_id: &str,
+    message: &RespMessage,
+) -> usize {
+    let mut delivered = 0;
+    for (pattern, clients) in subscribers {
+        if !glob_match(pattern, channel_id) {
+            continue;
+        }
+        let push = RespMessage::pubsub_pmessage(pattern, channel_id, message.clone());
+        for (client_id, sender) in clients {
+            if sender.send(push.clone()).is_err() {
+                println!("[CHANNEL-MNG] Error al propagarle pubsub a {}", client_id);
+            } else {
+                delivered += 1;
+            }
+        }
+    }
+    delivered
+}
+
 /// Gestor de canales para el sistema Pub/Sub (Publish/Subscribe).
 ///
 /// Maneja la suscripción y desuscripción de clientes a canales,
@@ -73,11 +182,15 @@ impl std::error::Error for ChannelManagerError {}
 /// - Si se hace SUBSCRIBE de un canal que no existe, se crea automáticamente
 /// - Un cliente puede hacer PUBLISH aunque no esté suscrito a un canal
 /// - Si un canal se queda sin suscriptores, se elimina automáticamente
+/// - Las publicaciones también llegan a los suscriptores de los patrones
+///   que cumple el canal
 pub struct ChannelManager {
     /// Receptor de mensajes con tuplas (client_id, Command, response_sender, client_sender)
     receiver: Receiver<(String, Command, Sender<String>, Sender<RespMessage>)>,
     /// Mapa de canales: channel_id -> { client_id -> sender al cliente }
     channels: HashMap<String, HashMap<String, Sender<RespMessage>>>,
+    /// Suscripciones a patrones: pattern -> { client_id -> sender al cliente }
+    patterns: PatternSubscribers,
 }
 
 impl ChannelManager {
@@ -94,6 +207,7 @@ impl ChannelManager {
         Self {
             receiver,
             channels: HashMap::new(),
+            patterns: HashMap::new(),
         }
     }
 
@@ -138,9 +252,27 @@ impl ChannelManager {
             Command::Unsubscribe(channel_id) => {
                 self.handle_unsubscribe(client_id, channel_id, response_sender)
             }
+            Command::PSubscribe(patterns) => {
+                add_pattern_subscriptions(&mut self.patterns, &client_id, patterns, &client_sender);
+                self.send_response(response_sender, "".to_string())
+            }
+            Command::PUnsubscribe(patterns) => {
+                remove_pattern_subscriptions(&mut self.patterns, &client_id, &patterns);
+                self.send_response(response_sender, "".to_string())
+            }
             Command::Publish(channel_id, message) => {
                 self.handle_publish(channel_id, message, response_sender)
             }
+            // La respuesta no es un string: se envía por el sender del cliente
+            Command::Pubsub(subcommand) => {
+                if client_sender
+                    .send(pubsub_reply(&self.channels, &subcommand))
+                    .is_err()
+                {
+                    println!("[CHANNEL-MNG] Error al responder PUBSUB a {}", client_id);
+                }
+                Ok(())
+            }
             _ => self.send_response(
                 response_sender,
                 "Comando no soportado en ChannelManager".to_string(),
@@ -248,15 +380,17 @@ impl ChannelManager {
 
         if let Some(subs) = self.channels.get(&channel_id) {
             // Enviar mensaje a todos los suscriptores
+            let push = RespMessage::pubsub_message(&channel_id, message.clone());
             for (_sub_id, sub_sender) in subs {
-                if let Err(_) = sub_sender.send(message.clone()) {
+                if sub_sender.send(push.clone()).is_err() {
                     println!("[CHANNEL-MNG] Error al propagarle pubsub a {}", _sub_id);
                     //return Err(ChannelManagerError::SendToSubscriberError(e.to_string()));
                 }
                 subscriber_count += 1;
             }
         }
-        // Si el canal no existe, subscriber_count será 0
+        subscriber_count += publish_to_patterns(&self.patterns, &channel_id, &message);
+        // Si nadie está suscripto, subscriber_count será 0
 
         // Confirmar publicación exitosa con el número de suscriptores
         self.send_response(response_sender, subscriber_count.to_string())
@@ -330,6 +464,15 @@ impl ChannelManager {
         self.channels.keys().cloned().collect()
     }
 
+    /// Obtiene los canales con al menos un suscriptor, ordenados por nombre.
+    ///
+    /// # Returns
+    ///
+    /// `Vec<String>` - Lista de IDs de canales
+    pub fn active_channels(&self) -> Vec<String> {
+        active_channels_of(&self.channels)
+    }
+
     /// Obtiene una lista de suscriptores de un canal específico.
     ///
     /// # Arguments
@@ -494,6 +637,80 @@ mod tests {
         drop(sender);
     }
 
+    /// Crea un gestor con `Winston` y `Tracer` en `doc:1`, `Mercy` en
+    /// `doc:2` y el canal `chat` sin suscriptores.
+    fn manager_with_collaborators() -> ChannelManager {
+        let (_, receiver) = mpsc::channel();
+        let mut manager = ChannelManager::new(receiver);
+        for (channel_id, clients) in [
+            ("doc:1", vec!["Winston", "Tracer"]),
+            ("doc:2", vec!["Mercy"]),
+            ("chat", vec![]),
+        ] {
+            let mut subs = HashMap::new();
+            for client_id in clients {
+                let (client_sender, _) = mpsc::channel();
+                subs.insert(client_id.to_string(), client_sender);
+            }
+            manager.channels.insert(channel_id.to_string(), subs);
+        }
+        manager
+    }
+
+    #[test]
+    fn test_active_channels() {
+        let manager = manager_with_collaborators();
+        assert_eq!(manager.active_channels(), vec!["doc:1", "doc:2"]);
+        assert_eq!(manager.subscriber_count("doc:1"), Some(2));
+    }
+
+    #[test]
+    fn test_pubsub_channels_and_numsub() {
+        let mut manager = manager_with_collaborators();
+        let (response_sender, _) = mpsc::channel();
+        let (client_sender, client_receiver) = mpsc::channel();
+        let text = |value: &str| RespMessage::BulkString(Some(value.as_bytes().to_vec()));
+        let mut pubsub = |subcommand: PubsubSubcommand| {
+            manager
+                .handle_command(
+                    "Echo".to_string(),
+                    Command::Pubsub(subcommand),
+                    response_sender.clone(),
+                    client_sender.clone(),
+                )
+                .unwrap();
+            client_receiver.recv().unwrap()
+        };
+
+        assert_eq!(
+            pubsub(PubsubSubcommand::Channels(None)),
+            RespMessage::Array(vec![text("doc:1"), text("doc:2")])
+        );
+        assert_eq!(
+            pubsub(PubsubSubcommand::Channels(Some("doc:[2-9]".to_string()))),
+            RespMessage::Array(vec![text("doc:2")])
+        );
+        assert_eq!(
+            pubsub(PubsubSubcommand::Channels(Some("chat*".to_string()))),
+            RespMessage::Array(vec![])
+        );
+        assert_eq!(
+            pubsub(PubsubSubcommand::NumSub(vec![
+                "doc:1".to_string(),
+                "chat".to_string(),
+                "doc:3".to_string()
+            ])),
+            RespMessage::Array(vec![
+                text("doc:1"),
+                RespMessage::Integer(2),
+                text("chat"),
+                RespMessage::Integer(0),
+                text("doc:3"),
+                RespMessage::Integer(0),
+            ])
+        );
+    }
+
     #[test]
     fn test_handle_subscribe_success() {
         let (sender, receiver) = mpsc::channel();
@@ -655,9 +872,12 @@ mod tests {
 
         assert!(result.is_ok());
 
-        // Verificar que el suscriptor recibió el mensaje
+        // Verificar que el suscriptor recibió el mensaje como push
         let received_message = client_receiver.recv().unwrap();
-        assert_eq!(received_message, message);
+        assert_eq!(
+            received_message,
+            RespMessage::pubsub_message("test_channel", message)
+        );
 
         // Verificar respuesta de confirmación
         let response = response_receiver.recv().unwrap();
@@ -666,6 +886,80 @@ mod tests {
         drop(sender);
     }
 
+    #[test]
+    fn test_handle_publish_to_pattern_subscribers() {
+        let (sender, receiver) = mpsc::channel();
+        let mut manager = ChannelManager::new(receiver);
+        let (client_sender, client_receiver) = mpsc::channel();
+        let (response_sender, response_receiver) = mpsc::channel();
+
+        manager
+            .handle_command(
+                "client1".to_string(),
+                Command::PSubscribe(vec!["hero:*".to_string(), "hero:T*".to_string()]),
+                response_sender.clone(),
+                client_sender.clone(),
+            )
+            .unwrap();
+        assert_eq!(response_receiver.recv().unwrap(), "");
+
+        // El canal cumple ambos patrones: llega un pmessage por cada uno
+        let message = RespMessage::SimpleString("Blink".to_string());
+        manager
+            .handle_publish(
+                "hero:Tracer".to_string(),
+                message.clone(),
+                response_sender.clone(),
+            )
+            .unwrap();
+        assert_eq!(response_receiver.recv().unwrap(), "2");
+        let mut received = vec![
+            client_receiver.recv().unwrap(),
+            client_receiver.recv().unwrap(),
+        ];
+        received.sort_by_key(|push| format!("{:?}", push));
+        assert_eq!(
+            received,
+            vec![
+                RespMessage::pubsub_pmessage("hero:*", "hero:Tracer", message.clone()),
+                RespMessage::pubsub_pmessage("hero:T*", "hero:Tracer", message.clone()),
+            ]
+        );
+
+        manager
+            .handle_command(
+                "client1".to_string(),
+                Command::PUnsubscribe(vec!["hero:T*".to_string()]),
+                response_sender.clone(),
+                client_sender.clone(),
+            )
+            .unwrap();
+        assert_eq!(response_receiver.recv().unwrap(), "");
+        manager
+            .handle_publish(
+                "map:Ilios".to_string(),
+                message.clone(),
+                response_sender.clone(),
+            )
+            .unwrap();
+        assert_eq!(response_receiver.recv().unwrap(), "0");
+
+        // Sin patrones, PUNSUBSCRIBE deja todos
+        manager
+            .handle_command(
+                "client1".to_string(),
+                Command::PUnsubscribe(vec![]),
+                response_sender,
+                client_sender,
+            )
+            .unwrap();
+        assert_eq!(response_receiver.recv().unwrap(), "");
+        assert!(manager.patterns.is_empty());
+        assert!(client_receiver.try_recv().is_err());
+
+        drop(sender);
+    }
+
     #[test]
     fn test_handle_publish_channel_not_exists() {
         let (sender, receiver) = mpsc::channel();
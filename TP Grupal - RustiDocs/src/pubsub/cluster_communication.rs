@@ -197,6 +197,7 @@ impl ClusterCommunicationManager {
                 channel,
                 message: msg,
                 source_node,
+                publisher,
             } => {
                 let mut data = Vec::new();
                 data.push(2); // Tipo: Publish
@@ -206,6 +207,11 @@ impl ClusterCommunicationManager {
                 data.extend_from_slice(msg.as_bytes());
                 data.extend_from_slice(&(source_node.len() as u16).to_be_bytes());
                 data.extend_from_slice(source_node.as_bytes());
+                // El publicador va al final y es opcional
+                if let Some(publisher) = publisher {
+                    data.extend_from_slice(&(publisher.len() as u16).to_be_bytes());
+                    data.extend_from_slice(publisher.as_bytes());
+                }
                 Ok(data)
             }
         }
@@ -354,11 +360,27 @@ impl ClusterCommunicationManager {
 
                 let source_node =
                     String::from_utf8_lossy(&data[offset..offset + source_len]).to_string();
+                offset += source_len;
+
+                let publisher = if data.len() < offset + 2 {
+                    None
+                } else {
+                    let publisher_len =
+                        u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
+                    offset += 2;
+                    if data.len() < offset + publisher_len {
+                        return Err(ClusterCommunicationError::SerializationError(
+                            "Datos insuficientes para publisher en Publish".to_string(),
+                        ));
+                    }
+                    Some(String::from_utf8_lossy(&data[offset..offset + publisher_len]).to_string())
+                };
 
                 Ok(PubSubMessage::Publish {
                     channel,
                     message,
                     source_node,
+                    publisher,
                 })
             }
             _ => Err(ClusterCommunicationError::SerializationError(format!(
@@ -449,6 +471,7 @@ mod tests {
             channel: "test_channel".to_string(),
             message: "Hello, World!".to_string(),
             source_node: "node1".to_string(),
+            publisher: None,
         };
 
         let serialized = ClusterCommunicationManager::serialize_pubsub_message(&message).unwrap();
@@ -460,14 +483,35 @@ mod tests {
             channel,
             message: msg,
             source_node,
+            publisher,
         } = deserialized
         {
             assert_eq!(channel, "test_channel");
             assert_eq!(msg, "Hello, World!");
             assert_eq!(source_node, "node1");
+            assert_eq!(publisher, None);
         }
     }
 
+    #[test]
+    fn test_serialize_deserialize_publish_keeps_publisher() {
+        let message = PubSubMessage::Publish {
+            channel: "INDEX".to_string(),
+            message: "Roster".to_string(),
+            source_node: "node1".to_string(),
+            publisher: Some("Illari".to_string()),
+        };
+
+        let serialized = ClusterCommunicationManager::serialize_pubsub_message(&message).unwrap();
+        let deserialized =
+            ClusterCommunicationManager::deserialize_pubsub_message(&serialized).unwrap();
+
+        let PubSubMessage::Publish { publisher, .. } = deserialized else {
+            panic!("Se esperaba un Publish");
+        };
+        assert_eq!(publisher, Some("Illari".to_string()));
+    }
+
     #[test]
     fn test_error_display() {
         let error = ClusterCommunicationError::SendError("connection failed".to_string());
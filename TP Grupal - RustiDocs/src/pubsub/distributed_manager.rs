@@ -1,6 +1,10 @@
 use crate::cluster::types::{KnownNode, NodeId};
 use crate::command::types::Command;
 use crate::network::resp_message::RespMessage;
+use crate::pubsub::channel_manager::{
+    PatternSubscribers, active_channels_of, add_pattern_subscriptions, publish_to_patterns,
+    pubsub_reply, remove_pattern_subscriptions,
+};
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::mpsc::{Receiver, Sender, TryRecvError};
@@ -100,6 +104,8 @@ pub enum PubSubMessage {
         channel: String,
         message: String,
         source_node: NodeId,
+        /// Usuario que hizo el `PUBLISH`, si el servidor lo marcó
+        publisher: Option<String>,
     },
 }
 
@@ -115,6 +121,8 @@ pub enum PubSubMessage {
 /// - Un cliente puede hacer PUBLISH aunque no esté suscrito a un canal
 /// - Si un canal se queda sin suscriptores, se elimina automáticamente
 /// - Los mensajes se propagan a todos los nodos del cluster
+/// - Las suscripciones a patrones son locales: como toda publicación se
+///   propaga a todos los nodos, cada nodo entrega a sus propios suscriptores
 pub struct DistributedPubSubManager {
     /// Receptor de mensajes locales
     receiver: Receiver<(String, Command, Sender<String>, Sender<RespMessage>)>,
@@ -122,6 +130,8 @@ pub struct DistributedPubSubManager {
     cluster_receiver: Receiver<PubSubMessage>,
     /// Mapa de canales locales: channel_id -> { client_id -> sender al cliente }
     local_channels: HashMap<String, HashMap<String, Sender<RespMessage>>>,
+    /// Suscripciones locales a patrones: pattern -> { client_id -> sender al cliente }
+    local_patterns: PatternSubscribers,
     /// Mapa de suscriptores remotos: channel_id -> HashSet<NodeId>
     remote_subscribers: HashMap<String, HashSet<NodeId>>,
     /// ID del nodo local
@@ -157,6 +167,7 @@ impl DistributedPubSubManager {
             receiver,
             cluster_receiver,
             local_channels: HashMap::new(),
+            local_patterns: HashMap::new(),
             remote_subscribers: HashMap::new(),
             local_node_id,
             known_nodes,
@@ -251,9 +262,27 @@ impl DistributedPubSubManager {
             Command::Unsubscribe(channel_id) => {
                 self.handle_unsubscribe(client_id, channel_id, response_sender)
             }
+            Command::PSubscribe(patterns) => {
+                add_pattern_subscriptions(
+                    &mut self.local_patterns,
+                    &client_id,
+                    patterns,
+                    &client_sender,
+                );
+                self.send_response(response_sender, "".to_string())
+            }
+            Command::PUnsubscribe(patterns) => {
+                remove_pattern_subscriptions(&mut self.local_patterns, &client_id, &patterns);
+                self.send_response(response_sender, "".to_string())
+            }
             Command::Publish(channel_id, message) => {
                 self.handle_publish(channel_id, message, response_sender)
             }
+            // La respuesta no es un string: se envía por el sender del cliente.
+            // Sólo se cuentan los suscriptores de este nodo.
+            Command::Pubsub(subcommand) => client_sender
+                .send(pubsub_reply(&self.local_channels, &subcommand))
+                .map_err(|e| DistributedPubSubError::SendResponseError(e.to_string())),
             _ => Err(DistributedPubSubError::UnsupportedCommandError(format!(
                 "Comando no soportado: {:?}",
                 command
@@ -406,14 +435,16 @@ impl DistributedPubSubManager {
 
         // Enviar mensaje a suscriptores locales
         if let Some(subscribers) = self.local_channels.get(&channel_id) {
+            let push = RespMessage::pubsub_message(&channel_id, message.clone());
             for (client_id, sender) in subscribers {
-                if let Err(e) = sender.send(message.clone()) {
+                if let Err(e) = sender.send(push.clone()) {
                     eprintln!("Error enviando mensaje a cliente {}: {}", client_id, e);
                 } else {
                     subscriber_count += 1;
                 }
             }
         }
+        subscriber_count += publish_to_patterns(&self.local_patterns, &channel_id, &message);
 
         // Propagar el mensaje a otros nodos (siempre, incluso si no hay suscriptores locales)
         if let Err(e) = self.propagate_publish(&channel_id, &message) {
@@ -497,7 +528,8 @@ impl DistributedPubSubManager {
         channel_id: &str,
         message: &RespMessage,
     ) -> Result<(), DistributedPubSubError> {
-        let message_str = match message {
+        let (publisher, message) = message.clone().split_publisher();
+        let message_str = match &message {
             RespMessage::BulkString(Some(bytes)) => String::from_utf8_lossy(bytes).to_string(),
             RespMessage::SimpleString(s) => s.clone(),
             RespMessage::Integer(i) => i.to_string(),
@@ -512,6 +544,7 @@ impl DistributedPubSubManager {
             channel: channel_id.to_string(),
             message: message_str,
             source_node: self.local_node_id.clone(),
+            publisher,
         };
 
         self.broadcast_to_cluster(pubsub_message)
@@ -614,6 +647,7 @@ impl DistributedPubSubManager {
                 channel,
                 message,
                 source_node,
+                publisher,
             } => {
                 let channel_clone = channel.clone();
                 println!(
@@ -633,7 +667,14 @@ impl DistributedPubSubManager {
                         .entry(channel.clone())
                         .or_insert_with(HashMap::new);
 
-                    let resp_message = RespMessage::SimpleString(message);
+                    let payload = match publisher {
+                        Some(user) => {
+                            RespMessage::with_publisher(RespMessage::SimpleString(message), &user)
+                        }
+                        None => RespMessage::SimpleString(message),
+                    };
+                    publish_to_patterns(&self.local_patterns, &channel, &payload);
+                    let resp_message = RespMessage::pubsub_message(&channel, payload);
                     if let Some(subscribers) = self.local_channels.get(&channel) {
                         println!(
                             "[DISTRIBUTED_PUBSUB] Encontrados {} suscriptores locales para canal '{}'",
@@ -716,6 +757,10 @@ impl DistributedPubSubManager {
         self.local_channels.keys().cloned().collect()
     }
 
+    pub fn active_channels(&self) -> Vec<String> {
+        active_channels_of(&self.local_channels)
+    }
+
     pub fn get_subscribers(&self, channel_id: &str) -> Option<Vec<String>> {
         self.local_channels
             .get(channel_id)
@@ -726,6 +771,8 @@ impl DistributedPubSubManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::network::presence::{PresenceMessage, presence_channel};
+    use crate::network::resp_message::RespProtocol;
     use std::sync::mpsc;
 
     fn create_test_manager() -> (
@@ -784,6 +831,46 @@ mod tests {
         assert_eq!(manager.channel_count(), 1);
     }
 
+    #[test]
+    fn test_presence_update_reaches_only_its_document() {
+        let (mut manager, _, _, _) = create_test_manager();
+        let (doc1_tx, doc1_rx) = mpsc::channel();
+        let (doc2_tx, doc2_rx) = mpsc::channel();
+        for (client_id, document, client_tx) in
+            [("client1", "doc1", doc1_tx), ("client2", "doc2", doc2_tx)]
+        {
+            manager
+                .handle_command(
+                    client_id.to_string(),
+                    Command::Subscribe(presence_channel(document)),
+                    mpsc::channel().0,
+                    client_tx,
+                )
+                .unwrap();
+        }
+
+        let update = PresenceMessage {
+            client_id: 3,
+            document: "doc1".to_string(),
+            position: 42,
+        };
+        let _ = manager.handle_command(
+            "client3".to_string(),
+            Command::Publish(presence_channel("doc1"), update.payload()),
+            mpsc::channel().0,
+            mpsc::channel().0,
+        );
+
+        let delivered: Vec<PresenceMessage> = doc1_rx
+            .try_iter()
+            .filter_map(|push| PresenceMessage::from_resp(&push.for_protocol(RespProtocol::Resp2)))
+            .collect();
+        assert_eq!(delivered, vec![update]);
+        assert!(doc2_rx.try_iter().all(|push| {
+            PresenceMessage::from_resp(&push.for_protocol(RespProtocol::Resp2)).is_none()
+        }));
+    }
+
     #[test]
     fn test_error_display() {
         let error = DistributedPubSubError::NetworkError("connection failed".to_string());
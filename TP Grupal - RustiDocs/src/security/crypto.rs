@@ -1,7 +1,9 @@
 //! Módulo de criptografía básica usando solo la biblioteca estándar
 //!
 //! Implementa algoritmos de encriptación simétrica y funciones hash
-//! usando operaciones matemáticas básicas.
+//! usando operaciones matemáticas básicas, y SHA-256, HMAC y PBKDF2 para
+//! guardar contraseñas. Sólo las sales salen del generador aleatorio del
+//! sistema operativo, a través de `rand`.
 
 use std::io::{Error as IoError, Read, Write};
 use std::net::TcpStream;
@@ -89,8 +91,184 @@ pub fn simple_hash(data: &[u8]) -> u64 {
     hash
 }
 
+/// Iteraciones de PBKDF2 en `hash_password`, para encarecer la prueba de
+/// contraseñas
+const PASSWORD_HASH_ITERATIONS: u32 = 10_000;
+
+/// Largo en bytes de un hash SHA-256
+pub const SHA256_LEN: usize = 32;
+
+/// Largo en bytes de un bloque de SHA-256
+const SHA256_BLOCK_LEN: usize = 64;
+
+/// Constantes de ronda de SHA-256 (FIPS 180-4)
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Estado inicial de SHA-256 (FIPS 180-4)
+const SHA256_INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Procesa un bloque de 64 bytes sobre el estado de SHA-256.
+fn sha256_compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+    for (i, word) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA256_K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (value, added) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *value = value.wrapping_add(added);
+    }
+}
+
+/// Completa SHA-256 sobre `data`, partiendo de `state` que ya procesó
+/// `processed` bytes.
+fn sha256_finish(mut state: [u32; 8], processed: usize, data: &[u8]) -> [u8; SHA256_LEN] {
+    let mut blocks = data.chunks_exact(SHA256_BLOCK_LEN);
+    for block in &mut blocks {
+        sha256_compress(&mut state, block);
+    }
+    let bit_len = ((processed + data.len()) as u64).wrapping_mul(8);
+    let mut tail = blocks.remainder().to_vec();
+    tail.push(0x80);
+    while tail.len() % SHA256_BLOCK_LEN != SHA256_BLOCK_LEN - 8 {
+        tail.push(0);
+    }
+    tail.extend_from_slice(&bit_len.to_be_bytes());
+    for block in tail.chunks_exact(SHA256_BLOCK_LEN) {
+        sha256_compress(&mut state, block);
+    }
+
+    let mut digest = [0u8; SHA256_LEN];
+    for (bytes, value) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+/// Hash SHA-256 (FIPS 180-4).
+pub fn sha256(data: &[u8]) -> [u8; SHA256_LEN] {
+    sha256_finish(SHA256_INITIAL_STATE, 0, data)
+}
+
+/// HMAC-SHA256 con la clave ya procesada: el estado de SHA-256 después de
+/// los bloques `ipad` y `opad`, que no cambian entre mensajes.
+struct HmacSha256 {
+    inner: [u32; 8],
+    outer: [u32; 8],
+}
+
+impl HmacSha256 {
+    fn new(key: &[u8]) -> Self {
+        let mut block = [0u8; SHA256_BLOCK_LEN];
+        if key.len() > SHA256_BLOCK_LEN {
+            block[..SHA256_LEN].copy_from_slice(&sha256(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let pad = |byte: u8| {
+            let mut state = SHA256_INITIAL_STATE;
+            let padded: Vec<u8> = block.iter().map(|b| b ^ byte).collect();
+            sha256_compress(&mut state, &padded);
+            state
+        };
+        Self {
+            inner: pad(0x36),
+            outer: pad(0x5c),
+        }
+    }
+
+    fn mac(&self, data: &[u8]) -> [u8; SHA256_LEN] {
+        let inner = sha256_finish(self.inner, SHA256_BLOCK_LEN, data);
+        sha256_finish(self.outer, SHA256_BLOCK_LEN, &inner)
+    }
+}
+
+/// HMAC-SHA256 (RFC 2104).
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; SHA256_LEN] {
+    HmacSha256::new(key).mac(data)
+}
+
+/// PBKDF2-HMAC-SHA256 (RFC 8018) con una clave derivada de 32 bytes.
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; SHA256_LEN] {
+    let hmac = HmacSha256::new(password);
+    let mut first = salt.to_vec();
+    first.extend_from_slice(&1u32.to_be_bytes());
+
+    let mut block = hmac.mac(&first);
+    let mut derived = block;
+    for _ in 1..iterations {
+        block = hmac.mac(&block);
+        for (byte, next) in derived.iter_mut().zip(block) {
+            *byte ^= next;
+        }
+    }
+    derived
+}
+
+/// Compara dos secuencias en tiempo constante respecto de su contenido,
+/// para no filtrar por timing cuántos bytes coinciden.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Hash de una contraseña con sal, con PBKDF2-HMAC-SHA256.
+pub fn hash_password(salt: &[u8], password: &[u8]) -> [u8; SHA256_LEN] {
+    pbkdf2_hmac_sha256(password, salt, PASSWORD_HASH_ITERATIONS)
+}
+
+/// Genera una sal de `length` bytes con el generador aleatorio del sistema
+/// operativo.
+pub fn generate_salt(length: usize) -> Vec<u8> {
+    use rand::RngCore;
+    let mut salt = vec![0u8; length];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    salt
+}
+
 /// Algoritmo de encriptación simétrica simple (XOR con clave expandida)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SimpleCipher {
     key: Vec<u8>,
     key_hash: u64,
@@ -321,6 +499,63 @@ mod tests {
         assert_ne!(simple_hash(data1), simple_hash(data3));
     }
 
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_sha256_known_digests() {
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        assert_eq!(
+            hex(&hmac_sha256(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        // Clave más larga que un bloque
+        assert_eq!(
+            hex(&hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_pbkdf2_hmac_sha256_known_keys() {
+        assert_eq!(
+            hex(&pbkdf2_hmac_sha256(b"password", b"salt", 1)),
+            "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"
+        );
+        assert_eq!(
+            hex(&pbkdf2_hmac_sha256(b"password", b"salt", 4096)),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"Kiriko", b"Kiriko"));
+        assert!(!constant_time_eq(b"Kiriko", b"Kirika"));
+        assert!(!constant_time_eq(b"Kiriko", b"Kiri"));
+    }
+
     #[test]
     fn test_simple_cipher() {
         let key = b"secret_key_32_bytes_long_key".to_vec();
@@ -2,10 +2,30 @@
 //!
 //! Implementa un protocolo de handshake básico y encriptación
 //! para proteger las comunicaciones.
+//!
+//! Todo lo que se envía va en registros `tipo (1 byte) | largo (4 bytes,
+//! big endian) | payload`, de a lo sumo `MAX_RECORD_LEN` bytes de payload.
+//! El handshake es:
+//!
+//! 1. Cliente: `ClientHello` con un id de sesión de `SESSION_ID_LEN` bytes
+//! 2. Servidor: `ServerHello` con el mismo id de sesión
+//! 3. Servidor: `KeyExchange` con su material de clave
+//! 4. Cliente: `Finished` con el hash del id de sesión
+//! 5. Servidor: `Finished` con el mismo hash
+//!
+//! Luego los datos viajan cifrados en registros `ApplicationData`.
+//! Cualquier registro inesperado hace fallar el handshake, y quien lo
+//! detecta cierra la conexión.
 
 use crate::security::crypto::{CryptoError, SimpleCipher, SimpleRng, simple_hash};
-use std::io::{Error as IoError, Read, Write};
-use std::net::TcpStream;
+use std::io::{Error as IoError, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream};
+
+/// Largo del id de sesión que envía el cliente en el `ClientHello`
+pub const SESSION_ID_LEN: usize = 32;
+
+/// Largo máximo del payload de un registro
+pub const MAX_RECORD_LEN: usize = 16 * 1024 * 1024;
 
 /// Error que puede ocurrir durante operaciones TLS
 #[derive(Debug, Clone)]
@@ -165,6 +185,8 @@ pub struct TlsClientStream {
     stream: TcpStream,
     cipher: Option<SimpleCipher>,
     session_id: Vec<u8>,
+    /// Datos descifrados que no entraron en la última lectura
+    pending: Vec<u8>,
 }
 
 /// Stream TLS del servidor
@@ -173,6 +195,8 @@ pub struct TlsServerStream {
     stream: TcpStream,
     cipher: Option<SimpleCipher>,
     session_id: Vec<u8>,
+    /// Datos descifrados que no entraron en la última lectura
+    pending: Vec<u8>,
 }
 
 /// Stream TLS que puede ser cliente o servidor
@@ -187,6 +211,7 @@ impl TlsClientStream {
             stream,
             cipher: None,
             session_id: Vec::new(),
+            pending: Vec::new(),
         };
 
         client_stream.perform_handshake()?;
@@ -196,7 +221,7 @@ impl TlsClientStream {
     fn perform_handshake(&mut self) -> Result<(), TlsError> {
         // Paso 1: ClientHello
         let mut rng = SimpleRng::new_from_time();
-        self.session_id = rng.generate_bytes(32);
+        self.session_id = rng.generate_bytes(SESSION_ID_LEN);
 
         let client_hello = TlsMessage::new(TlsMessageType::ClientHello, self.session_id.clone());
 
@@ -255,18 +280,8 @@ impl TlsClientStream {
     }
 
     fn receive_message(&mut self) -> Result<TlsMessage, TlsError> {
-        let mut header = [0u8; 5];
-        self.stream.read_exact(&mut header)?;
-
-        let message_type = TlsMessageType::from_u8(header[0])
-            .ok_or_else(|| TlsError::Protocol("Tipo de mensaje inválido".to_string()))?;
-
-        let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
-
-        let mut payload = vec![0u8; length];
-        self.stream.read_exact(&mut payload)?;
-
-        Ok(TlsMessage::new(message_type, payload))
+        read_record(&mut self.stream)?
+            .ok_or_else(|| TlsError::Handshake("Conexión cerrada".to_string()))
     }
 }
 
@@ -276,6 +291,7 @@ impl TlsServerStream {
             stream,
             cipher: None,
             session_id: Vec::new(),
+            pending: Vec::new(),
         };
 
         server_stream.perform_handshake(config)?;
@@ -285,7 +301,9 @@ impl TlsServerStream {
     fn perform_handshake(&mut self, config: TlsServerConfig) -> Result<(), TlsError> {
         // Paso 1: Recibir ClientHello
         let client_hello = self.receive_message()?;
-        if client_hello.message_type != TlsMessageType::ClientHello {
+        if client_hello.message_type != TlsMessageType::ClientHello
+            || client_hello.payload.len() != SESSION_ID_LEN
+        {
             return Err(TlsError::Handshake("Esperaba ClientHello".to_string()));
         }
 
@@ -357,62 +375,96 @@ impl TlsServerStream {
     }
 
     fn receive_message(&mut self) -> Result<TlsMessage, TlsError> {
-        let mut header = [0u8; 5];
-        self.stream.read_exact(&mut header)?;
-
-        let message_type = TlsMessageType::from_u8(header[0])
-            .ok_or_else(|| TlsError::Protocol("Tipo de mensaje inválido".to_string()))?;
-
-        let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        read_record(&mut self.stream)?
+            .ok_or_else(|| TlsError::Handshake("Conexión cerrada".to_string()))
+    }
+}
 
-        let mut payload = vec![0u8; length];
-        self.stream.read_exact(&mut payload)?;
+impl TlsServerStream {
+    /// Clona el stream, compartiendo la sesión ya establecida, para que
+    /// un hilo lea y otro escriba sobre la misma conexión.
+    pub fn try_clone(&self) -> Result<Self, IoError> {
+        Ok(Self {
+            stream: self.stream.try_clone()?,
+            cipher: self.cipher.clone(),
+            session_id: self.session_id.clone(),
+            pending: Vec::new(),
+        })
+    }
 
-        Ok(TlsMessage::new(message_type, payload))
+    /// Cierra la conexión subyacente.
+    pub fn shutdown(&self, how: Shutdown) -> Result<(), IoError> {
+        self.stream.shutdown(how)
     }
 }
 
-impl Read for TlsClientStream {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
-        if let Some(cipher) = &self.cipher {
-            // Leer mensaje encriptado
-            let message = {
-                let mut header = [0u8; 5];
-                self.stream.read_exact(&mut header)?;
-
-                let message_type = TlsMessageType::from_u8(header[0]).ok_or_else(|| {
-                    IoError::new(std::io::ErrorKind::InvalidData, "Tipo de mensaje inválido")
-                })?;
+/// Lee un registro completo de la conexión.
+///
+/// # Returns
+///
+/// El registro leído, o `None` si la conexión se cerró antes de empezarlo
+fn read_record(stream: &mut TcpStream) -> Result<Option<TlsMessage>, IoError> {
+    let mut header = [0u8; 5];
+    if stream.read(&mut header[..1])? == 0 {
+        return Ok(None);
+    }
+    stream.read_exact(&mut header[1..])?;
 
-                let length =
-                    u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    let message_type = TlsMessageType::from_u8(header[0])
+        .ok_or_else(|| IoError::new(ErrorKind::InvalidData, "Tipo de mensaje inválido"))?;
 
-                let mut payload = vec![0u8; length];
-                self.stream.read_exact(&mut payload)?;
+    let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if length > MAX_RECORD_LEN {
+        return Err(IoError::new(
+            ErrorKind::InvalidData,
+            "Registro TLS demasiado largo",
+        ));
+    }
 
-                TlsMessage::new(message_type, payload)
-            };
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload)?;
 
-            if message.message_type != TlsMessageType::ApplicationData {
-                return Err(IoError::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Tipo de mensaje inválido",
-                ));
-            }
+    Ok(Some(TlsMessage::new(message_type, payload)))
+}
 
-            // Desencriptar datos
-            let decrypted = cipher.decrypt(&message.payload);
+/// Lee datos de aplicación descifrados. Lo que no entra en `buf` queda en
+/// `pending` para la próxima lectura.
+fn read_application_data(
+    stream: &mut TcpStream,
+    cipher: Option<&SimpleCipher>,
+    pending: &mut Vec<u8>,
+    buf: &mut [u8],
+) -> Result<usize, IoError> {
+    let cipher =
+        cipher.ok_or_else(|| IoError::new(ErrorKind::NotConnected, "Handshake no completado"))?;
+
+    if pending.is_empty() {
+        let Some(message) = read_record(stream)? else {
+            return Ok(0);
+        };
+        if message.message_type != TlsMessageType::ApplicationData {
+            return Err(IoError::new(
+                ErrorKind::InvalidData,
+                "Tipo de mensaje inválido",
+            ));
+        }
+        *pending = cipher.decrypt(&message.payload);
+    }
 
-            let copy_len = std::cmp::min(decrypted.len(), buf.len());
-            buf[..copy_len].copy_from_slice(&decrypted[..copy_len]);
+    let copy_len = std::cmp::min(pending.len(), buf.len());
+    buf[..copy_len].copy_from_slice(&pending[..copy_len]);
+    pending.drain(..copy_len);
+    Ok(copy_len)
+}
 
-            Ok(copy_len)
-        } else {
-            Err(IoError::new(
-                std::io::ErrorKind::NotConnected,
-                "Handshake no completado",
-            ))
-        }
+impl Read for TlsClientStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+        read_application_data(
+            &mut self.stream,
+            self.cipher.as_ref(),
+            &mut self.pending,
+            buf,
+        )
     }
 }
 
@@ -444,45 +496,12 @@ impl Write for TlsClientStream {
 
 impl Read for TlsServerStream {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
-        if let Some(cipher) = &self.cipher {
-            // Leer mensaje encriptado
-            let message = {
-                let mut header = [0u8; 5];
-                self.stream.read_exact(&mut header)?;
-
-                let message_type = TlsMessageType::from_u8(header[0]).ok_or_else(|| {
-                    IoError::new(std::io::ErrorKind::InvalidData, "Tipo de mensaje inválido")
-                })?;
-
-                let length =
-                    u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
-
-                let mut payload = vec![0u8; length];
-                self.stream.read_exact(&mut payload)?;
-
-                TlsMessage::new(message_type, payload)
-            };
-
-            if message.message_type != TlsMessageType::ApplicationData {
-                return Err(IoError::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Tipo de mensaje inválido",
-                ));
-            }
-
-            // Desencriptar datos
-            let decrypted = cipher.decrypt(&message.payload);
-
-            let copy_len = std::cmp::min(decrypted.len(), buf.len());
-            buf[..copy_len].copy_from_slice(&decrypted[..copy_len]);
-
-            Ok(copy_len)
-        } else {
-            Err(IoError::new(
-                std::io::ErrorKind::NotConnected,
-                "Handshake no completado",
-            ))
-        }
+        read_application_data(
+            &mut self.stream,
+            self.cipher.as_ref(),
+            &mut self.pending,
+            buf,
+        )
     }
 }
 
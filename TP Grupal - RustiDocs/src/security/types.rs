@@ -1,7 +1,108 @@
+use std::fmt;
+
+use crate::security::crypto::{SHA256_LEN, constant_time_eq, generate_salt, hash_password};
+
 pub type Password = String; // => ESTO ES PARA QUE PUEDA SER CAMBIADO POR UNA CONTRASEÑA CIFRADA SI HACE FALTA
 
+/// Largo en bytes de la sal de cada contraseña
+const SALT_LEN: usize = 16;
+
+/// Contraseña guardada como hash PBKDF2-HMAC-SHA256 con sal, sin conservar
+/// el texto plano.
+///
+/// Se escribe como `<sal en hex>$<hash en hex>`, el formato que acepta
+/// la ACL con el prefijo `#`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordHash {
+    salt: Vec<u8>,
+    hash: [u8; SHA256_LEN],
+}
+
+impl PasswordHash {
+    /// Hashea la contraseña con una sal nueva.
+    pub fn new(password: &str) -> Self {
+        let salt = generate_salt(SALT_LEN);
+        let hash = hash_password(&salt, password.as_bytes());
+        Self { salt, hash }
+    }
+
+    /// Lee un hash escrito como `<sal en hex>$<hash en hex>`.
+    pub fn parse(encoded: &str) -> Option<Self> {
+        let (salt, hash) = encoded.split_once('$')?;
+        let salt = decode_hex(salt).filter(|salt| !salt.is_empty())?;
+        let hash = decode_hex(hash)?.try_into().ok()?;
+        Some(Self { salt, hash })
+    }
+
+    /// Verifica la contraseña recalculando el hash con la sal guardada. La
+    /// comparación no depende de cuántos bytes coinciden.
+    pub fn matches(&self, password: &str) -> bool {
+        constant_time_eq(&hash_password(&self.salt, password.as_bytes()), &self.hash)
+    }
+}
+
+impl fmt::Display for PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.salt {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, "$")?;
+        for byte in &self.hash {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodifica bytes escritos en hexadecimal.
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValidationError {
     UserNotFound,
     IncorrectPassword,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_password_hash_matches_only_its_password() {
+        let hash = PasswordHash::new("1234");
+        assert!(hash.matches("1234"));
+        assert!(!hash.matches("12345"));
+        assert!(!hash.matches(""));
+    }
+
+    #[test]
+    fn test_same_password_gets_distinct_salts() {
+        let kiriko = PasswordHash::new("kitsune");
+        let hanzo = PasswordHash::new("kitsune");
+        assert_ne!(kiriko, hanzo);
+        assert_ne!(kiriko.to_string(), hanzo.to_string());
+        assert!(kiriko.matches("kitsune") && hanzo.matches("kitsune"));
+    }
+
+    #[test]
+    fn test_password_hash_round_trips_through_text() {
+        let hash = PasswordHash::new("Shimada");
+        let parsed = PasswordHash::parse(&hash.to_string()).unwrap();
+        assert_eq!(parsed, hash);
+        assert!(parsed.matches("Shimada"));
+
+        assert_eq!(PasswordHash::parse("Shimada"), None);
+        assert_eq!(PasswordHash::parse("abc$0123"), None);
+        assert_eq!(PasswordHash::parse("zz$0123"), None);
+        // El hash tiene que ser de SHA-256
+        assert_eq!(PasswordHash::parse("ab$0123456789abcdef"), None);
+    }
+}
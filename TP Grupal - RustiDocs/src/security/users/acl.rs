@@ -1,6 +1,7 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+use crate::security::types::PasswordHash;
 use crate::security::users::permissions::Permissions;
 use crate::security::users::user::User;
 use crate::security::{
@@ -34,26 +35,98 @@ pub fn load_users_from_acl(path: &str) -> std::io::Result<UserBase> {
             None => continue,
         };
 
-        // Password
-        let password_token = match parts.next() {
-            Some(p) if p.starts_with('>') => p.trim_start_matches('>').to_string(),
+        // Password: `>clave` en texto plano, que se hashea al cargarla, o
+        // `#sal$hash` ya hasheada
+        let password = match parts.next() {
+            Some(p) if p.starts_with('>') => PasswordHash::new(p.trim_start_matches('>')),
+            Some(p) if p.starts_with('#') => match PasswordHash::parse(&p[1..]) {
+                Some(hash) => hash,
+                None => continue,
+            },
             _ => continue,
         };
 
         let mut permissions = Permissions::new();
-        if parts.clone().last().unwrap_or(&"") == "*" {
+        let is_super = parts.clone().any(|token| token == "*");
+        if is_super {
             permissions.set_super();
-        } else {
-            for token in parts {
-                if let Some(instr) = token.strip_prefix('+') {
-                    permissions.add_instruction(instr.to_string());
-                }
+        }
+        for token in parts {
+            if let Some(pattern) = token.strip_prefix('~') {
+                permissions.add_key_pattern(pattern.to_string());
+            } else if token == "allkeys" {
+                permissions.add_key_pattern("*".to_string());
+            } else if let Some(instr) = token.strip_prefix('+').filter(|_| !is_super) {
+                permissions.add_instruction(instr.to_uppercase());
             }
         }
 
-        let user = User::new(username, password_token, permissions);
+        let user = User::with_password_hash(username, password, permissions);
         user_base.add_user(user);
     }
 
     Ok(user_base)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn load(acl: &str) -> UserBase {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(acl.as_bytes()).unwrap();
+        load_users_from_acl(file.path().to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_super_user_can_run_every_command() {
+        let user_base = load("user super >1234 *\n");
+        let permissions = user_base
+            .validate_user("super", &"1234".to_string())
+            .unwrap();
+
+        for command in ["GET", "SET", "CLUSTER", "CLIENT", "PUBLISH", "FLUSHDB"] {
+            assert!(permissions.is_permited(command), "{}", command);
+        }
+        assert!(permissions.can_access_key("Hanamura"));
+    }
+
+    #[test]
+    fn test_user_commands_and_key_patterns_from_acl() {
+        let user_base = load("# lectura de mapas\nuser lector >lectura +get +EXISTS ~map:*\n");
+        let permissions = user_base
+            .validate_user("lector", &"lectura".to_string())
+            .unwrap();
+
+        assert!(permissions.is_permited("GET"));
+        assert!(permissions.is_permited("EXISTS"));
+        assert!(permissions.is_permited("get"));
+        assert!(!permissions.is_permited("SET"));
+        assert!(permissions.can_access_key("map:Ilios"));
+        assert!(!permissions.can_access_key("hero:Lucio"));
+    }
+
+    #[test]
+    fn test_hashed_password_from_acl() {
+        let hash = PasswordHash::new("Tracer");
+        let user_base = load(&format!(
+            "user lena #{} +GET\nuser emily #zz$1 +GET\n",
+            hash
+        ));
+
+        assert!(
+            user_base
+                .validate_user("lena", &"Tracer".to_string())
+                .is_ok()
+        );
+        assert!(
+            user_base
+                .validate_user("lena", &"Widow".to_string())
+                .is_err()
+        );
+        // Un hash mal escrito descarta al usuario
+        assert!(!user_base.user_exist("emily"));
+    }
+}
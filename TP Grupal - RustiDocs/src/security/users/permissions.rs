@@ -1,18 +1,38 @@
+use crate::command::utils::glob_match;
+
+/// Error devuelto cuando el usuario no tiene acceso a alguna de las claves
+/// del comando.
+pub const ERR_NOPERM_KEY: &str = "NOPERM No permissions to access a key";
+
+/// Error devuelto cuando el usuario no tiene permitido el comando.
+pub fn err_noperm_command(command: &str) -> String {
+    format!(
+        "NOPERM this user has no permissions to run the '{}' command",
+        command.to_lowercase()
+    )
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct Permissions {
     autorized_instructions: Vec<String>,
+    /// Patrones glob de las claves accesibles (`~patron` en la ACL). Sin
+    /// patrones el usuario accede a todas las claves.
+    key_patterns: Vec<String>,
 }
 
 impl Permissions {
     pub fn new() -> Self {
         Self {
             autorized_instructions: Vec::new(),
+            key_patterns: Vec::new(),
         }
     }
 
+    /// Verifica si el usuario puede ejecutar la instrucción, sin
+    /// distinguir mayúsculas en el nombre del comando
     pub fn is_permited(&self, instruction: &str) -> bool {
         self.autorized_instructions
-            .contains(&instruction.to_string())
+            .contains(&instruction.to_uppercase())
     }
 
     pub fn is_read_only(&self) -> bool {
@@ -28,6 +48,34 @@ impl Permissions {
         self.autorized_instructions.push(instruction);
     }
 
+    /// Agrega un patrón de claves accesibles por el usuario
+    pub fn add_key_pattern(&mut self, pattern: String) {
+        self.key_patterns.push(pattern);
+    }
+
+    /// Verifica si el usuario puede leer o escribir la clave
+    pub fn can_access_key(&self, key: &str) -> bool {
+        self.key_patterns.is_empty()
+            || self
+                .key_patterns
+                .iter()
+                .any(|pattern| glob_match(pattern, key))
+    }
+
+    /// Verifica si el usuario puede acceder a todas las claves que cumplen
+    /// el patrón. Como no se puede decidir en general si un patrón queda
+    /// contenido en otro, solo se acepta si el usuario accede a todas las
+    /// claves, si el patrón es uno de los suyos o si es una clave literal
+    /// accesible.
+    pub fn can_access_pattern(&self, pattern: &str) -> bool {
+        self.key_patterns.is_empty()
+            || self
+                .key_patterns
+                .iter()
+                .any(|granted| granted == "*" || granted == pattern)
+            || (!pattern.contains(['*', '?', '[', '\\']) && self.can_access_key(pattern))
+    }
+
     /// Declara al usuario como super usuario, con acceso a todas las
     /// instrucciones que existen
     pub fn set_super(&mut self) {
@@ -35,11 +83,23 @@ impl Permissions {
         self.autorized_instructions.push("APPEND".to_string());
         self.autorized_instructions.push("ECHO".to_string());
         self.autorized_instructions.push("GET".to_string());
+        self.autorized_instructions.push("MGET".to_string());
+        self.autorized_instructions.push("MSET".to_string());
+        self.autorized_instructions.push("INCR".to_string());
+        self.autorized_instructions.push("DECR".to_string());
+        self.autorized_instructions.push("INCRBY".to_string());
+        self.autorized_instructions.push("DECRBY".to_string());
         self.autorized_instructions.push("GETDEL".to_string());
+        self.autorized_instructions.push("GETSET".to_string());
+        self.autorized_instructions.push("SETRANGE".to_string());
         self.autorized_instructions.push("GETRANGE".to_string());
         self.autorized_instructions.push("SET".to_string());
+        self.autorized_instructions.push("SETNX".to_string());
         self.autorized_instructions.push("STRLEN".to_string());
         self.autorized_instructions.push("SUBSTR".to_string());
+        self.autorized_instructions.push("PFADD".to_string());
+        self.autorized_instructions.push("PFCOUNT".to_string());
+        self.autorized_instructions.push("PFMERGE".to_string());
 
         // List commands
         self.autorized_instructions.push("DEL".to_string());
@@ -47,29 +107,96 @@ impl Permissions {
         self.autorized_instructions.push("LPOP".to_string());
         self.autorized_instructions.push("LPUSH".to_string());
         self.autorized_instructions.push("LRANGE".to_string());
+        self.autorized_instructions.push("LINDEX".to_string());
+        self.autorized_instructions.push("LSET".to_string());
+        self.autorized_instructions.push("LREM".to_string());
+        self.autorized_instructions.push("LINSERT".to_string());
+        self.autorized_instructions.push("LMOVE".to_string());
+        self.autorized_instructions.push("RPOPLPUSH".to_string());
         self.autorized_instructions.push("RPOP".to_string());
         self.autorized_instructions.push("RPUSH".to_string());
+        self.autorized_instructions.push("SORT".to_string());
 
         // Set commands
         self.autorized_instructions.push("SADD".to_string());
         self.autorized_instructions.push("SCARD".to_string());
         self.autorized_instructions.push("SISMEMBER".to_string());
         self.autorized_instructions.push("SMEMBERS".to_string());
+        self.autorized_instructions.push("SINTER".to_string());
+        self.autorized_instructions.push("SUNION".to_string());
+        self.autorized_instructions.push("SDIFF".to_string());
+        self.autorized_instructions.push("SRANDMEMBER".to_string());
         self.autorized_instructions.push("SMOVE".to_string());
         self.autorized_instructions.push("SPOP".to_string());
+        self.autorized_instructions.push("SREM".to_string());
+        self.autorized_instructions.push("HSET".to_string());
+        self.autorized_instructions.push("HGET".to_string());
+        self.autorized_instructions.push("HDEL".to_string());
+        self.autorized_instructions.push("HGETALL".to_string());
+        self.autorized_instructions.push("GEOADD".to_string());
+        self.autorized_instructions.push("GEOPOS".to_string());
+        self.autorized_instructions.push("GEODIST".to_string());
+        self.autorized_instructions.push("GEOSEARCH".to_string());
+
+        // Stream commands
+        self.autorized_instructions.push("XADD".to_string());
+        self.autorized_instructions.push("XLEN".to_string());
+        self.autorized_instructions.push("XRANGE".to_string());
+        self.autorized_instructions.push("XREAD".to_string());
+        self.autorized_instructions.push("XGROUP".to_string());
+        self.autorized_instructions.push("XREADGROUP".to_string());
+        self.autorized_instructions.push("XACK".to_string());
+        self.autorized_instructions.push("XPENDING".to_string());
 
         // Database commands
         self.autorized_instructions.push("BGSAVE".to_string());
         self.autorized_instructions.push("SAVE".to_string());
+        self.autorized_instructions.push("BGREWRITEAOF".to_string());
+        self.autorized_instructions.push("INFO".to_string());
+        self.autorized_instructions.push("DUMP".to_string());
+        self.autorized_instructions.push("EXISTS".to_string());
+        self.autorized_instructions.push("TOUCH".to_string());
+        self.autorized_instructions.push("EXPIRE".to_string());
+        self.autorized_instructions.push("PEXPIREAT".to_string());
+        self.autorized_instructions.push("PERSIST".to_string());
+        self.autorized_instructions.push("TTL".to_string());
+        self.autorized_instructions.push("TYPE".to_string());
+        self.autorized_instructions.push("RENAME".to_string());
+        self.autorized_instructions.push("RENAMENX".to_string());
+        self.autorized_instructions.push("DBSIZE".to_string());
+        self.autorized_instructions.push("FLUSHDB".to_string());
+        self.autorized_instructions.push("OBJECT".to_string());
+        self.autorized_instructions.push("COMMAND".to_string());
+        self.autorized_instructions.push("RESTORE".to_string());
+        self.autorized_instructions.push("DEBUG".to_string());
+        self.autorized_instructions.push("LATENCY".to_string());
+        self.autorized_instructions.push("SCAN".to_string());
+        self.autorized_instructions.push("KEYS".to_string());
 
         // PubSub commands
         self.autorized_instructions.push("SUBSCRIBE".to_string());
         self.autorized_instructions.push("UNSUBSCRIBE".to_string());
+        self.autorized_instructions.push("PSUBSCRIBE".to_string());
+        self.autorized_instructions.push("PUNSUBSCRIBE".to_string());
         self.autorized_instructions.push("PUBLISH".to_string());
+        self.autorized_instructions.push("PUBSUB".to_string());
 
         // Cluster commands
         self.autorized_instructions.push("MEET".to_string());
         self.autorized_instructions.push("CLUSTER".to_string());
+        self.autorized_instructions.push("FAILOVER".to_string());
+        self.autorized_instructions.push("WAIT".to_string());
+        self.autorized_instructions.push("REPLICAOF".to_string());
+        self.autorized_instructions.push("SYNC".to_string());
         self.autorized_instructions.push("PING".to_string());
+
+        // Connection commands
+        self.autorized_instructions.push("CLIENT".to_string());
+        self.autorized_instructions.push("HELLO".to_string());
+
+        // Transaction commands
+        self.autorized_instructions.push("MULTI".to_string());
+        self.autorized_instructions.push("EXEC".to_string());
+        self.autorized_instructions.push("DISCARD".to_string());
     }
 }
@@ -1,14 +1,25 @@
-use crate::security::types::Password;
+use crate::security::types::{Password, PasswordHash};
 use crate::security::users::permissions::Permissions;
 
 pub struct User {
     pub username: String,
-    password: Password,
+    password: PasswordHash,
     pub allowed_instructios: Permissions,
 }
 
 impl User {
+    /// Crea un usuario con una contraseña en texto plano, que se guarda
+    /// hasheada con una sal propia.
     pub fn new(username: String, password: Password, allowed_instructios: Permissions) -> Self {
+        Self::with_password_hash(username, PasswordHash::new(&password), allowed_instructios)
+    }
+
+    /// Crea un usuario con una contraseña ya hasheada.
+    pub fn with_password_hash(
+        username: String,
+        password: PasswordHash,
+        allowed_instructios: Permissions,
+    ) -> Self {
         User {
             username,
             password,
@@ -17,7 +28,7 @@ impl User {
     }
 
     pub fn is_password(&self, password: &Password) -> bool {
-        password == &self.password
+        self.password.matches(password)
     }
 
     pub fn get_permission(&self) -> Permissions {
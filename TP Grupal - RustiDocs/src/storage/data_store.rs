@@ -1,12 +1,74 @@
 use crate::cluster::utils::{read_string_from_buffer, read_u32_from_buffer, read_u64_from_buffer};
-use std::collections::{HashMap, HashSet};
+use crate::config::node_configs::EvictionPolicy;
+use crate::storage::stream::Stream;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::io::Read;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Largo máximo, en bytes, de un string guardado con encoding `embstr`.
+pub const EMBSTR_SIZE_LIMIT: usize = 44;
+
+/// Cantidad máxima de elementos de una lista guardada como `listpack`.
+pub const LISTPACK_MAX_ENTRIES: usize = 128;
+
+/// Cantidad máxima de miembros de un set de enteros guardado como `intset`.
+pub const INTSET_MAX_ENTRIES: usize = 512;
+
+/// Posición de la clave en el espacio del cursor de SCAN (FNV-1a de 64 bits).
+///
+/// A diferencia de los buckets del HashMap, la posición no depende de la
+/// capacidad del mapa, por lo que un rehash no altera el orden de recorrido.
+pub fn scan_position(key: &str) -> u64 {
+    key.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Encoding con el que Redis guardaría un valor de tipo string: `int` si
+/// es un entero en su forma canónica, `embstr` si es corto y `raw` si no.
+pub fn string_encoding(value: &str) -> &'static str {
+    let is_int = value
+        .parse::<i64>()
+        .is_ok_and(|number| number.to_string() == value);
+    if is_int {
+        "int"
+    } else if value.len() <= EMBSTR_SIZE_LIMIT {
+        "embstr"
+    } else {
+        "raw"
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct DataStore {
     pub string_db: HashMap<String, String>,
     pub list_db: HashMap<String, Vec<String>>,
     pub set_db: HashMap<String, HashSet<String>>,
+    pub stream_db: HashMap<String, Stream>,
+    pub hash_db: HashMap<String, HashMap<String, String>>,
+    /// Sorted sets: el score de cada miembro. Los comandos GEO guardan
+    /// acá el geohash de cada punto.
+    pub zset_db: HashMap<String, HashMap<String, f64>>,
+    /// Último acceso de cada clave, usado como recencia para LRU.
+    /// No se persiste en los snapshots.
+    pub access_times: HashMap<String, Instant>,
+    /// Cantidad de accesos de cada clave, usada como frecuencia para LFU.
+    /// No se persiste en los snapshots.
+    pub access_counts: HashMap<String, u64>,
+    /// Vencimiento de las claves con TTL. Los snapshots lo guardan como
+    /// instante absoluto, ver `expiry_deadlines`.
+    pub expires: HashMap<String, Instant>,
+    /// Los mismos vencimientos ordenados por instante, para que la
+    /// expiración activa encuentre las claves vencidas sin recorrer todas.
+    expiry_queue: BTreeSet<(Instant, String)>,
+    /// Memoria aproximada de cada clave la última vez que se escribió.
+    key_sizes: HashMap<String, usize>,
+    /// Suma de `key_sizes`, para consultar el uso sin recorrer el dataset.
+    used_memory: usize,
+    /// Las mismas claves ordenadas por `scan_position`: el cursor de SCAN
+    /// retoma el recorrido con una búsqueda en el árbol, sin ordenar todas
+    /// las claves en cada llamada.
+    scan_index: BTreeSet<(u64, String)>,
 }
 
 impl DataStore {
@@ -15,26 +77,631 @@ impl DataStore {
             string_db: HashMap::new(),
             list_db: HashMap::new(),
             set_db: HashMap::new(),
+            stream_db: HashMap::new(),
+            hash_db: HashMap::new(),
+            zset_db: HashMap::new(),
+            access_times: HashMap::new(),
+            access_counts: HashMap::new(),
+            expires: HashMap::new(),
+            expiry_queue: BTreeSet::new(),
+            key_sizes: HashMap::new(),
+            used_memory: 0,
+            scan_index: BTreeSet::new(),
         }
     }
 
     // Métodos para manipular la base de datos
     pub fn set(&mut self, key: String, value: String) {
-        self.string_db.insert(key, value);
+        self.string_db.insert(key.clone(), value);
+        self.track_key(&key);
     }
 
+    /// Valor string de la clave. Una clave vencida que todavía no se
+    /// purgó se trata como ausente.
     pub fn get(&self, key: &str) -> Option<&String> {
+        if self.is_expired(key) {
+            return None;
+        }
         self.string_db.get(key)
     }
 
+    /// Lista guardada en la clave, `None` si no hay o ya venció.
+    pub fn get_list(&self, key: &str) -> Option<&Vec<String>> {
+        if self.is_expired(key) {
+            return None;
+        }
+        self.list_db.get(key)
+    }
+
+    /// Set guardado en la clave, `None` si no hay o ya venció.
+    pub fn get_set(&self, key: &str) -> Option<&HashSet<String>> {
+        if self.is_expired(key) {
+            return None;
+        }
+        self.set_db.get(key)
+    }
+
+    /// Hash guardado en la clave, `None` si no hay o ya venció.
+    pub fn get_hash(&self, key: &str) -> Option<&HashMap<String, String>> {
+        if self.is_expired(key) {
+            return None;
+        }
+        self.hash_db.get(key)
+    }
+
+    /// Stream guardado en la clave, `None` si no hay o ya venció.
+    pub fn get_stream(&self, key: &str) -> Option<&Stream> {
+        if self.is_expired(key) {
+            return None;
+        }
+        self.stream_db.get(key)
+    }
+
+    /// Sorted set guardado en la clave, `None` si no hay o ya venció.
+    pub fn get_zset(&self, key: &str) -> Option<&HashMap<String, f64>> {
+        if self.is_expired(key) {
+            return None;
+        }
+        self.zset_db.get(key)
+    }
+
+    /// Indica si la clave tiene un vencimiento que ya pasó.
+    pub fn is_expired(&self, key: &str) -> bool {
+        self.expires
+            .get(key)
+            .is_some_and(|deadline| *deadline <= Instant::now())
+    }
+
+    /// Indica si la clave existe en alguna de las bases.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.string_db.contains_key(key)
+            || self.list_db.contains_key(key)
+            || self.set_db.contains_key(key)
+            || self.stream_db.contains_key(key)
+            || self.hash_db.contains_key(key)
+            || self.zset_db.contains_key(key)
+    }
+
+    /// Borra la clave si guarda una lista, un set, un hash o un sorted set
+    /// vacío, como
+    /// hace Redis con las colecciones que se quedan sin elementos. Los
+    /// streams vacíos se conservan.
+    ///
+    /// Devuelve `true` si la clave se borró.
+    pub fn remove_if_empty(&mut self, key: &str) -> bool {
+        let empty = self.list_db.get(key).is_some_and(|list| list.is_empty())
+            || self.set_db.get(key).is_some_and(|set| set.is_empty())
+            || self.hash_db.get(key).is_some_and(|hash| hash.is_empty())
+            || self.zset_db.get(key).is_some_and(|zset| zset.is_empty());
+        if empty {
+            self.list_db.remove(key);
+            self.set_db.remove(key);
+            self.hash_db.remove(key);
+            self.zset_db.remove(key);
+            self.remove_expiry(key);
+            self.access_times.remove(key);
+            self.access_counts.remove(key);
+        }
+        empty
+    }
+
+    /// Itera sobre todas las claves de todas las bases.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.string_db
+            .keys()
+            .chain(self.list_db.keys())
+            .chain(self.set_db.keys())
+            .chain(self.stream_db.keys())
+            .chain(self.hash_db.keys())
+            .chain(self.zset_db.keys())
+    }
+
+    /// Actualiza la recencia y la frecuencia de la clave. Si la clave ya
+    /// no existe se descarta su registro de acceso.
+    ///
+    /// Devuelve `true` si la clave existía.
+    pub fn touch(&mut self, key: &str) -> bool {
+        if self.contains_key(key) {
+            self.access_times.insert(key.to_string(), Instant::now());
+            *self.access_counts.entry(key.to_string()).or_insert(0) += 1;
+            true
+        } else {
+            self.access_times.remove(key);
+            self.access_counts.remove(key);
+            false
+        }
+    }
+
+    /// Devuelve el instante del último acceso registrado de la clave.
+    pub fn get_access_time(&self, key: &str) -> Option<&Instant> {
+        self.access_times.get(key)
+    }
+
+    /// Fija el tiempo de vida de la clave a partir de ahora, o lo quita
+    /// con `None` dejando la clave persistente.
+    pub fn set_ttl(&mut self, key: &str, ttl: Option<Duration>) {
+        match ttl {
+            Some(ttl) => self.insert_expiry(key.to_string(), Instant::now() + ttl),
+            None => {
+                self.remove_expiry(key);
+            }
+        }
+    }
+
+    /// Registra el vencimiento de la clave, reemplazando el anterior.
+    fn insert_expiry(&mut self, key: String, deadline: Instant) {
+        if let Some(previous) = self.expires.insert(key.clone(), deadline) {
+            self.expiry_queue.remove(&(previous, key.clone()));
+        }
+        self.expiry_queue.insert((deadline, key));
+    }
+
+    /// Quita el vencimiento de la clave. Devuelve `true` si tenía uno.
+    fn remove_expiry(&mut self, key: &str) -> bool {
+        match self.expires.remove(key) {
+            Some(deadline) => {
+                self.expiry_queue.remove(&(deadline, key.to_string()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Borra la clave de todas las bases, junto con su vencimiento y su
+    /// registro de acceso.
+    fn remove_entry(&mut self, key: &str) {
+        self.string_db.remove(key);
+        self.list_db.remove(key);
+        self.set_db.remove(key);
+        self.stream_db.remove(key);
+        self.hash_db.remove(key);
+        self.zset_db.remove(key);
+        self.remove_expiry(key);
+        self.access_times.remove(key);
+        self.access_counts.remove(key);
+        self.track_key(key);
+    }
+
+    /// Quita el vencimiento de la clave, dejándola persistente.
+    ///
+    /// Devuelve `true` si la clave tenía un vencimiento.
+    pub fn clear_expiry(&mut self, key: &str) -> bool {
+        self.remove_expiry(key)
+    }
+
+    /// Mueve el valor de `source` a `destination`, sea del tipo que sea,
+    /// pisando lo que hubiera en el destino. El vencimiento y el registro
+    /// de acceso viajan con el valor.
+    ///
+    /// Devuelve `false` si `source` no existe o ya venció.
+    pub fn rename(&mut self, source: &str, destination: &str) -> bool {
+        if !self.contains_key(source) || self.is_expired(source) {
+            return false;
+        }
+        if source == destination {
+            return true;
+        }
+        self.remove_entry(destination);
+
+        let destination = destination.to_string();
+        if let Some(value) = self.string_db.remove(source) {
+            self.string_db.insert(destination.clone(), value);
+        } else if let Some(list) = self.list_db.remove(source) {
+            self.list_db.insert(destination.clone(), list);
+        } else if let Some(set) = self.set_db.remove(source) {
+            self.set_db.insert(destination.clone(), set);
+        } else if let Some(stream) = self.stream_db.remove(source) {
+            self.stream_db.insert(destination.clone(), stream);
+        } else if let Some(hash) = self.hash_db.remove(source) {
+            self.hash_db.insert(destination.clone(), hash);
+        } else if let Some(zset) = self.zset_db.remove(source) {
+            self.zset_db.insert(destination.clone(), zset);
+        }
+        if let Some(deadline) = self.expires.get(source).copied() {
+            self.remove_expiry(source);
+            self.insert_expiry(destination.clone(), deadline);
+        }
+        if let Some(accessed) = self.access_times.remove(source) {
+            self.access_times.insert(destination.clone(), accessed);
+        }
+        if let Some(count) = self.access_counts.remove(source) {
+            self.access_counts.insert(destination, count);
+        }
+        true
+    }
+
+    /// Vencimientos de las claves existentes, como instantes absolutos en
+    /// milisegundos desde la época UNIX. Es la forma en que se persisten,
+    /// porque un `Instant` no sobrevive a un reinicio.
+    pub fn expiry_deadlines(&self) -> Vec<(&String, u64)> {
+        let now = SystemTime::now();
+        self.expires
+            .iter()
+            .filter(|(key, _)| self.contains_key(key))
+            .map(|(key, deadline)| {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let unix_ms = (now + remaining)
+                    .duration_since(UNIX_EPOCH)
+                    .map(|since_epoch| since_epoch.as_millis() as u64)
+                    .unwrap_or_default();
+                (key, unix_ms)
+            })
+            .collect()
+    }
+
+    /// Fija el vencimiento de la clave en un instante absoluto, en
+    /// milisegundos desde la época UNIX. Un instante que ya pasó deja la
+    /// clave vencida.
+    pub fn set_expiry_at(&mut self, key: &str, unix_ms: u64) {
+        let remaining = (UNIX_EPOCH + Duration::from_millis(unix_ms))
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+        self.insert_expiry(key.to_string(), Instant::now() + remaining);
+    }
+
+    /// Tiempo de vida restante de la clave, `None` si no vence.
+    pub fn remaining_ttl(&self, key: &str) -> Option<Duration> {
+        self.expires
+            .get(key)
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Indica si la clave tiene un vencimiento pendiente de aplicar: ya
+    /// pasó, o la clave ya no existe.
+    pub fn needs_expiry(&self, key: &str) -> bool {
+        self.expires.contains_key(key) && (self.is_expired(key) || !self.contains_key(key))
+    }
+
+    /// Expiración perezosa de una clave que toca un comando: la borra si
+    /// su vencimiento ya pasó, y descarta el vencimiento de una clave que
+    /// ya no existe para que, si se vuelve a crear, no lo herede.
+    ///
+    /// Devuelve `true` si la clave venció y se borró.
+    pub fn expire_if_due(&mut self, key: &str) -> bool {
+        if !self.contains_key(key) {
+            self.remove_expiry(key);
+            return false;
+        }
+        if !self.is_expired(key) {
+            return false;
+        }
+        self.remove_entry(key);
+        true
+    }
+
+    /// Instante del próximo vencimiento, si alguna clave tiene TTL.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.expiry_queue.first().map(|(deadline, _)| *deadline)
+    }
+
+    /// Expiración activa: borra hasta `limit` claves vencidas al instante
+    /// `now`, en orden de vencimiento. Cada llamada cuesta a lo sumo
+    /// `limit` pasos, sin importar cuántas claves tienen TTL.
+    ///
+    /// Devuelve las claves borradas.
+    pub fn purge_expired(&mut self, now: Instant, limit: usize) -> Vec<String> {
+        let mut expired = vec![];
+        while expired.len() < limit {
+            let Some((deadline, key)) = self.expiry_queue.first().cloned() else {
+                break;
+            };
+            if deadline > now {
+                break;
+            }
+            if self.contains_key(&key) {
+                self.remove_entry(&key);
+                expired.push(key);
+            } else {
+                self.remove_expiry(&key);
+            }
+        }
+        expired
+    }
+
+    /// Crea `count` claves `prefix:N` con valores `value:N`, sin pisar las
+    /// claves existentes. Con `size`, cada valor se completa con ceros o
+    /// se corta hasta medir esa cantidad de bytes.
+    ///
+    /// Devuelve la cantidad de claves creadas.
+    pub fn populate(&mut self, count: usize, prefix: &str, size: Option<usize>) -> usize {
+        let mut created = 0;
+        for i in 0..count {
+            let key = format!("{}:{}", prefix, i);
+            if self.contains_key(&key) {
+                continue;
+            }
+            let mut value = format!("value:{}", i);
+            if let Some(size) = size {
+                value.truncate(size);
+                while value.len() < size {
+                    value.push('0');
+                }
+            }
+            self.string_db.insert(key.clone(), value);
+            self.track_key(&key);
+            created += 1;
+        }
+        created
+    }
+
+    /// Tipo del valor de la clave, como lo reporta `TYPE`: `none` si la
+    /// clave no existe o ya venció.
+    pub fn type_of(&self, key: &str) -> &'static str {
+        if self.is_expired(key) {
+            "none"
+        } else if self.string_db.contains_key(key) {
+            "string"
+        } else if self.list_db.contains_key(key) {
+            "list"
+        } else if self.set_db.contains_key(key) {
+            "set"
+        } else if self.stream_db.contains_key(key) {
+            "stream"
+        } else if self.hash_db.contains_key(key) {
+            "hash"
+        } else if self.zset_db.contains_key(key) {
+            "zset"
+        } else {
+            "none"
+        }
+    }
+
+    /// Encoding del valor de la clave, como lo reporta `OBJECT ENCODING`.
+    /// Strings, listas y sets eligen el encoding según su contenido y
+    /// tamaño; streams, hashes y sorted sets reportan el de su
+    /// representación general.
+    pub fn encoding(&self, key: &str) -> Option<&'static str> {
+        if let Some(value) = self.string_db.get(key) {
+            Some(string_encoding(value))
+        } else if let Some(list) = self.list_db.get(key) {
+            if list.len() <= LISTPACK_MAX_ENTRIES {
+                Some("listpack")
+            } else {
+                Some("quicklist")
+            }
+        } else if let Some(set) = self.set_db.get(key) {
+            let is_intset = set.len() <= INTSET_MAX_ENTRIES
+                && set.iter().all(|member| member.parse::<i64>().is_ok());
+            if is_intset {
+                Some("intset")
+            } else {
+                Some("hashtable")
+            }
+        } else if self.stream_db.contains_key(key) {
+            Some("stream")
+        } else if self.hash_db.contains_key(key) {
+            Some("hashtable")
+        } else if self.zset_db.contains_key(key) {
+            Some("skiplist")
+        } else {
+            None
+        }
+    }
+
     pub fn len(&self) -> usize {
-        self.string_db.len() + self.list_db.len() + self.set_db.len()
+        self.string_db.len()
+            + self.list_db.len()
+            + self.set_db.len()
+            + self.stream_db.len()
+            + self.hash_db.len()
+            + self.zset_db.len()
+    }
+
+    /// Borra todas las claves de todas las bases, junto con sus
+    /// vencimientos y registros de acceso.
+    pub fn clear(&mut self) {
+        self.string_db.clear();
+        self.list_db.clear();
+        self.set_db.clear();
+        self.stream_db.clear();
+        self.hash_db.clear();
+        self.zset_db.clear();
+        self.access_times.clear();
+        self.access_counts.clear();
+        self.expires.clear();
+        self.expiry_queue.clear();
+        self.key_sizes.clear();
+        self.used_memory = 0;
+        self.scan_index.clear();
+    }
+
+    /// Memoria aproximada, en bytes, que ocupan las claves y sus valores,
+    /// sin contar el overhead de las estructuras que los guardan. Se lleva
+    /// al día clave por clave con `track_key`.
+    pub fn approx_memory_usage(&self) -> usize {
+        self.used_memory
+    }
+
+    /// Vuelve a medir la memoria de la clave tras modificarla y ajusta el
+    /// total, y la agrega o la quita del índice de SCAN si se creó o se
+    /// borró. Cuesta lo que mide el valor de esa clave, no el dataset.
+    pub fn track_key(&mut self, key: &str) {
+        let size = self.key_memory_usage(key);
+        let previous = if size == 0 {
+            self.key_sizes.remove(key)
+        } else {
+            self.key_sizes.insert(key.to_string(), size)
+        };
+        match (previous.is_some(), size > 0) {
+            (false, true) => {
+                self.scan_index
+                    .insert((scan_position(key), key.to_string()));
+            }
+            (true, false) => {
+                self.scan_index
+                    .remove(&(scan_position(key), key.to_string()));
+            }
+            _ => {}
+        }
+        self.used_memory = self.used_memory + size - previous.unwrap_or(0);
+    }
+
+    /// Visita las claves a partir de la posición `cursor` del índice de
+    /// SCAN. Las claves con la misma posición se visitan siempre juntas,
+    /// aunque superen `count`, para que el cursor no quede a mitad de ellas.
+    ///
+    /// Devuelve el próximo cursor, 0 si ya no quedan claves, y las claves
+    /// visitadas que todavía existen.
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<&String>) {
+        let mut visited: Vec<(u64, &String)> = Vec::new();
+        let mut next_cursor = 0;
+        for (position, key) in self.scan_index.range((cursor, String::new())..) {
+            let full = visited.len() >= count.max(1);
+            if full && visited.last().is_some_and(|(last, _)| last != position) {
+                next_cursor = *position;
+                break;
+            }
+            visited.push((*position, key));
+        }
+        let keys = visited
+            .into_iter()
+            .map(|(_, key)| key)
+            .filter(|key| self.contains_key(key))
+            .collect();
+        (next_cursor, keys)
+    }
+
+    /// Mide desde cero la memoria de todas las claves y rearma el índice
+    /// de SCAN, después de cargar el store entero de un snapshot.
+    pub fn recompute_memory_usage(&mut self) {
+        let sizes: HashMap<String, usize> = self
+            .keys()
+            .map(|key| (key.clone(), self.key_memory_usage(key)))
+            .collect();
+        self.used_memory = sizes.values().sum();
+        self.scan_index = sizes
+            .keys()
+            .map(|key| (scan_position(key), key.clone()))
+            .collect();
+        self.key_sizes = sizes;
+    }
+
+    /// Memoria aproximada de una clave y su valor, 0 si no existe.
+    fn key_memory_usage(&self, key: &str) -> usize {
+        let value = if let Some(value) = self.string_db.get(key) {
+            value.len()
+        } else if let Some(list) = self.list_db.get(key) {
+            list.iter().map(String::len).sum()
+        } else if let Some(set) = self.set_db.get(key) {
+            set.iter().map(String::len).sum()
+        } else if let Some(stream) = self.stream_db.get(key) {
+            stream
+                .entries()
+                .flat_map(|(_, fields)| fields)
+                .map(|(field, value)| field.len() + value.len())
+                .sum()
+        } else if let Some(hash) = self.hash_db.get(key) {
+            hash.iter()
+                .map(|(field, value)| field.len() + value.len())
+                .sum()
+        } else if let Some(zset) = self.zset_db.get(key) {
+            zset.keys()
+                .map(|member| member.len() + size_of::<f64>())
+                .sum()
+        } else {
+            return 0;
+        };
+        key.len() + value
+    }
+
+    /// Desaloja claves según `policy` hasta que la memoria aproximada no
+    /// supere `maxmemory`. Con LRU se eligen primero las claves accedidas
+    /// hace más tiempo y con LFU las accedidas menos veces; las que nunca
+    /// se accedieron van antes que todas. Las claves de `protected` no se
+    /// desalojan, así una escritura no pierde la clave que acaba de crear.
+    ///
+    /// Devuelve las claves desalojadas, en orden.
+    pub fn evict(
+        &mut self,
+        maxmemory: usize,
+        policy: EvictionPolicy,
+        protected: &[&String],
+    ) -> Vec<String> {
+        if policy == EvictionPolicy::NoEviction || self.used_memory <= maxmemory {
+            return vec![];
+        }
+
+        let mut candidates: Vec<String> = self
+            .keys()
+            .filter(|key| !protected.contains(key))
+            .cloned()
+            .collect();
+        candidates.sort_by_key(|key| {
+            let count = match policy {
+                EvictionPolicy::AllKeysLfu => self.access_counts.get(key).copied().unwrap_or(0),
+                _ => 0,
+            };
+            (count, self.access_times.get(key).copied())
+        });
+
+        let mut evicted = vec![];
+        for key in candidates {
+            if self.used_memory <= maxmemory {
+                break;
+            }
+            self.remove_entry(&key);
+            evicted.push(key);
+        }
+        evicted
     }
 
     pub fn update(&mut self, data_store: DataStore) {
         self.string_db = data_store.string_db;
         self.list_db = data_store.list_db;
         self.set_db = data_store.set_db;
+        self.stream_db = data_store.stream_db;
+        self.hash_db = data_store.hash_db;
+        self.zset_db = data_store.zset_db;
+        self.expires = data_store.expires;
+        self.expiry_queue = data_store.expiry_queue;
+        self.key_sizes = data_store.key_sizes;
+        self.used_memory = data_store.used_memory;
+        self.scan_index = data_store.scan_index;
+    }
+
+    /// Indica si ambos stores tienen el mismo contenido, sin considerar
+    /// los registros de acceso.
+    pub fn has_same_data(&self, other: &DataStore) -> bool {
+        self.string_db == other.string_db
+            && self.list_db == other.list_db
+            && self.set_db == other.set_db
+            && self.stream_db == other.stream_db
+            && self.hash_db == other.hash_db
+            && self.zset_db == other.zset_db
+    }
+
+    /// Indica si ambos stores tienen vencimiento en las mismas claves, sin
+    /// comparar los instantes.
+    pub fn has_same_expiring_keys(&self, other: &DataStore) -> bool {
+        let expiring = |store: &DataStore| -> HashSet<String> {
+            store
+                .expiry_deadlines()
+                .into_iter()
+                .map(|(key, _)| key.clone())
+                .collect()
+        };
+        expiring(self) == expiring(other)
+    }
+
+    /// Claves cuyo valor difiere entre los dos stores, incluidas las que
+    /// existen en uno solo. Se devuelven ordenadas y sin repetir.
+    pub fn differing_keys(&self, other: &DataStore) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .keys()
+            .chain(other.keys())
+            .filter(|key| {
+                self.string_db.get(*key) != other.string_db.get(*key)
+                    || self.list_db.get(*key) != other.list_db.get(*key)
+                    || self.set_db.get(*key) != other.set_db.get(*key)
+                    || self.stream_db.get(*key) != other.stream_db.get(*key)
+                    || self.hash_db.get(*key) != other.hash_db.get(*key)
+                    || self.zset_db.get(*key) != other.zset_db.get(*key)
+            })
+            .cloned()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
     }
 
     pub(crate) fn sync_database<T: Clone>(
@@ -102,11 +769,23 @@ impl DataStore {
             set_db.insert(key, set);
         }
 
-        Ok(DataStore {
+        let mut store = DataStore {
             string_db,
             list_db,
             set_db,
-        })
+            stream_db: HashMap::new(),
+            hash_db: HashMap::new(),
+            zset_db: HashMap::new(),
+            access_times: HashMap::new(),
+            access_counts: HashMap::new(),
+            expires: HashMap::new(),
+            expiry_queue: BTreeSet::new(),
+            key_sizes: HashMap::new(),
+            used_memory: 0,
+            scan_index: BTreeSet::new(),
+        };
+        store.recompute_memory_usage();
+        Ok(store)
     }
 
     pub fn serialize(&self) -> Vec<u8> {
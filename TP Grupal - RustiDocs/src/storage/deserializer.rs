@@ -2,10 +2,11 @@
 
 // IMPORTS
 use crate::storage::DataStore;
-use std::collections::{HashMap, HashSet};
+use crate::storage::stream::{ConsumerGroup, PendingEntry, Stream, StreamId};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io;
-use std::io::Read;
+use std::io::{BufRead, Read};
 
 // CONSTANTES
 const USIZE_BYTES_SIZE: usize = 8;
@@ -29,7 +30,10 @@ fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
 }
 
 /// Lee un hashmap de strings a strings.
-fn read_string_map(ds_src: &mut File, str_db: &mut HashMap<String, String>) -> io::Result<()> {
+fn read_string_map<R: Read>(
+    ds_src: &mut R,
+    str_db: &mut HashMap<String, String>,
+) -> io::Result<()> {
     let str_db_len = read_len(ds_src)?;
     for _ in 0..str_db_len {
         let key = read_string(ds_src)?;
@@ -40,7 +44,10 @@ fn read_string_map(ds_src: &mut File, str_db: &mut HashMap<String, String>) -> i
 }
 
 /// Lee un hashmap de strings a vectores de strings.
-fn read_list_map(ds_src: &mut File, list_db: &mut HashMap<String, Vec<String>>) -> io::Result<()> {
+fn read_list_map<R: Read>(
+    ds_src: &mut R,
+    list_db: &mut HashMap<String, Vec<String>>,
+) -> io::Result<()> {
     let list_db_len = read_len(ds_src)?;
     for _ in 0..list_db_len {
         let key = read_string(ds_src)?;
@@ -55,8 +62,8 @@ fn read_list_map(ds_src: &mut File, list_db: &mut HashMap<String, Vec<String>>)
 }
 
 /// Lee un hashmap de strings a hashsets de strings.
-fn read_set_map(
-    ds_src: &mut File,
+fn read_set_map<R: Read>(
+    ds_src: &mut R,
     set_db: &mut HashMap<String, HashSet<String>>,
 ) -> io::Result<()> {
     let set_db_len = read_len(ds_src)?;
@@ -72,13 +79,171 @@ fn read_set_map(
     Ok(())
 }
 
+/// Lee un ID de stream guardado como dos enteros de 8 bytes.
+fn read_stream_id<R: Read>(reader: &mut R) -> io::Result<StreamId> {
+    let ms = read_len(reader)? as u64;
+    let seq = read_len(reader)? as u64;
+    Ok(StreamId::new(ms, seq))
+}
+
+/// Lee un hashmap de strings a streams. Los dumps generados antes de
+/// que existieran los streams terminan antes de esta sección, en cuyo
+/// caso no se carga ningún stream.
+fn read_stream_map<R: Read>(
+    ds_src: &mut R,
+    stream_db: &mut HashMap<String, Stream>,
+) -> io::Result<()> {
+    let stream_db_len = match read_len(ds_src) {
+        Ok(len) => len,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for _ in 0..stream_db_len {
+        let key = read_string(ds_src)?;
+        let last_id = read_stream_id(ds_src)?;
+        let entries_len = read_len(ds_src)?;
+        let mut entries = BTreeMap::new();
+        for _ in 0..entries_len {
+            let id = read_stream_id(ds_src)?;
+            let fields_len = read_len(ds_src)?;
+            let mut fields = Vec::new();
+            for _ in 0..fields_len {
+                fields.push((read_string(ds_src)?, read_string(ds_src)?));
+            }
+            entries.insert(id, fields);
+        }
+        let groups_len = read_len(ds_src)?;
+        let mut groups = HashMap::new();
+        for _ in 0..groups_len {
+            let name = read_string(ds_src)?;
+            let mut group = ConsumerGroup {
+                last_delivered: read_stream_id(ds_src)?,
+                pending: BTreeMap::new(),
+            };
+            let pending_len = read_len(ds_src)?;
+            for _ in 0..pending_len {
+                let id = read_stream_id(ds_src)?;
+                let consumer = read_string(ds_src)?;
+                let deliveries = read_len(ds_src)? as u64;
+                group.pending.insert(
+                    id,
+                    PendingEntry {
+                        consumer,
+                        deliveries,
+                    },
+                );
+            }
+            groups.insert(name, group);
+        }
+        stream_db.insert(key, Stream::from_parts(entries, last_id, groups));
+    }
+    Ok(())
+}
+
+/// Lee un hashmap de strings a hashes. Igual que con los streams, los
+/// dumps generados antes de que existieran los hashes terminan antes de
+/// esta sección.
+fn read_hash_map<R: Read>(
+    ds_src: &mut R,
+    hash_db: &mut HashMap<String, HashMap<String, String>>,
+) -> io::Result<()> {
+    let hash_db_len = match read_len(ds_src) {
+        Ok(len) => len,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for _ in 0..hash_db_len {
+        let key = read_string(ds_src)?;
+        let mut hash = HashMap::new();
+        read_string_map(ds_src, &mut hash)?;
+        hash_db.insert(key, hash);
+    }
+    Ok(())
+}
+
+/// Lee los vencimientos de las claves, guardados como instantes absolutos.
+/// Los dumps generados antes de que se persistieran terminan antes de
+/// esta sección, y sus claves quedan sin vencimiento.
+fn read_expires<R: Read>(ds_src: &mut R, ds: &mut DataStore) -> io::Result<()> {
+    let expires_len = match read_len(ds_src) {
+        Ok(len) => len,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for _ in 0..expires_len {
+        let key = read_string(ds_src)?;
+        let unix_ms = read_len(ds_src)? as u64;
+        ds.set_expiry_at(&key, unix_ms);
+    }
+    Ok(())
+}
+
+/// Lee los sorted sets. Van al final del dump, así que los generados
+/// antes de que existieran terminan antes de esta sección.
+fn read_zset_map<R: Read>(
+    ds_src: &mut R,
+    zset_db: &mut HashMap<String, HashMap<String, f64>>,
+) -> io::Result<()> {
+    let zset_db_len = match read_len(ds_src) {
+        Ok(len) => len,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for _ in 0..zset_db_len {
+        let key = read_string(ds_src)?;
+        let members = read_len(ds_src)?;
+        let mut zset = HashMap::new();
+        for _ in 0..members {
+            let member = read_string(ds_src)?;
+            let score = f64::from_bits(read_len(ds_src)? as u64);
+            zset.insert(member, score);
+        }
+        zset_db.insert(key, zset);
+    }
+    Ok(())
+}
+
 /// Dado el file dump.rdb, lee el contenido y lo devuelve en un DataStore.
 pub fn deserialize_db(path: String) -> Result<DataStore, io::Error> {
     let mut db_backup = File::open(path)?;
+    deserialize_ds(&mut db_backup)
+}
+
+/// Lee un DataStore serializado con el formato del dump.rdb, venga de un
+/// archivo o de otro nodo.
+pub fn deserialize_ds<R: Read>(ds_src: &mut R) -> Result<DataStore, io::Error> {
     let mut ds = DataStore::new();
 
-    read_string_map(&mut db_backup, &mut ds.string_db)?;
-    read_list_map(&mut db_backup, &mut ds.list_db)?;
-    read_set_map(&mut db_backup, &mut ds.set_db)?;
+    read_string_map(ds_src, &mut ds.string_db)?;
+    read_list_map(ds_src, &mut ds.list_db)?;
+    read_set_map(ds_src, &mut ds.set_db)?;
+    read_stream_map(ds_src, &mut ds.stream_db)?;
+    read_hash_map(ds_src, &mut ds.hash_db)?;
+    read_expires(ds_src, &mut ds)?;
+    read_zset_map(ds_src, &mut ds.zset_db)?;
+    ds.recompute_memory_usage();
     Ok(ds)
 }
+
+/// Lee la respuesta de `SYNC`: el DataStore serializado como un bulk
+/// string RESP (`$<largo>\r\n<bytes>\r\n`). Lo que sigue en la conexión
+/// son los comandos de escritura del master, que se leen aparte.
+pub fn read_sync_payload<R: BufRead>(reader: &mut R) -> Result<DataStore, io::Error> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut header = String::new();
+    reader.read_line(&mut header)?;
+    let len = header
+        .trim_end()
+        .strip_prefix('$')
+        .and_then(|len| len.parse::<usize>().ok())
+        .ok_or_else(|| invalid("SYNC payload without bulk length"))?;
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+    let mut crlf = [0u8; 2];
+    reader.read_exact(&mut crlf)?;
+    if &crlf != b"\r\n" {
+        return Err(invalid("SYNC payload not terminated by CRLF"));
+    }
+    deserialize_ds(&mut payload.as_slice())
+}
@@ -1,11 +1,13 @@
 //! Carga de datos desde disco.
 
 // IMPORTS
+use crate::command::aof_rewrite::replay;
 use crate::config::node_configs::NodeConfigs;
 use crate::logs::aof_logger::AofLogger;
 use crate::storage::DataStore;
 use crate::storage::deserializer::deserialize_db;
-use std::io;
+use std::fs::File;
+use std::io::{self, BufReader, ErrorKind};
 use std::sync::{Arc, RwLock};
 // FUNCIONES
 
@@ -14,6 +16,11 @@ use std::sync::{Arc, RwLock};
 pub struct DiskLoader {
     // Path del archivo del cual cargar.
     source: String,
+    // Path del AOF.
+    aof_source: String,
+    // Con `appendonly yes` el AOF tiene todas las escrituras y se carga
+    // antes que el snapshot.
+    append_only: bool,
     logger: Arc<AofLogger>,
 }
 
@@ -21,16 +28,28 @@ impl DiskLoader {
     pub fn new(settings: NodeConfigs, logger: Arc<AofLogger>) -> Self {
         DiskLoader {
             source: settings.get_snapshot_dst(),
+            aof_source: settings.get_aof_dst(),
+            append_only: settings.get_append_only(),
             logger,
         }
     }
 
     /// Método para cargar el estado inicial de la base de datos
-    /// a partir de un archivo en disco.
+    /// a partir de un archivo en disco. Con `appendonly yes` reproduce el
+    /// AOF si existe; si no, carga el snapshot.
     ///
     /// # Returns
     /// * `Arc<RwLock<DataStore>>` Base de datos lista para su uso.
     pub fn load(&self) -> Result<Arc<RwLock<DataStore>>, io::Error> {
+        if self.append_only && std::path::Path::new(&self.aof_source).exists() {
+            self.logger
+                .log_event(format!("Replaying AOF {}", self.aof_source));
+            let ds = self
+                .logger
+                .replay(&self.aof_source)
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            return Ok(Arc::new(RwLock::new(ds)));
+        }
         self.logger
             .log_event(format!("Starting DB retrieve from {}", self.source));
         let _ = if let Ok(metadata) = std::fs::metadata(&self.source) {
@@ -51,4 +70,145 @@ impl DiskLoader {
             .log_event("No DB backup was found, starting with blank ds".to_string());
         Ok(Arc::new(RwLock::new(DataStore::new())))
     }
+
+    /// Diagnóstico de `aof-rdb-check`: carga el RDB y reproduce el AOF en
+    /// stores separados y los compara. No cambia de qué archivo se carga
+    /// la base. Un archivo inexistente o vacío cuenta como una base vacía.
+    ///
+    /// # Returns
+    /// * `Vec<String>` Claves que difieren, vacío si ambos coinciden.
+    pub fn check_aof_rdb_consistency(&self) -> Result<Vec<String>, io::Error> {
+        let rdb = match std::fs::metadata(&self.source) {
+            Ok(metadata) if metadata.len() > 0 => deserialize_db(self.source.to_string())?,
+            _ => DataStore::new(),
+        };
+        let aof = match File::open(&self.aof_source) {
+            Ok(file) => replay(&mut BufReader::new(file))
+                .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => DataStore::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(rdb.differing_keys(&aof))
+    }
+
+    /// Corre `check_aof_rdb_consistency` y registra el resultado en el log.
+    pub fn report_aof_rdb_consistency(&self) {
+        match self.check_aof_rdb_consistency() {
+            Ok(keys) if keys.is_empty() => self.logger.log_event(format!(
+                "AOF {} and RDB {} agree",
+                self.aof_source, self.source
+            )),
+            Ok(keys) => self.logger.log_warning(format!(
+                "AOF {} and RDB {} differ on {} keys: {}",
+                self.aof_source,
+                self.source,
+                keys.len(),
+                keys.join(", ")
+            )),
+            Err(e) => self
+                .logger
+                .log_error(format!("AOF/RDB consistency check failed: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::aof_rewrite::rewrite;
+    use crate::storage::snapshot_manager::create_dump;
+
+    /// Crea un loader que lee `dump.rdb` y `appendonly.aof` de `dir`, con
+    /// `appendonly yes`.
+    fn create_loader(dir: &tempfile::TempDir) -> DiskLoader {
+        create_loader_with(dir, "yes")
+    }
+
+    fn create_loader_with(dir: &tempfile::TempDir, append_only: &str) -> DiskLoader {
+        let config = dir.path().join("node.conf");
+        std::fs::write(
+            &config,
+            format!(
+                "bind 0.0.0.0\nport 6379\ndir {}/\nlogfile {}\nappendonly {}\n",
+                dir.path().display(),
+                dir.path().join("node.log").display(),
+                append_only
+            ),
+        )
+        .unwrap();
+        let settings = NodeConfigs::new(config.to_str().unwrap()).unwrap();
+        DiskLoader::new(settings.clone(), AofLogger::new(settings))
+    }
+
+    /// Store con una clave de cada tipo.
+    fn create_store() -> DataStore {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Hazard".to_string());
+        store
+            .list_db
+            .insert("Tanks".to_string(), vec!["Orisa".to_string()]);
+        store
+            .set_db
+            .insert("Maps".to_string(), ["Samoa".to_string()].into());
+        store
+    }
+
+    #[test]
+    fn test_load_without_rdb_replays_aof() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = create_loader(&dir);
+        let store = create_store();
+        rewrite(&store, &loader.aof_source).unwrap();
+
+        let loaded = loader.load().unwrap();
+
+        assert!(loaded.read().unwrap().has_same_data(&store));
+    }
+
+    #[test]
+    fn test_load_prefers_aof_over_rdb_only_with_appendonly() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = create_store();
+        let mut newer = create_store();
+        newer.set("Hero".to_string(), "Freja".to_string());
+        let loader = create_loader(&dir);
+        create_dump(&store, &loader.source).unwrap();
+        rewrite(&newer, &loader.aof_source).unwrap();
+
+        let loaded = loader.load().unwrap();
+        assert!(loaded.read().unwrap().has_same_data(&newer));
+
+        let loaded = create_loader_with(&dir, "no").load().unwrap();
+        assert!(loaded.read().unwrap().has_same_data(&store));
+    }
+
+    #[test]
+    fn test_identical_rdb_and_aof_have_no_differences() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = create_loader(&dir);
+        let store = create_store();
+        create_dump(&store, &loader.source).unwrap();
+        rewrite(&store, &loader.aof_source).unwrap();
+
+        assert!(loader.check_aof_rdb_consistency().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_divergent_rdb_and_aof_report_differing_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = create_loader(&dir);
+        let store = create_store();
+        create_dump(&store, &loader.source).unwrap();
+
+        let mut diverged = create_store();
+        diverged.set("Hero".to_string(), "Juno".to_string());
+        diverged.list_db.remove("Tanks");
+        diverged.set("Support".to_string(), "Illari".to_string());
+        rewrite(&diverged, &loader.aof_source).unwrap();
+
+        assert_eq!(
+            loader.check_aof_rdb_consistency().unwrap(),
+            vec!["Hero", "Support", "Tanks"]
+        );
+    }
 }
@@ -0,0 +1,491 @@
+//! Codificación de valores individuales para DUMP/RESTORE.
+//!
+//! El payload de una clave tiene el formato:
+//!
+//! `[tipo: u8][valor][versión: u16][crc16: u16]`
+//!
+//! donde el CRC16 (XMODEM) se calcula sobre todos los bytes previos.
+//! Como las respuestas del servidor son strings, el payload viaja
+//! codificado en hexadecimal.
+
+// IMPORTS
+use crate::cluster::sharding::hash_slot::crc16_xmodem;
+use crate::cluster::utils::{read_string_from_buffer, read_u8_from_buffer, read_u64_from_buffer};
+use crate::storage::DataStore;
+use crate::storage::stream::{ConsumerGroup, PendingEntry, Stream, StreamId};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// CONSTANTES
+
+/// Versión del formato de payload.
+pub const DUMP_VERSION: u16 = 1;
+
+// TAGS DE TIPO
+const STRING_TAG: u8 = 0;
+const LIST_TAG: u8 = 1;
+const SET_TAG: u8 = 2;
+const ZSET_TAG: u8 = 3;
+const HASH_TAG: u8 = 4;
+const STREAM_TAG: u8 = 5;
+
+/// Largo del footer: versión (u16) + crc16 (u16).
+const FOOTER_LEN: usize = 4;
+
+// TAMAÑOS MÍNIMOS CODIFICADOS, para acotar los largos declarados
+const LEN_SIZE: usize = 8;
+const ID_SIZE: usize = 16;
+const PAIR_SIZE: usize = 2 * LEN_SIZE;
+const ZSET_MEMBER_SIZE: usize = LEN_SIZE + 8;
+const STREAM_ENTRY_SIZE: usize = ID_SIZE + LEN_SIZE;
+const GROUP_SIZE: usize = LEN_SIZE + ID_SIZE + LEN_SIZE;
+const PENDING_SIZE: usize = ID_SIZE + LEN_SIZE + 8;
+
+/// Errores al decodificar un payload de DUMP.
+#[derive(Debug, PartialEq)]
+pub enum DumpError {
+    /// El payload no respeta el formato esperado
+    BadFormat,
+    /// El tag de tipo no corresponde a un contenedor soportado
+    UnknownType(u8),
+    /// La versión o el checksum no coinciden
+    VersionOrChecksum,
+}
+
+impl std::fmt::Display for DumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DumpError::BadFormat => write!(f, "ERR Bad data format"),
+            DumpError::UnknownType(tag) => {
+                write!(f, "ERR Bad data format: unknown type tag {}", tag)
+            }
+            DumpError::VersionOrChecksum => {
+                write!(f, "ERR DUMP payload version or checksum are wrong")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DumpError {}
+
+/// Valor decodificado de un payload, listo para insertarse en el store.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DumpValue {
+    Str(String),
+    List(Vec<String>),
+    Set(HashSet<String>),
+    Hash(HashMap<String, String>),
+    Stream(Stream),
+    Zset(HashMap<String, f64>),
+}
+
+// FUNCIONES
+
+/// Función auxiliar para escribir una cadena con su largo como prefijo.
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u64).to_be_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+/// Función auxiliar para escribir una colección de cadenas.
+fn write_items<'a, I: ExactSizeIterator<Item = &'a String>>(bytes: &mut Vec<u8>, items: I) {
+    bytes.extend_from_slice(&(items.len() as u64).to_be_bytes());
+    for item in items {
+        write_string(bytes, item);
+    }
+}
+
+/// Función auxiliar para escribir un entero de 8 bytes.
+fn write_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Función auxiliar para escribir un ID de stream.
+fn write_stream_id(bytes: &mut Vec<u8>, id: &StreamId) {
+    write_u64(bytes, id.ms);
+    write_u64(bytes, id.seq);
+}
+
+/// Función auxiliar para escribir pares campo-valor.
+fn write_pairs<'a, I>(bytes: &mut Vec<u8>, pairs: I)
+where
+    I: ExactSizeIterator<Item = (&'a String, &'a String)>,
+{
+    write_u64(bytes, pairs.len() as u64);
+    for (field, value) in pairs {
+        write_string(bytes, field);
+        write_string(bytes, value);
+    }
+}
+
+/// Función auxiliar para escribir un stream: su último ID, sus entradas
+/// en orden y sus grupos de consumidores con sus pendientes.
+fn write_stream(bytes: &mut Vec<u8>, stream: &Stream) {
+    write_stream_id(bytes, &stream.last_id());
+    write_u64(bytes, stream.len() as u64);
+    for (id, fields) in stream.entries() {
+        write_stream_id(bytes, id);
+        write_pairs(bytes, fields.iter().map(|(field, value)| (field, value)));
+    }
+    write_u64(bytes, stream.groups().len() as u64);
+    for (name, group) in stream.groups() {
+        write_string(bytes, name);
+        write_stream_id(bytes, &group.last_delivered);
+        write_u64(bytes, group.pending.len() as u64);
+        for (id, entry) in &group.pending {
+            write_stream_id(bytes, id);
+            write_string(bytes, &entry.consumer);
+            write_u64(bytes, entry.deliveries);
+        }
+    }
+}
+
+/// Función auxiliar para escribir los miembros de un sorted set con los
+/// bits de su score.
+fn write_zset(bytes: &mut Vec<u8>, zset: &HashMap<String, f64>) {
+    write_u64(bytes, zset.len() as u64);
+    for (member, score) in zset {
+        write_string(bytes, member);
+        write_u64(bytes, score.to_bits());
+    }
+}
+
+/// Función auxiliar para leer un entero de 8 bytes. Si el payload se
+/// termina antes, está truncado.
+fn read_u64(reader: &mut &[u8]) -> Result<u64, DumpError> {
+    read_u64_from_buffer(reader).map_err(|_| DumpError::VersionOrChecksum)
+}
+
+/// Función auxiliar para leer un largo o una cantidad de elementos. Como
+/// el CRC se puede falsificar, antes de reservar memoria se verifica que
+/// lo declarado entre en lo que queda del payload, a razón de al menos
+/// `min_size` bytes por unidad.
+fn read_len(reader: &mut &[u8], min_size: usize) -> Result<usize, DumpError> {
+    let len = read_u64(reader)?;
+    usize::try_from(len)
+        .ok()
+        .filter(|len| {
+            len.checked_mul(min_size)
+                .is_some_and(|size| size <= reader.len())
+        })
+        .ok_or(DumpError::VersionOrChecksum)
+}
+
+/// Función auxiliar para leer un ID de stream.
+fn read_stream_id(reader: &mut &[u8]) -> Result<StreamId, DumpError> {
+    Ok(StreamId::new(read_u64(reader)?, read_u64(reader)?))
+}
+
+/// Función auxiliar para leer pares campo-valor.
+fn read_pairs(reader: &mut &[u8]) -> Result<Vec<(String, String)>, DumpError> {
+    let len = read_len(reader, PAIR_SIZE)?;
+    let mut pairs = Vec::new();
+    for _ in 0..len {
+        pairs.push((read_string(reader)?, read_string(reader)?));
+    }
+    Ok(pairs)
+}
+
+/// Función auxiliar para leer un stream escrito por `write_stream`.
+fn read_stream(reader: &mut &[u8]) -> Result<Stream, DumpError> {
+    let last_id = read_stream_id(reader)?;
+    let mut entries = BTreeMap::new();
+    for _ in 0..read_len(reader, STREAM_ENTRY_SIZE)? {
+        let id = read_stream_id(reader)?;
+        entries.insert(id, read_pairs(reader)?);
+    }
+    let mut groups = HashMap::new();
+    for _ in 0..read_len(reader, GROUP_SIZE)? {
+        let name = read_string(reader)?;
+        let mut group = ConsumerGroup {
+            last_delivered: read_stream_id(reader)?,
+            pending: BTreeMap::new(),
+        };
+        for _ in 0..read_len(reader, PENDING_SIZE)? {
+            let id = read_stream_id(reader)?;
+            let entry = PendingEntry {
+                consumer: read_string(reader)?,
+                deliveries: read_u64(reader)?,
+            };
+            group.pending.insert(id, entry);
+        }
+        groups.insert(name, group);
+    }
+    Ok(Stream::from_parts(entries, last_id, groups))
+}
+
+/// Función auxiliar para leer un sorted set escrito por `write_zset`.
+fn read_zset(reader: &mut &[u8]) -> Result<HashMap<String, f64>, DumpError> {
+    let len = read_len(reader, ZSET_MEMBER_SIZE)?;
+    let mut zset = HashMap::new();
+    for _ in 0..len {
+        let member = read_string(reader)?;
+        zset.insert(member, f64::from_bits(read_u64(reader)?));
+    }
+    Ok(zset)
+}
+
+/// Función auxiliar para leer una colección de cadenas.
+fn read_items(reader: &mut &[u8]) -> Result<Vec<String>, DumpError> {
+    let len = read_len(reader, LEN_SIZE)?;
+    let mut items = Vec::new();
+    for _ in 0..len {
+        items.push(read_string(reader)?);
+    }
+    Ok(items)
+}
+
+/// Función auxiliar para leer una cadena con su largo como prefijo.
+fn read_string(reader: &mut &[u8]) -> Result<String, DumpError> {
+    let len = read_len(reader, 1)?;
+    read_string_from_buffer(reader, len).map_err(|_| DumpError::BadFormat)
+}
+
+/// Serializa el valor de la clave en un payload versionado y con checksum.
+///
+/// # Arguments
+///
+/// * `store` Store de hashmaps
+/// * `key` Clave a serializar
+///
+/// # Returns
+///
+/// El payload, o `None` si la clave no existe.
+pub fn dump_value(store: &DataStore, key: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    if let Some(value) = store.string_db.get(key) {
+        bytes.push(STRING_TAG);
+        write_string(&mut bytes, value);
+    } else if let Some(list) = store.list_db.get(key) {
+        bytes.push(LIST_TAG);
+        write_items(&mut bytes, list.iter());
+    } else if let Some(set) = store.set_db.get(key) {
+        bytes.push(SET_TAG);
+        write_items(&mut bytes, set.iter());
+    } else if let Some(hash) = store.hash_db.get(key) {
+        bytes.push(HASH_TAG);
+        write_pairs(&mut bytes, hash.iter());
+    } else if let Some(stream) = store.stream_db.get(key) {
+        bytes.push(STREAM_TAG);
+        write_stream(&mut bytes, stream);
+    } else if let Some(zset) = store.zset_db.get(key) {
+        bytes.push(ZSET_TAG);
+        write_zset(&mut bytes, zset);
+    } else {
+        return None;
+    }
+    bytes.extend_from_slice(&DUMP_VERSION.to_be_bytes());
+    let crc = crc16_xmodem(&bytes);
+    bytes.extend_from_slice(&crc.to_be_bytes());
+    Some(bytes)
+}
+
+/// Valida y decodifica un payload generado por `dump_value`.
+///
+/// # Arguments
+///
+/// * `payload` Bytes del payload
+///
+/// # Returns
+///
+/// El valor decodificado, o el error correspondiente si el payload es inválido.
+pub fn restore_value(payload: &[u8]) -> Result<DumpValue, DumpError> {
+    if payload.len() < FOOTER_LEN + 1 {
+        return Err(DumpError::BadFormat);
+    }
+    let (data, crc_bytes) = payload.split_at(payload.len() - 2);
+    let crc = u16::from_be_bytes([crc_bytes[0], crc_bytes[1]]);
+    let version_bytes = &data[data.len() - 2..];
+    let version = u16::from_be_bytes([version_bytes[0], version_bytes[1]]);
+    if version != DUMP_VERSION || crc != crc16_xmodem(data) {
+        return Err(DumpError::VersionOrChecksum);
+    }
+
+    let mut reader = &data[..data.len() - 2];
+    let tag = read_u8_from_buffer(&mut reader).map_err(|_| DumpError::BadFormat)?;
+    let value = match tag {
+        STRING_TAG => DumpValue::Str(read_string(&mut reader)?),
+        LIST_TAG => DumpValue::List(read_items(&mut reader)?),
+        SET_TAG => DumpValue::Set(read_items(&mut reader)?.into_iter().collect()),
+        HASH_TAG => DumpValue::Hash(read_pairs(&mut reader)?.into_iter().collect()),
+        STREAM_TAG => DumpValue::Stream(read_stream(&mut reader)?),
+        ZSET_TAG => DumpValue::Zset(read_zset(&mut reader)?),
+        _ => return Err(DumpError::UnknownType(tag)),
+    };
+    if !reader.is_empty() {
+        return Err(DumpError::BadFormat);
+    }
+    Ok(value)
+}
+
+/// Codifica el payload en hexadecimal para poder enviarlo como string.
+pub fn to_hex(payload: &[u8]) -> String {
+    payload.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decodifica un payload en hexadecimal.
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, DumpError> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(DumpError::BadFormat);
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or(DumpError::BadFormat)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Arma un payload válido (versión y checksum) a partir de un cuerpo arbitrario.
+    fn seal(mut body: Vec<u8>) -> Vec<u8> {
+        body.extend_from_slice(&DUMP_VERSION.to_be_bytes());
+        let crc = crc16_xmodem(&body);
+        body.extend_from_slice(&crc.to_be_bytes());
+        body
+    }
+
+    #[test]
+    fn test_dump_restore_string_round_trip() {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Sojourn".to_string());
+
+        let payload = dump_value(&store, "Hero").unwrap();
+        assert_eq!(
+            restore_value(&payload).unwrap(),
+            DumpValue::Str("Sojourn".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dump_restore_list_and_set_round_trip() {
+        let mut store = DataStore::new();
+        let list = vec!["Ana".to_string(), "Kiriko".to_string()];
+        let set: HashSet<String> = ["Dorado".to_string(), "Ilios".to_string()].into();
+        store.list_db.insert("Support".to_string(), list.clone());
+        store.set_db.insert("Maps".to_string(), set.clone());
+
+        let payload = dump_value(&store, "Support").unwrap();
+        assert_eq!(restore_value(&payload).unwrap(), DumpValue::List(list));
+
+        let payload = dump_value(&store, "Maps").unwrap();
+        assert_eq!(restore_value(&payload).unwrap(), DumpValue::Set(set));
+    }
+
+    #[test]
+    fn test_dump_missing_key() {
+        let store = DataStore::new();
+        assert!(dump_value(&store, "Hero").is_none());
+    }
+
+    #[test]
+    fn test_dump_restore_hash_round_trip() {
+        let mut store = DataStore::new();
+        let hash: HashMap<String, String> = [
+            ("role".to_string(), "Tank".to_string()),
+            ("ultimate".to_string(), "Earthshatter".to_string()),
+        ]
+        .into();
+        store.hash_db.insert("Reinhardt".to_string(), hash.clone());
+
+        let payload = dump_value(&store, "Reinhardt").unwrap();
+        assert_eq!(restore_value(&payload).unwrap(), DumpValue::Hash(hash));
+    }
+
+    #[test]
+    fn test_dump_restore_stream_round_trip() {
+        let mut stream = Stream::new();
+        let fields = vec![("hero".to_string(), "Mercy".to_string())];
+        stream
+            .add(Some(StreamId::new(1700000000000, 1)), fields.clone(), 0)
+            .unwrap();
+        stream
+            .add(Some(StreamId::new(1700000000000, 2)), fields, 0)
+            .unwrap();
+        stream.create_group("Healers", StreamId::default()).unwrap();
+        stream.read_group("Healers", "Lucio", None, None).unwrap();
+        let mut store = DataStore::new();
+        store.stream_db.insert("Picks".to_string(), stream.clone());
+
+        let payload = dump_value(&store, "Picks").unwrap();
+        assert_eq!(restore_value(&payload).unwrap(), DumpValue::Stream(stream));
+    }
+
+    #[test]
+    fn test_dump_restore_zset_round_trip() {
+        let mut store = DataStore::new();
+        let zset: HashMap<String, f64> = [
+            ("Kings Row".to_string(), 3_520_051_062_284_386.0),
+            ("Paris".to_string(), 3_663_832_614_298_053.0),
+        ]
+        .into();
+        store.zset_db.insert("Maps".to_string(), zset.clone());
+
+        let payload = dump_value(&store, "Maps").unwrap();
+        assert_eq!(restore_value(&payload).unwrap(), DumpValue::Zset(zset));
+    }
+
+    #[test]
+    fn test_restore_unknown_type_tag() {
+        let payload = seal(vec![9, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(restore_value(&payload), Err(DumpError::UnknownType(9)));
+    }
+
+    #[test]
+    fn test_restore_corrupted_payload() {
+        let mut store = DataStore::new();
+        store.set("Hero".to_string(), "Sojourn".to_string());
+        let mut payload = dump_value(&store, "Hero").unwrap();
+        payload[3] ^= 0xff;
+
+        assert_eq!(restore_value(&payload), Err(DumpError::VersionOrChecksum));
+    }
+
+    #[test]
+    fn test_restore_length_beyond_payload() {
+        // Un string que declara 2^64 - 1 bytes con un CRC válido
+        let mut body = vec![STRING_TAG];
+        body.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert_eq!(
+            restore_value(&seal(body)),
+            Err(DumpError::VersionOrChecksum)
+        );
+
+        // Una lista que declara más elementos de los que entran
+        let mut body = vec![LIST_TAG];
+        body.extend_from_slice(&(1u64 << 40).to_be_bytes());
+        write_string(&mut body, "Doomfist");
+        assert_eq!(
+            restore_value(&seal(body)),
+            Err(DumpError::VersionOrChecksum)
+        );
+    }
+
+    #[test]
+    fn test_restore_truncated_payload() {
+        let mut store = DataStore::new();
+        store.hash_db.insert(
+            "Hero".to_string(),
+            HashMap::from([("name".to_string(), "Ramattra".to_string())]),
+        );
+        let payload = dump_value(&store, "Hero").unwrap();
+        let body = payload[..payload.len() - FOOTER_LEN - 3].to_vec();
+
+        assert_eq!(
+            restore_value(&seal(body)),
+            Err(DumpError::VersionOrChecksum)
+        );
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let payload = vec![0, 15, 16, 255];
+        assert_eq!(to_hex(&payload), "000f10ff");
+        assert_eq!(from_hex("000f10ff").unwrap(), payload);
+        assert_eq!(from_hex("0g"), Err(DumpError::BadFormat));
+    }
+}
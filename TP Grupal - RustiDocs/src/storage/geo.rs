@@ -0,0 +1,288 @@
+//! Codificación geoespacial para los comandos GEO.
+//!
+//! Las coordenadas se codifican en un geohash de 52 bits (26 bits por eje,
+//! intercalados) que puede guardarse como score de un sorted set, igual que
+//! en Redis. Al decodificar se obtiene el centro de la celda, por lo que la
+//! precisión es de aproximadamente medio metro.
+
+// IMPORTS
+use std::collections::HashMap;
+
+// CONSTANTES
+
+/// Bits de precisión por eje.
+const GEO_STEP: u32 = 26;
+
+/// Límites de longitud admitidos.
+pub const GEO_LONG_MIN: f64 = -180.0;
+pub const GEO_LONG_MAX: f64 = 180.0;
+
+/// Límites de latitud admitidos (proyección EPSG:3785).
+pub const GEO_LAT_MIN: f64 = -85.05112878;
+pub const GEO_LAT_MAX: f64 = 85.05112878;
+
+/// Radio terrestre en metros usado para las distancias.
+const EARTH_RADIUS_IN_METERS: f64 = 6372797.560856;
+
+/// Errores de las operaciones geoespaciales.
+#[derive(Debug, PartialEq)]
+pub enum GeoError {
+    /// Las coordenadas están fuera del rango admitido
+    InvalidCoordinates(f64, f64),
+}
+
+impl std::fmt::Display for GeoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeoError::InvalidCoordinates(lon, lat) => {
+                write!(f, "ERR invalid longitude,latitude pair {},{}", lon, lat)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeoError {}
+
+/// Unidades de distancia admitidas por los comandos GEO.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoUnit {
+    M,
+    Km,
+    Mi,
+    Ft,
+}
+
+impl GeoUnit {
+    /// Parsea la unidad (sin distinguir mayúsculas).
+    pub fn parse(unit: &str) -> Option<Self> {
+        match unit.to_lowercase().as_str() {
+            "m" => Some(GeoUnit::M),
+            "km" => Some(GeoUnit::Km),
+            "mi" => Some(GeoUnit::Mi),
+            "ft" => Some(GeoUnit::Ft),
+            _ => None,
+        }
+    }
+
+    /// Metros que equivalen a una unidad.
+    fn meters(&self) -> f64 {
+        match self {
+            GeoUnit::M => 1.0,
+            GeoUnit::Km => 1000.0,
+            GeoUnit::Mi => 1609.34,
+            GeoUnit::Ft => 0.3048,
+        }
+    }
+
+    pub fn to_meters(&self, value: f64) -> f64 {
+        value * self.meters()
+    }
+
+    pub fn from_meters(&self, meters: f64) -> f64 {
+        meters / self.meters()
+    }
+}
+
+// FUNCIONES
+
+/// Intercala los bits de `x` en las posiciones pares y los de `y` en las impares.
+fn interleave(x: u32, y: u32) -> u64 {
+    let mut hash = 0u64;
+    for bit in 0..GEO_STEP {
+        hash |= ((x as u64 >> bit) & 1) << (2 * bit);
+        hash |= ((y as u64 >> bit) & 1) << (2 * bit + 1);
+    }
+    hash
+}
+
+/// Inversa de `interleave`.
+fn deinterleave(hash: u64) -> (u32, u32) {
+    let mut x = 0u32;
+    let mut y = 0u32;
+    for bit in 0..GEO_STEP {
+        x |= (((hash >> (2 * bit)) & 1) as u32) << bit;
+        y |= (((hash >> (2 * bit + 1)) & 1) as u32) << bit;
+    }
+    (x, y)
+}
+
+/// Codifica las coordenadas en un geohash de 52 bits.
+///
+/// # Arguments
+///
+/// * `lon` Longitud en grados
+/// * `lat` Latitud en grados
+///
+/// # Returns
+///
+/// El geohash, o error si las coordenadas están fuera de rango.
+pub fn encode(lon: f64, lat: f64) -> Result<u64, GeoError> {
+    if !(GEO_LONG_MIN..=GEO_LONG_MAX).contains(&lon) || !(GEO_LAT_MIN..=GEO_LAT_MAX).contains(&lat)
+    {
+        return Err(GeoError::InvalidCoordinates(lon, lat));
+    }
+    let cells = (1u64 << GEO_STEP) as f64;
+    let lat_offset = (lat - GEO_LAT_MIN) / (GEO_LAT_MAX - GEO_LAT_MIN);
+    let lon_offset = (lon - GEO_LONG_MIN) / (GEO_LONG_MAX - GEO_LONG_MIN);
+    // El borde superior cae en la última celda
+    let max_cell = (1u32 << GEO_STEP) - 1;
+    let lat_cell = ((lat_offset * cells) as u32).min(max_cell);
+    let lon_cell = ((lon_offset * cells) as u32).min(max_cell);
+    Ok(interleave(lat_cell, lon_cell))
+}
+
+/// Decodifica un geohash al centro de su celda.
+///
+/// # Returns
+///
+/// `(longitud, latitud)` en grados.
+pub fn decode(hash: u64) -> (f64, f64) {
+    let (lat_cell, lon_cell) = deinterleave(hash);
+    let cells = (1u64 << GEO_STEP) as f64;
+    let lat_step = (GEO_LAT_MAX - GEO_LAT_MIN) / cells;
+    let lon_step = (GEO_LONG_MAX - GEO_LONG_MIN) / cells;
+    let lat = GEO_LAT_MIN + (lat_cell as f64 + 0.5) * lat_step;
+    let lon = GEO_LONG_MIN + (lon_cell as f64 + 0.5) * lon_step;
+    (lon, lat)
+}
+
+/// Distancia en metros entre dos puntos (fórmula de haversine).
+pub fn distance(lon1: f64, lat1: f64, lon2: f64, lat2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let v = ((lon2 - lon1).to_radians() / 2.0).sin();
+    let a = u * u + lat1.cos() * lat2.cos() * v * v;
+    2.0 * EARTH_RADIUS_IN_METERS * a.sqrt().asin()
+}
+
+/// Miembros cuyo geohash (score) cae dentro del radio indicado.
+///
+/// # Arguments
+///
+/// * `members` Miembros con su geohash como score
+/// * `lon`, `lat` Centro de la búsqueda
+/// * `radius` Radio en metros
+///
+/// # Returns
+///
+/// Los miembros encontrados con su distancia al centro, del más cercano al más lejano.
+pub fn search_by_radius(
+    members: &HashMap<String, f64>,
+    lon: f64,
+    lat: f64,
+    radius: f64,
+) -> Vec<(String, f64)> {
+    search(members, lon, lat, |dist, _, _| dist <= radius)
+}
+
+/// Miembros dentro del rectángulo de `width` x `height` metros centrado en el punto.
+pub fn search_by_box(
+    members: &HashMap<String, f64>,
+    lon: f64,
+    lat: f64,
+    width: f64,
+    height: f64,
+) -> Vec<(String, f64)> {
+    search(members, lon, lat, |_, member_lon, member_lat| {
+        // Se miden los desplazamientos sobre cada eje por separado
+        distance(lon, lat, lon, member_lat) <= height / 2.0
+            && distance(lon, member_lat, member_lon, member_lat) <= width / 2.0
+    })
+}
+
+fn search<F: Fn(f64, f64, f64) -> bool>(
+    members: &HashMap<String, f64>,
+    lon: f64,
+    lat: f64,
+    in_area: F,
+) -> Vec<(String, f64)> {
+    let mut found: Vec<(String, f64)> = members
+        .iter()
+        .filter_map(|(member, score)| {
+            let (member_lon, member_lat) = decode(*score as u64);
+            let dist = distance(lon, lat, member_lon, member_lat);
+            in_area(dist, member_lon, member_lat).then(|| (member.clone(), dist))
+        })
+        .collect();
+    found.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Coordenadas reales de los mapas
+    const KINGS_ROW: (f64, f64) = (-0.1278, 51.5074);
+    const PARIS: (f64, f64) = (2.3522, 48.8566);
+    const RIO: (f64, f64) = (-43.1729, -22.9068);
+
+    /// Simula un sorted set con los geohash como score.
+    fn maps() -> HashMap<String, f64> {
+        [("King's Row", KINGS_ROW), ("Paris", PARIS), ("Rio", RIO)]
+            .into_iter()
+            .map(|(name, (lon, lat))| (name.to_string(), encode(lon, lat).unwrap() as f64))
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        for (lon, lat) in [KINGS_ROW, PARIS, RIO, (179.9999, 85.05), (-180.0, -85.05)] {
+            let hash = encode(lon, lat).unwrap();
+            assert!(hash < 1 << 52);
+            // El score se guarda como f64 sin perder precisión
+            let (decoded_lon, decoded_lat) = decode(hash as f64 as u64);
+            assert!((decoded_lon - lon).abs() < 1e-5);
+            assert!((decoded_lat - lat).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_encode_invalid_coordinates() {
+        assert_eq!(
+            encode(181.0, 0.0),
+            Err(GeoError::InvalidCoordinates(181.0, 0.0))
+        );
+        assert!(encode(0.0, 86.0).is_err());
+    }
+
+    #[test]
+    fn test_distance_between_cities() {
+        let dist = distance(KINGS_ROW.0, KINGS_ROW.1, PARIS.0, PARIS.1);
+        // Londres - París ronda los 343 km
+        assert!((dist - 343_500.0).abs() < 2_000.0);
+        assert!((GeoUnit::Km.from_meters(dist) - 343.5).abs() < 2.0);
+        assert_eq!(distance(PARIS.0, PARIS.1, PARIS.0, PARIS.1), 0.0);
+    }
+
+    #[test]
+    fn test_search_by_radius_only_in_range() {
+        let members = maps();
+
+        let found = search_by_radius(&members, KINGS_ROW.0, KINGS_ROW.1, 500_000.0);
+        let names: Vec<&str> = found.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["King's Row", "Paris"]);
+
+        let found = search_by_radius(&members, KINGS_ROW.0, KINGS_ROW.1, 100_000.0);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "King's Row");
+    }
+
+    #[test]
+    fn test_search_by_box() {
+        let members = maps();
+        // Paris está ~295 km al sur y ~180 km al este de Londres
+        let found = search_by_box(&members, KINGS_ROW.0, KINGS_ROW.1, 400_000.0, 400_000.0);
+        assert_eq!(found.len(), 1);
+
+        let found = search_by_box(&members, KINGS_ROW.0, KINGS_ROW.1, 400_000.0, 700_000.0);
+        assert_eq!(found.len(), 2);
+    }
+
+    #[test]
+    fn test_geo_unit_parse() {
+        assert_eq!(GeoUnit::parse("KM"), Some(GeoUnit::Km));
+        assert_eq!(GeoUnit::parse("yd"), None);
+        assert_eq!(GeoUnit::Mi.to_meters(1.0), 1609.34);
+    }
+}
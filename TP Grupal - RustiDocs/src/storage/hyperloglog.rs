@@ -0,0 +1,226 @@
+//! Estimación de cardinalidad con HyperLogLog (PFADD/PFCOUNT/PFMERGE).
+//!
+//! El HLL se guarda como un string común con el formato:
+//!
+//! `HYLL[codificación][registros en hexadecimal]`
+//!
+//! donde la codificación es `S` (sparse, pares `índice: u16` + `valor: u8`
+//! sólo para los registros no nulos) o `D` (dense, un byte por registro).
+//! Mientras hay pocos registros ocupados se usa la representación sparse.
+
+// IMPORTS
+use crate::storage::dump::{from_hex, to_hex};
+
+// CONSTANTES
+
+/// Encabezado de todo string que contiene un HLL.
+const HLL_MAGIC: &str = "HYLL";
+const SPARSE_ENCODING: char = 'S';
+const DENSE_ENCODING: char = 'D';
+
+/// Bits del hash usados para elegir el registro.
+const HLL_P: u32 = 14;
+/// Cantidad de registros.
+const HLL_REGISTERS: usize = 1 << HLL_P;
+/// Tamaño máximo (en bytes) de la representación sparse.
+const HLL_SPARSE_MAX_BYTES: usize = 3000;
+/// Bytes por registro ocupado en la representación sparse.
+const SPARSE_ENTRY_BYTES: usize = 3;
+
+/// Semilla del hash, la misma que usa Redis.
+const HLL_HASH_SEED: u64 = 0xadc83b19;
+
+/// Error al interpretar un string como HLL.
+#[derive(Debug, PartialEq)]
+pub struct InvalidHll;
+
+impl std::fmt::Display for InvalidHll {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WRONGTYPE Key is not a valid HyperLogLog string value.")
+    }
+}
+
+impl std::error::Error for InvalidHll {}
+
+/// Registros de un HyperLogLog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; HLL_REGISTERS],
+        }
+    }
+
+    /// Agrega un elemento. Devuelve `true` si algún registro cambió.
+    pub fn add(&mut self, element: &str) -> bool {
+        let hash = murmur_hash64a(element.as_bytes(), HLL_HASH_SEED);
+        let index = (hash & (HLL_REGISTERS as u64 - 1)) as usize;
+        // Se fuerza un bit para que la cuenta de ceros quede acotada
+        let rest = (hash >> HLL_P) | (1 << (64 - HLL_P));
+        let run = rest.trailing_zeros() as u8 + 1;
+        if run > self.registers[index] {
+            self.registers[index] = run;
+            return true;
+        }
+        false
+    }
+
+    /// Une los registros de otro HLL (máximo registro a registro).
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other);
+        }
+    }
+
+    /// Estima la cantidad de elementos distintos agregados.
+    pub fn count(&self) -> u64 {
+        let m = HLL_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|register| 2f64.powi(-(*register as i32)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            // Corrección para cardinalidades chicas (linear counting)
+            return (m * (m / zeros as f64).ln()).round() as u64;
+        }
+        estimate.round() as u64
+    }
+
+    /// Serializa el HLL como string, eligiendo la codificación más chica.
+    pub fn to_string_value(&self) -> String {
+        let used: Vec<(usize, u8)> = self
+            .registers
+            .iter()
+            .enumerate()
+            .filter(|(_, register)| **register != 0)
+            .map(|(index, register)| (index, *register))
+            .collect();
+
+        if used.len() * SPARSE_ENTRY_BYTES <= HLL_SPARSE_MAX_BYTES {
+            let mut bytes = Vec::with_capacity(used.len() * SPARSE_ENTRY_BYTES);
+            for (index, register) in used {
+                bytes.extend_from_slice(&(index as u16).to_be_bytes());
+                bytes.push(register);
+            }
+            format!("{}{}{}", HLL_MAGIC, SPARSE_ENCODING, to_hex(&bytes))
+        } else {
+            format!("{}{}{}", HLL_MAGIC, DENSE_ENCODING, to_hex(&self.registers))
+        }
+    }
+
+    /// Interpreta un string generado por `to_string_value`.
+    pub fn from_string_value(value: &str) -> Result<Self, InvalidHll> {
+        let body = value.strip_prefix(HLL_MAGIC).ok_or(InvalidHll)?;
+        let mut chars = body.chars();
+        let encoding = chars.next().ok_or(InvalidHll)?;
+        let bytes = from_hex(chars.as_str()).map_err(|_| InvalidHll)?;
+
+        let mut hll = HyperLogLog::new();
+        match encoding {
+            SPARSE_ENCODING if bytes.len() % SPARSE_ENTRY_BYTES == 0 => {
+                for entry in bytes.chunks(SPARSE_ENTRY_BYTES) {
+                    let index = u16::from_be_bytes([entry[0], entry[1]]) as usize;
+                    *hll.registers.get_mut(index).ok_or(InvalidHll)? = entry[2];
+                }
+            }
+            DENSE_ENCODING if bytes.len() == HLL_REGISTERS => hll.registers = bytes,
+            _ => return Err(InvalidHll),
+        }
+        Ok(hll)
+    }
+}
+
+/// MurmurHash64A, el hash que usa Redis para sus HyperLogLog.
+fn murmur_hash64a(key: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4a7935bd1e995;
+    const R: u32 = 47;
+
+    let mut h = seed ^ (key.len() as u64).wrapping_mul(M);
+    let mut chunks = key.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut k = u64::from_le_bytes([
+            chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7],
+        ]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate() {
+            h ^= (*byte as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_within_error_bound() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..20_000 {
+            hll.add(&format!("player:{}", i));
+        }
+        let count = hll.count() as f64;
+        // Error estándar de 0.81% con 16384 registros, se tolera 3%
+        assert!((count - 20_000.0).abs() / 20_000.0 < 0.03, "{}", count);
+    }
+
+    #[test]
+    fn test_repeated_elements_do_not_change_registers() {
+        let mut hll = HyperLogLog::new();
+        assert!(hll.add("Tracer"));
+        assert!(!hll.add("Tracer"));
+        assert_eq!(hll.count(), 1);
+    }
+
+    #[test]
+    fn test_string_value_round_trip_sparse_and_dense() {
+        let mut hll = HyperLogLog::new();
+        for hero in ["Ana", "Genji", "Mercy"] {
+            hll.add(hero);
+        }
+        let value = hll.to_string_value();
+        assert!(value.starts_with("HYLLS"));
+        assert_eq!(HyperLogLog::from_string_value(&value).unwrap(), hll);
+
+        for i in 0..5_000 {
+            hll.add(&format!("bot:{}", i));
+        }
+        let value = hll.to_string_value();
+        assert!(value.starts_with("HYLLD"));
+        assert_eq!(HyperLogLog::from_string_value(&value).unwrap(), hll);
+    }
+
+    #[test]
+    fn test_invalid_string_value() {
+        assert_eq!(HyperLogLog::from_string_value("Overwatch"), Err(InvalidHll));
+        assert_eq!(HyperLogLog::from_string_value("HYLLD00"), Err(InvalidHll));
+    }
+}
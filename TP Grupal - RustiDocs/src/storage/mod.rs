@@ -1,8 +1,12 @@
 pub mod data_store;
 pub mod deserializer;
 pub mod disk_loader;
+pub mod dump;
+pub mod geo;
+pub mod hyperloglog;
 pub mod serializer;
 pub mod snapshot_manager;
+pub mod stream;
 
 pub use data_store::DataStore;
 pub use disk_loader::DiskLoader;
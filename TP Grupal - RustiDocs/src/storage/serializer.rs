@@ -2,8 +2,8 @@
 
 // IMPORTS
 use crate::storage::DataStore;
+use crate::storage::stream::{Stream, StreamId};
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
 use std::io;
 use std::io::Write;
 
@@ -24,8 +24,9 @@ where
 
 /// Función auxiliar para iterar sobre un HashMap y serializar sus
 /// componentes "iterables" en un archivo
-fn iterate_and_write<T, K, V, VI>(db: T, dest: &mut File) -> io::Result<()>
+fn iterate_and_write<T, K, V, VI, W>(db: T, dest: &mut W) -> io::Result<()>
 where
+    W: Write,
     T: IntoIterator<Item = (K, V)>,
     K: AsRef<str>,
     V: IntoIterator<Item = VI>,
@@ -43,7 +44,10 @@ where
 }
 
 /// Serializa un HashMap de Vectores de Strings a un archivo
-fn serialize_vec_nested_hm(db: &HashMap<String, Vec<String>>, dest: &mut File) -> io::Result<()> {
+fn serialize_vec_nested_hm<W: Write>(
+    db: &HashMap<String, Vec<String>>,
+    dest: &mut W,
+) -> io::Result<()> {
     let list_db_len = db.len();
     dest.write_all(&list_db_len.to_be_bytes())?;
     iterate_and_write(db, dest)?;
@@ -51,9 +55,9 @@ fn serialize_vec_nested_hm(db: &HashMap<String, Vec<String>>, dest: &mut File) -
 }
 
 /// Serializa un HashMap de HashSets de Strings a un archivo
-fn serialize_set_nested_hm(
+fn serialize_set_nested_hm<W: Write>(
     db: &HashMap<String, HashSet<String>>,
-    dest: &mut File,
+    dest: &mut W,
 ) -> io::Result<()> {
     let set_db_len = db.len();
     dest.write_all(&set_db_len.to_be_bytes())?;
@@ -72,12 +76,107 @@ fn serialize_simple_hm<W: Write>(db: &HashMap<String, String>, dest: &mut W) ->
     Ok(())
 }
 
+/// Escribe un ID de stream como dos enteros de 8 bytes.
+fn write_stream_id<W: Write>(writer: &mut W, id: &StreamId) -> io::Result<()> {
+    writer.write_all(&id.ms.to_be_bytes())?;
+    writer.write_all(&id.seq.to_be_bytes())?;
+    Ok(())
+}
+
+/// Serializa un HashMap de streams a un archivo. Por cada stream se
+/// guarda su último ID, para que los IDs generados no retrocedan al
+/// recargarlo, sus entradas en orden y sus grupos de consumidores.
+fn serialize_stream_hm<W: Write>(db: &HashMap<String, Stream>, dest: &mut W) -> io::Result<()> {
+    dest.write_all(&db.len().to_be_bytes())?;
+    for (key, stream) in db {
+        write_string(dest, key)?;
+        write_stream_id(dest, &stream.last_id())?;
+        dest.write_all(&stream.len().to_be_bytes())?;
+        for (id, fields) in stream.entries() {
+            write_stream_id(dest, id)?;
+            dest.write_all(&fields.len().to_be_bytes())?;
+            for (field, value) in fields {
+                write_string(dest, field)?;
+                write_string(dest, value)?;
+            }
+        }
+        dest.write_all(&stream.groups().len().to_be_bytes())?;
+        for (name, group) in stream.groups() {
+            write_string(dest, name)?;
+            write_stream_id(dest, &group.last_delivered)?;
+            dest.write_all(&group.pending.len().to_be_bytes())?;
+            for (id, entry) in &group.pending {
+                write_stream_id(dest, id)?;
+                write_string(dest, &entry.consumer)?;
+                dest.write_all(&entry.deliveries.to_be_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Serializa un HashMap de hashes a un archivo, como pares campo-valor.
+fn serialize_hash_hm<W: Write>(
+    db: &HashMap<String, HashMap<String, String>>,
+    dest: &mut W,
+) -> io::Result<()> {
+    dest.write_all(&db.len().to_be_bytes())?;
+    for (key, hash) in db {
+        write_string(dest, key)?;
+        serialize_simple_hm(hash, dest)?;
+    }
+    Ok(())
+}
+
+/// Serializa los vencimientos de las claves como instantes absolutos,
+/// en milisegundos desde la época UNIX, para que sigan valiendo al
+/// cargarlos después de un reinicio o en otro nodo.
+fn serialize_expires<W: Write>(ds: &DataStore, dest: &mut W) -> io::Result<()> {
+    let deadlines = ds.expiry_deadlines();
+    dest.write_all(&deadlines.len().to_be_bytes())?;
+    for (key, unix_ms) in deadlines {
+        write_string(dest, key)?;
+        dest.write_all(&unix_ms.to_be_bytes())?;
+    }
+    Ok(())
+}
+
+/// Serializa los sorted sets: por cada clave, la cantidad de miembros
+/// y cada miembro con los bits de su score.
+fn serialize_zset_hm<W: Write>(
+    db: &HashMap<String, HashMap<String, f64>>,
+    dest: &mut W,
+) -> io::Result<()> {
+    dest.write_all(&db.len().to_be_bytes())?;
+    for (key, zset) in db {
+        write_string(dest, key)?;
+        dest.write_all(&zset.len().to_be_bytes())?;
+        for (member, score) in zset {
+            write_string(dest, member)?;
+            dest.write_all(&score.to_bits().to_be_bytes())?;
+        }
+    }
+    Ok(())
+}
+
 /// Itera sobre el datastore y serializa los datos en un archivo
 /// a medida que lo recorre parra evitar guardar todo el archivo
 /// en memoria al mismo tiempo.
-pub fn serialize_ds(ds: &DataStore, dest: &mut File) -> Result<(), io::Error> {
+pub fn serialize_ds<W: Write>(ds: &DataStore, dest: &mut W) -> Result<(), io::Error> {
     serialize_simple_hm(&ds.string_db, dest)?;
     serialize_vec_nested_hm(&ds.list_db, dest)?;
     serialize_set_nested_hm(&ds.set_db, dest)?;
+    serialize_stream_hm(&ds.stream_db, dest)?;
+    serialize_hash_hm(&ds.hash_db, dest)?;
+    serialize_expires(ds, dest)?;
+    serialize_zset_hm(&ds.zset_db, dest)?;
     Ok(())
 }
+
+/// Serializa el datastore en memoria, con el mismo formato que el
+/// dump.rdb, para transferirlo a otro nodo (SYNC).
+pub fn serialize_to_bytes(ds: &DataStore) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    serialize_ds(ds, &mut bytes)?;
+    Ok(bytes)
+}
@@ -0,0 +1,469 @@
+//! Tipo de dato stream (XADD/XLEN/XRANGE/XREAD).
+//!
+//! Un stream es un log de solo agregado: cada entrada tiene un ID
+//! `<ms>-<seq>` y un conjunto de pares campo/valor. Los IDs son
+//! estrictamente crecientes, por lo que las entradas quedan ordenadas
+//! por ID en un `BTreeMap`.
+//!
+//! Los grupos de consumidores (XGROUP/XREADGROUP/XACK/XPENDING) entregan
+//! cada entrada una vez por grupo y la mantienen en la lista de pendientes
+//! (PEL) hasta que se confirma con XACK, logrando una entrega *at-least-once*.
+
+// IMPORTS
+use std::collections::{BTreeMap, HashMap};
+
+/// Campos de una entrada, en el orden en que fueron agregados.
+pub type StreamFields = Vec<(String, String)>;
+
+/// Errores de las operaciones sobre streams.
+#[derive(Debug, PartialEq)]
+pub enum StreamError {
+    /// El ID no respeta el formato `<ms>-<seq>`
+    InvalidId,
+    /// El ID explícito no es mayor al último del stream
+    IdTooSmall,
+    /// El ID explícito es `0-0`
+    IdZero,
+    /// Ya existe un grupo con ese nombre
+    BusyGroup,
+    /// No existe el grupo indicado
+    NoGroup,
+}
+
+impl std::fmt::Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::InvalidId => write!(
+                f,
+                "ERR Invalid stream ID specified as stream command argument"
+            ),
+            StreamError::IdTooSmall => write!(
+                f,
+                "ERR The ID specified in XADD is equal or smaller than the target stream top item"
+            ),
+            StreamError::IdZero => {
+                write!(f, "ERR The ID specified in XADD must be greater than 0-0")
+            }
+            StreamError::BusyGroup => write!(f, "BUSYGROUP Consumer Group name already exists"),
+            StreamError::NoGroup => write!(f, "NOGROUP No such consumer group"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+/// ID de una entrada: milisegundos y número de secuencia dentro del milisegundo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        Self { ms, seq }
+    }
+
+    /// Parsea un ID `<ms>-<seq>`. Si falta la secuencia se usa `default_seq`.
+    pub fn parse(id: &str, default_seq: u64) -> Result<Self, StreamError> {
+        let (ms, seq) = match id.split_once('-') {
+            Some((ms, seq)) => (ms, seq.parse().map_err(|_| StreamError::InvalidId)?),
+            None => (id, default_seq),
+        };
+        let ms = ms.parse().map_err(|_| StreamError::InvalidId)?;
+        Ok(Self { ms, seq })
+    }
+
+    /// Parsea el extremo de un rango: `-` es el mínimo, `+` el máximo y
+    /// un ID sin secuencia abarca todo el milisegundo.
+    pub fn parse_bound(id: &str, is_start: bool) -> Result<Self, StreamError> {
+        match id {
+            "-" => Ok(Self::MIN),
+            "+" => Ok(Self::MAX),
+            _ => Self::parse(id, if is_start { 0 } else { u64::MAX }),
+        }
+    }
+
+    /// Siguiente ID posible, o `None` si es el máximo.
+    fn next(&self) -> Option<Self> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(Self::new(self.ms, seq)),
+            None => self.ms.checked_add(1).map(|ms| Self::new(ms, 0)),
+        }
+    }
+}
+
+impl std::fmt::Display for StreamId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// Entrada entregada a un consumidor y todavía no confirmada.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingEntry {
+    /// Consumidor al que se entregó
+    pub consumer: String,
+    /// Cantidad de veces que se entregó
+    pub deliveries: u64,
+}
+
+/// Estado de un grupo de consumidores.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConsumerGroup {
+    /// Último ID entregado a algún consumidor del grupo
+    pub last_delivered: StreamId,
+    /// Entradas entregadas pendientes de XACK (PEL)
+    pub pending: BTreeMap<StreamId, PendingEntry>,
+}
+
+/// Resumen de la PEL de un grupo (XPENDING).
+#[derive(Debug, PartialEq)]
+pub struct PendingSummary {
+    /// Cantidad de entradas pendientes
+    pub count: usize,
+    /// Menor y mayor ID pendiente, si hay alguno
+    pub bounds: Option<(StreamId, StreamId)>,
+    /// Cantidad de pendientes por consumidor
+    pub consumers: BTreeMap<String, usize>,
+}
+
+/// Entradas de un stream ordenadas por ID.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Stream {
+    entries: BTreeMap<StreamId, StreamFields>,
+    /// Último ID generado. Se conserva aunque se borren entradas
+    /// para que los IDs nunca retrocedan.
+    last_id: StreamId,
+    groups: HashMap<String, ConsumerGroup>,
+}
+
+impl Stream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reconstruye un stream a partir de sus entradas, su último ID y sus grupos.
+    pub fn from_parts(
+        entries: BTreeMap<StreamId, StreamFields>,
+        last_id: StreamId,
+        groups: HashMap<String, ConsumerGroup>,
+    ) -> Self {
+        Self {
+            entries,
+            last_id,
+            groups,
+        }
+    }
+
+    /// Grupos de consumidores del stream.
+    pub fn groups(&self) -> &HashMap<String, ConsumerGroup> {
+        &self.groups
+    }
+
+    pub fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Itera las entradas en orden de ID.
+    pub fn entries(&self) -> impl Iterator<Item = (&StreamId, &StreamFields)> {
+        self.entries.iter()
+    }
+
+    /// Agrega una entrada (XADD).
+    ///
+    /// # Arguments
+    ///
+    /// * `id` ID explícito, o `None` para generarlo (`*`)
+    /// * `fields` Pares campo/valor de la entrada
+    /// * `now_ms` Tiempo actual en milisegundos, usado al generar el ID
+    ///
+    /// # Returns
+    ///
+    /// El ID de la entrada, o error si el ID explícito no es mayor al último.
+    pub fn add(
+        &mut self,
+        id: Option<StreamId>,
+        fields: StreamFields,
+        now_ms: u64,
+    ) -> Result<StreamId, StreamError> {
+        let id = match id {
+            Some(id) if id == StreamId::MIN => return Err(StreamError::IdZero),
+            Some(id) if id <= self.last_id => return Err(StreamError::IdTooSmall),
+            Some(id) => id,
+            // Si el reloj retrocede se sigue a partir del último ID
+            None if now_ms > self.last_id.ms => StreamId::new(now_ms, 0),
+            None => self.last_id.next().ok_or(StreamError::IdTooSmall)?,
+        };
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        Ok(id)
+    }
+
+    /// Entradas con ID entre `start` y `end` (inclusive), hasta `count` si se indica.
+    pub fn range(
+        &self,
+        start: StreamId,
+        end: StreamId,
+        count: Option<usize>,
+    ) -> Vec<(&StreamId, &StreamFields)> {
+        if start > end {
+            return vec![];
+        }
+        self.entries
+            .range(start..=end)
+            .take(count.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    /// Entradas con ID estrictamente mayor a `id` (XREAD).
+    pub fn read_after(
+        &self,
+        id: StreamId,
+        count: Option<usize>,
+    ) -> Vec<(&StreamId, &StreamFields)> {
+        match id.next() {
+            Some(start) => self.range(start, StreamId::MAX, count),
+            None => vec![],
+        }
+    }
+
+    /// Crea un grupo de consumidores que entregará las entradas
+    /// posteriores a `last_delivered` (XGROUP CREATE).
+    pub fn create_group(
+        &mut self,
+        name: &str,
+        last_delivered: StreamId,
+    ) -> Result<(), StreamError> {
+        if self.groups.contains_key(name) {
+            return Err(StreamError::BusyGroup);
+        }
+        let group = ConsumerGroup {
+            last_delivered,
+            pending: BTreeMap::new(),
+        };
+        self.groups.insert(name.to_string(), group);
+        Ok(())
+    }
+
+    /// Entrega entradas a un consumidor del grupo (XREADGROUP).
+    ///
+    /// # Arguments
+    ///
+    /// * `group` Nombre del grupo
+    /// * `consumer` Nombre del consumidor
+    /// * `after` `None` (`>`) para entregar entradas nuevas; un ID para
+    ///   volver a leer las pendientes del consumidor posteriores a él
+    /// * `count` Cantidad máxima de entradas
+    ///
+    /// # Returns
+    ///
+    /// Las entradas entregadas, que quedan en la PEL del grupo.
+    pub fn read_group(
+        &mut self,
+        group: &str,
+        consumer: &str,
+        after: Option<StreamId>,
+        count: Option<usize>,
+    ) -> Result<Vec<(StreamId, StreamFields)>, StreamError> {
+        let state = self.groups.get_mut(group).ok_or(StreamError::NoGroup)?;
+        let limit = count.unwrap_or(usize::MAX);
+        let mut delivered = vec![];
+
+        match after {
+            None => {
+                let start = match state.last_delivered.next() {
+                    Some(start) => start,
+                    None => return Ok(delivered),
+                };
+                for (id, fields) in self.entries.range(start..).take(limit) {
+                    state.pending.insert(
+                        *id,
+                        PendingEntry {
+                            consumer: consumer.to_string(),
+                            deliveries: 1,
+                        },
+                    );
+                    state.last_delivered = *id;
+                    delivered.push((*id, fields.clone()));
+                }
+            }
+            Some(after) => {
+                let own_pending = state
+                    .pending
+                    .iter_mut()
+                    .filter(|(id, entry)| **id > after && entry.consumer == consumer)
+                    .take(limit);
+                for (id, entry) in own_pending {
+                    if let Some(fields) = self.entries.get(id) {
+                        entry.deliveries += 1;
+                        delivered.push((*id, fields.clone()));
+                    }
+                }
+            }
+        }
+        Ok(delivered)
+    }
+
+    /// Confirma entradas, quitándolas de la PEL del grupo (XACK).
+    ///
+    /// # Returns
+    ///
+    /// Cantidad de entradas que estaban pendientes.
+    pub fn ack(&mut self, group: &str, ids: &[StreamId]) -> Result<usize, StreamError> {
+        let state = self.groups.get_mut(group).ok_or(StreamError::NoGroup)?;
+        Ok(ids
+            .iter()
+            .filter(|id| state.pending.remove(id).is_some())
+            .count())
+    }
+
+    /// Resumen de las entradas pendientes del grupo (XPENDING).
+    pub fn pending_summary(&self, group: &str) -> Result<PendingSummary, StreamError> {
+        let state = self.groups.get(group).ok_or(StreamError::NoGroup)?;
+        let first = state.pending.keys().next();
+        let last = state.pending.keys().next_back();
+        let mut consumers = BTreeMap::new();
+        for entry in state.pending.values() {
+            *consumers.entry(entry.consumer.clone()).or_insert(0) += 1;
+        }
+        Ok(PendingSummary {
+            count: state.pending.len(),
+            bounds: first.zip(last).map(|(first, last)| (*first, *last)),
+            consumers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(hero: &str) -> StreamFields {
+        vec![("hero".to_string(), hero.to_string())]
+    }
+
+    #[test]
+    fn test_parse_ids_and_bounds() {
+        assert_eq!(
+            StreamId::parse("1526919030474-55", 0),
+            Ok(StreamId::new(1526919030474, 55))
+        );
+        assert_eq!(StreamId::parse("5", 0), Ok(StreamId::new(5, 0)));
+        assert_eq!(
+            StreamId::parse_bound("5", false),
+            Ok(StreamId::new(5, u64::MAX))
+        );
+        assert_eq!(StreamId::parse_bound("-", true), Ok(StreamId::MIN));
+        assert_eq!(StreamId::parse_bound("+", false), Ok(StreamId::MAX));
+        assert_eq!(StreamId::parse("Tracer-1", 0), Err(StreamError::InvalidId));
+        assert_eq!(StreamId::new(7, 3).to_string(), "7-3");
+    }
+
+    #[test]
+    fn test_auto_id_does_not_go_back_with_clock() {
+        let mut stream = Stream::new();
+        let first = stream.add(None, fields("Ana"), 100).unwrap();
+        let second = stream.add(None, fields("Mercy"), 90).unwrap();
+        assert_eq!(first, StreamId::new(100, 0));
+        assert_eq!(second, StreamId::new(100, 1));
+        assert_eq!(
+            stream.add(None, fields("Lucio"), 200).unwrap(),
+            StreamId::new(200, 0)
+        );
+    }
+
+    #[test]
+    fn test_explicit_zero_id() {
+        let mut stream = Stream::new();
+        assert_eq!(
+            stream.add(Some(StreamId::MIN), fields("Ana"), 0),
+            Err(StreamError::IdZero)
+        );
+        assert!(stream.is_empty());
+    }
+
+    #[test]
+    fn test_read_after_is_exclusive() {
+        let mut stream = Stream::new();
+        for (ms, hero) in [(1, "Ana"), (2, "Mercy"), (3, "Lucio")] {
+            stream
+                .add(Some(StreamId::new(ms, 0)), fields(hero), 0)
+                .unwrap();
+        }
+        let ids: Vec<StreamId> = stream
+            .read_after(StreamId::new(1, 0), None)
+            .into_iter()
+            .map(|(id, _)| *id)
+            .collect();
+        assert_eq!(ids, vec![StreamId::new(2, 0), StreamId::new(3, 0)]);
+        assert!(stream.read_after(StreamId::MAX, None).is_empty());
+    }
+
+    #[test]
+    fn test_read_group_rereads_own_pending() {
+        let mut stream = Stream::new();
+        for ms in 1..=3 {
+            stream
+                .add(Some(StreamId::new(ms, 0)), fields("Sombra"), 0)
+                .unwrap();
+        }
+        stream.create_group("Talon", StreamId::MIN).unwrap();
+        assert_eq!(
+            stream.create_group("Talon", StreamId::MIN),
+            Err(StreamError::BusyGroup)
+        );
+
+        assert_eq!(
+            stream
+                .read_group("Talon", "Reaper", None, Some(2))
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            stream
+                .read_group("Talon", "Widowmaker", None, None)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Reaper vuelve a leer sus pendientes, no las de Widowmaker
+        let history = stream
+            .read_group("Talon", "Reaper", Some(StreamId::MIN), None)
+            .unwrap();
+        let ids: Vec<StreamId> = history.into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![StreamId::new(1, 0), StreamId::new(2, 0)]);
+        assert_eq!(
+            stream.groups()["Talon"].pending[&StreamId::new(1, 0)].deliveries,
+            2
+        );
+
+        let summary = stream.pending_summary("Talon").unwrap();
+        assert_eq!(summary.count, 3);
+        assert_eq!(
+            summary.bounds,
+            Some((StreamId::new(1, 0), StreamId::new(3, 0)))
+        );
+        assert_eq!(summary.consumers["Reaper"], 2);
+        assert_eq!(
+            stream.read_group("Overwatch", "Tracer", None, None),
+            Err(StreamError::NoGroup)
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! Tests de integración para el cluster
+//!
+//! Este archivo contiene tests que levantan nodos reales del cluster y
+//! verifican cómo los ve un cliente, incluyendo las redirecciones.
+
+mod integration_tests;
+
+// use integration_tests::cluster_tests::*;
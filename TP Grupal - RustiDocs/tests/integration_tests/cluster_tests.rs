@@ -0,0 +1,129 @@
+//! Tests del pool de conexiones contra nodos reales del cluster
+
+use rustidocs::{
+    client_lib::pool::Pool,
+    cluster::{
+        cluster_node::ClusterNode, comms::node_input::NODAL_COMMS_PORT,
+        sharding::hash_slot::hash_slot,
+    },
+    config::node_configs::NodeConfigs,
+    network::RespMessage,
+};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Usuario de `user.acl` con todos los permisos
+const USERNAME: &str = "super";
+const PASSWORD: &str = "1234";
+
+/// Levanta un nodo como lo hace el binario `node`, en un puerto libre y con
+/// sus archivos en `temp_dir`. Sin `known_node` arranca como primer nodo del
+/// cluster; si no, se une al cluster de ese nodo.
+///
+/// # Returns
+///
+/// Dirección en la que el nodo atiende clientes
+fn start_node(temp_dir: &TempDir, node_id: &str, known_node: Option<String>) -> String {
+    let port = free_port();
+    let config_path = temp_dir.path().join("test.conf");
+    let config_content = format!(
+        "bind 127.0.0.1\nport {}\nrole M\nmaxclients 1000\nsave 900 15\ndbfilename dump.rdb\ndir {}\nlogfile {}\nloglevel notice\nnode-id {}",
+        port,
+        temp_dir.path().display(),
+        temp_dir.path().join("test.log").display(),
+        node_id,
+    );
+    std::fs::write(&config_path, config_content).expect("Failed to write config file");
+    let settings =
+        NodeConfigs::new(config_path.to_string_lossy().as_ref()).expect("Failed to load config");
+
+    thread::spawn(move || {
+        let mut node = ClusterNode::new(settings).expect("Failed to create node");
+        let _ = node.start(known_node);
+    });
+    let address = format!("127.0.0.1:{}", port);
+    wait_until(|| TcpStream::connect(&address).is_ok());
+    address
+}
+
+/// Puerto libre que deja lugar para el del bus del cluster.
+fn free_port() -> u16 {
+    loop {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        if port.checked_add(NODAL_COMMS_PORT).is_some() {
+            return port;
+        }
+    }
+}
+
+/// Reintenta `condition` hasta que se cumpla, por no más de 10 segundos.
+fn wait_until<F: FnMut() -> bool>(mut condition: F) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !condition() {
+        assert!(
+            Instant::now() < deadline,
+            "La condición no se cumplió a tiempo"
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Test de que el pool sigue el `MOVED` de un nodo real: cuando un nodo
+/// nuevo se lleva la mitad de los slots, el primero redirige sus claves, y
+/// el pool refresca el mapa de slots y reintenta el comando en el nuevo
+#[test]
+fn test_pool_follows_moved_from_real_node() {
+    let dir_a = TempDir::new().expect("Failed to create temp directory");
+    let dir_b = TempDir::new().expect("Failed to create temp directory");
+    let node_a = start_node(&dir_a, "a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0", None);
+
+    // El pool arranca con todos los slots en el nodo A
+    let pool = Pool::new(
+        node_a.clone(),
+        USERNAME.to_string(),
+        PASSWORD.to_string(),
+        2,
+    )
+    .expect("Failed to create pool");
+    let tracer_slot = hash_slot("Tracer").unwrap();
+    let genji_slot = hash_slot("Genji").unwrap();
+    assert_eq!(pool.node_for_slot(tracer_slot), Some(node_a.clone()));
+    assert_eq!(pool.node_for_slot(genji_slot), Some(node_a.clone()));
+
+    // El nodo B se une y se queda con los slots 8192-16383
+    let node_b = start_node(
+        &dir_b,
+        "b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0",
+        Some(node_a.clone()),
+    );
+    // Se espera a que ambos nodos informen el mismo reparto de slots
+    wait_until(|| match (pool.slots_of(&node_a), pool.slots_of(&node_b)) {
+        (Ok(view_a), Ok(view_b)) => view_a.len() == 2 && view_a == view_b,
+        _ => false,
+    });
+    assert_eq!(pool.node_for_slot(tracer_slot), Some(node_a.clone()));
+
+    // A redirige Tracer a B, y el pool lo sigue
+    let reply = pool.query(&["SET", "Tracer", "Oxton"]).unwrap();
+    assert_eq!(reply, RespMessage::SimpleString("OK".to_string()));
+    assert_eq!(pool.node_for_slot(tracer_slot), Some(node_b.clone()));
+    assert_eq!(pool.node_for_slot(genji_slot), Some(node_a.clone()));
+
+    // Con el mapa actualizado cada clave va directo a su nodo
+    let reply = pool.query(&["SET", "Genji", "Shimada"]).unwrap();
+    assert_eq!(reply, RespMessage::SimpleString("OK".to_string()));
+    let replies = pool.mget(&["Tracer", "Genji"]).unwrap();
+    assert_eq!(
+        replies,
+        vec![
+            RespMessage::BulkString(Some(b"Oxton".to_vec())),
+            RespMessage::BulkString(Some(b"Shimada".to_vec())),
+        ]
+    );
+}
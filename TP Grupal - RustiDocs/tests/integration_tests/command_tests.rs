@@ -2,10 +2,17 @@
 
 use crate::integration_tests::TestRedisServer;
 use rustidocs::{
-    command::{types::Command, types::ResponseType},
+    cluster::state::node_data::NodeData,
+    command::{
+        CommandExecutor, Instruction, types::Command, types::ResponseType, types::SetOptions,
+    },
+    network::RespMessage,
     storage::DataStore,
 };
-use std::sync::{Arc, RwLock};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock, mpsc},
+};
 
 /// Tests para comandos de strings
 #[test]
@@ -14,7 +21,11 @@ fn test_string_commands() {
     let store = Arc::new(RwLock::new(DataStore::new()));
 
     // Crear un comando SET simple
-    let set_cmd = Command::Set("string_key".to_string(), "hello".to_string());
+    let set_cmd = Command::Set(
+        "string_key".to_string(),
+        "hello".to_string(),
+        SetOptions::default(),
+    );
 
     // Ejecutar el comando directamente en el store
     {
@@ -182,7 +193,11 @@ fn test_delete_commands() {
     // Crear algunos datos
     {
         let mut store_guard = store.write().unwrap();
-        let set_cmd = Command::Set("delete_key".to_string(), "value".to_string());
+        let set_cmd = Command::Set(
+            "delete_key".to_string(),
+            "value".to_string(),
+            SetOptions::default(),
+        );
         set_cmd.execute_write(&mut *store_guard).unwrap();
 
         let lpush_cmd = Command::Lpush("delete_list".to_string(), vec!["item1".to_string()]);
@@ -235,9 +250,21 @@ fn test_string_operations() {
         let mut store_guard = store.write().unwrap();
 
         // SET múltiples valores
-        let set_cmd1 = Command::Set("key1".to_string(), "value1".to_string());
-        let set_cmd2 = Command::Set("key2".to_string(), "value2".to_string());
-        let set_cmd3 = Command::Set("key3".to_string(), "value3".to_string());
+        let set_cmd1 = Command::Set(
+            "key1".to_string(),
+            "value1".to_string(),
+            SetOptions::default(),
+        );
+        let set_cmd2 = Command::Set(
+            "key2".to_string(),
+            "value2".to_string(),
+            SetOptions::default(),
+        );
+        let set_cmd3 = Command::Set(
+            "key3".to_string(),
+            "value3".to_string(),
+            SetOptions::default(),
+        );
 
         assert!(set_cmd1.execute_write(&mut *store_guard).is_ok());
         assert!(set_cmd2.execute_write(&mut *store_guard).is_ok());
@@ -429,7 +456,11 @@ fn test_wrong_type_errors() {
     // Crear un string
     {
         let mut store_guard = store.write().unwrap();
-        let set_cmd = Command::Set("mixed_key".to_string(), "string_value".to_string());
+        let set_cmd = Command::Set(
+            "mixed_key".to_string(),
+            "string_value".to_string(),
+            SetOptions::default(),
+        );
         set_cmd.execute_write(&mut *store_guard).unwrap();
     }
 
@@ -449,3 +480,51 @@ fn test_wrong_type_errors() {
         assert!(result.is_err()); // Debería fallar por tipo incorrecto
     }
 }
+
+/// Tests para transacciones MULTI/EXEC/DISCARD a través del executor
+#[test]
+fn test_transaction_commands() {
+    let server = TestRedisServer::new();
+    let store = Arc::new(RwLock::new(DataStore::new()));
+    let mut node_data = NodeData::new(server.config.clone());
+    node_data.set_as_master();
+    let mut executor = CommandExecutor::new(
+        store.clone(),
+        mpsc::channel().1,
+        server.config.clone(),
+        server.logger.clone(),
+        mpsc::channel().0,
+        Arc::new(RwLock::new(HashMap::new())),
+        Arc::new(RwLock::new(node_data)),
+        Arc::new(RwLock::new(HashMap::new())),
+    );
+    let (response_tx, response_rx) = mpsc::channel();
+    let mut send = |cmd: &str, args: &[&str]| {
+        let instruction = Instruction::new(
+            cmd.to_string(),
+            args.iter().map(|arg| arg.to_string()).collect(),
+        );
+        executor.handle_instruction("client_1".to_string(), instruction, &response_tx);
+        response_rx.try_recv().unwrap()
+    };
+    let ok = RespMessage::SimpleString("OK".to_string());
+    let queued = RespMessage::SimpleString("QUEUED".to_string());
+
+    // MULTI; SET a 1; INCR a; EXEC
+    assert_eq!(send("MULTI", &[]), ok);
+    assert_eq!(send("SET", &["a", "1"]), queued);
+    assert_eq!(send("INCR", &["a"]), queued);
+    assert_eq!(
+        send("EXEC", &[]),
+        RespMessage::Array(vec![ok.clone(), RespMessage::Integer(2)])
+    );
+    assert_eq!(store.read().unwrap().get("a"), Some(&"2".to_string()));
+
+    // DISCARD no aplica ninguna escritura
+    assert_eq!(send("MULTI", &[]), ok);
+    assert_eq!(send("SET", &["b", "1"]), queued);
+    assert_eq!(send("DEL", &["a"]), queued);
+    assert_eq!(send("DISCARD", &[]), ok);
+    assert_eq!(store.read().unwrap().get("b"), None);
+    assert_eq!(store.read().unwrap().get("a"), Some(&"2".to_string()));
+}
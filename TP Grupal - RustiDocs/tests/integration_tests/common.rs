@@ -273,7 +273,7 @@ impl TestCommonConfig {
     /// Crea un archivo de configuración temporal
     pub fn create_config_file(&self) -> Result<String, Box<dyn std::error::Error>> {
         let config_path = self.temp_dir.path().join("test.conf");
-        let config_content = "bind 0.0.0.0\nport 6379\nrole M\nmaxclients 1000\nsave 900 15\ndbfilename dump.rdb\ndir ./\nlogfile test.log\nloglevel notice\nnode-id test123\nhash-slots 0-16383";
+        let config_content = "bind 0.0.0.0\nport 6379\nrole M\nmaxclients 1000\nsave 900 15\ndbfilename dump.rdb\ndir ./\nlogfile test.log\nloglevel notice\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\nhash-slots 0-16383";
 
         persistence::write_file(config_path.to_string_lossy().as_ref(), config_content)?;
         Ok(config_path.to_string_lossy().to_string())
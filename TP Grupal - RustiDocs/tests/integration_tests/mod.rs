@@ -4,6 +4,7 @@
 //! incluyendo el protocolo cliente/servidor, comandos Redis, almacenamiento en disco
 //! y funcionalidad Pub/Sub.
 
+pub mod cluster_tests;
 pub mod command_tests;
 pub mod common;
 pub mod persistence_tests;
@@ -35,7 +36,7 @@ impl TestRedisServer {
 
         // Crear un archivo de configuración temporal
         let config_path = temp_dir.path().join("test.conf");
-        let config_content = "bind 0.0.0.0\nport 6379\nrole M\nmaxclients 1000\nsave 900 15\ndbfilename dump.rdb\ndir ./\nlogfile test.log\nloglevel notice\nnode-id test123\nhash-slots 0-16383";
+        let config_content = "bind 0.0.0.0\nport 6379\nrole M\nmaxclients 1000\nsave 900 15\ndbfilename dump.rdb\ndir ./\nlogfile test.log\nloglevel notice\nnode-id e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca\nhash-slots 0-16383";
         std::fs::write(&config_path, config_content).expect("Failed to write nodes file");
 
         let config = NodeConfigs::new(config_path.to_string_lossy().as_ref())
@@ -1,9 +1,17 @@
 //! Tests para la persistencia de datos en disco
 
 use crate::integration_tests::TestRedisServer;
+use rustidocs::network::RespMessage;
 use rustidocs::storage::DataStore;
+use rustidocs::storage::deserializer::{deserialize_ds, read_sync_payload};
+use rustidocs::storage::serializer::serialize_to_bytes;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::{BufReader, Write};
+use std::os::unix::net::UnixStream;
 use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
 
 /// Tests para verificar que los datos se guardan correctamente en disco
 #[test]
@@ -362,3 +370,100 @@ fn test_persistence_edge_cases() {
     let result = _server.save_to_disk();
     assert!(result.is_ok(), "Failed to persist edge case data");
 }
+
+/// Test de la transferencia de SYNC: el store serializado viaja por un
+/// pipe como bulk string y del otro lado se reconstruye idéntico
+#[test]
+fn test_sync_transfer_rebuilds_identical_store() {
+    let mut master = DataStore::new();
+    master.set("sync_key".to_string(), "Ramattra".to_string());
+    master.set("sync_empty".to_string(), "".to_string());
+    master.list_db.insert(
+        "sync_list".to_string(),
+        vec![
+            "Orisa".to_string(),
+            "Zarya".to_string(),
+            "Orisa".to_string(),
+        ],
+    );
+    master.set_db.insert(
+        "sync_set".to_string(),
+        HashSet::from(["Ana".to_string(), "Kiriko".to_string()]),
+    );
+    master.hash_db.insert(
+        "sync_hash".to_string(),
+        HashMap::from([("role".to_string(), "tank".to_string())]),
+    );
+    master.zset_db.insert(
+        "sync_geo".to_string(),
+        HashMap::from([("Kings Row".to_string(), 3_520_051_062_284_386.0)]),
+    );
+
+    let (mut sender, receiver) = UnixStream::pair().expect("Failed to create pipe");
+    let payload = serialize_to_bytes(&master).expect("Failed to serialize store");
+    let writer = thread::spawn(move || {
+        let bulk = RespMessage::BulkString(Some(payload));
+        sender.write_all(&bulk.as_bytes()).unwrap();
+        // Después del store sigue el backlog de comandos
+        let backlog = RespMessage::Array(vec![RespMessage::BulkString(Some(b"DEL".to_vec()))]);
+        sender.write_all(&backlog.as_bytes()).unwrap();
+    });
+
+    let mut reader = BufReader::new(receiver);
+    let replica = read_sync_payload(&mut reader).expect("Failed to read SYNC payload");
+    writer.join().unwrap();
+
+    assert_eq!(replica.string_db, master.string_db);
+    assert_eq!(replica.list_db, master.list_db);
+    assert_eq!(replica.set_db, master.set_db);
+    assert_eq!(replica.hash_db, master.hash_db);
+    assert_eq!(replica.zset_db, master.zset_db);
+    assert!(replica.stream_db.is_empty());
+
+    // El backlog queda sin consumir en la conexión
+    let mut rest = String::new();
+    std::io::BufRead::read_line(&mut reader, &mut rest).unwrap();
+    assert_eq!(rest, "*1\r\n");
+}
+
+/// Test de que los vencimientos sobreviven al snapshot: se guardan como
+/// instantes absolutos y al cargarlos quedan con el mismo tiempo restante
+#[test]
+fn test_snapshot_preserves_ttls() {
+    let mut store = DataStore::new();
+    store.set("ttl_key".to_string(), "Lifeweaver".to_string());
+    store.set("persistent_key".to_string(), "Illari".to_string());
+    store
+        .list_db
+        .insert("ttl_list".to_string(), vec!["Mauga".to_string()]);
+    store.set_ttl("ttl_key", Some(Duration::from_secs(10)));
+    store.set_ttl("ttl_list", Some(Duration::from_secs(100)));
+    // Vencimiento de una clave que ya no existe: no se persiste
+    store.set_ttl("gone_key", Some(Duration::from_secs(10)));
+
+    let bytes = serialize_to_bytes(&store).expect("Failed to serialize store");
+    let loaded = deserialize_ds(&mut bytes.as_slice()).expect("Failed to load store");
+
+    let remaining = loaded.remaining_ttl("ttl_key").unwrap();
+    assert!(remaining <= Duration::from_secs(10));
+    assert!(remaining > Duration::from_secs(9));
+    assert!(loaded.remaining_ttl("ttl_list").unwrap() > Duration::from_secs(99));
+    assert!(loaded.remaining_ttl("persistent_key").is_none());
+    assert!(!loaded.expires.contains_key("gone_key"));
+}
+
+/// Test de que una clave cuyo vencimiento pasa mientras está guardada se
+/// carga ya vencida
+#[test]
+fn test_snapshot_key_expired_while_saved() {
+    let mut store = DataStore::new();
+    store.set("short_key".to_string(), "Venture".to_string());
+    store.set_ttl("short_key", Some(Duration::from_millis(20)));
+
+    let bytes = serialize_to_bytes(&store).expect("Failed to serialize store");
+    thread::sleep(Duration::from_millis(40));
+    let loaded = deserialize_ds(&mut bytes.as_slice()).expect("Failed to load store");
+
+    assert!(loaded.is_expired("short_key"));
+    assert_eq!(loaded.get("short_key"), None);
+}
@@ -1,5 +1,11 @@
 use crate::integration_tests::common::{parse_resp_from_bytes, serialize_resp_to_bytes};
-use rustidocs::network::resp_message::RespMessage;
+use rustidocs::{
+    command::Instruction, network::resp_message::RespMessage,
+    network::resp_parser::parse_resp_line, storage::DataStore,
+};
+use std::io::{BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
 
 /// Tests para parsing de mensajes RESP simples
 #[test]
@@ -158,3 +164,64 @@ fn test_server_response() {
     let serialized_response = serialize_resp_to_bytes(&response);
     assert_eq!(serialized_response, b"+OK\r\n");
 }
+
+/// Atiende `count` pedidos de una conexión como lo hace el servidor:
+/// parsea cada comando del stream, lo ejecuta y devuelve todas las
+/// respuestas juntas en una sola escritura.
+fn serve_pipeline(stream: TcpStream, count: usize) {
+    let mut store = DataStore::new();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut replies = Vec::new();
+    for _ in 0..count {
+        let parsed = parse_resp_line(&mut reader).unwrap();
+        let command = Instruction::try_from(parsed).unwrap().to_command().unwrap();
+        let response = if command.is_read_only() {
+            command.execute_read(&store, None, None, None, None, None)
+        } else {
+            command.execute_write(&mut store)
+        };
+        replies.extend(RespMessage::from_response(response.unwrap()).as_bytes());
+    }
+    (&stream).write_all(&replies).unwrap();
+}
+
+/// Tests para pipelining de comandos
+#[test]
+fn test_pipelined_commands_reply_in_order() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve_pipeline(stream, 3);
+    });
+
+    let mut client = TcpStream::connect(addr).unwrap();
+    let pipeline = [
+        b"*3\r\n$3\r\nSET\r\n$1\r\na\r\n$1\r\n1\r\n".as_slice(),
+        b"*2\r\n$3\r\nGET\r\n$1\r\na\r\n",
+        b"*2\r\n$4\r\nINCR\r\n$1\r\na\r\n",
+    ]
+    .concat();
+    // El INCR llega partido: el servidor debe conservar lo recibido
+    // hasta completarlo con la siguiente escritura
+    let (first, rest) = pipeline.split_at(pipeline.len() - 6);
+    client.write_all(first).unwrap();
+    client.flush().unwrap();
+    thread::sleep(std::time::Duration::from_millis(50));
+    client.write_all(rest).unwrap();
+
+    let mut reader = BufReader::new(client);
+    assert_eq!(
+        parse_resp_line(&mut reader).unwrap(),
+        RespMessage::SimpleString("OK".to_string())
+    );
+    assert_eq!(
+        parse_resp_line(&mut reader).unwrap(),
+        RespMessage::BulkString(Some(b"1".to_vec()))
+    );
+    assert_eq!(
+        parse_resp_line(&mut reader).unwrap(),
+        RespMessage::Integer(2)
+    );
+    server.join().unwrap();
+}
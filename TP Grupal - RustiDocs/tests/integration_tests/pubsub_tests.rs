@@ -2,10 +2,15 @@
 
 use crate::integration_tests::TestRedisServer;
 use rustidocs::{
-    command::types::Command, network::RespMessage, pubsub::ChannelManager, storage::DataStore,
+    command::commands::{psubscribe, publish},
+    command::types::{Command, ResponseType},
+    network::RespMessage,
+    pubsub::ChannelManager,
+    storage::DataStore,
 };
 use std::sync::mpsc;
 use std::sync::{Arc, RwLock};
+use std::thread;
 
 /// Tests para funcionalidad básica de Pub/Sub
 #[test]
@@ -393,3 +398,42 @@ fn test_distributed_pubsub() {
 
     println!("Distributed Pub/Sub test completed");
 }
+
+/// Tests para suscripciones a patrones
+#[test]
+fn test_pattern_subscriber_receives_matching_publish() {
+    let (pubsub_sender, pubsub_receiver) = mpsc::channel();
+    thread::spawn(move || ChannelManager::new(pubsub_receiver).run());
+    let (subscriber_sender, subscriber_receiver) = mpsc::channel();
+
+    let result = psubscribe(
+        "subscriber".to_string(),
+        vec!["news.*".to_string()],
+        &pubsub_sender,
+        &subscriber_sender,
+    );
+    assert!(result.is_ok());
+
+    let message = RespMessage::SimpleString("Nuevo héroe anunciado".to_string());
+    let delivered = publish(
+        "publisher".to_string(),
+        "news.tech".to_string(),
+        &pubsub_sender,
+        &message,
+    );
+    assert!(matches!(delivered, Ok(ResponseType::Int(1))));
+    assert_eq!(
+        subscriber_receiver.recv().unwrap(),
+        RespMessage::pubsub_pmessage("news.*", "news.tech", message.clone())
+    );
+
+    // Un canal que no cumple el patrón no llega al suscriptor
+    let delivered = publish(
+        "publisher".to_string(),
+        "sports.tech".to_string(),
+        &pubsub_sender,
+        &message,
+    );
+    assert!(matches!(delivered, Ok(ResponseType::Int(0))));
+    assert!(subscriber_receiver.try_recv().is_err());
+}
@@ -157,6 +157,8 @@ impl Forth79 {
             }
             if &tokens[i] == "IF" {
                 self.push_if_token(tokens, &mut i, &mut res);
+            } else if &tokens[i] == "DO" {
+                self.push_do_token(tokens, &mut i, &mut res);
             } else {
                 res.push(self.token_to_op(&tokens[i]));
                 i += 1;
@@ -185,6 +187,25 @@ impl Forth79 {
         res.push(if_operator);
     }
 
+    /// Mapea un bloque `DO ... LOOP`, respetando los loops anidados. El `LOOP` de cierre
+    /// no se incluye en el cuerpo; si falta, el cuerpo llega hasta el final de la línea.
+    fn push_do_token(&mut self, tokens: &[String], i: &mut usize, res: &mut Vec<Operation>) {
+        let mut body_tokens: Vec<String> = Vec::new();
+        let mut depth = 1;
+        *i += 1;
+        while *i < tokens.len() {
+            depth += if tokens[*i] == "DO" { 1 } else { 0 };
+            depth -= if tokens[*i] == "LOOP" { 1 } else { 0 };
+            *i += 1;
+            if depth == 0 {
+                break;
+            }
+            body_tokens.push(tokens[*i - 1].to_string());
+        }
+        let body = self.parse_line(&mut body_tokens);
+        res.push(Operation::DoLoop(body));
+    }
+
     /// Analiza un solo lado de la rama if. Util para condicionales anidados.
     fn push_branch(
         &mut self,
@@ -218,12 +239,16 @@ impl Forth79 {
             "-" => Operation::Sub,
             "*" => Operation::Mul,
             "/" => Operation::Div,
+            "MOD" => Operation::Mod,
+            "/MOD" => Operation::SlashMod,
+            "ABS" => Operation::Abs,
             "DUP" => Operation::Dup,
             "DROP" => Operation::Drop,
             "SWAP" => Operation::Swap,
             "OVER" => Operation::Over,
             "ROT" => Operation::Rot,
             "." => Operation::Dot,
+            ".S" => Operation::PrintStack,
             "EMIT" => Operation::Emit,
             "CR" => Operation::Cr,
             "=" => Operation::Eq,
@@ -235,11 +260,13 @@ impl Forth79 {
             "IF" => Operation::BranchIf(Vec::new(), Vec::new()),
             "ELSE" => Operation::BranchElse,
             "THEN" => Operation::BranchEnd,
+            "I" => Operation::LoopIndex,
             _ => {
                 if let Ok(n) = token.parse::<i16>() {
                     Operation::N(n)
-                } else if token.starts_with(".\"") {
-                    Operation::Print(token[3..token.len() - 1].trim().to_string())
+                } else if let Some(text) = token.strip_prefix(".\"") {
+                    let text = text.strip_suffix('"').unwrap_or(text);
+                    Operation::Print(text.trim().to_string())
                 } else {
                     Operation::Unknown
                 }
@@ -307,10 +334,13 @@ fn tokenize(line: &String) -> Vec<String> {
     let mut tokens: Vec<String> = Vec::new();
     let mut i = 0;
     while i < pseudo_tokens.len() {
+        if pseudo_tokens[i] == "(" {
+            i = skip_comment(&pseudo_tokens, i + 1);
+            continue;
+        }
         if pseudo_tokens[i] == ".\"" {
-            let (aux, j) = extend_token(&pseudo_tokens, &mut i, "\"");
-            i = j;
-            tokens.push(aux);
+            tokens.push(extend_token(&pseudo_tokens, &mut i, "\""));
+            continue;
         } else {
             if pseudo_tokens[i] == "" {
                 i += 1;
@@ -323,25 +353,39 @@ fn tokenize(line: &String) -> Vec<String> {
     tokens
 }
 
+/// Saltea un comentario `( ... )` desde la posición `i`, que es la siguiente al `(`.
+/// Si el comentario no se cierra, se consume hasta el final de la línea.
+/// # Retorna
+/// `usize` - La posición del primer token después del comentario.
+fn skip_comment(tokens: &[String], mut i: usize) -> usize {
+    while i < tokens.len() && !tokens[i].ends_with(')') {
+        i += 1;
+    }
+    i + 1
+}
+
 /// Exitendo el token actual hasta encontrar el delimitador final.
 /// Util para el caso `." palabra1 palabra2     palabra3"`
+/// Si el delimitador no aparece, se toma hasta el final de la línea y se cierra el token.
 /// # Retorna
-/// `String` - El nuevo token extendido
-/// `usize` - La cantidad de saltos hechos, para quela función superior no pierda el rastro
-/// de la modificación hecha.
-fn extend_token(tokens: &Vec<String>, i: &mut usize, delimiter: &str) -> (String, usize) {
+/// `String` - El nuevo token extendido. `i` queda en el primer token posterior.
+fn extend_token(tokens: &[String], i: &mut usize, delimiter: &str) -> String {
     let mut aux: String = String::new();
     aux.push_str(&tokens[*i]);
     *i += 1;
-    while !tokens[*i].ends_with(delimiter) {
+    while *i < tokens.len() && !tokens[*i].ends_with(delimiter) {
         aux.push_str(" ");
         aux.push_str(&tokens[*i]);
         *i += 1;
     }
-    aux.push_str(" ");
-    aux.push_str(&tokens[*i]);
-    *i += 1;
-    (aux, *i)
+    if *i < tokens.len() {
+        aux.push_str(" ");
+        aux.push_str(&tokens[*i]);
+        *i += 1;
+    } else {
+        aux.push_str(delimiter);
+    }
+    aux
 }
 
 /// Cambia el diccionario por otro con las definiciones expandidas.
@@ -456,6 +500,38 @@ mod tests {
         assert_eq!(tokens, vec!["A"]);
     }
 
+    #[test]
+    fn test_tokenize_string_literal() {
+        let line = String::from(".\" Hello   World\" 1");
+        let tokens = tokenize(&line);
+
+        assert_eq!(tokens, vec![".\" Hello   World\"", "1"]);
+    }
+
+    #[test]
+    fn test_tokenize_unterminated_string_literal() {
+        let line = String::from("1 .\" Hello World");
+        let tokens = tokenize(&line);
+
+        assert_eq!(tokens, vec!["1", ".\" Hello World\""]);
+    }
+
+    #[test]
+    fn test_tokenize_skips_comments() {
+        let line = String::from("( this   is ignored ) 1 2 +");
+        let tokens = tokenize(&line);
+
+        assert_eq!(tokens, vec!["1", "2", "+"]);
+    }
+
+    #[test]
+    fn test_tokenize_skips_unterminated_comment() {
+        let line = String::from("1 2 ( no se cierra + .");
+        let tokens = tokenize(&line);
+
+        assert_eq!(tokens, vec!["1", "2"]);
+    }
+
     #[test]
     fn test_tokenize_tokenizes_correctly_lots_of_whitespaces() {
         let line = String::from(": A   1    2 3    ;                  ");
@@ -2,11 +2,15 @@ use crate::stack::Stack;
 
 /// Struct `Operation` sirve para representar las operaciones de los tokens.
 /// OBS: Else y Then (End) están para delimintar durente el parseo y en tiempo de ejecució no hacen nada.
+/// `DoLoop` guarda el cuerpo del `DO ... LOOP`, el `LOOP` no llega a ser una operación.
 pub enum Operation {
     Add,
     Sub,
     Mul,
     Div,
+    Mod,
+    SlashMod,
+    Abs,
     Dup,
     Drop,
     Swap,
@@ -16,6 +20,7 @@ pub enum Operation {
     Emit,
     Cr,
     Print(String),
+    PrintStack,
     Eq,
     Lt,
     Gt,
@@ -25,6 +30,8 @@ pub enum Operation {
     BranchIf(Vec<Operation>, Vec<Operation>),
     BranchElse, // Aunque no hagan nada, los necesito
     BranchEnd,  // para que la función pueda definir bien los ifs anidados.
+    DoLoop(Vec<Operation>),
+    LoopIndex,
     N(i16),
     Unknown,
 }
@@ -33,18 +40,23 @@ impl Operation {
     pub fn apply(&self, stack: &mut Stack, stack_size: usize, buffer: &mut Vec<String>) -> bool {
         match self {
             Operation::N(n) => add_to_the_stack(&n, stack, stack_size, buffer),
-            Operation::Add | Operation::Sub | Operation::Mul | Operation::Div => {
-                arithmetic_operation(stack, self, buffer)
-            }
+            Operation::Add
+            | Operation::Sub
+            | Operation::Mul
+            | Operation::Div
+            | Operation::Mod
+            | Operation::SlashMod => arithmetic_operation(stack, self, buffer),
+            Operation::Abs => abs_operation(stack, buffer),
             Operation::Dup => duplicate_peak(stack, stack_size, buffer),
             Operation::Drop => drop_peak(stack, buffer),
             Operation::Swap => swap_first_two_items(stack, buffer),
             Operation::Over => over_operation(stack, stack_size, buffer),
-            Operation::Rot => rotate_stack_by_one(stack, buffer),
+            Operation::Rot => rotate_top_three(stack, buffer),
             Operation::Dot => pop_and_print(stack, buffer, false),
             Operation::Emit => pop_and_print(stack, buffer, true),
             Operation::Cr => print_operation(buffer, "\n".to_string()),
             Operation::Print(str) => print_operation(buffer, str.to_string()),
+            Operation::PrintStack => print_stack(stack, buffer),
             Operation::Eq | Operation::Lt | Operation::Gt => {
                 comparison_operation(stack, &self, buffer)
             }
@@ -53,6 +65,8 @@ impl Operation {
             Operation::BranchIf(pos_branch, neg_branch) => {
                 browse_if_clause(pos_branch, neg_branch, stack, stack_size, buffer)
             }
+            Operation::DoLoop(body) => run_do_loop(body, stack, stack_size, buffer),
+            Operation::LoopIndex => push_loop_index(stack, stack_size, buffer),
             Operation::Unknown => crate::forth_79::Error::UnknownWord.throw_error(buffer),
             Operation::BranchElse | Operation::BranchEnd => true,
         }
@@ -95,6 +109,19 @@ fn arithmetic_operation(
                 }
                 stack.push(b / a);
             }
+            Operation::Mod => {
+                if a == 0 {
+                    return crate::forth_79::Error::DivisionByZero.throw_error(buffer);
+                }
+                stack.push(b.wrapping_rem(a));
+            }
+            Operation::SlashMod => {
+                if a == 0 {
+                    return crate::forth_79::Error::DivisionByZero.throw_error(buffer);
+                }
+                stack.push(b.wrapping_div(a));
+                stack.push(b.wrapping_rem(a)); // El resto queda en el tope.
+            }
             _ => return false,
         }
         return true;
@@ -102,6 +129,15 @@ fn arithmetic_operation(
     crate::forth_79::Error::Underflow.throw_error(buffer)
 }
 
+fn abs_operation(stack: &mut Stack, buffer: &mut Vec<String>) -> bool {
+    let a: Option<i16> = stack.pop();
+    if let Some(a) = a {
+        stack.push(a.wrapping_abs());
+        return true;
+    }
+    crate::forth_79::Error::Underflow.throw_error(buffer)
+}
+
 fn duplicate_peak(stack: &mut Stack, stack_size: usize, buffer: &mut Vec<String>) -> bool {
     if stack.len() + 1 >= stack_size {
         return crate::forth_79::Error::Overflow.throw_error(buffer);
@@ -146,9 +182,13 @@ fn over_operation(stack: &mut Stack, stack_size: usize, buffer: &mut Vec<String>
     crate::forth_79::Error::Underflow.throw_error(buffer)
 }
 
-fn rotate_stack_by_one(stack: &mut Stack, buffer: &mut Vec<String>) -> bool {
-    let a: Option<i16> = stack.remove(0);
-    if let Some(a) = a {
+/// ROT: rota los tres elementos del tope (`a b c -- b c a`), el resto de la pila no se toca.
+fn rotate_top_three(stack: &mut Stack, buffer: &mut Vec<String>) -> bool {
+    let (c, b): (Option<i16>, Option<i16>) = stack.pop_peak();
+    let a: Option<i16> = stack.pop();
+    if let (Some(a), Some(b), Some(c)) = (a, b, c) {
+        stack.push(b);
+        stack.push(c);
         stack.push(a);
         return true;
     }
@@ -173,6 +213,16 @@ fn print_operation(buffer: &mut Vec<String>, str: String) -> bool {
     true
 }
 
+/// `.S`: imprime la pila completa como `<n> a b c` sin sacar nada.
+fn print_stack(stack: &Stack, buffer: &mut Vec<String>) -> bool {
+    let mut output: String = format!("<{}>", stack.len());
+    for item in stack.get_items() {
+        output.push_str(&format!(" {}", item));
+    }
+    buffer.push(output);
+    true
+}
+
 fn comparison_operation(
     stack: &mut Stack,
     operation: &Operation,
@@ -264,6 +314,41 @@ fn browse_if_clause(
     crate::forth_79::Error::Underflow.throw_error(buffer)
 }
 
+/// `limit start DO ... LOOP`: corre el cuerpo con el índice desde `start` hasta `limit - 1`.
+/// Como en Forth-79, el cuerpo se ejecuta al menos una vez.
+fn run_do_loop(
+    body: &[Operation],
+    stack: &mut Stack,
+    stack_size: usize,
+    buffer: &mut Vec<String>,
+) -> bool {
+    let (start, limit): (Option<i16>, Option<i16>) = stack.pop_peak();
+    if let (Some(start), Some(limit)) = (start, limit) {
+        let mut index: i32 = start as i32;
+        loop {
+            stack.push_loop_index(index as i16);
+            let ok = body.iter().all(|op| op.apply(stack, stack_size, buffer));
+            stack.pop_loop_index();
+            if !ok {
+                return false;
+            }
+            index += 1;
+            if index >= limit as i32 {
+                return true;
+            }
+        }
+    }
+    crate::forth_79::Error::Underflow.throw_error(buffer)
+}
+
+/// `I`: pushea el índice del loop más interno.
+fn push_loop_index(stack: &mut Stack, stack_size: usize, buffer: &mut Vec<String>) -> bool {
+    if let Some(index) = stack.loop_index() {
+        return add_to_the_stack(&index, stack, stack_size, buffer);
+    }
+    crate::forth_79::Error::Underflow.throw_error(buffer)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -616,6 +701,182 @@ mod tests {
         assert_eq!(stack.len(), 0);
     }
 
+    /* TEST MÓDULO */
+
+    #[test]
+    fn test_mod_items_in_a_stack() {
+        let mut stack = Stack::new();
+        stack.push(7);
+        stack.push(2);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Mod;
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.pop().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_mod_neg_dividend() {
+        let mut stack = Stack::new();
+        stack.push(-7);
+        stack.push(2);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Mod;
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.pop().unwrap(), -1);
+    }
+
+    #[test]
+    fn test_mod_neg_divisor() {
+        let mut stack = Stack::new();
+        stack.push(7);
+        stack.push(-2);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Mod;
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.pop().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_mod_by_0() {
+        let mut stack = Stack::new();
+        stack.push(10);
+        stack.push(0);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Mod;
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.len(), 0);
+    }
+
+    #[test]
+    fn test_mod_cant_mod_1_item_in_a_stack() {
+        let mut stack = set_up_one_item_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Mod;
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.len(), 0);
+    }
+
+    /* TEST /MOD */
+
+    #[test]
+    fn test_slash_mod_pushes_remainder_on_top() {
+        let mut stack = Stack::new();
+        stack.push(7);
+        stack.push(2);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::SlashMod;
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.pop().unwrap(), 1);
+        assert_eq!(stack.pop().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_slash_mod_neg_items() {
+        let mut stack = Stack::new();
+        stack.push(-7);
+        stack.push(2);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::SlashMod;
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.pop().unwrap(), -1);
+        assert_eq!(stack.pop().unwrap(), -3);
+    }
+
+    #[test]
+    fn test_slash_mod_by_0() {
+        let mut stack = Stack::new();
+        stack.push(10);
+        stack.push(0);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::SlashMod;
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.len(), 0);
+    }
+
+    #[test]
+    fn test_slash_mod_cant_div_0_items_in_a_stack() {
+        let mut stack = set_up_empty_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::SlashMod;
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.len(), 0);
+    }
+
+    /* TEST ABS */
+
+    #[test]
+    fn test_abs_neg_item() {
+        let mut stack = Stack::new();
+        stack.push(1);
+        stack.push(-5);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Abs;
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.pop().unwrap(), 5);
+        assert_eq!(stack.pop().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_abs_pos_item() {
+        let mut stack = set_up_one_item_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Abs;
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.pop().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_abs_underflow() {
+        let mut stack = set_up_empty_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Abs;
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.len(), 0);
+    }
+
     /* TESTS DROP */
 
     #[test]
@@ -793,16 +1054,41 @@ mod tests {
     }
 
     #[test]
-    fn test_rot_w_1_item_in_a_stack() {
-        let mut stack = set_up_one_item_stack();
+    fn test_rot_only_rotates_the_top_three_items() {
+        let mut stack = set_up_full_stack();
+        stack.push(3);
+        stack.push(4);
         let stack_size: usize = 10;
         let mut buffer = Vec::new();
         let operation = Operation::Rot;
 
         assert!(operation.apply(&mut stack, stack_size, &mut buffer));
         assert_eq!(buffer.len(), 0);
-        assert_eq!(stack.len(), 1);
-        assert_eq!(stack.pop().unwrap(), 1);
+        assert_eq!(stack.get_items(), vec![1, 3, 4, 2]); // El 1 del fondo no se mueve.
+    }
+
+    #[test]
+    fn test_rot_underflow_w_1_item_in_a_stack() {
+        let mut stack = set_up_one_item_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Rot;
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.len(), 0); // Las operaciones consumen los datos que tocan, no hay undo.
+    }
+
+    #[test]
+    fn test_rot_underflow_w_2_items_in_a_stack() {
+        let mut stack = set_up_full_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::Rot;
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.len(), 0);
     }
 
     #[test]
@@ -842,6 +1128,32 @@ mod tests {
         assert_eq!(stack.len(), 0);
     }
 
+    /* TESTS PRINT STACK */
+    #[test]
+    fn test_print_stack_w_full_stack() {
+        let mut stack = set_up_full_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::PrintStack;
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer, vec!["<2> 1 2".to_string()]);
+        assert_eq!(stack.len(), 2); // No consume nada.
+        assert_eq!(stack.get_items(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_print_stack_w_empty_stack() {
+        let mut stack = set_up_empty_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::PrintStack;
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer, vec!["<0>".to_string()]);
+        assert_eq!(stack.len(), 0);
+    }
+
     /* TESTS EMIT */
 
     #[test]
@@ -1305,6 +1617,73 @@ mod tests {
         assert_eq!(stack.len(), 0);
     }
 
+    /* TESTS DO LOOP */
+    #[test]
+    fn test_do_loop_runs_body_with_index() {
+        let mut stack = Stack::new();
+        stack.push(3);
+        stack.push(0);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::DoLoop(vec![Operation::LoopIndex]);
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(stack.get_items(), vec![0, 1, 2]);
+        assert_eq!(stack.loop_index(), None); // Al terminar no queda ningún índice.
+    }
+
+    #[test]
+    fn test_do_loop_runs_at_least_once() {
+        let mut stack = Stack::new();
+        stack.push(0);
+        stack.push(5);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::DoLoop(vec![Operation::LoopIndex]);
+
+        assert!(operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(stack.get_items(), vec![5]);
+    }
+
+    #[test]
+    fn test_do_loop_underflow_w_1_item_in_a_stack() {
+        let mut stack = set_up_one_item_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::DoLoop(vec![Operation::LoopIndex]);
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.len(), 0);
+    }
+
+    #[test]
+    fn test_do_loop_stops_when_body_fails() {
+        let mut stack = Stack::new();
+        stack.push(10);
+        stack.push(0);
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::DoLoop(vec![Operation::Drop]);
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.loop_index(), None);
+    }
+
+    #[test]
+    fn test_loop_index_outside_a_loop() {
+        let mut stack = set_up_empty_stack();
+        let stack_size: usize = 10;
+        let mut buffer = Vec::new();
+        let operation = Operation::LoopIndex;
+
+        assert!(!operation.apply(&mut stack, stack_size, &mut buffer));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(stack.len(), 0);
+    }
+
     /* TESTS IF */
     #[test]
     fn test_if_underflow() {
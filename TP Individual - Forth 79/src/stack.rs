@@ -1,13 +1,19 @@
 use std::fmt;
 
 /// Estructura LIFO básica para asociar a Forth-79.
+/// `loop_indices` hace de pila de retorno: guarda el índice de cada `DO ... LOOP` en curso,
+/// el del loop más interno al final.
 pub struct Stack {
     data: Vec<i16>,
+    loop_indices: Vec<i16>,
 }
 
 impl Stack {
     pub fn new() -> Stack {
-        Stack { data: Vec::new() }
+        Stack {
+            data: Vec::new(),
+            loop_indices: Vec::new(),
+        }
     }
 
     pub fn push(&mut self, value: i16) {
@@ -22,12 +28,19 @@ impl Stack {
         (self.data.pop(), self.data.pop())
     }
 
-    pub fn remove(&mut self, n: usize) -> Option<i16> {
-        if n < self.data.len() {
-            return Some(self.data.remove(n));
-        }
-        None
+    pub fn push_loop_index(&mut self, index: i16) {
+        self.loop_indices.push(index);
+    }
+
+    pub fn pop_loop_index(&mut self) -> Option<i16> {
+        self.loop_indices.pop()
+    }
+
+    /// Índice del loop más interno, `None` si no hay ningún loop en curso.
+    pub fn loop_index(&self) -> Option<i16> {
+        self.loop_indices.last().copied()
     }
+
     pub fn len(&self) -> usize {
         self.data.len()
     }
@@ -119,6 +132,27 @@ mod tests {
         assert_eq!(stack.len(), 0);
     }
 
+    #[test]
+    fn test_loop_indices_dont_touch_the_data() {
+        let mut stack = Stack::new();
+        stack.push(1);
+        stack.push_loop_index(0);
+        stack.push_loop_index(5);
+
+        assert_eq!(stack.loop_index(), Some(5));
+        assert_eq!(stack.pop_loop_index(), Some(5));
+        assert_eq!(stack.loop_index(), Some(0));
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.pop(), Some(1));
+    }
+
+    #[test]
+    fn test_loop_index_without_loops() {
+        let mut stack = Stack::new();
+        assert_eq!(stack.loop_index(), None);
+        assert_eq!(stack.pop_loop_index(), None);
+    }
+
     #[test]
     fn test_display_empty_stack() {
         let stack = Stack::new();
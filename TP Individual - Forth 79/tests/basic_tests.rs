@@ -78,6 +78,27 @@ fn test_div_3() {
     assert_eq!(forth.get_stack_state(), [1, 4]);
 }
 
+#[test]
+fn test_mod() {
+    let mut forth = Forth79::new();
+    forth.interpret_line("13 5 mod -13 5 MOD".to_string(), &mut io::stdout());
+    assert_eq!(forth.get_stack_state(), [3, -3]);
+}
+
+#[test]
+fn test_slash_mod() {
+    let mut forth = Forth79::new();
+    forth.interpret_line("13 5 /mod".to_string(), &mut io::stdout());
+    assert_eq!(forth.get_stack_state(), [2, 3]);
+}
+
+#[test]
+fn test_abs() {
+    let mut forth = Forth79::new();
+    forth.interpret_line("-4 abs 4 ABS".to_string(), &mut io::stdout());
+    assert_eq!(forth.get_stack_state(), [4, 4]);
+}
+
 #[test]
 fn test_add_sub() {
     let mut forth = Forth79::new();
@@ -192,6 +213,26 @@ fn test_word_definition_2() {
     assert_eq!(forth.get_stack_state(), [1, 2, 3]);
 }
 
+#[test]
+fn test_word_definition_square() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+    forth.interpret_line(": SQUARE DUP * ;".to_string(), &mut buffer);
+    forth.interpret_line("3 SQUARE .".to_string(), &mut buffer);
+    assert_eq!(String::from_utf8(buffer).unwrap(), "9");
+    assert_eq!(forth.get_stack_state(), []);
+}
+
+#[test]
+fn test_word_redefinition_square() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+    forth.interpret_line(": square dup * ;".to_string(), &mut buffer);
+    forth.interpret_line(": square dup + ;".to_string(), &mut buffer);
+    forth.interpret_line("3 square .".to_string(), &mut buffer);
+    assert_eq!(String::from_utf8(buffer).unwrap(), "6");
+}
+
 #[test]
 fn test_word_redefinition() {
     let mut forth = Forth79::new();
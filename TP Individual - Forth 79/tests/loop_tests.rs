@@ -0,0 +1,56 @@
+use forth::forth_79::Forth79;
+use std::io;
+
+#[test]
+fn test_do_loop_prints_index() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line("10 0 DO I . LOOP".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "0 1 2 3 4 5 6 7 8 9");
+    assert_eq!(forth.get_stack_state(), []);
+}
+
+#[test]
+fn test_do_loop_lowercase() {
+    let mut forth = Forth79::new();
+    forth.interpret_line("3 0 do i loop".to_string(), &mut io::stdout());
+    assert_eq!(forth.get_stack_state(), [0, 1, 2]);
+}
+
+#[test]
+fn test_nested_do_loops() {
+    let mut forth = Forth79::new();
+    forth.interpret_line("2 0 DO 3 1 DO I LOOP LOOP".to_string(), &mut io::stdout());
+    assert_eq!(forth.get_stack_state(), [1, 2, 1, 2]);
+}
+
+#[test]
+fn test_do_loop_with_if() {
+    let mut forth = Forth79::new();
+    forth.interpret_line("4 0 DO I 2 < IF I THEN LOOP".to_string(), &mut io::stdout());
+    assert_eq!(forth.get_stack_state(), [0, 1]);
+}
+
+#[test]
+fn test_do_loop_inside_word_definition() {
+    let mut forth = Forth79::new();
+    forth.interpret_line(
+        ": sum-to 0 swap 0 do i + loop ;".to_string(),
+        &mut io::stdout(),
+    );
+    forth.interpret_line("5 sum-to".to_string(), &mut io::stdout());
+    assert_eq!(forth.get_stack_state(), [10]);
+}
+
+#[test]
+fn test_do_loop_underflow() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line("0 DO I LOOP".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "stack-underflow\n");
+    assert_eq!(forth.get_stack_state(), []);
+}
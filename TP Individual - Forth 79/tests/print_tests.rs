@@ -139,3 +139,102 @@ fn test_dot_quote_and_cr() {
     assert_eq!(String::from_utf8(buffer).unwrap(), "hello\nworld");
     assert_eq!(forth.get_stack_state(), []);
 }
+
+#[test]
+fn test_comment_is_ignored() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line("( this is ignored ) 1 2 +".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "");
+    assert_eq!(forth.get_stack_state(), [3]);
+}
+
+#[test]
+fn test_comment_between_words() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line("1 2 ( suma ) + .".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "3");
+    assert_eq!(forth.get_stack_state(), []);
+}
+
+#[test]
+fn test_comment_inside_word_definition() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line(": square ( n -- n*n ) dup * ;".to_string(), &mut buffer);
+    forth.interpret_line("4 square .".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "16");
+}
+
+#[test]
+fn test_dot_quote_doesnt_touch_the_stack() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line("1 2 .\" Hello World\"".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "Hello World");
+    assert_eq!(forth.get_stack_state(), [1, 2]);
+}
+
+#[test]
+fn test_dot_quote_followed_by_words() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line(".\" suma:\" 1 2 + .".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "suma: 3");
+    assert_eq!(forth.get_stack_state(), []);
+}
+
+#[test]
+fn test_dot_quote_empty_string() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line(".\" \" 1 .\"\"".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), " ");
+    assert_eq!(forth.get_stack_state(), [1]);
+}
+
+#[test]
+fn test_dot_quote_unterminated() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line(".\" hello world".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "hello world");
+    assert_eq!(forth.get_stack_state(), []);
+}
+
+#[test]
+fn test_dot_s_prints_the_stack() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line("1 2 .s".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "<2> 1 2");
+    assert_eq!(forth.get_stack_state(), [1, 2]);
+}
+
+#[test]
+fn test_dot_s_empty_stack() {
+    let mut forth = Forth79::new();
+    let mut buffer = Vec::new();
+
+    forth.interpret_line(".S".to_string(), &mut buffer);
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), "<0>");
+    assert_eq!(forth.get_stack_state(), []);
+}